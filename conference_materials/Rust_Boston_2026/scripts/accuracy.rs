@@ -0,0 +1,135 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! ULP error of each solid-angle kernel variant against the double-double
+//! kernel, over random, near-degenerate, and extreme-scale inputs.
+//!
+//! ```text
+//! rust-script accuracy.rs [n] [seed] [distribution...]
+//! ```
+//!
+//! Distributions are `random`, `sliver`, `tiny` and `huge` (default: all),
+//! from the seeded generator in `solid_angle/inputs.rs`.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/fixed.rs"]
+mod fixed;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/interval.rs"]
+mod interval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use inputs::Distribution;
+
+type Tet = [[f64; 3]; 4];
+
+/// The unfused kernel of the talk's type-2 slide: every product and sum rounded on its own
+fn solid_angle_no_fma(tet: Tet) -> f64 {
+    let sub = |a: [f64; 3], b: [f64; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    let (a, b, c) = (sub(tet[1], tet[0]), sub(tet[2], tet[0]), sub(tet[3], tet[0]));
+    let (la, lb, lc) = (dot(a, a).sqrt(), dot(b, b).sqrt(), dot(c, c).sqrt());
+    let abc = la * lb * lc;
+    let triple = dot(a, cross(b, c));
+    let denom = abc + dot(a, b) * lc + dot(a, c) * lb + dot(b, c) * la;
+    if abc != 0.0 {
+        2.0 * libm::atan2(triple, denom)
+    } else {
+        0.0
+    }
+}
+
+/// Kernel variant under test, returning its result and the tetrahedron it
+/// actually evaluated, or `None` if the input is out of range for it
+type Variant = (&'static str, fn(Tet) -> Option<(f64, Tet)>);
+
+/// Grid spacing for the fixed-point variant, covering coordinates up to ±16
+const FIXED_RESOLUTION: f64 = 1.0 / (1_u64 << 36) as f64;
+
+const VARIANTS: [Variant; 5] = [
+    ("no-fma", |t| Some((solid_angle_no_fma(t), t))),
+    ("fma", |t| Some((tetrahedron::solid_angle_tetrahedron_scalar(t[0], t[1], t[2], t[3]), t))),
+    ("fixed 2^-36", |t| {
+        // Judged against the snapped geometry, so only kernel error is counted
+        let q = [t[0], t[1], t[2], t[3]].map(|v| fixed::snap(v, FIXED_RESOLUTION));
+        let q = [q[0].ok()?, q[1].ok()?, q[2].ok()?, q[3].ok()?];
+        let extent = q[1..].iter().flat_map(|v| (0..3).map(|i| (v[i] - q[0][i]).abs())).max()?;
+        if extent < 1 << 20 {
+            return None; // Too small for the grid to resolve
+        }
+        let snapped = q.map(|v| v.map(|x| x as f64 * FIXED_RESOLUTION));
+        Some((fixed::solid_angle_tetrahedron_scalar_fixed(q[0], q[1], q[2], q[3]), snapped))
+    }),
+    ("interval mid", |t| match interval::solid_angle_tetrahedron_scalar_filtered(t[0], t[1], t[2], t[3]) {
+        interval::Filtered::Enclosure(enc) => Some((enc.mid(), t)),
+        interval::Filtered::Refined(dd) => Some((dd.to_f64(), t)),
+    }),
+    ("double-double", |t| Some((dd::solid_angle_tetrahedron_scalar_dd(t[0], t[1], t[2], t[3]).to_f64(), t))),
+];
+
+/// Error of `x` in units of the last place of the exact value
+fn ulp_error(x: f64, exact: dd::DoubleDouble) -> f64 {
+    if exact.hi == 0.0 {
+        return if x == 0.0 { 0.0 } else { f64::INFINITY };
+    }
+    let ulp = exact.hi.abs().next_up() - exact.hi.abs();
+    ((x - exact.hi) - exact.lo).abs() / ulp
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let n: usize = args.first().map_or(100_000, |s| s.parse().unwrap());
+    let seed: u64 = args.get(1).map_or(0, |s| s.parse().unwrap());
+    let dists: Vec<Distribution> = match args.get(2..) {
+        Some(d) if !d.is_empty() => d.iter().map(|d| d.parse().unwrap()).collect(),
+        _ => Distribution::ALL.to_vec(),
+    };
+
+    println!("n = {n}, seed = {seed}");
+    println!("{:<8} {:<14} {:>14} {:>12} {:>9}", "inputs", "variant", "max ulp", "mean ulp", "skipped");
+    for dist in dists {
+        let tets = inputs::tetrahedra(dist, seed, n);
+        let exact: Vec<dd::DoubleDouble> = tets
+            .iter()
+            .map(|t| dd::solid_angle_tetrahedron_scalar_dd(t[0], t[1], t[2], t[3]))
+            .collect();
+
+        for (name, kernel) in VARIANTS {
+            let (mut max, mut sum, mut count) = (0.0_f64, 0.0, 0);
+            for (&t, &e) in tets.iter().zip(&exact) {
+                if let Some((x, evaluated)) = kernel(t) {
+                    let e = if evaluated == t {
+                        e
+                    } else {
+                        let t = evaluated;
+                        dd::solid_angle_tetrahedron_scalar_dd(t[0], t[1], t[2], t[3])
+                    };
+                    let err = ulp_error(x, e);
+                    max = max.max(err);
+                    sum += err;
+                    count += 1;
+                }
+            }
+            let mean = sum / count.max(1) as f64;
+            println!("{:<8} {name:<14} {max:>14.3} {mean:>12.3} {:>9}", dist.name(), n - count);
+        }
+    }
+}
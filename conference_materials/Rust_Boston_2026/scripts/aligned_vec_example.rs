@@ -0,0 +1,98 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//!
+//! [target.'cfg(target_os = "linux")'.dependencies]
+//! libc = { version = "0.2", optional = true }
+//!
+//! [features]
+//! hugepages = ["dep:libc"]
+//! ```
+//!
+//! Throughput of the solid-angle kernel and a memory-bound multiply with
+//! 64-byte-aligned buffers versus buffers deliberately offset by one `f64`,
+//! and of the multiply with huge-page buffers, which pays off well past
+//! the last-level cache; try `n` of 10⁸.
+//!
+//! ```text
+//! rust-script aligned_vec_example.rs [n]
+//! cd $(rust-script -p aligned_vec_example.rs | tail -1) && cargo run --release --features hugepages -- 100000000
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/aligned_vec.rs"]
+mod aligned_vec;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use aligned_vec::{AlignedVec, AllocPolicy, HUGE_PAGE};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Best of several runs, to dodge scheduler noise
+fn best_of(reps: usize, mut f: impl FnMut()) -> Duration {
+    (0..reps)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn mul(a: &[f64], b: &[f64], out: &mut [f64]) {
+    let n = out.len();
+    assert!(a.len() == n && b.len() == n);
+    for i in 0..n {
+        out[i] = a[i] * b[i];
+    }
+}
+
+fn main() {
+    let n: usize = std::env::args().nth(1).map_or(1 << 22, |s| s.parse().unwrap());
+
+    // Offsetting by one element into an aligned buffer forces the worst case,
+    // rather than relying on whatever alignment the allocator happens to give
+    let x: AlignedVec<f64> = (0..n + 1).map(|i| 1.0 + i as f64 * 1e-9).collect();
+    let mut out: AlignedVec<f64> = AlignedVec::from_elem(0.0, n + 1);
+    assert_eq!(x.as_ptr() as usize % 64, 0);
+    assert_eq!(out.as_ptr() as usize % 64, 0);
+
+    let t_aligned = best_of(10, || mul(black_box(&x[..n]), black_box(&x[..n]), &mut out[..n]));
+    let t_offset = best_of(10, || mul(black_box(&x[1..]), black_box(&x[1..]), &mut out[1..]));
+    println!("mul, n = {n}");
+    println!("    aligned: {:8.3} ns/elem", t_aligned.as_nanos() as f64 / n as f64);
+    println!("    offset:  {:8.3} ns/elem", t_offset.as_nanos() as f64 / n as f64);
+
+    // Huge pages, with the policy carried through clone
+    let mut huge = AlignedVec::<f64>::with_capacity_in(n, AllocPolicy::HugePages);
+    huge.extend(x[..n].iter().copied());
+    let mut huge_out = huge.clone();
+    assert_eq!(huge_out.policy(), AllocPolicy::HugePages);
+    if n * 8 >= HUGE_PAGE {
+        assert_eq!(huge.as_ptr() as usize % HUGE_PAGE, 0);
+    }
+    let t_huge = best_of(10, || mul(black_box(&huge), black_box(&huge), &mut huge_out));
+    println!("    huge pages: {:5.3} ns/elem", t_huge.as_nanos() as f64 / n as f64);
+
+    // Solid angles. Tetrahedra are 96 bytes, so only every other one starts
+    // on a cache line either way; the outputs are what alignment controls.
+    let n = n / 16;
+    let tets: AlignedVec<[[f64; 3]; 4]> = (0..n)
+        .map(|i| {
+            let s = 1.0 + i as f64 * 1e-6;
+            [[0.0; 3], [s, 0.0, 0.0], [0.0, s, 0.0], [0.0, 0.0, s]]
+        })
+        .collect();
+    let t_aligned = best_of(10, || tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut out[..n]).unwrap());
+    let t_offset = best_of(10, || tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut out[1..n + 1]).unwrap());
+    println!("solid_angle_tetrahedron, n = {n}");
+    println!("    aligned: {:8.3} ns/elem", t_aligned.as_nanos() as f64 / n as f64);
+    println!("    offset:  {:8.3} ns/elem", t_offset.as_nanos() as f64 / n as f64);
+}
@@ -0,0 +1,88 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! `atan2` angles against `acos` near parallel, and dihedral angles
+//! checked against the solid-angle kernel: at each vertex of a
+//! tetrahedron, the solid angle is the sum of the three dihedral angles
+//! there, less π.
+//!
+//! ```text
+//! rust-script angles_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/angles.rs"]
+mod angles;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::PI;
+use std::time::Instant;
+use tetrahedron::solid_angle_tetrahedron_scalar;
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+
+    // Near parallel and antiparallel, where acos has nothing left
+    println!("{:>8} {:>12} {:>12}  (relative error)", "offset", "atan2", "acos");
+    for t in [1e-3_f64, 1e-6, 1e-9, 1e-12] {
+        for (u, v, exact) in [([1.0, 0.0, 0.0], [1.0, t, 0.0], t.atan()), ([1.0, 0.0, 0.0], [-1.0, t, 0.0], PI - t.atan())] {
+            let robust = angles::angle_between(u, v);
+            let naive = (vec3::dot(u, v) / (vec3::norm(u) * vec3::norm(v))).acos();
+            let (robust, naive) = ((robust - exact).abs() / exact, (naive - exact).abs() / exact);
+            println!("{t:>8.0e} {robust:>12.1e} {naive:>12.1e}");
+            assert!(robust <= 4.0 * f64::EPSILON, "angle_between lost accuracy");
+        }
+    }
+    assert_eq!(angles::angle_between([0.0; 3], [1.0, 2.0, 3.0]), 0.0);
+
+    // Regular tetrahedron: every dihedral angle is acos(1/3)
+    let regular = [[1.0, 1.0, 1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]];
+    for edge in 0..6 {
+        assert!((angles::dihedral_angle(regular, edge) - (1.0_f64 / 3.0).acos()).abs() < 1e-15);
+    }
+
+    // Random tetrahedra: solid angle at each vertex against its dihedrals
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 165, n);
+    let mut dihedral = vec![[0.0; 6]; n];
+    let start = Instant::now();
+    angles::dihedral_angles(&tets, &mut dihedral)?;
+    let serial = start.elapsed().as_secs_f64();
+    let mut par_out = vec![[0.0; 6]; n];
+    let start = Instant::now();
+    angles::dihedral_angles_par(&tets, &mut par_out)?;
+    let parallel = start.elapsed().as_secs_f64();
+    assert!(dihedral == par_out, "parallel driver differs from serial");
+
+    let mut worst = 0.0_f64;
+    for (t, d) in tets.iter().zip(&dihedral) {
+        for vertex in 0..4 {
+            let others: Vec<[f64; 3]> = (0..4).filter(|&k| k != vertex).map(|k| t[k]).collect();
+            let omega = solid_angle_tetrahedron_scalar(t[vertex], others[0], others[1], others[2]).abs();
+            let sum: f64 = angles::EDGES.iter().zip(d).filter(|(e, _)| e.contains(&vertex)).map(|(_, &angle)| angle).sum();
+            worst = worst.max((sum - PI - omega).abs());
+        }
+    }
+    println!("{n} tetrahedra: worst |Σ dihedral - π - Ω| = {worst:.1e} sr");
+    println!("{:.1} ns/tetrahedron serial, {:.1} parallel", 1e9 * serial / n as f64, 1e9 * parallel / n as f64);
+    assert!(worst < 1e-12, "dihedral angles disagree with the solid-angle kernel");
+
+    Ok(())
+}
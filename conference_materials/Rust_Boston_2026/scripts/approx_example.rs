@@ -0,0 +1,106 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! math-poly = []
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! The approximate kernel against the full one: its error on every input
+//! distribution, and the throughput it buys, serial and parallel.
+//!
+//! ```text
+//! rust-script approx_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/approx.rs"]
+mod approx;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use approx::Precision;
+use inputs::Distribution;
+use std::hint::black_box;
+use std::time::Instant;
+
+type Driver = fn(&[[[f64; 3]; 4]], &mut [f64], Precision) -> Result<(), &'static str>;
+
+/// Best-of-5 time per element of `f` at `precision` (ns)
+fn ns_per_elem(tets: &[[[f64; 3]; 4]], out: &mut [f64], f: Driver, precision: Precision) -> f64 {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f(black_box(tets), black_box(&mut *out), precision).unwrap();
+            start.elapsed().as_nanos() as f64
+        })
+        .fold(f64::INFINITY, f64::min)
+        / tets.len() as f64
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+
+    // IEEE special cases agree with the C library in sign, NaN and zero,
+    // and in value to the polynomial's error
+    let specials = [0.0, -0.0, 1.0, -1.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN, f64::MIN_POSITIVE, f64::MAX];
+    for y in specials {
+        for x in specials {
+            let (want, got) = (y.atan2(x), approx::atan2_approx(y, x));
+            let close = want.is_sign_negative() == got.is_sign_negative() && (got - want).abs() <= 1e-7 * want.abs();
+            assert!(close || (want.is_nan() && got.is_nan()), "atan2({y}, {x}): {got} vs {want}");
+        }
+    }
+
+    println!("{:<7} {:>9} {:>9}", "", "max rel", "max abs");
+    let (mut full, mut fast) = (vec![0.0; n], vec![0.0; n]);
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 163, n);
+        approx::solid_angle_tetrahedron_with(&tets, &mut full, Precision::Full)?;
+        approx::solid_angle_tetrahedron_with(&tets, &mut fast, Precision::Approx)?;
+        let (mut rel, mut abs) = (0.0_f64, 0.0_f64);
+        for (&f, &a) in full.iter().zip(&fast) {
+            abs = abs.max((a - f).abs());
+            rel = rel.max(if f == 0.0 { (a - f).abs() } else { ((a - f) / f).abs() });
+        }
+        println!("{:<7} {rel:>9.1e} {abs:>9.1e}", dist.name());
+        // Slivers' angles are near 0 or ±2π, where cancellation in the
+        // denominator costs relative accuracy; hold them to absolute
+        if dist == Distribution::Sliver {
+            assert!(abs < 1e-4, "approximate kernel off on slivers");
+        } else {
+            assert!(rel < 1e-6, "approximate kernel off on {} inputs", dist.name());
+        }
+    }
+
+    let tets = inputs::tetrahedra(Distribution::Random, 163, n);
+    println!("ns/elem on {} with {} threads", dispatch::path().name(), rayon::current_num_threads());
+    println!("{:<7} {:>7} {:>7} {:>8}", "", "full", "approx", "speedup");
+    for (name, f) in [("serial", approx::solid_angle_tetrahedron_with as Driver), ("par", approx::solid_angle_tetrahedra_with_par)] {
+        let t_full = ns_per_elem(&tets, &mut full, f, Precision::Full);
+        let t_fast = ns_per_elem(&tets, &mut fast, f, Precision::Approx);
+        println!("{name:<7} {t_full:>7.2} {t_fast:>7.2} {:>7.1}x", t_full / t_fast);
+    }
+
+    Ok(())
+}
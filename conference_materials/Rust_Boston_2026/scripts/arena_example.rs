@@ -0,0 +1,131 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! The bump arena of `solid_angle/arena.rs`: buffers carved from one
+//! block, the block grown once and then reused, and a BVH rebuilt every
+//! frame from arena buffers matching one rebuilt with fresh ones, with
+//! the time a launch-sized stage spends allocating either way.
+//!
+//! ```text
+//! rust-script arena_example.rs [frames]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use arena::Arena;
+use bvh::Bvh;
+use mesh::TriMesh;
+use std::hint::black_box;
+use std::time::Instant;
+
+type Tet = [[f64; 3]; 4];
+
+/// Octahedra of radius `r` at `centers`
+fn octahedra(centers: &[[f64; 3]], r: f64) -> TriMesh {
+    let corners = [[r, 0.0, 0.0], [-r, 0.0, 0.0], [0.0, r, 0.0], [0.0, -r, 0.0], [0.0, 0.0, r], [0.0, 0.0, -r]];
+    let vertices = centers.iter().flat_map(|c| corners.map(|d| std::array::from_fn(|k| c[k] + d[k]))).collect();
+    let faces = (0..centers.len() as u32)
+        .flat_map(|i| [[0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4], [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5]].map(|f| f.map(|v| 6 * i + v)))
+        .collect();
+    TriMesh::new(vertices, faces).unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let frames: usize = std::env::args().nth(1).map_or(50, |s| s.parse().unwrap());
+
+    // Disjoint buffers, in order, from one block
+    let mut arena = Arena::<f64>::new();
+    assert_eq!(arena.capacity(), 0);
+    let [a, b, c] = arena.buffers([3, 0, 5])?;
+    assert_eq!((a.len(), b.len(), c.len()), (3, 0, 5));
+    a.fill(1.0);
+    c.fill(2.0);
+    assert_eq!((arena.capacity(), arena.grown()), (8, 1));
+
+    // Smaller calls reuse the block and see what was left in it
+    let [d] = arena.buffers([4])?;
+    assert_eq!(d, &[1.0, 1.0, 1.0, 2.0]);
+    assert_eq!((arena.capacity(), arena.grown()), (8, 1));
+    let [_, e] = arena.buffers([6, 4])?;
+    assert_eq!((e.len(), arena.capacity(), arena.grown()), (4, 10, 2));
+    assert_eq!(arena.buffers([usize::MAX, 1]).err(), Some("Arena size overflows"));
+    arena.release();
+    assert_eq!(arena.capacity(), 0);
+
+    // A cloud stirred a little each frame, its BVH refit and regrouped
+    // each time: with arena buffers as with fresh ones
+    let mut rng = inputs::Pcg64::new(176, 0);
+    let mut centers: Vec<[f64; 3]> = (0..5_000).map(|_| rng.point()).collect();
+    let mut mesh = octahedra(&centers, 2e-3);
+    let (mut fresh, mut reused) = (Bvh::new(&mesh), Bvh::new(&mesh));
+    let mut scratch = Arena::new();
+    let mut regrouped = 0;
+    for frame in 0..frames {
+        for c in centers.iter_mut().skip(frame % 100).step_by(100) {
+            *c = c.map(|x| x + 0.05 * (rng.unit() - 0.5));
+        }
+        mesh.vertices_mut().copy_from_slice(octahedra(&centers, 2e-3).vertices());
+        fresh.refit(&mesh);
+        reused.refit(&mesh);
+        let n = fresh.rebuild_degraded(&mesh, 2.0);
+        assert_eq!(reused.rebuild_degraded_in(&mesh, 2.0, &mut scratch), n);
+        assert!(fresh == reused, "frame {frame}: arena rebuild differs");
+        regrouped += n;
+    }
+    println!("{frames} frames, {regrouped} faces regrouped, scratch of {} f64 grown {} time(s)", scratch.capacity(), scratch.grown());
+    assert!(regrouped > 0 && scratch.grown() == 1);
+
+    // A batch queue's launch: gather, solve, scatter, 64k tetrahedra a time
+    let n = 1 << 16;
+    let tets: Vec<Tet> = (0..n).map(|_| rng.tet()).collect();
+    let start = Instant::now();
+    for _ in 0..frames {
+        let mut inputs = black_box(vec![[[0.0; 3]; 4]; n]);
+        inputs.copy_from_slice(&tets);
+        let mut out = black_box(vec![0.0; n]);
+        tetrahedron::solid_angle_tetrahedron(&inputs, &mut out)?;
+    }
+    let allocating = start.elapsed() / frames as u32;
+    let (mut inputs, mut outputs) = (Arena::<Tet>::new(), Arena::<f64>::new());
+    let start = Instant::now();
+    for _ in 0..frames {
+        let ([gathered], [out]) = (inputs.buffers([n])?, outputs.buffers([n])?);
+        gathered.copy_from_slice(&tets);
+        tetrahedron::solid_angle_tetrahedron(black_box(gathered), black_box(out))?;
+    }
+    let reusing = start.elapsed() / frames as u32;
+    println!("launch of {n}: {allocating:.2?} allocating, {reusing:.2?} from arenas");
+    assert_eq!((inputs.grown(), outputs.grown()), (1, 1));
+    Ok(())
+}
@@ -0,0 +1,61 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Small fixed-size batches, as inside another hot loop, through the
+//! const-generic array kernel and through the slice kernel.
+//!
+//! ```text
+//! rust-script array_example.rs [batches]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+type Tet = [[f64; 3]; 4];
+
+/// Time `batches` batches of `N` through each kernel, checking they agree
+fn compare<const N: usize>(tets: &[Tet], batches: usize) {
+    let leaves: Vec<[Tet; N]> = tets.as_chunks::<N>().0.to_vec();
+    let (mut sum_array, mut sum_slice) = (0.0, 0.0);
+
+    let start = Instant::now();
+    for leaf in leaves.iter().cycle().take(batches) {
+        let mut out = [0.0; N];
+        tetrahedron::solid_angle_tetrahedron_array(black_box(leaf), &mut out);
+        sum_array += out.iter().sum::<f64>();
+    }
+    let t_array = start.elapsed();
+
+    let start = Instant::now();
+    for leaf in leaves.iter().cycle().take(batches) {
+        let mut out = [0.0; N];
+        tetrahedron::solid_angle_tetrahedron(black_box(leaf), &mut out).unwrap();
+        sum_slice += out.iter().sum::<f64>();
+    }
+    let t_slice = start.elapsed();
+
+    assert_eq!(sum_array, sum_slice);
+    let ns = |t: std::time::Duration| t.as_nanos() as f64 / (batches * N) as f64;
+    println!("N = {N:>2}: array {:>6.2} ns/elem, slice {:>6.2} ns/elem", ns(t_array), ns(t_slice));
+}
+
+fn main() {
+    let batches: usize = std::env::args().nth(1).map_or(1 << 18, |s| s.parse().unwrap());
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 143, 1 << 12);
+    compare::<4>(&tets, batches);
+    compare::<8>(&tets, batches);
+    compare::<16>(&tets, batches);
+}
@@ -0,0 +1,116 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//!
+//! [features]
+//! default = ["asm-export"]
+//! asm-export = []
+//!
+//! [profile.release]
+//! strip = false # Keep the symbol table for objdump
+//! ```
+//!
+//! Regression check for code generation: disassembles the exported
+//! kernels in this very executable and checks each contains the
+//! instructions it should, e.g. `vfmadd` for the FMA kernel and `vmulpd`
+//! for the auto-vectorized loop.
+//!
+//! Needs `objdump`, and an x86-64-v3 build so that `mul_add` can lower to
+//! `vfmadd` instead of a call to libm's `fma`:
+//!
+//! ```text
+//! RUSTFLAGS="-C target-cpu=x86-64-v3" rust-script --force asm_check.rs
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "asm-export"))]
+compile_error!("asm_check.rs disassembles the asm-export kernels, so needs that feature");
+
+#[cfg(feature = "asm-export")]
+#[path = "solid_angle/asm_export.rs"]
+mod asm_export;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::process::{Command, ExitCode};
+
+/// Exported symbol, and mnemonic prefixes that must each appear in it
+const EXPECTED: [(&str, &[&str]); 4] = [
+    ("solid_angle_export_tetrahedron_scalar", &["vfmadd", "vsqrtsd"]),
+    ("solid_angle_export_tetrahedron", &["vfmadd", "vsqrtsd"]),
+    ("solid_angle_export_tetrahedron_scalar_dd", &["vfmsub", "vfmadd"]),
+    ("solid_angle_export_slice_mul", &["vmulpd"]),
+];
+
+/// Instructions in the disassembly of `symbol` in `binary`, following the
+/// tail jump if the export compiled down to one
+fn instructions(binary: &str, symbol: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("objdump")
+        .args(["-d", "--no-show-raw-insn", "--no-addresses", &format!("--disassemble={symbol}"), binary])
+        .output()
+        .map_err(|e| format!("Could not run objdump: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+
+    // Instruction lines are indented: "\tvfmadd231sd %xmm1,%xmm2,%xmm0"
+    let text = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<String> = text
+        .lines()
+        .filter(|l| l.starts_with(char::is_whitespace))
+        .map(|l| l.trim().to_owned())
+        .collect();
+    match &lines[..] {
+        [] => Err(format!("{symbol} not found in {binary}")),
+        [jmp] if jmp.starts_with("jmp") => {
+            let target = jmp.split(['<', '>']).nth(1).ok_or(format!("Unresolved tail jump in {symbol}"))?;
+            instructions(binary, target)
+        }
+        _ => Ok(lines),
+    }
+}
+
+fn main() -> ExitCode {
+    if !cfg!(all(target_arch = "x86_64", target_feature = "fma")) {
+        eprintln!("Build for x86-64-v3: RUSTFLAGS=\"-C target-cpu=x86-64-v3\" rust-script --force asm_check.rs");
+        return ExitCode::FAILURE;
+    }
+    let binary = std::env::current_exe().unwrap().display().to_string();
+
+    // Nothing calls the exports, so reference them or the linker drops them
+    std::hint::black_box([
+        asm_export::solid_angle_export_tetrahedron_scalar as *const (),
+        asm_export::solid_angle_export_tetrahedron as *const (),
+        asm_export::solid_angle_export_tetrahedron_scalar_dd as *const (),
+        asm_export::solid_angle_export_slice_mul as *const (),
+    ]);
+
+    let mut failed = false;
+    for (symbol, required) in EXPECTED {
+        match instructions(&binary, symbol) {
+            Ok(found) => {
+                let missing: Vec<&str> = required.iter().copied().filter(|r| !found.iter().any(|i| i.starts_with(r))).collect();
+                let status = if missing.is_empty() { "ok" } else { "MISSING" };
+                println!("{status:<8} {symbol} ({} instructions) {missing:?}", found.len());
+                failed |= !missing.is_empty();
+            }
+            Err(e) => {
+                println!("ERROR    {symbol}: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,61 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Solid angle of the Earth from orbit and limb occlusion, on WGS 84.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/astro.rs"]
+mod astro;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use astro::Spheroid;
+use std::f64::consts::PI;
+
+fn main() {
+    let wgs84 = Spheroid::WGS84;
+
+    // Geodetic round trip
+    for (lat, lon, h) in [(42.3601, -71.0589, 10.0), (-89.9, 45.0, 3e3), (0.0, 180.0, 35_786e3)] {
+        let p = astro::geodetic_to_ecef(&wgs84, lat, lon, h);
+        let [lat2, lon2, h2] = astro::ecef_to_geodetic(&wgs84, p);
+        assert!((lat2 - lat).abs() < 1e-9 && (lon2 - lon).abs() < 1e-9 && (h2 - h).abs() < 1e-6);
+    }
+
+    // A sphere matches the closed form
+    let r = 6_371e3;
+    let sphere = Spheroid::sphere(r);
+    for d in [r + 400e3, r + 20_200e3, 384_400e3] {
+        let exact = astro::solid_angle_sphere(r, d);
+        let limb = astro::earth_solid_angle(&sphere, [d * 0.6, d * 0.8, 0.0]).unwrap();
+        println!("sphere from {:>9.0} km: {exact:.15e} sr, limb fan {:.1e} relative", d / 1e3, (limb - exact).abs() / exact);
+        assert!((limb - exact).abs() < 1e-13 * exact);
+    }
+
+    // Same altitude, different geometry: over the pole the satellite is 21 km
+    // nearer the center and its limb is close to the round equator
+    for (name, lat, lon) in [("GEO over the equator", 0.0, -75.0), ("35786 km over the pole", 90.0, 0.0)] {
+        let sat = astro::geodetic_to_ecef(&wgs84, lat, lon, 35_786e3);
+        let omega = astro::earth_solid_angle(&wgs84, sat).unwrap();
+        println!("{name}: {omega:.9} sr, {:.6}% of the sky", 100.0 * omega / (4.0 * PI));
+    }
+
+    // Occlusion from GEO over (0, -75): Quito is in view, Singapore is not
+    let sat = astro::geodetic_to_ecef(&wgs84, 0.0, -75.0, 35_786e3);
+    let quito = astro::geodetic_to_ecef(&wgs84, -0.18, -78.47, 2850.0);
+    let singapore = astro::geodetic_to_ecef(&wgs84, 1.35, 103.82, 15.0);
+    assert!(!astro::occluded_target(&wgs84, sat, quito));
+    assert!(astro::occluded_target(&wgs84, sat, singapore));
+
+    // Straight down is blocked, straight up is not
+    let down = sat.map(|x| -x);
+    assert!(astro::occluded_direction(&wgs84, sat, down));
+    assert!(!astro::occluded_direction(&wgs84, sat, sat));
+}
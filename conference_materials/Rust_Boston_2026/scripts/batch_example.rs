@@ -0,0 +1,200 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! A kernel from outside the crate on the crate's drivers: the
+//! electrostatic potential of a shell of point charges, as a
+//! [batch::BatchKernel], checked against the shell theorem, serial
+//! against parallel, and the portable path against the dispatched one.
+//! The solid angle kernel written the same way matches the shipped one,
+//! and a plain function gets the same wrappers from `batch_kernel!`.
+//! Degenerate tetrahedra, as errors, are reported by index without
+//! stopping the batch.
+//!
+//! ```text
+//! rust-script batch_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/batch.rs"]
+mod batch;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use batch::{batch_kernel, BatchError, BatchKernel, OnError, StrictTetrahedra, Tetrahedra, TryBatchKernel};
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+use vec3::{norm, sub};
+
+/// Potential `Σ q / r` of point charges, in units where Coulomb's
+/// constant is one
+struct Potential {
+    charges: Vec<([f64; 3], f64)>,
+}
+
+impl BatchKernel for Potential {
+    type Input = [f64; 3];
+    type Output = f64;
+
+    #[inline]
+    fn eval(&self, p: &[f64; 3]) -> f64 {
+        self.charges.iter().map(|&(s, q)| q / norm(sub(*p, s))).sum()
+    }
+}
+
+/// `n` equal charges adding up to `total`, spread evenly over a sphere of
+/// `radius` on a Fibonacci lattice
+fn shell(n: usize, radius: f64, total: f64) -> Potential {
+    let golden = PI * (3.0 - 5.0_f64.sqrt());
+    let charges = (0..n)
+        .map(|i| {
+            let z = 1.0 - (2 * i + 1) as f64 / n as f64;
+            let (r, phi) = ((1.0 - z * z).sqrt(), golden * i as f64);
+            ([radius * r * phi.cos(), radius * r * phi.sin(), radius * z], total / n as f64)
+        })
+        .collect();
+    Potential { charges }
+}
+
+/// Potential of a unit charge at the origin
+#[inline]
+fn coulomb(p: [f64; 3]) -> f64 {
+    1.0 / norm(p)
+}
+
+batch_kernel! {
+    /// Vector variant of [coulomb]
+    fn coulomb(p: [f64; 3]) -> f64 => coulombs, coulombs_par, coulombs_vec
+}
+
+/// Best-of-5 wall time
+fn best(mut f: impl FnMut()) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 14, |s| s.parse().unwrap());
+    println!("dispatch path: {}", dispatch::path().name());
+
+    // Points along a ray, inside and outside a unit shell of unit charge:
+    // a constant 1 inside, 1 / r outside, up to the lattice's graininess
+    let kernel = shell(4000, 1.0, 1.0);
+    let points: Vec<[f64; 3]> = (0..n).map(|i| [0.3, -0.2, 0.1].map(|x| x * (i as f64 + 0.5) * 20.0 / n as f64)).collect();
+    let mut serial = vec![0.0; n];
+    kernel.eval_slice(&points, &mut serial)?;
+    for (p, &phi) in points.iter().zip(&serial) {
+        let r = norm(*p);
+        let expect = if r < 1.0 { 1.0 } else { 1.0 / r };
+        if (r - 1.0).abs() > 0.1 {
+            assert!((phi - expect).abs() < 1e-3 * expect, "r = {r}: {phi} against {expect}");
+        }
+    }
+
+    // Each element as eval gives it, in parallel and serial alike
+    let by_element: Vec<f64> = points.iter().map(|p| kernel.eval(p)).collect();
+    assert!(serial.iter().zip(&by_element).all(|(a, b)| a.to_bits() == b.to_bits()));
+    for threshold in [0, par::DEFAULT_PAR_THRESHOLD, usize::MAX] {
+        par::set_par_threshold(threshold);
+        let mut parallel = vec![0.0; n];
+        kernel.eval_par(&points, &mut parallel)?;
+        assert!(parallel.iter().zip(&serial).all(|(a, b)| a.to_bits() == b.to_bits()), "threshold {threshold}");
+    }
+    par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+
+    // The solid angle kernel as a BatchKernel is the shipped one, on every
+    // distribution and through both drivers
+    for dist in inputs::Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 197, 20_001);
+        let (mut shipped, mut ours, mut ours_par) = (vec![0.0; tets.len()], vec![0.0; tets.len()], vec![0.0; tets.len()]);
+        dispatch::solid_angle_tetrahedra_dispatch_par(&tets, &mut shipped)?;
+        Tetrahedra.eval_slice(&tets, &mut ours)?;
+        Tetrahedra.eval_par(&tets, &mut ours_par)?;
+        let same = |x: &[f64]| x.iter().zip(&shipped).all(|(a, b)| a.to_bits() == b.to_bits());
+        assert!(same(&ours) && same(&ours_par), "{} differs from the shipped kernel", dist.name());
+    }
+
+    // A lone charge's potential, wrapped by the macro instead, matches the
+    // kernel that holds it as a source
+    let lone = Potential { charges: vec![([0.0; 3], 1.0)] };
+    let mut ours = vec![0.0; n];
+    lone.eval_par(&points, &mut ours)?;
+    assert_eq!(coulombs_vec(&points), ours);
+    coulombs(&points, &mut ours)?;
+    assert_eq!(coulombs_vec(&points), ours);
+    assert_eq!(coulombs_par(&points, &mut ours[1..]), Err("Dimension mismatch"));
+
+    // Degenerate tetrahedra as errors: every one reported by index, or the
+    // first, serial and parallel alike, and the rest computed as usual
+    let mut tets = inputs::tetrahedra(inputs::Distribution::Random, 199, 20_000);
+    let bad = [3, 12_345, 19_999];
+    for &i in &bad {
+        tets[i][2] = tets[i][0];
+    }
+    let mut expect = vec![0.0; tets.len()];
+    Tetrahedra.eval_slice(&tets, &mut expect)?;
+    for threshold in [0, usize::MAX] {
+        par::set_par_threshold(threshold);
+        let mut strict = vec![-1.0; tets.len()];
+        let Err(BatchError::Elements(errors)) = StrictTetrahedra.try_eval_par(&tets, &mut strict, OnError::CollectAll) else {
+            panic!("degenerate tetrahedra not reported");
+        };
+        assert_eq!(errors.iter().map(|e| e.0).collect::<Vec<_>>(), bad, "threshold {threshold}");
+        assert!(bad.iter().all(|&i| strict[i] == -1.0) && (0..tets.len()).filter(|i| !bad.contains(i)).all(|i| strict[i] == expect[i]));
+        let first = StrictTetrahedra.try_eval_par(&tets, &mut strict, OnError::FailFast);
+        assert_eq!(first, Err(BatchError::Elements(vec![(3, "Degenerate tetrahedron")])), "threshold {threshold}");
+    }
+    par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+    let err = StrictTetrahedra.try_eval_slice(&tets, &mut expect, OnError::CollectAll).unwrap_err();
+    assert_eq!(err.to_string(), "3 elements failed, first 3: Degenerate tetrahedron");
+    assert_eq!(StrictTetrahedra.try_eval_par(&tets[4..12_345], &mut expect[4..12_345], OnError::FailFast), Ok(()));
+    assert_eq!(StrictTetrahedra.try_eval_par(&tets, &mut expect[1..], OnError::CollectAll), Err(BatchError::DimensionMismatch));
+
+    // Degenerate inputs
+    assert_eq!(kernel.eval_slice(&points, &mut serial[1..]), Err("Dimension mismatch"));
+    assert_eq!(kernel.eval_par(&points[1..], &mut serial), Err("Dimension mismatch"));
+    assert_eq!(Tetrahedra.eval_par(&[], &mut []), Ok(()));
+
+    // What dispatch and threads buy a kernel written without either in mind
+    let mut out = vec![0.0; n];
+    dispatch::set_path(Some(dispatch::Path::Portable))?;
+    let portable = best(|| kernel.eval_slice(&points, &mut out).unwrap());
+    dispatch::set_path(None)?;
+    let dispatched = best(|| kernel.eval_slice(&points, &mut out).unwrap());
+    let parallel = best(|| kernel.eval_par(&points, &mut out).unwrap());
+    let ns = |t: Duration| t.as_nanos() as f64 / n as f64;
+    println!("potential of {} charges at n = {n} points", kernel.charges.len());
+    println!("    portable: {:>9.1} ns/elem", ns(portable));
+    println!("    dispatch: {:>9.1} ns/elem ({:.2}x)", ns(dispatched), portable.as_secs_f64() / dispatched.as_secs_f64());
+    println!("    parallel: {:>9.1} ns/elem ({:.2}x)", ns(parallel), portable.as_secs_f64() / parallel.as_secs_f64());
+    Ok(())
+}
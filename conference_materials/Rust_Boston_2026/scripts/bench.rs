@@ -0,0 +1,871 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! tikv-jemallocator = { version = "0.6", optional = true }
+//! mimalloc = { version = "0.1", optional = true }
+//!
+//! [target.'cfg(target_os = "linux")'.dependencies]
+//! perf-event2 = { version = "0.7", optional = true }
+//! libc = { version = "0.2", optional = true }
+//!
+//! [features]
+//! energy = []
+//! hugepages = ["dep:libc"]
+//! jemalloc = ["dep:tikv-jemallocator"]
+//! mimalloc = ["dep:mimalloc"]
+//! perf-events = ["dep:perf-event2"]
+//! rvv = []
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Throughput of each kernel variant on random tetrahedra, seeded so that
+//! every machine times the same inputs.
+//!
+//! ```text
+//! rust-script bench.rs [n] [reps] [best|interleaved|cold|alloc|instructions]
+//! ```
+//!
+//! Buffers are pre-touched page by page and each kernel gets two untimed
+//! warm-up runs, so first-touch page faults and cold caches stay out of
+//! the numbers; otherwise they dominate the parallel speedup for big
+//! buffers. `cold` measures that cost instead: each kernel's first run
+//! into a freshly allocated output, next to its warm best-of time, with
+//! the page faults taken (from `/proc/self/stat`, on Linux). With the
+//! `hugepages` feature on Linux, buffers are `madvise`d for transparent
+//! huge pages before first touch, which only takes effect when
+//! `/sys/kernel/mm/transparent_hugepage/enabled` is `madvise` or `always`.
+//!
+//! The default `best` mode times each kernel `reps` times back to back and
+//! keeps the fastest. On a laptop that biases whichever kernel runs once
+//! the machine has heated up, so `interleaved` instead runs `reps` rounds
+//! of every kernel in a shuffled order, reading the clock frequency around
+//! each run (`cpufreq` or `/proc/cpuinfo` on Linux, `sysctl` on Intel
+//! Macs). Runs below 90% of the typical clock count as throttled and are
+//! dropped, and each kernel gets its median with a 95% confidence
+//! interval; 30 or more rounds give usable intervals.
+//!
+//! rust-script has no feature flags, so counters go through the generated
+//! package:
+//!
+//! ```text
+//! cd $(rust-script -p bench.rs | tail -1) && cargo run --release --features perf-events -- [n] [reps]
+//! ```
+//!
+//! With `perf-events` on Linux, each kernel run is also wrapped in
+//! hardware counters, reporting IPC, last-level cache misses, and on Intel
+//! the retired double-precision FLOPs (`FP_ARITH_INST_RETIRED`, which
+//! counts an FMA as two). FMA utilization is FLOPs per cycle over the
+//! scalar-FMA peak of two ports, 4 FLOP/cycle on recent x86 cores.
+//! Counters need `perf_event_paranoid <= 2` and a PMU, which most VMs do
+//! not expose; without one, only timings are reported.
+//!
+//! With `energy`, each kernel is also looped for half a second under an
+//! energy meter, reporting nanojoules per element and mean power, for
+//! performance-per-watt comparisons between variants. On Linux this reads
+//! the RAPL package counters in `/sys/class/powercap`, which are root-only
+//! since 5.10; on macOS it samples `powermetrics`, which needs `sudo`.
+//! Either way it is the whole package, idle cores and other processes
+//! included, so compare variants on a quiet machine. VMs rarely expose
+//! either.
+//!
+//! `alloc` times the allocation-heavy stages instead, on a terrain grid of
+//! about `n` faces: loading it from `.npz`, building its adjacency, and
+//! building its BVH, best of `reps` each. These vary by up to 2x between
+//! global allocators, so the benchmark matrix runs it once per allocator
+//! feature and compares:
+//!
+//! ```text
+//! cd $(rust-script -p bench.rs | tail -1)
+//! for a in "" jemalloc mimalloc; do cargo run --release --features "$a" -- 1000000 10 alloc; done
+//! ```
+//!
+//! `instructions` counts instead of timing, after `iai`: each serial
+//! kernel runs once in a copy of this program under Valgrind's
+//! cachegrind, on `n` elements and on none, and the difference in
+//! instructions executed is divided by `n`. The counts don't move with
+//! load, clock or neighbours, so a 1% change is real, which makes it the
+//! check for the loops that only stay fast while they vectorize. Given a
+//! baseline file, it fails on any kernel more than
+//! [INSTRUCTION_TOLERANCE] above it, and writes one if there is none:
+//!
+//! ```text
+//! rust-script bench.rs 65536 1 instructions instructions.txt
+//! ```
+//!
+//! Counts depend on the compiler, the dispatch path and the `atan2`
+//! backend, so keep a baseline per CI machine and delete it to take a new
+//! one. Needs `valgrind` on the `PATH`; the parallel kernels are left out,
+//! as rayon's idle threads spin for a varying number of instructions.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/alloc.rs"]
+mod alloc;
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/fixed.rs"]
+mod fixed;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/interval.rs"]
+mod interval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::hint::black_box;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+type Tet = [[f64; 3]; 4];
+
+/// Slice kernel under test, writing one `f64` per element
+type Kernel = fn(&[Tet], &mut [f64]) -> Result<(), &'static str>;
+
+const KERNELS: [(&str, Kernel); 8] = [
+    ("fma", tetrahedron::solid_angle_tetrahedron),
+    ("fma par", par::solid_angle_tetrahedra_par),
+    ("dispatch", dispatch::solid_angle_tetrahedron_dispatch),
+    ("dispatch par", dispatch::solid_angle_tetrahedra_dispatch_par),
+    ("condition", |t, out| condition::solid_angle_tetrahedron_cond(t, out, &mut vec![0.0; t.len()])),
+    ("fixed 1e-9", |t, out| fixed::solid_angle_tetrahedron_fixed(t, 1e-9, out)),
+    ("interval", |t, out| {
+        let mut filtered = vec![interval::Filtered::Enclosure(interval::Interval::point(0.0)); t.len()];
+        interval::solid_angle_tetrahedron_filtered(t, &mut filtered)?;
+        for (y, f) in out.iter_mut().zip(filtered) {
+            *y = match f {
+                interval::Filtered::Enclosure(enc) => enc.mid(),
+                interval::Filtered::Refined(dd) => dd.to_f64(),
+            };
+        }
+        Ok(())
+    }),
+    ("double-double", dd::solid_angle_tetrahedron_dd),
+];
+
+/// Counter totals over one measured run
+#[derive(Clone, Copy, Debug, Default)]
+struct Counts {
+    cycles: u64,
+    instructions: u64,
+    cache_misses: u64,
+    /// Retired double-precision FLOPs, where the PMU has an event for them
+    flops: Option<u64>,
+}
+
+#[cfg(all(feature = "perf-events", target_os = "linux"))]
+mod counters {
+    use super::Counts;
+    use perf_event::events::{Hardware, Raw};
+    use perf_event::{Builder, Counter, Group, GroupData};
+    use std::io;
+
+    /// `FP_ARITH_INST_RETIRED` umasks for scalar, 128-, 256- and 512-bit
+    /// packed double, with the FLOPs per count of each
+    #[cfg(target_arch = "x86_64")]
+    const FP_ARITH_DOUBLE: [(u64, u64); 4] = [(0x01c7, 1), (0x04c7, 2), (0x10c7, 4), (0x40c7, 8)];
+
+    pub struct Counters {
+        basic: Group,
+        cycles: Counter,
+        instructions: Counter,
+        cache_misses: Counter,
+        /// In a group of their own, as they may not be schedulable alongside the rest
+        fp: Option<(Group, Vec<(Counter, u64)>)>,
+    }
+
+    impl Counters {
+        pub fn new() -> io::Result<Self> {
+            let mut basic = Group::new()?;
+            let cycles = basic.add(&Builder::new(Hardware::CPU_CYCLES))?;
+            let instructions = basic.add(&Builder::new(Hardware::INSTRUCTIONS))?;
+            let cache_misses = basic.add(&Builder::new(Hardware::CACHE_MISSES))?;
+            Ok(Self { basic, cycles, instructions, cache_misses, fp: Self::fp_counters().ok() })
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        fn fp_counters() -> io::Result<(Group, Vec<(Counter, u64)>)> {
+            let mut group = Group::new()?;
+            let counters = FP_ARITH_DOUBLE
+                .iter()
+                .map(|&(config, flops)| Ok((group.add(&Builder::new(Raw::new(config)))?, flops)))
+                .collect::<io::Result<_>>()?;
+            Ok((group, counters))
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        fn fp_counters() -> io::Result<(Group, Vec<(Counter, u64)>)> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+
+        /// Counts over one call of `f`, scaled up if the kernel multiplexed the PMU
+        pub fn measure(&mut self, f: impl FnOnce()) -> io::Result<Counts> {
+            self.basic.reset()?;
+            if let Some((group, _)) = &mut self.fp {
+                group.reset()?;
+                group.enable()?;
+            }
+            self.basic.enable()?;
+            f();
+            self.basic.disable()?;
+            if let Some((group, _)) = &mut self.fp {
+                group.disable()?;
+            }
+
+            let basic = self.basic.read()?;
+            let flops = match &mut self.fp {
+                Some((group, counters)) => {
+                    let data = group.read()?;
+                    Some(counters.iter().map(|(c, flops)| scaled(&data, c) * flops).sum())
+                }
+                None => None,
+            };
+            Ok(Counts {
+                cycles: scaled(&basic, &self.cycles),
+                instructions: scaled(&basic, &self.instructions),
+                cache_misses: scaled(&basic, &self.cache_misses),
+                flops,
+            })
+        }
+    }
+
+    /// Count extrapolated over the time the group was enabled but not scheduled
+    fn scaled(data: &GroupData, counter: &Counter) -> u64 {
+        let count = data[counter];
+        match (data.time_enabled(), data.time_running()) {
+            (Some(enabled), Some(running)) if !running.is_zero() => {
+                (count as f64 * enabled.as_secs_f64() / running.as_secs_f64()) as u64
+            }
+            _ => count,
+        }
+    }
+}
+
+/// Stand-in without the feature, or off Linux
+#[cfg(not(all(feature = "perf-events", target_os = "linux")))]
+mod counters {
+    use super::Counts;
+    use std::io;
+
+    pub struct Counters;
+
+    impl Counters {
+        pub fn new() -> io::Result<Self> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "built without the perf-events feature"))
+        }
+
+        pub fn measure(&mut self, f: impl FnOnce()) -> io::Result<Counts> {
+            f();
+            Ok(Counts::default())
+        }
+    }
+}
+
+#[cfg(all(feature = "energy", target_os = "linux"))]
+mod energy {
+    use std::fs;
+    use std::io;
+    use std::path::PathBuf;
+
+    const POWERCAP: &str = "/sys/class/powercap";
+
+    /// One RAPL package domain, whose counter wraps at `range` µJ
+    struct Domain {
+        energy_uj: PathBuf,
+        range: u64,
+    }
+
+    pub struct Meter {
+        packages: Vec<Domain>,
+    }
+
+    fn read_u64(path: PathBuf) -> io::Result<u64> {
+        fs::read_to_string(path)?.trim().parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    impl Meter {
+        pub fn new() -> io::Result<Self> {
+            let mut packages = Vec::new();
+            let zones = fs::read_dir(POWERCAP).map_err(|e| io::Error::new(e.kind(), format!("{POWERCAP}: {e}")))?;
+            for entry in zones {
+                let dir = entry?.path();
+                // Top-level zones are `intel-rapl:<n>`, also on AMD; `psys`
+                // zones there overlap the packages, and subzones are parts of them
+                let top_level = dir.file_name().and_then(|s| s.to_str()).is_some_and(|s| {
+                    s.strip_prefix("intel-rapl:").is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                });
+                if !top_level || !fs::read_to_string(dir.join("name"))?.starts_with("package") {
+                    continue;
+                }
+                let domain = Domain { energy_uj: dir.join("energy_uj"), range: read_u64(dir.join("max_energy_range_uj"))? };
+                read_u64(domain.energy_uj.clone())?; // Fail here, not mid-run, if root-only
+                packages.push(domain);
+            }
+            if packages.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "no RAPL package zones"));
+            }
+            Ok(Self { packages })
+        }
+
+        fn read(&self) -> io::Result<Vec<u64>> {
+            self.packages.iter().map(|d| read_u64(d.energy_uj.clone())).collect()
+        }
+
+        /// Joules used by all packages over one call of `f`
+        pub fn measure(&mut self, f: impl FnOnce()) -> io::Result<f64> {
+            let before = self.read()?;
+            f();
+            let after = self.read()?;
+            let microjoules: u64 = self
+                .packages
+                .iter()
+                .zip(before.iter().zip(&after))
+                .map(|(d, (&b, &a))| if a >= b { a - b } else { a + d.range - b })
+                .sum();
+            Ok(microjoules as f64 * 1e-6)
+        }
+    }
+}
+
+#[cfg(all(feature = "energy", target_os = "macos"))]
+mod energy {
+    use std::io;
+    use std::process::{Command, Stdio};
+    use std::time::Instant;
+
+    /// `powermetrics` sampling interval, ms
+    const INTERVAL_MS: &str = "100";
+
+    pub struct Meter;
+
+    impl Meter {
+        pub fn new() -> io::Result<Self> {
+            let status = Command::new("powermetrics")
+                .args(["--samplers", "cpu_power", "-i", "1", "-n", "1"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()?;
+            if !status.success() {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "powermetrics needs sudo"));
+            }
+            Ok(Self)
+        }
+
+        /// Joules used by the CPU over one call of `f`, as its mean sampled
+        /// power times the elapsed time
+        pub fn measure(&mut self, f: impl FnOnce()) -> io::Result<f64> {
+            let mut child = Command::new("powermetrics")
+                .args(["--samplers", "cpu_power", "-i", INTERVAL_MS])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()?;
+            let start = Instant::now();
+            f();
+            let seconds = start.elapsed().as_secs_f64();
+            child.kill()?;
+            let output = child.wait_with_output()?;
+
+            let watts: Vec<f64> = String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_watts).collect();
+            if watts.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "no powermetrics samples; run for longer"));
+            }
+            Ok(seconds * watts.iter().sum::<f64>() / watts.len() as f64)
+        }
+    }
+
+    /// Power from a sample line: `CPU Power: 1234 mW` on Apple silicon,
+    /// `Intel energy model derived package power (CPUs+GT+SA): 1.23W` on Intel
+    fn parse_watts(line: &str) -> Option<f64> {
+        if let Some(mw) = line.strip_prefix("CPU Power:") {
+            return mw.trim().strip_suffix("mW")?.trim().parse::<f64>().ok().map(|mw| 1e-3 * mw);
+        }
+        if line.starts_with("Intel energy model derived package power") {
+            return line.rsplit(':').next()?.trim().strip_suffix('W')?.parse().ok();
+        }
+        None
+    }
+}
+
+/// Stand-in without the feature, or off Linux and macOS
+#[cfg(not(all(feature = "energy", any(target_os = "linux", target_os = "macos"))))]
+mod energy {
+    use std::io;
+
+    pub struct Meter;
+
+    impl Meter {
+        pub fn new() -> io::Result<Self> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "built without the energy feature"))
+        }
+
+        pub fn measure(&mut self, f: impl FnOnce()) -> io::Result<f64> {
+            f();
+            Ok(0.0)
+        }
+    }
+}
+
+/// CPU clock as the OS reports it
+mod clock {
+    /// Mean clock over cores in MHz, or None where the OS doesn't say
+    #[cfg(target_os = "linux")]
+    pub fn mhz() -> Option<f64> {
+        use std::fs;
+
+        // cpufreq where there is a driver; /proc/cpuinfo on x86 otherwise
+        let cpufreq: Vec<f64> = fs::read_dir("/sys/devices/system/cpu")
+            .ok()?
+            .filter_map(|e| {
+                let path = e.ok()?.path().join("cpufreq/scaling_cur_freq");
+                fs::read_to_string(path).ok()?.trim().parse::<f64>().ok()
+            })
+            .map(|khz| 1e-3 * khz)
+            .collect();
+        let mhz = if cpufreq.is_empty() {
+            let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+            cpuinfo.lines().filter(|l| l.starts_with("cpu MHz")).filter_map(|l| l.split(':').nth(1)?.trim().parse().ok()).collect()
+        } else {
+            cpufreq
+        };
+        (!mhz.is_empty()).then(|| mhz.iter().sum::<f64>() / mhz.len() as f64)
+    }
+
+    /// Nominal clock in MHz; Apple silicon doesn't report one
+    #[cfg(target_os = "macos")]
+    pub fn mhz() -> Option<f64> {
+        let out = std::process::Command::new("sysctl").args(["-n", "hw.cpufrequency"]).output().ok()?;
+        String::from_utf8_lossy(&out.stdout).trim().parse::<f64>().ok().map(|hz| 1e-6 * hz)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn mhz() -> Option<f64> {
+        None
+    }
+}
+
+/// Fraction of the typical clock below which a run counts as throttled
+const THROTTLED: f64 = 0.9;
+
+/// Distribution-free 95% confidence interval for the median of sorted
+/// `x`, from the binomial distribution of ranks about it; None for fewer
+/// than 8 samples, where it would run past the ends
+fn median_ci(x: &[f64]) -> Option<(f64, f64)> {
+    let k = x.len() as f64;
+    let (lo, hi) = ((0.5 * k - 0.98 * k.sqrt()).floor(), (0.5 * k + 1.0 + 0.98 * k.sqrt()).ceil());
+    (lo >= 1.0 && hi <= k).then(|| (x[lo as usize - 1], x[hi as usize - 1]))
+}
+
+/// Rounds of every kernel in shuffled order, dropping throttled runs
+fn interleaved(tets: &[Tet], out: &mut [f64], rounds: usize) -> Result<(), &'static str> {
+    let n = tets.len();
+    let mut rng = inputs::Pcg64::new(157, 0);
+    let mut order: Vec<usize> = (0..KERNELS.len()).collect();
+    // Seconds and the lower of the clocks either side, per kernel
+    let mut samples = vec![Vec::with_capacity(rounds); KERNELS.len()];
+    for _ in 0..WARMUP {
+        for (_, kernel) in KERNELS {
+            kernel(black_box(tets), black_box(out))?;
+        }
+    }
+    for _ in 0..rounds {
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.below(i + 1));
+        }
+        for &k in &order {
+            let before = clock::mhz();
+            let start = Instant::now();
+            (KERNELS[k].1)(black_box(tets), black_box(out))?;
+            let seconds = start.elapsed().as_secs_f64();
+            samples[k].push((seconds, before.zip(clock::mhz()).map(|(a, b)| a.min(b))));
+        }
+    }
+
+    // Typical clock as the 90th percentile, so turbo counts as normal
+    let mut clocks: Vec<f64> = samples.iter().flatten().filter_map(|s| s.1).collect();
+    clocks.sort_by(f64::total_cmp);
+    let typical = clocks.get(clocks.len() * 9 / 10).copied();
+    match typical {
+        Some(f) => println!("n = {n}, {rounds} rounds, clock {f:.0} MHz typical, dropping runs below {:.0} MHz", THROTTLED * f),
+        None => println!("n = {n}, {rounds} rounds, clock unavailable; keeping every run"),
+    }
+
+    println!("{:<14} {:>10} {:>21} {:>6}", "kernel", "ns/elem", "95% CI", "kept");
+    for ((name, _), s) in KERNELS.iter().zip(&samples) {
+        let mut ns: Vec<f64> = s
+            .iter()
+            .filter(|(_, mhz)| match (mhz, typical) {
+                (Some(m), Some(f)) => *m >= THROTTLED * f,
+                _ => true,
+            })
+            .map(|(seconds, _)| 1e9 * seconds / n as f64)
+            .collect();
+        ns.sort_by(f64::total_cmp);
+        print!("{name:<14} ");
+        if ns.is_empty() {
+            print!("{:>10} {:>21}", "-", "-");
+        } else {
+            print!("{:>10.2} ", ns[ns.len() / 2]);
+            match median_ci(&ns) {
+                Some((lo, hi)) => print!("{:>21}", format!("[{lo:.2}, {hi:.2}]")),
+                None => print!("{:>21}", "-"),
+            }
+        }
+        println!(" {:>3}/{rounds}", ns.len());
+    }
+    Ok(())
+}
+
+/// Untimed runs before timing
+const WARMUP: usize = 2;
+
+/// Smallest page size in use, the stride for pre-touching
+const PAGE: usize = 4096;
+
+/// Ask for transparent huge pages over the whole pages of `buf`, which
+/// must not have been touched yet for it to take effect at first touch
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+fn advise_huge_pages<T>(buf: &mut [T]) -> std::io::Result<()> {
+    // SAFETY: sysconf only reads configuration
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = buf.as_mut_ptr() as usize;
+    let (aligned, end) = (start.next_multiple_of(page), start + size_of_val(buf));
+    if aligned >= end {
+        return Ok(());
+    }
+    // SAFETY: The range is page-aligned and inside `buf`, and the advice
+    // never changes its contents
+    match unsafe { libc::madvise(aligned as *mut libc::c_void, end - aligned, libc::MADV_HUGEPAGE) } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error()),
+    }
+}
+
+#[cfg(not(all(feature = "hugepages", target_os = "linux")))]
+fn advise_huge_pages<T>(_buf: &mut [T]) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "built without the hugepages feature"))
+}
+
+/// Write every page of `buf`, so its page faults happen here, not in a timed run
+fn pretouch<T>(buf: &mut [T]) {
+    let step = (PAGE / size_of::<T>()).max(1);
+    for i in (0..buf.len()).step_by(step) {
+        let p: *mut T = &mut buf[i];
+        // SAFETY: p is in bounds and initialized; volatile, so the write of
+        // an unchanged value isn't optimized out
+        unsafe { p.write_volatile(p.read()) };
+    }
+}
+
+/// Zeroed buffer whose pages haven't been touched yet: large zeroed
+/// allocations come straight from the OS, which maps pages on first write
+fn untouched<T: Clone + Default>(n: usize) -> Vec<T> {
+    let mut buf = vec![T::default(); n];
+    advise_huge_pages(&mut buf).ok(); // Reported once, in main
+    buf
+}
+
+/// Minor page faults of this process so far, where the OS says
+fn minor_faults() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // `minflt` is the 10th field, and the 8th after the parenthesized name
+    stat.rsplit_once(')')?.1.split_whitespace().nth(7)?.parse().ok()
+}
+
+/// First run of each kernel into a fresh output, then its warm best-of
+fn cold(tets: &[Tet], reps: usize) -> Result<(), &'static str> {
+    let n = tets.len();
+    // All allocated before any is freed, so each comes fresh from the OS
+    let mut fresh: Vec<Vec<f64>> = KERNELS.iter().map(|_| untouched(n)).collect();
+    println!("n = {n}, cold first run, then warm best of {reps}");
+    println!("{:<14} {:>10} {:>10} {:>7} {:>12}", "kernel", "cold ns/el", "warm ns/el", "ratio", "faults/page");
+    for ((name, kernel), out) in KERNELS.into_iter().zip(&mut fresh) {
+        let faults = minor_faults();
+        let start = Instant::now();
+        kernel(black_box(tets), black_box(out))?;
+        let cold = start.elapsed().as_secs_f64() / n as f64;
+        let faults = faults.zip(minor_faults()).map(|(a, b)| b - a);
+
+        for _ in 0..WARMUP {
+            kernel(black_box(tets), black_box(out))?;
+        }
+        let mut best = Duration::MAX;
+        for _ in 0..reps {
+            let start = Instant::now();
+            kernel(black_box(tets), black_box(out))?;
+            best = best.min(start.elapsed());
+        }
+        let warm = best.as_secs_f64() / n as f64;
+
+        print!("{name:<14} {:>10.2} {:>10.2} {:>7.2}", 1e9 * cold, 1e9 * warm, cold / warm);
+        let pages = (size_of_val(out.as_slice()) as f64 / PAGE as f64).ceil();
+        match faults {
+            Some(f) => println!(" {:>12.3}", f as f64 / pages),
+            None => println!(" {:>12}", "-"),
+        }
+    }
+    Ok(())
+}
+
+/// Loading, adjacency and BVH build of a terrain grid of about `n` faces,
+/// best of `reps` each, under this build's global allocator
+fn allocation(n: usize, reps: usize) -> Result<(), &'static str> {
+    let side = ((n as f64 / 2.0).sqrt().ceil() as usize).max(1);
+    let w = side + 1;
+    let mut rng = inputs::Pcg64::new(0, 0);
+    let vertices = (0..w * w).map(|i| [(i % w) as f64, (i / w) as f64, rng.unit()]).collect();
+    let faces = (0..side * side)
+        .flat_map(|c| {
+            let v = (c / side * w + c % side) as u32;
+            let w = w as u32;
+            [[v, v + 1, v + w + 1], [v, v + w + 1, v + w]]
+        })
+        .collect();
+    let mesh = mesh::TriMesh::new(vertices, faces)?;
+    let mut npz = std::io::Cursor::new(Vec::new());
+    mesh_io::write_tri_mesh_npz(&mut npz, &mesh).map_err(|_| "Failed to write the mesh")?;
+    let npz = npz.into_inner();
+
+    let stages: [(&str, &dyn Fn()); 3] = [
+        ("mesh load", &|| drop(black_box(mesh_io::read_tri_mesh_npz(std::io::Cursor::new(black_box(&npz))).unwrap()))),
+        ("adjacency", &|| drop(black_box(mesh.build_adjacency()))),
+        ("BVH build", &|| drop(black_box(bvh::Bvh::new(black_box(&mesh))))),
+    ];
+    let faces = mesh.faces().len();
+    println!("{faces} faces, best of {reps}, {} allocator", alloc::ALLOCATOR);
+    println!("{:<14} {:>10} {:>10}", "stage", "ms", "ns/face");
+    for (name, stage) in stages {
+        for _ in 0..WARMUP {
+            stage();
+        }
+        let mut best = Duration::MAX;
+        for _ in 0..reps {
+            let start = Instant::now();
+            stage();
+            best = best.min(start.elapsed());
+        }
+        println!("{name:<14} {:>10.2} {:>10.1}", 1e3 * best.as_secs_f64(), 1e9 * best.as_secs_f64() / faces as f64);
+    }
+    Ok(())
+}
+
+/// Relative increase in instructions per element over the baseline that
+/// fails the `instructions` mode
+const INSTRUCTION_TOLERANCE: f64 = 0.01;
+
+/// Mode the `instructions` mode runs its copies under cachegrind in
+const INSTRUCTIONS_CHILD: &str = "instructions-child";
+
+/// Instructions executed by a copy of this program running kernel `k` on
+/// the first `len` of `n` elements, from cachegrind's summary
+fn cachegrind_instructions(n: usize, k: usize, len: usize) -> Result<u64, &'static str> {
+    let exe = std::env::current_exe().map_err(|_| "No path to this executable")?;
+    let file = std::env::temp_dir().join(format!("bench-cachegrind-{}-{k}-{len}.out", std::process::id()));
+    let status = Command::new("valgrind")
+        .args(["--tool=cachegrind", "--cache-sim=no", "--branch-sim=no", "--quiet"])
+        .arg(format!("--cachegrind-out-file={}", file.display()))
+        .arg(exe)
+        .args([n.to_string(), "1".into(), INSTRUCTIONS_CHILD.into(), k.to_string(), len.to_string()])
+        .status()
+        .map_err(|_| "Could not run valgrind; the instructions mode needs it on the PATH")?;
+    let text = std::fs::read_to_string(&file);
+    std::fs::remove_file(&file).ok();
+    if !status.success() {
+        return Err("Kernel failed under cachegrind");
+    }
+
+    // "summary: 123456789", with only instructions counted
+    text.ok()
+        .and_then(|t| t.lines().find_map(|l| l.strip_prefix("summary:")?.split_whitespace().next()?.parse().ok()))
+        .ok_or("No instruction count in cachegrind's output")
+}
+
+/// Instructions per element of each serial kernel, against `baseline`
+/// where there is one
+fn instructions(n: usize, baseline: Option<&str>) -> Result<(), &'static str> {
+    let previous: Option<Vec<(String, f64)>> = baseline.and_then(|path| std::fs::read_to_string(path).ok()).map(|text| {
+        text.lines()
+            .filter_map(|l| {
+                let (name, count) = l.rsplit_once('\t')?;
+                Some((name.to_string(), count.trim().parse().ok()?))
+            })
+            .collect()
+    });
+
+    println!("n = {n}, instructions per element under cachegrind");
+    println!("{:<14} {:>12} {:>12} {:>8}", "kernel", "instr/elem", "baseline", "change");
+    let (mut table, mut regressed) = (String::new(), false);
+    for (k, (name, _)) in KERNELS.iter().enumerate().filter(|(_, (name, _))| !name.ends_with(" par")) {
+        let (full, empty) = (cachegrind_instructions(n, k, n)?, cachegrind_instructions(n, k, 0)?);
+        let per_elem = full.saturating_sub(empty) as f64 / n as f64;
+        table.push_str(&format!("{name}\t{per_elem:.2}\n"));
+
+        print!("{name:<14} {per_elem:>12.2}");
+        match previous.as_ref().and_then(|p| p.iter().find(|(b, _)| b == name)) {
+            Some(&(_, base)) => {
+                let change = per_elem / base - 1.0;
+                let flag = if change > INSTRUCTION_TOLERANCE { " REGRESSED" } else { "" };
+                println!(" {base:>12.2} {:>+7.1}%{flag}", 100.0 * change);
+                regressed |= change > INSTRUCTION_TOLERANCE;
+            }
+            None => println!(" {:>12} {:>8}", "-", "-"),
+        }
+    }
+
+    match (baseline, previous) {
+        (Some(path), None) => {
+            std::fs::write(path, table).map_err(|_| "Could not write the baseline")?;
+            println!("Wrote baseline {path}");
+        }
+        (Some(_), Some(_)) if regressed => return Err("Instruction counts regressed"),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Minimum time each kernel is looped under the energy meter, long
+/// enough for RAPL's ~1 ms updates and several `powermetrics` samples
+const ENERGY_TIME: Duration = Duration::from_millis(500);
+
+fn main() -> Result<(), &'static str> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let n: usize = args.first().map_or(1 << 18, |s| s.parse().unwrap());
+    let reps: usize = args.get(1).map_or(10, |s| s.parse().unwrap());
+    let mode = args.get(2).map_or("best", |s| s.as_str());
+    match mode {
+        "alloc" => return allocation(n, reps),
+        "instructions" => return instructions(n, args.get(3).map(String::as_str)),
+        INSTRUCTIONS_CHILD => {
+            // Only what differs between the two runs of a kernel is counted
+            let k: usize = args[3].parse().unwrap();
+            let len: usize = args[4].parse().unwrap();
+            let tets = inputs::tetrahedra(inputs::Distribution::Random, 0, n);
+            let mut out = vec![0.0; n];
+            return (KERNELS[k].1)(black_box(&tets[..len]), black_box(&mut out[..len]));
+        }
+        _ => {}
+    }
+
+    // Same data on every machine. Kept until the end, since freeing it
+    // would raise glibc's mmap threshold, and the cold outputs would then
+    // come from the heap, already touched.
+    let generated: Vec<Tet> = inputs::tetrahedra(inputs::Distribution::Random, 0, n);
+    let mut tets: Vec<Tet> = untouched(n);
+    match advise_huge_pages(&mut tets) {
+        Ok(()) => println!("Huge pages advised"),
+        Err(e) => println!("Huge pages not advised ({e})"),
+    }
+    tets.copy_from_slice(&generated); // Touches every page
+    if mode == "cold" {
+        return cold(&tets, reps);
+    }
+    let mut out: Vec<f64> = untouched(n);
+    pretouch(&mut out);
+    match mode {
+        "best" => {}
+        "interleaved" => return interleaved(&tets, &mut out, reps),
+        _ => return Err("Unknown mode; expected best, interleaved, cold, alloc or instructions"),
+    }
+
+    let mut counters = match counters::Counters::new() {
+        Ok(c) => Some(c),
+        Err(e) => {
+            println!("Hardware counters unavailable ({e}); timings only");
+            None
+        }
+    };
+
+    let mut meter = match energy::Meter::new() {
+        Ok(m) => Some(m),
+        Err(e) => {
+            println!("Energy meter unavailable ({e})");
+            None
+        }
+    };
+
+    println!("n = {n}, best of {reps}, dispatch path {}, {} allocator", dispatch::path().name(), alloc::ALLOCATOR);
+    print!("{:<14} {:>10} {:>8}", "kernel", "ns/elem", "Melem/s");
+    if meter.is_some() {
+        print!(" {:>8} {:>7}", "nJ/elem", "W");
+    }
+    println!(" {:>6} {:>12} {:>10} {:>8}", "IPC", "LLC miss/el", "FLOP/elem", "FMA use");
+    for (name, kernel) in KERNELS {
+        // Warm-up, best-of wall time, then one counted run
+        for _ in 0..WARMUP {
+            kernel(black_box(&tets), black_box(&mut out))?;
+        }
+        let mut best = Duration::MAX;
+        for _ in 0..reps {
+            let start = Instant::now();
+            kernel(black_box(&tets), black_box(&mut out))?;
+            best = best.min(start.elapsed());
+        }
+        let per_elem = best.as_secs_f64() / n as f64;
+        print!("{name:<14} {:>10.2} {:>8.1}", per_elem * 1e9, 1e-6 / per_elem);
+
+        // Looped until the meter has enough to resolve
+        if let Some(meter) = &mut meter {
+            let (start, mut passes) = (Instant::now(), 0);
+            let joules = meter.measure(|| {
+                while passes == 0 || start.elapsed() < ENERGY_TIME {
+                    kernel(black_box(&tets), black_box(&mut out)).unwrap();
+                    passes += 1;
+                }
+            });
+            match joules {
+                Ok(j) => print!(" {:>8.2} {:>7.1}", 1e9 * j / (passes * n) as f64, j / start.elapsed().as_secs_f64()),
+                Err(_) => print!(" {:>8} {:>7}", "-", "-"),
+            }
+        }
+
+        let counts = counters.as_mut().and_then(|c| c.measure(|| kernel(black_box(&tets), black_box(&mut out)).unwrap()).ok());
+        match counts {
+            Some(c) if c.cycles > 0 => {
+                let ipc = c.instructions as f64 / c.cycles as f64;
+                let misses = c.cache_misses as f64 / n as f64;
+                print!(" {ipc:>6.2} {misses:>12.4}");
+                if let Some(flops) = c.flops {
+                    let fma_use = flops as f64 / c.cycles as f64 / 4.0;
+                    print!(" {:>10.1} {:>7.1}%", flops as f64 / n as f64, 100.0 * fma_use);
+                }
+                println!();
+            }
+            _ => println!(),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Bounding boxes and spheres: the exact minimum sphere against brute
+//! force on small sets, and Ritter's approximation against it on large
+//! ones.
+//!
+//! ```text
+//! rust-script bounds_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use bounds::{Aabb, Sphere};
+use inputs::Pcg64;
+use std::time::Instant;
+
+/// Smallest sphere through 2 to 4 of the points that contains them all,
+/// trying every subset
+fn brute_force_sphere(points: &[[f64; 3]]) -> f64 {
+    let n = points.len();
+    let mut best = f64::INFINITY;
+    let mut consider = |subset: &[[f64; 3]]| {
+        let s = bounds::welzl_sphere(subset);
+        if points.iter().all(|&p| s.contains(p)) {
+            best = best.min(s.radius);
+        }
+    };
+    for i in 0..n {
+        for j in i + 1..n {
+            consider(&[points[i], points[j]]);
+            for k in j + 1..n {
+                consider(&[points[i], points[j], points[k]]);
+                for l in k + 1..n {
+                    consider(&[points[i], points[j], points[k], points[l]]);
+                }
+            }
+        }
+    }
+    best
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 22, |s| s.parse().unwrap());
+    let mut rng = Pcg64::new(152, 0);
+
+    // Per element: a regular tetrahedron's sphere is its circumsphere, and
+    // an obtuse triangle's sits on its longest edge
+    let s = 8.0_f64.sqrt() / 3.0;
+    let regular = [[0.0, 0.0, 1.0], [s, 0.0, -1.0 / 3.0], [-s / 2.0, s * 0.75_f64.sqrt(), -1.0 / 3.0], [-s / 2.0, -s * 0.75_f64.sqrt(), -1.0 / 3.0]];
+    let mut spheres = [Sphere::EMPTY];
+    bounds::spheres(&[regular], &mut spheres)?;
+    println!("regular tetrahedron: center {:?}, radius {}", spheres[0].center, spheres[0].radius);
+    assert!(vec3::norm(spheres[0].center) < 1e-15 && (spheres[0].radius - 1.0).abs() < 1e-15);
+    bounds::spheres(&[[[0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [1.0, 1.0, 0.0]]], &mut spheres)?;
+    assert_eq!(spheres[0], Sphere { center: [2.0, 0.0, 0.0], radius: 2.0 });
+    let mut boxes = [Aabb::EMPTY];
+    bounds::aabbs(&[regular], &mut boxes)?;
+    assert_eq!(boxes[0].hi[2], 1.0);
+
+    // Exact spheres against brute force, including points on a grid with
+    // many cospherical subsets
+    for trial in 0..200 {
+        let points: Vec<[f64; 3]> = if trial % 4 == 0 {
+            (0..10).map(|_| [0, 1, 2].map(|_| rng.below(3) as f64)).collect()
+        } else {
+            (0..3 + trial % 10).map(|_| rng.point()).collect()
+        };
+        let s = bounds::welzl_sphere(&points);
+        assert!(points.iter().all(|&p| s.contains(p)));
+        let best = brute_force_sphere(&points);
+        assert!((s.radius - best).abs() <= 1e-12 * best.max(1.0), "trial {trial}: {} vs {best}", s.radius);
+    }
+
+    // Large sets: points in a ball, plus a few far outliers
+    let mut points: Vec<[f64; 3]> = (0..n).map(|_| rng.point()).filter(|p| vec3::norm(*p) <= 1.0).collect();
+    points.extend([[3.0, 0.0, 0.0], [0.0, -2.5, 1.0]]);
+
+    let start = Instant::now();
+    let b = bounds::aabb(&points);
+    println!("{} points: aabb {:?} in {:.2?}", points.len(), b, start.elapsed());
+    assert_eq!(b, Aabb::from_points(&points));
+
+    let start = Instant::now();
+    let ritter = bounds::ritter_sphere(&points);
+    let ritter_time = start.elapsed();
+    let start = Instant::now();
+    let welzl = bounds::welzl_sphere(&points);
+    let welzl_time = start.elapsed();
+    println!("ritter radius {:.6} in {ritter_time:.2?}", ritter.radius);
+    println!("welzl radius {:.6} in {welzl_time:.2?}", welzl.radius);
+    assert!(points.iter().all(|&p| ritter.contains(p) && welzl.contains(p)));
+    assert!(ritter.radius >= welzl.radius * (1.0 - 1e-12));
+
+    Ok(())
+}
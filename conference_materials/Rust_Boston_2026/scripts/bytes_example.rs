@@ -0,0 +1,58 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! bytemuck = { version = "1", optional = true }
+//! memmap2 = "0.9"
+//!
+//! [features]
+//! default = ["bytemuck"]
+//! bytemuck = ["dep:bytemuck"]
+//! ```
+//!
+//! Solid angles straight out of a memory-mapped raw dump of tetrahedra:
+//!
+//! ```text
+//! rust-script bytes_example.rs tets.bin
+//! ```
+//!
+//! With no arguments, writes and maps a small dump in the temp directory.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "bytemuck"))]
+compile_error!("bytes_example.rs needs the bytemuck feature");
+
+#[path = "solid_angle/bytes.rs"]
+mod bytes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::fs::File;
+
+fn main() -> std::io::Result<()> {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path.into(),
+        None => {
+            // Octant of the unit sphere, in two orientations
+            let origin = [0.0; 3];
+            let (x, y, z) = ([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]);
+            let tets = [[origin, x, y, z], [origin, y, x, z]];
+            let path = std::env::temp_dir().join("bytes_example_tets.bin");
+            std::fs::write(&path, bytes::tetrahedra_as_bytes(&tets))?;
+            path
+        }
+    };
+
+    // SAFETY: The file is not modified by this or any other process while mapped
+    let map = unsafe { memmap2::Mmap::map(&File::open(&path)?)? };
+    let tets = bytes::tetrahedra_from_bytes(&map).map_err(std::io::Error::other)?; // Page-aligned, no copy
+
+    let mut out = vec![0.0; tets.len()];
+    tetrahedron::solid_angle_tetrahedron(tets, &mut out).unwrap();
+    println!("Solid angles (sr) from {}: {out:?}", path.display());
+    Ok(())
+}
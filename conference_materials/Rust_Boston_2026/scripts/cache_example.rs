@@ -0,0 +1,134 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A view factor matrix through the result cache: computed once, then
+//! read back; recomputed when a vertex moves by one ulp or the entry is
+//! damaged; and small entries evicted least recently used first.
+//!
+//! ```text
+//! rust-script cache_example.rs [sphere sectors]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/cache.rs"]
+mod cache;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use cache::{Cache, Fingerprint};
+use npy::NpyArray;
+use std::cell::Cell;
+use std::f64::consts::{PI, TAU};
+use std::time::{Duration, Instant};
+
+/// UV sphere with `n` sectors and `n / 2` stacks, wound inward
+fn sphere(n: u32) -> mesh::TriMesh {
+    let stacks = n / 2;
+    let mut vertices = vec![[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+    for i in 1..stacks {
+        let (st, ct) = (PI * i as f64 / stacks as f64).sin_cos();
+        vertices.extend((0..n).map(|j| {
+            let (sp, cp) = (TAU * j as f64 / n as f64).sin_cos();
+            [st * cp, st * sp, ct]
+        }));
+    }
+    let id = |i: u32, j: u32| 2 + (i - 1) * n + j % n;
+    let mut faces = Vec::new();
+    for j in 0..n {
+        faces.push([0, id(1, j + 1), id(1, j)]);
+        faces.push([1, id(stacks - 1, j), id(stacks - 1, j + 1)]);
+        for i in 1..stacks - 1 {
+            faces.push([id(i, j), id(i, j + 1), id(i + 1, j + 1)]);
+            faces.push([id(i, j), id(i + 1, j + 1), id(i + 1, j)]);
+        }
+    }
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let sectors: u32 = std::env::args().nth(1).map_or(Ok(32), |s| s.parse())?;
+    let dir = std::env::temp_dir().join(format!("solid_angle_cache_{}", std::process::id()));
+    let cache = Cache::open(&dir, 1 << 30)?;
+
+    let computed = Cell::new(0);
+    let view_factors = |mesh: &mesh::TriMesh| -> Result<NpyArray, Box<dyn std::error::Error>> {
+        let key = Fingerprint::new("view_factors").mesh(mesh).finish();
+        cache.get_or_compute(key, || {
+            computed.set(computed.get() + 1);
+            let n = mesh.faces().len();
+            let mut f = vec![0.0; n * n];
+            thermal::view_factors(mesh, &mut f)?;
+            Ok(NpyArray { shape: vec![n, n], data: f })
+        })
+    };
+
+    // Once computed, then read back
+    let mut enclosure = sphere(sectors);
+    let start = Instant::now();
+    let first = view_factors(&enclosure)?;
+    let compute = start.elapsed();
+    let start = Instant::now();
+    let again = view_factors(&enclosure)?;
+    let read = start.elapsed();
+    assert!(again == first && computed.get() == 1, "cache miss on unchanged geometry");
+    println!("{} faces: computed in {compute:.1?}, read back in {read:.1?} ({} MB on disk)", enclosure.faces().len(), cache.size()? >> 20);
+
+    // One ulp on one vertex is new geometry
+    let mut vertices = enclosure.vertices().to_vec();
+    vertices[5][0] = vertices[5][0].next_up();
+    enclosure = mesh::TriMesh::new(vertices, enclosure.faces().to_vec())?;
+    let moved = view_factors(&enclosure)?;
+    assert!(computed.get() == 2 && moved != first, "moved vertex not recomputed");
+
+    // So is a parameter, and a different computation over the same mesh
+    let keys = [
+        Fingerprint::new("view_factors").mesh(&enclosure).finish(),
+        Fingerprint::new("view_factors").mesh(&enclosure).f64(0.0).finish(),
+        Fingerprint::new("view_factors").mesh(&enclosure).f64(-0.0).finish(),
+        Fingerprint::new("irradiance").mesh(&enclosure).finish(),
+    ];
+    assert!((0..4).all(|i| (i + 1..4).all(|j| keys[i] != keys[j])), "keys collide");
+
+    // A damaged entry is a miss, then replaced
+    let path = dir.join(format!("{:032x}.npy", keys[0].0));
+    std::fs::write(&path, &std::fs::read(&path)?[..1000])?;
+    assert!(cache.get(keys[0])?.is_none() && !path.exists());
+    assert!(view_factors(&enclosure)? == moved && computed.get() == 3);
+
+    // Room for two of three small entries: using the first keeps it over
+    // the second
+    cache.clear()?;
+    assert!(cache.size()? == 0);
+    let data = vec![1.0; 1000];
+    let key = |i: u64| Fingerprint::new("small").u64(i).finish();
+    cache.put(key(0), &[1000], &data)?;
+    let small = Cache::open(&dir, 2 * cache.size()?)?;
+    std::thread::sleep(Duration::from_millis(20));
+    small.put(key(1), &[1000], &data)?;
+    assert!(small.get(key(0))?.is_some());
+    std::thread::sleep(Duration::from_millis(20));
+    small.put(key(2), &[1000], &data)?;
+    let kept = [0, 1, 2].map(|i| dir.join(format!("{:032x}.npy", key(i).0)).exists());
+    assert!(kept == [true, false, true], "evicted the wrong entry: {kept:?}");
+    assert!(small.remove(key(0))? && !small.remove(key(0))?);
+    println!("LRU eviction and removal: ok");
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Stopping a long batch from outside: a token cancelled before the call,
+//! one cancelled by the kernel itself part way through, and one
+//! cancelled from another thread while a slow batch runs, with how long
+//! the driver took to notice. Uncancelled, the cancellable drivers give
+//! the same results as the others.
+//!
+//! ```text
+//! rust-script cancel_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/batch.rs"]
+mod batch;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use batch::BatchKernel;
+use par::{CancellationToken, CANCELLED};
+use std::time::{Duration, Instant};
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 200, n);
+
+    // Never cancelled: the same as the drivers without a token
+    let token = CancellationToken::new();
+    let (mut plain, mut ours) = (vec![0.0; n], vec![0.0; n]);
+    par::solid_angle_tetrahedra_par(&tets, &mut plain)?;
+    par::solid_angle_tetrahedra_par_cancellable(&tets, &mut ours, &token)?;
+    assert_eq!(plain, ours);
+    dispatch::solid_angle_tetrahedra_dispatch_par_cancellable(&tets, &mut ours, &token)?;
+    assert_eq!(plain, ours);
+    batch::Tetrahedra.eval_par_cancellable(&tets, &mut ours, &token)?;
+    assert_eq!(plain, ours);
+    assert_eq!(par::solid_angle_tetrahedra_par_cancellable(&tets, &mut ours[1..], &token), Err("Dimension mismatch"));
+
+    // Cancelled before the call: nothing written, above the threshold or
+    // below
+    token.cancel();
+    for len in [n, 100] {
+        let mut out = vec![-1.0; len];
+        assert_eq!(par::solid_angle_tetrahedra_par_cancellable(&tets[..len], &mut out, &token), Err(CANCELLED));
+        assert!(out.iter().all(|&x| x == -1.0));
+    }
+
+    // Cancelled by the kernel on reaching element `stop`: every thread
+    // stops at its next chunk, so little past it is written, serially or
+    // in parallel
+    let threads = rayon::current_num_threads();
+    for (threshold, how) in [(0, "parallel"), (usize::MAX, "serial")] {
+        par::set_par_threshold(threshold);
+        let token = CancellationToken::new();
+        let stop = n / 8;
+        let kernel = batch::from_fn(|&i: &usize| {
+            if i == stop {
+                token.cancel();
+            }
+            1.0
+        });
+        let indices: Vec<usize> = (0..n).collect();
+        let mut out = vec![0.0; n];
+        assert_eq!(kernel.eval_par_cancellable(&indices, &mut out, &token), Err(CANCELLED));
+        let written = out.iter().filter(|&&x| x == 1.0).count();
+        println!("{how}: {written} of {n} written, cancelled at {stop}");
+        assert!(out[stop] == 1.0 && written <= stop + 2 * (threads + 1) * par::max_chunk(), "{written} written");
+    }
+    par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+
+    // From another thread, as a UI would, while a slow batch runs
+    let slow = batch::from_fn(|&x: &f64| (0..2000).fold(x, |acc, k| (acc + k as f64).sqrt()));
+    let inputs: Vec<f64> = (0..n).map(|i| i as f64).collect();
+    let mut out = vec![0.0; n];
+    let token = CancellationToken::new();
+    let (result, latency) = std::thread::scope(|s| {
+        let canceller = s.spawn(|| {
+            std::thread::sleep(Duration::from_millis(50));
+            token.cancel();
+            Instant::now()
+        });
+        let result = slow.eval_par_cancellable(&inputs, &mut out, &token);
+        let returned = Instant::now();
+        (result, returned.saturating_duration_since(canceller.join().unwrap()))
+    });
+    assert_eq!(result, Err(CANCELLED));
+    let done = out.iter().filter(|&&x| x != 0.0).count();
+    println!("cancelled from another thread after {done} of {n} elements, returned {latency:.2?} after cancel()");
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Clipping a sphere to a half-space and to a box, checking that the cuts
+//! are watertight, that every open edge lies on a cutting plane, and that
+//! an octant of the sphere subtends an eighth of the full solid angle.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/clip.rs"]
+mod clip;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/predicates.rs"]
+mod predicates;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use clip::{clip_by_aabb, clip_by_plane, Plane};
+use inputs::Pcg64;
+use mesh::TriMesh;
+use std::f64::consts::PI;
+
+/// Unit UV sphere, outward-wound, with `4 n` longitudes and `2 n`
+/// latitude bands so the coordinate planes run along its edges
+fn sphere(n: u32) -> TriMesh {
+    let (lon, lat) = (4 * n, 2 * n);
+    let mut vertices = vec![[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+    for i in 1..lat {
+        let theta = PI * f64::from(i) / f64::from(lat);
+        for j in 0..lon {
+            let phi = 2.0 * PI * f64::from(j) / f64::from(lon);
+            vertices.push([theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()]);
+        }
+    }
+    // Exact zeros on the coordinate planes, as a mesher would write them
+    vertices.iter_mut().flatten().for_each(|x| *x = if x.abs() < 1e-15 { 0.0 } else { *x });
+
+    let ring = |i: u32, j: u32| 2 + (i - 1) * lon + j % lon;
+    let mut faces = Vec::new();
+    for j in 0..lon {
+        faces.push([0, ring(1, j), ring(1, j + 1)]);
+        faces.push([1, ring(lat - 1, j + 1), ring(lat - 1, j)]);
+        for i in 1..lat - 1 {
+            faces.push([ring(i, j), ring(i + 1, j), ring(i + 1, j + 1)]);
+            faces.push([ring(i, j), ring(i + 1, j + 1), ring(i, j + 1)]);
+        }
+    }
+    TriMesh::new(vertices, faces).unwrap()
+}
+
+/// Check that `mesh` is a manifold whose open edges all lie on a plane
+/// of `planes`, returning the number of open edges
+fn check_cut(mesh: &TriMesh, planes: &[Plane]) -> usize {
+    let a = mesh.build_adjacency();
+    assert_eq!(a.non_manifold_edges().count(), 0);
+    let on_plane = |x: [f64; 3], p: &Plane| {
+        let d: f64 = (0..3).map(|k| p.normal[k] * (x[k] - p.point[k])).sum();
+        d.abs() < 1e-12
+    };
+    for e in a.boundary_edges() {
+        let [u, v] = a.edges[e as usize].map(|i| mesh.vertices()[i as usize]);
+        assert!(planes.iter().any(|p| on_plane(u, p) && on_plane(v, p)), "open edge off the cut");
+    }
+    a.boundary_edges().count()
+}
+
+fn main() -> Result<(), &'static str> {
+    // Exact sides, where rounding would cancel the 1 and put it on the plane
+    let side = predicates::plane_side([0.0; 3], [1.0; 3], [1e16, 1.0, -1e16]);
+    assert_eq!(side, std::cmp::Ordering::Greater);
+
+    let mut ball = sphere(16);
+    let ids = (0..ball.faces().len()).map(|f| f as f64).collect();
+    ball.face_attributes_mut().insert("id", ids)?;
+    let z = ball.vertices().iter().map(|v| v[2]).collect();
+    ball.vertex_attributes_mut().insert("z", z)?;
+    assert!(ball.build_adjacency().is_closed_manifold());
+
+    // Random planes through the ball: one loop of open edges per cut, and
+    // interpolated attributes that match the geometry
+    let mut rng = Pcg64::new(151, 0);
+    for _ in 0..100 {
+        let plane = Plane::new(rng.point().map(|x| 0.5 * x), rng.point());
+        let half = clip_by_plane(&ball, plane);
+        let open = check_cut(&half, &[plane]);
+        assert!(open > 0);
+        for (v, &z) in half.vertices().iter().zip(half.vertex_attributes().get("z").unwrap()) {
+            assert!((v[2] - z).abs() < 1e-15);
+        }
+        let ids = half.face_attributes().get("id").unwrap();
+        assert!(ids.iter().all(|&f| f >= 0.0 && (f as usize) < ball.faces().len()));
+    }
+
+    // A cut along existing edges leaves nothing to re-triangulate
+    let equator = Plane::new([0.0; 3], [0.0, 0.0, 1.0]);
+    let upper = clip_by_plane(&ball, equator);
+    let open = check_cut(&upper, &[equator]);
+    println!("upper hemisphere: {} of {} faces, {open} open edges", upper.faces().len(), ball.faces().len());
+    assert_eq!(upper.faces().len(), ball.faces().len() / 2);
+
+    // Octant: an eighth of the sphere, seen from its center
+    let octant = clip_by_aabb(&ball, [0.0; 3], [2.0; 3]);
+    let mut omega = [0.0];
+    multi_origin::solid_angles_multi_origin(&octant, &[[0.0; 3]], &mut omega)?;
+    println!("octant: {} faces, {:.15} × 4π", octant.faces().len(), omega[0] / (4.0 * PI));
+    assert!((omega[0] - PI / 2.0).abs() < 1e-12);
+
+    // An off-axis box, cutting through faces on all six sides
+    let (lo, hi) = ([-0.7, -0.6, -0.8], [0.65, 0.75, 0.9]);
+    let boxed = clip_by_aabb(&ball, lo, hi);
+    let planes: Vec<Plane> = (0..3)
+        .flat_map(|k| {
+            let mut n = [0.0; 3];
+            n[k] = 1.0;
+            [Plane::new(lo, n), Plane::new(hi, n)]
+        })
+        .collect();
+    let open = check_cut(&boxed, &planes);
+    println!("boxed: {} faces, {open} open edges", boxed.faces().len());
+    for v in boxed.vertices() {
+        assert!((0..3).all(|k| v[k] >= lo[k] - 1e-15 && v[k] <= hi[k] + 1e-15));
+    }
+
+    Ok(())
+}
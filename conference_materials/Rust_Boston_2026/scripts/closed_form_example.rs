@@ -0,0 +1,99 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! soft-float = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Shapes with solid angles in closed form, generated at compile time:
+//! the regular polyhedra and boxes seen from their centres, each face's
+//! share of the sphere from the kernel against its table, to within its
+//! conditioning, and each total `4π`.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use closed_form::SpecialCase;
+use std::f64::consts::PI;
+
+closed_form::special_cases! {
+    TETRAHEDRON = regular_tetrahedron();
+    OCTAHEDRON = octahedron();
+    CUBE = cube();
+    ICOSAHEDRON = icosahedron();
+    DODECAHEDRON = dodecahedron();
+    /// The cube again, by way of its closed form
+    UNIT_BOX = box_faces([1.0; 3]);
+    SLAB = box_faces([4.0, 3.0, 0.25]);
+    ROD = box_faces([0.1, 0.2, 5.0]);
+}
+
+// Exact where rational, and usable as constants
+const _: () = assert!(CUBE.fractions[0] == 1.0 / 12.0 && DODECAHEDRON.fractions[59] == 1.0 / 60.0);
+const _: () = assert!(ROD.fractions[0] > 0.1 && ROD.fractions[8] < 1e-3); // Nearly all sides
+const ICOSAHEDRON_FACE: f64 = ICOSAHEDRON.solid_angles()[0];
+
+/// Error the kernel may make on a face, in units of `κ ε |Ω|`
+const TOLERANCE: f64 = 4.0;
+
+/// Worst error in the kernel's face solid angles against the table, in
+/// units of `κ ε |Ω|`, and in their total, relative to `4π`
+fn check<const V: usize, const F: usize>(name: &str, case: &SpecialCase<V, F>) -> Result<(f64, f64), &'static str> {
+    let mut faces = [0.0; F];
+    case.mesh.face_solid_angles(case.origin, &mut faces)?;
+    let (mut worst, mut bound) = (0.0_f64, 0.0);
+    for ((tri, got), expected) in case.mesh.triangles().zip(faces).zip(case.solid_angles()) {
+        let (_, cond) = condition::solid_angle_tetrahedron_scalar_cond(case.origin, tri[0], tri[1], tri[2]);
+        let scale = f64::EPSILON * cond.max(1.0) * expected.abs();
+        worst = worst.max((got - expected).abs() / scale);
+        bound += TOLERANCE * scale;
+    }
+    let total = (case.mesh.solid_angle(case.origin) - 4.0 * PI).abs();
+    let shares: f64 = case.fractions.iter().sum();
+    assert!(worst <= TOLERANCE && total <= bound, "{name}: {worst:.2} κε|Ω| per face, {total:e} in total");
+    assert!((shares - 1.0).abs() < F as f64 * f64::EPSILON, "{name}: shares sum to {shares}");
+    Ok((worst, total / (4.0 * PI)))
+}
+
+fn main() -> Result<(), &'static str> {
+    println!("{:>12} {:>6} {:>20} {:>10} {:>10}", "", "faces", "fraction of 4π", "face κε|Ω|", "total err");
+    macro_rules! row {
+        ($($case:ident),*) => {$({
+            let (worst, total) = check(stringify!($case), &$case)?;
+            let (lo, hi) = $case.fractions.iter().fold((1.0_f64, 0.0_f64), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+            let fraction = if lo == hi { format!("{lo:.15}") } else { format!("{lo:.6}..{hi:.6}") };
+            println!("{:>12} {:>6} {fraction:>20} {worst:>10.2} {total:>10.1e}", stringify!($case).to_lowercase(), $case.fractions.len());
+        })*};
+    }
+    row!(TETRAHEDRON, OCTAHEDRON, CUBE, ICOSAHEDRON, DODECAHEDRON, UNIT_BOX, SLAB, ROD);
+
+    // The box's closed form agrees with the cube's symmetry
+    assert!(UNIT_BOX.fractions.iter().all(|&x| (x - 1.0 / 12.0).abs() < 1e-16));
+    println!("icosahedron face: {ICOSAHEDRON_FACE:.15} sr, a const");
+    Ok(())
+}
@@ -0,0 +1,155 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A cube with a debris shell inside it doubles the winding number there
+//! until the shell is split off. Then labels of many shuffled cubes are
+//! checked against a serial union-find:
+//!
+//! ```text
+//! rust-script components_example.rs [cubes]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/components.rs"]
+mod components;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use components::{components, split_components};
+use inputs::Pcg64;
+use mesh::TriMesh;
+use std::f64::consts::PI;
+use std::time::Instant;
+
+/// Outward-wound cube of side `size` at `center`, as vertices and faces
+/// numbered from `first`
+fn cube(center: [f64; 3], size: f64, first: u32) -> (Vec<[f64; 3]>, Vec<[u32; 3]>) {
+    let vertices = (0..8)
+        .map(|i| [0, 1, 2].map(|k| center[k] + size * (((i >> k) & 1) as f64 - 0.5)))
+        .collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]].map(|f| f.map(|v| v + first))).collect();
+    (vertices, faces)
+}
+
+/// Width of a mesh along x, enough to tell the shells apart
+fn width(mesh: &TriMesh) -> f64 {
+    let x = mesh.vertices().iter().map(|v| v[0]);
+    x.clone().fold(f64::NEG_INFINITY, f64::max) - x.fold(f64::INFINITY, f64::min)
+}
+
+/// Serial union-find labels, numbered the same way as [components]
+fn components_serial(mesh: &TriMesh) -> Vec<u32> {
+    fn find(parent: &mut [u32], mut x: u32) -> u32 {
+        while parent[x as usize] != x {
+            parent[x as usize] = parent[parent[x as usize] as usize];
+            x = parent[x as usize];
+        }
+        x
+    }
+    let mut parent: Vec<u32> = (0..mesh.vertices().len() as u32).collect();
+    for &[a, b, c] in mesh.faces() {
+        for v in [b, c] {
+            let (ra, rv) = (find(&mut parent, a), find(&mut parent, v));
+            parent[ra.max(rv) as usize] = ra.min(rv);
+        }
+    }
+    let roots: Vec<u32> = mesh.faces().iter().map(|f| find(&mut parent, f[0])).collect();
+    let mut sorted = roots.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    roots.iter().map(|r| sorted.binary_search(r).unwrap() as u32).collect()
+}
+
+fn main() -> Result<(), &'static str> {
+    let cubes: u32 = std::env::args().nth(1).map_or(100_000, |s| s.parse().unwrap());
+
+    // Unit cube with a small shell inside and another far away
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    for (center, size) in [([0.0; 3], 1.0), ([0.2, 0.2, 0.2], 0.1), ([5.0, 0.0, 0.0], 0.1)] {
+        let (v, f) = cube(center, size, vertices.len() as u32);
+        vertices.extend(v);
+        faces.extend(f);
+    }
+    let mut scan = TriMesh::new(vertices, faces)?;
+    let ids = (0..scan.faces().len()).map(|f| f as f64).collect();
+    scan.face_attributes_mut().insert("id", ids)?;
+
+    let origin = [[0.2, 0.2, 0.2]];
+    let mut omega = [0.0];
+    multi_origin::solid_angles_multi_origin(&scan, &origin, &mut omega)?;
+    println!("with debris: {:.12} × 4π", omega[0] / (4.0 * PI));
+    assert!((omega[0] - 8.0 * PI).abs() < 1e-12);
+
+    let parts = split_components(&scan)?;
+    println!("{} components of {:?} faces", parts.len(), parts.iter().map(|p| p.faces().len()).collect::<Vec<_>>());
+    assert_eq!(parts.len(), 3);
+    let largest = parts.iter().max_by(|a, b| width(a).total_cmp(&width(b))).unwrap();
+    assert_eq!(largest.face_attributes().get("id").unwrap()[..], (0..12).map(f64::from).collect::<Vec<_>>()[..]);
+    multi_origin::solid_angles_multi_origin(largest, &origin, &mut omega)?;
+    println!("largest alone: {:.12} × 4π", omega[0] / (4.0 * PI));
+    assert!((omega[0] - 4.0 * PI).abs() < 1e-12);
+
+    // Many cubes, with vertices and faces shuffled so components interleave
+    let mut rng = Pcg64::new(150, 0);
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    for i in 0..cubes {
+        let (v, f) = cube([i as f64, 0.0, 0.0], 0.5, vertices.len() as u32);
+        vertices.extend(v);
+        faces.extend(f);
+    }
+    let mut order: Vec<u32> = (0..vertices.len() as u32).collect();
+    for i in (1..order.len()).rev() {
+        order.swap(i, rng.below(i + 1));
+    }
+    for i in (1..faces.len()).rev() {
+        faces.swap(i, rng.below(i + 1));
+    }
+    let faces = faces.iter().map(|f| f.map(|v| order[v as usize])).collect();
+    let mesh = TriMesh::new(vertices, faces)?;
+
+    let start = Instant::now();
+    let labels = components(&mesh);
+    let parallel = start.elapsed();
+    let start = Instant::now();
+    let expected = components_serial(&mesh);
+    let serial = start.elapsed();
+    println!(
+        "{cubes} shuffled cubes: {} components in {parallel:.2?} on {} threads, serial {serial:.2?}",
+        labels.iter().max().map_or(0, |&c| c + 1),
+        rayon::current_num_threads()
+    );
+    assert_eq!(labels, expected);
+    assert_eq!(labels.iter().max().map_or(0, |&c| c + 1), cubes);
+
+    Ok(())
+}
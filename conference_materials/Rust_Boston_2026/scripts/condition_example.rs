@@ -0,0 +1,55 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Flagging unreliable elements by condition estimate, and checking the
+//! estimate against the double-double result.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+/// Condition estimate above which an element is re-evaluated in double-double
+const COND_LIMIT: f64 = 1e6;
+
+fn main() -> Result<(), &'static str> {
+    let origin = [0.1, -0.2, 0.3];
+    let mut tets = vec![
+        // Octant
+        [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        // Generic
+        [origin, [1.3, 0.2, -0.4], [-0.7, 1.1, 0.5], [0.2, -0.9, 1.7]],
+        // Flat triangle seen from within its own plane, nearly behind: Ω ≈ ±2π
+        [[0.25, 0.25, 1e-13], [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    ];
+    // Slivers seen edge-on, flattening by 10x each
+    let (v1, v2) = ([1.3, 0.2, -0.4], [-0.7, 1.1, 0.5]);
+    for k in 2..=14 {
+        let eps = 10_f64.powi(-k);
+        let v3 = [0, 1, 2].map(|i| origin[i] + 0.6 * (v1[i] - origin[i]) + 0.7 * (v2[i] - origin[i]) + eps);
+        tets.push([origin, v1, v2, v3]);
+    }
+
+    let n = tets.len();
+    let (mut out, mut cond) = (vec![0.0; n], vec![0.0; n]);
+    condition::solid_angle_tetrahedron_cond(&tets, &mut out, &mut cond)?;
+
+    println!("{:>24} {:>10} {:>10}  ", "Ω (sr)", "κ", "rel. err");
+    for ((tet, &omega), &kappa) in tets.iter().zip(&out).zip(&cond) {
+        let [v0, v1, v2, v3] = *tet;
+        let exact = dd::solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3).to_f64();
+        let rel_err = ((omega - exact) / exact).abs();
+
+        // The estimate should bound the observed error, to within a constant
+        assert!(rel_err <= 16.0 * kappa * f64::EPSILON);
+        let flag = if kappa > COND_LIMIT { "re-evaluate" } else { "" };
+        println!("{omega:>+24.16e} {kappa:>10.1e} {rel_err:>10.1e}  {flag}");
+    }
+    Ok(())
+}
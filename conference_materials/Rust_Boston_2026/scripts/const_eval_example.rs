@@ -0,0 +1,173 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! soft-float = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Solid angles baked at compile time: a detector's pixels as seen from a
+//! fixed source, in a `static` built by the `const` kernel, matching the
+//! runtime kernel bit for bit and summing to the panel's closed form. Then
+//! the `const` kernel and its `fma`, `sqrt` and `atan2` against their
+//! runtime counterparts, evaluated at run time on every input distribution
+//! and on random bit patterns.
+//!
+//! ```text
+//! rust-script const_eval_example.rs [n]
+//! cd $(rust-script -p const_eval_example.rs | tail -1) && cargo run --release --features soft-float
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use const_eval::{solid_angle_tetrahedron_scalar_const, solid_angles_const};
+use inputs::Distribution;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use tetrahedron::solid_angle_tetrahedron_scalar;
+
+/// Pixels per side of the detector
+const PIXELS: usize = 8;
+/// Pixel pitch (m)
+const PITCH: f64 = 0.5e-3;
+/// Source on the detector's axis, this far from its face (m)
+const SOURCE: [f64; 3] = [0.0, 0.0, -3.0e-3];
+
+/// Two triangles per pixel, each with the source as the apex, wound to
+/// face the source
+const fn detector() -> [[[f64; 3]; 4]; 2 * PIXELS * PIXELS] {
+    let mut tets = [[[0.0; 3]; 4]; 2 * PIXELS * PIXELS];
+    let half = PITCH * PIXELS as f64 / 2.0;
+    let mut i = 0;
+    while i < PIXELS * PIXELS {
+        let x0 = (i % PIXELS) as f64 * PITCH - half;
+        let y0 = (i / PIXELS) as f64 * PITCH - half;
+        let (x1, y1) = (x0 + PITCH, y0 + PITCH);
+        tets[2 * i] = [SOURCE, [x0, y0, 0.0], [x1, y0, 0.0], [x1, y1, 0.0]];
+        tets[2 * i + 1] = [SOURCE, [x0, y0, 0.0], [x1, y1, 0.0], [x0, y1, 0.0]];
+        i += 1;
+    }
+    tets
+}
+
+const DETECTOR: [[[f64; 3]; 4]; 2 * PIXELS * PIXELS] = detector();
+
+/// Solid angle of each half-pixel at the source (sr), computed by rustc
+static DETECTOR_SOLID_ANGLES: [f64; 2 * PIXELS * PIXELS] = solid_angles_const(&DETECTOR);
+
+/// Solid angle of the panel's centre pixel, checked at compile time
+const CENTRE: f64 = {
+    let i = 2 * (PIXELS / 2 * PIXELS + PIXELS / 2);
+    DETECTOR_SOLID_ANGLES[i] + DETECTOR_SOLID_ANGLES[i + 1]
+};
+const _: () = assert!(CENTRE > 0.0 && CENTRE < PITCH * PITCH / (SOURCE[2] * SOURCE[2]));
+
+/// Random finite `f64` of any exponent and sign
+fn any_finite(rng: &mut inputs::Pcg64) -> f64 {
+    loop {
+        let x = f64::from_bits(rng.next_u64());
+        if x.is_finite() {
+            return x;
+        }
+    }
+}
+
+/// Random `f64` of magnitude `[2^-lo, 2^hi)`, either sign
+fn any_scale(rng: &mut inputs::Pcg64, lo: f64, hi: f64) -> f64 {
+    let x = rng.uniform(-lo, hi).exp2();
+    if rng.next_u64() & 1 == 0 { x } else { -x }
+}
+
+/// Count of `(f(x), g(x))` that differ bitwise, NaNs being equal
+fn mismatches(pairs: impl Iterator<Item = (f64, f64)>) -> usize {
+    pairs.filter(|(a, b)| a.to_bits() != b.to_bits() && !(a.is_nan() && b.is_nan())).count()
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 18, |s| s.parse().unwrap());
+    println!("multiply-add {}, atan2 from {}", if vec3::FUSED { "fused" } else { "unfused" }, math::backend());
+
+    // The baked table is the runtime kernel's, and sums to the panel's
+    // closed form for a source on its axis
+    for (tet, &baked) in DETECTOR.iter().zip(&DETECTOR_SOLID_ANGLES) {
+        let runtime = solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]);
+        assert_eq!(baked.to_bits(), runtime.to_bits(), "Baked {baked:e} against runtime {runtime:e}");
+    }
+    let half = PITCH * PIXELS as f64 / 2.0;
+    let d2 = SOURCE[2] * SOURCE[2];
+    let closed_form = 4.0 * (half * half / (half * half + d2)).asin();
+    let total: f64 = DETECTOR_SOLID_ANGLES.iter().sum();
+    assert!((total - closed_form).abs() < 1e-14 * closed_form, "{total} against {closed_form}");
+    println!("detector: {} half-pixels baked, {total:.15} sr, closed form {closed_form:.15}, centre pixel {CENTRE:.6e} sr", DETECTOR.len());
+
+    // The building blocks, on random bit patterns and in the ranges the
+    // kernel uses, including products cancelling against the addend; fma
+    // only where its result is normal
+    let mut rng = inputs::Pcg64::new(181, 0);
+    let specials = [0.0, -0.0, 1.0, -1.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN, f64::MIN_POSITIVE, 5e-324, f64::MAX];
+    let fma_cases: Vec<[f64; 3]> = (0..n)
+        .map(|i| match i % 4 {
+            0 => [any_scale(&mut rng, 500.0, 500.0), any_scale(&mut rng, 500.0, 500.0), any_scale(&mut rng, 1000.0, 1000.0)],
+            1 => {
+                let (a, b) = (any_scale(&mut rng, 60.0, 60.0), any_scale(&mut rng, 60.0, 60.0));
+                [a, b, -(a * b) * (1.0 + rng.uniform(-1e-12, 1e-12))]
+            }
+            2 => [rng.uniform(-1.0, 1.0), rng.uniform(-1.0, 1.0), rng.uniform(-1.0, 1.0)],
+            _ => [specials[rng.below(specials.len())], any_scale(&mut rng, 8.0, 8.0), specials[rng.below(specials.len())]],
+        })
+        .collect();
+    let fma_differ = mismatches(fma_cases.iter().map(|&[a, b, c]| (const_eval::fma(a, b, c), a.mul_add(b, c))).filter(|(_, y)| !y.is_subnormal()));
+    let roots: Vec<f64> = (0..n).map(|i| if i % 2 == 0 { any_finite(&mut rng).abs() } else { specials[i / 2 % specials.len()] }).collect();
+    let sqrt_differ = mismatches(roots.iter().map(|&x| (const_eval::sqrt(x), x.sqrt())));
+    let atans: Vec<[f64; 2]> = (0..n)
+        .map(|i| match i % 3 {
+            0 => [any_finite(&mut rng), any_finite(&mut rng)],
+            1 => [any_scale(&mut rng, 40.0, 40.0), any_scale(&mut rng, 40.0, 40.0)],
+            _ => [specials[rng.below(specials.len())], specials[rng.below(specials.len())]],
+        })
+        .collect();
+    let atan2_differ = mismatches(atans.iter().map(|&[y, x]| (const_eval::atan2(y, x), libm::atan2(y, x))));
+    println!("fma {fma_differ}, sqrt {sqrt_differ}, atan2 {atan2_differ} of {n} differ from the runtime functions");
+    assert_eq!(fma_differ + sqrt_differ + atan2_differ, 0);
+
+    // The whole kernel, where it matches: under the libm backend, which
+    // the runtime kernel always uses unfused
+    if math::backend() != "libm" {
+        println!("atan2 from {}: the const kernel matches the libm backend only", math::backend());
+        return Ok(());
+    }
+    println!("{:>7} {:>9} {:>10} {:>14}", "", "differ", "ns/elem", "const ns/elem");
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 181, n);
+        let mut runtime = vec![0.0; n];
+        let start = Instant::now();
+        tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut runtime)?;
+        let elapsed = start.elapsed();
+        let start = Instant::now();
+        let consts: Vec<f64> = black_box(&tets).iter().map(|t| solid_angle_tetrahedron_scalar_const(t[0], t[1], t[2], t[3])).collect();
+        let elapsed_const = start.elapsed();
+
+        let differ = mismatches(consts.iter().copied().zip(runtime.iter().copied()));
+        assert_eq!(differ, 0, "{}: const kernel differs from the runtime kernel", dist.name());
+        let per_elem = |t: Duration| 1e9 * t.max(Duration::from_nanos(1)).as_secs_f64() / n as f64;
+        println!("{:>7} {differ:>9} {:>10.2} {:>14.2}", dist.name(), per_elem(elapsed), per_elem(elapsed_const));
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Solid angles of increasingly flat slivers in `f64` and double-double.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use dd::DoubleDouble;
+
+fn main() {
+    // Sanity check against π
+    let one = DoubleDouble::from_f64(1.0);
+    let pi = DoubleDouble::atan2(one, one) * DoubleDouble::from_f64(4.0);
+    println!("4 atan2(1, 1) - π = {:e}", (pi - DoubleDouble::PI).to_f64());
+
+    // Slivers: the last vertex sits h = 2^-k off the plane of the other three.
+    // Coordinates have 26 significant bits, so every vertex is exact, but the
+    // triple product needs 78 and cancels down to O(h) in f64.
+    let q = |x: u32| x as f64 / (1_u64 << 26) as f64;
+    let a = [q(45_875_243), q(7_209_779), q(8_521_093)];
+    let b = [q(3_817_221), q(51_200_321), q(19_711_917)];
+    let plane = [a[0] + b[0], a[1] + b[1], a[2] + b[2]];
+
+    // For small h the angle is linear in h, so Ω / h should settle to a constant
+    println!("{:>3} {:>24} {:>24}", "k", "f64 Ω/h", "double-double Ω/h");
+    let mut prev = f64::NAN;
+    for k in (10..=50).step_by(5) {
+        let h = (-(k as f64)).exp2();
+        let sliver = [[0.0; 3], a, b, [plane[0], plane[1], plane[2] + h]];
+        let [v0, v1, v2, v3] = sliver;
+        let f64_angle = tetrahedron::solid_angle_tetrahedron_scalar(v0, v1, v2, v3);
+        let dd_angle = dd::solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3).to_f64();
+        println!("{k:>3} {:>24.16e} {:>24.16e}", f64_angle / h, dd_angle / h);
+        if k >= 30 {
+            assert!(((dd_angle / h - prev) / prev).abs() < 1e-8);
+        }
+        prev = dd_angle / h;
+    }
+}
@@ -0,0 +1,96 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Unit vectors from one origin to random points at every scale: how far
+//! from unit length they land, the edge cases, and throughput.
+//!
+//! ```text
+//! rust-script directions_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/directions.rs"]
+mod directions;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use dd::DoubleDouble;
+use std::f64::consts::FRAC_1_SQRT_2;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Best-of-5 time per point of `f` (ns)
+fn ns_per_point(n: usize, mut f: impl FnMut()) -> f64 {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_nanos() as f64
+        })
+        .fold(f64::INFINITY, f64::min)
+        / n as f64
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+
+    // Edge cases: no direction, subnormal, overflowing and infinite offsets
+    let u = directions::unit_vector;
+    assert_eq!(u([1.0, 2.0, 3.0], [1.0, 2.0, 3.0]), [0.0; 3]);
+    assert_eq!(u([5e-324, 0.0, 0.0], [0.0; 3]), [1.0, 0.0, 0.0]);
+    assert_eq!(u([0.0, -1e300, 0.0], [0.0, 1e300, 0.0]), [0.0, -1.0, 0.0]);
+    let diagonal = u([f64::INFINITY, 1.0, f64::NEG_INFINITY], [0.0; 3]);
+    assert!((diagonal[0] - FRAC_1_SQRT_2).abs() <= f64::EPSILON && diagonal[1] == 0.0 && diagonal[2] == -diagonal[0]);
+    assert!(u([f64::NAN, 0.0, 0.0], [0.0; 3]).iter().all(|x| x.is_nan()));
+
+    // Offsets of every magnitude, across the fast path's edges too
+    let mut rng = inputs::Pcg64::new(164, 0);
+    let origin = [0.25, -0.5, 0.125];
+    let points: Vec<[f64; 3]> = (0..n)
+        .map(|_| {
+            let scale = 10f64.powf(rng.uniform(-300.0, 300.0));
+            rng.point().map(|x| x * scale)
+        })
+        .collect();
+    let mut out = vec![[0.0; 3]; n];
+    directions::unit_vectors(&points, origin, &mut out)?;
+    let mut par_out = vec![[0.0; 3]; n];
+    directions::unit_vectors_par(&points, origin, &mut par_out)?;
+    assert!(out == par_out, "parallel driver differs from serial");
+
+    // Distance from unit length in ulps of 1, with the norm in double-double
+    let mut worst = 0.0_f64;
+    for v in &out {
+        let sq = v.iter().fold(DoubleDouble::from_f64(0.0), |acc, &x| acc + DoubleDouble::from_f64(x) * DoubleDouble::from_f64(x));
+        worst = worst.max(((sq.hi - 1.0) + sq.lo).abs() / 2.0 / f64::EPSILON);
+    }
+    println!("{n} points: worst |‖u‖ - 1| = {worst:.2} ulp");
+    assert!(worst <= 2.0, "unit vectors off unit length");
+
+    // Throughput at ordinary scale, where the fast path runs throughout
+    let near: Vec<[f64; 3]> = (0..n).map(|_| rng.point()).collect();
+    let serial = ns_per_point(n, || directions::unit_vectors(black_box(&near), origin, &mut out).unwrap());
+    let parallel = ns_per_point(n, || directions::unit_vectors_par(black_box(&near), origin, &mut out).unwrap());
+    println!("{serial:.2} ns/point serial, {parallel:.2} parallel on {} threads", rayon::current_num_threads());
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! The runtime-dispatched kernel against the portable one: bit-identical
+//! on every input distribution, and how much faster on this CPU.
+//!
+//! ```text
+//! rust-script dispatch_example.rs [n]
+//! ```
+//!
+//! On RISC-V with the V extension, build the generated package with
+//! `--features rvv` to check the experimental vector kernel.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use inputs::Distribution;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+type Kernel = fn(&[[[f64; 3]; 4]], &mut [f64]) -> Result<(), &'static str>;
+
+/// Best-of-10 wall time
+fn best(tets: &[[[f64; 3]; 4]], out: &mut [f64], f: Kernel) -> Duration {
+    (0..10)
+        .map(|_| {
+            let start = Instant::now();
+            f(black_box(tets), out).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 18, |s| s.parse().unwrap());
+    println!("dispatch path: {}", dispatch::path().name());
+
+    // Odd length, so the NEON path's leftover element is covered too
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 141, 1001);
+        let (mut portable, mut dispatched) = (vec![0.0; tets.len()], vec![0.0; tets.len()]);
+        tetrahedron::solid_angle_tetrahedron(&tets, &mut portable)?;
+        dispatch::solid_angle_tetrahedron_dispatch(&tets, &mut dispatched)?;
+        let same = portable.iter().zip(&dispatched).all(|(p, d)| p.to_bits() == d.to_bits());
+        assert!(same, "{} differs from the portable kernel", dist.name());
+    }
+    assert!(dispatch::solid_angle_tetrahedron_dispatch(&[[[0.0; 3]; 4]; 2], &mut [0.0]).is_err());
+    println!("bit-identical to the portable kernel on every distribution");
+
+    let tets = inputs::tetrahedra(Distribution::Random, 0, n);
+    let mut out = vec![0.0; n];
+    let portable = best(&tets, &mut out, tetrahedron::solid_angle_tetrahedron);
+    let dispatched = best(&tets, &mut out, dispatch::solid_angle_tetrahedron_dispatch);
+    let ns = |t: Duration| t.as_nanos() as f64 / n as f64;
+    println!("n = {n}");
+    println!("    portable: {:>6.2} ns/elem", ns(portable));
+    println!("    dispatch: {:>6.2} ns/elem ({:.2}x)", ns(dispatched), portable.as_secs_f64() / dispatched.as_secs_f64());
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//!
+//! [features]
+//! default = ["ephemeris"]
+//! ephemeris = []
+//! ```
+//!
+//! Sun and Moon from the low-precision series: seasons, Moon phase,
+//! eclipse of a GEO satellite, and the sunlit part of the Earth it sees.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "ephemeris"))]
+compile_error!("ephemeris_example.rs needs the ephemeris feature");
+
+#[path = "solid_angle/astro.rs"]
+mod astro;
+#[cfg(feature = "ephemeris")]
+#[path = "solid_angle/ephemeris.rs"]
+mod ephemeris;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use astro::Spheroid;
+use ephemeris::{julian_date, AU};
+use vec3::norm;
+
+/// Declination of an equator-of-date position
+fn declination(p: [f64; 3]) -> f64 {
+    (p[2] / norm(p)).asin().to_degrees() // (deg)
+}
+
+fn main() {
+    // Meeus example 7.a: 1957 October 4.81, Sputnik 1
+    assert_eq!(julian_date(1957, 10, 4, 0.81 * 24.0), 2_436_116.31);
+
+    // Sun on the 2024 March equinox (03:06 UTC) and June solstice (20:51 UTC)
+    let equinox = julian_date(2024, 3, 20, 3.1);
+    let solstice = julian_date(2024, 6, 20, 20.85);
+    let (sun_equinox, sun_solstice) = (ephemeris::sun_position(equinox), ephemeris::sun_position(solstice));
+    println!(
+        "Sun declination: {:+.3}° at the equinox, {:+.3}° at the solstice, {:.5} AU",
+        declination(sun_equinox),
+        declination(sun_solstice),
+        norm(sun_solstice) / AU
+    );
+    assert!(declination(sun_equinox).abs() < 0.02 && (declination(sun_solstice) - 23.44).abs() < 0.02);
+    assert!((norm(sun_solstice) / AU - 1.0163).abs() < 1e-3); // Near aphelion
+
+    // Moon: full 2024-01-25 17:54 UTC, new 2024-02-09 22:59 UTC
+    let full = ephemeris::moon_illuminated_fraction(julian_date(2024, 1, 25, 17.9));
+    let new = ephemeris::moon_illuminated_fraction(julian_date(2024, 2, 9, 23.0));
+    let distance = norm(ephemeris::moon_position(julian_date(2024, 1, 25, 17.9)));
+    println!("Moon illuminated: {full:.4} full, {new:.4} new, {:.0} km away", distance / 1e3);
+    assert!(full > 0.998 && new < 0.002);
+    assert!((356_000e3..407_000e3).contains(&distance));
+
+    // Solar noon over Greenwich on the equinox puts the Sun near longitude 0
+    let noon = julian_date(2024, 3, 20, 12.1);
+    let sun = ephemeris::inertial_to_ecef(ephemeris::sun_position(noon), noon);
+    let lon = libm::atan2(sun[1], sun[0]).to_degrees();
+    assert!(lon.abs() < 1.0, "{lon}");
+
+    // Near the equinox a GEO satellite at local midnight is eclipsed for
+    // about 70 minutes; six hours later it is in full sun
+    let wgs84 = Spheroid::WGS84;
+    let sat = astro::geodetic_to_ecef(&wgs84, 0.0, 180.0, 35_786e3);
+    assert!(ephemeris::in_earth_shadow(&wgs84, sat, noon));
+    assert!(!ephemeris::in_earth_shadow(&wgs84, sat, noon + 0.25));
+
+    // Sunlit part of the Earth seen from that satellite: dark at local
+    // midnight, full at local noon, half at dawn
+    for (name, jd, expect) in [("midnight", noon, 0.0), ("dawn", noon + 0.25, 0.5), ("noon", noon + 0.5, 1.0)] {
+        let (omega, fraction) = ephemeris::sunlit_earth_solid_angle(&wgs84, sat, jd, 100_000).unwrap();
+        println!("GEO at local {name}: {omega:.6} sr sunlit, {:.2}% of the disk", 100.0 * fraction);
+        assert!((fraction - expect).abs() < 0.02, "{name}: {fraction}");
+    }
+}
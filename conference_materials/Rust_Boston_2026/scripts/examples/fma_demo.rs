@@ -0,0 +1,42 @@
+//! The talk's FMA kernel: what fusing the multiply-adds buys, as the
+//! library kernel's error against its double-double reference on each
+//! input distribution, and its time.
+//!
+//! ```text
+//! cargo run --release --example fma_demo [n]
+//! cargo run --release --example fma_demo --features soft-float [n]
+//! ```
+//!
+//! The second runs the same kernel with every product and sum rounded on
+//! its own, the slide's "before", for comparison. Errors are in units of
+//! the reference's ulp; slivers are ill-conditioned, so their worst is
+//! large either way (see `solid_angle/condition.rs`). A portable x86_64
+//! build calls a software `fma`, so time the fused kernel with
+//! `RUSTFLAGS="-C target-cpu=native"`, or through `solid_angle/dispatch.rs`.
+
+use solid_angle::inputs::{self, Distribution};
+use solid_angle::{dd, tetrahedron, vec3};
+use std::hint::black_box;
+use std::time::Instant;
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 18, |s| s.parse().unwrap());
+    println!("multiply-add {}", if vec3::FUSED { "fused" } else { "unfused" });
+
+    println!("{:>7} {:>12} {:>12} {:>10}", "", "max ulp", "median ulp", "ns/elem");
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 154, n);
+        let mut out = vec![0.0; n];
+        let start = Instant::now();
+        tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut out)?;
+        let elapsed = start.elapsed();
+        let mut reference = vec![0.0; n];
+        dd::solid_angle_tetrahedron_dd(&tets, &mut reference)?;
+
+        let mut ulps: Vec<f64> = out.iter().zip(&reference).map(|(x, r)| (x - r).abs() / f64::EPSILON.max(r.abs() * f64::EPSILON)).collect();
+        ulps.sort_by(f64::total_cmp);
+        let (max, median) = (ulps[n - 1], ulps[n / 2]);
+        println!("{:>7} {max:>12.3e} {median:>12.3} {:>10.2}", dist.name(), 1e9 * elapsed.as_secs_f64() / n as f64);
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! The talk's auto-vectorization baseline: an elementwise product whose
+//! bounds are checked once before the loop, so the loop has no branch
+//! left but its own and packs into vector multiplies. The library ships
+//! it as `solid_angle_export_slice_mul`, under a stable symbol so that
+//! `asm_check.rs` can find the `vmulpd` in it.
+//!
+//! ```text
+//! cargo run --release --example slice_mul_demo --features asm-export [n]
+//! ```
+
+use solid_angle::asm_export::solid_angle_export_slice_mul;
+use solid_angle::inputs::Pcg64;
+use std::hint::black_box;
+use std::time::Instant;
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 22, |s| s.parse().unwrap());
+    let mut rng = Pcg64::new(154, 0);
+    let (a, b): (Vec<f64>, Vec<f64>) = (0..n).map(|_| (rng.uniform(-1.0, 1.0), rng.uniform(-1.0, 1.0))).unzip();
+
+    let mut out = vec![0.0; n];
+    let start = Instant::now();
+    solid_angle_export_slice_mul(black_box(&a), black_box(&b), &mut out)?;
+    let elapsed = start.elapsed();
+    assert!(out.iter().zip(a.iter().zip(&b)).all(|(&y, (&x, &z))| y == x * z));
+    println!("{n} products, {:.3} ns each", 1e9 * elapsed.as_secs_f64() / n as f64);
+
+    assert_eq!(solid_angle_export_slice_mul(&a, &b[1..], &mut out), Err("Dimension mismatch"));
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! The talk's type-2 kernel: the solid angle of each of a batch of
+//! tetrahedra, first by the serial slice kernel and then by the parallel
+//! driver over it, both from the library rather than copies of it.
+//!
+//! ```text
+//! cargo run --release --example type2_demo [n]
+//! cargo run --release --example type2_demo --features soft-float [n]
+//! ```
+//!
+//! The slide's "before" has a separate rounding for every product and
+//! sum, as the library does on targets without FMA or with `soft-float`;
+//! as built on x86_64 it is the fused kernel of `fma_demo.rs` instead.
+
+use solid_angle::inputs::{self, Distribution};
+use solid_angle::{par, tetrahedron, vec3};
+use std::hint::black_box;
+use std::time::Instant;
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+    println!("multiply-add {}, {} threads", if vec3::FUSED { "fused" } else { "unfused" }, rayon::current_num_threads());
+
+    println!("{:>7} {:>13} {:>13}", "", "serial ns/el", "par ns/el");
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 154, n);
+
+        let mut serial = vec![0.0; n];
+        let start = Instant::now();
+        tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut serial)?;
+        let serial_time = start.elapsed();
+
+        let mut parallel = vec![0.0; n];
+        let start = Instant::now();
+        par::solid_angle_tetrahedra_par(black_box(&tets), &mut parallel)?;
+        let par_time = start.elapsed();
+
+        // Chunking changes which thread does the work, not the arithmetic
+        assert!(serial.iter().zip(&parallel).all(|(a, b)| a.to_bits() == b.to_bits()));
+        let ns = |t: std::time::Duration| 1e9 * t.as_secs_f64() / n as f64;
+        println!("{:>7} {:>13.2} {:>13.2}", dist.name(), ns(serial_time), ns(par_time));
+    }
+
+    // Bounds are checked once, before the loop
+    assert_eq!(tetrahedron::solid_angle_tetrahedron(&[[[0.0; 3]; 4]], &mut []), Err("Dimension mismatch"));
+    Ok(())
+}
@@ -0,0 +1,44 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Sign of the solid angle for a nearly flat tetrahedron, in floating
+//! point versus on a fixed-point grid.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/fixed.rs"]
+mod fixed;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+fn main() -> Result<(), &'static str> {
+    // Viewpoint on a large CAD face, far from the model origin.
+    // In exact arithmetic the first three vertices and the last one are coplanar.
+    let offset = 123.45678; // (m)
+    let p = |x: f64, y: f64| [offset + 0.1 * x, offset + 0.3 * y, offset + 0.7 * (x + y)];
+    let flat = [p(0.0, 0.0), p(1.0, 0.0), p(0.0, 1.0), p(1.0, 1.0)];
+
+    let mut out = [0.0; 1];
+    tetrahedron::solid_angle_tetrahedron(&[flat], &mut out)?;
+    println!("f64:         {:+e} sr", out[0]);
+    fixed::solid_angle_tetrahedron_fixed(&[flat], 1e-9, &mut out)?;
+    println!("fixed-point: {:+e} sr", out[0]);
+    assert_eq!(out[0], 0.0);
+
+    // A 1 nm bump resolves with a definite sign either way up
+    let mut up = flat;
+    up[3][2] += 1e-9;
+    let mut down = flat;
+    down[3][2] -= 1e-9;
+    let mut out = [0.0; 2];
+    fixed::solid_angle_tetrahedron_fixed(&[up, down], 1e-9, &mut out)?;
+    println!("±1 nm:       {:+e} sr, {:+e} sr", out[0], out[1]);
+    assert!(out[0] * out[1] < 0.0);
+    Ok(())
+}
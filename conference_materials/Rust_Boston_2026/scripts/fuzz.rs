@@ -0,0 +1,392 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! serde_json = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! default = ["step"]
+//! step = []
+//! defmt = ["dep:defmt"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Mutation fuzzing of the file readers and the kernels, for as long as
+//! it is left running. Each reader is a target with a few small valid
+//! files to mutate: `.npy`/`.npz`, STL, OBJ, PLY and MSH meshes, glTF
+//! JSON and `.glb`, and STEP. Readers must return, `Ok` or `Err`, rather
+//! than panic or abort; kernels must return NaN or `|Ω| <= 2π` for any
+//! bit pattern.
+//!
+//! ```text
+//! rust-script fuzz.rs [iterations] [seed] [target]
+//! ```
+//!
+//! Iterations go round the targets in turn, or all to `target` if named.
+//!
+//! This is a std-only stand-in for `cargo fuzz`, which needs a library
+//! crate to link against. Run it under `cargo miri` or with
+//! `-Zsanitizer=address` for UB checks beyond the bounds checks.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/fixed.rs"]
+mod fixed;
+#[path = "solid_angle/gltf.rs"]
+mod gltf;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/instance.rs"]
+mod instance;
+#[path = "solid_angle/interval.rs"]
+mod interval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_formats.rs"]
+mod mesh_formats;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/scene.rs"]
+mod scene;
+#[path = "solid_angle/step.rs"]
+mod step;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/visibility.rs"]
+mod visibility;
+
+use std::f64::consts::TAU;
+use std::io::Cursor;
+use std::panic;
+
+type Tet = [[f64; 3]; 4];
+
+/// Fuzz inputs from the shared seeded generator
+struct Rng(inputs::Pcg64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0.below(n)
+    }
+
+    /// Mostly interesting floats, sometimes raw bit patterns
+    fn float(&mut self) -> f64 {
+        const SPECIAL: [f64; 10] = [0.0, -0.0, 1.0, -1.0, f64::MIN_POSITIVE, 5e-324, f64::MAX, f64::INFINITY, f64::NEG_INFINITY, f64::NAN];
+        match self.below(4) {
+            0 => SPECIAL[self.below(SPECIAL.len())],
+            1 => f64::from_bits(self.next_u64()),
+            _ => self.0.uniform(-1.0, 1.0),
+        }
+    }
+
+    fn tet(&mut self) -> Tet {
+        let mut tet = [[0.0; 3]; 4];
+        tet.iter_mut().flatten().for_each(|x| *x = self.float());
+        // Repeat vertices now and then, for the degenerate branches
+        if self.below(8) == 0 {
+            tet[self.below(3) + 1] = tet[0];
+        }
+        tet
+    }
+
+    /// A few byte flips, insertions, deletions and truncations, and for
+    /// the text formats, ranges cut or copied elsewhere and tokens likely
+    /// to sit on a boundary
+    fn mutate(&mut self, bytes: &mut Vec<u8>) {
+        const TOKENS: [&[u8]; 12] = [b"0", b"-1", b"4294967295", b"18446744073709551615", b"1e308", b"nan", b"()", b"(", b")", b",", b" ", b"\n"];
+        for _ in 0..=self.below(4) {
+            if bytes.is_empty() {
+                bytes.push(self.next_u64() as u8);
+                continue;
+            }
+            let i = self.below(bytes.len());
+            let range = i..bytes.len().min(i + 1 + self.below(32));
+            match self.below(8) {
+                0 => bytes[i] ^= 1 << self.below(8),
+                1 => bytes[i] = self.next_u64() as u8,
+                2 => bytes.insert(i, self.next_u64() as u8),
+                3 => _ = bytes.drain(range),
+                4 => {
+                    let copied = bytes[range].to_vec();
+                    let at = self.below(bytes.len() + 1);
+                    bytes.splice(at..at, copied);
+                }
+                5 | 6 => _ = bytes.splice(i..i, TOKENS[self.below(TOKENS.len())].iter().copied()),
+                _ => bytes.truncate(i),
+            }
+        }
+    }
+}
+
+/// A reader under fuzz: the valid files it starts from, and whether it
+/// accepted a mutated one
+struct Target {
+    name: &'static str,
+    seeds: Vec<Vec<u8>>,
+    read: fn(&[u8]) -> bool,
+}
+
+/// A triangle, its indices padded to four bytes, as a glTF buffer
+const GLTF_BUFFER: &str = "AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA=";
+
+/// One triangle under a scaled and moved parent, by `buffer`
+fn gltf_json(buffer: serde_json::Value) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "asset": {"version": "2.0"},
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"name": "root", "scale": [2.0, 2.0, 2.0], "children": [1]}, {"translation": [1.0, 0.0, 0.0], "mesh": 0}],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1, "material": 0}]}],
+        "materials": [{"name": "steel"}],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"},
+        ],
+        "bufferViews": [{"buffer": 0, "byteOffset": 0, "byteLength": 36}, {"buffer": 0, "byteOffset": 36, "byteLength": 6}],
+        "buffers": [buffer],
+    }))
+    .unwrap()
+}
+
+/// The same triangle as a `.glb`, its buffer in the binary chunk
+fn glb() -> Vec<u8> {
+    let mut json = gltf_json(serde_json::json!({"byteLength": 44}));
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin: Vec<u8> = [0.0_f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter().flat_map(|x| x.to_le_bytes()).collect();
+    bin.extend_from_slice(&[0, 0, 1, 0, 2, 0, 0, 0]);
+    let mut out = Vec::new();
+    for word in [0x4654_6C67, 2, (12 + 8 + json.len() + 8 + bin.len()) as u32, json.len() as u32, 0x4E4F_534A] {
+        out.extend_from_slice(&u32::to_le_bytes(word));
+    }
+    out.extend_from_slice(&json);
+    for word in [bin.len() as u32, 0x004E_4942] {
+        out.extend_from_slice(&u32::to_le_bytes(word));
+    }
+    out.extend_from_slice(&bin);
+    out
+}
+
+/// A triangle with a polyline side, and a disk, in millimetres
+const STEP: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1=(LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.));
+#2=(NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.));
+#3=CARTESIAN_POINT('',(0.,0.,0.));
+#4=CARTESIAN_POINT('',(1.,0.,0.));
+#5=CARTESIAN_POINT('',(0.,1.,0.));
+#6=CARTESIAN_POINT('',(0.5,0.5,0.));
+#7=VERTEX_POINT('',#3);
+#8=VERTEX_POINT('',#4);
+#9=VERTEX_POINT('',#5);
+#10=DIRECTION('',(1.,0.,0.));
+#11=VECTOR('',#10,1.);
+#12=LINE('',#3,#11);
+#13=EDGE_CURVE('',#7,#8,#12,.T.);
+#14=POLYLINE('',(#4,#6,#5));
+#15=EDGE_CURVE('',#8,#9,#14,.T.);
+#16=DIRECTION('',(0.,-1.,0.));
+#17=VECTOR('',#16,1.);
+#18=LINE('',#5,#17);
+#19=EDGE_CURVE('',#9,#7,#18,.T.);
+#20=ORIENTED_EDGE('',*,*,#13,.T.);
+#21=ORIENTED_EDGE('',*,*,#15,.T.);
+#22=ORIENTED_EDGE('',*,*,#19,.T.);
+#23=EDGE_LOOP('',(#20,#21,#22));
+#24=FACE_OUTER_BOUND('',#23,.T.);
+#25=DIRECTION('',(0.,0.,1.));
+#26=AXIS2_PLACEMENT_3D('',#3,#25,#10);
+#27=PLANE('',#26);
+#28=ADVANCED_FACE('',(#24),#27,.T.);
+#29=CARTESIAN_POINT('',(3.,0.,0.));
+#30=VERTEX_POINT('',#29);
+#31=CARTESIAN_POINT('',(2.,0.,0.));
+#32=AXIS2_PLACEMENT_3D('',#31,#25,#10);
+#33=CIRCLE('',#32,1.);
+#34=EDGE_CURVE('',#30,#30,#33,.T.);
+#35=ORIENTED_EDGE('',*,*,#34,.T.);
+#36=EDGE_LOOP('',(#35));
+#37=FACE_OUTER_BOUND('',#36,.T.);
+#38=PLANE('',#32);
+#39=ADVANCED_FACE('',(#37),#38,.T.);
+#40=OPEN_SHELL('',(#28,#39));
+#41=SHELL_BASED_SURFACE_MODEL('seed',(#40));
+ENDSEC;
+END-ISO-10303-21;
+";
+
+/// A tetrahedron's faces, for the mesh formats
+const TET_FACES: [[u32; 3]; 4] = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+const TET_CORNERS: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+fn stl_seeds() -> Vec<Vec<u8>> {
+    let mut ascii = String::from("solid tet\n");
+    let mut binary = vec![b' '; 80];
+    binary.extend_from_slice(&4_u32.to_le_bytes());
+    for f in TET_FACES {
+        ascii += "facet normal 0 0 0\nouter loop\n";
+        binary.extend_from_slice(&[0; 12]);
+        for v in f {
+            let [x, y, z] = TET_CORNERS[v as usize];
+            ascii += &format!("vertex {x} {y} {z}\n");
+            [x, y, z].iter().for_each(|c| binary.extend_from_slice(&c.to_le_bytes()));
+        }
+        ascii += "endloop\nendfacet\n";
+        binary.extend_from_slice(&[0; 2]);
+    }
+    vec![(ascii + "endsolid tet\n").into_bytes(), binary]
+}
+
+fn obj_seed() -> Vec<u8> {
+    let mut s: String = TET_CORNERS.iter().map(|[x, y, z]| format!("v {x} {y} {z}\n")).collect();
+    s += "vt 0 0\nvn 0 0 1\nf 1/1/1 3/1/1 2/1/1\nf -4 -3 -1\nf 1 4 3\nf 2 3 4\n";
+    s.into_bytes()
+}
+
+fn ply_seeds() -> Vec<Vec<u8>> {
+    let header = |format: &str| {
+        format!("ply\nformat {format} 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\nelement face 4\nproperty list uchar int vertex_indices\nend_header\n")
+    };
+    let mut ascii = header("ascii");
+    let mut binary = header("binary_little_endian").into_bytes();
+    for [x, y, z] in TET_CORNERS {
+        ascii += &format!("{x} {y} {z}\n");
+        [x, y, z].iter().for_each(|c| binary.extend_from_slice(&c.to_le_bytes()));
+    }
+    for [a, b, c] in TET_FACES {
+        ascii += &format!("3 {a} {b} {c}\n");
+        binary.push(3);
+        [a, b, c].iter().for_each(|&v| binary.extend_from_slice(&(v as i32).to_le_bytes()));
+    }
+    vec![ascii.into_bytes(), binary]
+}
+
+fn msh_seeds() -> Vec<Vec<u8>> {
+    let nodes: String = TET_CORNERS.iter().enumerate().map(|(i, [x, y, z])| format!("{} {x} {y} {z}\n", i + 1)).collect();
+    let faces: String = TET_FACES.iter().enumerate().map(|(i, [a, b, c])| format!("{} 2 2 1 1 {} {} {}\n", i + 1, a + 1, b + 1, c + 1)).collect();
+    let v2 = format!("$MeshFormat\n2.2 0 8\n$EndMeshFormat\n$Nodes\n4\n{nodes}$EndNodes\n$Elements\n4\n{faces}$EndElements\n");
+    let coordinates: String = TET_CORNERS.iter().map(|[x, y, z]| format!("{x} {y} {z}\n")).collect();
+    let v4 = format!("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 4 1 4\n3 1 0 4\n1\n2\n3\n4\n{coordinates}$EndNodes\n$Elements\n1 1 1 1\n3 1 4 1\n1 1 2 3 4\n$EndElements\n");
+    vec![v2.into_bytes(), v4.into_bytes()]
+}
+
+/// Every reader, with its seeds
+fn targets() -> Vec<Target> {
+    let tets = [[[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]; 3];
+    let mut npy_buf = Vec::new();
+    npy::write_tetrahedra(&mut npy_buf, &tets).unwrap();
+    let mut npz_buf = Cursor::new(Vec::new());
+    npy::write_npz(&mut npz_buf, &[("tets", &[3, 4, 3], tets.as_flattened().as_flattened())]).unwrap();
+    let gltf_seed = gltf_json(serde_json::json!({"byteLength": 44, "uri": format!("data:application/octet-stream;base64,{GLTF_BUFFER}")}));
+
+    vec![
+        Target { name: "npy", seeds: vec![npy_buf], read: |b| npy::read_f64(&mut Cursor::new(b)).is_ok() },
+        Target { name: "npz", seeds: vec![npz_buf.into_inner()], read: |b| npy::read_npz(Cursor::new(b)).is_ok() },
+        Target { name: "stl", seeds: stl_seeds(), read: |b| mesh_formats::read_stl(&mut &b[..]).is_ok() },
+        Target { name: "obj", seeds: vec![obj_seed()], read: |b| mesh_formats::read_obj(&mut &b[..]).is_ok() },
+        Target { name: "ply", seeds: ply_seeds(), read: |b| mesh_formats::read_ply(&mut &b[..]).is_ok() },
+        Target { name: "msh", seeds: msh_seeds(), read: |b| mesh_formats::read_msh(&mut &b[..]).is_ok() },
+        Target { name: "gltf", seeds: vec![gltf_seed], read: |b| gltf::parse_gltf(b).is_ok() },
+        Target { name: "glb", seeds: vec![glb()], read: |b| gltf::read_glb(&mut &b[..]).is_ok() },
+        Target { name: "step", seeds: vec![STEP.as_bytes().to_vec()], read: |b| step::read_step(&mut &b[..], 1e-4).is_ok() },
+    ]
+}
+
+fn check_kernels(tet: Tet) {
+    let [v0, v1, v2, v3] = tet;
+    let bounded = |omega: f64| omega.is_nan() || omega.abs() <= TAU;
+
+    let omega = tetrahedron::solid_angle_tetrahedron_scalar(v0, v1, v2, v3);
+    assert!(bounded(omega), "fma kernel: {omega} for {tet:?}");
+    let (omega, _) = condition::solid_angle_tetrahedron_scalar_cond(v0, v1, v2, v3);
+    assert!(bounded(omega), "condition kernel: {omega} for {tet:?}");
+    let omega = dd::solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3).to_f64();
+    assert!(bounded(omega), "double-double kernel: {omega} for {tet:?}");
+    if let Some(enc) = interval::solid_angle_tetrahedron_scalar_interval(v0, v1, v2, v3) {
+        // Enclosures are widened by a few ulps past the exact range
+        let slack = 8.0 * f64::EPSILON * TAU;
+        assert!(!(enc.lo < -TAU - slack || enc.hi > TAU + slack), "interval kernel: {enc:?} for {tet:?}");
+    }
+    let mut out = [0.0];
+    if fixed::solid_angle_tetrahedron_fixed(&[tet], 1e-9, &mut out).is_ok() {
+        assert!(bounded(out[0]), "fixed kernel: {} for {tet:?}", out[0]);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let iterations: u64 = args.first().map_or(100_000, |s| s.parse().unwrap());
+    let seed: u64 = args.get(1).map_or(0, |s| s.parse().unwrap());
+    let mut rng = Rng(inputs::Pcg64::new(seed, 0));
+    let mut targets = targets();
+    if let Some(name) = args.get(2) {
+        targets.retain(|t| t.name == name);
+        assert!(!targets.is_empty(), "No target {name}");
+    }
+
+    // Every seed must parse, or the mutations start from nowhere
+    for t in &targets {
+        assert!(t.seeds.iter().all(|s| (t.read)(s)), "{} seed rejected", t.name);
+    }
+
+    let mut counts = vec![(0_u64, 0_u64); targets.len()];
+    for i in 0..iterations {
+        let k = i as usize % targets.len();
+        let target = &targets[k];
+        let mut bytes = target.seeds[rng.below(target.seeds.len())].clone();
+        rng.mutate(&mut bytes);
+        let tet = rng.tet();
+
+        let result = panic::catch_unwind(|| {
+            check_kernels(tet);
+            (target.read)(&bytes)
+        });
+        match result {
+            Ok(true) => counts[k].0 += 1,
+            Ok(false) => counts[k].1 += 1,
+            Err(_) => {
+                eprintln!("iteration {i} (seed {seed}), {} failed on {} bytes: {bytes:02x?}", target.name, bytes.len());
+                std::process::exit(1);
+            }
+        }
+    }
+    println!("{iterations} iterations, no panics");
+    for (t, (accepted, rejected)) in targets.iter().zip(counts) {
+        println!("{:>6}: {accepted} files parsed, {rejected} rejected", t.name);
+    }
+}
@@ -0,0 +1,65 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! ```
+//!
+//! Seeded benchmark inputs, byte-identical on every platform, so runs on
+//! different machines see the same data:
+//!
+//! ```text
+//! rust-script gen_inputs.rs <distribution> <n> <seed> tets.npy
+//! ```
+//!
+//! With no arguments, checks the generator against the reference PCG64
+//! sequence and each distribution against its known-answer hash.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+
+use inputs::{Distribution, Pcg64};
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+/// FNV-1a over the little-endian bytes of every coordinate
+fn fnv1a(tets: &[[[f64; 3]; 4]]) -> u64 {
+    tets.as_flattened().as_flattened().iter().flat_map(|x| x.to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Hash of 1000 tetrahedra from seed 0, per distribution
+const KNOWN_ANSWERS: [(Distribution, u64); 4] = [
+    (Distribution::Random, 0x8934_dd4c_30c1_0577),
+    (Distribution::Sliver, 0x7eb3_5baf_0701_2e91),
+    (Distribution::Tiny, 0x93dd_6b09_2ec7_0fe0),
+    (Distribution::Huge, 0x8c40_a612_02b9_8da3),
+];
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [dist, n, seed, out_path] = &args[..] {
+        let dist: Distribution = dist.parse().map_err(io::Error::other)?;
+        let n: usize = n.parse().map_err(io::Error::other)?;
+        let seed: u64 = seed.parse().map_err(io::Error::other)?;
+        let tets = inputs::tetrahedra(dist, seed, n);
+        npy::write_tetrahedra(&mut BufWriter::new(File::create(out_path)?), &tets)?;
+        println!("Wrote {n} {} tetrahedra (seed {seed}, hash {:#018x}) to {out_path}", dist.name(), fnv1a(&tets));
+        return Ok(());
+    }
+
+    // pcg64_srandom_r(42, 54) in the reference C library
+    let mut rng = Pcg64::new(42, 54);
+    assert_eq!([rng.next_u64(), rng.next_u64(), rng.next_u64()], [0x86b1_da1d_7206_2b68, 0x1304_aa46_c985_3d39, 0xa367_0e9e_0dd5_0358]);
+
+    for (dist, expected) in KNOWN_ANSWERS {
+        let hash = fnv1a(&inputs::tetrahedra(dist, 0, 1000));
+        println!("{:<8} {hash:#018x}", dist.name());
+        assert_eq!(hash, expected, "{} inputs differ from the reference", dist.name());
+    }
+    Ok(())
+}
@@ -0,0 +1,283 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! serde_json = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A small assembly written as glTF three ways (embedded buffer, `.glb`,
+//! and `.gltf` beside a `.bin`) and read back into the same scene: the
+//! hierarchy's transforms composed, one shared mesh per glTF mesh,
+//! mirrored parts still wound outward, strips and fans wound as
+//! triangles, materials by name, and malformed files refused.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/gltf.rs"]
+mod gltf;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/instance.rs"]
+mod instance;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/scene.rs"]
+mod scene;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/visibility.rs"]
+mod visibility;
+
+use instance::Affine3;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+use vec3::{cross, sub};
+
+/// Standard base64, padded
+fn base64(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0_u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for k in 0..4 {
+            out.push(if k <= chunk.len() { DIGITS[(bits >> (18 - 6 * k) & 63) as usize] as char } else { '=' });
+        }
+    }
+    out
+}
+
+/// `.glb` of `doc` with `bin` as its binary chunk, each padded to 4 bytes
+fn glb(doc: &Value, bin: &[u8]) -> Vec<u8> {
+    let mut json = serde_json::to_vec(doc).expect("Serializable");
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+    let mut out = Vec::new();
+    for word in [0x4654_6C67, 2, (12 + 8 + json.len() + 8 + bin.len()) as u32, json.len() as u32, 0x4E4F_534A] {
+        out.extend_from_slice(&u32::to_le_bytes(word));
+    }
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(&0x004E_4942_u32.to_le_bytes());
+    out.extend_from_slice(&bin);
+    out
+}
+
+/// A change to a valid file that should make it unreadable
+type Edit = dyn Fn(&mut Value);
+
+/// Normal of a triangle on its counterclockwise side, unnormalized
+fn normal([a, b, c]: [[f64; 3]; 3]) -> [f64; 3] {
+    cross(sub(b, a), sub(c, a))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Buffer: a unit cube's positions and two halves of its indices, a
+    // square's positions in fan and in strip order, and the fan's indices
+    let cube = primitives::cuboid([-0.5; 3], [0.5; 3])?;
+    let mut bin = Vec::new();
+    let f32s = |bin: &mut Vec<u8>, points: &[[f64; 3]]| points.iter().flatten().for_each(|&x| bin.extend_from_slice(&(x as f32).to_le_bytes()));
+    f32s(&mut bin, cube.vertices());
+    cube.faces().iter().flatten().for_each(|&i| bin.extend_from_slice(&(i as u16).to_le_bytes()));
+    f32s(&mut bin, &[[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0]]);
+    f32s(&mut bin, &[[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, 1.0, 0.0]]);
+    bin.extend_from_slice(&[0, 1, 2, 3]);
+
+    // A root turned a quarter about z and raised, a wing under it by
+    // matrix, three panels under that sharing one name, the last
+    // mirrored; a fan with no name, and a strip
+    let doc = json!({
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0, 5, 6]}],
+        "nodes": [
+            {"name": "array", "translation": [0.0, 0.0, 5.0], "rotation": [0.0, 0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2], "children": [1]},
+            {"name": "wing", "matrix": [2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 1.0, 0.0, 0.0, 1.0], "children": [2, 3, 4]},
+            {"name": "panel", "mesh": 0},
+            {"name": "panel", "mesh": 0, "translation": [3.0, 0.0, 0.0]},
+            {"name": "panel", "mesh": 0, "translation": [6.0, 0.0, 0.0], "scale": [-1.0, 1.0, 1.0]},
+            {"mesh": 1, "translation": [0.0, 0.0, -3.0]},
+            {"name": "strip", "mesh": 2},
+        ],
+        "meshes": [
+            {"name": "panel", "primitives": [
+                {"attributes": {"POSITION": 0}, "indices": 1, "material": 0},
+                {"attributes": {"POSITION": 0}, "indices": 2, "material": 1},
+            ]},
+            {"primitives": [{"attributes": {"POSITION": 3}, "indices": 5, "mode": 6}, {"attributes": {"POSITION": 3}, "mode": 1}]},
+            {"primitives": [{"attributes": {"POSITION": 4}, "mode": 5}]},
+        ],
+        "materials": [{"name": "cell"}, {"name": "frame", "pbrMetallicRoughness": {"metallicFactor": 1.0}}],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 8, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 18, "type": "SCALAR"},
+            {"bufferView": 1, "byteOffset": 36, "componentType": 5123, "count": 18, "type": "SCALAR"},
+            {"bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC3"},
+            {"bufferView": 3, "componentType": 5126, "count": 4, "type": "VEC3"},
+            {"bufferView": 4, "componentType": 5121, "count": 4, "type": "SCALAR"},
+        ],
+        "bufferViews": [
+            {"buffer": 0, "byteOffset": 0, "byteLength": 96},
+            {"buffer": 0, "byteOffset": 96, "byteLength": 72},
+            {"buffer": 0, "byteOffset": 168, "byteLength": 48},
+            {"buffer": 0, "byteOffset": 216, "byteLength": 48},
+            {"buffer": 0, "byteOffset": 264, "byteLength": 4},
+        ],
+        "buffers": [{"byteLength": bin.len(), "uri": format!("data:application/octet-stream;base64,{}", base64(&bin))}],
+    });
+    let imported = gltf::parse_gltf(&serde_json::to_vec(&doc)?)?;
+    let scene = &imported.scene;
+
+    // Structure: one mesh per glTF mesh, names by path, indices kept
+    assert_eq!(scene.names(), ["array/wing/panel", "array/wing/panel#3", "array/wing/panel#4", "node5", "strip"]);
+    assert_eq!(scene.meshes(), 3);
+    assert_eq!(scene.instance_attributes().get("node"), Some(&[2.0, 3.0, 4.0, 5.0, 6.0][..]));
+    assert_eq!(scene.instance_attributes().get("mesh"), Some(&[0.0, 0.0, 0.0, 1.0, 2.0][..]));
+    assert_eq!(imported.materials, ["cell", "frame"]);
+    let panel = &scene.instances()[0].mesh;
+    assert_eq!((panel.vertices(), panel.faces()), (cube.vertices(), cube.faces()));
+    assert_eq!(panel.face_attributes().get("material"), Some(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0][..]));
+    assert_eq!(scene.instances()[3].mesh.face_attributes().get("material"), Some(&[-1.0, -1.0][..]));
+
+    // Transforms composed down the hierarchy, against building them here
+    let root = Affine3::rotation([0.0, 0.0, 1.0], FRAC_PI_2).then(&Affine3::translation([0.0, 0.0, 5.0]));
+    let wing = Affine3::scaling(2.0).then(&Affine3::translation([1.0, 0.0, 0.0])).then(&root);
+    let mirror = Affine3 { linear: [[-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], translation: [6.0, 0.0, 0.0] };
+    let expected = [Affine3::IDENTITY.then(&wing), Affine3::translation([3.0, 0.0, 0.0]).then(&wing), mirror.then(&wing)];
+    for (i, e) in expected.iter().enumerate() {
+        for p in [[0.0; 3], [0.3, -0.2, 0.4]] {
+            let (got, want) = (scene.instances()[i].transform.apply(p), e.apply(p));
+            assert!((0..3).all(|k| (got[k] - want[k]).abs() < 1e-14), "Panel {i}: {got:?} against {want:?}");
+        }
+    }
+
+    // Inside every panel, the mirrored one too, the panels subtend 4π
+    let panels = &scene.instances()[..3];
+    let centres: Vec<[f64; 3]> = panels.iter().map(|i| i.transform.apply([0.1, 0.0, 0.0])).collect();
+    let mut omega = vec![0.0; 3];
+    instance::solid_angles(panels, &centres, &mut omega)?;
+    assert!(omega.iter().all(|w| (w - 4.0 * PI).abs() < 1e-12), "{omega:?}");
+    assert!(scene.instances()[2].mirrored());
+
+    // Fan and strip both wound counterclockwise about +z; lines skipped
+    for i in [3, 4] {
+        let mesh = &scene.instances()[i].mesh;
+        assert_eq!(mesh.faces().len(), 2);
+        assert!(mesh.triangles().all(|t| normal(t)[2] > 0.0 && normal(t)[0] == 0.0), "Instance {i} misses +z");
+    }
+
+    // The same from a .glb, and from a .gltf beside its .bin
+    let mut bound = doc.clone();
+    bound["buffers"][0].as_object_mut().ok_or("Buffer not an object")?.remove("uri");
+    assert_eq!(gltf::read_glb(&mut glb(&bound, &bin).as_slice())?, imported);
+    let dir = std::env::temp_dir().join(format!("gltf_example_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let mut external = doc.clone();
+    external["buffers"][0]["uri"] = json!("assembly.bin");
+    std::fs::write(dir.join("assembly.bin"), &bin)?;
+    std::fs::write(dir.join("assembly.gltf"), serde_json::to_vec(&external)?)?;
+    std::fs::write(dir.join("assembly.glb"), glb(&bound, &bin))?;
+    assert_eq!(gltf::read_gltf(dir.join("assembly.gltf"))?, imported);
+    assert_eq!(gltf::read_gltf(dir.join("assembly.glb"))?, imported);
+    assert!(gltf::parse_gltf(&serde_json::to_vec(&external)?).is_err()); // No directory to look in
+
+    // URIs are percent-decoded, and can't leave the directory, though
+    // the buffer is there to read at each of these
+    std::fs::create_dir_all(dir.join("parts"))?;
+    std::fs::write(dir.join("parts").join("wing panel.bin"), &bin)?;
+    let outside = dir.join("parts").join("assembly.gltf");
+    let mut with_uri = |uri: &str| -> Result<Option<String>, Box<dyn Error>> {
+        external["buffers"][0]["uri"] = json!(uri);
+        std::fs::write(&outside, serde_json::to_vec(&external)?)?;
+        Ok(gltf::read_gltf(&outside).err().map(|e| e.to_string()))
+    };
+    assert_eq!(with_uri("wing%20panel.bin")?, None);
+    assert_eq!(with_uri("./wing%20panel.bin")?, None);
+    let absolute = dir.join("assembly.bin").to_string_lossy().into_owned();
+    let refusals = [
+        ("../assembly.bin", "Buffer URI outside its directory"),
+        ("%2e%2e/assembly.bin", "Buffer URI outside its directory"),
+        ("..%5Cassembly.bin", "Buffer URI outside its directory"),
+        (&absolute, "Buffer URI not a relative path"),
+        (&format!("file://{absolute}"), "Buffer URI not a relative path"),
+        ("wing%2panel.bin", "Malformed percent escape in buffer URI"),
+        ("wing%ff.bin", "Buffer URI not UTF-8"),
+    ];
+    for (uri, message) in refusals {
+        assert_eq!(with_uri(uri)?.as_deref(), Some(message), "{uri}");
+    }
+    std::fs::remove_dir_all(&dir)?;
+
+    // Malformed files
+    let refused = |edit: &Edit| {
+        let mut bad = doc.clone();
+        edit(&mut bad);
+        gltf::parse_gltf(&serde_json::to_vec(&bad).expect("Serializable")).err().map(|e| e.to_string())
+    };
+    let cases: [(&Edit, &str); 12] = [
+        (&|d| d["asset"]["version"] = json!("1.0"), "Unsupported glTF version"),
+        (&|d| d["nodes"][1]["children"] = json!([0]), "Node hierarchy has a cycle"),
+        (&|d| d["nodes"][1]["children"] = json!([2, 2]), "Node with more than one parent"),
+        (&|d| d["nodes"][5]["children"] = json!([2]), "Node with more than one parent"),
+        (&|d| d["scenes"][0]["nodes"] = json!([0, 5, 5]), "Scene lists a node twice"),
+        (&|d| d["accessors"][0]["componentType"] = json!(5123), "Positions must be floats"),
+        (&|d| d["accessors"][1]["sparse"] = json!({"count": 1}), "Sparse accessors are not supported"),
+        (&|d| d["accessors"][2]["count"] = json!(19), "Accessor out of its buffer"),
+        (&|d| d["accessors"][2]["count"] = json!(u64::MAX), "Accessor out of its buffer"),
+        (&|d| d["accessors"][0]["count"] = json!((1_u64 << 62) + 1), "Accessor out of its buffer"),
+        (&|d| d["nodes"][2]["mesh"] = json!(7), "Mesh index out of range"),
+        (&|d| d["buffers"][0]["byteLength"] = json!(4096), "Buffer shorter than its byteLength"),
+    ];
+    for (edit, message) in cases {
+        assert_eq!(refused(edit).as_deref(), Some(message));
+    }
+    let whole = glb(&bound, &bin);
+    assert!(gltf::read_glb(&mut &whole[..whole.len() - 4]).is_err());
+    assert!(gltf::parse_gltf(b"{").is_err());
+
+    println!("{} instances of {} meshes, {} faces; materials {:?}", scene.instances().len(), scene.meshes(), scene.faces(), imported.materials);
+    for (name, w) in scene.names().iter().zip(&omega) {
+        println!("{name:>20}: {w:.15} sr inside");
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! half = { version = "2", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! default = ["half"]
+//! half = ["dep:half"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Half-precision vertex buffers, as exported from an ML pipeline or read
+//! back from a GPU, widened on the fly by the mixed-precision kernels.
+//!
+//! ```text
+//! rust-script half_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "half"))]
+compile_error!("half_example.rs needs the half feature");
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mixed.rs"]
+mod mixed;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use half::{bf16, f16};
+use mixed::Storage;
+
+/// Vertices as raw 16-bit words, little-endian, as a foreign buffer would arrive
+fn to_words<S: Storage>(tets: &[[[f64; 3]; 4]], bits: fn(S) -> u16) -> Vec<u8> {
+    tets.iter().flatten().flatten().flat_map(|&x| bits(S::from_f64(x)).to_le_bytes()).collect()
+}
+
+/// Tetrahedra back out of 16-bit words, without widening
+fn from_words<S: Storage>(bytes: &[u8], from_bits: fn(u16) -> S) -> Vec<[[S; 3]; 4]> {
+    let x: Vec<S> = bytes.chunks_exact(2).map(|w| from_bits(u16::from_le_bytes([w[0], w[1]]))).collect();
+    x.chunks_exact(12).map(|t| std::array::from_fn(|v| std::array::from_fn(|k| t[3 * v + k]))).collect()
+}
+
+/// Median and largest error of `S` storage against the all-`f64` result
+fn errors<S: Storage>(narrow: &[[[S; 3]; 4]], exact: &[f64]) -> Result<(f64, f64), &'static str> {
+    let mut out = vec![0.0_f64; narrow.len()];
+    mixed::solid_angle_tetrahedra_mixed_par(narrow, &mut out)?;
+    let mut err: Vec<f64> = out.iter().zip(exact).map(|(y, e)| (y - e).abs()).collect();
+    err.sort_by(f64::total_cmp);
+    Ok((err[err.len() / 2], err[err.len() - 1]))
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 16, |s| s.parse().unwrap());
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 146, n);
+    let mut exact = vec![0.0; n];
+    par::solid_angle_tetrahedra_par(&tets, &mut exact)?;
+
+    let f16s = from_words(&to_words(&tets, f16::to_bits), f16::from_bits);
+    let bf16s = from_words(&to_words(&tets, bf16::to_bits), bf16::from_bits);
+    let f32s: Vec<[[f32; 3]; 4]> = tets.iter().map(|t| t.map(|v| v.map(|x| x as f32))).collect();
+
+    // Half outputs too, e.g. to write straight back into a half buffer
+    let mut half_out = vec![f16::ZERO; n];
+    mixed::solid_angle_tetrahedra_mixed_par(&f16s, &mut half_out)?;
+    let mut wide_out = vec![0.0; n];
+    mixed::solid_angle_tetrahedra_mixed_par(&f16s, &mut wide_out)?;
+    assert!(half_out.iter().zip(&wide_out).all(|(h, w)| *h == f16::from_f64(*w)));
+
+    // The worst cases are near-flat tetrahedra rounding to the other orientation
+    println!("n = {n}, abs error against f64 storage (sr):");
+    println!("{:>8} {:>10} {:>10}", "storage", "median", "max");
+    for (name, (median, max)) in [("f32", errors(&f32s, &exact)?), ("f16", errors(&f16s, &exact)?), ("bf16", errors(&bf16s, &exact)?)] {
+        println!("{name:>8} {median:>10.3e} {max:>10.3e}");
+    }
+    Ok(())
+}
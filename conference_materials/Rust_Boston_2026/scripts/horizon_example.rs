@@ -0,0 +1,143 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Horizon masks from inside a faceted conical crater, where they are
+//! known exactly at each sector's center, over a flat square, where the
+//! ground falls away toward each edge, and over rough ground, checked by
+//! segment casts; then the open sky.
+//!
+//! ```text
+//! rust-script horizon_example.rs [sectors]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::time::Instant;
+
+/// Cone `z = slope r` out to `radius`, in `rings` rings of `sectors`
+/// sectors, the first sector's spoke pointing north
+fn crater(sectors: u32, rings: u32, radius: f64, slope: f64) -> mesh::TriMesh {
+    let mut vertices = vec![[0.0; 3]];
+    for ring in 1..=rings {
+        let r = radius * f64::from(ring) / f64::from(rings);
+        vertices.extend((0..sectors).map(|s| {
+            let d = horizon::direction(TAU * f64::from(s) / f64::from(sectors), 0.0);
+            [r * d[0], r * d[1], slope * r]
+        }));
+    }
+    let id = |ring: u32, s: u32| if ring == 0 { 0 } else { 1 + (ring - 1) * sectors + s % sectors };
+    let mut faces: Vec<[u32; 3]> = (0..sectors).map(|s| [0, id(1, s), id(1, s + 1)]).collect();
+    for ring in 1..rings {
+        faces.extend((0..sectors).flat_map(|s| [[id(ring, s), id(ring + 1, s), id(ring + 1, s + 1)], [id(ring, s), id(ring + 1, s + 1), id(ring, s + 1)]]));
+    }
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+/// Square `[-half, half]²` at `z = 0`, in `n` by `n` cells
+fn plain(n: u32, half: f64) -> mesh::TriMesh {
+    let at = |i: u32| -half + 2.0 * half * f64::from(i) / f64::from(n);
+    let vertices = (0..=n).flat_map(|j| (0..=n).map(move |i| [at(i), at(j), 0.0])).collect();
+    let id = |i: u32, j: u32| j * (n + 1) + i;
+    let faces = (0..n)
+        .flat_map(|j| (0..n).flat_map(move |i| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let sectors: u32 = std::env::args().nth(1).map_or(360, |s| s.parse().unwrap());
+
+    // Crater: along a sector's center line the facets rise as
+    // z = slope ρ / cos(π / sectors), so the rim is the mask there
+    let (radius, slope, height) = (1000.0, 0.3, 20.0);
+    let terrain = crater(sectors, 50, radius, slope);
+    let bvh = bvh::Bvh::new(&terrain);
+    let start = Instant::now();
+    let profile = horizon::horizon_profile([0.0, 0.0, height], &terrain, &bvh, sectors as usize)?;
+    let elapsed = start.elapsed();
+    let exact = ((slope * radius - height) / (radius * (PI / f64::from(sectors)).cos())).atan();
+    let worst = profile.iter().map(|&m| (m - exact).abs()).fold(0.0, f64::max);
+    let sky = horizon::sky_solid_angle(&profile);
+    println!(
+        "crater, {} faces: mask {:.4}° off by at most {worst:.1e} rad; sky {sky:.4} sr; {:.1?} for {sectors} bins",
+        terrain.faces().len(),
+        exact.to_degrees(),
+        elapsed
+    );
+    assert!(worst < 1e-12, "crater mask off");
+    assert!((sky - TAU * (1.0 - exact.sin())).abs() < 1e-8);
+
+    // Plain, observed off center: the mask dips to the nearest edge
+    let (half, height, offset) = (500.0, 2.0, [100.0, -50.0]);
+    let terrain = plain(20, half);
+    let bvh = bvh::Bvh::new(&terrain);
+    let bins = 16;
+    let profile = horizon::horizon_profile([offset[0], offset[1], height], &terrain, &bvh, bins)?;
+    for (i, &mask) in profile.iter().enumerate() {
+        let d = horizon::direction((i as f64 + 0.5) * TAU / bins as f64, 0.0);
+        let edge = (0..2).map(|k| (half * d[k].signum() - offset[k]) / d[k]).fold(f64::INFINITY, f64::min);
+        assert!((mask - (-height / edge).atan()).abs() < 1e-12, "plain mask off in bin {i}");
+    }
+    let sky = horizon::sky_solid_angle(&profile);
+    println!("plain: sky {sky:.4} sr, a little over 2π from the edges");
+    assert!(sky > TAU && sky < TAU + 0.1);
+
+    // Rough ground, against segment casts just under and over each mask
+    let mut rng = inputs::Pcg64::new(167, 0);
+    let mut rough = plain(40, half);
+    let vertices = rough.vertices().iter().map(|&[x, y, _]| [x, y, rng.uniform(0.0, 30.0)]).collect();
+    rough = mesh::TriMesh::new(vertices, rough.faces().to_vec())?;
+    let bvh = bvh::Bvh::new(&rough);
+    for _ in 0..20 {
+        let observer = [rng.uniform(-400.0, 400.0), rng.uniform(-400.0, 400.0), 35.0];
+        let profile = horizon::horizon_profile(observer, &rough, &bvh, bins)?;
+        for (i, &mask) in profile.iter().enumerate() {
+            let az = (i as f64 + 0.5) * TAU / bins as f64;
+            let cast = |el: f64| bvh.occluded(&rough, observer, std::array::from_fn(|k| observer[k] + 4.0 * half * horizon::direction(az, el)[k]));
+            assert!(cast(mask - 1e-9) && !cast(mask + 1e-9), "rough mask off in bin {i}");
+        }
+    }
+    let terrain = plain(20, half);
+    let bvh = bvh::Bvh::new(&terrain);
+
+    // Under a roof, and beside the plain
+    assert!(horizon::horizon_profile([3.0, 7.0, -1.0], &terrain, &bvh, 4)?.iter().all(|&m| m == FRAC_PI_2));
+    assert!(horizon::horizon_profile([half + 100.0, 0.0, height], &terrain, &bvh, 4).is_err());
+    assert!(horizon::horizon_profile([0.0; 3], &crater(8, 2, 1.0, 1.0), &bvh, 4).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! A wobbling sphere seen from points inside and out, updated frame by
+//! frame from the vertices that moved: solid angles against a fresh sum
+//! of the whole mesh, and the refit BVH against a rebuilt one.
+//!
+//! ```text
+//! rust-script incremental_example.rs [frames] [vertices moved per frame]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/incremental.rs"]
+mod incremental;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use incremental::MovingMesh;
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+/// Unit UV sphere, wound outward
+fn sphere(n: u32) -> mesh::TriMesh {
+    let vertices = (0..=n)
+        .flat_map(|i| {
+            let theta = PI * f64::from(i) / f64::from(n);
+            (0..2 * n).map(move |j| {
+                let phi = PI * f64::from(j) / f64::from(n);
+                [theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()]
+            })
+        })
+        .collect();
+    let id = |i: u32, j: u32| i * 2 * n + j % (2 * n);
+    let faces = (0..n)
+        .flat_map(|i| (0..2 * n).flat_map(move |j| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut args = std::env::args().skip(1).map(|s| s.parse::<usize>().unwrap());
+    let (frames, per_frame) = (args.next().unwrap_or(200), args.next().unwrap_or(16));
+    let mut rng = inputs::Pcg64::new(173, 0);
+
+    // Origins well inside and well outside, clear of the wobble
+    let origins: Vec<[f64; 3]> = (0..2000)
+        .map(|i| rng.point().map(|x| x * if i % 2 == 0 { 0.7 } else { 3.0 }))
+        .filter(|&p| !(0.8..1.3).contains(&vec3::norm(p)))
+        .collect();
+    let start = Instant::now();
+    let mut moving = MovingMesh::new(sphere(64), origins.clone());
+    let full = start.elapsed();
+    let n = moving.mesh().vertices().len();
+
+    // Each frame nudges a few vertices radially, by up to 5%
+    let mut update = Duration::ZERO;
+    let mut dirty = 0;
+    for _ in 0..frames {
+        for _ in 0..per_frame {
+            let v = rng.below(n);
+            let scale = 1.0 + 0.1 * (rng.unit() - 0.5);
+            let p = moving.mesh().vertices()[v];
+            moving.move_vertex(v, p.map(|x| x * scale))?;
+        }
+        assert!(moving.pending() == per_frame);
+        let start = Instant::now();
+        dirty += moving.update();
+        update += start.elapsed();
+    }
+    assert!(moving.pending() == 0 && moving.update() == 0);
+    assert!(moving.move_vertex(n, [0.0; 3]).is_err());
+
+    // Against summing the final mesh from scratch
+    let fresh = MovingMesh::new(moving.mesh().clone(), origins.clone());
+    let worst = (0..origins.len()).map(|i| (moving.solid_angle(i) - fresh.solid_angle(i)).abs()).fold(0.0, f64::max);
+    println!(
+        "{} faces, {} origins: full pass {full:.1?}; {frames} updates of {per_frame} vertices ({} faces) {:.1?} each; off a fresh sum by {worst:.1e} after all",
+        moving.mesh().faces().len(),
+        origins.len(),
+        dirty / frames,
+        update / frames as u32
+    );
+    assert!(worst < 1e-13, "incremental solid angles drifted");
+    for (i, o) in origins.iter().enumerate() {
+        let expected = if vec3::norm(*o) < 0.8 { 1.0 } else { 0.0 };
+        assert!((moving.winding_number(i) - expected).abs() < 1e-12, "winding number at {o:?}");
+    }
+
+    // The refit BVH answers as a rebuilt one, and its boxes hold every face
+    let rebuilt = bvh::Bvh::new(moving.mesh());
+    for _ in 0..20_000 {
+        let (from, to) = (rng.point().map(|x| 1.5 * x), rng.point().map(|x| 1.5 * x));
+        assert!(moving.bvh().occluded(moving.mesh(), from, to) == rebuilt.occluded(moving.mesh(), from, to), "refit BVH disagrees");
+    }
+    let faces = bounds::Aabb::from_points(moving.mesh().vertices());
+    assert!(moving.bvh().bounds() == faces, "refit root box");
+
+    // And rebuilding from scratch agrees to the last bit with a fresh one
+    moving.rebuild();
+    assert!(moving.solid_angles() == fresh.solid_angles() && *moving.bvh() == rebuilt);
+    println!("refit BVH agrees with a rebuilt one on 20000 segments");
+    Ok(())
+}
@@ -0,0 +1,167 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A solar array of one shared panel placed hundreds of times: solid
+//! angles and view factors bitwise the same as for the array flattened
+//! into one mesh, occlusion through one BVH in the panel's frame against
+//! a BVH over the flattened array, and mirrored instances still wound
+//! outward.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/instance.rs"]
+mod instance;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use instance::{Affine3, Instance};
+use mesh::TriMesh;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::sync::Arc;
+
+/// Every instance placed and concatenated into one mesh
+fn flatten(instances: &[Instance]) -> Result<TriMesh, &'static str> {
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    for placed in instances.iter().map(Instance::to_mesh) {
+        let base = vertices.len() as u32;
+        faces.extend(placed.faces().iter().map(|f| f.map(|v| v + base)));
+        vertices.extend_from_slice(placed.vertices());
+    }
+    TriMesh::new(vertices, faces)
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut rng = inputs::Pcg64::new(185, 0);
+
+    // Transforms compose and invert
+    let t = Affine3::rotation([1.0, -2.0, 0.5], 0.7).then(&Affine3::scaling(2.5)).then(&Affine3::translation([3.0, -1.0, 4.0]));
+    let round_trip = t.then(&t.inverse()?);
+    let err = (0..3).flat_map(|i| (0..3).map(move |j| (i, j))).map(|(i, j)| (round_trip.linear[i][j] - Affine3::IDENTITY.linear[i][j]).abs()).fold(0.0, f64::max);
+    assert!(err < 1e-15 && round_trip.translation.iter().all(|x| x.abs() < 1e-14), "{round_trip:?}");
+    assert!((t.determinant() - 2.5_f64.powi(3)).abs() < 1e-13);
+    assert_eq!(Affine3::scaling(0.0).inverse().err(), Some("Singular transform"));
+
+    // Twenty rows of ten panels, each tilted toward the sun on a post
+    let panel = Arc::new(primitives::cuboid([-0.5, -0.3, -0.01], [0.5, 0.3, 0.01])?);
+    let panel_bvh = bvh::Bvh::new(&panel);
+    let array: Vec<Instance> = (0..200)
+        .map(|i| {
+            let (row, col) = (f64::from(i / 10), f64::from(i % 10));
+            let tilt = Affine3::rotation([1.0, 0.0, 0.0], 0.4 + 0.02 * col);
+            let spin = Affine3::rotation([0.0, 0.0, 1.0], 0.05 * row);
+            Instance::new(panel.clone(), tilt.then(&spin).then(&Affine3::translation([1.2 * col, 1.5 * row, 1.0])))
+        })
+        .collect();
+    let flat = flatten(&array)?;
+    let shared = panel.vertices().len();
+    println!("{} panels, {} faces: {shared} vertices shared against {} flattened", array.len(), flat.faces().len(), flat.vertices().len());
+
+    // Solid angles: the same triangles in the same order
+    let origins: Vec<[f64; 3]> = (0..500).map(|_| [rng.uniform(-2.0, 13.0), rng.uniform(-2.0, 31.0), rng.uniform(-1.0, 3.0)]).collect();
+    let (mut instanced, mut flattened) = (vec![0.0; origins.len()], vec![0.0; origins.len()]);
+    instance::solid_angles(&array, &origins, &mut instanced)?;
+    multi_origin::solid_angles_multi_origin(&flat, &origins, &mut flattened)?;
+    assert_eq!(instanced, flattened);
+
+    // Occlusion in each panel's frame against the flattened BVH; the two
+    // may only part on segments grazing an edge
+    let flat_bvh = bvh::Bvh::new(&flat);
+    let bvhs = vec![&panel_bvh; array.len()];
+    let (mut blocked, mut differ) = (0, 0);
+    let n = 20_000;
+    for _ in 0..n {
+        let from = [rng.uniform(-2.0, 13.0), rng.uniform(-2.0, 31.0), 0.0];
+        let to = [rng.uniform(-2.0, 13.0), rng.uniform(-2.0, 31.0), 2.5];
+        let hit = instance::occluded(&array, &bvhs, from, to)?;
+        blocked += usize::from(hit);
+        differ += usize::from(hit != flat_bvh.occluded(&flat, from, to));
+    }
+    assert!(differ <= n / 1000, "{differ} of {n} segments disagree");
+    assert!(blocked > n / 10, "Only {blocked} of {n} segments blocked");
+    println!("{blocked} of {n} segments blocked; {differ} disagree with the flattened BVH");
+    assert_eq!(instance::occluded(&array, &bvhs[1..], [0.0; 3], [1.0; 3]).err(), Some("Dimension mismatch"));
+
+    // Mirrored parts, a left and right bracket from one mesh, stay wound
+    // outward: 4π inside each, none outside
+    let bracket = Arc::new(primitives::cone([0.0; 3], [0.3, 0.2, 1.0], 0.4, 16)?);
+    let right = Affine3::translation([1.0, 0.0, 0.0]);
+    let left = Affine3 { linear: [[-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], translation: [-1.0, 0.0, 0.0] };
+    let pair = [Instance::new(bracket.clone(), right), Instance::new(bracket.clone(), left)];
+    assert!(!pair[0].mirrored() && pair[1].mirrored());
+    let mut omega = [0.0; 3];
+    instance::solid_angles(&pair, &[[1.05, 0.0, 0.3], [-1.05, 0.0, 0.3], [0.0, 0.0, 0.3]], &mut omega)?;
+    assert!((omega[0] - 4.0 * PI).abs() < 1e-12 && (omega[1] - 4.0 * PI).abs() < 1e-12 && omega[2].abs() < 1e-12, "{omega:?}");
+
+    // View factors in a closed room of mirrored and rotated walls, each a
+    // unit square facing inward: the same matrix as the flattened room's,
+    // rows summing to 1
+    let wall = Arc::new(TriMesh::new(vec![[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.5, 0.5, 0.0], [-0.5, 0.5, 0.0]], vec![[0, 1, 2], [0, 2, 3]])?);
+    let up = Affine3::translation([0.0, 0.0, -0.5]);
+    let mirror = Affine3 { linear: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]], translation: [0.0, 0.0, 0.5] };
+    let side = |axis: [f64; 3], angle: f64| Instance::new(wall.clone(), up.then(&Affine3::rotation(axis, angle)));
+    let room = [
+        Instance::new(wall.clone(), up),
+        Instance::new(wall.clone(), mirror),
+        side([1.0, 0.0, 0.0], FRAC_PI_2),
+        side([1.0, 0.0, 0.0], -FRAC_PI_2),
+        side([0.0, 1.0, 0.0], FRAC_PI_2),
+        side([0.0, 1.0, 0.0], -FRAC_PI_2),
+    ];
+    let flat_room = flatten(&room)?;
+    let n = flat_room.faces().len();
+    let (mut f, mut expected) = (vec![0.0; n * n], vec![0.0; n * n]);
+    instance::view_factors(&room, &mut f)?;
+    thermal::view_factors(&flat_room, &mut expected)?;
+    assert_eq!(f, expected);
+    let worst = f.chunks(n).map(|row| (row.iter().sum::<f64>() - 1.0).abs()).fold(0.0, f64::max);
+    assert!(worst < 1e-12, "Room rows miss 1 by {worst:e}");
+    assert_eq!(instance::view_factors(&room, &mut f[1..]).err(), Some("Dimension mismatch"));
+    println!("room of {n} faces: view factor rows sum to 1 within {worst:.1e}");
+    Ok(())
+}
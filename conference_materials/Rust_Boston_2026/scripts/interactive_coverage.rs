@@ -0,0 +1,483 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//! flate2 = { version = "1", default-features = false, features = ["zlib-rs"], optional = true }
+//! crc32fast = { version = "1", optional = true }
+//! winit = { version = "0.30", optional = true }
+//! wgpu = { version = "24", optional = true }
+//! pollster = { version = "0.4", optional = true }
+//!
+//! [features]
+//! default = ["plot", "window"]
+//! defmt = ["dep:defmt"]
+//! geotiff = []
+//! plot = ["dep:flate2", "dep:crc32fast"]
+//! window = ["dep:winit", "dep:wgpu", "dep:pollster"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Live coverage: a sensor panel moved about a scene with the arrow keys
+//! (Page Up and Down for height), and a grid of probes on a plane through
+//! the scene's base showing the solid angle everything subtends at each,
+//! on a log scale: `4π` inside closed parts, and the sensor's footprint
+//! wherever it hovers. Each move goes through [incremental::MovingMesh], so only the
+//! sensor's two faces are summed again, for every probe, per frame; the
+//! window title shows how long that took.
+//!
+//! ```text
+//! rust-script interactive_coverage.rs [scene.npz]
+//! rust-script interactive_coverage.rs [scene.npz] --frames 500
+//! ```
+//!
+//! The scene is a `.npz` mesh as `solid_angle/mesh_io.rs` writes, or a few
+//! boxes. `--frames`, or a build without the `window` feature, sweeps the
+//! sensor round a circle with no window and checks the running totals
+//! against a fresh sum. Escape quits.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "plot"))]
+compile_error!("interactive_coverage.rs needs the plot feature");
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/incremental.rs"]
+mod incremental;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/plot.rs"]
+mod plot;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/raster.rs"]
+mod raster;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use incremental::MovingMesh;
+use mesh::TriMesh;
+use plot::Image;
+use raster::Raster;
+use std::error::Error;
+use std::f64::consts::{PI, TAU};
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{Duration, Instant};
+
+/// Probes along each side of the grid
+const GRID: usize = 192;
+
+/// Sensor travel per key press, as a share of the scene's size
+const STEP: f64 = 0.02;
+
+/// Side of the sensor panel, as a share of the scene's size
+const SENSOR: f64 = 0.15;
+
+/// Least solid angle shown above the bottom of the colormap (sr)
+const FAINTEST: f64 = 1e-3;
+
+/// The scene and the sensor as one [MovingMesh], seen from the probes
+struct Coverage {
+    moving: MovingMesh,
+    /// First of the sensor's four vertices, after the scene's
+    sensor: usize,
+    corners: [[f64; 3]; 4],
+    /// Probe grid, for its geometry; values come from `moving`
+    grid: Raster,
+    /// Largest side of the scene's bounding box
+    size: f64,
+}
+
+impl Coverage {
+    /// `scene` with the sensor above its middle, and probes over its
+    /// footprint and a margin, a hundredth of its size above its base
+    fn new(scene: TriMesh) -> Result<Self, &'static str> {
+        // Check bounds
+        if scene.faces().is_empty() {
+            return Err("Scene has no faces");
+        }
+
+        let [lo, hi] = scene.vertices().iter().fold([[f64::INFINITY; 3], [f64::NEG_INFINITY; 3]], |[lo, hi], v| {
+            [std::array::from_fn(|k| lo[k].min(v[k])), std::array::from_fn(|k| hi[k].max(v[k]))]
+        });
+        let size = (0..3).map(|k| hi[k] - lo[k]).fold(0.0, f64::max);
+        let (mid, half) = ([0, 1].map(|k| 0.5 * (lo[k] + hi[k])), 0.5 * SENSOR * size);
+        let z = hi[2] + 0.25 * size;
+
+        // Wound counterclockwise from above, so seen from below it counts
+        // positive, as the inside of an outward-wound part does
+        let corners = [[-half, -half], [half, -half], [half, half], [-half, half]].map(|[dx, dy]| [mid[0] + dx, mid[1] + dy, z]);
+        let sensor = scene.vertices().len();
+        let mut vertices = scene.vertices().to_vec();
+        vertices.extend(corners);
+        let mut faces = scene.faces().to_vec();
+        let s = sensor as u32;
+        faces.extend([[s, s + 1, s + 2], [s, s + 2, s + 3]]);
+        let mesh = TriMesh::new(vertices, faces)?;
+
+        let span = 1.2 * size;
+        let grid = Raster::new(GRID, GRID, [mid[0] - 0.5 * span, mid[1] + 0.5 * span], span / GRID as f64);
+        let height = lo[2] + 0.01 * size;
+        let probes = (0..GRID * GRID)
+            .map(|i| {
+                let [x, y] = grid.center(i / GRID, i % GRID);
+                [x, y, height]
+            })
+            .collect();
+        Ok(Self { moving: MovingMesh::new(mesh, probes), sensor, corners, grid, size })
+    }
+
+    /// Move the sensor by `d` and bring every probe up to date; returns
+    /// the faces summed again
+    fn shift(&mut self, d: [f64; 3]) -> usize {
+        for (k, c) in self.corners.iter_mut().enumerate() {
+            *c = std::array::from_fn(|i| c[i] + d[i]);
+            self.moving.move_vertex(self.sensor + k, *c).expect("Sensor vertices exist");
+        }
+        self.moving.update()
+    }
+
+    /// Sensor's center
+    fn position(&self) -> [f64; 3] {
+        std::array::from_fn(|k| self.corners.iter().map(|c| c[k]).sum::<f64>() / 4.0)
+    }
+
+    /// Heatmap of the probes, north up, on a log scale from [FAINTEST]
+    /// to `4π` sr: the sensor's footprint is a small fraction of what the
+    /// parts subtend at probes inside them
+    fn image(&self) -> Image {
+        let data = self.moving.solid_angles().into_iter().map(|x| x.max(FAINTEST).log10()).collect();
+        let grid = Raster { data, ..self.grid.clone() };
+        plot::heatmap(&grid, Some([FAINTEST.log10(), (4.0 * PI).log10()]), 1).expect("Grid matches its data")
+    }
+}
+
+/// A few boxes of different heights, as a stand-in scene
+fn default_scene() -> Result<TriMesh, &'static str> {
+    let boxes = [([0.0, 0.0, 0.0], [1.0, 2.0, 1.5]), ([2.0, 0.5, 0.0], [3.5, 1.0, 0.6]), ([0.5, 3.0, 0.0], [3.0, 3.5, 2.5])];
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    for (lo, hi) in boxes {
+        let b = primitives::cuboid(lo, hi)?;
+        let base = vertices.len() as u32;
+        faces.extend(b.faces().iter().map(|f| f.map(|v| v + base)));
+        vertices.extend_from_slice(b.vertices());
+    }
+    TriMesh::new(vertices, faces)
+}
+
+/// Sweep the sensor round a circle for `frames` frames with no window,
+/// then check the running totals against a fresh sum of every face
+fn sweep(coverage: &mut Coverage, frames: usize) -> Result<(), Box<dyn Error>> {
+    let radius = 0.3 * coverage.size;
+    let (mut updating, mut drawing) = (Duration::ZERO, Duration::ZERO);
+    let mut at = [radius, 0.0];
+    coverage.shift([radius, 0.0, 0.0]);
+    for i in 1..=frames {
+        let (s, c) = (TAU * i as f64 / frames as f64).sin_cos();
+        let next = [radius * c, radius * s];
+        let start = Instant::now();
+        coverage.shift([next[0] - at[0], next[1] - at[1], 0.0]);
+        updating += start.elapsed();
+        let start = Instant::now();
+        std::hint::black_box(coverage.image());
+        drawing += start.elapsed();
+        at = next;
+    }
+
+    let start = Instant::now();
+    let fresh = MovingMesh::new(coverage.moving.mesh().clone(), coverage.moving.origins().to_vec());
+    let full = start.elapsed();
+    let worst = coverage.moving.solid_angles().iter().zip(fresh.solid_angles()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+    println!(
+        "{} probes, {} faces: {:.1?} per update and {:.1?} per image over {frames} frames, {full:.1?} summing afresh; worst drift {worst:.1e} sr",
+        GRID * GRID,
+        coverage.moving.mesh().faces().len(),
+        updating / frames.max(1) as u32,
+        drawing / frames.max(1) as u32
+    );
+    if worst > 1e-9 * 4.0 * PI {
+        return Err("Running totals drifted from a fresh sum".into());
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let scene = match args.iter().find(|a| a.ends_with(".npz")) {
+        Some(path) => mesh_io::read_tri_mesh_npz(BufReader::new(File::open(path)?))?,
+        None => default_scene()?,
+    };
+    let mut coverage = Coverage::new(scene)?;
+    let frames = args.iter().position(|a| a == "--frames").map(|i| args.get(i + 1).map_or(Ok(360), |n| n.parse())).transpose()?;
+
+    #[cfg(feature = "window")]
+    if frames.is_none() {
+        let event_loop = winit::event_loop::EventLoop::new()?;
+        let mut app = window::App { coverage, gpu: None, error: None };
+        event_loop.run_app(&mut app)?;
+        return app.error.map_or(Ok(()), Err);
+    }
+    sweep(&mut coverage, frames.unwrap_or(360))
+}
+
+/// The window: the probes' heatmap as a texture on one screen-filling
+/// triangle, redrawn after each key press
+#[cfg(feature = "window")]
+mod window {
+    use super::{Coverage, STEP};
+    use crate::plot::Image;
+    use std::error::Error;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use winit::application::ApplicationHandler;
+    use winit::dpi::LogicalSize;
+    use winit::event::{ElementState, KeyEvent, WindowEvent};
+    use winit::event_loop::ActiveEventLoop;
+    use winit::keyboard::{Key, NamedKey};
+    use winit::window::{Window, WindowId};
+
+    const SHADER: &str = r"
+@group(0) @binding(0) var image: texture_2d<f32>;
+@group(0) @binding(1) var nearest: sampler;
+
+struct Out {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs(@builtin(vertex_index) i: u32) -> Out {
+    let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+    return Out(vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0), uv);
+}
+
+@fragment
+fn fs(in: Out) -> @location(0) vec4<f32> {
+    return textureSample(image, nearest, in.uv);
+}
+";
+
+    pub struct App {
+        pub coverage: Coverage,
+        pub gpu: Option<Gpu>,
+        pub error: Option<Box<dyn Error>>,
+    }
+
+    /// Where the sensor is, and what the last move cost
+    fn title(coverage: &Coverage, faces: usize, took: Duration) -> String {
+        let [x, y, z] = coverage.position();
+        format!("sensor at ({x:.2}, {y:.2}, {z:.2}): {faces} faces x {} probes in {took:.1?}", coverage.moving.origins().len())
+    }
+
+    impl ApplicationHandler for App {
+        fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+            if self.gpu.is_some() {
+                return;
+            }
+            let attributes = Window::default_attributes().with_title("coverage").with_inner_size(LogicalSize::new(768, 768));
+            let image = self.coverage.image();
+            let gpu = event_loop.create_window(attributes).map_err(Into::into).and_then(|w| pollster::block_on(Gpu::new(Arc::new(w), &image)));
+            match gpu {
+                Ok(gpu) => {
+                    gpu.window.set_title(&title(&self.coverage, 0, Duration::ZERO));
+                    self.gpu = Some(gpu);
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                    event_loop.exit();
+                }
+            }
+        }
+
+        fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+            let Some(gpu) = &mut self.gpu else {
+                return;
+            };
+            match event {
+                WindowEvent::CloseRequested => event_loop.exit(),
+                WindowEvent::Resized(size) => gpu.resize(size.width, size.height),
+                WindowEvent::KeyboardInput { event: KeyEvent { logical_key: Key::Named(key), state: ElementState::Pressed, .. }, .. } => {
+                    let s = STEP * self.coverage.size;
+                    let d = match key {
+                        NamedKey::ArrowLeft => [-s, 0.0, 0.0],
+                        NamedKey::ArrowRight => [s, 0.0, 0.0],
+                        NamedKey::ArrowUp => [0.0, s, 0.0],
+                        NamedKey::ArrowDown => [0.0, -s, 0.0],
+                        NamedKey::PageUp => [0.0, 0.0, s],
+                        NamedKey::PageDown => [0.0, 0.0, -s],
+                        NamedKey::Escape => return event_loop.exit(),
+                        _ => return,
+                    };
+                    let start = Instant::now();
+                    let faces = self.coverage.shift(d);
+                    let took = start.elapsed();
+                    gpu.upload(&self.coverage.image());
+                    gpu.window.set_title(&title(&self.coverage, faces, took));
+                    gpu.window.request_redraw();
+                }
+                WindowEvent::RedrawRequested => match gpu.render() {
+                    Ok(()) => {}
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        let size = gpu.window.inner_size();
+                        gpu.resize(size.width, size.height);
+                    }
+                    Err(e) => {
+                        self.error = Some(e.into());
+                        event_loop.exit();
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    pub struct Gpu {
+        window: Arc<Window>,
+        surface: wgpu::Surface<'static>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        texture: wgpu::Texture,
+        pipeline: wgpu::RenderPipeline,
+        bind_group: wgpu::BindGroup,
+    }
+
+    impl Gpu {
+        /// Surface, pipeline and a texture the size of `image`, holding it
+        async fn new(window: Arc<Window>, image: &Image) -> Result<Self, Box<dyn Error>> {
+            let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+            let surface = instance.create_surface(Arc::clone(&window))?;
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions { compatible_surface: Some(&surface), ..Default::default() })
+                .await
+                .ok_or("No GPU adapter for this window")?;
+            let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor::default(), None).await?;
+            let size = window.inner_size();
+            let config = surface.get_default_config(&adapter, size.width.max(1), size.height.max(1)).ok_or("Surface not supported by the adapter")?;
+            surface.configure(&device, &config);
+
+            // sRGB, as the colormap's bytes are
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("probes"),
+                size: wgpu::Extent3d { width: image.width as u32, height: image.height as u32, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default()); // Nearest, clamped
+
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor { label: Some("heatmap"), source: wgpu::ShaderSource::Wgsl(SHADER.into()) });
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("heatmap"),
+                layout: None,
+                vertex: wgpu::VertexState { module: &shader, entry_point: Some("vs"), buffers: &[], compilation_options: Default::default() },
+                fragment: Some(wgpu::FragmentState { module: &shader, entry_point: Some("fs"), targets: &[Some(config.format.into())], compilation_options: Default::default() }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("heatmap"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
+                ],
+            });
+
+            let gpu = Self { window, surface, device, queue, config, texture, pipeline, bind_group };
+            gpu.upload(image);
+            Ok(gpu)
+        }
+
+        fn resize(&mut self, width: u32, height: u32) {
+            if width > 0 && height > 0 {
+                (self.config.width, self.config.height) = (width, height);
+                self.surface.configure(&self.device, &self.config);
+            }
+        }
+
+        /// Replace the texture's pixels; `image` must be the size it was
+        fn upload(&self, image: &Image) {
+            let size = self.texture.size();
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo { texture: &self.texture, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
+                image.pixels.as_flattened(),
+                wgpu::TexelCopyBufferLayout { offset: 0, bytes_per_row: Some(4 * size.width), rows_per_image: Some(size.height) },
+                size,
+            );
+        }
+
+        fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+            let frame = self.surface.get_current_texture()?;
+            let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("heatmap"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &self.bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+            self.queue.submit([encoder.finish()]);
+            frame.present();
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,51 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Certified enclosures of solid angles, and the double-double fallback
+//! near the atan2 branch point.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/interval.rs"]
+mod interval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use interval::Filtered;
+
+fn main() -> Result<(), &'static str> {
+    let origin = [0.1, -0.2, 0.3];
+    let tets = [
+        // Octant
+        [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        // Generic
+        [origin, [1.3, 0.2, -0.4], [-0.7, 1.1, 0.5], [0.2, -0.9, 1.7]],
+        // Sliver seen edge-on
+        [origin, [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 1e-12]],
+        // Flat triangle seen from within its own plane, behind: Ω ≈ ±2π
+        [[0.25, 0.25, 0.0], [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    ];
+    let mut out = [Filtered::Enclosure(interval::Interval::point(0.0)); 4];
+    interval::solid_angle_tetrahedron_filtered(&tets, &mut out)?;
+
+    for (tet, result) in tets.iter().zip(&out) {
+        let [v0, v1, v2, v3] = *tet;
+        let reference = dd::solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3).to_f64();
+        match result {
+            Filtered::Enclosure(enc) => {
+                assert!(enc.contains(reference));
+                println!("[{:+.17e}, {:+.17e}] sr, width {:.1e}", enc.lo, enc.hi, enc.width());
+            }
+            Filtered::Refined(dd) => println!("double-double fallback: {:+.17e} sr", dd.to_f64()),
+        }
+    }
+    Ok(())
+}
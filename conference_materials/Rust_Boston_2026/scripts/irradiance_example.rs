@@ -0,0 +1,121 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Sun and sky on the ground in front of a wall: the sky it blocks
+//! against the wall's view factor by Lambert's formula, the Sun behind it
+//! and in front of it, and a row of points batched in parallel.
+//!
+//! ```text
+//! rust-script irradiance_example.rs [samples per source]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/irradiance.rs"]
+mod irradiance;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sources.rs"]
+mod sources;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use irradiance::{irradiance, irradiance_batch};
+use sources::{Disk, Sky, Source};
+use std::time::Instant;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let samples: usize = std::env::args().nth(1).map_or(Ok(1 << 14), |s| s.parse())?;
+
+    // Wall 40 m long and 10 m high, 5 m north of the origin, facing south
+    let (d, h) = (5.0, 10.0);
+    let corners = [[-20.0, d, 0.0], [20.0, d, 0.0], [20.0, d, h], [-20.0, d, h]];
+    let wall = mesh::TriMesh::new(corners.to_vec(), vec![[0, 1, 2], [0, 2, 3]])?;
+    let bvh = bvh::Bvh::new(&wall);
+    let occluder = Some((&wall, &bvh));
+
+    // Sky on the ground and on planes tilted up toward the wall, which
+    // keep it all in front: what the wall hides is its projected solid
+    // angle
+    let sky = Sky::from_horizontal_irradiance(100.0);
+    let sources: [&(dyn Source + Sync); 1] = [&sky];
+    let mut worst = 0.0_f64;
+    for normal in [[0.0, 0.0, 1.0], [0.0, 0.6, 0.8], [0.0, 0.8, 0.6]] {
+        let hidden: f64 = wall.triangles().map(|t| thermal::view_factor_point_triangle([0.0; 3], normal, t)).sum();
+        let exact = sky.irradiance(normal) - 100.0 * hidden;
+        let e = irradiance([0.0; 3], normal, &sources, occluder, samples);
+        worst = worst.max((e - exact).abs() / 100.0);
+    }
+    println!("sky past the wall, {samples} samples: off by at most {worst:.1e} of the open sky");
+    assert!(worst < 2e-3, "sky past the wall off");
+
+    // Sun low in the north is behind the wall, in the south it isn't, and
+    // grazing the top edge it is half hidden
+    let grazing = [0.0, d, h];
+    for (dir, expected) in [([0.0, 1.0, 0.5], 0.0), ([0.0, -1.0, 0.5], 1.0), (grazing, 0.5)] {
+        let sun = Disk::sun(dir, 800.0);
+        let e = irradiance([0.0; 3], [0.0, 0.0, 1.0], &[&sun], occluder, samples);
+        let share = e / sun.irradiance([0.0, 0.0, 1.0]);
+        println!("sun toward {dir:?}: {e:.2} W/m², {share:.3} of it unblocked");
+        assert!((share - expected).abs() < 2e-2, "sun share off");
+    }
+
+    // Without an occluder it's the closed forms
+    let sun = Disk::sun([0.3, -1.0, 0.8], 800.0);
+    let sources: [&(dyn Source + Sync); 2] = [&sun, &sky];
+    let normal = [0.0, -0.5, 0.75_f64.sqrt()];
+    assert!(irradiance([0.0; 3], normal, &sources, None, samples) == sun.irradiance(normal) + sky.irradiance(normal));
+
+    // A row of points walking away from the wall, serial and parallel
+    let points: Vec<[f64; 3]> = (0..256).map(|i| [0.0, d - 0.1 - 0.2 * i as f64, 0.0]).collect();
+    let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+    let (mut serial, mut parallel) = (vec![0.0; points.len()], vec![0.0; points.len()]);
+    par::set_par_threshold(usize::MAX);
+    irradiance_batch(&points, &normals, &sources, occluder, samples, &mut serial)?;
+    par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+    let start = Instant::now();
+    irradiance_batch(&points, &normals, &sources, occluder, samples, &mut parallel)?;
+    let elapsed = start.elapsed();
+    assert!(serial == parallel, "parallel batch differs");
+    assert!(serial.windows(2).all(|w| w[1] >= w[0] - 1e-9), "irradiance should grow away from the wall");
+    assert!(irradiance_batch(&points, &normals[1..], &sources, occluder, samples, &mut parallel).is_err());
+    println!(
+        "{} points: {:.1} W/m² at the wall to {:.1} W/m² {:.0} m out; {:.0} ns/ray",
+        points.len(),
+        serial[0],
+        serial[points.len() - 1],
+        d - points[points.len() - 1][1],
+        1e9 * elapsed.as_secs_f64() / (points.len() * 2 * samples) as f64
+    );
+    Ok(())
+}
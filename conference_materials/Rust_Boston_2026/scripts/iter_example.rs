@@ -0,0 +1,41 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! ```
+//!
+//! Solid angles inside an iterator pipeline, without intermediate `Vec`s.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::PI;
+
+fn main() {
+    // Faces of a unit cube, two triangles each, seen from its center
+    let corner = |i: usize| [(i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5];
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let triangles = quads.iter().flat_map(|q| {
+        [
+            [corner(q[0]), corner(q[1]), corner(q[2])],
+            [corner(q[0]), corner(q[2]), corner(q[3])],
+        ]
+    });
+
+    // Total solid angle of the closed, outward-facing surface
+    let total: f64 = tetrahedron::solid_angle_triangles_iter([0.0; 3], triangles.clone()).sum();
+    println!("Cube seen from its center: {total:.15} sr (4π = {:.15})", 4.0 * PI);
+
+    // Same surface as tetrahedra, keeping only the triangles of the +z face
+    let top: f64 = tetrahedron::solid_angle_iter(triangles.map(|tri| [[0.0; 3], tri[0], tri[1], tri[2]]))
+        .zip(quads.iter().flat_map(|q| [q, q]))
+        .filter(|(_, q)| q.iter().all(|&i| corner(i)[2] > 0.0))
+        .map(|(omega, _)| omega)
+        .sum();
+    println!("Top face: {top:.15} sr (4π/6 = {:.15})", 4.0 * PI / 6.0);
+}
@@ -0,0 +1,92 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! k-means on separated blobs of points, recovering their centers, and
+//! the same clustering on one thread and on several, bit for bit. Then
+//! the faces of a cylinder grouped into patches.
+//!
+//! ```text
+//! rust-script kmeans_example.rs [points per blob]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/cluster.rs"]
+mod cluster;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+
+use inputs::Pcg64;
+use mesh::TriMesh;
+use std::f64::consts::TAU;
+use std::time::Instant;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let per_blob: usize = std::env::args().nth(1).map_or(100_000, |s| s.parse().unwrap());
+
+    // Eight blobs of side 0.5 at the corners of a cube of side 10
+    let mut rng = Pcg64::new(153, 0);
+    let truth: Vec<[f64; 3]> = (0..8).map(|i| [0, 1, 2].map(|k| 10.0 * ((i >> k) & 1) as f64)).collect();
+    let points: Vec<[f64; 3]> = (0..8 * per_blob)
+        .map(|i| {
+            let c = truth[i % 8];
+            [0, 1, 2].map(|k| c[k] + rng.uniform(-0.25, 0.25))
+        })
+        .collect();
+
+    let start = Instant::now();
+    let found = cluster::kmeans(&points, 8, 1, 100)?;
+    println!(
+        "{} points, k = 8: {} iterations, inertia {:.1}, in {:.2?}",
+        points.len(),
+        found.iterations,
+        found.inertia,
+        start.elapsed()
+    );
+    for c in &found.centers {
+        let nearest = truth.iter().map(|t| (0..3).map(|k| (t[k] - c[k]).powi(2)).sum::<f64>()).fold(f64::INFINITY, f64::min);
+        assert!(nearest.sqrt() < 0.01, "center {c:?} not at a blob");
+    }
+    assert!(found.sizes.iter().all(|&n| n == per_blob));
+    for (i, &l) in found.labels.iter().enumerate() {
+        assert_eq!(found.labels[i % 8], l); // Same blob, same cluster
+    }
+
+    // Thread count doesn't change the result
+    let single = rayon::ThreadPoolBuilder::new().num_threads(1).build()?.install(|| cluster::kmeans(&points, 8, 1, 100))?;
+    let several = rayon::ThreadPoolBuilder::new().num_threads(4).build()?.install(|| cluster::kmeans(&points, 8, 1, 100))?;
+    assert_eq!(single, found);
+    assert_eq!(several, found);
+
+    // Patches on a cylinder's side
+    let (around, along) = (64_u32, 32_u32);
+    let vertices = (0..around * (along + 1))
+        .map(|i| {
+            let (phi, z) = (TAU * f64::from(i % around) / f64::from(around), f64::from(i / around) / f64::from(along));
+            [phi.cos(), phi.sin(), 4.0 * z]
+        })
+        .collect();
+    let id = |i: u32, j: u32| j * around + i % around;
+    let faces = (0..along)
+        .flat_map(|j| (0..around).flat_map(move |i| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    let cylinder = TriMesh::new(vertices, faces)?;
+    let patches = cluster::kmeans(&cluster::face_centroids(&cylinder), 16, 2, 100)?;
+    let (smallest, largest) = (patches.sizes.iter().min().unwrap(), patches.sizes.iter().max().unwrap());
+    println!("cylinder: {} faces in 16 patches of {smallest} to {largest}", cylinder.faces().len());
+    assert!(patches.sizes.iter().all(|&n| n > 0));
+
+    assert!(cluster::kmeans(&points[..3], 4, 0, 10).is_err());
+    Ok(())
+}
@@ -0,0 +1,196 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! math-poly = []
+//! math-std = []
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Accuracy and speed of each `atan2` backend in `solid_angle/math.rs`,
+//! and the kernel's throughput on the one compiled in:
+//!
+//! ```text
+//! rust-script math_example.rs [n]
+//! cd $(rust-script -p math_example.rs | tail -1) && cargo run --release --features math-poly
+//! ```
+//!
+//! Errors are against the double-double `atan2`, over angles of every
+//! quadrant and argument ratios from 10⁻²⁰ to 10²⁰, then over a grid of
+//! each sign quadrant: mantissas and exponent gaps of both arguments at
+//! three scales, and the ulps either side of the axes and diagonals. The
+//! polynomial and its SIMD forms, which must agree bit for bit, are held
+//! to 2 ulp there and to that plus `libm`'s 1.5 against `libm`. The
+//! polynomial pays off inlined in the kernel, where the loop around it
+//! vectorizes, more than called on its own.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use dd::DoubleDouble;
+use std::hint::black_box;
+use std::time::Instant;
+
+type Atan2 = fn(f64, f64) -> f64;
+
+const BACKENDS: [(&str, Atan2); 3] = [("libm", libm::atan2), ("std", f64::atan2), ("poly", math::atan2_poly)];
+
+/// As in `accuracy.rs`
+fn ulp_error(x: f64, exact: DoubleDouble) -> f64 {
+    if exact.hi == 0.0 {
+        return if x == 0.0 { 0.0 } else { f64::INFINITY };
+    }
+    let ulp = exact.hi.abs().next_up() - exact.hi.abs();
+    ((x - exact.hi) - exact.lo).abs() / ulp
+}
+
+/// Best-of-5 time of `f` (ns)
+fn best_of_5(mut f: impl FnMut()) -> f64 {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+        .as_nanos() as f64
+}
+
+/// Time per call of `f` over `pairs` (ns), through a function pointer,
+/// so this is the cost of the call itself, not of inlining it
+fn ns_per_call(pairs: &[(f64, f64)], out: &mut [f64], f: Atan2) -> f64 {
+    let ns = best_of_5(|| {
+        for (o, &(y, x)) in out.iter_mut().zip(black_box(pairs)) {
+            *o = f(y, x);
+        }
+        black_box(&mut *out);
+    });
+    ns / pairs.len() as f64
+}
+
+/// Max error of the polynomial against the double-double `atan2` and
+/// against `libm` over `pairs`, checking the SIMD forms match it exactly
+fn poly_errors(pairs: &[(f64, f64)]) -> (f64, f64) {
+    let (ys, xs): (Vec<f64>, Vec<f64>) = pairs.iter().copied().unzip();
+    let mut simd = vec![0.0; pairs.len()];
+    math::atan2_poly_slice(&ys, &xs, &mut simd).unwrap();
+    let (mut exact_max, mut libm_max) = (0.0_f64, 0.0_f64);
+    for (&(y, x), s) in pairs.iter().zip(simd) {
+        let poly = math::atan2_poly(y, x);
+        assert_eq!(poly.to_bits(), s.to_bits(), "SIMD atan2({y:e}, {x:e})");
+        let exact = DoubleDouble::atan2(DoubleDouble::from_f64(y), DoubleDouble::from_f64(x));
+        exact_max = exact_max.max(ulp_error(poly, exact));
+        libm_max = libm_max.max(ulp_error(poly, DoubleDouble::from_f64(libm::atan2(y, x))));
+    }
+    (exact_max, libm_max)
+}
+
+/// Each sign quadrant on a grid of arguments, with the neighbourhoods of
+/// its axes and diagonal, where the reduction and the fold switch over
+fn quadrant_sweep() {
+    println!("{:<10} {:>8} {:>9} {:>10}", "quadrant", "pairs", "max ulp", "vs libm");
+    for (sy, sx) in [(1.0, 1.0), (1.0, -1.0), (-1.0, -1.0), (-1.0, 1.0)] {
+        let mut pairs = Vec::new();
+        for scale in [1e-180, 1.0, 1e180] {
+            for gap in -64..=64 {
+                for (i, j) in (0..32).flat_map(|i| (0..32).map(move |j| (i, j))) {
+                    let y = (1.0 + f64::from(i) / 32.0) * 2f64.powi(gap) * scale;
+                    let x = (1.0 + f64::from(j) / 32.0) * scale;
+                    pairs.push((sy * y, sx * x));
+                }
+            }
+            // Ulps either side of y = x, and ratios of 10⁻¹⁷ by the axes
+            let (mut above, mut below) = (scale, scale);
+            for _ in 0..2048 {
+                for near in [above, below] {
+                    pairs.extend([(sy * scale, sx * near), (sy * 1e-17 * near, sx * scale), (sy * scale, sx * 1e-17 * near)]);
+                }
+                (above, below) = (above.next_up(), below.next_down());
+            }
+        }
+        let (exact, libm) = poly_errors(&pairs);
+        println!("{:<10} {:>8} {exact:>9.2} {libm:>10.2}", format!("({sy:+}, {sx:+})"), pairs.len());
+        assert!(exact <= 2.0, "polynomial atan2 out of bounds");
+        assert!(libm <= 3.5, "polynomial atan2 further from libm than both bounds allow");
+    }
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 20, |s| s.parse().unwrap());
+
+    // IEEE special cases agree with the C library exactly
+    let specials = [0.0, -0.0, 1.0, -1.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN, f64::MIN_POSITIVE, 1e-310, f64::MAX];
+    for y in specials {
+        for x in specials {
+            let (want, got) = (y.atan2(x), math::atan2_poly(y, x));
+            assert!(want.to_bits() == got.to_bits() || (want.is_nan() && got.is_nan()), "atan2({y}, {x}): {got} vs {want}");
+        }
+    }
+    let (ys, xs): (Vec<f64>, Vec<f64>) = specials.iter().flat_map(|&y| specials.map(|x| (y, x))).unzip();
+    let mut simd = vec![0.0; ys.len()];
+    math::atan2_poly_slice(&ys, &xs, &mut simd)?;
+    assert!(ys.iter().zip(&xs).zip(&simd).all(|((&y, &x), s)| math::atan2_poly(y, x).to_bits() == s.to_bits()));
+
+    // Random angles in every quadrant, at every ratio of arguments
+    let mut rng = inputs::Pcg64::new(161, 0);
+    let pairs: Vec<(f64, f64)> = (0..n)
+        .map(|_| {
+            let sign = |rng: &mut inputs::Pcg64| if rng.unit() < 0.5 { -1.0 } else { 1.0 };
+            let y = sign(&mut rng) * 10f64.powf(rng.uniform(-10.0, 10.0));
+            let x = sign(&mut rng) * 10f64.powf(rng.uniform(-10.0, 10.0));
+            (y, x)
+        })
+        .collect();
+    let exact: Vec<DoubleDouble> =
+        pairs.iter().map(|&(y, x)| DoubleDouble::atan2(DoubleDouble::from_f64(y), DoubleDouble::from_f64(x))).collect();
+
+    let mut out = vec![0.0; n];
+    println!("atan2 over {n} pairs, kernel backend {}", math::backend());
+    println!("{:<6} {:>8} {:>9} {:>10}", "", "max ulp", "mean ulp", "ns/call");
+    for (name, f) in BACKENDS {
+        let errors: Vec<f64> = pairs.iter().zip(&exact).map(|(&(y, x), &e)| ulp_error(f(y, x), e)).collect();
+        let max = errors.iter().copied().fold(0.0, f64::max);
+        let mean = errors.iter().sum::<f64>() / n as f64;
+        println!("{name:<6} {max:>8.2} {mean:>9.3} {:>10.2}", ns_per_call(&pairs, &mut out, f));
+        assert!(max <= 2.0, "{name} atan2 out of bounds");
+    }
+    let (ys, xs): (Vec<f64>, Vec<f64>) = pairs.iter().copied().unzip();
+    let simd = best_of_5(|| math::atan2_poly_slice(black_box(&ys), black_box(&xs), &mut out).unwrap()) / n as f64;
+    println!("{:<6} {:>8} {:>9} {simd:>10.2}", "simd", "", "");
+
+    quadrant_sweep();
+
+    // The kernel on the compiled backend; every SIMD path agrees with it
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 161, n);
+    let mut dispatched = vec![0.0; n];
+    let serial = best_of_5(|| tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut out).unwrap()) / n as f64;
+    let simd = best_of_5(|| dispatch::solid_angle_tetrahedron_dispatch(black_box(&tets), &mut dispatched).unwrap()) / n as f64;
+    println!("kernel: {serial:.2} ns/elem as built, {simd:.2} ns/elem dispatched ({})", dispatch::path().name());
+    assert!(out.iter().zip(&dispatched).all(|(a, b)| a.to_bits() == b.to_bits()));
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! serde_json = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! A mesh file's vital statistics, for whoever needs them without
+//! writing any Rust: vertex and face counts, connected components,
+//! volume, surface area, centroid and inertia, whether it is watertight
+//! and if not why, histograms of face quality and smallest angle, and the
+//! total solid angle and winding number the mesh subtends at each point
+//! given.
+//!
+//! ```text
+//! rust-script mesh_analyze.rs part.stl
+//! rust-script mesh_analyze.rs --point 0,0,0 --point 1,2,3.5 scan.ply
+//! rust-script mesh_analyze.rs --json --bins 16 model.msh > report.json
+//! ```
+//!
+//! Meshes are read as in `solid_angle/mesh_formats.rs`: STL, OBJ, PLY
+//! or Gmsh MSH, or `.npz` as in `solid_angle/mesh_io.rs`. The report is
+//! text on stdout, or one JSON object with `--json`.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/components.rs"]
+mod components;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_formats.rs"]
+mod mesh_formats;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/mesh_metrics.rs"]
+mod mesh_metrics;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/units.rs"]
+mod units;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TriMesh;
+use serde_json::json;
+use stats::Stats;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+const USAGE: &str = "Usage: mesh_analyze [--json] [--bins <n>] [--point <x,y,z>]... <mesh.stl|obj|ply|msh|npz>";
+
+/// Histogram bins unless `--bins` says otherwise
+const DEFAULT_BINS: usize = 20;
+
+fn read(path: &Path) -> io::Result<TriMesh> {
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("npz")) {
+        return mesh_io::read_tri_mesh_npz(BufReader::new(File::open(path)?));
+    }
+    mesh_formats::read_mesh(path)
+}
+
+/// `x,y,z`
+fn parse_point(s: &str) -> io::Result<[f64; 3]> {
+    let xyz: Vec<f64> = s.split(',').map(|x| x.trim().parse()).collect::<Result<_, _>>().map_err(|_| io::Error::other(USAGE))?;
+    xyz.try_into().map_err(|_| io::Error::other(USAGE))
+}
+
+/// A histogram as JSON: its range, and the count in each equal bin
+fn histogram_json(stats: &Stats) -> serde_json::Value {
+    json!({"min": stats.min, "max": stats.max, "mean": stats.mean, "std": stats.std, "counts": stats.histogram})
+}
+
+fn main() -> io::Result<()> {
+    let (mut as_json, mut bins, mut points, mut path) = (false, DEFAULT_BINS, Vec::new(), None);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            "--bins" => bins = args.next().and_then(|n| n.parse().ok()).ok_or_else(|| io::Error::other(USAGE))?,
+            "--point" => points.push(parse_point(&args.next().ok_or_else(|| io::Error::other(USAGE))?)?),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return Err(io::Error::other(USAGE)),
+        }
+    }
+    let path = path.ok_or_else(|| io::Error::other(USAGE))?;
+    let mesh = read(Path::new(&path))?;
+
+    let mass = mesh_metrics::mass_properties(&mesh);
+    let seal = mesh_metrics::watertightness(&mesh);
+    let labels = components::components(&mesh);
+    let components = labels.iter().copied().max().map_or(0, |n| n as usize + 1);
+    let quality = stats::summarize_bins(&mesh_metrics::face_quality(&mesh), bins);
+    let angles = stats::summarize_bins(&mesh_metrics::min_angles(&mesh), bins);
+    let mut omega = vec![0.0; points.len()];
+    multi_origin::solid_angles_multi_origin(&mesh, &points, &mut omega).map_err(io::Error::other)?;
+
+    if as_json {
+        let report = json!({
+            "file": path,
+            "vertices": mesh.vertices().len(),
+            "faces": mesh.faces().len(),
+            "components": components,
+            "volume": mass.volume,
+            "area": mass.area,
+            "centroid": mass.centroid,
+            "inertia": mass.inertia,
+            "watertight": seal.is_watertight(),
+            "boundary_edges": seal.boundary_edges,
+            "non_manifold_edges": seal.non_manifold_edges,
+            "misoriented_edges": seal.misoriented_edges,
+            "degenerate_faces": seal.degenerate_faces,
+            "face_quality": histogram_json(&quality),
+            "min_angle_deg": histogram_json(&angles),
+            "solid_angles": points.iter().zip(&omega).map(|(p, &w)| json!({"point": p, "sr": w, "winding_number": w / (4.0 * PI)})).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).map_err(io::Error::other)?);
+        return Ok(());
+    }
+
+    println!("{path}: {} vertices, {} faces, {components} component(s)", mesh.vertices().len(), mesh.faces().len());
+    println!("volume: {:.6e}", mass.volume);
+    println!("area: {:.6e}", mass.area);
+    println!("centroid: [{:.6e}, {:.6e}, {:.6e}]", mass.centroid[0], mass.centroid[1], mass.centroid[2]);
+    println!("inertia about the centroid, per unit density:");
+    for row in mass.inertia {
+        println!("    [{:>13.6e}, {:>13.6e}, {:>13.6e}]", row[0], row[1], row[2]);
+    }
+    if seal.is_watertight() {
+        println!("watertight: yes");
+    } else {
+        println!(
+            "watertight: no ({} boundary, {} non-manifold, {} misoriented edges)",
+            seal.boundary_edges, seal.non_manifold_edges, seal.misoriented_edges
+        );
+    }
+    println!("degenerate faces: {}", seal.degenerate_faces);
+    if !seal.is_watertight() || mass.volume < 0.0 {
+        println!("(volume, centroid and inertia assume a closed mesh wound outward)");
+    }
+    print!("face quality, 1 equilateral:\n{quality}");
+    print!("smallest angle (deg):\n{angles}");
+    for (p, &w) in points.iter().zip(&omega) {
+        println!("solid angle at [{}, {}, {}]: {}, winding number {:.6}", p[0], p[1], p[2], units::Sr(w), w / (4.0 * PI));
+    }
+    Ok(())
+}
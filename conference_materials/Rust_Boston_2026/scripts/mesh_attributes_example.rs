@@ -0,0 +1,75 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! serde = { version = "1", features = ["derive"] }
+//! serde_json = "1"
+//!
+//! [features]
+//! default = ["serde"]
+//! serde = []
+//! ```
+//!
+//! Per-face and per-vertex attributes riding along with a mesh, through
+//! `.npz` and through serde.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+
+use mesh::{TetMesh, TriMesh};
+use std::io::Cursor;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Tetrahedron surface, with thermal properties per face and a
+    // temperature per vertex
+    let vertices = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut surface = TriMesh::new(vertices.clone(), vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]])?;
+    surface.face_attributes_mut().insert("emissivity", vec![0.85, 0.85, 0.05, 0.9])?;
+    surface.face_attributes_mut().insert("region", vec![1.0, 1.0, 2.0, 3.0])?;
+    surface.vertex_attributes_mut().insert("temperature", vec![290.0, 310.0, 305.0, 350.0])?;
+    assert!(surface.face_attributes_mut().insert("emissivity", vec![1.0]).is_err()); // Wrong length
+
+    // Results go in place, alongside the inputs
+    surface.face_attributes_mut().insert("solid_angle", vec![0.0; 4])?;
+    surface.face_attributes_mut().get_mut("solid_angle").unwrap()[3] = 1.5;
+
+    // Through .npz, readable from numpy as mesh["face/emissivity"]
+    let mut buf = Cursor::new(Vec::new());
+    mesh_io::write_tri_mesh_npz(&mut buf, &surface)?;
+    buf.set_position(0);
+    let loaded = mesh_io::read_tri_mesh_npz(&mut buf)?;
+    assert_eq!(loaded, surface);
+    for (name, values) in loaded.face_attributes().iter() {
+        println!("face/{name}: {values:?}");
+    }
+
+    // Through serde
+    let json = serde_json::to_string(&surface)?;
+    assert_eq!(serde_json::from_str::<TriMesh>(&json)?, surface);
+
+    // Deserialization checks what the constructors check
+    let bad_index = json.replacen("[1,2,3]", "[1,2,9]", 1);
+    assert!(serde_json::from_str::<TriMesh>(&bad_index).is_err());
+    let bad_len = json.replacen("[290.0,310.0,305.0,350.0]", "[290.0]", 1);
+    assert!(serde_json::from_str::<TriMesh>(&bad_len).is_err());
+
+    // Volume mesh, with a region per cell
+    let mut volume = TetMesh::new(vertices, vec![[0, 1, 2, 3]])?;
+    volume.cell_attributes_mut().insert("region", vec![7.0])?;
+    let mut buf = Cursor::new(Vec::new());
+    mesh_io::write_tet_mesh_npz(&mut buf, &volume)?;
+    buf.set_position(0);
+    assert_eq!(mesh_io::read_tet_mesh_npz(&mut buf)?, volume);
+    let json = serde_json::to_string(&volume)?;
+    assert_eq!(serde_json::from_str::<TetMesh>(&json)?, volume);
+    println!("{json}");
+    Ok(())
+}
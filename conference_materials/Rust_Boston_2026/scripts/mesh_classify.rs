@@ -0,0 +1,157 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! indicatif = { version = "0.18", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! default = ["progress"]
+//! progress = ["dep:indicatif"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Inside or outside a mesh, for many points at once: a mesh file and a
+//! file of query points in, one value per point out, by the fast winding
+//! number of `solid_angle/winding.rs`, in parallel, with a progress bar
+//! and a per-stage timing summary on stderr.
+//!
+//! ```text
+//! rust-script mesh_classify.rs part.stl points.csv inside.csv
+//! rust-script mesh_classify.rs --winding scan.ply points.npy winding.npy
+//! rust-script mesh_classify.rs --beta 4 model.msh points.npy inside.npy
+//! ```
+//!
+//! Meshes are read as by `mesh_analyze.rs`: STL, OBJ, PLY, Gmsh MSH or
+//! `.npz`. Points are an `(n, 3)` `.npy` array or CSV rows of `x,y,z`
+//! (a header row and `#` comments are skipped). Out is `1` inside and `0`
+//! outside (winding number above or below `1/2`), or with `--winding`
+//! the winding number itself: an `(n,)` `.npy` array, or CSV rows of
+//! `x,y,z,inside` or `x,y,z,winding_number`. `--beta` trades speed for
+//! accuracy as in `solid_angle/winding.rs`; `--exact` sums every face.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_formats.rs"]
+mod mesh_formats;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/progress.rs"]
+mod progress;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/winding.rs"]
+mod winding;
+
+use mesh::TriMesh;
+use progress::Progress;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use winding::FastWinding;
+
+const USAGE: &str = "Usage: mesh_classify [--winding] [--beta <b> | --exact] <mesh.stl|obj|ply|msh|npz> <points.csv|npy> <out.csv|npy>";
+
+/// Points per progress update
+const CHUNK: usize = 1 << 14;
+
+fn is_npy(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("npy"))
+}
+
+fn read_mesh(path: &Path) -> io::Result<TriMesh> {
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("npz")) {
+        return mesh_io::read_tri_mesh_npz(BufReader::new(File::open(path)?));
+    }
+    mesh_formats::read_mesh(path)
+}
+
+/// Rows of `x,y,z`, skipping blank lines, `#` comments and a header row
+fn read_csv_points<R: BufRead>(r: R) -> io::Result<Vec<[f64; 3]>> {
+    let mut points = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row: Option<Vec<f64>> = line.split(',').map(|x| x.trim().parse().ok()).collect();
+        match row.as_deref() {
+            Some(&[x, y, z]) => points.push([x, y, z]),
+            None if points.is_empty() && i == 0 => {} // Header
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Line {} is not x,y,z", i + 1))),
+        }
+    }
+    Ok(points)
+}
+
+fn main() -> io::Result<()> {
+    let (mut winding_numbers, mut beta, mut paths) = (false, winding::DEFAULT_BETA, Vec::new());
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--winding" => winding_numbers = true,
+            "--beta" => beta = args.next().and_then(|b| b.parse().ok()).ok_or_else(|| io::Error::other(USAGE))?,
+            "--exact" => beta = f64::INFINITY,
+            _ if !arg.starts_with("--") => paths.push(arg),
+            _ => return Err(io::Error::other(USAGE)),
+        }
+    }
+    let [mesh_path, points_path, out_path] = <[String; 3]>::try_from(paths).map_err(|_| io::Error::other(USAGE))?;
+    let (points_path, out_path) = (Path::new(&points_path), Path::new(&out_path));
+    let mut progress = Progress::new();
+
+    progress.stage("read", 0);
+    let mesh = read_mesh(Path::new(&mesh_path))?;
+    let points = if is_npy(points_path) {
+        npy::read_points(&mut BufReader::new(File::open(points_path)?))?
+    } else {
+        read_csv_points(BufReader::new(File::open(points_path)?))?
+    };
+
+    progress.stage("build", 0);
+    let tree = FastWinding::new(&mesh).with_beta(beta).map_err(io::Error::other)?;
+
+    progress.stage("classify", points.len() as u64);
+    let mut out = vec![0.0; points.len()];
+    progress
+        .run_chunked(points.len(), CHUNK, |r| tree.winding_numbers(&mesh, &points[r.clone()], &mut out[r]))
+        .map_err(io::Error::other)?;
+    let inside = out.iter().filter(|&&w| w > 0.5).count();
+    if !winding_numbers {
+        out.iter_mut().for_each(|w| *w = f64::from(u8::from(*w > 0.5)));
+    }
+
+    progress.stage("write", 0);
+    let mut w = BufWriter::new(File::create(out_path)?);
+    if is_npy(out_path) {
+        npy::write_f64(&mut w, &[out.len()], &out)?;
+    } else {
+        writeln!(w, "x,y,z,{}", if winding_numbers { "winding_number" } else { "inside" })?;
+        for (p, v) in points.iter().zip(&out) {
+            writeln!(w, "{},{},{},{v}", p[0], p[1], p[2])?;
+        }
+    }
+    w.flush()?;
+
+    // Per-stage timing summary
+    for (stage, time) in progress.finish() {
+        eprintln!("{stage:>12}: {:>10.3} ms", time.as_secs_f64() * 1e3);
+    }
+    println!("{} faces, {} points: {inside} inside, {} outside", mesh.faces().len(), points.len(), points.len() - inside);
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Fixed-capacity meshes: an octahedron in a `static` matching the heap
+//! mesh's solid angles bit for bit, a sensor's rectangular field of view
+//! against its closed form and as a coverage test, capacity errors, and
+//! all of it without a single allocation, counted by a wrapper around the
+//! system allocator.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh_fixed::TriMeshFixed;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The system allocator, counting allocations
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// SAFETY: Forwards to the system allocator unchanged
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: The caller's contract is System's
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: As above; `ptr` came from System.alloc
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const OCTAHEDRON_VERTICES: [[f64; 3]; 6] = [[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+const OCTAHEDRON_FACES: [[u32; 3]; 8] = [[0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4], [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5]];
+
+/// Unit octahedron, outward-wound, checked at compile time
+static OCTAHEDRON: TriMeshFixed<6, 8> = match TriMeshFixed::from_arrays(OCTAHEDRON_VERTICES, OCTAHEDRON_FACES) {
+    Ok(mesh) => mesh,
+    Err(_) => panic!("Octahedron references a missing vertex"),
+};
+
+/// Field of view of half-angles `alpha` and `beta` about `+z`, as a
+/// closed pyramid from the sensor at the origin out to `range`
+fn field_of_view(alpha: f64, beta: f64, range: f64) -> Result<TriMeshFixed<5, 6>, &'static str> {
+    let (x, y) = (range * alpha.tan(), range * beta.tan());
+    let mut fov = TriMeshFixed::new();
+    let apex = fov.push_vertex([0.0; 3])?;
+    let corners = [[x, y], [-x, y], [-x, -y], [x, -y]].map(|[cx, cy]| fov.push_vertex([cx, cy, range]));
+    let [a, b, c, d] = [corners[0]?, corners[1]?, corners[2]?, corners[3]?];
+    for face in [[apex, b, a], [apex, c, b], [apex, d, c], [apex, a, d], [a, b, c], [a, c, d]] {
+        fov.push_face(face)?;
+    }
+    Ok(fov)
+}
+
+fn main() -> Result<(), &'static str> {
+    // Heap mesh and its results, made before counting starts
+    let heap = mesh::TriMesh::new(OCTAHEDRON_VERTICES.to_vec(), OCTAHEDRON_FACES.to_vec())?;
+    let origins: Vec<[f64; 3]> = (0..125).map(|i| [i % 5, (i / 5) % 5, i / 25].map(|k| 0.6 * k as f64 - 1.2)).collect();
+    let mut expected = vec![0.0; origins.len()];
+    multi_origin::solid_angles_multi_origin(&heap, &origins, &mut expected)?;
+    let mut got = vec![0.0; origins.len()];
+    let mut faces = [0.0; 8];
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+
+    // Same sums in the same order as the heap mesh's
+    OCTAHEDRON.solid_angles(&origins, &mut got)?;
+    assert!(got.iter().zip(&expected).all(|(a, b)| a.to_bits() == b.to_bits()));
+    assert!((OCTAHEDRON.winding_number([0.1, -0.2, 0.3]) - 1.0).abs() < 1e-15);
+    assert!(OCTAHEDRON.winding_number([0.9, 0.9, 0.0]).abs() < 1e-15);
+    OCTAHEDRON.face_solid_angles([0.0; 3], &mut faces)?;
+    assert!(faces.iter().all(|&w| (w - PI / 2.0).abs() < 1e-15)); // An octant each
+
+    // The field of view from the sensor is the base's solid angle, the
+    // sides passing through the apex; a target is covered when inside
+    let (alpha, beta) = (0.35_f64, 0.2_f64);
+    let fov = field_of_view(alpha, beta, 100.0)?;
+    let closed_form = 4.0 * (alpha.sin() * beta.sin()).asin();
+    let omega = fov.solid_angle([0.0; 3]);
+    assert!((omega - closed_form).abs() < 1e-15 * closed_form, "{omega} against {closed_form}");
+    let covered = |target: [f64; 3]| fov.winding_number(target) > 0.5;
+    assert!(covered([3.0, 1.0, 20.0]) && !covered([9.0, 1.0, 20.0]) && !covered([0.0, 0.0, 120.0]));
+
+    // Capacities hold
+    let mut small = TriMeshFixed::<3, 1>::from_slices(&OCTAHEDRON_VERTICES[..3], &[[0, 1, 2]])?;
+    assert_eq!(small.push_vertex([0.0; 3]), Err("Vertex capacity exceeded"));
+    assert_eq!(small.push_face([2, 1, 0]), Err("Face capacity exceeded"));
+    small.clear();
+    small.push_vertex([0.0; 3])?;
+    assert_eq!(small.push_face([0, 0, 1]), Err("Element references a missing vertex"));
+    assert!(TriMeshFixed::<2, 1>::from_slices(&OCTAHEDRON_VERTICES, &[]).is_err());
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(allocations, 0);
+
+    println!("octahedron: {} origins bit-identical to the heap mesh", origins.len());
+    println!("field of view {alpha} x {beta} rad: {omega:.15} sr, closed form {closed_form:.15}");
+    println!("{} bytes for the field of view, {allocations} allocations", size_of_val(&fov));
+    Ok(())
+}
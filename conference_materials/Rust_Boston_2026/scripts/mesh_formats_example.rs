@@ -0,0 +1,265 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! One box written as every mesh format `mesh_analyze.rs` reads (ASCII
+//! and binary STL, OBJ with quads and negative indices, PLY in all three
+//! encodings with properties to read past, Gmsh MSH 2.2 with triangles
+//! and 4.1 with only tetrahedra) and read back as the same solid. Its
+//! volume, centroid and inertia are checked against the closed forms,
+//! a hole and a flipped face are found, and malformed files refused.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_formats.rs"]
+mod mesh_formats;
+#[path = "solid_angle/mesh_metrics.rs"]
+mod mesh_metrics;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TriMesh;
+use mesh_formats::{read_msh, read_obj, read_ply, read_stl};
+use std::fmt::Write as _;
+use std::io;
+
+/// Corner `i` of the box from `lo` to `hi`: bit 0 for `x`, 1 for `y`, 2 for `z`
+fn corner(lo: [f64; 3], hi: [f64; 3], i: usize) -> [f64; 3] {
+    std::array::from_fn(|k| if i >> k & 1 == 1 { hi[k] } else { lo[k] })
+}
+
+/// The six faces of the box, as quads wound outward
+const QUADS: [[u32; 4]; 6] = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+
+fn boxed(lo: [f64; 3], hi: [f64; 3]) -> TriMesh {
+    let faces = QUADS.iter().flat_map(|&[a, b, c, d]| [[a, b, c], [a, c, d]]).collect();
+    TriMesh::new((0..8).map(|i| corner(lo, hi, i)).collect(), faces).expect("Indices in range")
+}
+
+fn stl_ascii(mesh: &TriMesh) -> String {
+    let mut s = String::from("solid box\n");
+    for [a, b, c] in mesh.triangles() {
+        s += "  facet normal 0 0 0\n    outer loop\n";
+        for p in [a, b, c] {
+            writeln!(s, "      vertex {:e} {:e} {:e}", p[0], p[1], p[2]).unwrap();
+        }
+        s += "    endloop\n  endfacet\n";
+    }
+    s + "endsolid box\n"
+}
+
+/// Binary STL, its header starting with `solid` as some exporters' do
+fn stl_binary(mesh: &TriMesh) -> Vec<u8> {
+    let mut b = b"solid but binary".to_vec();
+    b.resize(80, b' ');
+    b.extend_from_slice(&(mesh.faces().len() as u32).to_le_bytes());
+    for t in mesh.triangles() {
+        b.extend_from_slice(&[0; 12]);
+        t.iter().flatten().for_each(|&x| b.extend_from_slice(&(x as f32).to_le_bytes()));
+        b.extend_from_slice(&[0; 2]);
+    }
+    b
+}
+
+/// OBJ with the box's faces as quads, half of them by negative index,
+/// and lines that aren't vertices or faces
+fn obj(mesh: &TriMesh) -> String {
+    let mut s = String::from("# box\no box\n");
+    for p in mesh.vertices() {
+        writeln!(s, "v {} {} {}\nvn 0 0 1", p[0], p[1], p[2]).unwrap();
+    }
+    s += "vt 0 0\nusemtl steel\ns off\n";
+    for (i, q) in QUADS.iter().enumerate() {
+        let ids = q.map(|v| if i % 2 == 0 { format!("{}/1/1", v + 1) } else { format!("{}", v as i64 - 8) });
+        writeln!(s, "f {}", ids.join(" ")).unwrap();
+    }
+    s + "l 1 2\n"
+}
+
+/// PLY in `format`, with a color per vertex, quads as faces with a
+/// flag after their indices, and an element after them to read past
+fn ply(mesh: &TriMesh, format: &str) -> Vec<u8> {
+    let mut b = format!(
+        "ply\nformat {format} 1.0\ncomment box\nelement vertex {}\nproperty float x\nproperty float y\nproperty double z\nproperty uchar red\n\
+         element face 6\nproperty list uchar int vertex_indices\nproperty short flag\nelement edge 1\nproperty int vertex1\nproperty int vertex2\nend_header\n",
+        mesh.vertices().len()
+    )
+    .into_bytes();
+    let big = format == "binary_big_endian";
+    macro_rules! put {
+        ($x:expr) => {
+            if big {
+                b.extend_from_slice(&$x.to_be_bytes())
+            } else {
+                b.extend_from_slice(&$x.to_le_bytes())
+            }
+        };
+    }
+    if format == "ascii" {
+        for p in mesh.vertices() {
+            writeln!(appending(&mut b), "{} {} {} 255", p[0], p[1], p[2]).unwrap();
+        }
+        for q in QUADS {
+            writeln!(appending(&mut b), "4 {} {} {} {} -1", q[0], q[1], q[2], q[3]).unwrap();
+        }
+        b.extend_from_slice(b"0 1\n");
+        return b;
+    }
+    for p in mesh.vertices() {
+        put!(p[0] as f32);
+        put!(p[1] as f32);
+        put!(p[2]);
+        b.push(255);
+    }
+    for q in QUADS {
+        b.push(4);
+        q.iter().for_each(|&v| put!(v as i32));
+        put!(-1_i16);
+    }
+    put!(0_i32);
+    put!(1_i32);
+    b
+}
+
+/// Text appended to a byte buffer
+fn appending(b: &mut Vec<u8>) -> impl std::fmt::Write + '_ {
+    struct Append<'a>(&'a mut Vec<u8>);
+    impl std::fmt::Write for Append<'_> {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.extend_from_slice(s.as_bytes());
+            Ok(())
+        }
+    }
+    Append(b)
+}
+
+/// MSH 2.2: nodes tagged from 10, the box's triangles, and a line and a
+/// point element to read past, after a section to skip
+fn msh2(mesh: &TriMesh) -> String {
+    let mut s = String::from("$MeshFormat\n2.2 0 8\n$EndMeshFormat\n$PhysicalNames\n1\n2 1 \"skin\"\n$EndPhysicalNames\n");
+    writeln!(s, "$Nodes\n{}", mesh.vertices().len()).unwrap();
+    for (i, p) in mesh.vertices().iter().enumerate() {
+        writeln!(s, "{} {} {} {}", i + 10, p[0], p[1], p[2]).unwrap();
+    }
+    writeln!(s, "$EndNodes\n$Elements\n{}", mesh.faces().len() + 2).unwrap();
+    s += "1 15 2 0 1 10\n2 1 2 0 1 10 11\n";
+    for (i, f) in mesh.faces().iter().enumerate() {
+        writeln!(s, "{} 2 2 1 1 {} {} {}", i + 3, f[0] + 10, f[1] + 10, f[2] + 10).unwrap();
+    }
+    s + "$EndElements\n"
+}
+
+/// MSH 4.1 of the box as six tetrahedra about its diagonal, wound either
+/// way, and nothing else: the reader must find the boundary
+fn msh4(lo: [f64; 3], hi: [f64; 3]) -> String {
+    let mut s = String::from("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 8 1 8\n3 1 0 8\n");
+    for i in 1..=8 {
+        writeln!(s, "{i}").unwrap();
+    }
+    for i in 0..8 {
+        let p = corner(lo, hi, i);
+        writeln!(s, "{} {} {}", p[0], p[1], p[2]).unwrap();
+    }
+    s += "$EndNodes\n$Elements\n1 6 1 6\n3 1 4 6\n";
+    let axes = [[1, 2, 4], [1, 4, 2], [2, 1, 4], [2, 4, 1], [4, 1, 2], [4, 2, 1]];
+    for (i, [a, b, _]) in axes.into_iter().enumerate() {
+        // Tags from 1, so corner i is node i + 1
+        writeln!(s, "{} 1 {} {} 8", i + 1, a + 1, a + b + 1).unwrap();
+    }
+    s + "$EndElements\n"
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-12 * b.abs().max(1.0)
+}
+
+fn main() -> io::Result<()> {
+    let (lo, hi) = ([1.0, -2.0, 0.5], [3.0, -1.0, 3.5]);
+    let reference = boxed(lo, hi);
+
+    // Every format back as the same closed solid
+    let meshes = [
+        ("stl ascii", read_stl(&mut stl_ascii(&reference).as_bytes())?),
+        ("stl binary", read_stl(&mut stl_binary(&reference).as_slice())?),
+        ("obj", read_obj(&mut obj(&reference).as_bytes())?),
+        ("ply ascii", read_ply(&mut ply(&reference, "ascii").as_slice())?),
+        ("ply little", read_ply(&mut ply(&reference, "binary_little_endian").as_slice())?),
+        ("ply big", read_ply(&mut ply(&reference, "binary_big_endian").as_slice())?),
+        ("msh 2.2", read_msh(&mut msh2(&reference).as_bytes())?),
+        ("msh 4.1", read_msh(&mut msh4(lo, hi).as_bytes())?),
+    ];
+
+    // Against the closed forms for a box of sides a, b, c
+    let [a, b, c] = [0, 1, 2].map(|k| hi[k] - lo[k]);
+    let volume = a * b * c;
+    let inertia = [b * b + c * c, a * a + c * c, a * a + b * b].map(|s| volume * s / 12.0);
+    for (name, mesh) in &meshes {
+        assert_eq!((mesh.vertices().len(), mesh.faces().len()), (8, 12), "{name}");
+        let m = mesh_metrics::mass_properties(mesh);
+        assert!(close(m.volume, volume) && close(m.area, 2.0 * (a * b + b * c + c * a)), "{name}: {m:?}");
+        assert!((0..3).all(|k| close(m.centroid[k], 0.5 * (lo[k] + hi[k]))), "{name}: {m:?}");
+        for (r, row) in m.inertia.iter().enumerate() {
+            assert!((0..3).all(|k| (row[k] - if r == k { inertia[r] } else { 0.0 }).abs() < 1e-12), "{name}: {m:?}");
+        }
+        let seal = mesh_metrics::watertightness(mesh);
+        assert!(seal.is_watertight() && seal.degenerate_faces == 0, "{name}: {seal:?}");
+        println!("{name:<10} volume {:.6} area {:.6} watertight", m.volume, m.area);
+    }
+
+    // A missing face leaves a boundary, a flipped one misorients three
+    // edges, and a sliver is found by its quality
+    let mut faces = reference.faces().to_vec();
+    faces.pop();
+    let open = mesh_metrics::watertightness(&TriMesh::new(reference.vertices().to_vec(), faces.clone()).unwrap());
+    assert_eq!((open.boundary_edges, open.misoriented_edges, open.is_watertight()), (3, 0, false));
+    faces.push([reference.faces()[11][0], reference.faces()[11][2], reference.faces()[11][1]]);
+    let flipped = mesh_metrics::watertightness(&TriMesh::new(reference.vertices().to_vec(), faces).unwrap());
+    assert_eq!((flipped.boundary_edges, flipped.misoriented_edges), (0, 3));
+    let shapes = TriMesh::new(vec![[0.0; 3], [1.0, 0.0, 0.0], [0.5, 0.75_f64.sqrt(), 0.0], [0.5, 1e-6, 0.0]], vec![[0, 1, 2], [0, 1, 3], [0, 0, 1]]).unwrap();
+    let quality = mesh_metrics::face_quality(&shapes);
+    let angles = mesh_metrics::min_angles(&shapes);
+    assert!(close(quality[0], 1.0) && quality[1] < 1e-5 && quality[2] == 0.0, "{quality:?}");
+    assert!((angles[0] - 60.0).abs() < 1e-12 && angles[1] < 1e-3, "{angles:?}");
+    assert_eq!(mesh_metrics::watertightness(&shapes).degenerate_faces, 1);
+
+    // Malformed files
+    let refused = |r: io::Result<TriMesh>| r.err().map(|e| e.to_string());
+    // Short by a byte, it's read as ASCII for the `solid` its header starts
+    // with; with another header, it's binary of the wrong length
+    let mut binary = stl_binary(&reference);
+    binary.pop();
+    assert_eq!(refused(read_stl(&mut binary.as_slice())).as_deref(), Some("STL text not UTF-8"));
+    binary[..5].copy_from_slice(b"box  ");
+    assert_eq!(refused(read_stl(&mut binary.as_slice())).as_deref(), Some("STL file not of its triangle count"));
+    assert_eq!(refused(read_stl(&mut "solid x\nfacet\nouter loop\nvertex 0 0 0\nendloop\nendfacet".as_bytes())).as_deref(), Some("STL facet without three vertices"));
+    assert_eq!(refused(read_obj(&mut "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n".as_bytes())).as_deref(), Some("OBJ face index out of range"));
+    assert_eq!(refused(read_obj(&mut "v 0 0 0\nv 1 0 0\nf 1 2\n".as_bytes())).as_deref(), Some("Face with fewer than three vertices"));
+    let truncated = ply(&reference, "binary_little_endian");
+    assert_eq!(refused(read_ply(&mut &truncated[..truncated.len() - 9])).as_deref(), Some("Truncated PLY file"));
+    let huge = String::from_utf8_lossy(&ply(&reference, "ascii")).replace("element vertex 8", "element vertex 4000000000");
+    assert_eq!(refused(read_ply(&mut huge.as_bytes())).as_deref(), Some("Truncated PLY file"));
+    let listed = "ply\nformat ascii 1.0\nelement vertex 1\nproperty list uchar float x\nproperty float y\nproperty float z\nend_header\n0 1 2\n";
+    assert_eq!(refused(read_ply(&mut listed.as_bytes())).as_deref(), Some("PLY vertex coordinate is a list"));
+    let forged = format!("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 1 1 1\n{} 1 {} 1\n1\n0 0 0\n", u64::MAX, u64::MAX);
+    assert_eq!(refused(read_msh(&mut forged.as_bytes())).as_deref(), Some("Malformed MSH node block"));
+    assert_eq!(refused(read_msh(&mut "$MeshFormat\n4.1 1 8\n$EndMeshFormat\n".as_bytes())).as_deref(), Some("Binary MSH files are not supported"));
+    let stray = "$MeshFormat\n2.2 0 8\n$EndMeshFormat\n$Nodes\n3\n1 0 0 0\n2 1 0 0\n3 0 1 0\n$EndNodes\n$Elements\n1\n1 2 0 1 2 4\n$EndElements\n";
+    assert_eq!(refused(read_msh(&mut stray.as_bytes())).as_deref(), Some("MSH element on an unknown node"));
+    assert_eq!(refused(read_msh(&mut "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 1".as_bytes())).as_deref(), Some("Truncated MSH file"));
+    assert_eq!(refused(mesh_formats::read_mesh("box.3mf")).as_deref(), Some("Unknown mesh file extension"));
+    Ok(())
+}
@@ -0,0 +1,78 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! half = { version = "2", optional = true }
+//!
+//! [features]
+//! half = ["dep:half"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! `f32` vertex storage with `f64` arithmetic against all-`f64`, on a
+//! sweep large enough to be memory-bound: throughput, and the error the
+//! narrow storage costs.
+//!
+//! ```text
+//! rust-script mixed_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mixed.rs"]
+mod mixed;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Best-of-5 wall time of `f`
+fn best(mut f: impl FnMut()) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 22, |s| s.parse().unwrap());
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 145, n);
+    let narrow: Vec<[[f32; 3]; 4]> = tets.iter().map(|t| t.map(|v| v.map(|x| x as f32))).collect();
+
+    let mut exact = vec![0.0; n];
+    let mut wide_out = vec![0.0_f64; n];
+    let mut narrow_out = vec![0.0_f32; n];
+    let t_f64 = best(|| par::solid_angle_tetrahedra_par(black_box(&tets), &mut exact).unwrap());
+    let t_mixed = best(|| mixed::solid_angle_tetrahedra_mixed_par(black_box(&narrow), &mut wide_out).unwrap());
+    let t_f32 = best(|| mixed::solid_angle_tetrahedra_mixed_par(black_box(&narrow), &mut narrow_out).unwrap());
+
+    // Widening is exact, so mixed matches the f64 kernel on the widened inputs
+    let widened: Vec<[[f64; 3]; 4]> = narrow.iter().map(|t| t.map(|v| v.map(f64::from))).collect();
+    let mut reference = vec![0.0; n];
+    tetrahedron::solid_angle_tetrahedron(&widened, &mut reference)?;
+    assert!(reference.iter().zip(&wide_out).all(|(r, m)| r.to_bits() == m.to_bits()));
+
+    let max_err = |out: &mut dyn Iterator<Item = f64>| out.zip(&exact).map(|(y, e)| (y - e).abs()).fold(0.0, f64::max);
+    let ns = |t: Duration| t.as_nanos() as f64 / n as f64;
+    println!("n = {n}");
+    println!("{:<22} {:>8} {:>14}", "storage in -> out", "ns/elem", "max abs err");
+    println!("{:<22} {:>8.2} {:>14}", "f64 -> f64", ns(t_f64), "-");
+    println!("{:<22} {:>8.2} {:>14.3e}", "f32 -> f64", ns(t_mixed), max_err(&mut wide_out.iter().copied()));
+    println!("{:<22} {:>8.2} {:>14.3e}", "f32 -> f32", ns(t_f32), max_err(&mut narrow_out.iter().map(|&y| f64::from(y))));
+    Ok(())
+}
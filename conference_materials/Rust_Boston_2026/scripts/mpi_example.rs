@@ -0,0 +1,128 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! mpi = { version = "0.8", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! default = ["mpi"]
+//! mpi = ["dep:mpi"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Solid angles across MPI ranks: a scatter/gather batch from one root,
+//! a total over partitioned meshes, and winding numbers of a cube whose
+//! faces are spread over the ranks. Build once, then launch the binary
+//! under MPI, since each rank would otherwise race to compile it:
+//!
+//! ```text
+//! rust-script --package mpi_example.rs
+//! mpirun -n 4 ~/.cache/rust-script/binaries/release/mpi_example
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "mpi"))]
+compile_error!("mpi_example.rs needs the mpi feature");
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[cfg(feature = "mpi")]
+#[path = "solid_angle/distributed.rs"]
+mod distributed;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/partition.rs"]
+mod partition;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::{TetMesh, TriMesh};
+use mpi::traits::*;
+use std::f64::consts::PI;
+
+/// Box of `n³` unit cubes, six tetrahedra each
+fn box_mesh(n: u32) -> Result<TetMesh, &'static str> {
+    let id = |i: u32, j: u32, k: u32| i + (n + 1) * (j + (n + 1) * k);
+    let vertices = (0..(n + 1).pow(3))
+        .map(|v| [v % (n + 1), v / (n + 1) % (n + 1), v / (n + 1) / (n + 1)].map(f64::from))
+        .collect();
+    let mut cells = Vec::new();
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                let c = |b: u32| id(i + (b & 1), j + ((b >> 1) & 1), k + ((b >> 2) & 1));
+                for (a, b) in [(1, 3), (3, 2), (2, 6), (6, 4), (4, 5), (5, 1)] {
+                    cells.push([c(0), c(a), c(b), c(7)]);
+                }
+            }
+        }
+    }
+    TetMesh::new(vertices, cells)
+}
+
+fn main() -> Result<(), &'static str> {
+    let universe = mpi::initialize().ok_or("MPI was already initialized")?;
+    let world = universe.world();
+    let (rank, size) = (world.rank(), world.size());
+    let mesh = box_mesh(16)?;
+    let all: Vec<[[f64; 3]; 4]> = mesh.tetrahedra().collect();
+
+    // Scatter from the root and gather back, matching a local run there
+    let root = 0;
+    let (tets, mut out) = if rank == root { (&all[..], vec![0.0; all.len()]) } else { (&[][..], Vec::new()) };
+    distributed::solid_angle_tetrahedra_mpi(&world, root, tets, &mut out)?;
+    if rank == root {
+        let mut serial = vec![0.0; all.len()];
+        tetrahedron::solid_angle_tetrahedron(&all, &mut serial)?;
+        assert_eq!(out, serial);
+        println!("{size} ranks: scattered {} tetrahedra", all.len());
+    }
+
+    // Each rank owns one part of the mesh; the total matches the whole
+    let assignment = partition::partition_rcb(&mesh, size as usize)?;
+    let own: Vec<[[f64; 3]; 4]> = (0..all.len()).filter(|&c| assignment[c] == rank as u32).map(|c| all[c]).collect();
+    let total = distributed::total_solid_angle_mpi(&world, &own)?;
+    let whole: f64 = all.iter().map(|t| tetrahedron::solid_angle_tetrahedron_scalar(t[0], t[1], t[2], t[3])).sum();
+    assert!((total - whole).abs() < 1e-9 * whole);
+    if rank == root {
+        println!("total solid angle {total:.9} sr over {size} parts");
+    }
+
+    // Cube faces dealt round-robin over the ranks; winding numbers at shared points
+    let vertices: Vec<[f64; 3]> = (0..8)
+        .map(|i| [(i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5])
+        .collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads
+        .iter()
+        .flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]])
+        .enumerate()
+        .filter(|(f, _)| f % size as usize == rank as usize)
+        .map(|(_, f)| f)
+        .collect();
+    let local = TriMesh::new(vertices, faces)?;
+    let origins = [[0.0, 0.0, 0.0], [0.4, -0.3, 0.2], [3.0, 0.0, 0.0]];
+    let mut omega = [0.0; 3];
+    distributed::solid_angles_multi_origin_mpi(&world, &local, &origins, &mut omega)?;
+    let winding = omega.map(|w| w / (4.0 * PI));
+    assert!((winding[0] - 1.0).abs() < 1e-12 && (winding[1] - 1.0).abs() < 1e-12 && winding[2].abs() < 1e-12);
+    if rank == root {
+        println!("winding numbers {winding:?}");
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Solid angle of a cube seen from points inside, outside, and on it.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::PI;
+
+fn main() -> Result<(), &'static str> {
+    // Unit cube centered on the origin, outward-wound
+    let vertices = (0..8)
+        .map(|i| [(i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5])
+        .collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]]).collect();
+    let cube = mesh::TriMesh::new(vertices, faces)?;
+
+    let origins = [
+        [0.0, 0.0, 0.0],  // Center
+        [0.4, -0.3, 0.2], // Inside, off-center
+        [0.5, 0.0, 0.0],  // Face center
+        [0.5, 0.5, 0.5],  // Corner
+        [3.0, 0.0, 0.0],  // Outside
+    ];
+    let mut out = vec![0.0; origins.len()];
+    multi_origin::solid_angles_multi_origin(&cube, &origins, &mut out)?;
+
+    for (o, omega) in origins.iter().zip(&out) {
+        println!("{o:?}: {:.12} × 4π", omega / (4.0 * PI));
+    }
+    Ok(())
+}
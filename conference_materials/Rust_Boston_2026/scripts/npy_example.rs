@@ -0,0 +1,63 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! ```
+//!
+//! Solid angles of tetrahedra prepared in Python:
+//!
+//! ```text
+//! rust-script npy_example.rs tets.npy solid_angles.npy
+//! ```
+//!
+//! With no arguments, round-trips a few tetrahedra through `.npy` and `.npz`.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor};
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [tets_path, out_path] = &args[..] {
+        let tets = npy::read_tetrahedra(&mut BufReader::new(File::open(tets_path)?))?;
+        let mut out = vec![0.0; tets.len()];
+        tetrahedron::solid_angle_tetrahedron(&tets, &mut out).unwrap();
+        npy::write_f64(&mut BufWriter::new(File::create(out_path)?), &[out.len()], &out)?;
+        println!("Wrote {} solid angles to {out_path}", out.len());
+        return Ok(());
+    }
+
+    // Octant of the unit sphere, in two orientations
+    let origin = [0.0; 3];
+    let (x, y, z) = ([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]);
+    let tets = vec![[origin, x, y, z], [origin, y, x, z]];
+    let mut out = vec![0.0; tets.len()];
+    tetrahedron::solid_angle_tetrahedron(&tets, &mut out).unwrap();
+
+    // .npy
+    let mut buf = Vec::new();
+    npy::write_tetrahedra(&mut buf, &tets)?;
+    assert_eq!(buf.len() % 64, (12 * 8 * tets.len()) % 64); // Data is 64-byte aligned
+    assert_eq!(npy::read_tetrahedra(&mut &buf[..])?, tets);
+
+    // .npz
+    let mut zip = Cursor::new(Vec::new());
+    npy::write_npz(&mut zip, &[("tets", &[2, 4, 3], tets.as_flattened().as_flattened()), ("omega", &[2], &out)])?;
+    zip.set_position(0);
+    let arrays = npy::read_npz(zip)?;
+    assert_eq!(arrays[1].0, "omega");
+    assert_eq!(arrays[1].1.data, out);
+
+    println!("Octant solid angles (sr): {out:?}");
+    Ok(())
+}
@@ -0,0 +1,50 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Solid angles as one stage of a larger rayon pipeline.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use par::ParSolidAngles;
+use rayon::prelude::*;
+
+fn main() -> Result<(), &'static str> {
+    let n = 1 << 20;
+
+    // Tetrahedra generated and reduced inside one parallel chain
+    let tet = |i: usize| {
+        let s = 1.0 + i as f64 * 1e-6;
+        [[0.0; 3], [s, 0.0, 0.0], [0.0, s, 0.0], [0.0, 0.0, s]]
+    };
+    let total: f64 = (0..n).into_par_iter().map(tet).par_solid_angles().sum();
+
+    // Indexed chains can still be collected in order
+    let mut fused = Vec::new();
+    (0..n).into_par_iter().map(tet).par_solid_angles().collect_into_vec(&mut fused);
+
+    // Same as materializing the inputs and calling the slice kernel
+    let tets: Vec<_> = (0..n).map(tet).collect();
+    let mut sliced = vec![0.0; n];
+    par::solid_angle_tetrahedra_par(&tets, &mut sliced)?;
+    assert_eq!(fused, sliced);
+
+    println!("{n} octants: {total:.6} sr");
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Where the parallel driver starts to beat the serial one, with and
+//! without the serial fallback.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+type Kernel = fn(&[[[f64; 3]; 4]], &mut [f64]) -> Result<(), &'static str>;
+
+/// Best-of-10 time per element (ns)
+fn ns_per_elem(tets: &[[[f64; 3]; 4]], out: &mut [f64], f: Kernel) -> f64 {
+    let best = (0..10)
+        .map(|_| {
+            let start = Instant::now();
+            f(black_box(tets), out).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    best.as_nanos() as f64 / tets.len().max(1) as f64
+}
+
+fn main() {
+    // Empty input used to panic on a zero chunk size
+    par::solid_angle_tetrahedra_par(&[], &mut []).unwrap();
+
+    println!("{:>9} {:>10} {:>10} {:>10}", "n", "serial", "par", "par(0)");
+    for n in [10, 100, 1_000, 10_000, 100_000, 1_000_000] {
+        let tets = vec![[[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]; n];
+        let mut out = vec![0.0; n];
+
+        par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+        let serial = ns_per_elem(&tets, &mut out, tetrahedron::solid_angle_tetrahedron);
+        let fallback = ns_per_elem(&tets, &mut out, par::solid_angle_tetrahedra_par);
+        par::set_par_threshold(0);
+        let always = ns_per_elem(&tets, &mut out, par::solid_angle_tetrahedra_par);
+        println!("{n:>9} {serial:>10.2} {fallback:>10.2} {always:>10.2}");
+    }
+}
@@ -0,0 +1,109 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Recursive coordinate bisection of a box of tetrahedra, with ghost
+//! layers, and a per-part run of the batch kernel that adds up to the
+//! whole-mesh result.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/partition.rs"]
+mod partition;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TetMesh;
+
+/// Box of `nx × ny × nz` unit cubes, six tetrahedra each
+fn box_mesh(nx: u32, ny: u32, nz: u32) -> Result<TetMesh, &'static str> {
+    let id = |i: u32, j: u32, k: u32| i + (nx + 1) * (j + (ny + 1) * k);
+    let mut vertices = Vec::new();
+    for k in 0..=nz {
+        for j in 0..=ny {
+            for i in 0..=nx {
+                vertices.push([i as f64, j as f64, k as f64]);
+            }
+        }
+    }
+    let mut cells = Vec::new();
+    for k in 0..nz {
+        for j in 0..ny {
+            for i in 0..nx {
+                // Corners by bit: x = 1, y = 2, z = 4; fan around the 0-7 diagonal
+                let c = |b: u32| id(i + (b & 1), j + ((b >> 1) & 1), k + ((b >> 2) & 1));
+                for (a, b) in [(1, 3), (3, 2), (2, 6), (6, 4), (4, 5), (5, 1)] {
+                    cells.push([c(0), c(a), c(b), c(7)]);
+                }
+            }
+        }
+    }
+    TetMesh::new(vertices, cells)
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut mesh = box_mesh(12, 7, 5)?;
+    let n = mesh.cells().len();
+    mesh.cell_attributes_mut().insert("region", (0..n).map(|c| (c % 3) as f64).collect())?;
+
+    for parts in [1, 2, 5, 8] {
+        let assignment = partition::partition_rcb(&mesh, parts)?;
+        let split = partition::parts_with_ghosts(&mesh, &assignment, 1)?;
+
+        // Balanced, and every cell owned exactly once
+        let sizes: Vec<usize> = split.iter().map(|p| p.owned.len()).collect();
+        assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+        let mut owners = vec![0; n];
+        for part in &split {
+            for &c in &part.owned {
+                owners[c as usize] += 1;
+            }
+            // Ghosts are someone else's cells
+            assert!(part.ghosts.iter().all(|g| !part.owned.contains(g)));
+        }
+        assert!(owners.iter().all(|&k| k == 1));
+
+        // Local meshes carry their global IDs and a ghost flag
+        let ghosts: usize = split.iter().map(|p| p.ghosts.len()).sum();
+        println!("{parts} parts of {sizes:?} cells, {ghosts} ghost cells in total");
+        let mut total = 0.0;
+        for part in &split {
+            let local = part.to_mesh(&mesh)?;
+            let ghost = local.cell_attributes().get("ghost").unwrap();
+            let global = local.cell_attributes().get("global_id").unwrap();
+            assert_eq!(local.cell_attributes().get("region").unwrap()[0], (global[0] as usize % 3) as f64);
+
+            // Each part runs the batch kernel on its own cells
+            let tets: Vec<[[f64; 3]; 4]> = local.tetrahedra().collect();
+            let mut out = vec![0.0; tets.len()];
+            tetrahedron::solid_angle_tetrahedron(&tets, &mut out)?;
+            total += out.iter().zip(ghost).filter(|&(_, &g)| g == 0.0).map(|(x, _)| x).sum::<f64>();
+        }
+        let tets: Vec<[[f64; 3]; 4]> = mesh.tetrahedra().collect();
+        let mut out = vec![0.0; n];
+        tetrahedron::solid_angle_tetrahedron(&tets, &mut out)?;
+        let whole: f64 = out.iter().sum();
+        assert!((total - whole).abs() < 1e-9 * whole);
+    }
+
+    // Two ghost layers reach further than one
+    let assignment = partition::partition_rcb(&mesh, 4)?;
+    let one = partition::parts_with_ghosts(&mesh, &assignment, 1)?;
+    let two = partition::parts_with_ghosts(&mesh, &assignment, 2)?;
+    assert!(one.iter().zip(&two).all(|(a, b)| b.ghosts.len() > a.ghosts.len()));
+    assert!(partition::partition_rcb(&mesh, n + 1).is_err());
+    Ok(())
+}
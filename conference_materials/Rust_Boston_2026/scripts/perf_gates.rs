@@ -0,0 +1,154 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! default = ["math-poly"]
+//! math-poly = []
+//! math-std = []
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Performance regression gates, for the machine CI benchmarks on. A
+//! refactor that stops the FMA path vectorizing, or serializes the
+//! parallel drivers, still passes every correctness check, so this
+//! fails on the speedups instead:
+//!
+//! * the fastest [dispatch] path must beat the `portable` one, whose
+//!   `mul_add`s are calls to a software `fma` on x86_64, by [FMA_SPEEDUP];
+//! * the parallel driver on that path must beat the serial one by
+//!   [PAR_EFFICIENCY] times the worker threads, counting physical cores
+//!   only, as the drivers do.
+//!
+//! The FMA gate is built with the `math-poly` backend of
+//! [crate::math], since that is what lets the kernel loop vectorize; with
+//! `libm`'s `atan2` a call per element costs the same on every path and
+//! the gate has nothing to measure, so without the feature it is skipped.
+//!
+//! Timings mean nothing on a shared or throttled machine, so the gates
+//! only run with `SOLID_ANGLE_PERF_GATES` set, and otherwise report that
+//! they were skipped and pass:
+//!
+//! ```text
+//! SOLID_ANGLE_PERF_GATES=1 rust-script perf_gates.rs [n] [reps]
+//! ```
+//!
+//! Each kernel is timed best of `reps`, in alternation with the one it is
+//! compared to so that a clock change mid-run hits both.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use dispatch::Path;
+use std::hint::black_box;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+type Tet = [[f64; 3]; 4];
+
+/// Slice kernel under test, as in `bench.rs`
+type Kernel = fn(&[Tet], &mut [f64]) -> Result<(), &'static str>;
+
+/// Environment variable that turns the gates on
+const ENABLE: &str = "SOLID_ANGLE_PERF_GATES";
+
+/// Minimum speedup of the fastest dispatch path over `portable`
+const FMA_SPEEDUP: f64 = 1.5;
+
+/// Minimum parallel speedup per worker thread
+const PAR_EFFICIENCY: f64 = 0.7;
+
+/// Untimed runs of each kernel before timing, as in `bench.rs`
+const WARMUP: usize = 2;
+
+/// Best-of times of `a` and `b`, run in alternation
+fn race(tets: &[Tet], out: &mut [f64], reps: usize, a: impl Fn(&[Tet], &mut [f64]) -> Result<(), &'static str>, b: Kernel) -> Result<[Duration; 2], &'static str> {
+    for _ in 0..WARMUP {
+        a(black_box(tets), black_box(out))?;
+        b(black_box(tets), black_box(out))?;
+    }
+    let mut best = [Duration::MAX; 2];
+    for _ in 0..reps {
+        let start = Instant::now();
+        a(black_box(tets), black_box(out))?;
+        best[0] = best[0].min(start.elapsed());
+        let start = Instant::now();
+        b(black_box(tets), black_box(out))?;
+        best[1] = best[1].min(start.elapsed());
+    }
+    Ok(best)
+}
+
+/// Run `kernel` on `path`
+fn on_path(path: Path, kernel: Kernel) -> impl Fn(&[Tet], &mut [f64]) -> Result<(), &'static str> {
+    move |t, o| {
+        dispatch::set_path(Some(path))?;
+        let result = kernel(t, o);
+        dispatch::set_path(None)?;
+        result
+    }
+}
+
+/// Print one gate's result, and whether it passed
+fn gate(name: &str, slow: Duration, fast: Duration, required: f64) -> bool {
+    let speedup = slow.as_secs_f64() / fast.as_secs_f64();
+    let passed = speedup >= required;
+    let status = if passed { "ok" } else { "FAILED" };
+    println!("{status:<8} {name:<28} {speedup:>6.2}x (need {required:.2}x)");
+    passed
+}
+
+fn main() -> Result<ExitCode, &'static str> {
+    if std::env::var_os(ENABLE).is_none_or(|v| v.is_empty()) {
+        println!("Performance gates skipped; set {ENABLE}=1 on the CI benchmark machine to run them");
+        return Ok(ExitCode::SUCCESS);
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let n: usize = args.first().map_or(1 << 18, |s| s.parse().unwrap());
+    let reps: usize = args.get(1).map_or(20, |s| s.parse().unwrap());
+
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 0, n);
+    let mut out = vec![0.0; n];
+    let fastest = dispatch::path();
+    let threads = rayon::current_num_threads().min(num_cpus::get_physical());
+    println!("n = {n}, best of {reps}, dispatch path {}, {threads} worker threads", fastest.name());
+    let mut passed = true;
+
+    // The FMA path against the portable one, both serial
+    if !cfg!(feature = "math-poly") {
+        println!("skipped  {:<28} needs the math-poly feature", "fma over portable");
+    } else if fastest == Path::Portable {
+        println!("skipped  {:<28} only the portable path runs here", "fma over portable");
+    } else {
+        let portable = on_path(Path::Portable, dispatch::solid_angle_tetrahedron_dispatch);
+        let [slow, fast] = race(&tets, &mut out, reps, portable, dispatch::solid_angle_tetrahedron_dispatch)?;
+        passed &= gate(&format!("{} over portable", fastest.name()), slow, fast, FMA_SPEEDUP);
+    }
+
+    // Parallel scaling on the fastest path, with the threshold out of the way
+    par::set_par_threshold(0);
+    let [slow, fast] = race(&tets, &mut out, reps, dispatch::solid_angle_tetrahedron_dispatch, dispatch::solid_angle_tetrahedra_dispatch_par)?;
+    passed &= gate(&format!("parallel over serial, {threads}t"), slow, fast, PAR_EFFICIENCY * threads as f64);
+
+    Ok(if passed { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
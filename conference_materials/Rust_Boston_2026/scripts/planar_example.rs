@@ -0,0 +1,95 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Planar kernels: triangle area, subtended and winding angles, and
+//! point-in-polygon for an L-shaped room and a pentagram.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/batch.rs"]
+mod batch;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/planar.rs"]
+mod planar;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::{PI, TAU};
+
+fn main() -> Result<(), &'static str> {
+    // Orientation sets the sign
+    let tri = [[0.0, 0.0], [2.0, 0.0], [0.0, 1.0]];
+    let mut area = [0.0; 2];
+    planar::signed_area_triangle(&[tri, [tri[0], tri[2], tri[1]]], &mut area)?;
+    assert_eq!(area, [1.0, -1.0]);
+
+    // L-shaped room, counterclockwise
+    let room = [[0.0, 0.0], [4.0, 0.0], [4.0, 1.0], [1.0, 1.0], [1.0, 3.0], [0.0, 3.0]];
+    let probes = [[0.5, 0.5], [3.5, 0.5], [0.5, 2.5], [2.0, 2.0], [-1.0, 0.5]];
+    let mut angles = [0.0; 5];
+    let mut inside = [false; 5];
+    planar::winding_angle(&room, &probes, &mut angles)?;
+    planar::point_in_polygon(&room, &probes, &mut inside)?;
+    for ((p, a), i) in probes.iter().zip(&angles).zip(&inside) {
+        println!("{p:?}: {:+.15} × 2π, inside: {i}", a / TAU);
+    }
+    assert_eq!(inside, [true, true, true, false, false]);
+
+    // Pentagram winds its center twice
+    let star: Vec<[f64; 2]> = (0..5).map(|k| (PI / 2.0 + 2.0 * TAU * k as f64 / 5.0).sin_cos()).map(|(s, c)| [c, s]).collect();
+    let center = planar::winding_angle_scalar([0.0, 0.0], &star);
+    println!("pentagram center: {:+.15} × 2π", center / TAU);
+    assert!((center - 2.0 * TAU).abs() < 1e-12);
+
+    // Parallel driver agrees with the serial one on a grid of probes
+    let grid: Vec<[f64; 2]> = (0..200 * 200).map(|i| [(i % 200) as f64 * 0.025 - 0.5, (i / 200) as f64 * 0.025 - 0.5]).collect();
+    let (mut serial, mut parallel) = (vec![false; grid.len()], vec![false; grid.len()]);
+    planar::point_in_polygon(&room, &grid, &mut serial)?;
+    planar::point_in_polygon_par(&room, &grid, &mut parallel)?;
+    assert_eq!(serial, parallel);
+    println!("{} of {} grid points inside", serial.iter().filter(|&&b| b).count(), grid.len());
+
+    // Field of view taken up by walls, as seen by a sensor in the room
+    let wall = planar::plane_angle_segment([0.0, 0.0], [1.0, -1.0], [1.0, 1.0]);
+    assert!((wall - PI / 2.0).abs() < 1e-15);
+    let far_walls = [[4.0, 0.0], [4.0, 1.0], [1.0, 1.0], [1.0, 3.0], [0.0, 3.0]];
+    let sensors: Vec<[f64; 2]> = (0..20_000).map(|i| [0.5, 0.1 + 0.8 * i as f64 / 20_000.0]).collect();
+    let (mut serial, mut parallel) = (vec![0.0; sensors.len()], vec![0.0; sensors.len()]);
+    planar::plane_angle_polyline_multi_origin(&far_walls, &sensors, &mut serial)?;
+    planar::plane_angle_polyline_multi_origin_par(&far_walls, &sensors, &mut parallel)?;
+    assert_eq!(serial, parallel);
+    println!("far walls from {:?}: {:.6} rad", sensors[0], serial[0]);
+
+    // Each wall segment from each sensor, through the generated wrappers:
+    // the same as the scalar function, above the threshold and below
+    let segments: Vec<[[f64; 2]; 3]> = sensors.iter().flat_map(|&s| far_walls.windows(2).map(move |w| [s, w[0], w[1]])).collect();
+    let each: Vec<f64> = segments.iter().map(|&[o, p0, p1]| planar::plane_angle_segment(o, p0, p1)).collect();
+    let mut slice = vec![0.0; segments.len()];
+    planar::plane_angle_segments(&segments, &mut slice)?;
+    assert_eq!(slice, each);
+    assert_eq!(planar::plane_angle_segments_vec(&segments), each);
+    assert_eq!(planar::plane_angle_segments_vec(&segments[..10]), each[..10]);
+    assert_eq!(planar::signed_area_triangle_vec(&[tri]), [1.0]);
+    assert_eq!(planar::signed_area_triangle_par(&[tri], &mut []), Err("Dimension mismatch"));
+    Ok(())
+}
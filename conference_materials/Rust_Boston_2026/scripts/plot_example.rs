@@ -0,0 +1,196 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! flate2 = { version = "1", default-features = false, features = ["zlib-rs"], optional = true }
+//! crc32fast = { version = "1", optional = true }
+//!
+//! [features]
+//! default = ["plot"]
+//! geotiff = []
+//! plot = ["dep:flate2", "dep:crc32fast"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Quick-look plots: a histogram of tetrahedron solid angles, a heatmap
+//! of a valley's sky view factor, and a cap on the sphere in Mollweide,
+//! whose share of the ellipse is its share of the sphere. The PNGs are
+//! read back, CRCs and all.
+//!
+//! ```text
+//! rust-script plot_example.rs out_dir
+//! ```
+//!
+//! writes `histogram.svg`, `heatmap.png` and `mollweide.png` to look at.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "plot"))]
+compile_error!("plot_example.rs needs the plot feature");
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/plot.rs"]
+mod plot;
+#[path = "solid_angle/raster.rs"]
+mod raster;
+#[path = "solid_angle/spherical.rs"]
+mod spherical;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use plot::Image;
+use raster::Raster;
+use std::io::Read;
+
+/// Pixels of a PNG as [Image::write_png] writes it, checking every CRC
+fn read_png(png: &[u8]) -> Image {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let (mut at, mut size, mut idat) = (8, [0; 2], Vec::new());
+    loop {
+        let len = u32::from_be_bytes(png[at..at + 4].try_into().unwrap()) as usize;
+        let (kind, data) = (&png[at + 4..at + 8], &png[at + 8..at + 8 + len]);
+        let crc = u32::from_be_bytes(png[at + 8 + len..at + 12 + len].try_into().unwrap());
+        assert_eq!(crc32fast::hash(&png[at + 4..at + 8 + len]), crc, "CRC of {}", String::from_utf8_lossy(kind));
+        match kind {
+            b"IHDR" => {
+                size = [0, 4].map(|k| u32::from_be_bytes(data[k..k + 4].try_into().unwrap()) as usize);
+                assert_eq!(&data[8..], [8, 6, 0, 0, 0]);
+            }
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => panic!("Unexpected chunk"),
+        }
+        at += 12 + len;
+    }
+    assert_eq!(at + 12, png.len());
+
+    let mut raw = Vec::new();
+    flate2::read::ZlibDecoder::new(&idat[..]).read_to_end(&mut raw).unwrap();
+    let [width, height] = size;
+    assert_eq!(raw.len(), height * (1 + 4 * width));
+    let pixels = raw.chunks(1 + 4 * width).flat_map(|row| {
+        assert_eq!(row[0], 0);
+        row[1..].chunks(4).map(|p| p.try_into().unwrap())
+    });
+    Image { width, height, pixels: pixels.collect() }
+}
+
+fn png(image: &Image) -> Vec<u8> {
+    let mut out = Vec::new();
+    image.write_png(&mut out).unwrap();
+    out
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = std::env::args().nth(1);
+
+    // Histogram of per-element results
+    let n = 1 << 16;
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 191, n);
+    let mut omega = vec![0.0; n];
+    par::solid_angle_tetrahedra_par(&tets, &mut omega)?;
+    omega[3] = f64::NAN;
+    let s = stats::summarize(&omega);
+    let svg = plot::histogram_svg(&s, "Solid angle of random tetrahedra <sr>");
+    assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<rect ").count(), 1 + stats::DEFAULT_BINS);
+    assert!(svg.contains("&lt;sr&gt;") && svg.contains(&format!("n = {}, NaN = 1", n - 1)));
+    let peak = s.histogram.iter().max().unwrap();
+    assert!(svg.contains(&format!("<title>{peak}</title>")) && svg.contains(&format!(r#"text-anchor="end">{peak}</text>"#)));
+
+    // Colors: the ends of viridis, clamping, and NaN
+    assert_eq!(plot::colormap(0.0), [68, 1, 84, 255]);
+    assert_eq!(plot::colormap(1.0), [253, 231, 37, 255]);
+    assert_eq!(plot::colormap(-3.0), plot::colormap(0.0));
+    assert_eq!(plot::colormap(f64::INFINITY), plot::colormap(1.0));
+    assert_eq!(plot::colormap(f64::NAN), [0; 4]);
+    assert_eq!(plot::finite_range(&[f64::NAN, 2.0, -1.0]), Some([-1.0, 2.0]));
+    assert_eq!(plot::finite_range(&[f64::NAN]), None);
+
+    // Sky view factor down a V-shaped valley, its floor darkest, with a
+    // hole of no data
+    let mut dem = Raster::new(24, 16, [0.0, 16.0], 1.0);
+    for row in 0..dem.height {
+        for col in 0..dem.width {
+            dem.data[row * dem.width + col] = (col as f64 - 11.5).abs();
+        }
+    }
+    let mut svf = raster::sky_view_factor_raster(&dem, 0.1, 36)?;
+    svf.data[5 * svf.width + 2] = f64::NAN;
+    let heat = plot::heatmap(&svf, None, 4)?;
+    assert_eq!((heat.width, heat.height), (96, 64));
+    let [lo, hi] = plot::finite_range(&svf.data).unwrap();
+    let (floor, rim) = (svf.get(8, 11), svf.get(8, 0));
+    assert!(floor - lo < 1e-12 && rim > floor, "floor {floor}, rim {rim}, range {lo}..{hi}");
+    assert_eq!(heat.get(8 * 4 + 1, 11 * 4 + 3), plot::colormap(0.0));
+    assert_eq!(heat.get(5 * 4 + 3, 2 * 4), [0; 4]);
+    assert_eq!(read_png(&png(&heat)), heat);
+    let fixed = plot::heatmap(&svf, Some([0.0, 1.0]), 1)?;
+    assert_eq!(fixed.get(8, 11), plot::colormap(floor));
+
+    // A cap of 60° around +x on the sphere: a quarter of the sphere, so a
+    // quarter of the ellipse's pixels, equal area, with its center at the
+    // center of the image
+    let cap = |d: [f64; 3]| if d[0] >= 0.5 { 1.0 } else { 0.0 };
+    let map = plot::mollweide(cap, 720, None)?;
+    let inside = map.pixels.iter().filter(|p| p[3] == 255).count();
+    let lit = map.pixels.iter().filter(|&&p| p == plot::colormap(1.0)).count();
+    let share = lit as f64 / inside as f64;
+    println!("cap: {share:.4} of the ellipse (exact 0.25), ellipse {:.4} of the image (exact π/4)", inside as f64 / map.pixels.len() as f64);
+    assert!((share - 0.25).abs() < 2e-3);
+    assert!((inside as f64 / map.pixels.len() as f64 - std::f64::consts::FRAC_PI_4).abs() < 5e-3);
+    assert_eq!(map.get(180, 360), plot::colormap(1.0));
+    assert_eq!((map.get(0, 0), map.get(359, 719)), ([0; 4], [0; 4]));
+    assert_eq!(read_png(&png(&map)), map);
+
+    // Forward and inverse projection agree, poles and the dateline aside
+    for (lat, lon) in [(0.0, 0.0), (45.0, 90.0), (-30.0, -150.0), (80.0, 10.0), (-89.0, 179.0)] {
+        let d = spherical::lat_lon_to_unit(lat, lon);
+        let [x, y] = plot::mollweide_forward(d);
+        let back = plot::mollweide_inverse(x, y).unwrap();
+        assert!(vec3::norm(vec3::sub(back, d)) < 1e-9, "({lat}, {lon}): {back:?}");
+    }
+    assert!(plot::mollweide_inverse(2.9, 0.0).is_none());
+    assert_eq!(plot::mollweide_forward([0.0, 0.0, 1.0]), [0.0, std::f64::consts::SQRT_2]);
+
+    // Degenerate inputs
+    assert!(plot::heatmap(&svf, None, 0).is_err());
+    assert!(plot::mollweide(cap, 1, None).is_err());
+    assert!(Image::new(0, 3).write_png(&mut Vec::new()).is_err());
+    let flat = plot::heatmap(&Raster { data: vec![2.0; 4], ..Raster::new(2, 2, [0.0; 2], 1.0) }, None, 1)?;
+    assert_eq!(flat.pixels, vec![plot::colormap(0.5); 4]);
+
+    if let Some(dir) = out_dir {
+        let dir = std::path::Path::new(&dir);
+        std::fs::write(dir.join("histogram.svg"), svg)?;
+        std::fs::write(dir.join("heatmap.png"), png(&heat))?;
+        std::fs::write(dir.join("mollweide.png"), png(&map))?;
+        println!("wrote plots to {}", dir.display());
+    }
+    Ok(())
+}
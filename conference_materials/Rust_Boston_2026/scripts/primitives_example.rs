@@ -0,0 +1,142 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Ready-made meshes: every Platonic solid and primitive closed, manifold
+//! and wound outward (`4π` inside, 0 outside, positive volume), volumes
+//! against their closed forms, exact for the polyhedra and converging for
+//! the revolved primitives as the resolution grows, and bad parameters
+//! refused.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TriMesh;
+use std::f64::consts::PI;
+use vec3::{cross, dot, norm, sub};
+
+/// Enclosed volume, by the divergence theorem
+fn volume(mesh: &TriMesh) -> f64 {
+    mesh.triangles().map(|[a, b, c]| dot(a, cross(b, c)) / 6.0).sum()
+}
+
+/// Solid angle subtended at each of `origins`
+fn solid_angles(mesh: &TriMesh, origins: &[[f64; 3]]) -> Result<Vec<f64>, &'static str> {
+    let mut out = vec![0.0; origins.len()];
+    multi_origin::solid_angles_multi_origin(mesh, origins, &mut out)?;
+    Ok(out)
+}
+
+/// Closed, manifold and wound outward: `4π` at `inside`, nothing far
+/// away, positive volume, and a sphere's Euler characteristic
+fn check_closed(name: &str, mesh: &TriMesh, inside: [f64; 3]) -> Result<(), &'static str> {
+    let adjacency = mesh.build_adjacency();
+    assert!(adjacency.is_closed_manifold(), "{name}: not a closed manifold");
+    let euler = mesh.vertices().len() as i64 - adjacency.edges.len() as i64 + mesh.faces().len() as i64;
+    assert_eq!(euler, 2, "{name}: not a sphere");
+    let [at_inside, far] = solid_angles(mesh, &[inside, [1e3, -2e3, 5e2]])?[..] else { unreachable!() };
+    assert!((at_inside - 4.0 * PI).abs() < 1e-12 && far.abs() < 1e-12, "{name}: {at_inside} inside, {far} outside");
+    assert!(volume(mesh) > 0.0, "{name}: wound inward");
+    Ok(())
+}
+
+fn main() -> Result<(), &'static str> {
+    let center = [0.3, -1.2, 2.5];
+
+    // Platonic solids: face counts, circumradius, and volume against
+    // edge-length formulas, with a = edge
+    let golden = (1.0 + 5.0_f64.sqrt()) / 2.0;
+    let platonic = [
+        ("tetrahedron", primitives::regular_tetrahedron(center, 1.5), 4, (8.0_f64 / 3.0).sqrt(), 1.0 / (6.0 * 2.0_f64.sqrt())),
+        ("octahedron", primitives::octahedron(center, 1.5), 8, 2.0_f64.sqrt(), 2.0_f64.sqrt() / 3.0),
+        ("cube", primitives::cube(center, 1.5), 12, 2.0 / 3.0_f64.sqrt(), 1.0),
+        ("icosahedron", primitives::icosahedron(center, 1.5), 20, 2.0 / (golden * 5.0_f64.sqrt()).sqrt(), 5.0 * (3.0 + 5.0_f64.sqrt()) / 12.0),
+        ("dodecahedron", primitives::dodecahedron(center, 1.5), 60, 4.0 / (3.0_f64.sqrt() * (1.0 + 5.0_f64.sqrt())), (15.0 + 7.0 * 5.0_f64.sqrt()) / 4.0),
+    ];
+    println!("{:>12} {:>6} {:>12} {:>10}", "", "faces", "volume", "rel err");
+    for (name, mesh, faces, edge_per_radius, volume_per_edge3) in platonic {
+        check_closed(name, &mesh, center)?;
+        assert_eq!(mesh.faces().len(), faces, "{name}");
+        let radius = mesh.vertices().iter().map(|&v| norm(sub(v, center))).fold(0.0, f64::max);
+        assert!((radius - 1.5).abs() < 1e-14, "{name}: circumradius {radius}");
+        let expected = volume_per_edge3 * (edge_per_radius * 1.5_f64).powi(3);
+        let err = (volume(&mesh) - expected).abs() / expected;
+        assert!(err < 1e-14, "{name}: volume {} against {expected}", volume(&mesh));
+        println!("{name:>12} {faces:>6} {expected:>12.6} {err:>10.1e}");
+    }
+
+    // A box exactly; revolved primitives converging on their smooth forms
+    let (lo, hi) = ([-1.0, 0.5, 2.0], [3.0, 1.5, 2.25]);
+    let cuboid = primitives::cuboid(lo, hi)?;
+    check_closed("cuboid", &cuboid, [1.0, 1.0, 2.1])?;
+    assert!((volume(&cuboid) - 1.0).abs() < 1e-14);
+
+    let (a, b, r) = ([1.0, 2.0, -1.0], [2.0, 0.0, 1.0], 0.4);
+    let h = norm(sub(b, a));
+    let mid = [1.5, 1.0, 0.0];
+    println!("{:>12} {:>6} {:>12} {:>10}", "", "faces", "volume", "rel err");
+    for segments in [8, 64, 512] {
+        let rings = segments / 4;
+        let shapes = [
+            ("cylinder", primitives::cylinder(a, b, r, segments)?, PI * r * r * h),
+            ("cone", primitives::cone(a, b, r, segments)?, PI * r * r * h / 3.0),
+            ("capsule", primitives::capsule(a, b, r, segments, rings)?, PI * r * r * (h + 4.0 * r / 3.0)),
+            ("sphere", primitives::uv_sphere(mid, r, segments, rings)?, 4.0 * PI * r * r * r / 3.0),
+        ];
+        for (name, mesh, expected) in shapes {
+            let inside = if name == "cone" { [1.2, 1.6, -0.6] } else { mid };
+            check_closed(name, &mesh, inside)?;
+            let err = (volume(&mesh) - expected).abs() / expected;
+            assert!(err < 32.0 / f64::from(segments * segments), "{name}: volume {} against {expected}", volume(&mesh));
+
+            println!("{name:>12} {:>6} {expected:>12.6} {err:>10.1e}", mesh.faces().len());
+        }
+    }
+
+    // Parameters that can't make a closed mesh
+    assert_eq!(primitives::cuboid(hi, lo).err(), Some("Box corners out of order"));
+    assert_eq!(primitives::cylinder(a, b, r, 2).err(), Some("Too few segments"));
+    assert_eq!(primitives::cone(a, a, r, 8).err(), Some("Degenerate axis"));
+    assert_eq!(primitives::capsule(a, b, 0.0, 8, 2).err(), Some("Nonpositive radius"));
+    assert_eq!(primitives::uv_sphere(mid, r, 8, 0).err(), Some("Too few rings"));
+    Ok(())
+}
@@ -0,0 +1,149 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [target.'cfg(unix)'.dependencies]
+//! pprof = { version = "0.15", features = ["flamegraph"], optional = true }
+//!
+//! [features]
+//! pprof = ["dep:pprof"]
+//! rvv = []
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! The talk's workloads, looped for a fixed time so a sampling profiler
+//! gets a clear picture, for reproducing its hotspot analysis:
+//!
+//! ```text
+//! rust-script profile_solid_angle.rs [workload] [n] [threads] [seconds]
+//! ```
+//!
+//! Workloads are `serial`, `par`, `dispatch`, and `multi-origin` (a
+//! sphere mesh seen from `n / 1000` origins); the default is `par` over
+//! 2²⁰ tetrahedra on every core for 10 s. Built with the `pprof` feature
+//! on Unix, it samples itself at 997 Hz and writes
+//! `flamegraph-<workload>.svg`:
+//!
+//! ```text
+//! cd $(rust-script -p profile_solid_angle.rs | tail -1) && cargo run --release --features pprof -- par
+//! ```
+//!
+//! Without it, run under an external profiler, with debug info for
+//! readable frames:
+//!
+//! ```text
+//! CARGO_PROFILE_RELEASE_DEBUG=true cargo flamegraph -- par
+//! perf record -g --call-graph dwarf ./profile_solid_angle par && perf report
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::PI;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// One pass of a workload, repeated by the caller
+type Workload = Box<dyn FnMut() -> Result<(), &'static str> + Send>;
+
+/// Outward-wound UV sphere of `2 n²` faces
+fn sphere(n: u32) -> mesh::TriMesh {
+    let vertices = (0..=n)
+        .flat_map(|i| {
+            let theta = PI * f64::from(i) / f64::from(n);
+            (0..2 * n).map(move |j| {
+                let phi = PI * f64::from(j) / f64::from(n);
+                [theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()]
+            })
+        })
+        .collect();
+    let id = |i: u32, j: u32| i * 2 * n + j % (2 * n);
+    let faces = (0..n)
+        .flat_map(|i| (0..2 * n).flat_map(move |j| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+fn workload(name: &str, n: usize) -> Result<Workload, &'static str> {
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 0, n);
+    let mut out = vec![0.0; n];
+    Ok(match name {
+        "serial" => Box::new(move || tetrahedron::solid_angle_tetrahedron(black_box(&tets), black_box(&mut out))),
+        "par" => Box::new(move || par::solid_angle_tetrahedra_par(black_box(&tets), black_box(&mut out))),
+        "dispatch" => Box::new(move || dispatch::solid_angle_tetrahedra_dispatch_par(black_box(&tets), black_box(&mut out))),
+        "multi-origin" => {
+            let ball = sphere(64);
+            let mut rng = inputs::Pcg64::new(155, 0);
+            let origins: Vec<[f64; 3]> = (0..(n / 1000).max(1)).map(|_| rng.point().map(|x| 0.5 * x)).collect();
+            let mut out = vec![0.0; origins.len()];
+            Box::new(move || multi_origin::solid_angles_multi_origin(black_box(&ball), black_box(&origins), black_box(&mut out)))
+        }
+        _ => return Err("Unknown workload; expected serial, par, dispatch or multi-origin"),
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let name = args.first().map_or("par", |s| s.as_str());
+    let n: usize = args.get(1).map_or(Ok(1 << 20), |s| s.parse())?;
+    let threads: usize = args.get(2).map_or(Ok(num_cpus::get_physical()), |s| s.parse())?;
+    let seconds: f64 = args.get(3).map_or(Ok(10.0), |s| s.parse())?;
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+    let mut run = workload(name, n)?;
+
+    #[cfg(all(feature = "pprof", unix))]
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(997) // Prime, so sampling doesn't lock step with loops
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+
+    let (start, budget) = (Instant::now(), Duration::from_secs_f64(seconds));
+    let mut passes = 0_u64;
+    pool.install(|| -> Result<(), &'static str> {
+        while start.elapsed() < budget {
+            run()?;
+            passes += 1;
+        }
+        Ok(())
+    })?;
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("{name}: n = {n}, {threads} threads, {passes} passes in {elapsed:.1} s, {:.2} ms/pass", 1e3 * elapsed / passes as f64);
+
+    #[cfg(all(feature = "pprof", unix))]
+    {
+        let path = format!("flamegraph-{name}.svg");
+        guard.report().build()?.flamegraph(std::fs::File::create(&path)?)?;
+        println!("wrote {path}");
+    }
+
+    Ok(())
+}
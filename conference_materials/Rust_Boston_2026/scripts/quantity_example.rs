@@ -0,0 +1,161 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Typed quantities: conversions and dimensioned arithmetic, a part in
+//! millimetres queried from metres against the raw API's silent mix-up,
+//! and view factors in a room in metres with a shelf in inches and a
+//! sensor in millimetres, against the same room drawn all in metres.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/quantity.rs"]
+mod quantity;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/units.rs"]
+mod units;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TriMesh;
+use quantity::{Area, Length, LengthUnit::*, SolidAngle, UnitMesh, Volume};
+use std::f64::consts::PI;
+
+/// Relative difference
+fn rel(a: f64, b: f64) -> f64 {
+    (a - b).abs() / b.abs()
+}
+
+/// Box from `lo` to `hi` with its faces wound inward, as an enclosure
+fn room(lo: [f64; 3], hi: [f64; 3]) -> Result<TriMesh, &'static str> {
+    let outward = primitives::cuboid(lo, hi)?;
+    TriMesh::new(outward.vertices().to_vec(), outward.faces().iter().map(|&[a, b, c]| [a, c, b]).collect())
+}
+
+fn main() -> Result<(), &'static str> {
+    // Conversions, and arithmetic only where the dimensions agree
+    let inch = Length::new(1.0, Inch);
+    assert!(rel(inch.to(Millimetre), 25.4) < 1e-15 && rel(Length::new(3.0, Foot).to(Inch), 36.0) < 1e-15);
+    let (w, d, h) = (Length::new(2.0, Metre), Length::new(150.0, Centimetre), Length::new(2500.0, Millimetre));
+    let floor: Area = w * d;
+    let space: Volume = floor * h;
+    assert!(rel(floor.to(Metre), 3.0) < 1e-15 && rel(space.to(Metre), 7.5) < 1e-15);
+    assert!(rel((space / h).to(Foot), 3.0 / 0.3048_f64.powi(2)) < 1e-14 && rel((space / floor).si(), 2.5) < 1e-15);
+    assert!(rel(Area::new(1.0, Foot).to(Inch), 144.0) < 1e-14 && rel(Volume::new(1.0, Centimetre).to(Millimetre), 1e3) < 1e-14);
+    assert!(rel(w / inch, 2.0 / 0.0254) < 1e-15 && (w - d * 2.0 + 0.5 * w).to(Metre) == 0.0);
+    let sides: Length = [w, d, h].into_iter().sum();
+    assert!(rel(sides.to(Millimetre), 6000.0) < 1e-15);
+
+    // A 10 mm cube queried in metres: 4π inside, and nothing half a metre
+    // away, where the raw API, taking 0.505 for millimetres, puts the
+    // origin inside
+    let cube = UnitMesh::new(primitives::cuboid([0.0; 3], [10.0; 3])?, Millimetre);
+    assert!(rel(cube.volume().to(Centimetre), 1.0) < 1e-14 && rel(cube.area().to(Millimetre), 600.0) < 1e-14);
+    let (inside, away) = (Metre.point([0.005, 0.004, 0.006]), Metre.point([0.505, 0.005, 0.005]));
+    let typed = cube.solid_angles(&[inside, away])?;
+    assert!((typed[0].sr() - 4.0 * PI).abs() < 1e-12 && typed[1].sr().abs() < 1e-12, "{typed:?}");
+    let mut raw = [0.0];
+    multi_origin::solid_angles_multi_origin(cube.mesh(), &[[0.505, 0.005, 0.005]], &mut raw)?;
+    assert!((raw[0] - 4.0 * PI).abs() < 1e-12);
+    println!("10 mm cube from 0.5 m: {} typed, {:.4} sr raw", typed[1], raw[0]);
+    assert_eq!(typed[0].to_string(), units::Sr(typed[0].sr()).to_string());
+    assert_eq!(format!("{:.2}", typed[0].in_unit(units::SolidAngleUnit::PercentOfSphere)), "100% of sphere");
+    assert_eq!(SolidAngle::from(units::Sr(typed[1].sr())), typed[1]);
+
+    let tri = [0, 1, 2].map(|k| cube.vertex(cube.mesh().faces()[0][k] as usize));
+    let one: SolidAngle = quantity::solid_angle_triangle(away, tri);
+    let mut each = [0.0];
+    tetrahedron::solid_angle_tetrahedron(&[[[0.505, 0.005, 0.005], tri[0].map(Length::si), tri[1].map(Length::si), tri[2].map(Length::si)]], &mut each)?;
+    assert_eq!(one.sr(), each[0]);
+
+    // Everything in metres goes through bit for bit
+    let metres = cube.to(Metre);
+    assert_eq!(metres.unit(), Metre);
+    let mut bits = [0.0; 2];
+    multi_origin::solid_angles_multi_origin(metres.mesh(), &[inside, away].map(|p| p.map(Length::si)), &mut bits)?;
+    assert_eq!(metres.solid_angles(&[inside, away])?.iter().map(|q| q.sr()).collect::<Vec<_>>(), bits);
+
+    // A 3 m room, a 20 inch shelf and a 50 mm sensor, concatenated in
+    // metres, against the same assembly drawn in metres throughout
+    let shelf_m = (0.5, 0.5 + 20.0 * 0.0254);
+    let parts = [
+        UnitMesh::new(room([0.0; 3], [3.0; 3])?, Metre),
+        UnitMesh::new(primitives::cuboid([0.5 / 0.0254, 0.5 / 0.0254, 40.0], [shelf_m.1 / 0.0254, shelf_m.1 / 0.0254, 41.0])?, Inch),
+        UnitMesh::new(primitives::cuboid([2000.0, 2000.0, 500.0], [2050.0, 2050.0, 550.0])?, Millimetre),
+    ];
+    let assembled = UnitMesh::concat(&parts, Metre)?;
+    let drawn = UnitMesh::concat(
+        &[
+            parts[0].clone(),
+            UnitMesh::new(primitives::cuboid([shelf_m.0, shelf_m.0, 40.0 * 0.0254], [shelf_m.1, shelf_m.1, 41.0 * 0.0254])?, Metre),
+            UnitMesh::new(primitives::cuboid([2.0, 2.0, 0.5], [2.05, 2.05, 0.55])?, Metre),
+        ],
+        Metre,
+    )?;
+    let (f, g) = (assembled.view_factors()?, drawn.view_factors()?);
+    let worst = f.iter().zip(&g).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+    assert!(worst < 1e-12, "view factors differ by {worst}");
+    let areas = assembled.face_areas();
+    let sensor: Area = areas[24..].iter().copied().sum();
+    assert!(rel(sensor.to(Millimetre), 6.0 * 2500.0) < 1e-12);
+    assert!(rel(assembled.area().si(), 54.0 + 2.0 * (20.0_f64 * 20.0 + 2.0 * 20.0) * 0.0254 * 0.0254 + 0.015) < 1e-12);
+
+    // Left in its own units, the sensor sits two kilometres off and sees
+    // nothing of the room, without complaint
+    let mixed = TriMesh::new(
+        parts.iter().flat_map(|p| p.mesh().vertices().iter().copied()).collect(),
+        parts
+            .iter()
+            .scan(0, |base, p| {
+                let faces: Vec<[u32; 3]> = p.mesh().faces().iter().map(|t| t.map(|v| v + *base)).collect();
+                *base += p.mesh().vertices().len() as u32;
+                Some(faces)
+            })
+            .flatten()
+            .collect(),
+    )?;
+    let n = mixed.faces().len();
+    let mut wrong = vec![0.0; n * n];
+    thermal::view_factors(&mixed, &mut wrong)?;
+    let sensor_to_room = |f: &[f64]| (24..n).map(|i| f[i * n..i * n + 12].iter().sum::<f64>()).sum::<f64>() / 12.0;
+    let (right, wrong) = (sensor_to_room(&f), sensor_to_room(&wrong));
+    println!("sensor to room: {right:.4} assembled in metres, {wrong:.2e} in mixed units");
+    assert!(right > 0.5 && wrong < 1e-6);
+    Ok(())
+}
@@ -0,0 +1,124 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! A cloud of small octahedra, jittered and then scattered: the refit
+//! BVH keeps answering exactly, its SAH growth shows when scattering has
+//! made it slow, and rebuilding the degraded subtrees brings it back.
+//!
+//! ```text
+//! rust-script refit_example.rs [particles] [segments]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use bvh::Bvh;
+use mesh::TriMesh;
+use std::time::Instant;
+
+/// Octahedra of radius `r` at `centers`
+fn octahedra(centers: &[[f64; 3]], r: f64) -> TriMesh {
+    let corners = [[r, 0.0, 0.0], [-r, 0.0, 0.0], [0.0, r, 0.0], [0.0, -r, 0.0], [0.0, 0.0, r], [0.0, 0.0, -r]];
+    let vertices = centers.iter().flat_map(|c| corners.map(|d| std::array::from_fn(|k| c[k] + d[k]))).collect();
+    let faces = (0..centers.len() as u32)
+        .flat_map(|i| [[0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4], [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5]].map(|f| f.map(|v| 6 * i + v)))
+        .collect();
+    TriMesh::new(vertices, faces).unwrap()
+}
+
+/// Seconds per query over `segments`, and which were occluded; checked
+/// against a freshly built tree
+fn queries(bvh: &Bvh, mesh: &TriMesh, segments: &[([f64; 3], [f64; 3])]) -> f64 {
+    let start = Instant::now();
+    let hits: Vec<bool> = segments.iter().map(|&(a, b)| bvh.occluded(mesh, a, b)).collect();
+    let elapsed = start.elapsed().as_secs_f64() / segments.len() as f64;
+    let fresh = Bvh::new(mesh);
+    assert!(segments.iter().zip(&hits).all(|(&(a, b), &hit)| fresh.occluded(mesh, a, b) == hit), "refit tree disagrees with a fresh one");
+    elapsed
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1).map(|s| s.parse::<usize>().unwrap());
+    let (particles, n) = (args.next().unwrap_or(20_000), args.next().unwrap_or(100_000));
+    let mut rng = inputs::Pcg64::new(174, 0);
+    let segments: Vec<_> = (0..n).map(|_| (rng.point(), rng.point())).collect();
+
+    let centers: Vec<[f64; 3]> = (0..particles).map(|_| rng.point()).collect();
+    let mut mesh = octahedra(&centers, 2e-3);
+    let mut bvh = Bvh::new(&mesh);
+    assert!(bvh.growth() == 1.0 && bvh.rebuild_degraded(&mesh, 1.0) == 0);
+    let fresh = queries(&bvh, &mesh, &segments);
+    println!("{} faces, fresh: {:.0} ns/query", mesh.faces().len(), 1e9 * fresh);
+
+    // Jitter by a particle's size: refitting is all it takes
+    let jittered: Vec<[f64; 3]> = centers.iter().map(|c| c.map(|x| x + 2e-3 * (rng.unit() - 0.5))).collect();
+    mesh.vertices_mut().copy_from_slice(octahedra(&jittered, 2e-3).vertices());
+    bvh.refit(&mesh);
+    let growth = bvh.growth();
+    let jitter = queries(&bvh, &mesh, &segments);
+    println!("jittered and refit: growth {growth:.3}, {:.0} ns/query", 1e9 * jitter);
+    assert!(growth < 1.1 && bvh.rebuild_degraded(&mesh, 2.0) == 0);
+
+    // Scatter every particle: the refit tree's boxes span the cloud
+    let scattered: Vec<[f64; 3]> = (0..particles).map(|_| rng.point()).collect();
+    let moved = octahedra(&scattered, 2e-3);
+    mesh.vertices_mut().copy_from_slice(moved.vertices());
+    bvh.refit(&mesh);
+    let growth = bvh.growth();
+    let scatter = queries(&bvh, &mesh, &segments);
+    println!("scattered and refit: growth {growth:.1}, {:.0} ns/query", 1e9 * scatter);
+    assert!(growth > 10.0 && scatter > 3.0 * fresh);
+
+    // Regroup what degraded
+    let start = Instant::now();
+    let regrouped = bvh.rebuild_degraded(&mesh, 2.0);
+    let rebuild = start.elapsed();
+    let growth = bvh.growth();
+    let restored = queries(&bvh, &mesh, &segments);
+    println!("rebuilt {regrouped} faces' subtrees in {rebuild:.1?}: growth {growth:.2}, {:.0} ns/query", 1e9 * restored);
+    assert!(growth == 1.0 && restored < 2.0 * fresh);
+
+    // Stir the particles in one corner among themselves: only subtrees
+    // there are regrouped, the rest of the tree kept
+    let mut centers = scattered;
+    for c in centers.iter_mut().filter(|c| c.iter().all(|&x| x > 0.5)) {
+        *c = rng.point().map(|x| 0.75 + 0.25 * x);
+    }
+    mesh.vertices_mut().copy_from_slice(octahedra(&centers, 2e-3).vertices());
+    bvh.refit(&mesh);
+    let growth = bvh.growth();
+    let regrouped = bvh.rebuild_degraded(&mesh, 2.0);
+    let partial = queries(&bvh, &mesh, &segments);
+    println!("corner stirred: growth {growth:.1}, rebuilt {regrouped} faces' subtrees: growth {:.2}, {:.0} ns/query", bvh.growth(), 1e9 * partial);
+    assert!(regrouped < mesh.faces().len() && partial < 2.0 * fresh);
+}
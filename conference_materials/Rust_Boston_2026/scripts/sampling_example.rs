@@ -0,0 +1,159 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Directions drawn over triangles, a square and a cone: every one must
+//! land inside the shape, each part of the shape must get its share by
+//! solid angle, and Monte Carlo integrals of `cos θ` must find the
+//! closed forms, faster with stratified uniforms than with plain ones.
+//!
+//! ```text
+//! rust-script sampling_example.rs [samples per shape]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use sampling::{Cone, DirectionSampler, SphericalPolygon, SphericalTriangle};
+use std::time::Instant;
+use tetrahedron::solid_angle_tetrahedron_scalar;
+use vec3::{cross, dot, norm};
+
+/// Whether `d` lies in the cone over `triangle` from the origin, by the
+/// signs of its barycentric weights there, to within `tol`
+fn inside(d: [f64; 3], [a, b, c]: [[f64; 3]; 3], tol: f64) -> bool {
+    let det = dot(a, cross(b, c));
+    let weights = [dot(d, cross(b, c)), dot(a, cross(d, c)), dot(a, cross(b, d))].map(|w| w / det);
+    weights.iter().all(|&w| w >= -tol)
+}
+
+/// Counts against expected shares must sit within five standard
+/// deviations of a multinomial
+fn check_shares(name: &str, counts: &[usize], shares: &[f64]) {
+    let n: usize = counts.iter().sum();
+    for (i, (&count, &p)) in counts.iter().zip(shares).enumerate() {
+        let (mean, sd) = (n as f64 * p, (n as f64 * p * (1.0 - p)).sqrt());
+        assert!((count as f64 - mean).abs() < 5.0 * sd + 1.0, "{name}: part {i} got {count}, expected {mean:.0} ± {sd:.0}");
+    }
+}
+
+/// `∫ n·ω dω` over the polygon's directions from the origin, by
+/// Lambert's formula: half the sum over edges of the angle each
+/// subtends times `n` on its plane's unit normal
+fn lambert(polygon: &[[f64; 3]], n: [f64; 3]) -> f64 {
+    let sum: f64 = (0..polygon.len())
+        .map(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            let c = cross(p, q);
+            libm::atan2(norm(c), dot(p, q)) * dot(n, c) / norm(c)
+        })
+        .sum();
+    0.5 * sum.abs()
+}
+
+/// Uniforms on a jittered `k` by `k` grid, `k²` of them
+fn stratified(rng: &mut inputs::Pcg64, k: usize) -> impl Iterator<Item = [f64; 2]> + '_ {
+    (0..k * k).map(move |i| [((i / k) as f64 + rng.unit()) / k as f64, ((i % k) as f64 + rng.unit()) / k as f64])
+}
+
+fn main() {
+    let n: usize = std::env::args().nth(1).map_or(1 << 16, |s| s.parse().unwrap());
+    let mut rng = inputs::Pcg64::new(169, 0);
+
+    // Random triangles, each split at its edge midpoints into four parts
+    let start = Instant::now();
+    for _ in 0..20 {
+        let tri = [rng.point(), rng.point(), rng.point()];
+        let sampler = SphericalTriangle::new([0.0; 3], tri);
+        let mid = |i: usize, j: usize| std::array::from_fn(|k| 0.5 * (tri[i][k] + tri[j][k]));
+        let (ab, bc, ca) = (mid(0, 1), mid(1, 2), mid(2, 0));
+        let parts = [[tri[0], ab, ca], [ab, tri[1], bc], [ca, bc, tri[2]], [ab, bc, ca]];
+        let shares: Vec<f64> = parts.iter().map(|p| solid_angle_tetrahedron_scalar([0.0; 3], p[0], p[1], p[2]).abs() / sampler.solid_angle()).collect();
+        assert!((shares.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let mut counts = [0; 4];
+        for _ in 0..n {
+            let d = sampler.sample([rng.unit(), rng.unit()]);
+            assert!((norm(d) - 1.0).abs() < 1e-14 && inside(d, tri, 1e-12), "sample outside its triangle");
+            let part = parts.iter().position(|&p| inside(d, p, 0.0)).unwrap_or(3);
+            counts[part] += 1;
+        }
+        check_shares("triangle", &counts, &shares);
+    }
+    println!("20 triangles, {n} samples each: all inside, shares by solid angle; {:.0} ns/sample", 1e9 * start.elapsed().as_secs_f64() / (20 * n) as f64);
+
+    // Small and far: Ω near 1e-14, where α + β + γ - π is all rounding
+    for scale in [1e-4, 1e-6] {
+        let tri = [[1e3, 0.0, 0.0], [1e3, scale, 0.0], [1e3, 0.3 * scale, scale]];
+        let sampler = SphericalTriangle::new([0.0; 3], tri);
+        for _ in 0..n / 16 {
+            let d = sampler.sample([rng.unit(), rng.unit()]);
+            assert!(inside(d, tri, 1e-6), "sample outside a small triangle");
+        }
+        println!("triangle {scale:.0e} across at 1e3: Ω = {:.3e} sr, samples inside", sampler.solid_angle());
+    }
+
+    // Square off to one side, split into quadrants across the fan
+    let square = [[1.0, -0.5, 2.0], [2.0, -0.5, 2.0], [2.0, 0.5, 2.0], [1.0, 0.5, 2.0]];
+    let sampler = SphericalPolygon::new([0.0; 3], &square).unwrap();
+    let center = [1.5, 0.0, 2.0];
+    let quads: Vec<[[f64; 3]; 4]> = (0..4)
+        .map(|i| {
+            let (p, q) = (square[i], square[(i + 1) % 4]);
+            let mid = |x: [f64; 3], y: [f64; 3]| std::array::from_fn(|k| 0.5 * (x[k] + y[k]));
+            [mid(square[(i + 3) % 4], p), p, mid(p, q), center]
+        })
+        .collect();
+    let omega = |q: &[[f64; 3]; 4]| solid_angle_tetrahedron_scalar([0.0; 3], q[0], q[1], q[2]).abs() + solid_angle_tetrahedron_scalar([0.0; 3], q[0], q[2], q[3]).abs();
+    let shares: Vec<f64> = quads.iter().map(|q| omega(q) / sampler.solid_angle()).collect();
+    let mut counts = [0; 4];
+    for _ in 0..n {
+        let d = sampler.sample([rng.unit(), rng.unit()]);
+        let part = quads.iter().position(|q| inside(d, [q[0], q[1], q[2]], 1e-12) || inside(d, [q[0], q[2], q[3]], 1e-12));
+        counts[part.expect("sample outside the square")] += 1;
+    }
+    check_shares("square", &counts, &shares);
+
+    // Its cosine integral about +z, plain against stratified
+    let exact = lambert(&square, [0.0, 0.0, 1.0]);
+    let k = (n as f64).sqrt() as usize;
+    let plain: f64 = (0..k * k).map(|_| sampler.sample([rng.unit(), rng.unit()])[2]).sum::<f64>() * sampler.solid_angle() / (k * k) as f64;
+    let strat: f64 = stratified(&mut rng, k).map(|u| sampler.sample(u)[2]).sum::<f64>() * sampler.solid_angle() / (k * k) as f64;
+    println!("square: ∫cos θ dω = {exact:.6}; relative error {:.1e} plain, {:.1e} stratified", (plain - exact).abs() / exact, (strat - exact).abs() / exact);
+    assert!((plain - exact).abs() / exact < 1e-2 && (strat - exact).abs() / exact < 1e-4);
+
+    // Cone: within the half-angle, mean cos θ = (1 + cos α) / 2
+    let (axis, half_angle) = ([1.0, -2.0, 0.5], 0.7_f64);
+    let cone = Cone::new(axis, half_angle);
+    let axis = axis.map(|x| x / norm(axis));
+    let mut sum = 0.0;
+    for _ in 0..n {
+        let d = cone.sample([rng.unit(), rng.unit()]);
+        let cos = dot(d, axis);
+        assert!((norm(d) - 1.0).abs() < 1e-14 && cos >= half_angle.cos() - 1e-15, "sample outside the cone");
+        sum += cos;
+    }
+    let (mean, expected) = (sum / n as f64, 0.5 * (1.0 + half_angle.cos()));
+    let sd = (1.0 - half_angle.cos()) / 12_f64.sqrt() / (n as f64).sqrt(); // cos θ is uniform
+    println!("cone: mean cos θ {mean:.5}, expected {expected:.5}; pdf {:.4}/sr", cone.pdf());
+    assert!((mean - expected).abs() < 5.0 * sd);
+}
@@ -0,0 +1,191 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A spacecraft as a scene: a bus, two wings of shared panels (one
+//! mirrored) and an antenna. Occlusion and coverage through the two-level
+//! BVH against one BVH over the flattened assembly, shadowed view factors
+//! between plates with and without a screen between them, and named
+//! per-instance attributes.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/instance.rs"]
+mod instance;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/scene.rs"]
+mod scene;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/visibility.rs"]
+mod visibility;
+
+use instance::{Affine3, Instance};
+use mesh::TriMesh;
+use scene::Scene;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Every instance placed and concatenated into one mesh
+fn flatten(instances: &[Instance]) -> Result<TriMesh, &'static str> {
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    for placed in instances.iter().map(Instance::to_mesh) {
+        let base = vertices.len() as u32;
+        faces.extend(placed.faces().iter().map(|f| f.map(|v| v + base)));
+        vertices.extend_from_slice(placed.vertices());
+    }
+    TriMesh::new(vertices, faces)
+}
+
+/// Random point in the cube of half-width `half` about the origin
+fn point(rng: &mut inputs::Pcg64, half: f64) -> [f64; 3] {
+    rng.point().map(|x| half * x)
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut rng = inputs::Pcg64::new(186, 0);
+
+    // A box bus, wings of 40 panels each along ±y, the -y wing the +y one
+    // mirrored, and an antenna on top
+    let bus = Arc::new(primitives::cuboid([-1.0; 3], [1.0; 3])?);
+    let panel = Arc::new(primitives::cuboid([-0.6, -0.45, -0.02], [0.6, 0.45, 0.02])?);
+    let antenna = Arc::new(primitives::cone([0.0; 3], [0.0, 0.0, 1.5], 0.7, 32)?);
+    let mirror = Affine3 { linear: [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]], translation: [0.0; 3] };
+    let mut parts = vec![("bus".to_string(), Instance::new(bus, Affine3::IDENTITY))];
+    for side in ["+y", "-y"] {
+        for k in 0..40 {
+            let (row, col) = (f64::from(k / 2), f64::from(k % 2));
+            let at = Affine3::rotation([0.0, 1.0, 0.0], 0.3).then(&Affine3::translation([1.25 * col - 0.6, 1.6 + 0.95 * row, 0.0]));
+            let at = if side == "-y" { at.then(&mirror) } else { at };
+            parts.push((format!("wing{side}/panel{k}"), Instance::new(panel.clone(), at)));
+        }
+    }
+    parts.push(("antenna".to_string(), Instance::new(antenna, Affine3::translation([0.0, 0.0, 1.0]))));
+    let mut spacecraft = Scene::new(parts.clone())?;
+    assert_eq!((spacecraft.instances().len(), spacecraft.meshes()), (82, 3));
+    let flat = flatten(spacecraft.instances())?;
+    assert_eq!(spacecraft.faces(), flat.faces().len());
+    assert_eq!(spacecraft.triangle(200), flat.triangle(200));
+    assert!((0..3).all(|k| spacecraft.bounds().lo[k] <= -1.0 && spacecraft.bounds().hi[k] >= 1.0));
+
+    // Names and per-instance attributes
+    let temperature: Vec<f64> = spacecraft.names().iter().map(|n| if n.starts_with("wing") { 330.0 } else { 290.0 }).collect();
+    spacecraft.instance_attributes_mut().insert("temperature", temperature)?;
+    let antenna = spacecraft.find("antenna").ok_or("No antenna")?;
+    assert_eq!(spacecraft.instance_attributes().get("temperature").map(|t| t[antenna]), Some(290.0));
+    assert_eq!(spacecraft.instance_attributes_mut().insert("emissivity", vec![0.9; 3]).err(), Some("Dimension mismatch"));
+    parts.push(("bus".to_string(), parts[0].1.clone()));
+    assert_eq!(Scene::new(parts).err(), Some("Duplicate instance name"));
+
+    // Solid angle: 4π inside the bus and inside the mirrored wing's panels
+    let inside_panel = spacecraft.instances()[spacecraft.find("wing-y/panel7").ok_or("No panel")?].transform.apply([0.1, 0.2, 0.0]);
+    let mut omega = [0.0; 3];
+    spacecraft.solid_angles(&[[0.2, 0.3, -0.4], inside_panel, [0.0, 0.0, 40.0]], &mut omega)?;
+    assert!((omega[0] - 4.0 * PI).abs() < 1e-11 && (omega[1] - 4.0 * PI).abs() < 1e-11 && omega[2].abs() < 1e-11, "{omega:?}");
+
+    // Occlusion through both levels against the flattened BVH
+    let flat_bvh = bvh::Bvh::new(&flat);
+    let segments: Vec<([f64; 3], [f64; 3])> = (0..50_000).map(|_| (point(&mut rng, 25.0), point(&mut rng, 25.0))).collect();
+    let start = Instant::now();
+    let two_level: Vec<bool> = segments.iter().map(|&(a, b)| spacecraft.occluded(a, b)).collect();
+    let t_scene = start.elapsed();
+    let start = Instant::now();
+    let one_level: Vec<bool> = segments.iter().map(|&(a, b)| flat_bvh.occluded(&flat, a, b)).collect();
+    let t_flat = start.elapsed();
+    let differ = two_level.iter().zip(&one_level).filter(|(a, b)| a != b).count();
+    let blocked = two_level.iter().filter(|&&b| b).count();
+    assert!(differ <= segments.len() / 1000, "{differ} of {} segments disagree", segments.len());
+    println!("{blocked} of {} segments blocked, {differ} disagree: {t_scene:?} in two levels, {t_flat:?} flattened", segments.len());
+
+    // Coverage: which ground stations on a sphere see which points about
+    // the spacecraft, against the flattened mesh
+    let stations: Vec<[f64; 3]> = (0..64)
+        .map(|_| {
+            let p = point(&mut rng, 1.0);
+            p.map(|x| 30.0 * x / vec3::norm(p))
+        })
+        .collect();
+    let sensors: Vec<[f64; 3]> = (0..200).map(|_| point(&mut rng, 4.0)).collect();
+    let coverage = spacecraft.visibility_matrix(&stations, &sensors);
+    let expected = visibility::visibility_matrix(&stations, &sensors, &flat, &flat_bvh)?;
+    assert!(coverage == expected, "Coverage disagrees with the flattened mesh");
+    println!("{} of {} station-sensor pairs in view", coverage.count(), stations.len() * sensors.len());
+
+    // Moving the antenna out of the way clears the segments it blocked
+    let (over, under) = ([0.0, 0.0, 10.0], [0.0, 0.0, 1.5]);
+    assert!(spacecraft.occluded(over, under));
+    spacecraft.set_transform(antenna, Affine3::translation([0.0, -3.0, 1.0]))?;
+    assert!(!spacecraft.occluded(over, under));
+    assert_eq!(spacecraft.set_transform(99, Affine3::IDENTITY).err(), Some("Dimension mismatch"));
+
+    // Two unit plates facing each other across a gap, then a larger screen
+    // between them facing the lower: exchange between the plates drops to
+    // nothing, and without the screen matches the unshadowed matrix
+    let plate = Arc::new(TriMesh::new(vec![[-0.5, -0.5, 0.0], [0.5, -0.5, 0.0], [0.5, 0.5, 0.0], [-0.5, 0.5, 0.0]], vec![[0, 1, 2], [0, 2, 3]])?);
+    let facing = Affine3 { linear: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]], translation: [0.0, 0.0, 1.0] };
+    let plates = vec![("lower".to_string(), Instance::new(plate.clone(), Affine3::IDENTITY)), ("upper".to_string(), Instance::new(plate.clone(), facing))];
+    let mut screened = plates.clone();
+    screened.push(("screen".to_string(), Instance::new(plate, Affine3::scaling(2.0).then(&facing).then(&Affine3::translation([0.0, 0.0, -0.5])))));
+    let (open, screened) = (Scene::new(plates)?, Scene::new(screened)?);
+    let n = open.faces();
+    let (mut f, mut expected) = (vec![0.0; n * n], vec![0.0; n * n]);
+    open.view_factors(&mut f)?;
+    instance::view_factors(open.instances(), &mut expected)?;
+    assert_eq!(f, expected);
+    let between: f64 = (0..2).flat_map(|i| (2..4).map(move |j| (i, j))).map(|(i, j)| f[i * n + j]).sum::<f64>() / 2.0;
+    assert!(between > 0.0, "The plates don't see each other");
+
+    let m = screened.faces();
+    let mut g = vec![0.0; m * m];
+    screened.view_factors(&mut g)?;
+    let blocked = (0..2).flat_map(|i| (2..4).map(move |j| (i, j))).all(|(i, j)| g[i * m + j] == 0.0 && g[j * m + i] == 0.0);
+    assert!(blocked, "The screen doesn't shadow the plates");
+    assert!(g[4] + g[5] > 0.0, "The lower plate doesn't see the screen");
+    println!("plate to plate: {between:.4} open, 0 screened");
+    Ok(())
+}
@@ -0,0 +1,198 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Signed distance functions: exact values where they have closed forms,
+//! never steeper than distance for any solid or combination, inside
+//! exactly where the matching meshes of `primitives.rs` wind once, and
+//! occlusion by sphere tracing against the BVH on the same polyhedron
+//! and against dense sampling on a cut and filleted occluder.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sdf.rs"]
+mod sdf;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use sdf::{Capsule, Cone, ConvexPolyhedron, Cuboid, Cylinder, Sdf, Sphere};
+use std::f64::consts::PI;
+use vec3::{norm, sub};
+
+/// Random point in the cube of half-width `half` about the origin
+fn point(rng: &mut inputs::Pcg64, half: f64) -> [f64; 3] {
+    rng.point().map(|x| half * x)
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut rng = inputs::Pcg64::new(184, 0);
+    let (a, b, r) = ([0.5, -0.5, -1.0], [-0.5, 0.5, 1.0], 0.6);
+    let h = norm(sub(b, a));
+    let axis = sub(b, a).map(|x| x / h);
+    let sphere = Sphere { center: [0.2, 0.1, -0.3], radius: 1.3 };
+    let cuboid = Cuboid { lo: [-1.0, -0.5, -1.5], hi: [1.5, 0.5, 1.0] };
+    let capsule = Capsule { a, b, radius: r };
+    let cylinder = Cylinder { a, b, radius: r };
+    let cone = Cone { base: a, apex: b, radius: r };
+    let icosahedron = ConvexPolyhedron::new(&primitives::icosahedron([0.1, 0.0, -0.2], 1.6))?;
+
+    // Closed forms: off a box's corner, beyond a cylinder's rim and a
+    // cone's apex, inside a capsule
+    let along = |t: f64, out: f64| std::array::from_fn(|k| a[k] + t * axis[k] + out * [1.0, 1.0, 0.0][k] / 2.0_f64.sqrt());
+    let exact = [
+        (cuboid.distance([2.5, 2.5, 3.0]), 3.0),
+        (sphere.distance(sphere.center), -1.3),
+        (cylinder.distance(along(h + 0.3, r + 0.4)), 0.5),
+        (cylinder.distance(along(0.5 * h, 0.1)), -0.5),
+        (cone.distance(along(h + 0.7, 0.0)), 0.7),
+        (cone.distance(along(-0.2, 0.3)), 0.2),
+        (capsule.distance(along(-0.1, 0.0)), -0.5),
+    ];
+    for (i, &(got, expected)) in exact.iter().enumerate() {
+        assert!((got - expected).abs() < 1e-14, "Closed form {i}: {got} against {expected}");
+    }
+
+    // Never steeper than distance, blended and cut solids included
+    let shapes: Vec<(&str, Box<dyn Sdf + Sync>)> = vec![
+        ("sphere", Box::new(sphere)),
+        ("cuboid", Box::new(cuboid)),
+        ("capsule", Box::new(capsule)),
+        ("cylinder", Box::new(cylinder)),
+        ("cone", Box::new(cone)),
+        ("icosahedron", Box::new(icosahedron.clone())),
+        ("union", Box::new(sphere.union(cone))),
+        ("intersection", Box::new(cuboid.intersection(capsule))),
+        ("difference", Box::new(cuboid.difference(cylinder))),
+        ("smooth union", Box::new(cylinder.smooth_union(sphere, 0.4))),
+    ];
+    for (name, shape) in &shapes {
+        for _ in 0..20_000 {
+            let (p, q) = (point(&mut rng, 3.0), point(&mut rng, 3.0));
+            let (dp, dq) = (shape.distance(p), shape.distance(q));
+            assert!((dp - dq).abs() <= norm(sub(p, q)) * (1.0 + 1e-12), "{name}: steeper than distance between {p:?} and {q:?}");
+        }
+    }
+
+    // Inside exactly where the meshes wind once, but for faceting
+    let meshes = [
+        ("sphere", Box::new(sphere) as Box<dyn Sdf + Sync>, primitives::uv_sphere(sphere.center, sphere.radius, 64, 16)?),
+        ("cuboid", Box::new(cuboid), primitives::cuboid(cuboid.lo, cuboid.hi)?),
+        ("capsule", Box::new(capsule), primitives::capsule(a, b, r, 64, 16)?),
+        ("cylinder", Box::new(cylinder), primitives::cylinder(a, b, r, 64)?),
+        ("cone", Box::new(cone), primitives::cone(a, b, r, 64)?),
+        ("icosahedron", Box::new(icosahedron.clone()), primitives::icosahedron([0.1, 0.0, -0.2], 1.6)),
+    ];
+    let points: Vec<[f64; 3]> = (0..4000).map(|_| point(&mut rng, 2.0)).collect();
+    println!("{:>12} {:>8} {:>8}", "", "inside", "checked");
+    for (name, shape, mesh) in &meshes {
+        let mut d = vec![0.0; points.len()];
+        sdf::distances(shape, &points, &mut d)?;
+        let mut omega = vec![0.0; points.len()];
+        multi_origin::solid_angles_multi_origin(mesh, &points, &mut omega)?;
+        let sagitta = 1.3 * (1.0 - (PI / 32.0).cos()); // Generous, for quads across
+        let checked: Vec<(f64, f64)> = d.into_iter().zip(omega).filter(|(d, _)| d.abs() > sagitta).collect();
+        let inside = checked.iter().filter(|(d, _)| *d < 0.0).count();
+        assert!(checked.iter().all(|&(d, w)| (d < 0.0) == (w > 2.0 * PI)), "{name}: inside the surface but not the mesh");
+        println!("{name:>12} {inside:>8} {:>8}", checked.len());
+    }
+
+    // Combinators as set operations, and the blend within its bound
+    let (u, i, diff) = (sphere.union(cone), cuboid.intersection(capsule), cuboid.difference(cylinder));
+    let smooth = cylinder.smooth_union(sphere, 0.4);
+    for &p in &points {
+        assert_eq!(u.contains(p), sphere.contains(p) || cone.contains(p));
+        assert_eq!(i.contains(p), cuboid.contains(p) && capsule.contains(p));
+        assert_eq!(diff.contains(p), cuboid.contains(p) && !cylinder.contains(p));
+        let hard = cylinder.distance(p).min(sphere.distance(p));
+        assert!(smooth.distance(p) <= hard && smooth.distance(p) >= hard - 0.1);
+    }
+
+    // Segments past the icosahedron, by sphere tracing and through its
+    // mesh's BVH, including segments from points on its surface. Inside,
+    // the solid blocks and the surface needn't; ends are kept outside.
+    let ico_mesh = primitives::icosahedron([0.1, 0.0, -0.2], 1.6);
+    let bvh = bvh::Bvh::new(&ico_mesh);
+    let mut outside = || loop {
+        let p = point(&mut rng, 4.0);
+        if !icosahedron.contains(p) {
+            break p;
+        }
+    };
+    let mut grazing = 0;
+    for k in 0..20_000 {
+        let from = if k % 4 == 0 { ico_mesh.vertices()[k % 12] } else { outside() };
+        let to = outside();
+        if icosahedron.occluded(from, to) == bvh.occluded(&ico_mesh, from, to) {
+            continue;
+        }
+        // Only leaving the surface within a few milliradians of tangent,
+        // slower than MAX_STEPS can follow
+        let near = std::array::from_fn(|j| from[j] + 1e-3 * (to[j] - from[j]));
+        let slope = icosahedron.distance(near) / (1e-3 * norm(sub(to, from)));
+        assert!(k % 4 == 0 && slope < 1e-2, "Sphere tracing and the BVH disagree from {from:?} to {to:?}");
+        grazing += 1;
+    }
+
+    // A procedural occluder, a plate with a hole and a filleted post,
+    // against dense sampling along each segment
+    let plate = Cuboid { lo: [-2.0, -2.0, 1.0], hi: [2.0, 2.0, 1.2] }.difference(Cylinder { a: [0.5, 0.0, 0.9], b: [0.5, 0.0, 1.3], radius: 0.4 });
+    let post = Capsule { a: [-1.0, 0.5, 1.2], b: [-1.0, 0.5, 2.5], radius: 0.2 };
+    let occluder = plate.smooth_union(post, 0.2);
+    let (mut blocked, mut mismatched) = (0, 0);
+    let n = 2000;
+    for _ in 0..n {
+        let dir = point(&mut rng, 1.0);
+        let to = dir.map(|x| 5.0 * x / norm(dir).max(1e-9));
+        let traced = occluder.occluded([0.0; 3], to);
+        let sampled = (1..4096).any(|j| occluder.contains(to.map(|x| x * f64::from(j) / 4096.0)));
+        blocked += usize::from(traced);
+        mismatched += usize::from(traced != sampled);
+    }
+    assert!(mismatched <= n / 200, "{mismatched} of {n} segments disagree with sampling");
+    println!("icosahedron: sphere tracing agrees with the BVH but for {grazing} of 20000 segments, grazing");
+    println!("occluder blocks {blocked} of {n} directions; {mismatched} disagree with sampling");
+    Ok(())
+}
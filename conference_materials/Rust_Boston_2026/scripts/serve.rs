@@ -0,0 +1,87 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! axum = { version = "0.8", optional = true }
+//! tokio = { version = "1", features = ["rt-multi-thread", "macros", "net"], optional = true }
+//! futures-util = { version = "0.3", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! tikv-jemallocator = { version = "0.6", optional = true }
+//! mimalloc = { version = "0.1", optional = true }
+//!
+//! [features]
+//! default = ["serve"]
+//! jemalloc = ["dep:tikv-jemallocator"]
+//! mimalloc = ["dep:mimalloc"]
+//! serve = ["dep:axum", "dep:tokio", "dep:futures-util"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! The kernels as an HTTP service; see `solid_angle/service.rs` for the
+//! routes:
+//!
+//! ```text
+//! rust-script serve.rs [address] [max .npz body in MiB] [queue budget in MiB]
+//! curl --data-binary @tets.bin localhost:8080/solid_angles > solid_angles.bin
+//! ```
+//!
+//! The `jemalloc` and `mimalloc` features swap the global allocator, as
+//! for the CLI; the allocator in use is logged at startup.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "serve"))]
+compile_error!("serve.rs needs the serve feature");
+
+#[path = "solid_angle/alloc.rs"]
+mod alloc;
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[cfg(feature = "serve")]
+#[path = "solid_angle/batch_queue.rs"]
+mod batch_queue;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[cfg(feature = "serve")]
+#[path = "solid_angle/service.rs"]
+mod service;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+/// Tetrahedra per coalesced launch, well above the parallel threshold
+const BATCH: usize = 1 << 16;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let address = args.first().map_or("127.0.0.1:8080", |a| a.as_str());
+    let mib = |i: usize, default: usize| args.get(i).map_or(Ok(default), |m| m.parse::<usize>()).map_err(std::io::Error::other);
+    let (max_body, budget) = (mib(1, 256)? << 20, mib(2, 1024)? << 20);
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    println!("Serving on http://{} ({} allocator)", listener.local_addr()?, alloc::ALLOCATOR);
+    let queue = batch_queue::BatchQueue::new(BATCH, budget);
+    axum::serve(listener, service::router(max_body, queue)).await
+}
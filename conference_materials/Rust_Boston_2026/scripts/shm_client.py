@@ -0,0 +1,84 @@
+"""Drive a resident shm_worker.rs through a shared-memory file.
+
+Tetrahedra go into the file as packed float64, the worker is told where
+they are over its stdin, and the solid angles are read back in place,
+with no serialization or process spawn per call.
+
+    uv run scripts/shm_client.py [path to built shm_worker]
+"""
+
+import mmap
+import subprocess
+import sys
+import time
+from pathlib import Path
+
+import numpy as np
+
+SHM_PATH = Path("/dev/shm/solid_angle_example")
+
+
+class Worker:
+    def __init__(self, command: list[str], path: Path, size: int) -> None:
+        self.path = path
+        with open(path, "wb") as f:
+            f.truncate(size)
+        self.file = open(path, "r+b")
+        self.map = mmap.mmap(self.file.fileno(), 0)
+        self.proc = subprocess.Popen(
+            [*command, str(path)], stdin=subprocess.PIPE, stdout=subprocess.PIPE, text=True
+        )
+
+    def request(self, line: str) -> None:
+        self.proc.stdin.write(line + "\n")
+        self.proc.stdin.flush()
+        reply = self.proc.stdout.readline().strip()
+        if reply != "ok":
+            raise RuntimeError(reply or "worker exited")
+
+    def solid_angles(self, tets: np.ndarray) -> np.ndarray:
+        """Solid angles of an (n, 4, 3) array of tetrahedra"""
+        n = len(tets)
+        out_offset = tets.nbytes
+        if out_offset + 8 * n > len(self.map):
+            self.map.close()
+            self.file.truncate(out_offset + 8 * n)
+            self.map = mmap.mmap(self.file.fileno(), 0)
+            self.request("remap")
+        np.frombuffer(self.map, np.float64, 12 * n).reshape(n, 4, 3)[:] = tets
+        self.request(f"tetrahedra {n} 0 {out_offset}")
+        return np.frombuffer(self.map, np.float64, n, out_offset).copy()
+
+    def close(self) -> None:
+        self.request("quit")
+        self.proc.wait()
+        self.map.close()
+        self.file.close()
+        self.path.unlink()
+
+
+def main() -> None:
+    command = sys.argv[1:] or ["rust-script", str(Path(__file__).parent / "shm_worker.rs")]
+    worker = Worker(command, SHM_PATH, 1 << 20)
+    try:
+        # Octant of the unit sphere, in both orientations: ±π/2
+        octant = np.array([[0, 0, 0], [1, 0, 0], [0, 1, 0], [0, 0, 1]], np.float64)
+        tets = np.stack([octant, octant[[0, 2, 1, 3]]])
+        print(worker.solid_angles(tets) / np.pi)
+
+        # Many small calls, where a spawn per call would dominate
+        rng = np.random.default_rng(0)
+        batch = rng.standard_normal((64, 4, 3))
+        start = time.perf_counter()
+        for _ in range(1000):
+            worker.solid_angles(batch)
+        print(f"{(time.perf_counter() - start) * 1e3:.3f} us per 64-element call")
+
+        # Growing past the mapped size remaps on both sides
+        print(worker.solid_angles(rng.standard_normal((20_000, 4, 3))).shape)
+    finally:
+        worker.close()
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,47 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! bytemuck = { version = "1", optional = true }
+//! memmap2 = "0.9"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! default = ["bytemuck"]
+//! bytemuck = ["dep:bytemuck"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Resident solid-angle worker serving a shared-memory file, driven over
+//! stdin/stdout by another process such as `shm_client.py`:
+//!
+//! ```text
+//! rust-script shm_worker.rs /dev/shm/solid_angle
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "bytemuck"))]
+compile_error!("shm_worker.rs needs the bytemuck feature");
+
+#[path = "solid_angle/bytes.rs"]
+mod bytes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/shm.rs"]
+mod shm;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::io;
+
+fn main() -> io::Result<()> {
+    let path = std::env::args().nth(1).ok_or_else(|| io::Error::other("Usage: shm_worker <shared file>"))?;
+    let mut buffer = shm::SharedBuffer::open(path)?;
+    shm::serve(&mut buffer, io::stdin().lock(), io::stdout().lock())
+}
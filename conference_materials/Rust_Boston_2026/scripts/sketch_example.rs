@@ -0,0 +1,97 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Percentiles and a histogram of solid angles over a stream of
+//! tetrahedra, in fixed memory: each chunk is generated, solved, pushed
+//! into per-thread sketches and dropped. At the default size the full
+//! result is also kept, to check the sketches against exact percentiles.
+//!
+//! ```text
+//! rust-script sketch_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sketch.rs"]
+mod sketch;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use inputs::{Distribution, Pcg64};
+use rayon::prelude::*;
+use sketch::{Histogram, TDigest};
+use std::f64::consts::TAU;
+
+const CHUNK: usize = 1 << 16;
+const QUANTILES: [f64; 7] = [0.001, 0.01, 0.25, 0.5, 0.75, 0.99, 0.999];
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 22, |s| s.parse().unwrap());
+    let keep = n <= 1 << 22; // Small enough to check exactly
+
+    // One generator stream per chunk, so the stream is the same at any thread count
+    let (histogram, mut digest, kept) = (0..n.div_ceil(CHUNK))
+        .into_par_iter()
+        .map(|c| {
+            let mut rng = Pcg64::new(148, c as u64);
+            let len = CHUNK.min(n - c * CHUNK);
+            let tets: Vec<_> = (0..len).map(|_| Distribution::Random.sample(&mut rng)).collect();
+            let mut out = vec![0.0; len];
+            tetrahedron::solid_angle_tetrahedron(&tets, &mut out)?;
+
+            let mut h = Histogram::new(-TAU, TAU, 64)?;
+            let mut d = TDigest::default();
+            out.iter().for_each(|&x| {
+                h.push(x);
+                d.push(x);
+            });
+            Ok((h, d, if keep { out } else { Vec::new() }))
+        })
+        .try_reduce(
+            || (Histogram::new(-TAU, TAU, 64).unwrap(), TDigest::default(), Vec::new()),
+            |(h0, d0, mut k0), (h1, d1, k1)| {
+                k0.extend(k1);
+                Ok((h0.merge(h1)?, d0.merge(d1), k0))
+            },
+        )?;
+
+    println!("n = {n}, {} centroids, {} bins", digest.len(), histogram.counts().len());
+    assert_eq!(histogram.count(), n as u64);
+    assert_eq!(digest.count(), n as u64);
+
+    let mut exact = kept;
+    exact.sort_by(f64::total_cmp);
+    println!("{:>7} {:>12} {:>12} {:>12} {:>10}", "q", "t-digest", "histogram", "exact", "rank err");
+    for q in QUANTILES {
+        let (d, h) = (digest.quantile(q), histogram.quantile(q));
+        if exact.is_empty() {
+            println!("{q:>7} {d:>12.6} {h:>12.6}");
+            continue;
+        }
+        let x = exact[((q * n as f64) as usize).min(n - 1)];
+        // Error in rank, the quantity a t-digest bounds
+        let rank = exact.partition_point(|&v| v < d) as f64 / n as f64;
+        println!("{q:>7} {d:>12.6} {h:>12.6} {x:>12.6} {:>10.2e}", (rank - q).abs());
+        // The first and last centroids span (π / compression)² of rank, and
+        // the middle ones about π / compression; errors are well inside both
+        assert!((rank - q).abs() <= 2.5e-4 + 2e-3 * q.min(1.0 - q), "t-digest off at q = {q}");
+        assert!((h - x).abs() <= 2.0 * TAU / 64.0, "histogram off by more than a bin at q = {q}");
+    }
+    Ok(())
+}
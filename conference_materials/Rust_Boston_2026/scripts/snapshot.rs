@@ -0,0 +1,291 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Golden-file snapshots: runs every kernel on fixed seeded inputs and
+//! compares against `golden/kernels.npz` within a few ulps, and, given a
+//! built `solid_angle_cli`, compares its output files and its stdout and
+//! stderr (numbers masked) against `golden/cli.*`. Catches numerical or
+//! formatting changes that no assertion in the examples would notice.
+//!
+//! ```text
+//! rust-script snapshot.rs [--cli path/to/solid_angle_cli] [--bless]
+//! ```
+//!
+//! `--bless` rewrites the golden files from the current code; review the
+//! diff before committing them.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/batch.rs"]
+mod batch;
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/fixed.rs"]
+mod fixed;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/interval.rs"]
+mod interval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/planar.rs"]
+mod planar;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use inputs::{Distribution, Pcg64};
+use mesh::TriMesh;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+type Tet = [[f64; 3]; 4];
+
+/// Named output array
+type Named = (&'static str, Vec<f64>);
+
+/// Ulps a value may move before the snapshot fails
+const TOLERANCE_ULPS: f64 = 4.0;
+
+/// Tetrahedra per distribution
+const N: usize = 64;
+
+fn golden_dir() -> PathBuf {
+    Path::new(file!()).parent().unwrap_or(Path::new(".")).join("golden")
+}
+
+/// Seeded inputs: `N` of each distribution
+fn seeded() -> Vec<Tet> {
+    Distribution::ALL.into_iter().flat_map(|d| inputs::tetrahedra(d, 138, N)).collect()
+}
+
+/// Unit cube centered on the origin, outward-wound
+fn cube() -> TriMesh {
+    let vertices = (0..8)
+        .map(|i| [(i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5])
+        .collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]]).collect();
+    TriMesh::new(vertices, faces).unwrap()
+}
+
+/// Named output of every kernel on the seeded inputs; NaN where a kernel rejects an input
+fn kernel_outputs() -> Result<Vec<Named>, &'static str> {
+    let tets = seeded();
+    let n = tets.len();
+    let each = |f: &dyn Fn(Tet) -> f64| tets.iter().map(|&t| f(t)).collect::<Vec<f64>>();
+    let mut outputs = Vec::new();
+
+    let mut out = vec![0.0; n];
+    tetrahedron::solid_angle_tetrahedron(&tets, &mut out)?;
+    outputs.push(("fma", out.clone()));
+    par::solid_angle_tetrahedra_par(&tets, &mut out)?;
+    outputs.push(("par", out.clone()));
+    let mut cond = vec![0.0; n];
+    condition::solid_angle_tetrahedron_cond(&tets, &mut out, &mut cond)?;
+    outputs.push(("condition", cond));
+    dd::solid_angle_tetrahedron_dd(&tets, &mut out)?;
+    outputs.push(("dd", out));
+    outputs.push((
+        "interval_mid",
+        each(&|t| match interval::solid_angle_tetrahedron_scalar_filtered(t[0], t[1], t[2], t[3]) {
+            interval::Filtered::Enclosure(enc) => enc.mid(),
+            interval::Filtered::Refined(dd) => dd.to_f64(),
+        }),
+    ));
+    outputs.push((
+        "fixed",
+        each(&|t| {
+            let mut y = [0.0];
+            fixed::solid_angle_tetrahedron_fixed(&[t], 1.0 / (1_u64 << 36) as f64, &mut y).map_or(f64::NAN, |()| y[0])
+        }),
+    ));
+
+    // Mesh and planar kernels, on the apexes of the tetrahedra
+    let points: Vec<[f64; 3]> = tets.iter().map(|t| t[0].map(|x| x.clamp(-1.0, 1.0))).collect();
+    let mut omega = vec![0.0; n];
+    multi_origin::solid_angles_multi_origin(&cube(), &points, &mut omega)?;
+    outputs.push(("multi_origin", omega));
+    let pentagram: Vec<[f64; 2]> = (0..5).map(|k| (k * 2) as f64 * std::f64::consts::TAU / 5.0).map(|a| [libm::cos(a), libm::sin(a)]).collect();
+    let mut winding = vec![0.0; n];
+    planar::winding_angle(&pentagram, &points.iter().map(|p| [p[0], p[1]]).collect::<Vec<_>>(), &mut winding)?;
+    outputs.push(("winding_angle", winding));
+    Ok(outputs)
+}
+
+/// Whether `x` is within the tolerance of `golden`, treating NaNs as equal
+fn close(x: f64, golden: f64) -> bool {
+    if x.is_nan() || golden.is_nan() {
+        return x.is_nan() && golden.is_nan();
+    }
+    let ulp = (golden.abs().next_up() - golden.abs()).max(f64::MIN_POSITIVE);
+    (x - golden).abs() <= TOLERANCE_ULPS * ulp
+}
+
+/// Compare named arrays against a golden `.npz`, or rewrite it; returns the number of mismatches
+fn check_arrays(path: &Path, arrays: &[Named], bless: bool) -> io::Result<usize> {
+    if bless {
+        let named: Vec<(&str, [usize; 1], &[f64])> = arrays.iter().map(|(k, v)| (*k, [v.len()], &v[..])).collect();
+        let refs: Vec<(&str, &[usize], &[f64])> = named.iter().map(|(k, s, v)| (*k, &s[..], *v)).collect();
+        npy::write_npz(BufWriter::new(File::create(path)?), &refs)?;
+        println!("blessed {}", path.display());
+        return Ok(0);
+    }
+
+    let golden = npy::read_npz(BufReader::new(File::open(path)?))?;
+    let mut failures = 0;
+    for (name, values) in arrays {
+        let Some((_, expected)) = golden.iter().find(|(k, _)| k == name) else {
+            println!("{name:<16} missing from {}", path.display());
+            failures += 1;
+            continue;
+        };
+        let bad: Vec<usize> = (0..values.len().max(expected.data.len()))
+            .filter(|&i| !matches!((values.get(i), expected.data.get(i)), (Some(&x), Some(&g)) if close(x, g)))
+            .collect();
+        match bad.first() {
+            None => println!("{name:<16} ok"),
+            Some(&i) => {
+                println!("{name:<16} {} of {} differ, first at {i}: {:?} vs golden {:?}", bad.len(), values.len(), values.get(i), expected.data.get(i));
+                failures += 1;
+            }
+        }
+    }
+    Ok(failures)
+}
+
+/// Compare text against a golden file, or rewrite it
+fn check_text(path: &Path, text: &str, bless: bool) -> io::Result<usize> {
+    if bless {
+        std::fs::write(path, text)?;
+        println!("blessed {}", path.display());
+        return Ok(0);
+    }
+    let golden = std::fs::read_to_string(path)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if golden == text {
+        println!("{name:<16} ok");
+        Ok(0)
+    } else {
+        println!("{name:<16} differs:\n--- golden\n{golden}--- actual\n{text}");
+        Ok(1)
+    }
+}
+
+/// Console text with every number replaced by `#` and runs of spaces
+/// collapsed, so timings and their padding don't count as changes.
+/// Numbers in brackets or followed by `,` or `%`, as `(342.2` and
+/// `0.8295%`, are masked too, keeping the punctuation.
+fn mask_numbers(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let words: Vec<String> = line.split_whitespace().map(mask_word).collect();
+            words.join(" ") + "\n"
+        })
+        .collect()
+}
+
+/// `word` with the number in it, if any, replaced by `#`; `NaN` and
+/// `inf` are words
+fn mask_word(word: &str) -> String {
+    let punctuation = |c: char| matches!(c, '(' | ')' | '[' | ']' | ',' | '%');
+    let number = word.trim_matches(punctuation);
+    if !number.contains(|c: char| c.is_ascii_digit()) || number.parse::<f64>().is_err() {
+        return word.to_string();
+    }
+    let start = word.len() - word.trim_start_matches(punctuation).len();
+    format!("{}#{}", &word[..start], &word[start + number.len()..])
+}
+
+/// Run the CLI on seeded files; returns its output arrays and its masked console text
+fn cli_outputs(cli: &Path, scratch: &Path) -> io::Result<(Vec<Named>, String)> {
+    std::fs::create_dir_all(scratch)?;
+    let file = |name: &str| scratch.join(name);
+    npy::write_tetrahedra(&mut BufWriter::new(File::create(file("tets.npy"))?), &seeded())?;
+    mesh_io::write_tri_mesh_npz(BufWriter::new(File::create(file("cube.npz"))?), &cube())?;
+    let mut rng = Pcg64::new(138, 1);
+    let points: Vec<[f64; 3]> = (0..N).map(|_| rng.point()).collect();
+    npy::write_points(&mut BufWriter::new(File::create(file("points.npy"))?), &points)?;
+
+    let runs: [(&str, Vec<PathBuf>); 2] = [
+        ("cli_tetrahedra", vec![file("tets.npy"), file("omega.npy")]),
+        ("cli_winding", vec![file("cube.npz"), file("points.npy"), file("winding.npy")]),
+    ];
+    let mut arrays = Vec::new();
+    let mut text = String::new();
+    for (name, args) in runs {
+        let mode = if name == "cli_tetrahedra" { "tetrahedra" } else { "winding" };
+        let output = Command::new(cli).arg(mode).args(&args).output()?;
+        let console = format!("$ {mode}\n{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        text += &mask_numbers(&console);
+        if !output.status.success() {
+            return Err(io::Error::other(format!("{} failed:\n{console}", cli.display())));
+        }
+        let result = npy::read_f64(&mut BufReader::new(File::open(args.last().unwrap())?))?;
+        arrays.push((name, result.data));
+    }
+    Ok((arrays, text))
+}
+
+fn main() -> io::Result<ExitCode> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bless = args.iter().any(|a| a == "--bless");
+    let cli = args.iter().position(|a| a == "--cli").and_then(|i| args.get(i + 1)).map(PathBuf::from);
+    let dir = golden_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let outputs = kernel_outputs().map_err(io::Error::other)?;
+    let mut failures = check_arrays(&dir.join("kernels.npz"), &outputs, bless)?;
+
+    match cli {
+        Some(cli) => {
+            let (arrays, text) = cli_outputs(&cli, &std::env::temp_dir().join("solid_angle_snapshot"))?;
+            failures += check_arrays(&dir.join("cli.npz"), &arrays, bless)?;
+            failures += check_text(&dir.join("cli.txt"), &text, bless)?;
+        }
+        None => println!("CLI snapshots skipped; pass --cli <path to built solid_angle_cli>"),
+    }
+
+    if failures > 0 {
+        println!("{failures} snapshot(s) differ; rerun with --bless if the change is intended");
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
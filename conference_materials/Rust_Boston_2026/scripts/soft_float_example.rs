@@ -0,0 +1,137 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! rvv = []
+//! soft-float = []
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! The kernel as built, fused or not (`solid_angle/vec3.rs`), against the
+//! fused kernel of the talk's FMA slide on every input distribution.
+//! Fused, the two must match bit for bit; unfused, each solid angle must
+//! be within `UNFUSED_TOLERANCE κ ε |Ω|` of the fused one, with `κ` the
+//! condition estimate. Every dispatch path must match the kernel as built
+//! either way.
+//!
+//! ```text
+//! rust-script soft_float_example.rs [n]
+//! cd $(rust-script -p soft_float_example.rs | tail -1) && cargo run --release --features soft-float
+//! ```
+//!
+//! A host with FMA runs the fused kernel unless built with `soft-float`,
+//! which forces the fallback that targets without FMA get by default.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/condition.rs"]
+mod condition;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use inputs::Distribution;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use vec3::{FUSED, UNFUSED_TOLERANCE};
+
+type Tet = [[f64; 3]; 4];
+
+/// The fused kernel of the talk's FMA slide, always fused: the reference
+/// the kernel as built must match, or come within tolerance of
+fn solid_angle_fused(tet: Tet) -> f64 {
+    let sub = |a: [f64; 3], b: [f64; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0].mul_add(v[0], u[1].mul_add(v[1], u[2] * v[2]));
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [u[1].mul_add(v[2], -u[2] * v[1]), u[2].mul_add(v[0], -u[0] * v[2]), u[0].mul_add(v[1], -u[1] * v[0])]
+    };
+    let (a, b, c) = (sub(tet[1], tet[0]), sub(tet[2], tet[0]), sub(tet[3], tet[0]));
+    let (la, lb, lc) = (dot(a, a).sqrt(), dot(b, b).sqrt(), dot(c, c).sqrt());
+    let abc = la * lb * lc;
+    let triple = dot(a, cross(b, c));
+    let denom = dot(a, b).mul_add(lc, dot(a, c).mul_add(lb, dot(b, c).mul_add(la, abc)));
+    if abc != 0.0 {
+        2.0 * libm::atan2(triple, denom)
+    } else {
+        0.0
+    }
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 18, |s| s.parse().unwrap());
+    println!(
+        "multiply-add {}, atan2 from {}, dispatch path {}",
+        if FUSED { "fused" } else { "unfused" },
+        math::backend(),
+        dispatch::path().name()
+    );
+
+    // Unfused, the helpers round every product and sum on their own
+    if !FUSED {
+        let mut rng = inputs::Pcg64::new(178, 0);
+        for _ in 0..10_000 {
+            let (u, v) = (rng.point(), rng.point());
+            assert_eq!(vec3::dot(u, v), u[0] * v[0] + (u[1] * v[1] + u[2] * v[2]));
+            assert_eq!(vec3::cross(u, v)[2], u[0] * v[1] - u[1] * v[0]);
+        }
+    }
+
+    println!("{:>7} {:>9} {:>14} {:>10}", "", "differ", "max |Δ|/κε|Ω|", "ns/elem");
+    for dist in Distribution::ALL {
+        let tets = inputs::tetrahedra(dist, 178, n);
+        let mut built = vec![0.0; n];
+        let start = Instant::now();
+        tetrahedron::solid_angle_tetrahedron(black_box(&tets), &mut built)?;
+        let elapsed = start.elapsed();
+        let fused: Vec<f64> = tets.iter().map(|&tet| solid_angle_fused(tet)).collect();
+
+        // Against the fused kernel, in units of its conditioning
+        let (mut differ, mut worst) = (0, 0.0_f64);
+        for ((tet, &x), &y) in tets.iter().zip(&built).zip(&fused) {
+            if x.to_bits() == y.to_bits() {
+                continue;
+            }
+            differ += 1;
+            // κ |Ω| from one evaluation, as near Ω = 0 the two differ in
+            // every digit; at Ω = 0 itself κ is infinite, and so the bound
+            let (angle, cond) = condition::solid_angle_tetrahedron_scalar_cond(tet[0], tet[1], tet[2], tet[3]);
+            let scale = if angle == 0.0 { f64::INFINITY } else { cond * angle.abs() };
+            let ratio = (x - y).abs() / (f64::EPSILON * scale.max(y.abs()));
+            assert!(ratio <= UNFUSED_TOLERANCE, "{}: {x:e} against fused {y:e}, κ = {cond:e}", dist.name());
+            worst = worst.max(ratio);
+        }
+        if FUSED {
+            assert_eq!(differ, 0, "Fused kernel differs from the FMA slide's");
+        }
+
+        // Every path this CPU runs rounds as the kernel does
+        for path in dispatch::Path::ALL.into_iter().filter(|p| p.supported()) {
+            dispatch::set_path(Some(path))?;
+            let mut out = vec![0.0; n];
+            dispatch::solid_angle_tetrahedron_dispatch(&tets, &mut out)?;
+            assert!(out.iter().zip(&built).all(|(a, b)| a.to_bits() == b.to_bits()), "{} path differs", path.name());
+        }
+        dispatch::set_path(None)?;
+
+        let per_elem = elapsed.max(Duration::from_nanos(1)).as_secs_f64() / n as f64;
+        println!("{:>7} {differ:>9} {worst:>14.2} {:>10.2}", dist.name(), 1e9 * per_elem);
+    }
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! Growable buffer with a guaranteed minimum alignment.
+//!
+//! `Vec<f64>` is only guaranteed 8-byte alignment, so the first element
+//! of a kernel input or output may sit anywhere in a cache line and every
+//! SIMD load/store near a line boundary is split in two. An
+//! `AlignedVec<f64>` starts on a 64-byte boundary (one cache line, and
+//! one AVX-512 register), so chunks of 8 `f64` never straddle lines.
+//!
+//! In practice the difference is negligible for the compute-bound kernels
+//! and up to ~10% for memory-bound ones at sizes well past L2; see
+//! `aligned_vec_example.rs`.
+//!
+//! Past L2 the next cost is TLB misses: at 10⁸ elements a buffer spans
+//! hundreds of thousands of 4 KiB pages. [AllocPolicy::HugePages] lays
+//! large buffers out in whole 2 MiB pages, and with the `hugepages`
+//! feature on Linux advises them for transparent huge pages before they
+//! are first touched. Without the feature, the layout still lets THP back
+//! them when it is set to `always`.
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+/// Huge page size for [AllocPolicy::HugePages]: the transparent huge page
+/// size on x86_64, and on AArch64 with 4 KiB base pages
+pub const HUGE_PAGE: usize = 2 << 20;
+
+/// How an [AlignedVec] lays out its storage
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AllocPolicy {
+    /// Aligned to `A`, from the global allocator
+    #[default]
+    Default,
+    /// Allocations of a huge page or more are whole huge pages, aligned
+    /// to one. Smaller ones are as [AllocPolicy::Default].
+    HugePages,
+}
+
+/// Growable buffer whose storage is aligned to at least `A` bytes
+pub struct AlignedVec<T, const A: usize = 64> {
+    ptr: NonNull<T>,
+    len: usize,
+    cap: usize,
+    policy: AllocPolicy,
+}
+
+// SAFETY: AlignedVec owns its elements, like Vec
+unsafe impl<T: Send, const A: usize> Send for AlignedVec<T, A> {}
+// SAFETY: Shared access only hands out &T, like Vec
+unsafe impl<T: Sync, const A: usize> Sync for AlignedVec<T, A> {}
+
+impl<T, const A: usize> AlignedVec<T, A> {
+    /// Effective alignment, never less than the element's own
+    pub const ALIGN: usize = {
+        assert!(A.is_power_of_two(), "Alignment must be a power of two");
+        if A > align_of::<T>() { A } else { align_of::<T>() }
+    };
+
+    /// Empty buffer. Does not allocate.
+    pub const fn new() -> Self {
+        Self::new_in(AllocPolicy::Default)
+    }
+
+    /// Empty buffer allocating by `policy`. Does not allocate.
+    pub const fn new_in(policy: AllocPolicy) -> Self {
+        // Dangling, but aligned, so even empty slices honor the guarantee
+        // SAFETY: ALIGN is a power of two, so non-zero
+        let ptr = unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(Self::ALIGN)) };
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { 0 };
+        Self { ptr, len: 0, cap, policy }
+    }
+
+    /// Empty buffer with room for at least `cap` elements
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, AllocPolicy::Default)
+    }
+
+    /// Empty buffer with room for at least `cap` elements, allocated by `policy`
+    pub fn with_capacity_in(cap: usize, policy: AllocPolicy) -> Self {
+        let mut v = Self::new_in(policy);
+        v.reserve(cap);
+        v
+    }
+
+    /// Buffer of `n` copies of `value`, e.g. a kernel output
+    pub fn from_elem(value: T, n: usize) -> Self
+    where
+        T: Clone,
+    {
+        let mut v = Self::with_capacity(n);
+        v.extend(std::iter::repeat_n(value, n));
+        v
+    }
+
+    /// Aligned copy of a slice
+    pub fn from_slice(values: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        let mut v = Self::with_capacity(values.len());
+        v.extend(values.iter().cloned());
+        v
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    #[inline]
+    pub fn policy(&self) -> AllocPolicy {
+        self.policy
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements are initialized and ptr is aligned and non-null
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: As in as_slice, and &mut self guarantees exclusive access
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Make room for at least `additional` more elements
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("Capacity overflow");
+        if required <= self.cap {
+            return;
+        }
+
+        // Grow geometrically to keep push amortized O(1)
+        let new_cap = required.max(2 * self.cap).max(4);
+        let new_layout = self.layout(new_cap);
+        // Use any padding up to whole huge pages, which layout() of the
+        // larger capacity pads to the same size
+        let new_cap = new_cap.max(new_layout.size() / size_of::<T>());
+        let new_ptr = if self.cap == 0 || self.policy == AllocPolicy::HugePages {
+            // Fresh allocation, advised before the copy first touches it
+            // SAFETY: Layout has non-zero size since T is not zero-sized here
+            let new_ptr = unsafe { alloc::alloc(new_layout) };
+            if !new_ptr.is_null() {
+                advise_huge_pages(new_ptr, new_layout);
+                if self.cap != 0 {
+                    // SAFETY: Both hold at least len elements and are distinct
+                    // allocations, and ptr was allocated with layout(cap)
+                    unsafe {
+                        ptr::copy_nonoverlapping(self.ptr.as_ptr(), new_ptr.cast(), self.len);
+                        alloc::dealloc(self.ptr.as_ptr().cast(), self.layout(self.cap));
+                    }
+                }
+            }
+            new_ptr
+        } else {
+            // SAFETY: ptr was allocated with layout(cap), and realloc keeps its alignment
+            unsafe { alloc::realloc(self.ptr.as_ptr().cast(), self.layout(self.cap), new_layout.size()) }
+        };
+        self.ptr = match NonNull::new(new_ptr.cast()) {
+            Some(p) => p,
+            None => alloc::handle_alloc_error(new_layout),
+        };
+        self.cap = new_cap;
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len == self.cap {
+            self.reserve(1);
+        }
+        // SAFETY: len < cap after reserving, so the slot is allocated and uninitialized
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: The slot at the old last index is initialized and now outside len
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    pub fn clear(&mut self) {
+        let elems: *mut [T] = self.as_mut_slice();
+        self.len = 0; // Leak rather than double-drop if a destructor panics
+        // SAFETY: The elements were initialized and are no longer reachable
+        unsafe { ptr::drop_in_place(elems) };
+    }
+
+    fn layout(&self, cap: usize) -> Layout {
+        let layout = Layout::array::<T>(cap).and_then(|l| l.align_to(Self::ALIGN));
+        match self.policy {
+            AllocPolicy::HugePages => layout.and_then(|l| {
+                if l.size() < HUGE_PAGE { Ok(l) } else { l.align_to(HUGE_PAGE).map(|l| l.pad_to_align()) }
+            }),
+            AllocPolicy::Default => layout,
+        }
+        .expect("Capacity overflow")
+    }
+}
+
+/// Ask for transparent huge pages over a fresh allocation, before its
+/// first touch. Best effort: THP may be disabled, and then this does nothing.
+#[cfg(all(feature = "hugepages", target_os = "linux", not(miri)))]
+fn advise_huge_pages(ptr: *mut u8, layout: Layout) {
+    if layout.align() >= HUGE_PAGE {
+        // SAFETY: ptr starts a live allocation of layout.size() bytes, aligned
+        // to a huge page and so to a page; the advice leaves contents as they are
+        unsafe { libc::madvise(ptr.cast(), layout.size(), libc::MADV_HUGEPAGE) };
+    }
+}
+
+#[cfg(not(all(feature = "hugepages", target_os = "linux", not(miri))))]
+fn advise_huge_pages(_ptr: *mut u8, _layout: Layout) {}
+
+impl<T, const A: usize> Drop for AlignedVec<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+        if self.cap != 0 && size_of::<T>() != 0 {
+            // SAFETY: ptr was allocated with layout(cap)
+            unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), self.layout(self.cap)) };
+        }
+    }
+}
+
+impl<T, const A: usize> Default for AlignedVec<T, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const A: usize> Deref for AlignedVec<T, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const A: usize> DerefMut for AlignedVec<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const A: usize> Clone for AlignedVec<T, A> {
+    fn clone(&self) -> Self {
+        let mut v = Self::with_capacity_in(self.len, self.policy);
+        v.extend(self.iter().cloned());
+        v
+    }
+}
+
+impl<T: fmt::Debug, const A: usize> fmt::Debug for AlignedVec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T: PartialEq, const A: usize> PartialEq for AlignedVec<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T, const A: usize> Extend<T> for AlignedVec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const A: usize> FromIterator<T> for AlignedVec<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
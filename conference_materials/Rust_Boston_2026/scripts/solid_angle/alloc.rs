@@ -0,0 +1,29 @@
+//! Global allocator of the build: the system's, or jemalloc or mimalloc
+//! behind the features of those names. Loading meshes and building BVHs
+//! and adjacency allocate heavily, and vary by up to 2x between
+//! allocators on some servers; `bench.rs alloc` times those stages under
+//! whichever allocator the build picked.
+//!
+//! Included by the CLI, the service and the benchmark. `#[global_allocator]`
+//! may sit in any module of a crate, so including this is all it takes.
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("The jemalloc and mimalloc features are exclusive");
+
+#[cfg(all(feature = "jemalloc", not(feature = "mimalloc")))]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(all(feature = "mimalloc", not(feature = "jemalloc")))]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// Name of the global allocator, for reports
+#[allow(unexpected_cfgs)] // Each script declares only the features it uses
+pub const ALLOCATOR: &str = if cfg!(feature = "jemalloc") {
+    "jemalloc"
+} else if cfg!(feature = "mimalloc") {
+    "mimalloc"
+} else {
+    "system"
+};
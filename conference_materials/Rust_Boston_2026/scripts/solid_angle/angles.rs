@@ -0,0 +1,94 @@
+//! Angles between vectors and dihedral angles of tetrahedra, for quality
+//! metrics and curvature.
+//!
+//! Both take `atan2` of a sine-like and a cosine-like quantity instead of
+//! `acos` of a normalized dot product. `acos` loses everything near 0 and
+//! π, where its slope is infinite: two vectors 1e-9 rad apart normalize
+//! to a dot product that rounds to exactly 1, and `acos` says they are
+//! parallel. `atan2(|u × v|, u · v)` keeps full relative accuracy there,
+//! needs no normalization, and so no division by a length that may be
+//! zero.
+
+use crate::par::{chunk_len, par_threshold};
+use crate::vec3::{cross, dot, norm, sub};
+use rayon::prelude::*;
+
+/// Vertex pairs of the six edges of a tetrahedron, the order of
+/// [dihedral_angles]' output
+pub const EDGES: [[usize; 2]; 6] = [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]];
+
+/// Angle between `u` and `v` in `[0, π]`; zero if either is zero
+#[inline]
+pub fn angle_between(u: [f64; 3], v: [f64; 3]) -> f64 {
+    libm::atan2(norm(cross(u, v)), dot(u, v)) // (rad)
+}
+
+/// Vector variant of [angle_between], pairing `u[i]` with `v[i]`
+#[inline]
+pub fn angles_between(u: &[[f64; 3]], v: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if u.len() != n || v.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = angle_between(u[i], v[i]);
+    }
+
+    Ok(())
+}
+
+/// Interior dihedral angle of a tetrahedron at edge `EDGES[edge]`, the
+/// angle between its two faces that meet there, in `[0, π]`; zero if the
+/// edge or either face is degenerate. Panics if `edge` is 6 or more.
+///
+/// With `e` the edge and `a`, `b` the other two vertices from its start,
+/// the face normals `e × a` and `e × b` have cross product `e [e, a, b]`,
+/// so the sine side is `|e| |[e, a, b]|`, on the FMA triple product.
+#[inline]
+pub fn dihedral_angle(tet: [[f64; 3]; 4], edge: usize) -> f64 {
+    let [p, q] = EDGES[edge];
+    let [r, s] = EDGES[5 - edge]; // The opposite edge
+    let e = sub(tet[q], tet[p]);
+    let (a, b) = (sub(tet[r], tet[p]), sub(tet[s], tet[p]));
+    let triple = dot(e, cross(a, b));
+    libm::atan2(norm(e) * triple.abs(), dot(cross(e, a), cross(e, b))) // (rad)
+}
+
+/// All six dihedral angles of each tetrahedron, in [EDGES] order
+#[inline]
+pub fn dihedral_angles(tetrahedra: &[[[f64; 3]; 4]], out: &mut [[f64; 6]]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = std::array::from_fn(|edge| dihedral_angle(tetrahedra[i], edge));
+    }
+
+    Ok(())
+}
+
+/// Thread-parallel [dihedral_angles], chunked as
+/// [crate::par::solid_angle_tetrahedra_par]
+pub fn dihedral_angles_par(tetrahedra: &[[[f64; 3]; 4]], out: &mut [[f64; 6]]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return dihedral_angles(tetrahedra, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(t, o)| dihedral_angles(t, o))
+}
@@ -0,0 +1,164 @@
+//! Fast approximate kernel, for coverage heatmaps and visualization,
+//! where the full kernel's last ten digits are wasted effort.
+//!
+//! [Precision] picks the kernel per call, through
+//! [solid_angle_tetrahedron_with] and [solid_angle_tetrahedra_with_par].
+//! The approximation keeps the fused triple product, whose sign decides
+//! the orientation of slivers, and cheapens everything else: `atan2` is
+//! [atan2_approx], and the lengths and denominator round once per
+//! operation instead of fusing, which also spares portable x86_64 builds
+//! their calls to a software `fma`. Against the full kernel:
+//!
+//! | Inputs ([crate::inputs]) | Max relative error | Max absolute error |
+//! |--------------------------|--------------------|--------------------|
+//! | random, tiny, huge       | 6e-8               | 9e-8 sr            |
+//! | sliver                   | 3e-8               | 1e-8 sr            |
+//!
+//! On the AVX2+FMA path that is 14 ns per tetrahedron against 33 for the
+//! full kernel with the default `libm` backend; with `math-poly` the full
+//! kernel vectorizes too and the gap shrinks to 1.3x.
+//!
+//! Lengths keep their `sqrt`. An `rsqrt` from the exponent bit trick
+//! leaves 2e-3 in the angle after one Newton step, where the three terms
+//! of the denominator cancel, and needs three steps to reach 1e-7, by
+//! which point it is slower than the hardware square root.
+//! `approx_example.rs` measures both kernels.
+
+use crate::dispatch;
+use crate::par::{chunk_len, par_threshold};
+use crate::vec3::{cross, dot};
+use rayon::prelude::*;
+
+/// Which kernel a call runs
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Precision {
+    /// [crate::tetrahedron], on the path from [crate::dispatch::path]
+    #[default]
+    Full,
+    /// [solid_angle_tetrahedron_approx], about 1e-7 relative error
+    Approx,
+}
+
+/// Coefficients of `atan(r) = r + r³ P(r²)` on `[0, 1]`, highest first,
+/// from SLEEF's single-precision `atan2f` (`atan2kf` in `sleefsp.c`)
+const ATAN: [f64; 8] = [
+    0.0028236389625817537,
+    -0.015956902876496315,
+    0.042504988610744476,
+    -0.07489009201526642,
+    0.1063479334115982,
+    -0.1420273631811142,
+    0.19992695748806,
+    -0.33333101868629456,
+];
+
+/// [crate::math::atan2_poly] at single precision: a shorter polynomial,
+/// unfused, with the quadrant fold in plain `f64`. Within 6e-8 of
+/// `atan2`, relative; IEEE special cases as [f64::atan2].
+#[inline]
+pub fn atan2_approx(y: f64, x: f64) -> f64 {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let (ay, ax) = (y.abs(), x.abs());
+    let (lo, hi) = (ay.min(ax), ay.max(ax));
+    let r = if hi == 0.0 {
+        0.0
+    } else if lo.is_infinite() {
+        1.0
+    } else {
+        lo / hi
+    };
+    let t = r * r;
+    let p = ATAN.iter().fold(0.0, |p, &c| p * t + c);
+    let a = r + r * t * p;
+    let a = if ay > ax { FRAC_PI_2 - a } else { a };
+    let a = if x.is_sign_negative() { PI - a } else { a };
+    let a = if x.is_nan() || y.is_nan() { f64::NAN } else { a };
+    a.copysign(y)
+}
+
+/// Unfused u0 v0 + u1 v1 + u2 v2
+#[inline]
+fn dot_approx(u: [f64; 3], v: [f64; 3]) -> f64 {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+/// Approximate [crate::tetrahedron::solid_angle_tetrahedron_scalar]
+#[inline(always)] // See approx_loop
+pub fn solid_angle_tetrahedron_approx_scalar(v0: [f64; 3], v1: [f64; 3], v2: [f64; 3], v3: [f64; 3]) -> f64 {
+    let sub = |v: [f64; 3]| [v[0] - v0[0], v[1] - v0[1], v[2] - v0[2]];
+    let (a, b, c) = (sub(v1), sub(v2), sub(v3));
+    let (la, lb, lc) = (dot_approx(a, a).sqrt(), dot_approx(b, b).sqrt(), dot_approx(c, c).sqrt());
+    let abc = la * lb * lc;
+
+    let triple = dot(a, cross(b, c)); // Fused, as in the full kernel
+    let denom = abc + dot_approx(b, c) * la + dot_approx(a, c) * lb + dot_approx(a, b) * lc;
+    let angle = 2.0 * atan2_approx(triple, denom);
+
+    if abc != 0.0 { angle } else { 0.0 }
+}
+
+/// Vector variant of [solid_angle_tetrahedron_approx_scalar]
+#[inline]
+pub fn solid_angle_tetrahedron_approx(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    approx_loop(tetrahedra, out);
+    Ok(())
+}
+
+/// Always inlined, with the kernel, or LLVM judges them too big to inline
+/// into the AVX2 copy, which then calls the portable build of each
+#[inline(always)]
+fn approx_loop(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) {
+    for (tet, y) in tetrahedra.iter().zip(out.iter_mut()) {
+        *y = solid_angle_tetrahedron_approx_scalar(tet[0], tet[1], tet[2], tet[3]);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn solid_angle_tetrahedron_approx_avx2(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    approx_loop(tetrahedra, out);
+    Ok(())
+}
+
+/// Solid angles at the chosen precision, on the path from
+/// [crate::dispatch::path]. The approximate kernel has no NEON or RVV
+/// variant, so those paths run it as built.
+pub fn solid_angle_tetrahedron_with(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64], precision: Precision) -> Result<(), &'static str> {
+    match (precision, dispatch::path()) {
+        (Precision::Full, _) => dispatch::solid_angle_tetrahedron_dispatch(tetrahedra, out),
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: The path is only chosen when the CPU has AVX2 and FMA
+        (Precision::Approx, dispatch::Path::Avx2Fma) => unsafe { solid_angle_tetrahedron_approx_avx2(tetrahedra, out) },
+        (Precision::Approx, _) => solid_angle_tetrahedron_approx(tetrahedra, out),
+    }
+}
+
+/// Thread-parallel [solid_angle_tetrahedron_with], chunked as
+/// [crate::par::solid_angle_tetrahedra_par]
+pub fn solid_angle_tetrahedra_with_par(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64], precision: Precision) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return solid_angle_tetrahedron_with(tetrahedra, out, precision);
+    }
+
+    let chunk = chunk_len(out.len());
+    (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(t, o)| solid_angle_tetrahedron_with(t, o, precision))
+}
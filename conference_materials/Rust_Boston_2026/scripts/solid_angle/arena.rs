@@ -0,0 +1,66 @@
+//! Bump arena for the transient buffers of a repeated stage: a batch
+//! queue's launch, a BVH rebuild each frame.
+//!
+//! Each call to [Arena::buffers] carves the lengths asked for, one after
+//! another, out of one block that is kept between calls. The block grows
+//! to the largest call seen and then stays, so a stage that runs over and
+//! over at similar sizes stops allocating after its first few runs,
+//! rather than freeing and reallocating buffers of many sizes each time.
+//! That churn is what fragments the heap of a long-running service.
+//!
+//! The buffers borrow the arena, so they are all released together when
+//! the last goes out of scope, and the next call reuses the same memory.
+//! They are not cleared: they hold whatever the previous call left, or
+//! `T::default()` where the block is new. Callers write every element
+//! before reading it, as the kernels' outputs are.
+//!
+//! There is no `unsafe` here: the block is a `Vec<T>` split with
+//! `split_at_mut`, so element types are `Copy` and one type per arena.
+//! Stages that need buffers of two types hold two arenas.
+
+/// See the module docs
+#[derive(Clone, Debug, Default)]
+pub struct Arena<T> {
+    block: Vec<T>,
+    grown: u64,
+}
+
+impl<T: Copy + Default> Arena<T> {
+    /// Empty arena. Does not allocate.
+    pub const fn new() -> Self {
+        Self { block: Vec::new(), grown: 0 }
+    }
+
+    /// Disjoint buffers of `lens` elements, in order, from the arena's
+    /// block, growing it first if they don't fit. Contents are stale.
+    pub fn buffers<const K: usize>(&mut self, lens: [usize; K]) -> Result<[&mut [T]; K], &'static str> {
+        let total = lens.iter().try_fold(0_usize, |total, &n| total.checked_add(n)).ok_or("Arena size overflows")?;
+        if total > self.block.len() {
+            self.block.resize(total, T::default());
+            self.grown += 1;
+        }
+        let mut rest = &mut self.block[..total];
+        Ok(std::array::from_fn(|i| {
+            let (buffer, tail) = std::mem::take(&mut rest).split_at_mut(lens[i]);
+            rest = tail;
+            buffer
+        }))
+    }
+
+    /// Elements the block holds, the most any call has asked for
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.block.len()
+    }
+
+    /// How many calls have had to grow the block
+    #[inline]
+    pub fn grown(&self) -> u64 {
+        self.grown
+    }
+
+    /// Free the block, as after a one-off burst much larger than usual
+    pub fn release(&mut self) {
+        self.block = Vec::new();
+    }
+}
@@ -0,0 +1,55 @@
+//! Kernels exported under stable, unmangled names, so their machine code
+//! can be found without chasing monomorphized symbol hashes:
+//!
+//! ```text
+//! objdump -d --no-show-raw-insn --disassemble=solid_angle_export_tetrahedron <binary>
+//! ```
+//!
+//! Unmangled names are global to the final link, which is why this module
+//! sits behind the `asm-export` feature rather than being always on.
+//! `#[inline(never)]` keeps each one a standalone function even when the
+//! caller is in the same crate.
+
+use crate::dd::{solid_angle_tetrahedron_scalar_dd, DoubleDouble};
+use crate::tetrahedron::{solid_angle_tetrahedron, solid_angle_tetrahedron_scalar};
+
+/// [solid_angle_tetrahedron_scalar]
+#[inline(never)]
+#[unsafe(no_mangle)]
+pub fn solid_angle_export_tetrahedron_scalar(v0: [f64; 3], v1: [f64; 3], v2: [f64; 3], v3: [f64; 3]) -> f64 {
+    solid_angle_tetrahedron_scalar(v0, v1, v2, v3)
+}
+
+/// [solid_angle_tetrahedron]
+#[inline(never)]
+#[unsafe(no_mangle)]
+pub fn solid_angle_export_tetrahedron(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    solid_angle_tetrahedron(tetrahedra, out)
+}
+
+/// [solid_angle_tetrahedron_scalar_dd]
+#[inline(never)]
+#[unsafe(no_mangle)]
+pub fn solid_angle_export_tetrahedron_scalar_dd(v0: [f64; 3], v1: [f64; 3], v2: [f64; 3], v3: [f64; 3]) -> DoubleDouble {
+    solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3)
+}
+
+/// Elementwise product of `examples/slice_mul_demo.rs`, the auto-vectorization
+/// baseline: no transcendental call in the loop, so it should be packed
+#[inline(never)]
+#[unsafe(no_mangle)]
+pub fn solid_angle_export_slice_mul(a: &[f64], b: &[f64], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds before loop!
+    // Otherwise, it will not vectorize
+    let n = out.len();
+    if a.len() != n || b.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do the calculations
+    for i in 0..n {
+        out[i] = a[i] * b[i];
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Earth as seen from orbit: the solid angle it covers and whether it
+//! blocks a line of sight, for an oblate spheroid Earth in ECEF
+//! (Earth-centered, Earth-fixed) coordinates.
+//!
+//! Scaling the axes by `1/a, 1/a, 1/b` maps the spheroid to the unit
+//! sphere and keeps tangency, so the limb seen from a satellite is the
+//! image of the circle where the tangent cone touches that sphere. That
+//! makes the limb exact and planar (an ellipse), and its solid angle comes
+//! from a fan of tetrahedra around it, extrapolated in the number of
+//! sides to near `f64` resolution.
+
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::vec3::{cross, dot, norm, sub};
+use std::f64::consts::{PI, TAU};
+
+/// WGS 84 semi-major axis
+pub const WGS84_A: f64 = 6_378_137.0; // (m)
+/// WGS 84 flattening
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// WGS 84 semi-minor axis
+pub const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F); // (m)
+
+/// Oblate spheroid with equatorial radius `a` and polar radius `b`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spheroid {
+    pub a: f64, // (m)
+    pub b: f64, // (m)
+}
+
+impl Spheroid {
+    pub const WGS84: Self = Self { a: WGS84_A, b: WGS84_B };
+
+    /// Sphere of radius `r`
+    pub const fn sphere(r: f64) -> Self {
+        Self { a: r, b: r }
+    }
+
+    /// First eccentricity squared
+    #[inline]
+    fn e2(&self) -> f64 {
+        1.0 - (self.b * self.b) / (self.a * self.a)
+    }
+
+    /// Map to the frame where this spheroid is the unit sphere
+    #[inline]
+    fn scale_to_unit(&self, p: [f64; 3]) -> [f64; 3] {
+        [p[0] / self.a, p[1] / self.a, p[2] / self.b]
+    }
+
+    #[inline]
+    fn scale_from_unit(&self, p: [f64; 3]) -> [f64; 3] {
+        [p[0] * self.a, p[1] * self.a, p[2] * self.b]
+    }
+}
+
+/// ECEF position of geodetic latitude and longitude in degrees at
+/// height `h` above the spheroid
+pub fn geodetic_to_ecef(spheroid: &Spheroid, lat: f64, lon: f64, h: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    let e2 = spheroid.e2();
+    let n = spheroid.a / (1.0 - e2 * sin_lat * sin_lat).sqrt(); // (m) Prime vertical radius
+    [
+        (n + h) * cos_lat * cos_lon,
+        (n + h) * cos_lat * sin_lon,
+        (n * (1.0 - e2) + h) * sin_lat,
+    ]
+}
+
+/// Geodetic `[lat, lon, h]` (degrees, degrees, m) of an ECEF position,
+/// by fixed-point iteration on latitude. Converges to machine precision
+/// in a handful of steps anywhere outside the core.
+pub fn ecef_to_geodetic(spheroid: &Spheroid, p: [f64; 3]) -> [f64; 3] {
+    let e2 = spheroid.e2();
+    let rho = p[0].hypot(p[1]); // (m) Distance from the axis
+    let lon = libm::atan2(p[1], p[0]);
+
+    let mut lat = libm::atan2(p[2], rho * (1.0 - e2));
+    let mut h = 0.0;
+    for _ in 0..8 {
+        let sin_lat = lat.sin();
+        let n = spheroid.a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        h = if lat.cos().abs() > 1e-9 { rho / lat.cos() - n } else { p[2].abs() - n * (1.0 - e2) };
+        let next = libm::atan2(p[2], rho * (1.0 - e2 * n / (n + h)));
+        if next == lat {
+            break;
+        }
+        lat = next;
+    }
+    [lat.to_degrees(), lon.to_degrees(), h]
+}
+
+/// Solid angle of a circular cone with the given half-angle, equal to the
+/// area of the spherical cap it cuts from the unit sphere
+#[inline]
+pub fn solid_angle_cone(half_angle: f64) -> f64 {
+    // 2π(1 - cos θ), written to keep its digits for small θ
+    let s = (0.5 * half_angle).sin();
+    4.0 * PI * s * s // (sr)
+}
+
+/// Solid angle of a sphere of radius `r` seen from distance `d > r`
+#[inline]
+pub fn solid_angle_sphere(r: f64, d: f64) -> f64 {
+    solid_angle_cone((r / d).asin()) // (sr)
+}
+
+/// Points on the limb of `spheroid` seen from `observer`, and its center,
+/// or `None` if the observer is not outside the spheroid
+fn limb(spheroid: &Spheroid, observer: [f64; 3], sides: usize) -> Option<(Vec<[f64; 3]>, [f64; 3])> {
+    // Tangent circle in the unit-sphere frame: center p/|p|², radius sqrt(1 - 1/|p|²)
+    let p = spheroid.scale_to_unit(observer);
+    let d2 = dot(p, p);
+    if d2 <= 1.0 {
+        return None;
+    }
+    let center = p.map(|x| x / d2);
+    let radius = (1.0 - 1.0 / d2).sqrt();
+
+    // Orthonormal basis of the circle's plane
+    let axis = p.map(|x| x / d2.sqrt());
+    let helper = if axis[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    let u = cross(axis, helper);
+    let u = u.map(|x| x / norm(u));
+    let v = cross(axis, u);
+
+    let points = (0..sides)
+        .map(|k| {
+            let (s, c) = (TAU * k as f64 / sides as f64).sin_cos();
+            spheroid.scale_from_unit([0, 1, 2].map(|i| center[i] + radius * (c * u[i] + s * v[i])))
+        })
+        .collect();
+    Some((points, spheroid.scale_from_unit(center)))
+}
+
+/// Solid angle of the limb polygon with the given number of sides
+fn limb_solid_angle(spheroid: &Spheroid, observer: [f64; 3], sides: usize) -> Option<f64> {
+    let (points, center) = limb(spheroid, observer, sides)?;
+    let total: f64 = (0..sides)
+        .map(|k| solid_angle_tetrahedron_scalar(observer, center, points[k], points[(k + 1) % sides]))
+        .sum();
+    Some(total.abs()) // (sr) Sign depends only on the basis handedness
+}
+
+/// Solid angle covered by `spheroid` as seen from `observer` in ECEF,
+/// or `None` if the observer is not outside it.
+///
+/// The inscribed limb polygon's error is a series in `1/sides²`, so two
+/// Richardson (Romberg) steps over 256, 512 and 1024 sides cancel it to
+/// near `f64` resolution.
+pub fn earth_solid_angle(spheroid: &Spheroid, observer: [f64; 3]) -> Option<f64> {
+    let [f0, f1, f2] = [256, 512, 1024].map(|sides| limb_solid_angle(spheroid, observer, sides));
+    let (f0, f1, f2) = (f0?, f1?, f2?);
+    let (r0, r1) = ((4.0 * f1 - f0) / 3.0, (4.0 * f2 - f1) / 3.0);
+    Some((16.0 * r1 - r0) / 15.0) // (sr)
+}
+
+/// Whether the ray from `observer` along `direction` hits `spheroid`,
+/// i.e. a target that way beyond the Earth is hidden by the limb
+pub fn occluded_direction(spheroid: &Spheroid, observer: [f64; 3], direction: [f64; 3]) -> bool {
+    first_hit(spheroid, observer, direction).is_some()
+}
+
+/// Whether `spheroid` blocks the straight line from `observer` to `target`
+pub fn occluded_target(spheroid: &Spheroid, observer: [f64; 3], target: [f64; 3]) -> bool {
+    matches!(first_hit(spheroid, observer, sub(target, observer)), Some(t) if t < 1.0)
+}
+
+/// Smallest positive `t` with `observer + t·direction` on the spheroid
+pub(crate) fn first_hit(spheroid: &Spheroid, observer: [f64; 3], direction: [f64; 3]) -> Option<f64> {
+    // |p + t d|² = 1 in the unit-sphere frame
+    let (p, d) = (spheroid.scale_to_unit(observer), spheroid.scale_to_unit(direction));
+    let (qa, qb, qc) = (dot(d, d), dot(p, d), dot(p, p) - 1.0);
+    let disc = qb * qb - qa * qc;
+    if disc < 0.0 || qa == 0.0 {
+        return None;
+    }
+    // Stable roots, then the nearer one ahead of the observer
+    let q = -(qb + disc.sqrt().copysign(qb));
+    let (t0, t1) = (q / qa, qc / q);
+    let (near, far) = (t0.min(t1), t0.max(t1));
+    if near > 0.0 {
+        Some(near)
+    } else if far > 0.0 {
+        Some(far)
+    } else {
+        None
+    }
+}
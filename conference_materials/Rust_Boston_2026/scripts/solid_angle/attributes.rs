@@ -0,0 +1,82 @@
+//! Named per-element data, such as emissivity, temperature, region ID, or
+//! a computed solid angle per face.
+
+use std::collections::BTreeMap;
+
+/// Named arrays with one value per element (vertex, face, or cell).
+/// Every array has the same length, checked on insert and on
+/// deserialization, so an attribute can't go out of step with its mesh.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "AttributeMapData<T>", bound(deserialize = "T: serde::Deserialize<'de>")))]
+pub struct AttributeMap<T> {
+    len: usize,
+    attributes: BTreeMap<String, Vec<T>>,
+}
+
+impl<T> AttributeMap<T> {
+    /// Empty map for `len` elements
+    pub const fn new(len: usize) -> Self {
+        Self { len, attributes: BTreeMap::new() }
+    }
+
+    /// Number of elements each array covers
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no arrays
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    /// Add or replace an array, returning the one it replaced
+    pub fn insert(&mut self, name: impl Into<String>, values: Vec<T>) -> Result<Option<Vec<T>>, &'static str> {
+        if values.len() != self.len {
+            return Err("Dimension mismatch");
+        }
+        Ok(self.attributes.insert(name.into(), values))
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&[T]> {
+        self.attributes.get(name).map(Vec::as_slice)
+    }
+
+    /// Values are mutable in place; the length is not
+    #[inline]
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut [T]> {
+        self.attributes.get_mut(name).map(Vec::as_mut_slice)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Vec<T>> {
+        self.attributes.remove(name)
+    }
+
+    /// Arrays in name order
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&str, &[T])> + '_ {
+        self.attributes.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+}
+
+/// Unchecked serialized form of [AttributeMap]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AttributeMapData<T> {
+    len: usize,
+    attributes: BTreeMap<String, Vec<T>>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<AttributeMapData<T>> for AttributeMap<T> {
+    type Error = &'static str;
+
+    fn try_from(data: AttributeMapData<T>) -> Result<Self, Self::Error> {
+        if data.attributes.values().any(|v| v.len() != data.len) {
+            return Err("Dimension mismatch");
+        }
+        Ok(Self { len: data.len, attributes: data.attributes })
+    }
+}
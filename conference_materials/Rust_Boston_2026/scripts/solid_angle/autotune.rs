@@ -0,0 +1,224 @@
+//! Per-machine tuning of the parallel drivers' settings: the kernel path,
+//! worker threads, chunk cap and serial-fallback threshold.
+//!
+//! [tune] sweeps them on random tetrahedra through
+//! [crate::dispatch::solid_angle_tetrahedra_dispatch_par], the driver
+//! every setting affects, and [Tuning::save] caches the winner in a
+//! per-host file:
+//!
+//! ```toml
+//! host = "build-07"
+//! path = "avx2+fma"
+//! threads = 16
+//! max_chunk = 4096
+//! par_threshold = 8192
+//! ns_per_elem = 1.93
+//! ```
+//!
+//! under `$XDG_CACHE_HOME/solid_angle/` (or `~/.cache/solid_angle/`, or
+//! `%LOCALAPPDATA%\solid_angle\`), or at `$SOLID_ANGLE_TUNING` if set.
+//! [load] reads it back and [Tuning::apply] sets the process-wide knobs.
+//! The chunk cap and threshold are shared by every `_par` driver, so they
+//! are tuned once for the lot rather than per kernel.
+
+use crate::dispatch::{self, Path};
+use crate::inputs;
+use crate::par;
+use std::hint::black_box;
+use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Chunk caps tried
+const MAX_CHUNKS: [usize; 6] = [256, 1024, 4096, 16384, 65536, 262144];
+
+/// Elements per timed repetition, so small sizes still take measurable time
+const MIN_TIMED: usize = 1 << 16;
+
+/// Best settings found by [tune]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuning {
+    pub path: Path,
+    /// Worker threads; sizing the pool is up to the caller, see [Tuning::apply]
+    pub threads: usize,
+    /// See [crate::par::set_max_chunk]
+    pub max_chunk: usize,
+    /// See [crate::par::set_par_threshold]
+    pub par_threshold: usize,
+    /// Time per element with these settings at the tuned size
+    pub ns_per_elem: f64,
+}
+
+impl Tuning {
+    /// Set the kernel path, chunk cap and serial-fallback threshold for
+    /// the whole process. Threads aren't set, since rayon's global pool
+    /// can only be sized once and the caller may have other ideas.
+    pub fn apply(&self) -> Result<(), &'static str> {
+        dispatch::set_path(Some(self.path))?;
+        par::set_max_chunk(self.max_chunk);
+        par::set_par_threshold(self.par_threshold);
+        Ok(())
+    }
+
+    /// Write to the per-host file, returning its path
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let path = tuning_file()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, self.to_toml())?;
+        Ok(path)
+    }
+
+    fn to_toml(self) -> String {
+        format!(
+            "host = \"{}\"\npath = \"{}\"\nthreads = {}\nmax_chunk = {}\npar_threshold = {}\nns_per_elem = {:.3}\n",
+            host(),
+            self.path.name(),
+            self.threads,
+            self.max_chunk,
+            self.par_threshold,
+            self.ns_per_elem
+        )
+    }
+
+    /// Parse the flat `key = value` subset of TOML that [Tuning::save]
+    /// writes, rejecting another host's file
+    fn from_toml(s: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("Tuning file: {msg}"));
+        let get = |key: &str| {
+            s.lines()
+                .filter_map(|l| l.split_once('='))
+                .find(|(k, _)| k.trim() == key)
+                .map(|(_, v)| v.trim().trim_matches('"').to_owned())
+                .ok_or_else(|| invalid(&format!("missing {key}")))
+        };
+        if get("host")? != host() {
+            return Err(invalid("written on another host"));
+        }
+        let number = |v: String| v.parse::<usize>().map_err(|_| invalid("bad number"));
+        Ok(Self {
+            path: Path::from_name(&get("path")?).ok_or_else(|| invalid("unknown path"))?,
+            threads: number(get("threads")?)?,
+            max_chunk: number(get("max_chunk")?)?,
+            par_threshold: number(get("par_threshold")?)?,
+            ns_per_elem: get("ns_per_elem")?.parse().map_err(|_| invalid("bad number"))?,
+        })
+    }
+}
+
+/// This machine's name, made safe for a file name
+fn host() -> String {
+    let name = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| {
+            let out = std::process::Command::new("hostname").output().ok()?;
+            String::from_utf8(out.stdout).ok()
+        })
+        .unwrap_or_else(|| "localhost".into());
+    name.trim().chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' }).collect()
+}
+
+/// Where this host's tuning lives
+pub fn tuning_file() -> io::Result<PathBuf> {
+    if let Some(path) = std::env::var_os("SOLID_ANGLE_TUNING") {
+        return Ok(path.into());
+    }
+    let env = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let cache = env("XDG_CACHE_HOME")
+        .or_else(|| env("HOME").map(|h| h.join(".cache")))
+        .or_else(|| env("LOCALAPPDATA"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No cache directory for the tuning file"))?;
+    Ok(cache.join("solid_angle").join(format!("{}.toml", host())))
+}
+
+/// This host's saved tuning, or None if it hasn't been tuned
+pub fn load() -> io::Result<Option<Tuning>> {
+    match std::fs::read_to_string(tuning_file()?) {
+        Ok(s) => Tuning::from_toml(&s).map(Some),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+type Kernel = fn(&[[[f64; 3]; 4]], &mut [f64]) -> Result<(), &'static str>;
+
+/// Best-of-5 time per element of `f` over the first `m` elements,
+/// repeated to at least [MIN_TIMED] elements per timing
+fn ns_per_elem(tets: &[[[f64; 3]; 4]], out: &mut [f64], m: usize, f: Kernel) -> Result<f64, &'static str> {
+    let repeats = MIN_TIMED.div_ceil(m);
+    let mut best = f64::INFINITY;
+    for _ in 0..5 {
+        let start = Instant::now();
+        for _ in 0..repeats {
+            f(black_box(&tets[..m]), black_box(&mut out[..m]))?;
+        }
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    Ok(1e9 * best / (repeats * m) as f64)
+}
+
+/// Sweep kernel paths, thread counts and chunk caps on `n` random
+/// tetrahedra, then find where the tuned parallel driver starts beating
+/// the serial kernel. Takes a few seconds per million elements. The chunk
+/// cap and threshold in force beforehand are restored, and the path goes
+/// back to the detected one.
+pub fn tune(n: usize) -> Result<Tuning, &'static str> {
+    if n == 0 {
+        return Err("Need elements to tune on");
+    }
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 158, n);
+    let mut out = vec![0.0; n];
+    let (saved_chunk, saved_threshold) = (par::max_chunk(), par::par_threshold());
+
+    // Path: the serial kernel on each one this CPU can run
+    let mut best_path = (Path::Portable, f64::INFINITY);
+    for path in Path::ALL.into_iter().filter(|p| p.supported()) {
+        dispatch::set_path(Some(path))?;
+        let t = ns_per_elem(&tets, &mut out, n.min(MIN_TIMED), dispatch::solid_angle_tetrahedron_dispatch)?;
+        if t < best_path.1 {
+            best_path = (path, t);
+        }
+    }
+    dispatch::set_path(Some(best_path.0))?;
+
+    // Threads and chunk cap, always parallel; powers of two up to the
+    // physical cores, and the core count itself
+    let cores = num_cpus::get_physical();
+    let mut threads: Vec<usize> = (0..).map(|i| 1 << i).take_while(|&t| t < cores).chain([cores]).collect();
+    threads.dedup();
+    par::set_par_threshold(0);
+    let mut best = (1, par::DEFAULT_MAX_CHUNK, f64::INFINITY);
+    for &t in &threads {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(t).build().map_err(|_| "Failed to build a thread pool")?;
+        for chunk in MAX_CHUNKS.into_iter().filter(|&c| c <= n.div_ceil(t)) {
+            par::set_max_chunk(chunk);
+            let time = pool.install(|| ns_per_elem(&tets, &mut out, n, dispatch::solid_angle_tetrahedra_dispatch_par))?;
+            if time < best.2 {
+                best = (t, chunk, time);
+            }
+        }
+    }
+    let (threads, max_chunk, ns_per_elem_par) = best;
+
+    // Threshold: the smallest power of two from which on parallel wins
+    par::set_max_chunk(max_chunk);
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(|_| "Failed to build a thread pool")?;
+    let mut par_threshold = usize::MAX;
+    let sizes: Vec<usize> = (6..).map(|i| 1 << i).take_while(|&m| m <= n).collect();
+    for &m in sizes.iter().rev() {
+        let serial = ns_per_elem(&tets, &mut out, m, dispatch::solid_angle_tetrahedron_dispatch)?;
+        let parallel = pool.install(|| ns_per_elem(&tets, &mut out, m, dispatch::solid_angle_tetrahedra_dispatch_par))?;
+        if parallel >= serial {
+            break;
+        }
+        par_threshold = m;
+    }
+
+    dispatch::set_path(None)?;
+    par::set_max_chunk(saved_chunk);
+    par::set_par_threshold(saved_threshold);
+    Ok(Tuning { path: best_path.0, threads, max_chunk, par_threshold, ns_per_elem: ns_per_elem_par })
+}
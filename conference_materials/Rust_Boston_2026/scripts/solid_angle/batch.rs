@@ -0,0 +1,340 @@
+//! The batching, parallel and dispatch machinery of the tetrahedron
+//! kernel, for any per-element function.
+//!
+//! Implement [BatchKernel::eval] for one element, such as the potential
+//! of a set of sources at a point, and [BatchKernel::eval_slice] and
+//! [BatchKernel::eval_par] come with it: one bounds check per call, a
+//! copy of the loop compiled for AVX2 and FMA taken where [crate::dispatch]
+//! would take it, and the parallel driver's chunking and fallback to
+//! serial below [crate::par::par_threshold]. [Tetrahedra] is the solid
+//! angle kernel itself, written this way.
+//!
+//! [from_fn] makes a kernel of a closure, and [batch_kernel] writes the
+//! slice, parallel and `Vec` wrappers of a scalar function over
+//! fixed-size arrays, which most kernels here are, as functions named
+//! after it.
+//!
+//! A kernel that can fail on an element, such as the solid angle with
+//! degenerate tetrahedra as errors, implements [TryBatchKernel] instead.
+//! A failure doesn't abort the batch: the elements that fail are
+//! reported as `(index, error)` pairs in [BatchError::Elements], all of
+//! them or only the first as the [OnError] policy says, and every other
+//! element is written as usual.
+//!
+//! The AVX2 copy only pays off if `eval` inlines into it, so mark it
+//! `#[inline]`; a call out of the loop runs the portable build whatever
+//! the path. There is no NEON or RVV copy, as those paths are the
+//! tetrahedron kernel written by hand: on AArch64, where NEON is part of
+//! the baseline, a kernel runs as built.
+
+use crate::dispatch::{path, Path};
+use crate::par::{cancellable, chunk_len, par_threshold, CancellationToken};
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::vec3::sub;
+use rayon::prelude::*;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A function of one element, run over slices; see the module docs
+pub trait BatchKernel: Sync {
+    type Input: Sync;
+    type Output: Send;
+
+    /// One element
+    fn eval(&self, input: &Self::Input) -> Self::Output;
+
+    /// Every element of `inputs` into `out`, on the path from
+    /// [crate::dispatch::path]
+    #[inline]
+    fn eval_slice(&self, inputs: &[Self::Input], out: &mut [Self::Output]) -> Result<(), &'static str> {
+        // Check bounds
+        if inputs.len() != out.len() {
+            return Err("Dimension mismatch");
+        }
+
+        match path() {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: The path is only chosen when the CPU has AVX2 and FMA
+            Path::Avx2Fma => unsafe { eval_avx2(self, inputs, out) },
+            _ => eval_loop(self, inputs, out),
+        }
+        Ok(())
+    }
+
+    /// Thread-parallel [BatchKernel::eval_slice], chunked as
+    /// [crate::par::solid_angle_tetrahedra_par] and serial below
+    /// [crate::par::par_threshold] elements
+    fn eval_par(&self, inputs: &[Self::Input], out: &mut [Self::Output]) -> Result<(), &'static str> {
+        // Check bounds
+        if inputs.len() != out.len() {
+            return Err("Dimension mismatch");
+        }
+
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("eval_par", kernel = std::any::type_name::<Self>(), n = out.len(), parallel = out.len() >= par_threshold()).entered();
+
+        // Small batches are faster without the thread pool
+        if out.len() < par_threshold() {
+            return self.eval_slice(inputs, out);
+        }
+
+        let chunk = chunk_len(out.len());
+        (inputs.par_chunks(chunk), out.par_chunks_mut(chunk))
+            .into_par_iter()
+            .try_for_each(|(x, y)| self.eval_slice(x, y))
+    }
+
+    /// [BatchKernel::eval_par], stopped by `token`; see
+    /// [crate::par::CancellationToken]
+    fn eval_par_cancellable(&self, inputs: &[Self::Input], out: &mut [Self::Output], token: &CancellationToken) -> Result<(), &'static str> {
+        cancellable(inputs, out, token, |x, y| self.eval_slice(x, y))
+    }
+}
+
+/// Always inlined, so that the AVX2 copy has the loop and not a call
+#[inline(always)]
+fn eval_loop<K: BatchKernel + ?Sized>(kernel: &K, inputs: &[K::Input], out: &mut [K::Output]) {
+    for (x, y) in inputs.iter().zip(out.iter_mut()) {
+        *y = kernel.eval(x);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn eval_avx2<K: BatchKernel + ?Sized>(kernel: &K, inputs: &[K::Input], out: &mut [K::Output]) {
+    eval_loop(kernel, inputs, out); // Inlined, so compiled with the features above
+}
+
+/// [crate::tetrahedron::solid_angle_tetrahedron_scalar] as a
+/// [BatchKernel], bit-identical to the dispatch kernels on every path
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tetrahedra;
+
+impl BatchKernel for Tetrahedra {
+    type Input = [[f64; 3]; 4];
+    type Output = f64;
+
+    #[inline]
+    fn eval(&self, tet: &[[f64; 3]; 4]) -> f64 {
+        solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3])
+    }
+}
+
+/// [BatchKernel] evaluating a closure, from [from_fn]
+#[derive(Clone, Copy)]
+pub struct FromFn<I, O, F> {
+    f: F,
+    types: PhantomData<fn(&I) -> O>,
+}
+
+/// `f` as a [BatchKernel], for kernels with nothing else to hold
+#[inline]
+pub fn from_fn<I: Sync, O: Send, F: Fn(&I) -> O + Sync>(f: F) -> FromFn<I, O, F> {
+    FromFn { f, types: PhantomData }
+}
+
+impl<I: Sync, O: Send, F: Fn(&I) -> O + Sync> BatchKernel for FromFn<I, O, F> {
+    type Input = I;
+    type Output = O;
+
+    #[inline]
+    fn eval(&self, input: &I) -> O {
+        (self.f)(input)
+    }
+}
+
+/// Slice, parallel and `Vec` wrappers of a scalar function, through
+/// [from_fn]. Given the element type and how it unpacks into the
+/// function's arguments,
+///
+/// ```ignore
+/// batch_kernel! {
+///     /// Vector variant of [signed_area_triangle_scalar]
+///     pub fn signed_area_triangle_scalar([p0, p1, p2]: [[f64; 2]; 3]) -> f64
+///         => signed_area_triangle, signed_area_triangle_par, signed_area_triangle_vec
+/// }
+/// ```
+///
+/// writes `signed_area_triangle(&[[[f64; 2]; 3]], &mut [f64])` by
+/// [BatchKernel::eval_slice], `signed_area_triangle_par` with the same
+/// signature by [BatchKernel::eval_par], and `signed_area_triangle_vec`,
+/// the parallel one into a new `Vec`, for outputs that are
+/// `Default + Clone`. The attributes go on the slice function and the
+/// visibility on all three; the scalar function is written as usual.
+/// An element passed whole is `fn scalar(x: [f64; 3]) -> f64`. Either
+/// way elements are passed by value, so must be `Copy`, as arrays of
+/// numbers are.
+#[allow(unused_macros)] // As dead_code in each script, for those that don't use it
+macro_rules! batch_kernel {
+    (@wrappers $(#[$attr:meta])*, $vis:vis, $scalar:ident, $eval:expr, $in:ty, $out:ty, $slice:ident, $par:ident, $vec:ident) => {
+        $(#[$attr])*
+        #[inline]
+        $vis fn $slice(inputs: &[$in], out: &mut [$out]) -> Result<(), &'static str> {
+            $crate::batch::BatchKernel::eval_slice(&$crate::batch::from_fn($eval), inputs, out)
+        }
+
+        #[doc = concat!("Thread-parallel [", stringify!($slice), "], chunked as [crate::par::solid_angle_tetrahedra_par]")]
+        #[doc = "and serial below [crate::par::par_threshold] elements"]
+        $vis fn $par(inputs: &[$in], out: &mut [$out]) -> Result<(), &'static str> {
+            $crate::batch::BatchKernel::eval_par(&$crate::batch::from_fn($eval), inputs, out)
+        }
+
+        #[doc = concat!("[", stringify!($par), "] into a new `Vec`")]
+        $vis fn $vec(inputs: &[$in]) -> Vec<$out> {
+            let mut out = vec![<$out>::default(); inputs.len()];
+            $par(inputs, &mut out).expect("Output sized to the input");
+            out
+        }
+    };
+    ($(#[$attr:meta])* $vis:vis fn $scalar:ident([$($v:ident),+ $(,)?]: $in:ty) -> $out:ty => $slice:ident, $par:ident, $vec:ident $(,)?) => {
+        $crate::batch::batch_kernel!(@wrappers $(#[$attr])*, $vis, $scalar, |&[$($v),+]: &$in| $scalar($($v),+), $in, $out, $slice, $par, $vec);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $scalar:ident($x:ident: $in:ty) -> $out:ty => $slice:ident, $par:ident, $vec:ident $(,)?) => {
+        $crate::batch::batch_kernel!(@wrappers $(#[$attr])*, $vis, $scalar, |&$x: &$in| $scalar($x), $in, $out, $slice, $par, $vec);
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use batch_kernel;
+
+/// Which failures a [TryBatchKernel] batch reports
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OnError {
+    /// The first by index, after which elements may be left unwritten
+    #[default]
+    FailFast,
+    /// Every one, with every other element written
+    CollectAll,
+}
+
+/// Why a [TryBatchKernel] batch failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum BatchError<E> {
+    /// Inputs and outputs of different lengths; nothing was written
+    DimensionMismatch,
+    /// Elements that failed, as `(index, error)` in index order. Their
+    /// outputs are left as they were.
+    Elements(Vec<(usize, E)>),
+}
+
+impl<E: fmt::Display> fmt::Display for BatchError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DimensionMismatch => write!(f, "Dimension mismatch"),
+            Self::Elements(errors) => match errors.first() {
+                Some((i, e)) => write!(f, "{} elements failed, first {i}: {e}", errors.len()),
+                None => write!(f, "No elements failed"),
+            },
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for BatchError<E> {}
+
+/// A function of one element that can fail, run over slices; see the
+/// module docs
+pub trait TryBatchKernel: Sync {
+    type Input: Sync;
+    type Output: Send;
+    type Error: Send;
+
+    /// One element
+    fn try_eval(&self, input: &Self::Input) -> Result<Self::Output, Self::Error>;
+
+    /// Every element of `inputs` into `out` as [BatchKernel::eval_slice],
+    /// reporting failures by `policy`
+    #[inline]
+    fn try_eval_slice(&self, inputs: &[Self::Input], out: &mut [Self::Output], policy: OnError) -> Result<(), BatchError<Self::Error>> {
+        // Check bounds
+        if inputs.len() != out.len() {
+            return Err(BatchError::DimensionMismatch);
+        }
+
+        failures(try_dispatch(self, inputs, out, 0, policy))
+    }
+
+    /// Thread-parallel [TryBatchKernel::try_eval_slice], as
+    /// [BatchKernel::eval_par]. Under [OnError::FailFast], chunks after
+    /// the one with the first failure stop early.
+    fn try_eval_par(&self, inputs: &[Self::Input], out: &mut [Self::Output], policy: OnError) -> Result<(), BatchError<Self::Error>> {
+        // Check bounds
+        if inputs.len() != out.len() {
+            return Err(BatchError::DimensionMismatch);
+        }
+
+        #[cfg(feature = "trace")]
+        let _span = tracing::debug_span!("try_eval_par", kernel = std::any::type_name::<Self>(), n = out.len(), parallel = out.len() >= par_threshold()).entered();
+
+        // Small batches are faster without the thread pool
+        if out.len() < par_threshold() {
+            return self.try_eval_slice(inputs, out, policy);
+        }
+
+        let chunk = chunk_len(out.len());
+        let chunks = (inputs.par_chunks(chunk), out.par_chunks_mut(chunk)).into_par_iter().enumerate();
+        let errors = match policy {
+            OnError::FailFast => chunks.find_map_first(|(c, (x, y))| try_dispatch(self, x, y, c * chunk, policy).pop()).into_iter().collect(),
+            OnError::CollectAll => chunks.map(|(c, (x, y))| try_dispatch(self, x, y, c * chunk, policy)).collect::<Vec<_>>().into_iter().flatten().collect(),
+        };
+        failures(errors)
+    }
+}
+
+fn failures<E>(errors: Vec<(usize, E)>) -> Result<(), BatchError<E>> {
+    if errors.is_empty() { Ok(()) } else { Err(BatchError::Elements(errors)) }
+}
+
+/// Failures of `kernel` over `inputs`, indexed from `first`, on the path
+/// from [crate::dispatch::path]
+#[inline]
+fn try_dispatch<K: TryBatchKernel + ?Sized>(kernel: &K, inputs: &[K::Input], out: &mut [K::Output], first: usize, policy: OnError) -> Vec<(usize, K::Error)> {
+    match path() {
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: The path is only chosen when the CPU has AVX2 and FMA
+        Path::Avx2Fma => unsafe { try_avx2(kernel, inputs, out, first, policy) },
+        _ => try_loop(kernel, inputs, out, first, policy),
+    }
+}
+
+/// Always inlined, as [eval_loop]
+#[inline(always)]
+fn try_loop<K: TryBatchKernel + ?Sized>(kernel: &K, inputs: &[K::Input], out: &mut [K::Output], first: usize, policy: OnError) -> Vec<(usize, K::Error)> {
+    let mut errors = Vec::new();
+    for (i, (x, y)) in inputs.iter().zip(out.iter_mut()).enumerate() {
+        match kernel.try_eval(x) {
+            Ok(v) => *y = v,
+            Err(e) => {
+                errors.push((first + i, e));
+                if policy == OnError::FailFast {
+                    break;
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn try_avx2<K: TryBatchKernel + ?Sized>(kernel: &K, inputs: &[K::Input], out: &mut [K::Output], first: usize, policy: OnError) -> Vec<(usize, K::Error)> {
+    try_loop(kernel, inputs, out, first, policy) // Inlined, so compiled with the features above
+}
+
+/// [Tetrahedra], failing where the apex coincides with another vertex and
+/// the solid angle is undefined, instead of giving zero: the CLI's
+/// `degeneracy = "error"`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StrictTetrahedra;
+
+impl TryBatchKernel for StrictTetrahedra {
+    type Input = [[f64; 3]; 4];
+    type Output = f64;
+    type Error = &'static str;
+
+    #[inline]
+    fn try_eval(&self, tet: &[[f64; 3]; 4]) -> Result<f64, &'static str> {
+        if tet[1..].iter().any(|&v| sub(v, tet[0]) == [0.0; 3]) {
+            return Err("Degenerate tetrahedron");
+        }
+        Ok(Tetrahedra.eval(tet))
+    }
+}
@@ -0,0 +1,123 @@
+//! Coalescing queue in front of the parallel driver, for services with
+//! many small concurrent requests.
+//!
+//! One task launches the kernel at a time, over everything queued since
+//! the last launch, up to a batch size; while it runs, new requests pile
+//! up and become the next launch. Small requests thus share the thread
+//! pool instead of each paying its fork/join overhead. Queued work is
+//! capped by a memory budget: once it is spent, submitters wait for
+//! earlier work to finish, which pushes back on clients rather than
+//! buffering without bound.
+//!
+//! Each launch gathers its inputs into one buffer and writes one buffer
+//! of results. Both come from [Arena]s the queue keeps between launches,
+//! so a busy queue reuses the same batch-sized blocks instead of
+//! allocating and freeing them at every launch.
+
+use crate::arena::Arena;
+use crate::par::solid_angle_tetrahedra_par;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+
+/// Bytes held per queued tetrahedron: its input and its result
+const ELEMENT_BYTES: usize = 96 + 8;
+
+#[derive(Default)]
+struct Stats {
+    launches: AtomicU64,
+    elements: AtomicU64,
+}
+
+struct Job {
+    tetrahedra: Vec<[[f64; 3]; 4]>,
+    reply: oneshot::Sender<Result<Vec<f64>, &'static str>>,
+    _budget: OwnedSemaphorePermit, // Returned once the results are handed off
+}
+
+/// Handle to the queue; clones share it
+#[derive(Clone)]
+pub struct BatchQueue {
+    jobs: mpsc::UnboundedSender<Job>,
+    budget: Arc<Semaphore>,
+    capacity: u32,
+    stats: Arc<Stats>,
+}
+
+impl BatchQueue {
+    /// Start the queue's task on the current tokio runtime. Launches
+    /// coalesce up to `batch` tetrahedra, and at most `budget_bytes` of
+    /// inputs and outputs are queued or running at once.
+    pub fn new(batch: usize, budget_bytes: usize) -> Self {
+        let capacity = (budget_bytes / ELEMENT_BYTES).clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize)) as u32;
+        let (jobs, rx) = mpsc::unbounded_channel();
+        let stats = Arc::new(Stats::default());
+        tokio::spawn(run(rx, batch.max(1), stats.clone()));
+        Self { jobs, budget: Arc::new(Semaphore::new(capacity as usize)), capacity, stats }
+    }
+
+    /// Solid angles of `tetrahedra`, once a launch has included them.
+    /// Waits for budget first if the queue is full.
+    pub async fn solid_angles(&self, tetrahedra: Vec<[[f64; 3]; 4]>) -> Result<Vec<f64>, &'static str> {
+        let n = u32::try_from(tetrahedra.len()).ok().filter(|&n| n <= self.capacity);
+        let n = n.ok_or("Request exceeds the memory budget")?;
+        let permit = self.budget.clone().acquire_many_owned(n).await.map_err(|_| "Queue closed")?;
+
+        let (reply, result) = oneshot::channel();
+        self.jobs.send(Job { tetrahedra, reply, _budget: permit }).map_err(|_| "Queue closed")?;
+        result.await.map_err(|_| "Queue closed")?
+    }
+
+    /// Kernel launches so far, and the tetrahedra they covered
+    pub fn stats(&self) -> (u64, u64) {
+        (self.stats.launches.load(Ordering::Relaxed), self.stats.elements.load(Ordering::Relaxed))
+    }
+}
+
+/// Drain the queue a launch at a time
+async fn run(mut rx: mpsc::UnboundedReceiver<Job>, batch: usize, stats: Arc<Stats>) {
+    let mut arenas = (Arena::new(), Arena::new());
+    while let Some(first) = rx.recv().await {
+        // Everything already waiting joins this launch, up to the batch size
+        let mut n = first.tetrahedra.len();
+        let mut jobs = vec![first];
+        while n < batch {
+            let Ok(job) = rx.try_recv() else { break };
+            n += job.tetrahedra.len();
+            jobs.push(job);
+        }
+        stats.launches.fetch_add(1, Ordering::Relaxed);
+        stats.elements.fetch_add(n as u64, Ordering::Relaxed);
+
+        let (mut inputs, mut outputs) = arenas;
+        let task = tokio::task::spawn_blocking(move || {
+            let result = launch(&jobs, n, &mut inputs, &mut outputs);
+
+            // Hand each job its own span of the results
+            let mut start = 0;
+            for job in jobs {
+                let len = job.tetrahedra.len();
+                let _ = job.reply.send(result.map(|out| out[start..start + len].to_vec())); // Submitter may be gone
+                start += len;
+            }
+            (inputs, outputs)
+        });
+        // A panic drops its replies, which submitters see as closed, and
+        // its arenas, which the next launch starts over
+        arenas = task.await.unwrap_or_default();
+    }
+}
+
+/// Solid angles of every job's tetrahedra, in order, gathered into and
+/// written to buffers from the arenas
+fn launch<'a>(jobs: &[Job], n: usize, inputs: &mut Arena<[[f64; 3]; 4]>, outputs: &'a mut Arena<f64>) -> Result<&'a [f64], &'static str> {
+    let [tetrahedra] = inputs.buffers([n])?;
+    let [out] = outputs.buffers([n])?;
+    let mut start = 0;
+    for job in jobs {
+        tetrahedra[start..start + job.tetrahedra.len()].copy_from_slice(&job.tetrahedra);
+        start += job.tetrahedra.len();
+    }
+    solid_angle_tetrahedra_par(tetrahedra, out)?;
+    Ok(out)
+}
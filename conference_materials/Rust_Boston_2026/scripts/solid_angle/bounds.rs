@@ -0,0 +1,279 @@
+//! Axis-aligned bounding boxes and bounding spheres, per element and
+//! over whole point sets.
+//!
+//! Two bounding spheres: [ritter_sphere] is Ritter's one-pass
+//! approximation, often 5-25% larger than the minimum, and
+//! [welzl_sphere] is the exact minimum by Welzl's randomized incremental
+//! algorithm, in expected linear time but serial. Ritter's grows chunks
+//! in parallel from a shared starting sphere and merges them, so it is
+//! the one for large sets where a slightly loose sphere is fine.
+
+use crate::inputs::Pcg64;
+use crate::par::{chunk_len, par_threshold};
+use crate::vec3::{cross, dot, norm, sub};
+use rayon::prelude::*;
+
+/// Relative slack in containment tests, absorbing rounding in centers
+/// computed from a few points
+const SLACK: f64 = 1e-12;
+
+/// Axis-aligned box from `lo` to `hi`; empty when any `lo > hi`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub lo: [f64; 3],
+    pub hi: [f64; 3],
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Aabb {
+    /// Box containing nothing, the identity of [Aabb::union]
+    pub const EMPTY: Self = Self { lo: [f64::INFINITY; 3], hi: [f64::NEG_INFINITY; 3] };
+
+    /// Smallest box containing `points`
+    pub fn from_points(points: &[[f64; 3]]) -> Self {
+        points.iter().fold(Self::EMPTY, |b, &p| b.include(p))
+    }
+
+    #[inline]
+    pub fn include(self, p: [f64; 3]) -> Self {
+        Self { lo: std::array::from_fn(|k| self.lo[k].min(p[k])), hi: std::array::from_fn(|k| self.hi[k].max(p[k])) }
+    }
+
+    #[inline]
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            lo: std::array::from_fn(|k| self.lo[k].min(other.lo[k])),
+            hi: std::array::from_fn(|k| self.hi[k].max(other.hi[k])),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|k| self.lo[k] > self.hi[k])
+    }
+
+    #[inline]
+    pub fn contains(&self, p: [f64; 3]) -> bool {
+        (0..3).all(|k| self.lo[k] <= p[k] && p[k] <= self.hi[k])
+    }
+
+    #[inline]
+    pub fn center(&self) -> [f64; 3] {
+        std::array::from_fn(|k| 0.5 * (self.lo[k] + self.hi[k]))
+    }
+
+    /// Side lengths
+    #[inline]
+    pub fn extent(&self) -> [f64; 3] {
+        sub(self.hi, self.lo)
+    }
+
+    /// Area of the box's surface; zero when empty
+    #[inline]
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let [x, y, z] = self.extent();
+        2.0 * (x * y + y * z + z * x)
+    }
+}
+
+/// Ball of `radius` about `center`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: [f64; 3],
+    pub radius: f64,
+}
+
+impl Sphere {
+    /// Sphere containing nothing; any point replaces it
+    pub const EMPTY: Self = Self { center: [0.0; 3], radius: f64::NEG_INFINITY };
+
+    /// Whether `p` is inside, up to rounding
+    #[inline]
+    pub fn contains(&self, p: [f64; 3]) -> bool {
+        norm(sub(p, self.center)) <= self.radius * (1.0 + SLACK)
+    }
+
+    /// Smallest sphere containing this one and `p`
+    #[inline]
+    pub fn include(self, p: [f64; 3]) -> Self {
+        if self.radius < 0.0 {
+            return Self { center: p, radius: 0.0 };
+        }
+        let d = norm(sub(p, self.center));
+        if d <= self.radius {
+            return self;
+        }
+        // Move the center toward p, keeping the far side where it is
+        let radius = 0.5 * (self.radius + d);
+        let t = (radius - self.radius) / d;
+        Self { center: std::array::from_fn(|k| self.center[k] + t * (p[k] - self.center[k])), radius }
+    }
+
+    /// Smallest sphere containing both
+    pub fn union(self, other: Self) -> Self {
+        let (big, small) = if self.radius >= other.radius { (self, other) } else { (other, self) };
+        if small.radius < 0.0 {
+            return big;
+        }
+        let d = norm(sub(small.center, big.center));
+        if d + small.radius <= big.radius {
+            return big;
+        }
+        let radius = 0.5 * (d + big.radius + small.radius);
+        let t = (radius - big.radius) / d;
+        Self { center: std::array::from_fn(|k| big.center[k] + t * (small.center[k] - big.center[k])), radius }
+    }
+}
+
+/// Box around all of `points`, reduced in parallel
+pub fn aabb(points: &[[f64; 3]]) -> Aabb {
+    if points.len() < par_threshold() {
+        return Aabb::from_points(points);
+    }
+    points.par_chunks(chunk_len(points.len())).map(Aabb::from_points).reduce(|| Aabb::EMPTY, Aabb::union)
+}
+
+/// Box around each element, such as a triangle or tetrahedron
+pub fn aabbs<const N: usize>(elements: &[[[f64; 3]; N]], out: &mut [Aabb]) -> Result<(), &'static str> {
+    // Check bounds
+    if elements.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    if out.len() < par_threshold() {
+        elements.iter().zip(out.iter_mut()).for_each(|(e, b)| *b = Aabb::from_points(e));
+    } else {
+        (elements, out).into_par_iter().for_each(|(e, b)| *b = Aabb::from_points(e));
+    }
+    Ok(())
+}
+
+/// Ritter's approximate bounding sphere: start from the sphere on a far
+/// pair of points, then grow it over every point, in parallel chunks
+pub fn ritter_sphere(points: &[[f64; 3]]) -> Sphere {
+    let Some(&first) = points.first() else {
+        return Sphere::EMPTY;
+    };
+    let farthest_from = |q: [f64; 3]| {
+        points
+            .par_iter()
+            .copied()
+            .map(|p| (dot(sub(p, q), sub(p, q)), p))
+            .reduce(|| (f64::NEG_INFINITY, q), |a, b| if b.0 > a.0 { b } else { a })
+            .1
+    };
+    let y = farthest_from(first);
+    let z = farthest_from(y);
+    let start = Sphere { center: std::array::from_fn(|k| 0.5 * (y[k] + z[k])), radius: 0.5 * norm(sub(z, y)) };
+
+    points
+        .par_chunks(chunk_len(points.len()))
+        .map(|c| c.iter().fold(start, |s, &p| s.include(p)))
+        .reduce(|| start, Sphere::union)
+}
+
+/// Exact minimum bounding sphere of `points`, by Welzl's algorithm in
+/// its iterative form over a fixed shuffle
+pub fn welzl_sphere(points: &[[f64; 3]]) -> Sphere {
+    // A fixed seed keeps results reproducible; any order is correct
+    let mut p = points.to_vec();
+    let mut rng = Pcg64::new(0, 0);
+    for i in (1..p.len()).rev() {
+        p.swap(i, rng.below(i + 1));
+    }
+    minimum_sphere(&p)
+}
+
+/// Minimum sphere of a few points each, such as triangles or tetrahedra
+pub fn spheres<const N: usize>(elements: &[[[f64; 3]; N]], out: &mut [Sphere]) -> Result<(), &'static str> {
+    // Check bounds
+    if elements.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    if out.len() < par_threshold() {
+        elements.iter().zip(out.iter_mut()).for_each(|(e, s)| *s = minimum_sphere(e));
+    } else {
+        (elements, out).into_par_iter().for_each(|(e, s)| *s = minimum_sphere(e));
+    }
+    Ok(())
+}
+
+/// Welzl's algorithm with the recursion unrolled into its four levels of
+/// boundary points; expected linear time when `p` is in random order
+fn minimum_sphere(p: &[[f64; 3]]) -> Sphere {
+    let mut s = Sphere::EMPTY;
+    for i in 0..p.len() {
+        if s.contains(p[i]) {
+            continue;
+        }
+        s = Sphere { center: p[i], radius: 0.0 };
+        for j in 0..i {
+            if s.contains(p[j]) {
+                continue;
+            }
+            s = sphere_2(p[i], p[j]);
+            for k in 0..j {
+                if s.contains(p[k]) {
+                    continue;
+                }
+                s = sphere_3(p[i], p[j], p[k]);
+                for l in 0..k {
+                    if !s.contains(p[l]) {
+                        // Coplanar points only get here by rounding, and
+                        // the current sphere is then as good as any
+                        s = sphere_4(p[i], p[j], p[k], p[l]).unwrap_or(s);
+                    }
+                }
+            }
+        }
+    }
+    s
+}
+
+/// Radius reaching every defining point, absorbing rounding in `center`
+fn through(center: [f64; 3], points: &[[f64; 3]]) -> Sphere {
+    let radius = points.iter().map(|&p| norm(sub(p, center))).fold(0.0, f64::max);
+    Sphere { center, radius }
+}
+
+fn sphere_2(a: [f64; 3], b: [f64; 3]) -> Sphere {
+    through(std::array::from_fn(|k| 0.5 * (a[k] + b[k])), &[a, b])
+}
+
+/// Smallest sphere with `a`, `b`, `c` on its boundary: the circumcircle's,
+/// or for (nearly) collinear points, the sphere on the farthest pair
+fn sphere_3(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> Sphere {
+    let (u, v) = (sub(b, a), sub(c, a));
+    let w = cross(u, v);
+    let ww = dot(w, w);
+    let (uu, vv) = (dot(u, u), dot(v, v));
+    if ww <= f64::EPSILON * uu * vv {
+        let pairs = [sphere_2(a, b), sphere_2(a, c), sphere_2(b, c)];
+        return pairs.into_iter().max_by(|x, y| x.radius.total_cmp(&y.radius)).unwrap();
+    }
+    let (vw, wu) = (cross(v, w), cross(w, u));
+    let center = std::array::from_fn(|k| a[k] + (uu * vw[k] + vv * wu[k]) / (2.0 * ww));
+    through(center, &[a, b, c])
+}
+
+/// Circumsphere of a tetrahedron, or None if it is (nearly) flat
+fn sphere_4(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> Option<Sphere> {
+    let (u, v, w) = (sub(b, a), sub(c, a), sub(d, a));
+    let (vw, wu, uv) = (cross(v, w), cross(w, u), cross(u, v));
+    let det = dot(u, vw);
+    let (uu, vv, ww) = (dot(u, u), dot(v, v), dot(w, w));
+    if det.abs() <= f64::EPSILON * (uu * vv * ww).sqrt() {
+        return None;
+    }
+    let center = std::array::from_fn(|k| a[k] + (uu * vw[k] + vv * wu[k] + ww * uv[k]) / (2.0 * det));
+    Some(through(center, &[a, b, c, d]))
+}
@@ -0,0 +1,428 @@
+//! Bounding volume hierarchy over the faces of a triangle mesh, for
+//! occlusion and horizon queries.
+//!
+//! Built top-down by splitting each node's faces at the median centroid
+//! along its box's longest axis, down to [LEAF] faces, so the tree is
+//! balanced, `log2(n / LEAF)` levels deep. Nodes are stored flat in
+//! depth-first order: an interior node's left child follows it, and it
+//! records where its right child is. Queries walk the tree with a fixed
+//! stack, which that depth keeps small.
+//!
+//! Moving geometry is followed by [Bvh::refit], which keeps the tree
+//! and recomputes its boxes. Faces that travel far from the ones they
+//! were grouped with stretch the boxes over them, and queries slow down
+//! (the results stay exact). [Bvh::growth] measures that as the surface
+//! area heuristic (SAH) cost against the cost when built, and
+//! [Bvh::rebuild_degraded] regroups just the subtrees whose boxes have
+//! grown past a limit. A subtree's shape depends only on how many faces
+//! it holds, so it is rebuilt in place, in the same nodes. Run every
+//! frame, it takes its working buffers from an [Arena] rather than
+//! allocating them each time ([Bvh::rebuild_degraded_in]).
+//!
+//! The BVH holds face indices, not a copy of the mesh, so queries take
+//! the mesh it was built from again; [Bvh::faces] guards against passing
+//! another.
+
+use crate::arena::Arena;
+use crate::bounds::Aabb;
+use crate::mesh::TriMesh;
+use crate::vec3::{cross, dot, sub};
+
+/// Most faces per leaf
+pub const LEAF: usize = 4;
+
+/// Segment parameters within this of either end don't count as hits, so
+/// points on the mesh itself (stations on a surface, vertices) don't
+/// occlude themselves
+pub const T_EPS: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Node {
+    bounds: Aabb,
+    /// Leaf: first face in `order`. Interior: index of the right child.
+    start: u32,
+    /// Faces in a leaf; zero for interior nodes
+    count: u32,
+}
+
+/// See the module docs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Face indices, each leaf's contiguous
+    order: Vec<u32>,
+    /// Surface area of each node's box when its subtree was last built
+    built: Vec<f64>,
+}
+
+impl Bvh {
+    /// Hierarchy over every face of `mesh`
+    pub fn new(mesh: &TriMesh) -> Self {
+        let mut order: Vec<u32> = (0..mesh.faces().len() as u32).collect();
+        let centroids: Vec<[f64; 3]> = mesh.triangles().map(|[a, b, c]| std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0)).collect();
+        let mut nodes = Vec::with_capacity(2 * order.len().div_ceil(LEAF));
+        if !order.is_empty() {
+            build(mesh, &centroids, &mut order, 0, &mut nodes);
+        }
+        let built = nodes.iter().map(|n| n.bounds.surface_area()).collect();
+        Self { nodes, order, built }
+    }
+
+    /// Faces of the mesh this was built from
+    #[inline]
+    pub fn faces(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Box around every face; empty for an empty mesh
+    #[inline]
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |root| root.bounds)
+    }
+
+    /// Recompute every box for `mesh`'s current vertex positions, keeping
+    /// the tree. Linear time against `n log n` to rebuild, and queries
+    /// stay exact, but boxes loosen as faces drift from the neighbours
+    /// they were grouped with, so rebuild after large motion.
+    pub fn refit(&mut self, mesh: &TriMesh) {
+        self.refit_by(mesh, |_| true);
+    }
+
+    /// [Bvh::refit] only where `faces` (indices into the mesh) lie: their
+    /// leaves and the nodes above them
+    pub fn refit_faces(&mut self, mesh: &TriMesh, faces: &[u32]) {
+        let mut moved = vec![false; self.faces()];
+        faces.iter().for_each(|&f| moved[f as usize] = true);
+        self.refit_by(mesh, |f| moved[f as usize]);
+    }
+
+    fn refit_by(&mut self, mesh: &TriMesh, moved: impl Fn(u32) -> bool) {
+        // Children follow their parent, so in reverse they come first
+        let mut changed = vec![false; self.nodes.len()];
+        for i in (0..self.nodes.len()).rev() {
+            let Node { start, count, .. } = self.nodes[i];
+            if count > 0 {
+                let faces = &self.order[start as usize..(start + count) as usize];
+                if faces.iter().any(|&f| moved(f)) {
+                    self.nodes[i].bounds = faces.iter().fold(Aabb::EMPTY, |b, &f| mesh.triangle(f as usize).into_iter().fold(b, Aabb::include));
+                    changed[i] = true;
+                }
+            } else if changed[i + 1] || changed[start as usize] {
+                self.nodes[i].bounds = self.nodes[i + 1].bounds.union(self.nodes[start as usize].bounds);
+                changed[i] = true;
+            }
+        }
+    }
+
+    /// SAH cost of the tree, the summed surface areas of its boxes, over
+    /// that cost when each subtree was built: 1 when fresh, rising as
+    /// refits stretch boxes, which slows queries about in proportion
+    pub fn growth(&self) -> f64 {
+        let now: f64 = self.nodes.iter().map(|n| n.bounds.surface_area()).sum();
+        let built: f64 = self.built.iter().sum();
+        if built > 0.0 {
+            now / built
+        } else {
+            1.0
+        }
+    }
+
+    /// Rebuild, in place, each highest subtree whose SAH cost is more than
+    /// `limit` times what it was when built, for `mesh`'s current vertex
+    /// positions; returns how many faces were regrouped. The tree should
+    /// be refit first. A limit of 2 leaves the growth from small motions
+    /// alone and regroups what has really moved.
+    pub fn rebuild_degraded(&mut self, mesh: &TriMesh, limit: f64) -> usize {
+        self.rebuild_degraded_in(mesh, limit, &mut Arena::new())
+    }
+
+    /// [Bvh::rebuild_degraded], with its working buffers from `scratch`
+    pub fn rebuild_degraded_in(&mut self, mesh: &TriMesh, limit: f64, scratch: &mut Arena<f64>) -> usize {
+        let n = self.nodes.len();
+        let [now, then, centroids] = scratch.buffers([n, n, 3 * self.faces()]).expect("Sizes of a tree in memory");
+        let centroids = centroids.as_chunks_mut::<3>().0;
+
+        // Each subtree's cost now and when built, children first
+        then.copy_from_slice(&self.built);
+        for i in (0..n).rev() {
+            let Node { bounds, start, count } = self.nodes[i];
+            now[i] = bounds.surface_area();
+            if count == 0 {
+                now[i] += now[i + 1] + now[start as usize];
+                then[i] += then[i + 1] + then[start as usize];
+            }
+        }
+
+        let (mut have_centroids, mut nodes) = (false, Vec::new());
+        let mut regrouped = 0;
+        let mut stack = if self.nodes.is_empty() { vec![] } else { vec![0_u32] };
+        while let Some(i) = stack.pop() {
+            let Node { start, count, .. } = self.nodes[i as usize];
+            if count > 0 {
+                continue; // Leaves have nothing to regroup
+            }
+            if now[i as usize] <= limit * then[i as usize] {
+                stack.extend([start, i + 1]);
+                continue;
+            }
+
+            // Its faces are the order from its leftmost leaf to its
+            // rightmost; the same count gives the same shape, so the new
+            // nodes fill the old ones' places
+            if !have_centroids {
+                for (c, [a, b, d]) in centroids.iter_mut().zip(mesh.triangles()) {
+                    *c = std::array::from_fn(|k| (a[k] + b[k] + d[k]) / 3.0);
+                }
+                have_centroids = true;
+            }
+            let first = self.leftmost(i);
+            let last = self.rightmost(i);
+            let (lo, hi) = (first.start as usize, (last.start + last.count) as usize);
+            build(mesh, centroids, &mut self.order[lo..hi], lo, &mut nodes);
+            for (k, mut node) in nodes.drain(..).enumerate() {
+                if node.count == 0 {
+                    node.start += i; // Right child, numbered from the subtree's root
+                }
+                self.built[i as usize + k] = node.bounds.surface_area();
+                self.nodes[i as usize + k] = node;
+            }
+            regrouped += hi - lo;
+        }
+        // Each rebuilt subtree covers the same faces as before, so its box
+        // and every box above it are unchanged
+        regrouped
+    }
+
+    fn leftmost(&self, mut i: u32) -> Node {
+        while self.nodes[i as usize].count == 0 {
+            i += 1;
+        }
+        self.nodes[i as usize]
+    }
+
+    fn rightmost(&self, mut i: u32) -> Node {
+        while self.nodes[i as usize].count == 0 {
+            i = self.nodes[i as usize].start;
+        }
+        self.nodes[i as usize]
+    }
+
+    /// Whether any face of `mesh` crosses the segment from `from` to `to`,
+    /// excluding [T_EPS] at either end
+    pub fn occluded(&self, mesh: &TriMesh, from: [f64; 3], to: [f64; 3]) -> bool {
+        let Some(root) = self.nodes.first() else {
+            return false;
+        };
+        let dir = sub(to, from);
+        let inv = dir.map(|d| 1.0 / d);
+        if !slab(&root.bounds, from, inv) {
+            return false;
+        }
+
+        let mut stack = [0_u32; 64]; // One more than the depth, at most
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let node = &self.nodes[stack[top] as usize];
+            if node.count > 0 {
+                let faces = &self.order[node.start as usize..(node.start + node.count) as usize];
+                if faces.iter().any(|&f| hits(mesh.triangle(f as usize), from, dir)) {
+                    return true;
+                }
+                continue;
+            }
+            let (left, right) = (stack[top] + 1, node.start);
+            for child in [right, left] {
+                if slab(&self.nodes[child as usize].bounds, from, inv) {
+                    stack[top] = child;
+                    top += 1;
+                }
+            }
+        }
+        false
+    }
+
+    /// Highest elevation angle (rad) of any point of `mesh` in the
+    /// vertical half-plane from `from` toward the horizontal unit `dir`
+    /// (east, north), `z` being up; `None` if the mesh doesn't reach into
+    /// it. Exact, by branch and bound: nodes are visited highest bound
+    /// first and skipped once they can't beat the best found.
+    pub fn max_elevation(&self, mesh: &TriMesh, from: [f64; 3], dir: [f64; 2]) -> Option<f64> {
+        let inv = dir.map(|d| 1.0 / d);
+        let mut best: Option<Slope> = None;
+        let beats = |s: Slope, best: Option<Slope>| best.is_none_or(|b| higher(s, b));
+
+        let mut stack = [(0_u32, (0.0, 0.0)); 64]; // One more than the depth, at most
+        let mut top = 0;
+        if let Some(bound) = self.nodes.first().and_then(|root| elevation_bound(&root.bounds, from, inv)) {
+            stack[0] = (0, bound);
+            top = 1;
+        }
+        while top > 0 {
+            top -= 1;
+            let (index, bound) = stack[top];
+            if !beats(bound, best) {
+                continue;
+            }
+            let node = &self.nodes[index as usize];
+            if node.count > 0 {
+                for &f in &self.order[node.start as usize..(node.start + node.count) as usize] {
+                    for s in section(mesh.triangle(f as usize), from, dir).into_iter().flatten() {
+                        if beats(s, best) {
+                            best = Some(s);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Higher child on top
+            let children = [index + 1, node.start].map(|c| (c, elevation_bound(&self.nodes[c as usize].bounds, from, inv)));
+            let children = match children {
+                [(_, Some(a)), (_, Some(b))] if higher(a, b) => [children[1], children[0]],
+                _ => children,
+            };
+            for (child, bound) in children {
+                if let Some(bound) = bound.filter(|&b| beats(b, best)) {
+                    stack[top] = (child, bound);
+                    top += 1;
+                }
+            }
+        }
+        best.map(|(rise, run)| libm::atan2(rise, run))
+    }
+}
+
+/// Elevation as rise over horizontal run, `run >= 0`, so it can be
+/// compared without an `atan2`
+type Slope = (f64, f64);
+
+/// The cross product decides, except straight up against straight down
+#[inline]
+fn higher(a: Slope, b: Slope) -> bool {
+    a.0 * b.1 > b.0 * a.1 || (a.1 == 0.0 && b.1 == 0.0 && a.0 > 0.0 && b.0 < 0.0)
+}
+
+/// Bound on the elevation of the box's part in the half-plane, if any:
+/// its top at the nearest run the half-plane enters it, or the farthest
+/// if the top is below `from`
+#[inline]
+fn elevation_bound(b: &Aabb, from: [f64; 3], inv: [f64; 2]) -> Option<Slope> {
+    let (mut t0, mut t1) = (0.0_f64, f64::INFINITY);
+    for k in 0..2 {
+        let (a, c) = ((b.lo[k] - from[k]) * inv[k], (b.hi[k] - from[k]) * inv[k]);
+        // min/max drop the NaN of a zero direction on the box's face
+        t0 = t0.max(a.min(c));
+        t1 = t1.min(a.max(c));
+    }
+    let rise = b.hi[2] - from[2];
+    if t0 > t1 {
+        None
+    } else if rise >= 0.0 && t0 == 0.0 {
+        Some((1.0, 0.0)) // Straight up
+    } else if rise >= 0.0 {
+        Some((rise, t0))
+    } else {
+        Some((rise, t1))
+    }
+}
+
+/// Where the vertical half-plane from `from` toward `dir` cuts the
+/// triangle, as the ends of the cut (the highest points, elevation being
+/// monotone along a line), clipped to the half-plane's edge above and
+/// below `from`; `from` itself is left out, having no elevation
+#[inline]
+fn section(triangle: [[f64; 3]; 3], from: [f64; 3], dir: [f64; 2]) -> [Option<Slope>; 3] {
+    let rel = triangle.map(|v| sub(v, from));
+    let side = rel.map(|p| dir[1] * p[0] - dir[0] * p[1]);
+    let at = rel.map(|p| (p[2], dir[0] * p[0] + dir[1] * p[1]));
+
+    // Ends of the cut: vertices on the plane, and edges crossing it
+    let mut ends = [None; 3];
+    let mut n = 0;
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        if side[i] == 0.0 {
+            ends[n] = Some(at[i]);
+            n += 1;
+        } else if side[j] != 0.0 && (side[i] < 0.0) != (side[j] < 0.0) {
+            let t = side[i] / (side[i] - side[j]);
+            ends[n] = Some((at[i].0 + t * (at[j].0 - at[i].0), at[i].1 + t * (at[j].1 - at[i].1)));
+            n += 1;
+        }
+    }
+
+    // Clip to run >= 0; a cut passing behind `from` is replaced by where
+    // it passes over or under it
+    let mut clipped = [None; 3];
+    for i in 0..n {
+        let (a, b) = (ends[i].unwrap(), ends[(i + 1) % n].unwrap());
+        if a.1 >= 0.0 {
+            clipped[i] = Some(a);
+        } else if b.1 > 0.0 {
+            clipped[i] = Some((a.0 + (b.0 - a.0) * a.1 / (a.1 - b.1), 0.0));
+        }
+    }
+    clipped.map(|s| s.filter(|&(rise, run)| rise != 0.0 || run != 0.0))
+}
+
+/// Append the subtree over `order` (which starts at `offset` in the full
+/// order) to `nodes`, returning its index
+fn build(mesh: &TriMesh, centroids: &[[f64; 3]], order: &mut [u32], offset: usize, nodes: &mut Vec<Node>) -> u32 {
+    let bounds = order.iter().fold(Aabb::EMPTY, |b, &f| mesh.triangle(f as usize).into_iter().fold(b, Aabb::include));
+    let index = nodes.len() as u32;
+    nodes.push(Node { bounds, start: offset as u32, count: order.len() as u32 });
+    if order.len() <= LEAF {
+        return index;
+    }
+
+    let extent = bounds.extent();
+    let axis = (0..3).fold(0, |a, k| if extent[k] > extent[a] { k } else { a });
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |&a, &b| centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis]));
+    let (left, right) = order.split_at_mut(mid);
+    build(mesh, centroids, left, offset, nodes);
+    let right = build(mesh, centroids, right, offset + mid, nodes);
+    nodes[index as usize] = Node { bounds, start: right, count: 0 };
+    index
+}
+
+/// Whether the segment `from + t dir`, `t` in `[0, 1]`, meets the box;
+/// `inv` is `1 / dir` per axis
+#[inline]
+pub(crate) fn slab(b: &Aabb, from: [f64; 3], inv: [f64; 3]) -> bool {
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+    for k in 0..3 {
+        let (a, c) = ((b.lo[k] - from[k]) * inv[k], (b.hi[k] - from[k]) * inv[k]);
+        // min/max drop the NaN of a zero direction on the box's face
+        t0 = t0.max(a.min(c));
+        t1 = t1.min(a.max(c));
+    }
+    t0 <= t1
+}
+
+/// Whether the segment from `from` to `to` crosses `triangle`, by the
+/// test [Bvh::occluded] applies to each face it reaches; for checking it
+/// against brute force
+#[inline]
+pub fn segment_crosses(triangle: [[f64; 3]; 3], from: [f64; 3], to: [f64; 3]) -> bool {
+    hits(triangle, from, sub(to, from))
+}
+
+/// Möller-Trumbore: whether the segment `from + t dir` crosses the
+/// triangle for `t` in `(T_EPS, 1 - T_EPS)`, either side facing
+#[inline]
+fn hits([a, b, c]: [[f64; 3]; 3], from: [f64; 3], dir: [f64; 3]) -> bool {
+    let (e1, e2) = (sub(b, a), sub(c, a));
+    let p = cross(dir, e2);
+    let det = dot(e1, p);
+    if det == 0.0 {
+        return false; // Parallel to the plane
+    }
+    let s = sub(from, a);
+    let u = dot(s, p) / det;
+    let q = cross(s, e1);
+    let v = dot(dir, q) / det;
+    let t = dot(e2, q) / det;
+    (0.0..=1.0).contains(&u) && v >= 0.0 && u + v <= 1.0 && t > T_EPS && t < 1.0 - T_EPS
+}
@@ -0,0 +1,54 @@
+//! Zero-copy reinterpretation of raw binary dumps as kernel inputs/outputs.
+//!
+//! The element types are plain nested `f64` arrays, which `bytemuck`
+//! already treats as `Pod`, so e.g. a memory-mapped file of packed
+//! tetrahedra can be handed to the kernels without copying. Data is
+//! native-endian with no header; use the `npy` module when the file
+//! needs to carry its own shape. Behind the `bytemuck` feature.
+#![cfg(feature = "bytemuck")]
+
+use bytemuck::PodCastError;
+
+fn cast_error(e: PodCastError) -> &'static str {
+    match e {
+        PodCastError::TargetAlignmentGreaterAndInputNotAligned => "Buffer is not 8-byte aligned",
+        PodCastError::OutputSliceWouldHaveSlop => "Buffer length is not a multiple of the element size",
+        _ => "Buffer cannot be reinterpreted",
+    }
+}
+
+/// Reinterpret bytes as packed tetrahedra, `[v0, v1, v2, v3]` with `[x, y, z]` vertices.
+#[inline]
+pub fn tetrahedra_from_bytes(bytes: &[u8]) -> Result<&[[[f64; 3]; 4]], &'static str> {
+    bytemuck::try_cast_slice(bytes).map_err(cast_error)
+}
+
+/// Reinterpret packed tetrahedra as bytes.
+#[inline]
+pub fn tetrahedra_as_bytes(tetrahedra: &[[[f64; 3]; 4]]) -> &[u8] {
+    bytemuck::cast_slice(tetrahedra)
+}
+
+/// Reinterpret bytes as packed `[x, y, z]` points.
+#[inline]
+pub fn points_from_bytes(bytes: &[u8]) -> Result<&[[f64; 3]], &'static str> {
+    bytemuck::try_cast_slice(bytes).map_err(cast_error)
+}
+
+/// Reinterpret packed points as bytes.
+#[inline]
+pub fn points_as_bytes(points: &[[f64; 3]]) -> &[u8] {
+    bytemuck::cast_slice(points)
+}
+
+/// Reinterpret a mutable byte buffer as a kernel output slice.
+#[inline]
+pub fn values_from_bytes_mut(bytes: &mut [u8]) -> Result<&mut [f64], &'static str> {
+    bytemuck::try_cast_slice_mut(bytes).map_err(cast_error)
+}
+
+/// Reinterpret kernel outputs as bytes.
+#[inline]
+pub fn values_as_bytes(values: &[f64]) -> &[u8] {
+    bytemuck::cast_slice(values)
+}
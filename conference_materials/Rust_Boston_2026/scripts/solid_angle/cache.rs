@@ -0,0 +1,225 @@
+//! On-disk cache of expensive results (view factor matrices, visibility
+//! and horizon grids), addressed by a hash of everything that went into
+//! them, so an iterative workflow only recomputes what changed.
+//!
+//! A [Key] is built with a [Fingerprint] over the inputs: the mesh, the
+//! parameters, and a name for the computation. Each entry is an `.npy`
+//! file named by its key, so it can be inspected from Python too. Changed
+//! geometry or parameters give a new key, and the stale entry ages out:
+//! the cache keeps itself under a size limit by evicting the least
+//! recently used entries, a hit counting as a use. [CACHE_VERSION] is in
+//! every key, so bumping it when a computation's results change
+//! invalidates everything at once; [Cache::remove] and [Cache::clear]
+//! drop entries by hand.
+//!
+//! The hash is 128-bit FNV-1a, fast and stable across platforms and
+//! releases but not cryptographic: fine for one user's own results, not
+//! for a directory others can write to. Entries are written to a
+//! temporary file and renamed into place, so concurrent runs sharing a
+//! cache never see a partial entry; an unreadable entry is a miss.
+
+use crate::mesh::TriMesh;
+use crate::npy::{read_f64, write_f64, NpyArray};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Part of every key; bump it when cached results would change
+pub const CACHE_VERSION: u32 = 1;
+
+const FNV_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// Content hash of a computation's inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key(pub u128);
+
+impl Key {
+    fn file_name(self) -> String {
+        format!("{:032x}.npy", self.0)
+    }
+}
+
+/// Builds a [Key] from a computation's inputs, in order. Slices are
+/// prefixed with their length, so moving data between two of them
+/// changes the key.
+#[derive(Clone, Debug)]
+pub struct Fingerprint {
+    state: u128,
+}
+
+impl Fingerprint {
+    /// Start a key for the computation called `name`
+    pub fn new(name: &str) -> Self {
+        let fp = Self { state: FNV_OFFSET };
+        fp.u64(CACHE_VERSION as u64).bytes(name.as_bytes())
+    }
+
+    /// Add raw bytes
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self = self.raw(&(bytes.len() as u64).to_le_bytes());
+        self.raw(bytes)
+    }
+
+    /// Add an integer parameter
+    pub fn u64(self, x: u64) -> Self {
+        self.raw(&x.to_le_bytes())
+    }
+
+    /// Add a float parameter, by its bits: `0.0` and `-0.0` differ
+    pub fn f64(self, x: f64) -> Self {
+        self.raw(&x.to_bits().to_le_bytes())
+    }
+
+    /// Add an array of floats
+    pub fn f64s(mut self, xs: &[f64]) -> Self {
+        self = self.u64(xs.len() as u64);
+        for &x in xs {
+            self = self.f64(x);
+        }
+        self
+    }
+
+    /// Add a mesh's vertices and faces; attributes don't count
+    pub fn mesh(mut self, mesh: &TriMesh) -> Self {
+        self = self.f64s(mesh.vertices().as_flattened()).u64(mesh.faces().len() as u64);
+        for &i in mesh.faces().as_flattened() {
+            self = self.raw(&i.to_le_bytes());
+        }
+        self
+    }
+
+    /// The key
+    pub fn finish(self) -> Key {
+        Key(self.state)
+    }
+
+    #[inline]
+    fn raw(mut self, bytes: &[u8]) -> Self {
+        for &b in bytes {
+            self.state = (self.state ^ b as u128).wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+}
+
+/// Directory of cached results, at most `max_bytes` in all
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl Cache {
+    /// Cache in `dir`, created if missing, holding at most `max_bytes`
+    pub fn open(dir: impl AsRef<Path>, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes })
+    }
+
+    /// The entry under `key`, if any. A hit marks it recently used.
+    pub fn get(&self, key: Key) -> io::Result<Option<NpyArray>> {
+        let path = self.dir.join(key.file_name());
+        let file = match File::options().read(true).write(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        file.set_modified(SystemTime::now())?;
+        match read_f64(&mut BufReader::new(file)) {
+            Ok(array) => Ok(Some(array)),
+            Err(_) => {
+                // Truncated or foreign; drop it and call it a miss
+                let _ = fs::remove_file(&path);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Store `data` of `shape` under `key`, then evict down to the limit.
+    /// An entry larger than the whole cache isn't kept.
+    pub fn put(&self, key: Key, shape: &[usize], data: &[f64]) -> io::Result<()> {
+        let path = self.dir.join(key.file_name());
+        let tmp = self.dir.join(format!("{}.{}.tmp", key.file_name(), std::process::id()));
+        let written = File::create(&tmp).and_then(|file| {
+            let mut w = BufWriter::new(file);
+            write_f64(&mut w, shape, data)?;
+            w.into_inner().map_err(|e| e.into_error())?.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+        self.evict()
+    }
+
+    /// The entry under `key`, or else `compute`'s result, stored under it
+    pub fn get_or_compute<E: From<io::Error>>(&self, key: Key, compute: impl FnOnce() -> Result<NpyArray, E>) -> Result<NpyArray, E> {
+        if let Some(array) = self.get(key)? {
+            return Ok(array);
+        }
+        let array = compute()?;
+        self.put(key, &array.shape, &array.data)?;
+        Ok(array)
+    }
+
+    /// Drop the entry under `key`; whether there was one
+    pub fn remove(&self, key: Key) -> io::Result<bool> {
+        match fs::remove_file(self.dir.join(key.file_name())) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Drop every entry
+    pub fn clear(&self) -> io::Result<()> {
+        for (path, _, _) in self.entries()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Total size of the entries (bytes)
+    pub fn size(&self) -> io::Result<u64> {
+        Ok(self.entries()?.iter().map(|&(_, len, _)| len).sum())
+    }
+
+    /// Entries with their sizes and last use
+    fn entries(&self) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if !(name.len() == 36 && name.ends_with(".npy") && name[..32].bytes().all(|b| b.is_ascii_hexdigit())) {
+                continue; // Temporary files and anything else in the directory
+            }
+            match fs::metadata(&path) {
+                Ok(meta) => entries.push((path, meta.len(), meta.modified()?)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {} // Removed by another run
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Least recently used entries out until the rest fit
+    fn evict(&self) -> io::Result<()> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|&(_, len, _)| len).sum();
+        entries.sort_by_key(|&(_, _, used)| used);
+        for (path, len, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            match fs::remove_file(path) {
+                Ok(()) => total -= len,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => total -= len,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
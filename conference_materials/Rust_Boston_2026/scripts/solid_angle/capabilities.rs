@@ -0,0 +1,131 @@
+//! What this build can do on this machine, for logs and bug reports.
+//!
+//! Each script is its own crate, so the features reported are the ones
+//! the script including this module was built with:
+//!
+//! | Feature      | Enables                                             |
+//! |--------------|-----------------------------------------------------|
+//! | `trace`      | `tracing` spans in the parallel drivers             |
+//! | `serde`      | (de)serializing meshes and settings                 |
+//! | `progress`   | `indicatif` progress bars                           |
+//! | `serve`      | the HTTP service                                    |
+//! | `mpi`        | distributed drivers                                 |
+//! | `asm-export` | `#[no_mangle]` kernels for disassembly              |
+//! | `ephemeris`  | the ephemeris reader                                |
+//! | `rvv`        | the experimental RISC-V vector kernel               |
+//! | `half`       | `f16`/`bf16` storage in the mixed-precision kernels |
+//! | `hugepages`  | transparent huge page advice for `AlignedVec`       |
+//! | `jemalloc`   | jemalloc as the global allocator                    |
+//! | `mimalloc`   | mimalloc as the global allocator                    |
+//! | `soft-float` | unfused kernels, as on targets without FMA          |
+//! | `defmt`      | `defmt::Format` for fixed-capacity meshes           |
+//!
+//! The kernels choose instructions at compile time, so the ISA paths in
+//! use are the compiled ones, except in [crate::dispatch], which picks a
+//! path at runtime. Those detected but not compiled are what a
+//! `-C target-cpu=native` build would gain elsewhere. There is no GPU
+//! backend, so no adapters are ever listed.
+
+use crate::dispatch::{self, Path};
+use std::fmt;
+
+/// One instruction-set extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Isa {
+    pub name: &'static str,
+    /// Enabled for the whole build, so the kernels use it
+    pub compiled: bool,
+    /// Supported by the CPU running this
+    pub detected: bool,
+}
+
+/// Build and machine report, from [capabilities]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+    /// `arch-os` of the build
+    pub target: String,
+    /// Cargo features compiled in
+    pub features: Vec<&'static str>,
+    /// Extensions relevant to the kernels
+    pub isa: Vec<Isa>,
+    /// Path [crate::dispatch] picked for this CPU
+    pub dispatch: Path,
+    /// Whether the kernels fuse multiply-adds; see [crate::vec3::FUSED]
+    pub fused: bool,
+    /// Worker threads in rayon's pool
+    pub threads: usize,
+    pub physical_cores: usize,
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+macro_rules! isa {
+    ($detect:ident: $($name:tt),*) => {
+        vec![$(Isa { name: $name, compiled: cfg!(target_feature = $name), detected: $detect!($name) }),*]
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+fn isa() -> Vec<Isa> {
+    isa!(is_x86_feature_detected: "sse4.2", "avx", "avx2", "fma", "avx512f", "avx512dq")
+}
+
+#[cfg(target_arch = "aarch64")]
+fn isa() -> Vec<Isa> {
+    use std::arch::is_aarch64_feature_detected;
+    isa!(is_aarch64_feature_detected: "neon", "sve", "sve2")
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn isa() -> Vec<Isa> {
+    Vec::new() // No runtime detection on stable
+}
+
+/// Features, ISA extensions and threads of this build on this machine
+#[allow(unexpected_cfgs)] // Each script declares only the features it uses
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("trace", cfg!(feature = "trace")),
+        ("serde", cfg!(feature = "serde")),
+        ("progress", cfg!(feature = "progress")),
+        ("serve", cfg!(feature = "serve")),
+        ("mpi", cfg!(feature = "mpi")),
+        ("asm-export", cfg!(feature = "asm-export")),
+        ("ephemeris", cfg!(feature = "ephemeris")),
+        ("rvv", cfg!(feature = "rvv")),
+        ("half", cfg!(feature = "half")),
+        ("hugepages", cfg!(feature = "hugepages")),
+        ("jemalloc", cfg!(feature = "jemalloc")),
+        ("mimalloc", cfg!(feature = "mimalloc")),
+        ("soft-float", cfg!(feature = "soft-float")),
+        ("defmt", cfg!(feature = "defmt")),
+    ];
+    Capabilities {
+        target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+        features: features.into_iter().filter(|&(_, on)| on).map(|(name, _)| name).collect(),
+        isa: isa(),
+        dispatch: dispatch::path(),
+        fused: crate::vec3::FUSED,
+        threads: rayon::current_num_threads(),
+        physical_cores: num_cpus::get_physical(),
+    }
+}
+
+impl fmt::Display for Capabilities {
+    /// One `key: value` line each, e.g. for pasting into an issue
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |pred: &dyn Fn(&Isa) -> bool| {
+            let v: Vec<&str> = self.isa.iter().filter(|i| pred(i)).map(|i| i.name).collect();
+            if v.is_empty() { "none".to_owned() } else { v.join(" ") }
+        };
+        let features = if self.features.is_empty() { "none".to_owned() } else { self.features.join(" ") };
+        writeln!(f, "target: {}", self.target)?;
+        writeln!(f, "features: {features}")?;
+        writeln!(f, "isa compiled: {}", names(&|i| i.compiled))?;
+        writeln!(f, "isa detected: {}", names(&|i| i.detected))?;
+        writeln!(f, "isa available, not compiled: {}", names(&|i| i.detected && !i.compiled))?;
+        writeln!(f, "dispatch: {}", self.dispatch.name())?;
+        writeln!(f, "multiply-add: {}", if self.fused { "fused" } else { "unfused" })?;
+        writeln!(f, "threads: {} ({} physical cores)", self.threads, self.physical_cores)?;
+        writeln!(f, "gpu: no backend")
+    }
+}
@@ -0,0 +1,131 @@
+//! Clipping a [TriMesh] to a half-space or a box, to restrict solid-angle
+//! and view-factor computations to a region of interest without full CSG.
+//!
+//! Each vertex is put on one side of the plane once, by the exact
+//! [crate::predicates::plane_side], so faces sharing it can't disagree.
+//! Each cut edge gets one new vertex, interpolated from its lower-numbered
+//! end, so faces sharing it get the same point. Together those keep the
+//! cut watertight: a closed mesh stays closed except along the cut, which
+//! is left open rather than capped. Cut faces keep their winding and their
+//! face attributes; new vertices interpolate the vertex attributes.
+
+use crate::mesh::TriMesh;
+use crate::predicates::plane_side;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Plane through `point` with normal `normal`; clipping keeps the side the
+/// normal points to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub point: [f64; 3],
+    pub normal: [f64; 3],
+}
+
+impl Plane {
+    pub const fn new(point: [f64; 3], normal: [f64; 3]) -> Self {
+        Self { point, normal }
+    }
+
+    /// Signed distance scaled by the normal's length, for interpolating
+    #[inline]
+    fn eval(&self, x: [f64; 3]) -> f64 {
+        (0..3).map(|k| self.normal[k] * (x[k] - self.point[k])).sum()
+    }
+}
+
+/// Part of `mesh` on the kept side of `plane` or on it, with faces that
+/// cross it cut and re-triangulated
+pub fn clip_by_plane(mesh: &TriMesh, plane: Plane) -> TriMesh {
+    let side: Vec<Ordering> =
+        mesh.vertices().par_iter().map(|&x| plane_side(plane.point, plane.normal, x)).collect();
+
+    // Vertices of the result: the originals in use, then one per cut edge,
+    // each a blend `(a, b, t)` of original vertices
+    let mut local = vec![u32::MAX; mesh.vertices().len()];
+    let mut blends: Vec<(u32, u32, f64)> = Vec::new();
+    let mut cuts: HashMap<[u32; 2], u32> = HashMap::new();
+    let mut faces = Vec::new();
+    let mut parents = Vec::new();
+
+    let mut keep = |v: u32, blends: &mut Vec<(u32, u32, f64)>| {
+        if local[v as usize] == u32::MAX {
+            local[v as usize] = blends.len() as u32;
+            blends.push((v, v, 0.0));
+        }
+        local[v as usize]
+    };
+
+    for (f, face) in mesh.faces().iter().enumerate() {
+        let sides = face.map(|v| side[v as usize]);
+        if sides.iter().all(|&s| s != Ordering::Less) {
+            faces.push(face.map(|v| keep(v, &mut blends)));
+            parents.push(f as u32);
+            continue;
+        }
+        if sides.iter().all(|&s| s != Ordering::Greater) {
+            continue;
+        }
+
+        // Walk the triangle, keeping vertices on the kept side or on the
+        // plane and adding a vertex where an edge crosses strictly
+        let mut polygon = Vec::with_capacity(4);
+        for k in 0..3 {
+            let (u, v) = (face[k], face[(k + 1) % 3]);
+            if sides[k] != Ordering::Less {
+                polygon.push(keep(u, &mut blends));
+            }
+            if sides[k] != Ordering::Equal && sides[(k + 1) % 3] == sides[k].reverse() {
+                let key = [u.min(v), u.max(v)];
+                let id = *cuts.entry(key).or_insert_with(|| {
+                    let (a, b) = (key[0] as usize, key[1] as usize);
+                    let (da, db) = (plane.eval(mesh.vertices()[a]), plane.eval(mesh.vertices()[b]));
+                    let t = if da == db { 0.5 } else { (da / (da - db)).clamp(0.0, 1.0) };
+                    blends.push((key[0], key[1], t));
+                    blends.len() as u32 - 1
+                });
+                polygon.push(id);
+            }
+        }
+
+        // A triangle or a quad, fanned from its first vertex
+        for k in 1..polygon.len() - 1 {
+            faces.push([polygon[0], polygon[k], polygon[k + 1]]);
+            parents.push(f as u32);
+        }
+    }
+
+    let lerp = |x: f64, y: f64, t: f64| if t == 0.0 { x } else { x + t * (y - x) };
+    let vertices = blends
+        .par_iter()
+        .map(|&(a, b, t)| {
+            let (x, y) = (mesh.vertices()[a as usize], mesh.vertices()[b as usize]);
+            [0, 1, 2].map(|k| lerp(x[k], y[k], t))
+        })
+        .collect();
+    let mut clipped = TriMesh::new(vertices, faces).expect("Clipped faces only reference new vertices");
+    for (name, values) in mesh.vertex_attributes().iter() {
+        let values = blends.iter().map(|&(a, b, t)| lerp(values[a as usize], values[b as usize], t)).collect();
+        clipped.vertex_attributes_mut().insert(name, values).expect("One value per new vertex");
+    }
+    for (name, values) in mesh.face_attributes().iter() {
+        let values = parents.iter().map(|&f| values[f as usize]).collect();
+        clipped.face_attributes_mut().insert(name, values).expect("One value per new face");
+    }
+    clipped
+}
+
+/// Part of `mesh` inside the axis-aligned box from `lo` to `hi`, clipped
+/// by each of its six faces in turn
+pub fn clip_by_aabb(mesh: &TriMesh, lo: [f64; 3], hi: [f64; 3]) -> TriMesh {
+    let mut clipped = mesh.clone();
+    for k in 0..3 {
+        let mut normal = [0.0; 3];
+        normal[k] = 1.0;
+        clipped = clip_by_plane(&clipped, Plane::new(lo, normal));
+        normal[k] = -1.0;
+        clipped = clip_by_plane(&clipped, Plane::new(hi, normal));
+    }
+    clipped
+}
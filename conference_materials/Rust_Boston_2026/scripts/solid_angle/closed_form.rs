@@ -0,0 +1,264 @@
+//! Shapes whose solid angles are known in closed form, generated at
+//! compile time, for tests and as constants.
+//!
+//! Each family is a `const fn` building a [TriMeshFixed] and the fraction
+//! of `4π` each of its faces subtends at [SpecialCase::origin]:
+//!
+//! * The regular polyhedra seen from their centres. By symmetry every face
+//!   takes the same share, so fractions are exactly `1/F`: the cube's
+//!   squares are split along a diagonal into congruent halves, and the
+//!   dodecahedron's pentagons fanned from their centres into fifths.
+//! * Boxes seen from their centres, split as the cube is. A face of half
+//!   extents `a × b` at distance `c` subtends `4 atan(ab / (c d))`, `d`
+//!   the half-diagonal, evaluated with [crate::const_eval::atan]; not
+//!   rational, but within an ulp or two.
+//!
+//! Consts need their types spelled out, and a family's capacities are its
+//! own business, so [special_cases] declares them by family name:
+//!
+//! ```ignore
+//! closed_form::special_cases! {
+//!     pub ICOSAHEDRON = icosahedron();
+//!     SLAB = box_faces([4.0, 3.0, 0.25]);
+//! }
+//! ```
+
+use crate::const_eval::{atan, cross, dot, sqrt, sub};
+use crate::mesh_fixed::TriMeshFixed;
+use std::f64::consts::{PI, SQRT_2};
+
+/// A closed mesh, an origin inside it, and the share of the sphere each
+/// face subtends there
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpecialCase<const V: usize, const F: usize> {
+    pub mesh: TriMeshFixed<V, F>,
+    pub origin: [f64; 3],
+    /// Fraction of `4π` each face subtends at the origin, summing to 1
+    pub fractions: [f64; F],
+}
+
+impl<const V: usize, const F: usize> SpecialCase<V, F> {
+    /// Solid angle each face subtends at the origin (sr)
+    pub const fn solid_angles(&self) -> [f64; F] {
+        let mut out = [0.0; F];
+        let mut i = 0;
+        while i < F {
+            out[i] = 4.0 * PI * self.fractions[i];
+            i += 1;
+        }
+        out
+    }
+}
+
+/// Declare `const`s of [SpecialCase]s by family, with their capacities
+#[allow(unused_macros)] // Not every script declares its own
+macro_rules! special_cases {
+    (@type regular_tetrahedron) => { $crate::closed_form::SpecialCase<4, 4> };
+    (@type octahedron) => { $crate::closed_form::SpecialCase<6, 8> };
+    (@type cube) => { $crate::closed_form::SpecialCase<8, 12> };
+    (@type icosahedron) => { $crate::closed_form::SpecialCase<12, 20> };
+    (@type dodecahedron) => { $crate::closed_form::SpecialCase<32, 60> };
+    (@type box_faces) => { $crate::closed_form::SpecialCase<8, 12> };
+    ($($(#[$attr:meta])* $vis:vis $name:ident = $family:ident($($arg:expr),* $(,)?);)*) => {
+        $($(#[$attr])* $vis const $name: $crate::closed_form::special_cases!(@type $family) = $crate::closed_form::$family($($arg),*);)*
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use special_cases;
+
+/// Mesh of `faces` wound counter-clockwise seen from outside a shape
+/// convex about the origin
+const fn outward<const V: usize, const F: usize>(vertices: [[f64; 3]; V], faces: [[u32; 3]; F]) -> TriMeshFixed<V, F> {
+    let mut faces = faces;
+    let mut i = 0;
+    while i < F {
+        let [a, b, c] = faces[i];
+        let [va, vb, vc] = [vertices[a as usize], vertices[b as usize], vertices[c as usize]];
+        if dot(va, cross(sub(vb, va), sub(vc, va))) < 0.0 {
+            faces[i] = [a, c, b];
+        }
+        i += 1;
+    }
+    match TriMeshFixed::from_arrays(vertices, faces) {
+        Ok(mesh) => mesh,
+        Err(_) => panic!("Element references a missing vertex"),
+    }
+}
+
+/// Whether `u` and `v` are `edge` apart, to rounding
+const fn edge_apart(u: [f64; 3], v: [f64; 3], edge: f64) -> bool {
+    let d = sub(u, v);
+    let (d2, e2) = (dot(d, d), edge * edge);
+    d2 - e2 < 1e-9 * e2 && e2 - d2 < 1e-9 * e2
+}
+
+/// Faces of a deltahedron, every triple of vertices an edge apart, in
+/// no particular winding
+const fn equilateral_faces<const V: usize, const F: usize>(vertices: [[f64; 3]; V], edge: f64) -> [[u32; 3]; F] {
+    let mut faces = [[0; 3]; F];
+    let mut n = 0;
+    let mut i = 0;
+    while i < V {
+        let mut j = i + 1;
+        while j < V {
+            let mut k = j + 1;
+            while k < V {
+                let [u, v, w] = [vertices[i], vertices[j], vertices[k]];
+                if edge_apart(u, v, edge) && edge_apart(v, w, edge) && edge_apart(w, u, edge) {
+                    assert!(n < F, "More faces than the family declares");
+                    faces[n] = [i as u32, j as u32, k as u32];
+                    n += 1;
+                }
+                k += 1;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    assert!(n == F, "Fewer faces than the family declares");
+    faces
+}
+
+/// Every face an equal share
+const fn equal_shares<const V: usize, const F: usize>(mesh: TriMeshFixed<V, F>) -> SpecialCase<V, F> {
+    SpecialCase { mesh, origin: [0.0; 3], fractions: [1.0 / F as f64; F] }
+}
+
+/// Regular tetrahedron of edge `2√2` about the origin: `1/4` a face
+pub const fn regular_tetrahedron() -> SpecialCase<4, 4> {
+    let vertices = [[1.0, 1.0, 1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]];
+    equal_shares(outward(vertices, equilateral_faces(vertices, 2.0 * SQRT_2)))
+}
+
+/// Octahedron of unit circumradius: `1/8` a face
+pub const fn octahedron() -> SpecialCase<6, 8> {
+    let vertices = [[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+    equal_shares(outward(vertices, equilateral_faces(vertices, SQRT_2)))
+}
+
+/// Icosahedron's vertices at `(0, ±1, ±φ)` and their cyclic
+/// permutations, edge 2
+const fn icosahedron_vertices() -> [[f64; 3]; 12] {
+    let phi = (1.0 + sqrt(5.0)) / 2.0;
+    let mut vertices = [[0.0; 3]; 12];
+    let mut i = 0;
+    while i < 12 {
+        let (s1, s2) = (if i & 1 == 0 { 1.0 } else { -1.0 }, if i & 2 == 0 { phi } else { -phi });
+        vertices[i] = match i / 4 {
+            0 => [0.0, s1, s2],
+            1 => [s1, s2, 0.0],
+            _ => [s2, 0.0, s1],
+        };
+        i += 1;
+    }
+    vertices
+}
+
+/// Icosahedron of edge 2: `1/20` a face
+pub const fn icosahedron() -> SpecialCase<12, 20> {
+    let vertices = icosahedron_vertices();
+    equal_shares(outward(vertices, equilateral_faces(vertices, 2.0)))
+}
+
+/// Dodecahedron dual to [icosahedron], its vertices at the icosahedron's
+/// face centroids, each pentagon fanned from its centre into fifths:
+/// `1/60` a face
+pub const fn dodecahedron() -> SpecialCase<32, 60> {
+    let iv = icosahedron_vertices();
+    let ifaces: [[u32; 3]; 20] = equilateral_faces(iv, 2.0);
+
+    // Centroids, then the pentagons' centres, on their icosahedron
+    // vertices' axes
+    let mut vertices = [[0.0; 3]; 32];
+    let mut f = 0;
+    while f < 20 {
+        let [a, b, c] = ifaces[f];
+        let [va, vb, vc] = [iv[a as usize], iv[b as usize], iv[c as usize]];
+        vertices[f] = [(va[0] + vb[0] + vc[0]) / 3.0, (va[1] + vb[1] + vc[1]) / 3.0, (va[2] + vb[2] + vc[2]) / 3.0];
+        let mut k = 0;
+        while k < 3 {
+            let v = ifaces[f][k] as usize;
+            let t = dot(vertices[f], iv[v]) / dot(iv[v], iv[v]);
+            vertices[20 + v] = [t * iv[v][0], t * iv[v][1], t * iv[v][2]];
+            k += 1;
+        }
+        f += 1;
+    }
+
+    // Each icosahedron edge is a dodecahedron edge between the centroids
+    // of the faces sharing it, with a fifth of a pentagon on either side
+    let mut faces = [[0; 3]; 60];
+    let mut n = 0;
+    let mut f = 0;
+    while f < 20 {
+        let mut g = f + 1;
+        while g < 20 {
+            let (mut shared, mut count) = ([0; 2], 0);
+            let mut k = 0;
+            while k < 3 {
+                let v = ifaces[f][k];
+                let [a, b, c] = ifaces[g];
+                if v == a || v == b || v == c {
+                    if count < 2 {
+                        shared[count] = v;
+                    }
+                    count += 1;
+                }
+                k += 1;
+            }
+            if count == 2 {
+                faces[n] = [20 + shared[0], f as u32, g as u32];
+                faces[n + 1] = [20 + shared[1], g as u32, f as u32];
+                n += 2;
+            }
+            g += 1;
+        }
+        f += 1;
+    }
+    assert!(n == 60, "Icosahedron without 30 edges");
+    equal_shares(outward(vertices, faces))
+}
+
+/// Vertex `i` of a box is at `(±x, ±y, ±z)`, bits 0, 1 and 2 of `i`
+/// choosing the signs; faces in pairs, `-x`, `+x`, `-y`, `+y`, `-z`, `+z`
+const BOX_FACES: [[u32; 3]; 12] = [[0, 2, 6], [0, 6, 4], [1, 3, 7], [1, 7, 5], [0, 1, 5], [0, 5, 4], [2, 3, 7], [2, 7, 6], [0, 1, 3], [0, 3, 2], [4, 5, 7], [4, 7, 6]];
+
+const fn box_mesh(half: [f64; 3]) -> TriMeshFixed<8, 12> {
+    let mut vertices = [[0.0; 3]; 8];
+    let mut i = 0;
+    while i < 8 {
+        let mut axis = 0;
+        while axis < 3 {
+            vertices[i][axis] = if i & (1 << axis) == 0 { -half[axis] } else { half[axis] };
+            axis += 1;
+        }
+        i += 1;
+    }
+    outward(vertices, BOX_FACES)
+}
+
+/// Cube of edge 2: `1/12` a face, two to a square
+pub const fn cube() -> SpecialCase<8, 12> {
+    equal_shares(box_mesh([1.0; 3]))
+}
+
+/// Box of half extents `half` about the origin, the share of each face
+/// from its closed form
+pub const fn box_faces(half: [f64; 3]) -> SpecialCase<8, 12> {
+    assert!(half[0] > 0.0 && half[1] > 0.0 && half[2] > 0.0, "Box of nonpositive extent");
+    let d = sqrt(dot(half, half));
+    let mut fractions = [0.0; 12];
+    let mut axis = 0;
+    while axis < 3 {
+        // Half of 4 atan(ab / (c d)), over 4π
+        let (a, b, c) = (half[(axis + 1) % 3], half[(axis + 2) % 3], half[axis]);
+        let share = atan(a * b / (c * d)) / (2.0 * PI);
+        let mut k = 0;
+        while k < 4 {
+            fractions[4 * axis + k] = share;
+            k += 1;
+        }
+        axis += 1;
+    }
+    SpecialCase { mesh: box_mesh(half), origin: [0.0; 3], fractions }
+}
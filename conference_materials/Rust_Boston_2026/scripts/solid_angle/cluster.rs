@@ -0,0 +1,133 @@
+//! k-means clustering of points, such as element centroids, into compact
+//! groups: patches for far-field approximations, blocks for the all-pairs
+//! view-factor assembly, or targets for coverage analysis.
+//!
+//! Seeding is k-means++ and iteration is Lloyd's, with the assignment
+//! step in parallel. Sums over points are taken per fixed-size chunk and
+//! combined in chunk order, so results are bit-identical at any thread
+//! count.
+
+use crate::inputs::Pcg64;
+use crate::mesh::TriMesh;
+use rayon::prelude::*;
+
+/// Points per parallel chunk; fixed, for reproducible sums
+const CHUNK: usize = 1 << 12;
+
+/// Result of [kmeans]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clusters {
+    pub centers: Vec<[f64; 3]>,
+    /// Cluster of each point
+    pub labels: Vec<u32>,
+    /// Points in each cluster
+    pub sizes: Vec<usize>,
+    /// Sum of squared distances from points to their centers
+    pub inertia: f64,
+    /// Lloyd iterations run
+    pub iterations: usize,
+}
+
+/// Centroid of each face, the usual points to cluster a surface by
+pub fn face_centroids(mesh: &TriMesh) -> Vec<[f64; 3]> {
+    mesh.triangles().map(|[a, b, c]| std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0)).collect()
+}
+
+#[inline]
+fn distance2(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|k| (a[k] - b[k]) * (a[k] - b[k])).sum()
+}
+
+/// Nearest center and the squared distance to it, lowest index on ties
+#[inline]
+fn nearest(p: [f64; 3], centers: &[[f64; 3]]) -> (u32, f64) {
+    centers.iter().enumerate().fold((0, f64::INFINITY), |best, (i, &c)| {
+        let d = distance2(p, c);
+        if d < best.1 { (i as u32, d) } else { best }
+    })
+}
+
+/// Sum over chunks, combined in chunk order
+fn chunked_sum(x: &[f64]) -> f64 {
+    let partials: Vec<f64> = x.par_chunks(CHUNK).map(|c| c.iter().sum()).collect();
+    partials.iter().sum()
+}
+
+/// `k` clusters of `points` by k-means++ seeding from `seed` and up to
+/// `max_iterations` rounds of Lloyd's algorithm, stopping early once no
+/// point changes cluster
+pub fn kmeans(points: &[[f64; 3]], k: usize, seed: u64, max_iterations: usize) -> Result<Clusters, &'static str> {
+    if k == 0 || k > points.len() || k > u32::MAX as usize {
+        return Err("Cluster count must be between 1 and the number of points");
+    }
+
+    // k-means++: each further center drawn with probability proportional
+    // to the squared distance to the nearest one so far
+    let mut rng = Pcg64::new(seed, 0);
+    let mut centers = vec![points[rng.below(points.len())]];
+    let mut d2: Vec<f64> = points.par_iter().map(|&p| distance2(p, centers[0])).collect();
+    while centers.len() < k {
+        let target = rng.unit() * chunked_sum(&d2);
+        let mut cumulative = 0.0;
+        let i = d2
+            .iter()
+            .position(|&d| {
+                cumulative += d;
+                cumulative > target
+            })
+            .unwrap_or_else(|| d2.iter().rposition(|&d| d > 0.0).unwrap_or(0)); // Rounding at the end
+        centers.push(points[i]);
+        let c = points[i];
+        d2.par_iter_mut().zip(points).for_each(|(d, &p)| *d = d.min(distance2(p, c)));
+    }
+
+    let mut labels = vec![u32::MAX; points.len()];
+    let mut sizes = vec![0; k];
+    let mut iterations = 0;
+    loop {
+        // Assign, accumulating per-chunk sums for the update
+        let chunks = (points.par_chunks(CHUNK), labels.par_chunks_mut(CHUNK), d2.par_chunks_mut(CHUNK));
+        let partials: Vec<(Vec<[f64; 3]>, Vec<usize>, usize)> = chunks
+            .into_par_iter()
+            .map(|(p, l, d)| {
+                let (mut sums, mut counts, mut changed) = (vec![[0.0; 3]; k], vec![0; k], 0);
+                for ((&p, l), d) in p.iter().zip(l).zip(d) {
+                    let (c, dist) = nearest(p, &centers);
+                    changed += usize::from(*l != c);
+                    (*l, *d) = (c, dist);
+                    (0..3).for_each(|j| sums[c as usize][j] += p[j]);
+                    counts[c as usize] += 1;
+                }
+                (sums, counts, changed)
+            })
+            .collect();
+        let mut sums = vec![[0.0; 3]; k];
+        let mut changed = 0;
+        sizes.fill(0);
+        for (s, n, c) in &partials {
+            for i in 0..k {
+                (0..3).for_each(|j| sums[i][j] += s[i][j]);
+                sizes[i] += n[i];
+            }
+            changed += c;
+        }
+        if changed == 0 || iterations == max_iterations {
+            break;
+        }
+        iterations += 1;
+
+        // Update; an emptied cluster restarts at the point farthest from
+        // its center
+        for i in 0..k {
+            if sizes[i] > 0 {
+                centers[i] = sums[i].map(|x| x / sizes[i] as f64);
+            } else {
+                let far = (0..points.len()).max_by(|&a, &b| d2[a].total_cmp(&d2[b]).then(b.cmp(&a))).unwrap();
+                centers[i] = points[far];
+                d2[far] = 0.0;
+            }
+        }
+    }
+
+    Ok(Clusters { centers, labels, sizes, inertia: chunked_sum(&d2), iterations })
+}
@@ -0,0 +1,77 @@
+//! Connected components of a [TriMesh], for separating debris shells
+//! from scanned meshes before volume or winding-number queries.
+//!
+//! Faces are connected when they share a vertex. Components come from a
+//! lock-free union-find over vertices: faces are unioned in parallel, each
+//! link hangs the larger root under the smaller with a compare-exchange,
+//! and finds halve paths as they go. Parent indices only ever decrease,
+//! so no interleaving can make a cycle, and every root ends up the lowest
+//! vertex of its component whatever the thread schedule.
+
+use crate::mesh::TriMesh;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Root of `x`, halving the path to it
+fn find(parent: &[AtomicU32], mut x: u32) -> u32 {
+    loop {
+        let p = parent[x as usize].load(Ordering::Acquire);
+        if p == x {
+            return x;
+        }
+        let gp = parent[p as usize].load(Ordering::Acquire);
+        // Losing this race only means another thread shortened it first
+        let _ = parent[x as usize].compare_exchange_weak(p, gp, Ordering::AcqRel, Ordering::Relaxed);
+        x = gp;
+    }
+}
+
+fn union(parent: &[AtomicU32], a: u32, b: u32) {
+    let (mut a, mut b) = (a, b);
+    loop {
+        (a, b) = (find(parent, a), find(parent, b));
+        if a == b {
+            return;
+        }
+        let (lo, hi) = (a.min(b), a.max(b));
+        // Fails if `hi` stopped being a root since the find; go again
+        if parent[hi as usize].compare_exchange(hi, lo, Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+            return;
+        }
+    }
+}
+
+/// Component of each face, numbered from 0 in order of each component's
+/// lowest vertex index
+pub fn components(mesh: &TriMesh) -> Vec<u32> {
+    let parent: Vec<AtomicU32> = (0..mesh.vertices().len() as u32).map(AtomicU32::new).collect();
+    mesh.faces().par_iter().for_each(|&[a, b, c]| {
+        union(&parent, a, b);
+        union(&parent, a, c);
+    });
+
+    // Number the roots in order; only vertices used by faces count
+    let roots: Vec<u32> = (0..parent.len() as u32).into_par_iter().map(|v| find(&parent, v)).collect();
+    let mut used = vec![false; roots.len()];
+    for face in mesh.faces() {
+        used[roots[face[0] as usize] as usize] = true;
+    }
+    let mut label = vec![u32::MAX; roots.len()];
+    for (c, (v, _)) in used.iter().enumerate().filter(|(_, u)| **u).enumerate() {
+        label[v] = c as u32;
+    }
+
+    mesh.faces().par_iter().map(|face| label[roots[face[0] as usize] as usize]).collect()
+}
+
+/// Each component of `mesh` as a standalone mesh, numbered as by
+/// [components], with attributes carried over
+pub fn split_components(mesh: &TriMesh) -> Result<Vec<TriMesh>, &'static str> {
+    let labels = components(mesh);
+    let count = labels.iter().map(|&c| c as usize + 1).max().unwrap_or(0);
+    let mut faces = vec![Vec::new(); count];
+    for (f, &c) in labels.iter().enumerate() {
+        faces[c as usize].push(f as u32);
+    }
+    faces.par_iter().map(|f| mesh.submesh(f)).collect()
+}
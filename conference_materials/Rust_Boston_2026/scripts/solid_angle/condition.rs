@@ -0,0 +1,74 @@
+//! Solid angle with a per-element condition estimate, to flag elements
+//! worth re-evaluating with [crate::dd] or [crate::interval] without
+//! paying for either everywhere.
+//!
+//! The kernel's two atan2 arguments each carry an absolute rounding error
+//! of a few `ε·abc`, which moves the half-angle `θ` by up to `ε·abc / r`
+//! with `r = hypot(triple, denom)`. That error is large relative to `θ`
+//! near `θ = 0` (slivers seen edge-on), and near `θ = ±π` the sign of the
+//! triple product can flip and send the result from `2π` to `-2π`. The
+//! estimate covers both:
+//!
+//! ```text
+//! κ = abc / (r · min(|θ|, π - |θ|))
+//! ```
+//!
+//! so the relative error of the result is about `κ·ε` (within a small
+//! constant). Regular elements come out near 1; anything past `1e8` has
+//! lost at least half its digits.
+
+use crate::vec3::{cross, dot, norm, sub};
+
+/// Variant of [crate::tetrahedron::solid_angle_tetrahedron_scalar]
+/// also returning the condition estimate κ described above.
+/// Exactly degenerate tetrahedra return `(0.0, 0.0)`, as the zero is exact.
+#[inline]
+pub fn solid_angle_tetrahedron_scalar_cond(
+    v0: [f64; 3],
+    v1: [f64; 3],
+    v2: [f64; 3],
+    v3: [f64; 3],
+) -> (f64, f64) {
+    // Vertex vectors
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0)); // (m)
+    let (la, lb, lc) = (norm(a), norm(b), norm(c)); // (m) Vertex vector lengths
+    let abc = la * lb * lc; // (m^3) Length product
+
+    // Solid angle
+    let triple = dot(a, cross(b, c)); // (m^3) Scalar triple product
+    let denom = dot(a, b).mul_add(lc, dot(a, c).mul_add(lb, dot(b, c).mul_add(la, abc))); // (m^3)
+    let half_angle = libm::atan2(triple, denom); // (rad)
+
+    // Distance to the nearer of the two ill-conditioned directions
+    let margin = half_angle.abs().min(std::f64::consts::PI - half_angle.abs()); // (rad)
+    let cond = abc / (libm::hypot(triple, denom) * margin); // (dimensionless)
+
+    if abc != 0.0 {
+        (2.0 * half_angle, cond)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+/// Vector variant of [solid_angle_tetrahedron_scalar_cond], writing the
+/// condition estimates to `cond`
+#[inline]
+pub fn solid_angle_tetrahedron_cond(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+    cond: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n || cond.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tet = tetrahedra[i];
+        (out[i], cond[i]) = solid_angle_tetrahedron_scalar_cond(tet[0], tet[1], tet[2], tet[3]);
+    }
+
+    Ok(())
+}
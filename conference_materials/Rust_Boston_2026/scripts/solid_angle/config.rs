@@ -0,0 +1,210 @@
+//! Run settings for the CLI tools, from a TOML file with command-line
+//! overrides, so a pipeline can be rerun exactly from a checked-in file:
+//!
+//! ```toml
+//! kernel = "dd"          # fma, native, serial or dd
+//! threads = 8            # Worker threads; all physical cores if absent
+//! chunk_size = 65536     # Elements per progress update
+//! input_format = "npy"   # npy, or raw native-endian f64
+//! output_format = "raw"
+//! degeneracy = "error"   # zero, nan or error
+//! ```
+//!
+//! Every key is optional, and a flag `--chunk-size 4096` overrides key
+//! `chunk_size`. Unknown keys are rejected rather than ignored.
+
+use crate::dd::solid_angle_tetrahedron_dd;
+use crate::dispatch::solid_angle_tetrahedra_dispatch_par;
+use crate::par::solid_angle_tetrahedra_par;
+use crate::progress::DEFAULT_PROGRESS_CHUNK;
+use crate::tetrahedron::solid_angle_tetrahedron;
+use crate::vec3::sub;
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+/// Kernel used for the solid angles
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kernel {
+    /// FMA kernel on the thread pool
+    #[default]
+    Fma,
+    /// FMA kernel on the thread pool, on the CPU's best path, see [crate::dispatch]
+    Native,
+    /// FMA kernel on the calling thread only
+    Serial,
+    /// Double-double kernel on the thread pool
+    Dd,
+}
+
+/// File layout of points, tetrahedra and results
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// NumPy `.npy`, see [crate::npy]
+    #[default]
+    Npy,
+    /// Packed native-endian `f64` with no header
+    Raw,
+}
+
+/// What to report for a tetrahedron whose apex coincides with another
+/// vertex, where the solid angle is undefined
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Degeneracy {
+    /// Zero, as the kernels do
+    #[default]
+    Zero,
+    /// NaN, so it survives into downstream statistics
+    Nan,
+    /// Fail the run, naming the first such element
+    Error,
+}
+
+/// Settings for one run
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub kernel: Kernel,
+    pub threads: Option<usize>,
+    pub chunk_size: Option<usize>,
+    pub input_format: Format,
+    pub output_format: Format,
+    pub degeneracy: Degeneracy,
+}
+
+fn invalid(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+}
+
+/// One enum value from a flag, through the same names as the file
+fn parse_enum<T: for<'de> Deserialize<'de>>(value: &str) -> io::Result<T> {
+    T::deserialize(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(value)).map_err(invalid)
+}
+
+impl FromStr for Config {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        toml::from_str(s).map_err(invalid)
+    }
+}
+
+impl Config {
+    /// Override the key `key` (flag spelling or file spelling) with `value`
+    pub fn set(&mut self, key: &str, value: &str) -> io::Result<()> {
+        let number = |v: &str| v.parse::<usize>().map_err(invalid);
+        match key.replace('-', "_").as_str() {
+            "kernel" => self.kernel = parse_enum(value)?,
+            "threads" => self.threads = Some(number(value)?),
+            "chunk_size" => self.chunk_size = Some(number(value)?),
+            "input_format" => self.input_format = parse_enum(value)?,
+            "output_format" => self.output_format = parse_enum(value)?,
+            "degeneracy" => self.degeneracy = parse_enum(value)?,
+            _ => return Err(invalid(format!("Unknown setting {key}"))),
+        }
+        Ok(())
+    }
+
+    /// Settings from `--config <file>` if given, then every other
+    /// `--key value` flag in `args`, which are removed, leaving the
+    /// positional arguments
+    pub fn from_args(args: &mut Vec<String>) -> io::Result<Self> {
+        let mut flags = Vec::new();
+        let mut positional = Vec::new();
+        let mut it = std::mem::take(args).into_iter();
+        while let Some(arg) = it.next() {
+            match arg.strip_prefix("--") {
+                Some(key) => flags.push((key.to_owned(), it.next().ok_or_else(|| invalid(format!("Missing value for --{key}")))?)),
+                None => positional.push(arg),
+            }
+        }
+        *args = positional;
+
+        let mut config = match flags.iter().find(|(k, _)| k == "config") {
+            Some((_, path)) => std::fs::read_to_string(path)?.parse()?,
+            None => Self::default(),
+        };
+        for (key, value) in flags.iter().filter(|(k, _)| k != "config") {
+            config.set(key, value)?;
+        }
+        Ok(config)
+    }
+
+    /// Elements per progress update
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size.unwrap_or(DEFAULT_PROGRESS_CHUNK).max(1)
+    }
+
+    /// Size rayon's global pool, if `threads` is set. Call once, before any parallel work.
+    pub fn init_threads(&self) -> io::Result<()> {
+        if let Some(n) = self.threads {
+            rayon::ThreadPoolBuilder::new().num_threads(n).build_global().map_err(invalid)?;
+        }
+        Ok(())
+    }
+
+    /// Solid angles with the configured kernel and degeneracy policy.
+    /// `first` is the index of `tetrahedra[0]` in the whole run, for errors.
+    pub fn solid_angles(&self, tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64], first: usize) -> io::Result<()> {
+        match self.kernel {
+            Kernel::Fma => solid_angle_tetrahedra_par(tetrahedra, out),
+            Kernel::Native => solid_angle_tetrahedra_dispatch_par(tetrahedra, out),
+            Kernel::Serial => solid_angle_tetrahedron(tetrahedra, out),
+            Kernel::Dd => {
+                if tetrahedra.len() != out.len() {
+                    return Err(invalid("Dimension mismatch"));
+                }
+                (tetrahedra.par_chunks(1024), out.par_chunks_mut(1024))
+                    .into_par_iter()
+                    .try_for_each(|(t, o)| solid_angle_tetrahedron_dd(t, o))
+            }
+        }
+        .map_err(invalid)?;
+
+        if self.degeneracy != Degeneracy::Zero {
+            for (i, (tet, y)) in tetrahedra.iter().zip(out.iter_mut()).enumerate() {
+                if tet[1..].iter().any(|&v| sub(v, tet[0]) == [0.0; 3]) {
+                    match self.degeneracy {
+                        Degeneracy::Nan => *y = f64::NAN,
+                        _ => return Err(invalid(format!("Tetrahedron {} is degenerate", first + i))),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read `N` values per element in the input format, e.g. 12 for tetrahedra
+    pub fn read_input<R: Read, const N: usize>(&self, r: &mut R) -> io::Result<Vec<[f64; N]>> {
+        match self.input_format {
+            Format::Npy => {
+                let arr = crate::npy::read_f64(r)?;
+                if arr.shape.is_empty() || arr.shape[1..].iter().product::<usize>() != N {
+                    return Err(invalid(format!("Expected an array of {N} values per element")));
+                }
+                Ok(arr.data.chunks_exact(N).map(|c| std::array::from_fn(|i| c[i])).collect())
+            }
+            Format::Raw => {
+                let mut bytes = Vec::new();
+                r.read_to_end(&mut bytes)?;
+                if bytes.len() % (8 * N) != 0 {
+                    return Err(invalid("Raw input is not whole elements"));
+                }
+                let x = |c: &[u8]| f64::from_ne_bytes(c.try_into().unwrap());
+                Ok(bytes.chunks_exact(8 * N).map(|c| std::array::from_fn(|i| x(&c[8 * i..8 * i + 8]))).collect())
+            }
+        }
+    }
+
+    /// Write one value per element in the output format
+    pub fn write_output<W: Write>(&self, w: &mut W, values: &[f64]) -> io::Result<()> {
+        match self.output_format {
+            Format::Npy => crate::npy::write_f64(w, &[values.len()], values),
+            Format::Raw => values.iter().try_for_each(|x| w.write_all(&x.to_ne_bytes())),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! The scalar kernel as a `const fn`, for baking tables of solid angles
+//! for fixed sensor geometries into the binary at compile time.
+//!
+//! [crate::tetrahedron::solid_angle_tetrahedron_scalar] itself can't be
+//! `const`: `mul_add`, `sqrt` and `atan2` aren't, and it should keep
+//! compiling to the hardware's instructions. So the operations it needs
+//! are rebuilt here from `const`-stable arithmetic and bit manipulation,
+//! each rounding exactly as its runtime counterpart does:
+//!
+//! * [fma] emulates a fused multiply-add with error-free transformations
+//!   and a sum rounded to odd (Boldo & Melquiond, "Emulation of FMA and
+//!   correctly rounded sums: proved algorithms using rounding to odd",
+//!   IEEE TC 2008), after scaling by powers of two so Dekker's product
+//!   neither overflows nor underflows.
+//! * [sqrt] takes the integer square root of the widened significand and
+//!   rounds it once, through an integer-to-float conversion.
+//! * [atan2] is `libm`'s, the fdlibm algorithm, line for line.
+//!
+//! Together they make [solid_angle_tetrahedron_scalar_const] bit-identical
+//! to the runtime kernel under the default `libm` backend, fused or not
+//! ([crate::vec3::FUSED]), for any result in the normal range; see
+//! `const_eval_example.rs`. Evaluated at run time it is several times slower
+//! than the runtime kernel, so it is only for constants.
+
+use crate::vec3::FUSED;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// `2^k` for `k` in `[-1022, 1023]`
+const fn pow2(k: i32) -> f64 {
+    f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+/// `x 2^k`, exact while the result is normal
+const fn scale(x: f64, k: i32) -> f64 {
+    let (mut x, mut k) = (x, k);
+    while k > 1023 {
+        x *= pow2(1023);
+        k -= 1023;
+    }
+    while k < -1022 {
+        x *= pow2(-1022);
+        k += 1022;
+    }
+    x * pow2(k)
+}
+
+/// `floor(log2 |x|)` of finite, nonzero `x`
+const fn exponent(x: f64) -> i32 {
+    let bits = x.to_bits();
+    match ((bits >> 52) & 0x7ff) as i32 {
+        0 => -1011 - (bits << 12 >> 12).leading_zeros() as i32, // Subnormal
+        e => e - 1023,
+    }
+}
+
+/// (s, e) with s = fl(a + b) and s + e = a + b exactly
+const fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// (hi, lo) with hi + lo = x and each of 26 significant bits or fewer
+const fn split(x: f64) -> (f64, f64) {
+    let c = 134217729.0 * x; // 2^27 + 1
+    let hi = c - (c - x);
+    (hi, x - hi)
+}
+
+/// (p, e) with p = fl(a * b) and p + e = a * b exactly, by Dekker's
+/// splitting, while nothing over- or underflows
+const fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    let ((ah, al), (bh, bl)) = (split(a), split(b));
+    (p, (((ah * bh - p) + ah * bl) + al * bh) + al * bl)
+}
+
+/// `a + b` rounded to odd: toward zero, then to the odd neighbour if
+/// that was inexact
+const fn add_odd(a: f64, b: f64) -> f64 {
+    let (s, e) = two_sum(a, b);
+    let bits = s.to_bits();
+    if e == 0.0 || bits & 1 == 1 {
+        s
+    } else if (e > 0.0) == (s > 0.0) {
+        f64::from_bits(bits + 1)
+    } else {
+        f64::from_bits(bits - 1)
+    }
+}
+
+/// `a b + c` with one rounding, as [f64::mul_add], for results in the
+/// normal range: a subnormal one is rounded twice, and may be an ulp off
+pub const fn fma(a: f64, b: f64, c: f64) -> f64 {
+    if !(a.is_finite() && b.is_finite()) || a == 0.0 || b == 0.0 {
+        return a * b + c; // Exact product, or IEEE's special cases
+    }
+    if !c.is_finite() {
+        return c; // However large the product, it's finite
+    }
+    if c == 0.0 {
+        return a * b;
+    }
+
+    // Significands of a and b to [1, 2), and c by the same factor. A
+    // c far above the product rounds to itself; one far below only
+    // matters by its sign, at a tie, so a smaller one does as well.
+    let k = exponent(a) + exponent(b);
+    let (a, b) = (scale(a, -exponent(a)), scale(b, -exponent(b)));
+    let d = exponent(c) - k;
+    if d > 60 {
+        return c;
+    }
+    let c = if d < -110 { pow2(-120).copysign(c) } else { scale(c, -k) };
+
+    let (uh, ul) = two_prod(a, b);
+    let (th, tl) = two_sum(c, uh);
+    scale(th + add_odd(tl, ul), k)
+}
+
+/// Correctly rounded square root, as [f64::sqrt]
+pub const fn sqrt(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 || x.is_infinite() {
+        return x;
+    }
+
+    // x = m 2^e, with e even
+    let bits = x.to_bits();
+    let (mut m, mut e) = match (bits >> 52) as i32 {
+        0 => (bits as u128, -1074), // Subnormal
+        biased => (((bits << 12 >> 12) | (1 << 52)) as u128, biased - 1075),
+    };
+    if e % 2 != 0 {
+        m <<= 1;
+        e -= 1;
+    }
+
+    // Widen by an even shift to 125 or 126 bits, so the root has 63 and
+    // its last bit can carry the remainder as a sticky bit below the
+    // rounding position
+    let shift = (m.leading_zeros() as i32 - 2) & !1;
+    let wide = m << shift;
+    let root = wide.isqrt();
+    let sticky = (root * root != wide) as u128;
+    scale((root | sticky) as f64, (e - shift) / 2)
+}
+
+// fdlibm's constants, digits as `libm` has them
+#[allow(clippy::excessive_precision)]
+const PI_LO: f64 = 1.2246467991473531772E-16;
+#[allow(clippy::excessive_precision)]
+const ATANHI: [f64; 4] = [4.63647609000806093515e-01, FRAC_PI_4, 9.82793723247329054082e-01, FRAC_PI_2];
+#[allow(clippy::excessive_precision)]
+const ATANLO: [f64; 4] = [2.26987774529616870924e-17, 3.06161699786838301793e-17, 1.39033110312309984516e-17, 6.12323399573676603587e-17];
+#[allow(clippy::excessive_precision)]
+const AT: [f64; 11] = [
+    3.33333333333329318027e-01,
+    -1.99999999998764832476e-01,
+    1.42857142725034663711e-01,
+    -1.11111104054623557880e-01,
+    9.09088713343650656196e-02,
+    -7.69187620504482999495e-02,
+    6.66107313738753120669e-02,
+    -5.83357013379057348645e-02,
+    4.97687799461593236017e-02,
+    -3.65315727442169155270e-02,
+    1.62858201153657823623e-02,
+];
+
+/// Arctangent, `libm::atan`: fdlibm's reduction to one of five intervals,
+/// then an odd polynomial
+pub const fn atan(x: f64) -> f64 {
+    let mut x = x;
+    let mut ix = (x.to_bits() >> 32) as u32;
+    let sign = ix >> 31;
+    ix &= 0x7fff_ffff;
+    if ix >= 0x4410_0000 {
+        // |x| >= 2^66
+        if x.is_nan() {
+            return x;
+        }
+        let z = ATANHI[3] + f64::from_bits(0x0380_0000);
+        return if sign != 0 { -z } else { z };
+    }
+
+    let id: i32 = if ix < 0x3fdc_0000 {
+        // |x| < 7/16
+        if ix < 0x3e40_0000 {
+            return x; // |x| < 2^-27
+        }
+        -1
+    } else {
+        x = x.abs();
+        if ix < 0x3ff3_0000 {
+            if ix < 0x3fe6_0000 {
+                // 7/16 <= |x| < 11/16
+                x = (2. * x - 1.) / (2. + x);
+                0
+            } else {
+                // 11/16 <= |x| < 19/16
+                x = (x - 1.) / (x + 1.);
+                1
+            }
+        } else if ix < 0x4003_8000 {
+            // 19/16 <= |x| < 39/16
+            x = (x - 1.5) / (1. + 1.5 * x);
+            2
+        } else {
+            x = -1. / x;
+            3
+        }
+    };
+
+    let z = x * x;
+    let w = z * z;
+    let s1 = z * (AT[0] + w * (AT[2] + w * (AT[4] + w * (AT[6] + w * (AT[8] + w * AT[10])))));
+    let s2 = w * (AT[1] + w * (AT[3] + w * (AT[5] + w * (AT[7] + w * AT[9]))));
+    if id < 0 {
+        return x - x * (s1 + s2);
+    }
+    let z = ATANHI[id as usize] - (x * (s1 + s2) - ATANLO[id as usize] - x);
+    if sign != 0 { -z } else { z }
+}
+
+/// Four-quadrant arctangent of `y / x`, `libm::atan2`
+pub const fn atan2(y: f64, x: f64) -> f64 {
+    if x.is_nan() || y.is_nan() {
+        return x + y;
+    }
+    let mut ix = (x.to_bits() >> 32) as u32;
+    let lx = x.to_bits() as u32;
+    let mut iy = (y.to_bits() >> 32) as u32;
+    let ly = y.to_bits() as u32;
+    if (ix.wrapping_sub(0x3ff0_0000) | lx) == 0 {
+        return atan(y); // x = 1
+    }
+    let m = ((iy >> 31) & 1) | ((ix >> 30) & 2); // 2 sign(x) + sign(y)
+    ix &= 0x7fff_ffff;
+    iy &= 0x7fff_ffff;
+
+    if (iy | ly) == 0 {
+        // y = 0
+        return match m {
+            0 | 1 => y,
+            2 => PI,
+            _ => -PI,
+        };
+    }
+    if (ix | lx) == 0 {
+        // x = 0
+        return if m & 1 != 0 { -PI / 2.0 } else { PI / 2.0 };
+    }
+    if ix == 0x7ff0_0000 {
+        // x infinite
+        return if iy == 0x7ff0_0000 {
+            match m {
+                0 => PI / 4.0,
+                1 => -PI / 4.0,
+                2 => 3.0 * PI / 4.0,
+                _ => -3.0 * PI / 4.0,
+            }
+        } else {
+            match m {
+                0 => 0.0,
+                1 => -0.0,
+                2 => PI,
+                _ => -PI,
+            }
+        };
+    }
+    if ix.wrapping_add(64 << 20) < iy || iy == 0x7ff0_0000 {
+        // |y / x| > 2^64
+        return if m & 1 != 0 { -PI / 2.0 } else { PI / 2.0 };
+    }
+
+    let z = if (m & 2 != 0) && iy.wrapping_add(64 << 20) < ix {
+        0.0 // |y / x| < 2^-64, x < 0
+    } else {
+        atan((y / x).abs())
+    };
+    match m {
+        0 => z,
+        1 => -z,
+        2 => PI - (z - PI_LO),
+        _ => (z - PI_LO) - PI,
+    }
+}
+
+/// `a b + c`, fused where [crate::vec3::mul_add] is
+const fn mul_add(a: f64, b: f64, c: f64) -> f64 {
+    if FUSED { fma(a, b, c) } else { a * b + c }
+}
+
+pub const fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+/// As [crate::vec3::dot], innermost pair first
+pub const fn dot(u: [f64; 3], v: [f64; 3]) -> f64 {
+    mul_add(u[0], v[0], mul_add(u[1], v[1], u[2] * v[2]))
+}
+
+pub const fn cross(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [mul_add(u[1], v[2], -u[2] * v[1]), mul_add(u[2], v[0], -u[0] * v[2]), mul_add(u[0], v[1], -u[1] * v[0])]
+}
+
+const fn norm(u: [f64; 3]) -> f64 {
+    sqrt(dot(u, u))
+}
+
+/// [crate::tetrahedron::solid_angle_tetrahedron_scalar], evaluable in a
+/// `const` or `static`
+pub const fn solid_angle_tetrahedron_scalar_const(v0: [f64; 3], v1: [f64; 3], v2: [f64; 3], v3: [f64; 3]) -> f64 {
+    // Vertex vectors
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0)); // (m)
+    let (la, lb, lc) = (norm(a), norm(b), norm(c)); // (m) Vertex vector lengths
+    let abc = la * lb * lc; // (m^3) Length product
+
+    // Solid angle
+    let triple = dot(a, cross(b, c)); // (m^3) Scalar triple product
+    let denom = mul_add(dot(a, b), lc, mul_add(dot(a, c), lb, mul_add(dot(b, c), la, abc))); // (m^3)
+    let angle = 2.0 * atan2(triple, denom); // (rad)
+
+    if abc != 0.0 { angle } else { 0.0 }
+}
+
+/// [solid_angle_tetrahedron_scalar_const] of each of `tetrahedra`, as a
+/// table: `static TABLE: [f64; N] = solid_angles_const(&TETRAHEDRA);`
+pub const fn solid_angles_const<const N: usize>(tetrahedra: &[[[f64; 3]; 4]; N]) -> [f64; N] {
+    let mut out = [0.0; N];
+    let mut i = 0;
+    while i < N {
+        let [v0, v1, v2, v3] = tetrahedra[i];
+        out[i] = solid_angle_tetrahedron_scalar_const(v0, v1, v2, v3);
+        i += 1;
+    }
+    out
+}
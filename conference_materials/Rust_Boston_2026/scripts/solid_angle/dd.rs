@@ -0,0 +1,259 @@
+//! Double-double arithmetic path for ill-conditioned tetrahedra.
+//!
+//! A double-double is an unevaluated sum `hi + lo` of two `f64` with
+//! `|lo| <= ulp(hi) / 2`, carrying about 32 significant decimal digits.
+//! The error-free transformations underneath (Dekker/Knuth two-sum and
+//! an FMA two-product) are what make this cheap: the rounding error of
+//! `a * b` is exactly `a.mul_add(b, -(a * b))`.
+//!
+//! Algorithms follow Hida, Li & Bailey (2001), "Algorithms for
+//! Quad-Double Precision Floating Point Arithmetic" (the QD library).
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Unevaluated sum `hi + lo`, with `lo` below half an ulp of `hi`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+/// (s, e) with s = fl(a + b) and s + e = a + b exactly
+#[inline]
+pub fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// As [two_sum], but only valid for |a| >= |b|
+#[inline]
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// (p, e) with p = fl(a * b) and p + e = a * b exactly
+#[inline]
+pub fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+// Low parts from the QD library
+const PI: DoubleDouble = DoubleDouble::new(std::f64::consts::PI, 1.224_646_799_147_353_2e-16);
+const FRAC_PI_2: DoubleDouble = DoubleDouble::new(std::f64::consts::FRAC_PI_2, 6.123_233_995_736_766e-17);
+
+impl DoubleDouble {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+    pub const PI: Self = PI;
+
+    #[inline]
+    pub const fn new(hi: f64, lo: f64) -> Self {
+        Self { hi, lo }
+    }
+
+    #[inline]
+    pub const fn from_f64(x: f64) -> Self {
+        Self::new(x, 0.0)
+    }
+
+    /// Nearest `f64`
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    #[inline]
+    fn renormalize((hi, lo): (f64, f64)) -> Self {
+        let (hi, lo) = quick_two_sum(hi, lo);
+        Self::new(hi, lo)
+    }
+
+    #[inline]
+    pub fn abs(self) -> Self {
+        if self.hi < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// Square root by one Newton step from the `f64` estimate
+    pub fn sqrt(self) -> Self {
+        if self.hi <= 0.0 {
+            return Self::from_f64(self.hi.sqrt()); // 0, or NaN for negative input
+        }
+        let x = 1.0 / self.hi.sqrt();
+        let ax = self.hi * x;
+        let residual = self - Self::renormalize(two_prod(ax, ax));
+        Self::renormalize(two_sum(ax, residual.hi * (x * 0.5)))
+    }
+
+    /// Sine and cosine, for arguments up to a few multiples of π
+    pub fn sin_cos(self) -> (Self, Self) {
+        // Reduce to |r| <= π/4 around the nearest multiple of π/2
+        let k = (self.hi / FRAC_PI_2.hi).round();
+        let r = self - FRAC_PI_2 * Self::from_f64(k);
+
+        // Taylor series, to below the last double-double bit
+        let r2 = r * r;
+        let (mut sin, mut cos) = (r, Self::from_f64(1.0));
+        let (mut s_term, mut c_term) = (r, Self::from_f64(1.0));
+        for i in 1..30 {
+            let (m, n) = ((2 * i) as f64, (2 * i + 1) as f64);
+            c_term = -(c_term * r2 / Self::from_f64((m - 1.0) * m));
+            s_term = -(s_term * r2 / Self::from_f64(m * n));
+            cos = cos + c_term;
+            sin = sin + s_term;
+            if c_term.hi.abs() < 1e-34 {
+                break;
+            }
+        }
+
+        // Rotate back by k quarter turns
+        match (k as i64).rem_euclid(4) {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        }
+    }
+
+    /// Four-quadrant arctangent of `y / x`, like [f64::atan2]
+    pub fn atan2(y: Self, x: Self) -> Self {
+        if x.hi == 0.0 && y.hi == 0.0 {
+            return Self::ZERO;
+        }
+
+        // The f64 angle is within an ulp or so, and tan of the remaining
+        // error is exactly (y cos θ - x sin θ) / (x cos θ + y sin θ).
+        // At that size, atan(t) = t to well past double-double precision.
+        let theta = Self::from_f64(libm::atan2(y.hi, x.hi));
+        let (sin, cos) = theta.sin_cos();
+        let t = (y * cos - x * sin) / (x * cos + y * sin);
+        theta + t
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let (s1, s2) = two_sum(self.hi, rhs.hi);
+        let (t1, t2) = two_sum(self.lo, rhs.lo);
+        let (s1, s2) = quick_two_sum(s1, s2 + t1);
+        Self::renormalize((s1, s2 + t2))
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let (p1, p2) = two_prod(self.hi, rhs.hi);
+        let p2 = self.hi.mul_add(rhs.lo, self.lo.mul_add(rhs.hi, p2));
+        Self::renormalize((p1, p2))
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    /// Long division, one `f64` quotient digit at a time
+    #[inline]
+    fn div(self, rhs: Self) -> Self {
+        let q1 = self.hi / rhs.hi;
+        let r = self - rhs * Self::from_f64(q1);
+        let q2 = r.hi / rhs.hi;
+        let r = r - rhs * Self::from_f64(q2);
+        let q3 = r.hi / rhs.hi;
+        Self::renormalize(quick_two_sum(q1, q2)) + Self::from_f64(q3)
+    }
+}
+
+type Vec3 = [DoubleDouble; 3];
+
+/// Exact difference of two points
+#[inline]
+fn sub(a: [f64; 3], b: [f64; 3]) -> Vec3 {
+    [0, 1, 2].map(|i| DoubleDouble::renormalize(two_sum(a[i], -b[i])))
+}
+
+#[inline]
+fn dot(u: Vec3, v: Vec3) -> DoubleDouble {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+#[inline]
+fn cross(u: Vec3, v: Vec3) -> Vec3 {
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+/// Double-double variant of [crate::tetrahedron::solid_angle_tetrahedron_scalar],
+/// accurate to ~32 digits even for slivers where every digit of the `f64`
+/// result is noise.
+pub fn solid_angle_tetrahedron_scalar_dd(
+    v0: [f64; 3],
+    v1: [f64; 3],
+    v2: [f64; 3],
+    v3: [f64; 3],
+) -> DoubleDouble {
+    // Vertex vectors, exact
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0)); // (m)
+    let (la, lb, lc) = (dot(a, a).sqrt(), dot(b, b).sqrt(), dot(c, c).sqrt()); // (m)
+    let abc = la * lb * lc; // (m^3)
+    if abc.hi == 0.0 {
+        return DoubleDouble::ZERO;
+    }
+
+    // Solid angle
+    let triple = dot(a, cross(b, c)); // (m^3)
+    let denom = abc + dot(a, b) * lc + dot(a, c) * lb + dot(b, c) * la; // (m^3)
+    let angle = DoubleDouble::atan2(triple, denom); // (rad)
+    angle + angle
+}
+
+/// Vector variant of [solid_angle_tetrahedron_scalar_dd], rounding to `f64`
+pub fn solid_angle_tetrahedron_dd(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tet = tetrahedra[i];
+        out[i] = solid_angle_tetrahedron_scalar_dd(tet[0], tet[1], tet[2], tet[3]).to_f64();
+    }
+
+    Ok(())
+}
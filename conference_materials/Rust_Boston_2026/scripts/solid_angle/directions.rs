@@ -0,0 +1,98 @@
+//! Unit direction vectors from an origin to many points: the first step
+//! of coverage maps, Monte Carlo estimates and spherical-harmonic fits,
+//! kept in one kernel so each doesn't write its own.
+//!
+//! The fast path is one square root and one division per point, with the
+//! three components multiplied by the reciprocal length; the result is
+//! within 2 ulp of unit length. It covers every offset whose squared
+//! length is a normal float, that is lengths from about 1e-154 to 1e154.
+//! Outside that, the offset is first scaled by a power of two near its
+//! largest component, which is exact, so tiny, huge and subnormal
+//! offsets normalize as accurately as any other. A zero offset has no
+//! direction and gives `[0, 0, 0]`, as degenerate tetrahedra give zero
+//! angles; infinite components point along their axes, and NaN stays NaN.
+
+use crate::par::{chunk_len, par_threshold};
+use rayon::prelude::*;
+
+/// Unit vector from `origin` toward `point`
+#[inline]
+pub fn unit_vector(point: [f64; 3], origin: [f64; 3]) -> [f64; 3] {
+    let d = [point[0] - origin[0], point[1] - origin[1], point[2] - origin[2]];
+    let len2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+    if len2.is_normal() {
+        let inv = 1.0 / len2.sqrt();
+        [d[0] * inv, d[1] * inv, d[2] * inv]
+    } else {
+        unit_vector_rescaled(d)
+    }
+}
+
+/// [unit_vector] off the fast path, out of line so that inlined calls
+/// carry only the fast one
+#[cold]
+#[inline(never)]
+fn unit_vector_rescaled(d: [f64; 3]) -> [f64; 3] {
+    let big = d[0].abs().max(d[1].abs()).max(d[2].abs());
+    let d = if d.iter().any(|x| x.is_nan()) {
+        return [f64::NAN; 3];
+    } else if big == 0.0 {
+        return [0.0; 3];
+    } else if big.is_infinite() {
+        // Only the infinite components, as unit steps
+        d.map(|x| if x.is_infinite() { x.signum() } else { 0.0 })
+    } else {
+        // By 2^-e for the exponent e of the largest component, in two
+        // factors so that neither over- or underflows
+        let e = ((big.to_bits() >> 52) as i32 - 1023).max(-1022);
+        let (s0, s1) = (2f64.powi(-e / 2), 2f64.powi(-e - -e / 2));
+        d.map(|x| x * s0 * s1)
+    };
+    let inv = 1.0 / (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+    [d[0] * inv, d[1] * inv, d[2] * inv]
+}
+
+/// Vector variant of [unit_vector], from one origin to each of `points`
+#[inline]
+pub fn unit_vectors(points: &[[f64; 3]], origin: [f64; 3], out: &mut [[f64; 3]]) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Fast path throughout, branch-free so it vectorizes; then, in the
+    // rare batch with points off it, the whole batch again
+    let mut rescale = false;
+    for (p, u) in points.iter().zip(out.iter_mut()) {
+        let d = [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]];
+        let len2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+        let inv = 1.0 / len2.sqrt();
+        *u = [d[0] * inv, d[1] * inv, d[2] * inv];
+        rescale |= !len2.is_normal();
+    }
+    if rescale {
+        for (p, u) in points.iter().zip(out.iter_mut()) {
+            *u = unit_vector(*p, origin);
+        }
+    }
+    Ok(())
+}
+
+/// Thread-parallel [unit_vectors], chunked as
+/// [crate::par::solid_angle_tetrahedra_par]
+pub fn unit_vectors_par(points: &[[f64; 3]], origin: [f64; 3], out: &mut [[f64; 3]]) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return unit_vectors(points, origin, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (points.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(p, u)| unit_vectors(p, origin, u))
+}
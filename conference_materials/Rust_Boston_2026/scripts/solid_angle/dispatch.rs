@@ -0,0 +1,175 @@
+//! Runtime choice of kernel path for the CPU at hand.
+//!
+//! A portable build targets the architecture baseline, so on x86_64 the
+//! `mul_add`s in the FMA kernel become calls to a software `fma`. Rather
+//! than ship one binary per CPU, the dispatcher detects support once and
+//! runs a copy of the kernel compiled for it:
+//!
+//! | Path       | Where                      | Kernel                                   |
+//! |------------|----------------------------|------------------------------------------|
+//! | `avx2+fma` | x86_64 with AVX2 and FMA   | the FMA kernel, compiled for those       |
+//! | `neon`     | AArch64                    | [crate::neon]                            |
+//! | `rvv`      | riscv64, `rvv` feature     | [crate::rvv] (experimental)              |
+//! | `portable` | anything else              | the FMA kernel as built                  |
+//!
+//! Every path rounds identically (FMA is correctly rounded in hardware
+//! and software alike), so the choice only changes speed. [set_path]
+//! overrides the choice, for comparing paths or pinning a tuned one.
+//! Where the kernel runs unfused ([crate::vec3::FUSED]), the NEON and RVV
+//! kernels, which fuse in their own instructions, are left out to keep
+//! that so.
+
+use crate::par::{cancellable, chunk_len, par_threshold, CancellationToken};
+use crate::tetrahedron::{solid_angle_tetrahedron, solid_angle_tetrahedron_scalar};
+use crate::vec3::FUSED;
+use rayon::prelude::*;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Kernel path chosen by [path]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Path {
+    Portable,
+    Avx2Fma,
+    Neon,
+    Rvv,
+}
+
+impl Path {
+    pub const ALL: [Self; 4] = [Self::Portable, Self::Avx2Fma, Self::Neon, Self::Rvv];
+
+    /// Whether this build can run the path on this CPU
+    pub fn supported(self) -> bool {
+        match self {
+            Self::Portable => true,
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2Fma => is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma"),
+            Self::Neon => cfg!(target_arch = "aarch64") && FUSED,
+            Self::Rvv => cfg!(all(feature = "rvv", target_arch = "riscv64")) && FUSED,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Inverse of [Path::name]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Portable => "portable",
+            Self::Avx2Fma => "avx2+fma",
+            Self::Neon => "neon",
+            Self::Rvv => "rvv",
+        }
+    }
+}
+
+static PATH: LazyLock<Path> = LazyLock::new(detect);
+
+/// Path from [set_path] as its index in [Path::ALL] plus one, or zero
+static OVERRIDE: AtomicU8 = AtomicU8::new(0);
+
+fn detect() -> Path {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        return Path::Avx2Fma;
+    }
+    #[cfg(target_arch = "aarch64")]
+    if FUSED {
+        return Path::Neon;
+    }
+    #[cfg(all(feature = "rvv", target_arch = "riscv64"))]
+    if FUSED {
+        return Path::Rvv;
+    }
+    #[allow(unreachable_code)]
+    Path::Portable
+}
+
+/// Path the dispatch kernels take: the one from [set_path], or else the
+/// fastest supported here, detected on first use
+#[inline]
+pub fn path() -> Path {
+    match OVERRIDE.load(Ordering::Relaxed) {
+        0 => *PATH,
+        i => Path::ALL[usize::from(i) - 1],
+    }
+}
+
+/// Use `path` for all threads from now on, or go back to the detected
+/// one with None
+pub fn set_path(path: Option<Path>) -> Result<(), &'static str> {
+    let i = match path {
+        Some(p) if !p.supported() => return Err("Kernel path not supported on this CPU"),
+        Some(p) => Path::ALL.iter().position(|&q| q == p).unwrap() as u8 + 1,
+        None => 0,
+    };
+    OVERRIDE.store(i, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Always inlined, with the kernel, as [crate::approx]'s loop is: once a
+/// binary calls [solid_angle_tetrahedron] from more than one place, LLVM
+/// keeps it out of line and the AVX2 copy becomes a jump to the portable
+/// build, which `perf_gates.rs` catches as no speedup
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn tetrahedron_loop(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) {
+    for (tet, y) in tetrahedra.iter().zip(out.iter_mut()) {
+        *y = solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn solid_angle_tetrahedron_avx2(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    tetrahedron_loop(tetrahedra, out); // Inlined, so compiled with the features above
+    Ok(())
+}
+
+/// Variant of [solid_angle_tetrahedron] on the path from [path]
+#[inline]
+pub fn solid_angle_tetrahedron_dispatch(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    match path() {
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: The path is only chosen when the CPU has AVX2 and FMA
+        Path::Avx2Fma => unsafe { solid_angle_tetrahedron_avx2(tetrahedra, out) },
+        #[cfg(target_arch = "aarch64")]
+        Path::Neon => crate::neon::solid_angle_tetrahedron_neon(tetrahedra, out),
+        #[cfg(all(feature = "rvv", target_arch = "riscv64"))]
+        Path::Rvv => crate::rvv::solid_angle_tetrahedron_rvv(tetrahedra, out),
+        _ => solid_angle_tetrahedron(tetrahedra, out),
+    }
+}
+
+/// Thread-parallel [solid_angle_tetrahedron_dispatch], chunked as
+/// [crate::par::solid_angle_tetrahedra_par]
+pub fn solid_angle_tetrahedra_dispatch_par(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return solid_angle_tetrahedron_dispatch(tetrahedra, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(t, o)| solid_angle_tetrahedron_dispatch(t, o))
+}
+
+/// [solid_angle_tetrahedra_dispatch_par], stopped by `token`; see
+/// [CancellationToken]
+pub fn solid_angle_tetrahedra_dispatch_par_cancellable(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64], token: &CancellationToken) -> Result<(), &'static str> {
+    cancellable(tetrahedra, out, token, solid_angle_tetrahedron_dispatch)
+}
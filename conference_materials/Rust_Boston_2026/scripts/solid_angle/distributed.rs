@@ -0,0 +1,128 @@
+//! MPI drivers for runs larger than one node.
+//!
+//! Each rank runs the rayon drivers on its own share, so MPI only moves
+//! inputs out and results back: a scatter/gather of tetrahedra held on
+//! one root rank, and reductions over work that is already distributed,
+//! such as the parts from [crate::partition].
+//!
+//! Behind the `mpi` feature, which needs an MPI installation to link.
+
+use crate::mesh::TriMesh;
+use crate::multi_origin::solid_angles_multi_origin;
+use crate::par::solid_angle_tetrahedra_par;
+use crate::sum::CompensatedSum;
+use mpi::collective::SystemOperation;
+use mpi::datatype::{Partition, PartitionMut};
+use mpi::traits::*;
+use mpi::Count;
+
+/// Broadcast in place of the element count when the root's inputs are bad
+const BAD_INPUT: u64 = u64::MAX;
+
+/// Contiguous share of `n` elements for each of `size` ranks, as
+/// `(counts, displacements)` in units of `width` values
+fn shares(n: usize, size: usize, width: usize) -> Result<(Vec<Count>, Vec<Count>), &'static str> {
+    if n.checked_mul(width).is_none_or(|v| v > Count::MAX as usize) {
+        return Err("Too many elements for one MPI message");
+    }
+    let bound = |r: usize| (n * r / size * width) as Count;
+    let counts = (0..size).map(|r| bound(r + 1) - bound(r)).collect();
+    let displs = (0..size).map(bound).collect();
+    Ok((counts, displs))
+}
+
+/// Solid angles of tetrahedra held on rank `root`, computed across all
+/// ranks of `comm` and gathered back into `out` on `root`.
+///
+/// Collective: every rank must call it. Only the root's `tetrahedra` and
+/// `out` are read and written; other ranks may pass empty slices. If the
+/// root's lengths do not match, every rank returns the error.
+pub fn solid_angle_tetrahedra_mpi<C: Communicator>(
+    comm: &C,
+    root: i32,
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    let is_root = comm.rank() == root;
+    let root_process = comm.process_at_rank(root);
+    let size = comm.size() as usize;
+
+    // Share the element count, or the root's bad news, so no rank waits on a scatter that never comes
+    let mut n = if !is_root {
+        0
+    } else if tetrahedra.len() != out.len() || shares(out.len(), size, 12).is_err() {
+        BAD_INPUT
+    } else {
+        out.len() as u64
+    };
+    root_process.broadcast_into(&mut n);
+    if n == BAD_INPUT {
+        return Err("Dimension mismatch or too many elements at root");
+    }
+    let n = n as usize;
+
+    // Scatter coordinates as flat f64, 12 per tetrahedron
+    let (counts, displs) = shares(n, size, 12)?;
+    let rank = comm.rank() as usize;
+    let mut local = vec![[[0.0; 3]; 4]; counts[rank] as usize / 12];
+    let local_flat = local.as_flattened_mut().as_flattened_mut();
+    if is_root {
+        let send = Partition::new(tetrahedra.as_flattened().as_flattened(), &counts[..], &displs[..]);
+        root_process.scatter_varcount_into_root(&send, local_flat);
+    } else {
+        root_process.scatter_varcount_into(local_flat);
+    }
+
+    // Local kernels, parallel within the rank
+    let mut local_out = vec![0.0; local.len()];
+    solid_angle_tetrahedra_par(&local, &mut local_out)?;
+
+    // Gather results in rank order, which is input order
+    let (counts, displs) = shares(n, size, 1)?;
+    if is_root {
+        let mut recv = PartitionMut::new(out, &counts[..], &displs[..]);
+        root_process.gather_varcount_into_root(&local_out[..], &mut recv);
+    } else {
+        root_process.gather_varcount_into(&local_out[..]);
+    }
+
+    Ok(())
+}
+
+/// Sum of the solid angles of each rank's own `tetrahedra`, returned on
+/// every rank. Collective.
+///
+/// Each rank sums its share with compensation, so only the final
+/// reduction over ranks is a plain floating-point sum.
+pub fn total_solid_angle_mpi<C: Communicator>(comm: &C, tetrahedra: &[[[f64; 3]; 4]]) -> Result<f64, &'static str> {
+    let mut local_out = vec![0.0; tetrahedra.len()];
+    solid_angle_tetrahedra_par(tetrahedra, &mut local_out)?;
+    let mut acc = CompensatedSum::new();
+    local_out.iter().for_each(|&x| acc.add(x));
+
+    let mut total = 0.0;
+    comm.all_reduce_into(&acc.value(), &mut total, SystemOperation::sum());
+    Ok(total)
+}
+
+/// Solid angle subtended at each of the shared `origins` by a mesh whose
+/// faces are split across ranks, each rank passing its own faces as
+/// `mesh`. Every rank gets the full result in `out`. Collective.
+///
+/// Divided by `4π`, these are winding numbers of the whole mesh.
+pub fn solid_angles_multi_origin_mpi<C: Communicator>(
+    comm: &C,
+    mesh: &TriMesh,
+    origins: &[[f64; 3]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if origins.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    let mut partial = vec![0.0; origins.len()];
+    solid_angles_multi_origin(mesh, origins, &mut partial)?;
+    comm.all_reduce_into(&partial[..], out, SystemOperation::sum());
+    Ok(())
+}
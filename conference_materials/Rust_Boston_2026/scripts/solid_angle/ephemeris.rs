@@ -0,0 +1,159 @@
+//! Low-precision analytic Sun and Moon positions, and the illumination
+//! questions they answer: Moon phase, Earth shadow, and how much of the
+//! Earth seen from orbit is sunlit.
+//!
+//! Series are the low-precision formulas of the Astronomical Almanac
+//! (also in Meeus, "Astronomical Algorithms", 2nd ed., ch. 25 and 47 in
+//! truncated form): about 0.01° for the Sun and 0.3° for the Moon over
+//! 1950-2050. Positions are geocentric, in the equator and equinox of
+//! date; Earth rotation uses GMST only, without precession or nutation,
+//! which is within the same error budget. Not a substitute for SPICE.
+//!
+//! Behind the `ephemeris` feature, since most users of the geometry never
+//! need it.
+
+use crate::astro::{earth_solid_angle, first_hit, occluded_target, Spheroid, WGS84_A};
+use crate::vec3::{cross, dot, norm, sub};
+use std::f64::consts::TAU;
+
+/// Astronomical unit
+pub const AU: f64 = 149_597_870_700.0; // (m)
+/// Julian date of the J2000.0 epoch
+pub const J2000: f64 = 2_451_545.0;
+
+/// Julian date of a UTC calendar date and fractional hour, valid for
+/// Gregorian dates (Meeus ch. 7)
+pub fn julian_date(year: i32, month: u32, day: u32, hour: f64) -> f64 {
+    let (y, m) = if month <= 2 { (year - 1, month + 12) } else { (year, month) };
+    let a = y.div_euclid(100);
+    let b = 2 - a + a.div_euclid(4);
+    (365.25 * (y + 4716) as f64).floor() + (30.6001 * (m + 1) as f64).floor() + day as f64 + b as f64 - 1524.5
+        + hour / 24.0
+}
+
+/// Rotate ecliptic longitude/latitude (rad) at distance `r` into the equator of date
+#[inline]
+fn ecliptic_to_equatorial(lon: f64, lat: f64, r: f64, obliquity: f64) -> [f64; 3] {
+    let (sl, cl) = lon.sin_cos();
+    let (sb, cb) = lat.sin_cos();
+    let (se, ce) = obliquity.sin_cos();
+    let [x, y, z] = [cb * cl, cb * sl, sb];
+    [r * x, r * (ce * y - se * z), r * (se * y + ce * z)]
+}
+
+/// Obliquity of the ecliptic, days `n` from J2000
+#[inline]
+fn obliquity(n: f64) -> f64 {
+    (23.439 - 4e-7 * n).to_radians()
+}
+
+/// Geocentric Sun position in the equator of date, at Julian date `jd`
+pub fn sun_position(jd: f64) -> [f64; 3] {
+    let n = jd - J2000; // (day)
+    let l = 280.460 + 0.985_647_4 * n; // (deg) Mean longitude
+    let g = (357.528 + 0.985_600_3 * n).to_radians(); // (rad) Mean anomaly
+    let lon = (l + 1.915 * g.sin() + 0.020 * (2.0 * g).sin()).to_radians();
+    let r = (1.000_14 - 0.016_71 * g.cos() - 0.000_14 * (2.0 * g).cos()) * AU; // (m)
+    ecliptic_to_equatorial(lon, 0.0, r, obliquity(n))
+}
+
+/// Geocentric Moon position in the equator of date, at Julian date `jd`
+pub fn moon_position(jd: f64) -> [f64; 3] {
+    let n = jd - J2000; // (day)
+    let t = n / 36_525.0; // (century)
+    let sin = |a: f64, b: f64| (a + b * t).to_radians().sin();
+    let cos = |a: f64, b: f64| (a + b * t).to_radians().cos();
+
+    let lon = 218.32 + 481_267.881 * t + 6.29 * sin(135.0, 477_198.87) - 1.27 * sin(259.3, -413_335.36)
+        + 0.66 * sin(235.7, 890_534.22)
+        + 0.21 * sin(269.9, 954_397.74)
+        - 0.19 * sin(357.5, 35_999.05)
+        - 0.11 * sin(186.5, 966_404.03); // (deg)
+    let lat = 5.13 * sin(93.3, 483_202.02) + 0.28 * sin(228.2, 960_400.89)
+        - 0.28 * sin(318.3, 6_003.15)
+        - 0.17 * sin(217.6, -407_332.21); // (deg)
+    let parallax = 0.9508 + 0.0518 * cos(135.0, 477_198.87) + 0.0095 * cos(259.3, -413_335.38)
+        + 0.0078 * cos(235.7, 890_534.22)
+        + 0.0028 * cos(269.9, 954_397.70); // (deg)
+    let r = WGS84_A / parallax.to_radians().sin(); // (m)
+    ecliptic_to_equatorial(lon.to_radians(), lat.to_radians(), r, obliquity(n))
+}
+
+/// Greenwich mean sidereal time at Julian date `jd`
+#[inline]
+pub fn gmst(jd: f64) -> f64 {
+    (280.460_618_37 + 360.985_647_366_29 * (jd - J2000)).to_radians().rem_euclid(TAU) // (rad)
+}
+
+/// Rotate an equator-of-date position into ECEF at Julian date `jd`
+#[inline]
+pub fn inertial_to_ecef(p: [f64; 3], jd: f64) -> [f64; 3] {
+    let (s, c) = gmst(jd).sin_cos();
+    [c * p[0] + s * p[1], -s * p[0] + c * p[1], p[2]]
+}
+
+/// Illuminated fraction of the Moon's disk at Julian date `jd`,
+/// 0 at new moon and 1 at full
+pub fn moon_illuminated_fraction(jd: f64) -> f64 {
+    let (sun, moon) = (sun_position(jd), moon_position(jd));
+    // Phase angle at the Moon, between the directions to Sun and Earth
+    let to_sun = sub(sun, moon);
+    let to_earth = moon.map(|x| -x);
+    let phase = libm::atan2(norm(cross(to_sun, to_earth)), dot(to_sun, to_earth));
+    0.5 * (1.0 + phase.cos())
+}
+
+/// Whether `observer` (ECEF) is in the Earth's shadow at Julian date `jd`,
+/// treating the Sun as a point (no penumbra)
+pub fn in_earth_shadow(spheroid: &Spheroid, observer: [f64; 3], jd: f64) -> bool {
+    let sun = inertial_to_ecef(sun_position(jd), jd);
+    occluded_target(spheroid, observer, sun)
+}
+
+/// Sunlit part of the Earth's disk seen from `observer` (ECEF) at Julian
+/// date `jd`, as `(solid angle, fraction of the disk)`, or `None` if the
+/// observer is not outside the spheroid.
+///
+/// Directions are a Fibonacci lattice over the cone around the limb, each
+/// standing for an equal solid angle; the fraction converges about as
+/// `1/samples`.
+pub fn sunlit_earth_solid_angle(spheroid: &Spheroid, observer: [f64; 3], jd: f64, samples: usize) -> Option<(f64, f64)> {
+    let total = earth_solid_angle(spheroid, observer)?; // (sr)
+    let sun = inertial_to_ecef(sun_position(jd), jd);
+
+    // Cone toward the center, wide enough for the limb of the equatorial radius
+    let d = norm(observer);
+    let axis = observer.map(|x| -x / d);
+    let cos_max = (1.0 - (spheroid.a / d).powi(2)).max(0.0).sqrt();
+    let helper = if axis[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    let u = cross(axis, helper);
+    let u = u.map(|x| x / norm(u));
+    let v = cross(axis, u);
+
+    let (mut hits, mut lit) = (0_usize, 0_usize);
+    let golden = TAU * (1.0 - 1.0 / 1.618_033_988_749_895);
+    for k in 0..samples {
+        // Uniform in solid angle: cos θ uniform over [cos_max, 1]
+        let cos_t = 1.0 - (1.0 - cos_max) * (k as f64 + 0.5) / samples as f64;
+        let sin_t = (1.0 - cos_t * cos_t).sqrt();
+        let (s, c) = (golden * k as f64).sin_cos();
+        let dir = [0, 1, 2].map(|i| cos_t * axis[i] + sin_t * (c * u[i] + s * v[i]));
+        if let Some(p) = surface_hit(spheroid, observer, dir) {
+            hits += 1;
+            // Outward normal of the spheroid at p
+            let normal = [p[0] / spheroid.a.powi(2), p[1] / spheroid.a.powi(2), p[2] / spheroid.b.powi(2)];
+            if dot(normal, sub(sun, p)) > 0.0 {
+                lit += 1;
+            }
+        }
+    }
+    let fraction = if hits == 0 { 0.0 } else { lit as f64 / hits as f64 };
+    Some((fraction * total, fraction))
+}
+
+/// First point where the ray from `observer` along `dir` meets the spheroid
+#[inline]
+fn surface_hit(spheroid: &Spheroid, observer: [f64; 3], dir: [f64; 3]) -> Option<[f64; 3]> {
+    let t = first_hit(spheroid, observer, dir)?;
+    Some([0, 1, 2].map(|i| observer[i] + t * dir[i]))
+}
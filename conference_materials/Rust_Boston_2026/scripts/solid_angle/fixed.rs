@@ -0,0 +1,88 @@
+//! Fixed-point coordinate mode with an exact orientation.
+//!
+//! The sign of the solid angle is the sign of the scalar triple product,
+//! which cancels catastrophically in `f64` for flat tetrahedra and can come
+//! out with either sign. Snapping vertices to an integer grid first makes
+//! the triple product exact in `i128`, so flat tetrahedra give exactly
+//! zero and near-flat ones always get the correct sign. Only the magnitude
+//! goes through floating point.
+
+/// Largest grid coordinate magnitude, which keeps every edge component
+/// below 2^41 and so the triple product below 2^126
+pub const MAX_GRID_COORD: i64 = 1 << 40;
+
+/// Snap a point to the grid with spacing `resolution` (m). At 1 nm, the
+/// grid spans about ±1 km.
+#[inline]
+pub fn snap(p: [f64; 3], resolution: f64) -> Result<[i64; 3], &'static str> {
+    let mut q = [0_i64; 3];
+    for i in 0..3 {
+        let x = (p[i] / resolution).round();
+        if !x.is_finite() || x.abs() > MAX_GRID_COORD as f64 {
+            return Err("Coordinate out of fixed-point range");
+        }
+        q[i] = x as i64;
+    }
+
+    Ok(q)
+}
+
+#[inline]
+fn sub(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+/// Exact scalar triple product `a · (b × c)`
+#[inline]
+fn triple(a: [i64; 3], b: [i64; 3], c: [i64; 3]) -> i128 {
+    let [a, b, c] = [a, b, c].map(|v| v.map(i128::from));
+    a[0] * (b[1] * c[2] - b[2] * c[1]) + a[1] * (b[2] * c[0] - b[0] * c[2]) + a[2] * (b[0] * c[1] - b[1] * c[0])
+}
+
+/// Solid angle of a tetrahedron with grid vertices, seen from the first.
+/// The grid spacing does not matter, since solid angle is scale-invariant.
+#[inline]
+pub fn solid_angle_tetrahedron_scalar_fixed(v0: [i64; 3], v1: [i64; 3], v2: [i64; 3], v3: [i64; 3]) -> f64 {
+    // Vertex vectors, exact in both integer and f64 (< 2^53)
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0));
+    let triple = triple(a, b, c) as f64; // Rounded, but the sign and zero are exact
+    let [a, b, c] = [a, b, c].map(|v| v.map(|x| x as f64));
+
+    // Denominator as in the floating-point kernel
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0].mul_add(v[0], u[1].mul_add(v[1], u[2] * v[2]));
+    let (la, lb, lc) = (dot(a, a).sqrt(), dot(b, b).sqrt(), dot(c, c).sqrt());
+    let abc = la * lb * lc;
+    let denom = dot(a, b).mul_add(lc, dot(a, c).mul_add(lb, dot(b, c).mul_add(la, abc)));
+    let angle = 2.0 * libm::atan2(triple, denom);
+
+    // Check for degeneracy _last_ to avoid disrupting flow
+    if abc != 0.0 {
+        angle
+    } else {
+        0.0
+    }
+}
+
+/// Vector variant of [solid_angle_tetrahedron_scalar_fixed], snapping
+/// floating-point tetrahedra to a grid with spacing `resolution` (m).
+#[inline]
+pub fn solid_angle_tetrahedron_fixed(
+    tetrahedra: &[[[f64; 3]; 4]],
+    resolution: f64,
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let [v0, v1, v2, v3] = tetrahedra[i];
+        let (v0, v1, v2, v3) = (snap(v0, resolution)?, snap(v1, resolution)?, snap(v2, resolution)?, snap(v3, resolution)?);
+        out[i] = solid_angle_tetrahedron_scalar_fixed(v0, v1, v2, v3);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+//! glTF 2.0 assemblies into a [Scene], keeping the instance structure an
+//! STL export would flatten away.
+//!
+//! Each glTF mesh becomes one shared [TriMesh], its primitives merged,
+//! and each node that places it an [Instance] with the node's transform
+//! composed down the hierarchy, which must be a forest as the spec
+//! requires: a node with two parents, or in a cycle, is refused.
+//! Instances are named by their path of node names from the root,
+//! `"wing/hinge/panel"`, with the node index (`"node12"`) standing in
+//! for a missing name. Attributes record the
+//! structure: per instance, `"node"` and `"mesh"` are glTF indices; per
+//! face, `"material"` indexes [Gltf::materials], or is -1 without one.
+//! Nothing else about materials is read.
+//!
+//! Both `.glb` and `.gltf` files are read, the latter with buffers
+//! embedded as data URIs or in files beside them. A buffer's URI is
+//! percent-decoded and must stay below the file's directory: absolute
+//! paths, other schemes and `..` are refused. Triangles, strips and fans are read;
+//! points and lines are skipped. Positions must be floats, so meshes
+//! quantized by `KHR_mesh_quantization` are refused, as are sparse
+//! accessors. Units and axes are glTF's own: metres, `+y` up.
+
+use crate::instance::{Affine3, Instance};
+use crate::mesh::TriMesh;
+use crate::scene::Scene;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// `"glTF"`, little-endian
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// An imported file's scene and the names of its materials
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gltf {
+    pub scene: Scene,
+    pub materials: Vec<String>,
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Read a `.glb` or `.gltf` file, with any buffers it references by
+/// relative path
+pub fn read_gltf(path: impl AsRef<Path>) -> io::Result<Gltf> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let load = |uri: &str| std::fs::read(dir.join(relative_uri(uri)?));
+    if bytes.starts_with(&GLB_MAGIC.to_le_bytes()) {
+        let (json, bin) = glb_chunks(&bytes)?;
+        import(json, bin, load)
+    } else {
+        import(&bytes, None, load)
+    }
+}
+
+/// The relative path a buffer URI names, percent-decoded, refused if it
+/// could reach outside the directory it is relative to
+fn relative_uri(uri: &str) -> io::Result<PathBuf> {
+    // A scheme is letters and such before a colon, ahead of any slash
+    let scheme = uri.find(':').is_some_and(|colon| !uri[..colon].contains('/'));
+    if scheme {
+        return Err(invalid("Buffer URI not a relative path"));
+    }
+    let mut decoded = Vec::with_capacity(uri.len());
+    let mut bytes = uri.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            decoded.push(b);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let digit = |h: Option<u8>| h.and_then(|h| (h as char).to_digit(16));
+        let [Some(hi), Some(lo)] = hex.map(digit) else {
+            return Err(invalid("Malformed percent escape in buffer URI"));
+        };
+        decoded.push((hi * 16 + lo) as u8);
+    }
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid("Buffer URI not UTF-8"))?;
+
+    // Checked after decoding, so `%2e%2e` can't slip past
+    let mut path = PathBuf::new();
+    for (i, part) in decoded.split(['/', '\\']).enumerate() {
+        match part {
+            "" if i == 0 => return Err(invalid("Buffer URI not a relative path")),
+            "" | "." => {}
+            ".." => return Err(invalid("Buffer URI outside its directory")),
+            _ if part.contains(':') || part.contains('\0') => return Err(invalid("Buffer URI not a relative path")),
+            _ => path.push(part),
+        }
+    }
+    if path.as_os_str().is_empty() {
+        return Err(invalid("Buffer URI not a relative path"));
+    }
+    Ok(path)
+}
+
+/// Read a `.glb` stream; its buffers must be the binary chunk or data URIs
+pub fn read_glb<R: Read>(r: &mut R) -> io::Result<Gltf> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    let (json, bin) = glb_chunks(&bytes)?;
+    import(json, bin, |_| Err(invalid("External buffers need read_gltf")))
+}
+
+/// Read `.gltf` JSON; its buffers must be data URIs
+pub fn parse_gltf(json: &[u8]) -> io::Result<Gltf> {
+    import(json, None, |_| Err(invalid("External buffers need read_gltf")))
+}
+
+/// The JSON chunk and binary chunk, if any, of a `.glb`
+fn glb_chunks(bytes: &[u8]) -> io::Result<(&[u8], Option<&[u8]>)> {
+    let word = |at: usize| bytes.get(at..at + 4).map(|w| u32::from_le_bytes(w.try_into().expect("4 bytes")));
+    if word(0) != Some(GLB_MAGIC) {
+        return Err(invalid("Not a glTF binary"));
+    }
+    if word(4) != Some(2) {
+        return Err(invalid("Unsupported glTF version"));
+    }
+    let len = word(8).ok_or_else(|| invalid("Truncated glTF binary"))? as usize;
+    let bytes = bytes.get(..len).ok_or_else(|| invalid("Truncated glTF binary"))?;
+
+    // Chunks follow the 12-byte header: length, type, data
+    let (mut json, mut bin, mut at) = (None, None, 12);
+    while at < bytes.len() {
+        let (Some(n), Some(kind)) = (word(at), word(at + 4)) else {
+            return Err(invalid("Truncated glTF binary"));
+        };
+        let data = bytes.get(at + 8..at + 8 + n as usize).ok_or_else(|| invalid("Truncated glTF binary"))?;
+        match kind {
+            CHUNK_JSON if json.is_none() => json = Some(data),
+            CHUNK_BIN if bin.is_none() => bin = Some(data),
+            _ => {} // Unknown chunks are skipped, per the spec
+        }
+        at += 8 + n as usize;
+    }
+    Ok((json.ok_or_else(|| invalid("glTF binary without JSON"))?, bin))
+}
+
+/// Build the scene from the JSON, the `.glb` binary chunk if any, and
+/// `load` for buffers in other files
+fn import(json: &[u8], bin: Option<&[u8]>, load: impl Fn(&str) -> io::Result<Vec<u8>>) -> io::Result<Gltf> {
+    let doc: Value = serde_json::from_slice(json).map_err(|_| invalid("Malformed glTF JSON"))?;
+    if !doc["asset"]["version"].as_str().is_some_and(|v| v.starts_with("2.")) {
+        return Err(invalid("Unsupported glTF version"));
+    }
+
+    // Buffers: the binary chunk has no URI; others embed or reference it
+    let mut buffers = Vec::new();
+    for (i, buffer) in list(&doc["buffers"]).iter().enumerate() {
+        let data = match buffer["uri"].as_str() {
+            None if i == 0 => bin.ok_or_else(|| invalid("Buffer without data"))?.to_vec(),
+            None => return Err(invalid("Buffer without data")),
+            Some(uri) if uri.starts_with("data:") => {
+                let (_, encoded) = uri.split_once(";base64,").ok_or_else(|| invalid("Data URI not base64"))?;
+                base64(encoded)?
+            }
+            Some(uri) => load(uri)?,
+        };
+        if data.len() < index(&buffer["byteLength"])? {
+            return Err(invalid("Buffer shorter than its byteLength"));
+        }
+        buffers.push(data);
+    }
+    let gltf = Document { doc: &doc, buffers };
+    let materials = list(&doc["materials"]).iter().enumerate().map(|(i, m)| m["name"].as_str().map_or_else(|| format!("material{i}"), str::to_string)).collect();
+
+    // Each node has at most one parent, as the spec requires; a node
+    // shared between parents would be expanded once per path to it
+    let nodes = list(&doc["nodes"]);
+    let mut parent = vec![None; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for child in list(&node["children"]) {
+            let slot = parent.get_mut(index(child)?).ok_or_else(|| invalid("Node index out of range"))?;
+            if slot.replace(i).is_some() {
+                return Err(invalid("Node with more than one parent"));
+            }
+        }
+    }
+
+    // Roots: the default scene's, else every node no other node parents
+    let roots: Vec<usize> = match doc["scenes"].get(doc["scene"].as_u64().unwrap_or(0) as usize) {
+        Some(scene) => list(&scene["nodes"]).iter().map(index).collect::<io::Result<_>>()?,
+        None => {
+            (0..nodes.len()).filter(|&i| parent[i].is_none()).collect()
+        }
+    };
+
+    // Walk down the hierarchy composing transforms. With one parent
+    // each, a node reached twice has gone round a cycle, or is a root
+    // the scene lists twice
+    let (mut instances, mut meshes, mut node_ids, mut mesh_ids) = (Vec::new(), HashMap::new(), Vec::new(), Vec::new());
+    let mut visited = vec![false; nodes.len()];
+    let mut stack: Vec<(usize, Affine3, String)> = roots.into_iter().rev().map(|r| (r, Affine3::IDENTITY, String::new())).collect();
+    while let Some((i, above, prefix)) = stack.pop() {
+        let node = nodes.get(i).ok_or_else(|| invalid("Node index out of range"))?;
+        if std::mem::replace(&mut visited[i], true) {
+            return Err(invalid(if parent[i].is_some() { "Node hierarchy has a cycle" } else { "Scene lists a node twice" }));
+        }
+        let transform = local_transform(node)?.then(&above);
+        let name = node["name"].as_str().map_or_else(|| format!("node{i}"), str::to_string);
+        let path = if prefix.is_empty() { name } else { format!("{prefix}/{name}") };
+        if !node["mesh"].is_null() {
+            let m = index(&node["mesh"])?;
+            let mesh = match meshes.get(&m) {
+                Some(mesh) => Arc::clone(mesh),
+                None => {
+                    let mesh = Arc::new(gltf.mesh(m)?);
+                    meshes.insert(m, Arc::clone(&mesh));
+                    mesh
+                }
+            };
+            instances.push((path.clone(), Instance::new(mesh, transform)));
+            node_ids.push(i as f64);
+            mesh_ids.push(m as f64);
+        }
+        for child in list(&node["children"]).iter().rev() {
+            stack.push((index(child)?, transform, path.clone()));
+        }
+    }
+
+    // Paths repeat where siblings share a name; the node index tells them apart
+    let mut seen = HashMap::new();
+    for ((name, _), &node) in instances.iter_mut().zip(&node_ids) {
+        if *seen.entry(name.clone()).and_modify(|n| *n += 1).or_insert(0) > 0 {
+            *name = format!("{name}#{node}");
+        }
+    }
+
+    let mut scene = Scene::new(instances).map_err(invalid)?;
+    scene.instance_attributes_mut().insert("node", node_ids).map_err(invalid)?;
+    scene.instance_attributes_mut().insert("mesh", mesh_ids).map_err(invalid)?;
+    Ok(Gltf { scene, materials })
+}
+
+/// The JSON with its buffers loaded
+struct Document<'a> {
+    doc: &'a Value,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Document<'_> {
+    /// Mesh `m`, its triangle primitives merged, with a `"material"` per face
+    fn mesh(&self, m: usize) -> io::Result<TriMesh> {
+        let mesh = self.doc["meshes"].get(m).ok_or_else(|| invalid("Mesh index out of range"))?;
+        let (mut vertices, mut faces, mut material) = (Vec::new(), Vec::new(), Vec::new());
+
+        // Primitives sharing positions share vertices: first vertex and
+        // count of each positions accessor read so far
+        let mut read = HashMap::new();
+        for primitive in list(&mesh["primitives"]) {
+            let mode = primitive["mode"].as_u64().unwrap_or(4);
+            if mode < 4 {
+                continue; // Points and lines
+            }
+            let a = index(&primitive["attributes"]["POSITION"])?;
+            let (base, n) = match read.get(&a) {
+                Some(&span) => span,
+                None => {
+                    let base = vertices.len() as u32;
+                    vertices.extend(self.accessor(a, "VEC3")?.chunks_exact(3).map(|p| [p[0], p[1], p[2]]));
+                    *read.entry(a).or_insert((base, vertices.len() as u32 - base))
+                }
+            };
+            let ids: Vec<u32> = match &primitive["indices"] {
+                Value::Null => (0..n).collect(),
+                i => self.accessor(index(i)?, "SCALAR")?.into_iter().map(|x| x as u32).collect(),
+            };
+            if ids.iter().any(|&i| i >= n) {
+                return Err(invalid("Vertex index out of range"));
+            }
+            let before = faces.len();
+            match mode {
+                4 => faces.extend(ids.chunks_exact(3).map(|t| [t[0], t[1], t[2]])),
+                5 => faces.extend((0..ids.len().saturating_sub(2)).map(|i| if i % 2 == 0 { [ids[i], ids[i + 1], ids[i + 2]] } else { [ids[i], ids[i + 2], ids[i + 1]] })),
+                6 => faces.extend((1..ids.len().saturating_sub(1)).map(|i| [ids[i], ids[i + 1], ids[0]])),
+                _ => return Err(invalid("Unknown primitive mode")),
+            }
+            for f in &mut faces[before..] {
+                *f = f.map(|i| i + base);
+            }
+            let id = if primitive["material"].is_null() { -1.0 } else { index(&primitive["material"])? as f64 };
+            material.resize(faces.len(), id);
+        }
+        let mut mesh = TriMesh::new(vertices, faces).map_err(invalid)?;
+        mesh.face_attributes_mut().insert("material", material).map_err(invalid)?;
+        Ok(mesh)
+    }
+
+    /// Values of accessor `a`, which must be of type `kind`, as `f64`:
+    /// positions as floats, indices as unsigned integers
+    fn accessor(&self, a: usize, kind: &str) -> io::Result<Vec<f64>> {
+        let accessor = self.doc["accessors"].get(a).ok_or_else(|| invalid("Accessor index out of range"))?;
+        if !accessor["sparse"].is_null() {
+            return Err(invalid("Sparse accessors are not supported"));
+        }
+        if accessor["type"].as_str() != Some(kind) {
+            return Err(invalid("Accessor of the wrong type"));
+        }
+        let components = if kind == "VEC3" { 3 } else { 1 };
+        let (size, read): (usize, fn(&[u8]) -> f64) = match (kind, accessor["componentType"].as_u64()) {
+            ("VEC3", Some(5126)) => (4, |b| f64::from(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))),
+            ("VEC3", _) => return Err(invalid("Positions must be floats")),
+            (_, Some(5121)) => (1, |b| f64::from(b[0])),
+            (_, Some(5123)) => (2, |b| f64::from(u16::from_le_bytes([b[0], b[1]]))),
+            (_, Some(5125)) => (4, |b| f64::from(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))),
+            _ => return Err(invalid("Indices must be unsigned integers")),
+        };
+
+        // Elements may be interleaved, `byteStride` apart. Offsets and
+        // counts are the file's, so sums that overflow are out of bounds,
+        // and the count is bounded by the view before anything is allocated
+        let count = index(&accessor["count"])?;
+        let view = self.doc["bufferViews"].get(index(&accessor["bufferView"])?).ok_or_else(|| invalid("Buffer view index out of range"))?;
+        let buffer = self.buffers.get(index(&view["buffer"])?).ok_or_else(|| invalid("Buffer index out of range"))?;
+        let view_start = index(&view["byteOffset"]).unwrap_or(0);
+        let element = size * components;
+        let stride = index(&view["byteStride"]).unwrap_or(element);
+        let start = view_start.checked_add(index(&accessor["byteOffset"]).unwrap_or(0));
+        let end = match count.checked_sub(1) {
+            None => start,
+            Some(last) => stride.checked_mul(last).and_then(|x| x.checked_add(start?)).and_then(|x| x.checked_add(element)),
+        };
+        let view_end = view_start.checked_add(index(&view["byteLength"])?);
+        let (Some(start), Some(end), Some(view_end)) = (start, end, view_end) else {
+            return Err(invalid("Accessor out of its buffer"));
+        };
+        if end > view_end || view_end > buffer.len() || stride < element {
+            return Err(invalid("Accessor out of its buffer"));
+        }
+        Ok((0..count).flat_map(|i| (0..components).map(move |c| start + i * stride + c * size)).map(|at| read(&buffer[at..at + size])).collect())
+    }
+}
+
+/// A node's transform relative to its parent: `matrix`, column-major, or
+/// translation, rotation and scale, applied scale first
+fn local_transform(node: &Value) -> io::Result<Affine3> {
+    let numbers = |v: &Value, n: usize| -> io::Result<Option<Vec<f64>>> {
+        match v {
+            Value::Null => Ok(None),
+            Value::Array(a) if a.len() == n => a.iter().map(|x| x.as_f64().ok_or_else(|| invalid("Transform not numeric"))).collect::<io::Result<_>>().map(Some),
+            _ => Err(invalid("Transform of the wrong length")),
+        }
+    };
+    if let Some(m) = numbers(&node["matrix"], 16)? {
+        let linear = std::array::from_fn(|r| std::array::from_fn(|c| m[4 * c + r]));
+        return Ok(Affine3 { linear, translation: [m[12], m[13], m[14]] });
+    }
+
+    let s = numbers(&node["scale"], 3)?.unwrap_or(vec![1.0; 3]);
+    let [x, y, z, w] = numbers(&node["rotation"], 4)?.map_or([0.0, 0.0, 0.0, 1.0], |q| [q[0], q[1], q[2], q[3]]);
+    let t = numbers(&node["translation"], 3)?.unwrap_or(vec![0.0; 3]);
+    let rotation = [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w), 2.0 * (x * z + y * w)],
+        [2.0 * (x * y + z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w)],
+        [2.0 * (x * z - y * w), 2.0 * (y * z + x * w), 1.0 - 2.0 * (x * x + y * y)],
+    ];
+    let linear = rotation.map(|row| std::array::from_fn(|c| row[c] * s[c]));
+    Ok(Affine3 { linear, translation: [t[0], t[1], t[2]] })
+}
+
+/// Elements of a JSON array; nothing for a missing one
+fn list(v: &Value) -> &[Value] {
+    v.as_array().map_or(&[], Vec::as_slice)
+}
+
+fn index(v: &Value) -> io::Result<usize> {
+    v.as_u64().map(|i| i as usize).ok_or_else(|| invalid("Missing or negative glTF index"))
+}
+
+/// Decode standard base64, padded or not
+fn base64(s: &str) -> io::Result<Vec<u8>> {
+    let digit = |c: u8| match c {
+        b'A'..=b'Z' => Ok(c - b'A'),
+        b'a'..=b'z' => Ok(c - b'a' + 26),
+        b'0'..=b'9' => Ok(c - b'0' + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(invalid("Invalid base64")),
+    };
+    let s = s.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.chunks(4) {
+        let bits = chunk.iter().try_fold(0_u32, |acc, &c| Ok::<_, io::Error>(acc << 6 | u32::from(digit(c)?)))? << (6 * (4 - chunk.len()));
+        let bytes = bits.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Ok(out)
+}
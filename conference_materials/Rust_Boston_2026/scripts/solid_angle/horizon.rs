@@ -0,0 +1,70 @@
+//! Horizon masks over terrain meshes, and the sky they leave open.
+//!
+//! Coordinates are local east-north-up: `x` east, `y` north, `z` up.
+//! Azimuth runs clockwise from north, so 90° is east; elevation is above
+//! the horizontal plane through the observer.
+//!
+//! Each azimuth's mask is the highest point of the terrain in the
+//! vertical half-plane that way, found exactly by one branch-and-bound
+//! walk of the [Bvh] ([Bvh::max_elevation]). Bisecting on elevation with
+//! segment casts would take some thirty casts per bin, each grazing the
+//! surface through most of the tree. Overhangs count as solid down to
+//! the ground.
+
+use crate::bvh::Bvh;
+use crate::mesh::TriMesh;
+use std::f64::consts::TAU;
+
+/// Direction at azimuth `az` clockwise from north and elevation `el` (rad)
+#[inline]
+pub fn direction(az: f64, el: f64) -> [f64; 3] {
+    let ((sa, ca), (se, ce)) = (az.sin_cos(), el.sin_cos());
+    [ce * sa, ce * ca, se]
+}
+
+/// Elevation mask seen from `observer` in each of `azimuth_bins` equal
+/// bins, sampled at bin centers starting from north: the highest
+/// elevation of terrain that way (rad), `π/2` under a roof. The terrain
+/// must reach out in every bin, and `bvh` must have been built from it.
+pub fn horizon_profile(observer: [f64; 3], terrain: &TriMesh, bvh: &Bvh, azimuth_bins: usize) -> Result<Vec<f64>, &'static str> {
+    masks(observer, terrain, bvh, azimuth_bins)?
+        .into_iter()
+        .map(|mask| mask.ok_or("Observer not over the terrain"))
+        .collect()
+}
+
+/// Sky view factor at `observer`, the share of a horizontal surface's
+/// view that is sky: the mean over `azimuth_bins` of `cos²` of the mask,
+/// masks below the horizontal and bins with no terrain counting as flat.
+/// 1 on open ground, down toward 0 at the bottom of a deep street
+/// canyon. Unlike [horizon_profile] this works at the edges of the
+/// terrain too.
+pub fn sky_view_factor(observer: [f64; 3], terrain: &TriMesh, bvh: &Bvh, azimuth_bins: usize) -> Result<f64, &'static str> {
+    let masks = masks(observer, terrain, bvh, azimuth_bins)?;
+    Ok(masks.iter().map(|mask| mask.map_or(1.0, |m| m.max(0.0).cos().powi(2))).sum::<f64>() / azimuth_bins as f64)
+}
+
+/// Masks at bin centers, `None` where there is no terrain
+fn masks(observer: [f64; 3], terrain: &TriMesh, bvh: &Bvh, azimuth_bins: usize) -> Result<Vec<Option<f64>>, &'static str> {
+    // Check the BVH is for this mesh, as far as can be told
+    if bvh.faces() != terrain.faces().len() {
+        return Err("BVH built for another mesh");
+    }
+
+    let width = TAU / azimuth_bins as f64;
+    let masks = (0..azimuth_bins)
+        .map(|i| {
+            let [east, north, _] = direction((i as f64 + 0.5) * width, 0.0);
+            bvh.max_elevation(terrain, observer, [east, north])
+        })
+        .collect();
+    Ok(masks)
+}
+
+/// Solid angle of the sky open above a mask from [horizon_profile] (sr):
+/// each bin contributes `Δaz (1 - sin(mask))`. A flat horizon gives 2π,
+/// a peak with the ground falling away on every side more.
+pub fn sky_solid_angle(profile: &[f64]) -> f64 {
+    let width = TAU / profile.len() as f64;
+    profile.iter().map(|&mask| width * (1.0 - mask.sin())).sum()
+}
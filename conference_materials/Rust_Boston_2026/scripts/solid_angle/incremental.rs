@@ -0,0 +1,167 @@
+//! Solid angles and winding numbers of a mesh whose vertices move, kept
+//! up to date frame by frame without recomputing the whole mesh.
+//!
+//! Moves are journaled and applied together by [MovingMesh::update]: the
+//! faces around moved vertices are the only ones whose terms change, so
+//! each origin's total drops their old terms and takes their new ones,
+//! `origins × dirty faces` kernel calls instead of `origins × faces`.
+//! Totals are [CompensatedSum]s, so a long run of updates stays within a
+//! few ulps of a fresh sum. The mesh's [Bvh] is refit over the same faces,
+//! keeping occlusion and horizon queries in step with the geometry.
+//!
+//! Refitting keeps the tree's grouping, which suits small motions. As
+//! faces travel further, subtrees whose boxes have grown past
+//! [REBUILD_GROWTH] times their size when built are regrouped in place
+//! ([Bvh::rebuild_degraded]), in buffers the mesh keeps from frame to
+//! frame; [MovingMesh::rebuild] starts over.
+
+use crate::arena::Arena;
+use crate::bvh::Bvh;
+use crate::mesh::TriMesh;
+use crate::par::par_threshold;
+use crate::sum::CompensatedSum;
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::topology::Csr;
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Growth in a subtree's box, by surface area, past which an update
+/// rebuilds it rather than refitting
+pub const REBUILD_GROWTH: f64 = 2.0;
+
+/// A mesh, the origins it is seen from, and the solid angle it subtends
+/// at each, as of the last [MovingMesh::update]
+#[derive(Clone, Debug)]
+pub struct MovingMesh {
+    mesh: TriMesh,
+    bvh: Bvh,
+    vertex_faces: Csr,
+    origins: Vec<[f64; 3]>,
+    totals: Vec<CompensatedSum>,
+    /// Moves since the last update, in order
+    journal: Vec<(u32, [f64; 3])>,
+    /// Working buffers of the BVH rebuild
+    scratch: Arena<f64>,
+}
+
+impl MovingMesh {
+    /// Track `mesh` as seen from `origins`, with a first full pass
+    pub fn new(mesh: TriMesh, origins: Vec<[f64; 3]>) -> Self {
+        let bvh = Bvh::new(&mesh);
+        let vertex_faces = mesh.build_adjacency().vertex_faces;
+        let totals = vec![CompensatedSum::new(); origins.len()];
+        let mut moving = Self { mesh, bvh, vertex_faces, origins, totals, journal: Vec::new(), scratch: Arena::new() };
+        moving.recompute();
+        moving
+    }
+
+    /// The mesh as of the last update
+    #[inline]
+    pub fn mesh(&self) -> &TriMesh {
+        &self.mesh
+    }
+
+    /// Hierarchy over [MovingMesh::mesh], refit at each update
+    #[inline]
+    pub fn bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    #[inline]
+    pub fn origins(&self) -> &[[f64; 3]] {
+        &self.origins
+    }
+
+    /// Solid angle subtended at origin `i`
+    #[inline]
+    pub fn solid_angle(&self, i: usize) -> f64 {
+        self.totals[i].value()
+    }
+
+    /// Solid angle subtended at every origin
+    pub fn solid_angles(&self) -> Vec<f64> {
+        self.totals.iter().map(CompensatedSum::value).collect()
+    }
+
+    /// Winding number of a closed mesh about origin `i`: 1 inside an
+    /// outward-wound mesh, 0 outside
+    #[inline]
+    pub fn winding_number(&self, i: usize) -> f64 {
+        self.solid_angle(i) / (4.0 * PI)
+    }
+
+    /// Move vertex `v` to `to` at the next update
+    pub fn move_vertex(&mut self, v: usize, to: [f64; 3]) -> Result<(), &'static str> {
+        // Check bounds
+        if v >= self.mesh.vertices().len() {
+            return Err("Vertex index out of range");
+        }
+        self.journal.push((v as u32, to));
+        Ok(())
+    }
+
+    /// Moves waiting for the next update
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Apply the journaled moves: the solid angles over the faces around
+    /// moved vertices, then the BVH, refit and regrouped where degraded.
+    /// Returns how many faces changed.
+    pub fn update(&mut self) -> usize {
+        if self.journal.is_empty() {
+            return 0;
+        }
+
+        // Faces around the moved vertices, before and after
+        let mut dirty: Vec<u32> = self.journal.iter().flat_map(|&(v, _)| self.vertex_faces.row(v as usize)).copied().collect();
+        dirty.sort_unstable();
+        dirty.dedup();
+        let old: Vec<[[f64; 3]; 3]> = dirty.iter().map(|&f| self.mesh.triangle(f as usize)).collect();
+        let vertices = self.mesh.vertices_mut();
+        for (v, to) in self.journal.drain(..) {
+            vertices[v as usize] = to;
+        }
+        let new: Vec<[[f64; 3]; 3]> = dirty.iter().map(|&f| self.mesh.triangle(f as usize)).collect();
+
+        // Old terms out, new ones in
+        let swap = |o: &[f64; 3], total: &mut CompensatedSum| {
+            for (a, b) in old.iter().zip(&new) {
+                total.add(-solid_angle_tetrahedron_scalar(*o, a[0], a[1], a[2]));
+                total.add(solid_angle_tetrahedron_scalar(*o, b[0], b[1], b[2]));
+            }
+        };
+        if self.origins.len().saturating_mul(dirty.len()) < par_threshold() {
+            self.origins.iter().zip(&mut self.totals).for_each(|(o, t)| swap(o, t));
+        } else {
+            self.origins.par_iter().zip(&mut self.totals).for_each(|(o, t)| swap(o, t));
+        }
+
+        self.bvh.refit_faces(&self.mesh, &dirty);
+        self.bvh.rebuild_degraded_in(&self.mesh, REBUILD_GROWTH, &mut self.scratch);
+        dirty.len()
+    }
+
+    /// Apply any pending moves, then start afresh: rebuild the BVH and
+    /// resum every solid angle from scratch
+    pub fn rebuild(&mut self) {
+        self.update();
+        self.bvh = Bvh::new(&self.mesh);
+        self.recompute();
+    }
+
+    /// Every origin's total over every face
+    fn recompute(&mut self) {
+        let mesh = &self.mesh;
+        let sum = |o: &[f64; 3], total: &mut CompensatedSum| {
+            *total = CompensatedSum::new();
+            mesh.triangles().for_each(|t| total.add(solid_angle_tetrahedron_scalar(*o, t[0], t[1], t[2])));
+        };
+        if self.origins.len().saturating_mul(mesh.faces().len()) < par_threshold() {
+            self.origins.iter().zip(&mut self.totals).for_each(|(o, t)| sum(o, t));
+        } else {
+            self.origins.par_iter().zip(&mut self.totals).for_each(|(o, t)| sum(o, t));
+        }
+    }
+}
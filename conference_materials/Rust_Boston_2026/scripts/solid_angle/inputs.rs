@@ -0,0 +1,138 @@
+//! Seeded random tetrahedra for benchmarks, accuracy runs and fuzzing.
+//!
+//! The generator is PCG64 (O'Neill's PCG XSL RR 128/64, the `pcg64` of
+//! the reference C library and of NumPy's bit generator, with the
+//! reference seeding rather than NumPy's `SeedSequence`). Given the same
+//! seed, stream and count, every platform produces byte-identical
+//! inputs: the generator is integer-only, floats are built from exactly
+//! 53 random bits, no arithmetic is fused or reassociated, and the one
+//! transcendental, `pow` for log-uniform scales, comes from `libm`
+//! rather than the platform's math library. `gen_inputs.rs` checks
+//! known-answer hashes of each distribution.
+
+use std::str::FromStr;
+
+type Tet = [[f64; 3]; 4];
+
+const MULTIPLIER: u128 = 0x2360_ed05_1fc6_5da4_4385_df64_9fcc_f645;
+
+/// PCG XSL RR 128/64 generator
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pcg64 {
+    state: u128,
+    increment: u128,
+}
+
+impl Pcg64 {
+    /// Generator for `seed` on stream `stream`, seeded as the reference
+    /// `pcg64_srandom_r(seed, stream)`. Different streams are independent
+    /// sequences, e.g. one per thread.
+    pub const fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self { state: 0, increment: ((stream as u128) << 1) | 1 };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed as u128);
+        rng.step();
+        rng
+    }
+
+    #[inline]
+    const fn step(&mut self) {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.step();
+        let rot = (self.state >> 122) as u32;
+        (((self.state >> 64) as u64) ^ (self.state as u64)).rotate_right(rot)
+    }
+
+    /// Uniform on `0..n`, by a widening multiply rather than a modulus
+    #[inline]
+    pub fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    /// Uniform on `[0, 1)`, from the top 53 bits
+    #[inline]
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+    }
+
+    /// Uniform on `[lo, hi)`
+    #[inline]
+    pub fn uniform(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.unit()
+    }
+
+    /// Uniform in the cube `[-1, 1)³`
+    pub fn point(&mut self) -> [f64; 3] {
+        [self.uniform(-1.0, 1.0), self.uniform(-1.0, 1.0), self.uniform(-1.0, 1.0)]
+    }
+
+    /// Four vertices uniform in the cube `[-1, 1)³`
+    pub fn tet(&mut self) -> Tet {
+        [self.point(), self.point(), self.point(), self.point()]
+    }
+}
+
+/// Families of test tetrahedra
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    /// Vertices uniform in `[-1, 1)³`
+    Random,
+    /// Last vertex 10⁻⁴ to 10⁻¹⁴ off the plane of the others
+    Sliver,
+    /// Random, scaled by 10⁻¹⁰⁰ to 10⁻¹
+    Tiny,
+    /// Random, scaled by 10¹ to 10¹⁰⁰
+    Huge,
+}
+
+impl Distribution {
+    pub const ALL: [Self; 4] = [Self::Random, Self::Sliver, Self::Tiny, Self::Huge];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Sliver => "sliver",
+            Self::Tiny => "tiny",
+            Self::Huge => "huge",
+        }
+    }
+
+    /// Draw one tetrahedron
+    pub fn sample(self, rng: &mut Pcg64) -> Tet {
+        match self {
+            Self::Random => rng.tet(),
+            Self::Sliver => {
+                let [v0, v1, v2, _] = rng.tet();
+                let (s, t) = (rng.uniform(-1.0, 2.0), rng.uniform(-1.0, 2.0));
+                let eps = libm::pow(10.0, rng.uniform(-14.0, -4.0));
+                let [n0, n1, n2] = rng.point();
+                let v3 = [0, 1, 2].map(|i| v0[i] + s * (v1[i] - v0[i]) + t * (v2[i] - v0[i]));
+                [v0, v1, v2, [v3[0] + eps * n0, v3[1] + eps * n1, v3[2] + eps * n2]]
+            }
+            Self::Tiny | Self::Huge => {
+                // Exponents kept clear of where the cubed lengths under/overflow
+                let exponent = if self == Self::Tiny { rng.uniform(-100.0, -1.0) } else { rng.uniform(1.0, 100.0) };
+                let scale = libm::pow(10.0, exponent);
+                rng.tet().map(|v| v.map(|x| x * scale))
+            }
+        }
+    }
+}
+
+impl FromStr for Distribution {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|d| d.name() == s).ok_or("Unknown distribution")
+    }
+}
+
+/// `n` tetrahedra from `dist`, the same on every platform for a given `seed`
+pub fn tetrahedra(dist: Distribution, seed: u64, n: usize) -> Vec<Tet> {
+    let mut rng = Pcg64::new(seed, 0);
+    (0..n).map(|_| dist.sample(&mut rng)).collect()
+}
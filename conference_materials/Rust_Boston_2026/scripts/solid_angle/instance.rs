@@ -0,0 +1,221 @@
+//! One mesh placed many times over, for scenes with thousands of copies
+//! of the same part (the panels of a solar array, tiles, fasteners)
+//! without a copy of its vertices for each.
+//!
+//! An [Instance] shares its mesh through an [Arc] and records where it
+//! goes as an [Affine3]. Placed triangles are computed as the kernels
+//! stream them, and cost nine multiply-adds a vertex. A mirroring
+//! transform turns a mesh inside out, so its faces are rewound to keep
+//! a closed part's solid angle `+4π` inside.
+//!
+//! Occlusion doesn't place the triangles at all. An affine map takes
+//! segments to segments and keeps where along them they cross a face, so
+//! [Instance::occluded] carries the segment back into the mesh's own
+//! frame, and one [Bvh] built there serves every instance of the mesh.
+
+use crate::bvh::Bvh;
+use crate::mesh::TriMesh;
+use crate::multi_origin::{solid_angles_block, BLOCK};
+use crate::par::par_threshold;
+use crate::thermal::view_factors_with;
+use crate::vec3::{cross, dot, norm};
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// Affine map `x ↦ linear x + translation`, `linear` by rows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine3 {
+    pub linear: [[f64; 3]; 3],
+    pub translation: [f64; 3],
+}
+
+impl Default for Affine3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine3 {
+    pub const IDENTITY: Self = Self { linear: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], translation: [0.0; 3] };
+
+    pub fn translation(t: [f64; 3]) -> Self {
+        Self { translation: t, ..Self::IDENTITY }
+    }
+
+    /// Uniform scaling by `s` about the origin
+    pub fn scaling(s: f64) -> Self {
+        Self { linear: Self::IDENTITY.linear.map(|row| row.map(|x| s * x)), ..Self::IDENTITY }
+    }
+
+    /// Right-handed rotation by `angle` (rad) about `axis` through the
+    /// origin; `axis` needn't be unit
+    pub fn rotation(axis: [f64; 3], angle: f64) -> Self {
+        let len = norm(axis);
+        let [x, y, z] = axis.map(|a| a / len);
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        let linear = [
+            [c + t * x * x, t * x * y - s * z, t * x * z + s * y],
+            [t * x * y + s * z, c + t * y * y, t * y * z - s * x],
+            [t * x * z - s * y, t * y * z + s * x, c + t * z * z],
+        ];
+        Self { linear, translation: [0.0; 3] }
+    }
+
+    /// This map followed by `next`
+    pub fn then(&self, next: &Affine3) -> Self {
+        let linear = std::array::from_fn(|i| std::array::from_fn(|j| (0..3).map(|k| next.linear[i][k] * self.linear[k][j]).sum()));
+        Self { linear, translation: next.apply(self.translation) }
+    }
+
+    #[inline]
+    pub fn apply(&self, p: [f64; 3]) -> [f64; 3] {
+        let [a, b, c] = self.linear;
+        let t = self.translation;
+        [dot(a, p) + t[0], dot(b, p) + t[1], dot(c, p) + t[2]]
+    }
+
+    /// The linear part alone, for directions and displacements
+    #[inline]
+    pub fn apply_vector(&self, v: [f64; 3]) -> [f64; 3] {
+        self.linear.map(|row| dot(row, v))
+    }
+
+    /// Volume scale of the map; negative for a mirroring one
+    pub fn determinant(&self) -> f64 {
+        let [a, b, c] = self.linear;
+        dot(a, cross(b, c))
+    }
+
+    /// The map undoing this one, by the adjugate
+    pub fn inverse(&self) -> Result<Self, &'static str> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return Err("Singular transform");
+        }
+        let [a, b, c] = self.linear;
+
+        // Columns of the adjugate are the cross products of row pairs
+        let cols = [cross(b, c), cross(c, a), cross(a, b)];
+        let linear = std::array::from_fn(|i| std::array::from_fn(|j| cols[j][i] / det));
+        let inverse = Self { linear, translation: [0.0; 3] };
+        Ok(Self { translation: inverse.apply_vector(self.translation).map(|x| -x), ..inverse })
+    }
+}
+
+/// A shared mesh, placed by `transform`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Instance {
+    pub mesh: Arc<TriMesh>,
+    pub transform: Affine3,
+}
+
+impl Instance {
+    pub fn new(mesh: Arc<TriMesh>, transform: Affine3) -> Self {
+        Self { mesh, transform }
+    }
+
+    /// Whether the transform mirrors, so faces are rewound when placed
+    #[inline]
+    pub fn mirrored(&self) -> bool {
+        self.transform.determinant() < 0.0
+    }
+
+    #[inline]
+    pub fn faces(&self) -> usize {
+        self.mesh.faces().len()
+    }
+
+    /// Placed vertex positions of face `i`
+    #[inline]
+    pub fn triangle(&self, i: usize) -> [[f64; 3]; 3] {
+        let [a, b, c] = self.mesh.triangle(i).map(|v| self.transform.apply(v));
+        if self.mirrored() {
+            [a, c, b]
+        } else {
+            [a, b, c]
+        }
+    }
+
+    /// Placed vertex positions of every face, in order
+    pub fn triangles(&self) -> impl ExactSizeIterator<Item = [[f64; 3]; 3]> + Clone + '_ {
+        (0..self.faces()).map(|i| self.triangle(i))
+    }
+
+    /// A mesh of its own with the transform applied, for code that needs
+    /// one; attributes are not carried over
+    pub fn to_mesh(&self) -> TriMesh {
+        let vertices = self.mesh.vertices().iter().map(|&v| self.transform.apply(v)).collect();
+        let faces = if self.mirrored() {
+            self.mesh.faces().iter().map(|&[a, b, c]| [a, c, b]).collect()
+        } else {
+            self.mesh.faces().to_vec()
+        };
+        TriMesh::new(vertices, faces).expect("Faces only reference the mesh's own vertices")
+    }
+
+    /// Whether any placed face crosses the segment from `from` to `to`,
+    /// excluding [crate::bvh::T_EPS] at either end, by `bvh` built on the
+    /// untransformed mesh. A singular transform flattens the mesh, which
+    /// then blocks nothing.
+    pub fn occluded(&self, bvh: &Bvh, from: [f64; 3], to: [f64; 3]) -> bool {
+        self.transform.inverse().is_ok_and(|inv| bvh.occluded(&self.mesh, inv.apply(from), inv.apply(to)))
+    }
+}
+
+/// Total solid angle subtended by every instance at each origin, as
+/// [crate::multi_origin::solid_angles_multi_origin] for one mesh
+pub fn solid_angles(instances: &[Instance], origins: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if origins.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Parallelize over origins, never over faces, so sums stay deterministic
+    let faces: usize = instances.iter().map(Instance::faces).sum();
+    let triangles = || instances.iter().flat_map(Instance::triangles);
+    if origins.len().saturating_mul(faces) < par_threshold() {
+        origins
+            .chunks(BLOCK)
+            .zip(out.chunks_mut(BLOCK))
+            .for_each(|(o, s)| solid_angles_block(triangles(), o, s));
+    } else {
+        (origins.par_chunks(BLOCK), out.par_chunks_mut(BLOCK))
+            .into_par_iter()
+            .for_each(|(o, s)| solid_angles_block(triangles(), o, s));
+    }
+
+    Ok(())
+}
+
+/// Whether any instance blocks the segment from `from` to `to`, with
+/// `bvhs[i]` built on the mesh of `instances[i]`; instances sharing a
+/// mesh can share its [Bvh] as well
+pub fn occluded(instances: &[Instance], bvhs: &[&Bvh], from: [f64; 3], to: [f64; 3]) -> Result<bool, &'static str> {
+    // Check bounds
+    if bvhs.len() != instances.len() || instances.iter().zip(bvhs).any(|(i, b)| i.faces() != b.faces()) {
+        return Err("Dimension mismatch");
+    }
+    Ok(instances.iter().zip(bvhs).any(|(i, b)| i.occluded(b, from, to)))
+}
+
+/// View factor matrix between the faces of every instance, as
+/// [crate::thermal::view_factors], numbering faces through the instances
+/// in order
+pub fn view_factors(instances: &[Instance], out: &mut [f64]) -> Result<(), &'static str> {
+    // First face of each instance, and past the last
+    let mut starts = Vec::with_capacity(instances.len() + 1);
+    starts.push(0);
+    for i in instances {
+        starts.push(starts[starts.len() - 1] + i.faces());
+    }
+    let n = starts[instances.len()];
+    view_factors_with(
+        n,
+        |f| {
+            let i = starts.partition_point(|&s| s <= f) - 1;
+            instances[i].triangle(f - starts[i])
+        },
+        out,
+    )
+}
@@ -0,0 +1,194 @@
+//! Interval arithmetic variant with guaranteed enclosures.
+//!
+//! Rust has no stable control over the FPU rounding mode, so directed
+//! rounding is emulated: every operation is computed round-to-nearest
+//! (error at most half an ulp) and then widened outward by one ulp, which
+//! always contains the exact result. Enclosures come out a few ulps wide.
+//!
+//! The one transcendental, `atan2`, is taken from `libm` at the corners
+//! of the (triple, denominator) box and widened by two ulps. That assumes
+//! libm's fdlibm-derived atan2 is within one ulp, which is the enclosure's
+//! only assumption beyond IEEE 754 arithmetic.
+
+use crate::dd::{solid_angle_tetrahedron_scalar_dd, DoubleDouble};
+use std::ops::{Add, Mul, Sub};
+
+/// Closed interval `[lo, hi]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    #[inline]
+    pub const fn new(lo: f64, hi: f64) -> Self {
+        Self { lo, hi }
+    }
+
+    #[inline]
+    pub const fn point(x: f64) -> Self {
+        Self::new(x, x)
+    }
+
+    /// Interval around a round-to-nearest result
+    #[inline]
+    fn rounded(lo: f64, hi: f64) -> Self {
+        Self::new(lo.next_down(), hi.next_up())
+    }
+
+    #[inline]
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    #[inline]
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    #[inline]
+    pub fn mid(&self) -> f64 {
+        0.5 * self.lo + 0.5 * self.hi
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> Self {
+        Self::rounded(self.lo.max(0.0).sqrt(), self.hi.sqrt())
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::rounded(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::rounded(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let p = [self.lo * rhs.lo, self.lo * rhs.hi, self.hi * rhs.lo, self.hi * rhs.hi];
+        Self::rounded(p.into_iter().fold(f64::INFINITY, f64::min), p.into_iter().fold(f64::NEG_INFINITY, f64::max))
+    }
+}
+
+type Vec3 = [Interval; 3];
+
+#[inline]
+fn sub(a: [f64; 3], b: [f64; 3]) -> Vec3 {
+    [0, 1, 2].map(|i| Interval::point(a[i]) - Interval::point(b[i]))
+}
+
+#[inline]
+fn dot(u: Vec3, v: Vec3) -> Interval {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+#[inline]
+fn cross(u: Vec3, v: Vec3) -> Vec3 {
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+/// Enclosure of `atan2(y, x)` over a box, or `None` if the box touches
+/// the branch cut on the negative x axis (including the origin), where the
+/// angle jumps between `-π` and `π`.
+#[inline]
+pub fn atan2(y: Interval, x: Interval) -> Option<Interval> {
+    if y.lo <= 0.0 && 0.0 <= y.hi && x.lo <= 0.0 {
+        return None;
+    }
+
+    // Away from the cut, the box sees an arc of directions whose ends are corners
+    let corners = [(y.lo, x.lo), (y.lo, x.hi), (y.hi, x.lo), (y.hi, x.hi)].map(|(y, x)| libm::atan2(y, x));
+    let lo = corners.into_iter().fold(f64::INFINITY, f64::min);
+    let hi = corners.into_iter().fold(f64::NEG_INFINITY, f64::max);
+    Some(Interval::new(lo.next_down().next_down(), hi.next_up().next_up()))
+}
+
+/// Guaranteed enclosure of the exact solid angle of a tetrahedron, seen
+/// from its first vertex, or `None` if it reaches the atan2 branch point
+/// (flat tetrahedra seen from behind, where the angle is near `±2π`).
+pub fn solid_angle_tetrahedron_scalar_interval(
+    v0: [f64; 3],
+    v1: [f64; 3],
+    v2: [f64; 3],
+    v3: [f64; 3],
+) -> Option<Interval> {
+    // Coincident vertices are exactly degenerate, as in the f64 kernel
+    if v1 == v0 || v2 == v0 || v3 == v0 {
+        return Some(Interval::point(0.0));
+    }
+
+    // Vertex vectors
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0)); // (m)
+    let (la, lb, lc) = (dot(a, a).sqrt(), dot(b, b).sqrt(), dot(c, c).sqrt()); // (m)
+    let abc = la * lb * lc; // (m^3)
+
+    // Solid angle
+    let triple = dot(a, cross(b, c)); // (m^3)
+    let denom = abc + dot(a, b) * lc + dot(a, c) * lb + dot(b, c) * la; // (m^3)
+    let angle = atan2(triple, denom)?; // (rad)
+    Some(Interval::new(2.0 * angle.lo, 2.0 * angle.hi)) // Exact scaling
+}
+
+/// Result of [solid_angle_tetrahedron_scalar_filtered]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filtered {
+    /// Guaranteed enclosure of the exact solid angle
+    Enclosure(Interval),
+    /// Double-double value where the enclosure reached the branch point.
+    /// Accurate, but not certified.
+    Refined(DoubleDouble),
+}
+
+/// Filtered solid angle: the cheap interval enclosure when it exists,
+/// falling back to double-double only near the atan2 branch point.
+pub fn solid_angle_tetrahedron_scalar_filtered(
+    v0: [f64; 3],
+    v1: [f64; 3],
+    v2: [f64; 3],
+    v3: [f64; 3],
+) -> Filtered {
+    match solid_angle_tetrahedron_scalar_interval(v0, v1, v2, v3) {
+        Some(enclosure) => Filtered::Enclosure(enclosure),
+        None => Filtered::Refined(solid_angle_tetrahedron_scalar_dd(v0, v1, v2, v3)),
+    }
+}
+
+/// Vector variant of [solid_angle_tetrahedron_scalar_filtered]
+pub fn solid_angle_tetrahedron_filtered(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [Filtered],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tet = tetrahedra[i];
+        out[i] = solid_angle_tetrahedron_scalar_filtered(tet[0], tet[1], tet[2], tet[3]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Irradiance at points on surfaces from a set of [Source]s, with terrain
+//! or buildings in the way: the quantity solar and thermal models start
+//! from.
+//!
+//! Each source's unoccluded irradiance on the plane is exact
+//! ([Source::irradiance]); occlusion scales it by the share of the
+//! source's cosine-weighted radiance that reaches the point past the
+//! mesh. So an unblocked source carries no sampling error at all, and a
+//! partly blocked one only the error of that share.
+//!
+//! The share is estimated from two sets of directions, the source's own
+//! samples and cosine-weighted ones about the plane's normal, combined by
+//! Veach's balance heuristic (multiple importance sampling). The source's
+//! samples alone do for a small disk, but a sky sampled about the zenith
+//! weights directions near the horizon by `n·ω / cos θ`, unbounded on a
+//! tilted plane; with both, every weight is at most `L π`. Directions sit
+//! at the centers of a fixed grid of uniforms, the same for every point,
+//! so neighbouring points get smoothly varying results rather than
+//! independent noise.
+
+use crate::bvh::Bvh;
+use crate::mesh::TriMesh;
+use crate::par::{chunk_len, par_threshold};
+use crate::sampling::frame;
+use crate::sources::Source;
+use crate::vec3::{dot, norm};
+use rayon::prelude::*;
+use std::f64::consts::{PI, TAU};
+
+/// Irradiance on the plane at `point` with unit `normal`, from the side
+/// it faces: the sum over `sources` of each one's irradiance, scaled by
+/// the share of its light over about `samples` directions that
+/// `occluder` (a mesh and a BVH built from it) doesn't block. Rays leave
+/// `point` itself, so a point on the mesh should be lifted off it
+/// slightly.
+pub fn irradiance(point: [f64; 3], normal: [f64; 3], sources: &[&(dyn Source + Sync)], occluder: Option<(&TriMesh, &Bvh)>, samples: usize) -> f64 {
+    // Half the samples from each strategy
+    let k = (((samples as f64) / 2.0).sqrt().ceil() as usize).max(1);
+    let grid = (0..k * k).map(|i| [((i / k) as f64 + 0.5) / k as f64, ((i % k) as f64 + 0.5) / k as f64]);
+    let [s, t, n] = frame(normal);
+
+    let mut total = 0.0;
+    for source in sources {
+        let exact = source.irradiance(normal);
+        let Some((mesh, bvh)) = occluder.filter(|_| exact > 0.0) else {
+            total += exact;
+            continue;
+        };
+
+        // Long enough to leave the mesh's box from anywhere in it
+        let bounds = bvh.bounds();
+        let far: [f64; 3] = std::array::from_fn(|i| (bounds.lo[i] - point[i]).abs().max((bounds.hi[i] - point[i]).abs()));
+        let reach = 1.0 + norm(far);
+
+        let (mut all, mut open) = (0.0, 0.0);
+        let from_source = grid.clone().map(|u| source.sample(u));
+        let from_normal = grid.clone().map(|u| {
+            // Malley's method about the normal
+            let r = u[0].sqrt();
+            let (sin_phi, cos_phi) = (TAU * u[1]).sin_cos();
+            let z = (1.0 - u[0]).sqrt();
+            std::array::from_fn(|j| r * (cos_phi * s[j] + sin_phi * t[j]) + z * n[j])
+        });
+        for d in from_source.chain(from_normal) {
+            let (cos, radiance) = (dot(normal, d), source.radiance(d));
+            if cos <= 0.0 || radiance == 0.0 {
+                continue;
+            }
+            let weight = radiance * cos / (source.pdf(d) + cos / PI);
+            all += weight;
+            if !bvh.occluded(mesh, point, std::array::from_fn(|j| point[j] + reach * d[j])) {
+                open += weight;
+            }
+        }
+        if all > 0.0 {
+            total += exact * open / all;
+        }
+    }
+    total
+}
+
+/// [irradiance] at each of `points` with its unit normal in `normals`,
+/// into `out`, in parallel over points
+pub fn irradiance_batch(
+    points: &[[f64; 3]],
+    normals: &[[f64; 3]],
+    sources: &[&(dyn Source + Sync)],
+    occluder: Option<(&TriMesh, &Bvh)>,
+    samples: usize,
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != normals.len() || points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+    // Check the BVH is for this mesh, as far as can be told
+    if occluder.is_some_and(|(mesh, bvh)| bvh.faces() != mesh.faces().len()) {
+        return Err("BVH built for another mesh");
+    }
+
+    let at = |i: usize| irradiance(points[i], normals[i], sources, occluder, samples);
+
+    // Few rays are faster without the thread pool
+    let rays = if occluder.is_some() { samples.max(1) * sources.len() } else { 1 };
+    if points.len() * rays < par_threshold() {
+        out.iter_mut().enumerate().for_each(|(i, e)| *e = at(i));
+    } else {
+        let chunk = chunk_len(out.len());
+        out.par_chunks_mut(chunk).enumerate().for_each(|(c, values)| {
+            values.iter_mut().enumerate().for_each(|(i, e)| *e = at(c * chunk + i));
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! The shared modules as one library crate, for the talk demos in
+//! `examples/`: they call the kernels the rest of the scripts include,
+//! rather than copies, so the demos can't drift from them.
+//!
+//! The scripts alongside still include these files by `#[path]`, each
+//! as its own crate with its own manifest, so every module must keep
+//! compiling in both. The features here are the ones the modules test.
+//! `alloc.rs` is left out, with its allocator features: the global
+//! allocator is the binary's choice, not a library's.
+
+pub mod aligned_vec;
+pub mod angles;
+pub mod approx;
+pub mod arena;
+pub mod asm_export;
+pub mod astro;
+pub mod attributes;
+pub mod autotune;
+pub mod batch;
+#[cfg(feature = "serve")]
+pub mod batch_queue;
+pub mod bounds;
+pub mod bvh;
+pub mod bytes;
+pub mod cache;
+pub mod capabilities;
+pub mod clip;
+pub mod closed_form;
+pub mod cluster;
+pub mod components;
+pub mod condition;
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod const_eval;
+pub mod dd;
+pub mod directions;
+pub mod dispatch;
+#[cfg(feature = "mpi")]
+pub mod distributed;
+pub mod ephemeris;
+pub mod fixed;
+pub mod gltf;
+pub mod horizon;
+pub mod incremental;
+pub mod inputs;
+pub mod instance;
+pub mod interval;
+pub mod irradiance;
+pub mod math;
+pub mod mesh;
+pub mod mesh_fixed;
+pub mod mesh_formats;
+pub mod mesh_io;
+pub mod mesh_metrics;
+pub mod mixed;
+pub mod multi_origin;
+pub mod neon;
+pub mod npy;
+pub mod par;
+pub mod partition;
+pub mod planar;
+pub mod plot;
+pub mod predicates;
+pub mod primitives;
+pub mod progress;
+pub mod quantity;
+pub mod raster;
+pub mod rvv;
+pub mod sampling;
+pub mod scene;
+pub mod sdf;
+#[cfg(feature = "serve")]
+pub mod service;
+pub mod shm;
+pub mod sketch;
+pub mod sources;
+pub mod spherical;
+pub mod stats;
+pub mod step;
+pub mod sum;
+pub mod tetrahedron;
+pub mod thermal;
+pub mod topology;
+pub mod units;
+pub mod vec3;
+pub mod viewer;
+pub mod visibility;
+pub mod winding;
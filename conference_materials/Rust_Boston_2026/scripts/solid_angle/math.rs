@@ -0,0 +1,349 @@
+//! Backend for the transcendental in the FMA kernel and its SIMD paths,
+//! chosen at compile time:
+//!
+//! | Feature     | `atan2`                      | Max error (measured)    | Inlines, vectorizes |
+//! |-------------|------------------------------|-------------------------|---------------------|
+//! | (default)   | the `libm` crate, from fdlibm | 1.5 ulp                 | no, a call          |
+//! | `math-std`  | `f64::atan2`, the C library's | 0.52 ulp with glibc     | no, a call          |
+//! | `math-poly` | [atan2_poly]                 | 1.3 ulp                 | yes                 |
+//!
+//! Errors are over random arguments of every sign and ratio, against the
+//! double-double `atan2`; see `math_example.rs`. The polynomial is what
+//! pays off: with it, the AVX2+FMA dispatch path runs at 16 ns per
+//! tetrahedron instead of 34, since the kernel loop no longer stops at a
+//! call per element. Called on its own in a portable x86_64 build it is
+//! the slowest of the three, 100 ns against 40, since each of its 21
+//! `mul_add`s is a call to a software `fma` there.
+//!
+//! For code that vectorizes by hand, [atan2_f64x2] (NEON), [atan2_f64x4]
+//! (AVX2+FMA) and [atan2_f64x8] (AVX-512F) are the polynomial on whole
+//! registers, and [atan2_poly_slice] runs the widest of them the CPU has
+//! over slices. They repeat [atan2_poly]'s operations lane for lane, FMA
+//! for FMA, so every lane is bit-identical to it.
+//!
+//! If both features are enabled, `math-poly` wins, since Cargo unifies
+//! features across a build and one crate asking for it shouldn't be
+//! overridden. Every path of the kernel goes through [atan2], or under
+//! `math-poly` its vector forms, so [crate::neon] and [crate::rvv] stay
+//! bit-identical to [crate::tetrahedron] under any backend, but results
+//! differ from one backend to the next; golden files and
+//! `soft_float_example.rs` assume the default. The other kernels (fixed-point, condition,
+//! interval, double-double) keep `libm`, since their error analyses are
+//! built on it.
+//!
+//! Where multiply-adds are unfused ([crate::vec3::FUSED]), `atan2` is
+//! `libm`'s whatever the features: the polynomial leans on FMA for its
+//! accuracy, and software FMA would make it the slowest choice anyway.
+
+#[cfg(target_arch = "aarch64")]
+use std::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Four-quadrant arctangent of `y / x` from the configured backend
+#[inline]
+#[allow(unexpected_cfgs)] // Each script declares only the features it uses
+pub fn atan2(y: f64, x: f64) -> f64 {
+    if !crate::vec3::FUSED {
+        libm::atan2(y, x)
+    } else if cfg!(feature = "math-poly") {
+        atan2_poly(y, x)
+    } else if cfg!(feature = "math-std") {
+        y.atan2(x)
+    } else {
+        libm::atan2(y, x)
+    }
+}
+
+/// Name of the configured backend, for reports
+#[allow(unexpected_cfgs)]
+pub fn backend() -> &'static str {
+    if !crate::vec3::FUSED {
+        "libm"
+    } else if cfg!(feature = "math-poly") {
+        "poly"
+    } else if cfg!(feature = "math-std") {
+        "std"
+    } else {
+        "libm"
+    }
+}
+
+/// Coefficients of `atan(r) = r + r³ P(r²)` on `[0, 1]`, highest first,
+/// from SLEEF's 3.5-ulp `atan2` (`atan2k` in `sleefdp.c`); the rest of
+/// that bound is its unfused Horner and single-double fold, not these
+const ATAN: [f64; 19] = [
+    -1.887960084630735e-05,
+    0.00020985007664581698,
+    -0.0011061183148667248,
+    0.003700267441887131,
+    -0.008898961958876555,
+    0.016599329773529202,
+    -0.025451762493231264,
+    0.03378525800013531,
+    -0.04076291912768365,
+    0.04666671500778406,
+    -0.052367485230348246,
+    0.05876663929266736,
+    -0.06665735793610805,
+    0.07692195383117696,
+    -0.09090899500824501,
+    0.11111110564826142,
+    -0.1428571426677133,
+    0.19999999999659127,
+    -0.3333333333333111,
+];
+
+/// `c + c_lo - (h + l)` as an unevaluated sum, by two-sum
+#[inline(always)]
+fn reflect(c: f64, c_lo: f64, h: f64, l: f64) -> (f64, f64) {
+    let d = c - h;
+    let bb = d - c;
+    let e = (c - (d - bb)) + (-h - bb);
+    (d, e + (c_lo - l))
+}
+
+/// `π/2 - FRAC_PI_2` and `π - PI`, rounded
+const FRAC_PI_2_LO: f64 = 6.123233995736766e-17;
+const PI_LO: f64 = 1.2246467991473532e-16;
+
+/// Polynomial `atan2` in branch-free form: reduce to `atan(r)` with
+/// `r = min(|x|, |y|) / max(|x|, |y|)` in `[0, 1]`, then fold back by
+/// quadrant. Horner's rule is fused, and the division's error and the
+/// fold are carried in double-double, for 1.3 ulp. IEEE special cases (signed zeros, infinities, NaN) match
+/// [f64::atan2].
+#[inline]
+pub fn atan2_poly(y: f64, x: f64) -> f64 {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let (ay, ax) = (y.abs(), x.abs());
+    let (lo, hi) = (ay.min(ax), ay.max(ax));
+    // 0/0 is atan2(±0, ±0)'s zero, and ∞/∞ the diagonal's one
+    let r = if hi == 0.0 {
+        0.0
+    } else if lo.is_infinite() {
+        1.0
+    } else {
+        lo / hi
+    };
+
+    // The division's rounding error, exact by FMA, carried into the sum;
+    // it is an ulp of the result where r crosses a power of two
+    let r_lo = (-r).mul_add(hi, lo) / hi;
+    let r_lo = if hi == 0.0 || hi.is_infinite() { 0.0 } else { r_lo };
+
+    let t = r * r;
+    let p = ATAN.iter().fold(0.0_f64, |p, &c| p.mul_add(t, c));
+    let s = (p * t).mul_add(r, r_lo);
+
+    // atan(r) as an unevaluated sum, folded by quadrant with one rounding
+    // at the end; π/2 and π are double-doubles too, or their own rounding
+    // is half an ulp
+    let (h, l) = (r + s, s - ((r + s) - r)); // |r| >= |s|
+    let (h, l) = if ay > ax { reflect(FRAC_PI_2, FRAC_PI_2_LO, h, l) } else { (h, l) };
+    let (h, l) = if x.is_sign_negative() { reflect(PI, PI_LO, h, l) } else { (h, l) };
+    let a = h + l;
+    let a = if x.is_nan() || y.is_nan() { f64::NAN } else { a };
+    a.copysign(y)
+}
+
+/// [atan2_poly] on four lanes, operation for operation, so every lane is
+/// bit-identical to it
+///
+/// # Safety
+/// The CPU must support AVX2 and FMA.
+#[cfg(target_arch = "x86_64")]
+#[inline]
+#[target_feature(enable = "avx2,fma")]
+pub fn atan2_f64x4(y: __m256d, x: __m256d) -> __m256d {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    let splat = _mm256_set1_pd;
+    let sign = splat(-0.0);
+
+    let (ay, ax) = (_mm256_andnot_pd(sign, y), _mm256_andnot_pd(sign, x));
+    let (lo, hi) = (_mm256_min_pd(ay, ax), _mm256_max_pd(ay, ax));
+    let zero = _mm256_cmp_pd::<_CMP_EQ_OQ>(hi, splat(0.0));
+    let inf = _mm256_cmp_pd::<_CMP_EQ_OQ>(lo, splat(f64::INFINITY));
+    let r = _mm256_div_pd(lo, hi);
+    let r = _mm256_blendv_pd(_mm256_blendv_pd(r, splat(1.0), inf), splat(0.0), zero);
+
+    let r_lo = _mm256_div_pd(_mm256_fnmadd_pd(r, hi, lo), hi);
+    let r_lo = _mm256_andnot_pd(_mm256_or_pd(zero, _mm256_cmp_pd::<_CMP_EQ_OQ>(hi, splat(f64::INFINITY))), r_lo);
+
+    let t = _mm256_mul_pd(r, r);
+    let p = ATAN.iter().fold(splat(0.0), |p, &c| _mm256_fmadd_pd(p, t, splat(c)));
+    let s = _mm256_fmadd_pd(_mm256_mul_pd(p, t), r, r_lo);
+
+    let h = _mm256_add_pd(r, s);
+    let l = _mm256_sub_pd(s, _mm256_sub_pd(h, r));
+    let reflect = |c: f64, c_lo: f64, h: __m256d, l: __m256d| {
+        let d = _mm256_sub_pd(splat(c), h);
+        let bb = _mm256_sub_pd(d, splat(c));
+        let e = _mm256_add_pd(_mm256_sub_pd(splat(c), _mm256_sub_pd(d, bb)), _mm256_sub_pd(_mm256_xor_pd(h, sign), bb));
+        (d, _mm256_add_pd(e, _mm256_sub_pd(splat(c_lo), l)))
+    };
+    let steep = _mm256_cmp_pd::<_CMP_GT_OQ>(ay, ax);
+    let (d, e) = reflect(FRAC_PI_2, FRAC_PI_2_LO, h, l);
+    let (h, l) = (_mm256_blendv_pd(h, d, steep), _mm256_blendv_pd(l, e, steep));
+    let (d, e) = reflect(PI, PI_LO, h, l);
+    let (h, l) = (_mm256_blendv_pd(h, d, x), _mm256_blendv_pd(l, e, x)); // By x's sign bit
+    let a = _mm256_add_pd(h, l);
+    let a = _mm256_blendv_pd(a, splat(f64::NAN), _mm256_cmp_pd::<_CMP_UNORD_Q>(x, y));
+    _mm256_or_pd(_mm256_andnot_pd(sign, a), _mm256_and_pd(sign, y))
+}
+
+/// [atan2_poly] on eight lanes, bit-identical to it as [atan2_f64x4] is
+///
+/// # Safety
+/// The CPU must support AVX-512F.
+#[cfg(target_arch = "x86_64")]
+#[inline]
+#[target_feature(enable = "avx512f")]
+pub fn atan2_f64x8(y: __m512d, x: __m512d) -> __m512d {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    let splat = _mm512_set1_pd;
+    // Sign bit twiddling in the integer domain; the `_pd` forms need AVX-512DQ
+    let bits = _mm512_castpd_si512;
+    let sign = _mm512_set1_epi64(i64::MIN);
+    let abs = |v: __m512d| _mm512_castsi512_pd(_mm512_andnot_si512(sign, bits(v)));
+    let neg = |v: __m512d| _mm512_castsi512_pd(_mm512_xor_si512(sign, bits(v)));
+
+    let (ay, ax) = (abs(y), abs(x));
+    let (lo, hi) = (_mm512_min_pd(ay, ax), _mm512_max_pd(ay, ax));
+    let zero = _mm512_cmp_pd_mask::<_CMP_EQ_OQ>(hi, splat(0.0));
+    let inf = _mm512_cmp_pd_mask::<_CMP_EQ_OQ>(lo, splat(f64::INFINITY));
+    let r = _mm512_div_pd(lo, hi);
+    let r = _mm512_mask_blend_pd(zero, _mm512_mask_blend_pd(inf, r, splat(1.0)), splat(0.0));
+
+    let r_lo = _mm512_div_pd(_mm512_fnmadd_pd(r, hi, lo), hi);
+    let r_lo = _mm512_mask_blend_pd(zero | _mm512_cmp_pd_mask::<_CMP_EQ_OQ>(hi, splat(f64::INFINITY)), r_lo, splat(0.0));
+
+    let t = _mm512_mul_pd(r, r);
+    let p = ATAN.iter().fold(splat(0.0), |p, &c| _mm512_fmadd_pd(p, t, splat(c)));
+    let s = _mm512_fmadd_pd(_mm512_mul_pd(p, t), r, r_lo);
+
+    let h = _mm512_add_pd(r, s);
+    let l = _mm512_sub_pd(s, _mm512_sub_pd(h, r));
+    let reflect = |c: f64, c_lo: f64, h: __m512d, l: __m512d| {
+        let d = _mm512_sub_pd(splat(c), h);
+        let bb = _mm512_sub_pd(d, splat(c));
+        let e = _mm512_add_pd(_mm512_sub_pd(splat(c), _mm512_sub_pd(d, bb)), _mm512_sub_pd(neg(h), bb));
+        (d, _mm512_add_pd(e, _mm512_sub_pd(splat(c_lo), l)))
+    };
+    let steep = _mm512_cmp_pd_mask::<_CMP_GT_OQ>(ay, ax);
+    let (d, e) = reflect(FRAC_PI_2, FRAC_PI_2_LO, h, l);
+    let (h, l) = (_mm512_mask_blend_pd(steep, h, d), _mm512_mask_blend_pd(steep, l, e));
+    let (d, e) = reflect(PI, PI_LO, h, l);
+    let negative = _mm512_test_epi64_mask(bits(x), sign);
+    let (h, l) = (_mm512_mask_blend_pd(negative, h, d), _mm512_mask_blend_pd(negative, l, e));
+    let a = _mm512_add_pd(h, l);
+    let a = _mm512_mask_blend_pd(_mm512_cmp_pd_mask::<_CMP_UNORD_Q>(x, y), a, splat(f64::NAN));
+    _mm512_castsi512_pd(_mm512_or_si512(bits(abs(a)), _mm512_and_si512(sign, bits(y))))
+}
+
+/// [atan2_poly] on two lanes, bit-identical to it as [atan2_f64x4] is
+#[cfg(target_arch = "aarch64")]
+#[inline]
+#[target_feature(enable = "neon")]
+pub fn atan2_f64x2(y: float64x2_t, x: float64x2_t) -> float64x2_t {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    let splat = vdupq_n_f64;
+
+    let (ay, ax) = (vabsq_f64(y), vabsq_f64(x));
+    let (lo, hi) = (vminq_f64(ay, ax), vmaxq_f64(ay, ax));
+    let zero = vceqq_f64(hi, splat(0.0));
+    let inf = vceqq_f64(lo, splat(f64::INFINITY));
+    let r = vdivq_f64(lo, hi);
+    let r = vbslq_f64(zero, splat(0.0), vbslq_f64(inf, splat(1.0), r));
+
+    let r_lo = vdivq_f64(vfmsq_f64(lo, r, hi), hi);
+    let r_lo = vbslq_f64(vorrq_u64(zero, vceqq_f64(hi, splat(f64::INFINITY))), splat(0.0), r_lo);
+
+    let t = vmulq_f64(r, r);
+    let p = ATAN.iter().fold(splat(0.0), |p, &c| vfmaq_f64(splat(c), p, t));
+    let s = vfmaq_f64(r_lo, vmulq_f64(p, t), r);
+
+    let h = vaddq_f64(r, s);
+    let l = vsubq_f64(s, vsubq_f64(h, r));
+    let reflect = |c: f64, c_lo: f64, h: float64x2_t, l: float64x2_t| {
+        let d = vsubq_f64(splat(c), h);
+        let bb = vsubq_f64(d, splat(c));
+        let e = vaddq_f64(vsubq_f64(splat(c), vsubq_f64(d, bb)), vsubq_f64(vnegq_f64(h), bb));
+        (d, vaddq_f64(e, vsubq_f64(splat(c_lo), l)))
+    };
+    let steep = vcgtq_f64(ay, ax);
+    let (d, e) = reflect(FRAC_PI_2, FRAC_PI_2_LO, h, l);
+    let (h, l) = (vbslq_f64(steep, d, h), vbslq_f64(steep, e, l));
+    let (d, e) = reflect(PI, PI_LO, h, l);
+    let negative = vreinterpretq_u64_s64(vshrq_n_s64::<63>(vreinterpretq_s64_f64(x)));
+    let (h, l) = (vbslq_f64(negative, d, h), vbslq_f64(negative, e, l));
+    let a = vaddq_f64(h, l);
+    let ordered = vandq_u64(vceqq_f64(x, x), vceqq_f64(y, y));
+    let a = vbslq_f64(ordered, a, splat(f64::NAN));
+    vbslq_f64(vdupq_n_u64(1 << 63), y, a)
+}
+
+/// `out[i] = atan2_poly(y[i], x[i])`, in the widest vectors this CPU has
+/// (AVX-512, AVX2+FMA, NEON), so bit-identical to [atan2_poly] on any
+pub fn atan2_poly_slice(y: &[f64], x: &[f64], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if y.len() != out.len() || x.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    let done = if is_x86_feature_detected!("avx512f") {
+        // SAFETY: AVX-512F was just detected
+        unsafe { atan2_slice_x8(y, x, out) }
+    } else if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        // SAFETY: AVX2 and FMA were just detected
+        unsafe { atan2_slice_x4(y, x, out) }
+    } else {
+        0
+    };
+    #[cfg(target_arch = "aarch64")]
+    // SAFETY: NEON is in the baseline of every AArch64 target
+    let done = unsafe { atan2_slice_x2(y, x, out) };
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let done = 0;
+
+    for ((o, &y), &x) in out[done..].iter_mut().zip(&y[done..]).zip(&x[done..]) {
+        *o = atan2_poly(y, x);
+    }
+    Ok(())
+}
+
+/// Whole vectors of [atan2_poly_slice], returning how many elements were
+/// done. The slices have equal length.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn atan2_slice_x4(y: &[f64], x: &[f64], out: &mut [f64]) -> usize {
+    let (out_chunks, _) = out.as_chunks_mut::<4>();
+    for ((o, y), x) in out_chunks.iter_mut().zip(y.as_chunks::<4>().0).zip(x.as_chunks::<4>().0) {
+        // SAFETY: Each chunk is four f64s; unaligned loads and stores
+        unsafe { _mm256_storeu_pd(o.as_mut_ptr(), atan2_f64x4(_mm256_loadu_pd(y.as_ptr()), _mm256_loadu_pd(x.as_ptr()))) };
+    }
+    4 * out_chunks.len()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+fn atan2_slice_x8(y: &[f64], x: &[f64], out: &mut [f64]) -> usize {
+    let (out_chunks, _) = out.as_chunks_mut::<8>();
+    for ((o, y), x) in out_chunks.iter_mut().zip(y.as_chunks::<8>().0).zip(x.as_chunks::<8>().0) {
+        // SAFETY: Each chunk is eight f64s; unaligned loads and stores
+        unsafe { _mm512_storeu_pd(o.as_mut_ptr(), atan2_f64x8(_mm512_loadu_pd(y.as_ptr()), _mm512_loadu_pd(x.as_ptr()))) };
+    }
+    8 * out_chunks.len()
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+fn atan2_slice_x2(y: &[f64], x: &[f64], out: &mut [f64]) -> usize {
+    let (out_chunks, _) = out.as_chunks_mut::<2>();
+    for ((o, y), x) in out_chunks.iter_mut().zip(y.as_chunks::<2>().0).zip(x.as_chunks::<2>().0) {
+        // SAFETY: Each chunk is two f64s, which NEON loads need no alignment for
+        unsafe { vst1q_f64(o.as_mut_ptr(), atan2_f64x2(vld1q_f64(y.as_ptr()), vld1q_f64(x.as_ptr()))) };
+    }
+    2 * out_chunks.len()
+}
@@ -0,0 +1,237 @@
+//! Indexed triangle and tetrahedron meshes, with named per-element
+//! attributes.
+
+use crate::attributes::AttributeMap;
+
+/// Triangle mesh with shared vertices.
+/// Closed meshes should wind faces counter-clockwise seen from outside,
+/// so that points inside subtend `+4π`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TriMeshData"))]
+pub struct TriMesh {
+    vertices: Vec<[f64; 3]>,
+    faces: Vec<[u32; 3]>,
+    vertex_attributes: AttributeMap<f64>,
+    face_attributes: AttributeMap<f64>,
+}
+
+impl TriMesh {
+    /// Mesh from vertices and faces, checking that faces only reference
+    /// existing vertices
+    pub fn new(vertices: Vec<[f64; 3]>, faces: Vec<[u32; 3]>) -> Result<Self, &'static str> {
+        check_indices(&vertices, &faces)?;
+        let vertex_attributes = AttributeMap::new(vertices.len());
+        let face_attributes = AttributeMap::new(faces.len());
+
+        Ok(Self { vertices, faces, vertex_attributes, face_attributes })
+    }
+
+    #[inline]
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices
+    }
+
+    /// Vertex positions to move in place; the faces stay as they are
+    #[inline]
+    pub fn vertices_mut(&mut self) -> &mut [[f64; 3]] {
+        &mut self.vertices
+    }
+
+    #[inline]
+    pub fn faces(&self) -> &[[u32; 3]] {
+        &self.faces
+    }
+
+    /// Vertex positions of face `i`
+    #[inline]
+    pub fn triangle(&self, i: usize) -> [[f64; 3]; 3] {
+        let [a, b, c] = self.faces[i];
+        [self.vertices[a as usize], self.vertices[b as usize], self.vertices[c as usize]]
+    }
+
+    /// Vertex positions of every face, in order
+    pub fn triangles(&self) -> impl ExactSizeIterator<Item = [[f64; 3]; 3]> + Clone + '_ {
+        (0..self.faces.len()).map(|i| self.triangle(i))
+    }
+
+    #[inline]
+    pub fn vertex_attributes(&self) -> &AttributeMap<f64> {
+        &self.vertex_attributes
+    }
+
+    #[inline]
+    pub fn vertex_attributes_mut(&mut self) -> &mut AttributeMap<f64> {
+        &mut self.vertex_attributes
+    }
+
+    #[inline]
+    pub fn face_attributes(&self) -> &AttributeMap<f64> {
+        &self.face_attributes
+    }
+
+    #[inline]
+    pub fn face_attributes_mut(&mut self) -> &mut AttributeMap<f64> {
+        &mut self.face_attributes
+    }
+
+    /// Standalone mesh of the given faces, in that order, with only the
+    /// vertices they use, renumbered in order of first use, and the
+    /// attributes carried over
+    pub fn submesh(&self, faces: &[u32]) -> Result<TriMesh, &'static str> {
+        if faces.iter().any(|&f| f as usize >= self.faces.len()) {
+            return Err("Face index out of range");
+        }
+
+        let mut local = vec![u32::MAX; self.vertices.len()];
+        let mut global_vertices = Vec::new();
+        let local_faces = faces
+            .iter()
+            .map(|&f| {
+                self.faces[f as usize].map(|v| {
+                    if local[v as usize] == u32::MAX {
+                        local[v as usize] = global_vertices.len() as u32;
+                        global_vertices.push(v);
+                    }
+                    local[v as usize]
+                })
+            })
+            .collect();
+        let vertices = global_vertices.iter().map(|&v| self.vertices[v as usize]).collect();
+        let mut sub = TriMesh::new(vertices, local_faces)?;
+
+        for (name, values) in self.vertex_attributes.iter() {
+            sub.vertex_attributes.insert(name, global_vertices.iter().map(|&v| values[v as usize]).collect())?;
+        }
+        for (name, values) in self.face_attributes.iter() {
+            sub.face_attributes.insert(name, faces.iter().map(|&f| values[f as usize]).collect())?;
+        }
+        Ok(sub)
+    }
+}
+
+/// Tetrahedron mesh with shared vertices.
+/// Cells should be positively oriented, `(v1 - v0) · ((v2 - v0) × (v3 - v0)) > 0`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TetMeshData"))]
+pub struct TetMesh {
+    vertices: Vec<[f64; 3]>,
+    cells: Vec<[u32; 4]>,
+    vertex_attributes: AttributeMap<f64>,
+    cell_attributes: AttributeMap<f64>,
+}
+
+impl TetMesh {
+    /// Mesh from vertices and cells, checking that cells only reference
+    /// existing vertices
+    pub fn new(vertices: Vec<[f64; 3]>, cells: Vec<[u32; 4]>) -> Result<Self, &'static str> {
+        check_indices(&vertices, &cells)?;
+        let vertex_attributes = AttributeMap::new(vertices.len());
+        let cell_attributes = AttributeMap::new(cells.len());
+
+        Ok(Self { vertices, cells, vertex_attributes, cell_attributes })
+    }
+
+    #[inline]
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[[u32; 4]] {
+        &self.cells
+    }
+
+    /// Vertex positions of cell `i`
+    #[inline]
+    pub fn tetrahedron(&self, i: usize) -> [[f64; 3]; 4] {
+        self.cells[i].map(|v| self.vertices[v as usize])
+    }
+
+    /// Vertex positions of every cell, in order
+    pub fn tetrahedra(&self) -> impl ExactSizeIterator<Item = [[f64; 3]; 4]> + Clone + '_ {
+        (0..self.cells.len()).map(|i| self.tetrahedron(i))
+    }
+
+    #[inline]
+    pub fn vertex_attributes(&self) -> &AttributeMap<f64> {
+        &self.vertex_attributes
+    }
+
+    #[inline]
+    pub fn vertex_attributes_mut(&mut self) -> &mut AttributeMap<f64> {
+        &mut self.vertex_attributes
+    }
+
+    #[inline]
+    pub fn cell_attributes(&self) -> &AttributeMap<f64> {
+        &self.cell_attributes
+    }
+
+    #[inline]
+    pub fn cell_attributes_mut(&mut self) -> &mut AttributeMap<f64> {
+        &mut self.cell_attributes
+    }
+}
+
+/// Check that `u32` indices can address every vertex and that elements
+/// only reference existing ones
+fn check_indices<const N: usize>(vertices: &[[f64; 3]], elements: &[[u32; N]]) -> Result<(), &'static str> {
+    if vertices.len() > u32::MAX as usize {
+        return Err("Too many vertices for u32 indices");
+    }
+    let n = vertices.len() as u32;
+    if elements.iter().flatten().any(|&i| i >= n) {
+        return Err("Element references a missing vertex");
+    }
+    Ok(())
+}
+
+/// Unchecked serialized form of [TriMesh]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TriMeshData {
+    vertices: Vec<[f64; 3]>,
+    faces: Vec<[u32; 3]>,
+    vertex_attributes: AttributeMap<f64>,
+    face_attributes: AttributeMap<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TriMeshData> for TriMesh {
+    type Error = &'static str;
+
+    fn try_from(data: TriMeshData) -> Result<Self, Self::Error> {
+        check_indices(&data.vertices, &data.faces)?;
+        if data.vertex_attributes.len() != data.vertices.len() || data.face_attributes.len() != data.faces.len() {
+            return Err("Dimension mismatch");
+        }
+        let TriMeshData { vertices, faces, vertex_attributes, face_attributes } = data;
+        Ok(Self { vertices, faces, vertex_attributes, face_attributes })
+    }
+}
+
+/// Unchecked serialized form of [TetMesh]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TetMeshData {
+    vertices: Vec<[f64; 3]>,
+    cells: Vec<[u32; 4]>,
+    vertex_attributes: AttributeMap<f64>,
+    cell_attributes: AttributeMap<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TetMeshData> for TetMesh {
+    type Error = &'static str;
+
+    fn try_from(data: TetMeshData) -> Result<Self, Self::Error> {
+        check_indices(&data.vertices, &data.cells)?;
+        if data.vertex_attributes.len() != data.vertices.len() || data.cell_attributes.len() != data.cells.len() {
+            return Err("Dimension mismatch");
+        }
+        let TetMeshData { vertices, cells, vertex_attributes, cell_attributes } = data;
+        Ok(Self { vertices, cells, vertex_attributes, cell_attributes })
+    }
+}
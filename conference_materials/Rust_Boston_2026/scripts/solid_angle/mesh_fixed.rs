@@ -0,0 +1,175 @@
+//! Triangle mesh of fixed capacity, stored inline, and kernels over it
+//! that never touch the heap: for the small meshes of flight software
+//! (a sensor's field-of-view pyramid, a few simple occluders), on
+//! hardware with no allocator at all.
+//!
+//! Capacities are const generics, so a [TriMeshFixed] lives on the stack
+//! or in a `static` and its size is known at link time; filling it past
+//! either capacity is an error rather than a reallocation. The kernels
+//! take slices in and out and sum in face order with [CompensatedSum], so
+//! results are bit-identical to [crate::multi_origin]'s on the same mesh.
+//! Nothing here needs more than `core`, and with [crate::vec3::FUSED] off
+//! neither do the kernels.
+//!
+//! With the `defmt` feature, meshes implement `defmt::Format`, to be
+//! logged over RTT without pulling in `core::fmt`. Errors are
+//! `&'static str`, which `defmt` formats as they are.
+//!
+//! Fixed capacity, that is; the fixed-point kernel is [crate::fixed].
+
+use crate::sum::CompensatedSum;
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use std::f64::consts::PI;
+
+/// Triangle mesh of at most `V` vertices and `F` faces, wound as
+/// [crate::mesh::TriMesh] is: counter-clockwise seen from outside
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriMeshFixed<const V: usize, const F: usize> {
+    vertices: [[f64; 3]; V],
+    faces: [[u32; 3]; F],
+    vertex_count: usize,
+    face_count: usize,
+}
+
+/// The vertices and faces in use, not the spare capacity
+#[cfg(feature = "defmt")]
+impl<const V: usize, const F: usize> defmt::Format for TriMeshFixed<V, F> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "TriMeshFixed vertices={} faces={}", self.vertices(), self.faces());
+    }
+}
+
+impl<const V: usize, const F: usize> Default for TriMeshFixed<V, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const V: usize, const F: usize> TriMeshFixed<V, F> {
+    /// Empty mesh
+    pub const fn new() -> Self {
+        Self { vertices: [[0.0; 3]; V], faces: [[0; 3]; F], vertex_count: 0, face_count: 0 }
+    }
+
+    /// Mesh filling its capacity exactly, checking that faces only
+    /// reference existing vertices; usable in a `const` or `static`
+    pub const fn from_arrays(vertices: [[f64; 3]; V], faces: [[u32; 3]; F]) -> Result<Self, &'static str> {
+        let mut i = 0;
+        while i < F {
+            let [a, b, c] = faces[i];
+            if a as usize >= V || b as usize >= V || c as usize >= V {
+                return Err("Element references a missing vertex");
+            }
+            i += 1;
+        }
+        Ok(Self { vertices, faces, vertex_count: V, face_count: F })
+    }
+
+    /// Mesh of up to the capacity in vertices and faces
+    pub fn from_slices(vertices: &[[f64; 3]], faces: &[[u32; 3]]) -> Result<Self, &'static str> {
+        let mut mesh = Self::new();
+        for &v in vertices {
+            mesh.push_vertex(v)?;
+        }
+        for &f in faces {
+            mesh.push_face(f)?;
+        }
+        Ok(mesh)
+    }
+
+    /// Add a vertex, returning its index
+    pub fn push_vertex(&mut self, vertex: [f64; 3]) -> Result<u32, &'static str> {
+        if self.vertex_count == V {
+            return Err("Vertex capacity exceeded");
+        }
+        self.vertices[self.vertex_count] = vertex;
+        self.vertex_count += 1;
+        Ok(self.vertex_count as u32 - 1)
+    }
+
+    /// Add a face of existing vertices, returning its index
+    pub fn push_face(&mut self, face: [u32; 3]) -> Result<u32, &'static str> {
+        if self.face_count == F {
+            return Err("Face capacity exceeded");
+        }
+        if face.iter().any(|&v| v as usize >= self.vertex_count) {
+            return Err("Element references a missing vertex");
+        }
+        self.faces[self.face_count] = face;
+        self.face_count += 1;
+        Ok(self.face_count as u32 - 1)
+    }
+
+    /// Drop every vertex and face, keeping the storage
+    pub fn clear(&mut self) {
+        self.vertex_count = 0;
+        self.face_count = 0;
+    }
+
+    #[inline]
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices[..self.vertex_count]
+    }
+
+    /// Vertex positions to move in place; the faces stay as they are
+    #[inline]
+    pub fn vertices_mut(&mut self) -> &mut [[f64; 3]] {
+        &mut self.vertices[..self.vertex_count]
+    }
+
+    #[inline]
+    pub fn faces(&self) -> &[[u32; 3]] {
+        &self.faces[..self.face_count]
+    }
+
+    /// Vertex positions of face `i`
+    #[inline]
+    pub fn triangle(&self, i: usize) -> [[f64; 3]; 3] {
+        self.faces()[i].map(|v| self.vertices[v as usize])
+    }
+
+    /// Vertex positions of every face, in order
+    pub fn triangles(&self) -> impl ExactSizeIterator<Item = [[f64; 3]; 3]> + Clone + '_ {
+        (0..self.face_count).map(|i| self.triangle(i))
+    }
+
+    /// Total solid angle subtended at `origin`: `+4π` inside a closed
+    /// outward-wound mesh and `0` outside
+    pub fn solid_angle(&self, origin: [f64; 3]) -> f64 {
+        let mut total = CompensatedSum::new();
+        for tri in self.triangles() {
+            total.add(solid_angle_tetrahedron_scalar(origin, tri[0], tri[1], tri[2]));
+        }
+        total.value()
+    }
+
+    /// Winding number of a closed mesh about `origin`: 1 inside, 0 outside
+    #[inline]
+    pub fn winding_number(&self, origin: [f64; 3]) -> f64 {
+        self.solid_angle(origin) / (4.0 * PI)
+    }
+
+    /// [TriMeshFixed::solid_angle] at each of `origins`, into `out`
+    pub fn solid_angles(&self, origins: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+        // Check bounds
+        if origins.len() != out.len() {
+            return Err("Dimension mismatch");
+        }
+        for (o, y) in origins.iter().zip(out.iter_mut()) {
+            *y = self.solid_angle(*o);
+        }
+        Ok(())
+    }
+
+    /// Solid angle each face subtends at `origin`, into `out`, one per face
+    pub fn face_solid_angles(&self, origin: [f64; 3], out: &mut [f64]) -> Result<(), &'static str> {
+        // Check bounds
+        if out.len() != self.face_count {
+            return Err("Dimension mismatch");
+        }
+        for (tri, y) in self.triangles().zip(out.iter_mut()) {
+            *y = solid_angle_tetrahedron_scalar(origin, tri[0], tri[1], tri[2]);
+        }
+        Ok(())
+    }
+}
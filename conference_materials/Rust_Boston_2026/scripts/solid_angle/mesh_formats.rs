@@ -0,0 +1,544 @@
+//! Triangle meshes from the files CAD, scanning and meshing tools write:
+//! STL (ASCII and binary), Wavefront OBJ, PLY (ASCII and binary of
+//! either byte order) and Gmsh MSH (ASCII, versions 2.2 and 4.1).
+//!
+//! STL stores each triangle's corners on their own, so corners are welded
+//! where their coordinates are equal to the bit (`-0.0` as `0.0`); the
+//! other formats index shared vertices already, and are kept as written.
+//! Polygons are split into fans about their first corner, which is right
+//! for the convex faces exporters write. An MSH file's triangles and
+//! quadrilaterals are read; one with only tetrahedra gives their boundary,
+//! wound outward. Normals, colors, texture coordinates and groups are
+//! ignored, and coordinates are in whatever unit the file is.
+//!
+//! Counts in headers are the file's, so nothing is allocated by them:
+//! arrays grow as elements are actually read.
+
+use crate::mesh::TriMesh;
+use crate::vec3::{cross, dot, sub};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// A file format, told by extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MeshFormat {
+    Stl,
+    Obj,
+    Ply,
+    Msh,
+}
+
+impl MeshFormat {
+    /// `.stl`, `.obj`, `.ply` or `.msh`, in any case
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "stl" => Some(Self::Stl),
+            "obj" => Some(Self::Obj),
+            "ply" => Some(Self::Ply),
+            "msh" => Some(Self::Msh),
+            _ => None,
+        }
+    }
+}
+
+/// Read a mesh file of a format told by its extension
+pub fn read_mesh(path: impl AsRef<Path>) -> io::Result<TriMesh> {
+    let path = path.as_ref();
+    let format = MeshFormat::from_path(path).ok_or_else(|| invalid("Unknown mesh file extension"))?;
+    let mut r = io::BufReader::new(std::fs::File::open(path)?);
+    match format {
+        MeshFormat::Stl => read_stl(&mut r),
+        MeshFormat::Obj => read_obj(&mut r),
+        MeshFormat::Ply => read_ply(&mut r),
+        MeshFormat::Msh => read_msh(&mut r),
+    }
+}
+
+/// Binary STL, or ASCII if it starts with `solid` and isn't sized as a
+/// binary one (some binary exporters start their header with `solid`
+/// too)
+pub fn read_stl<R: Read>(r: &mut R) -> io::Result<TriMesh> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    let binary_len = bytes.get(80..84).map(|n| 84 + 50 * u32::from_le_bytes(n.try_into().expect("4 bytes")) as u64);
+    if binary_len != Some(bytes.len() as u64) && bytes.trim_ascii_start().starts_with(b"solid") {
+        return read_stl_ascii(&bytes);
+    }
+    if binary_len.is_none() {
+        return Err(invalid("Truncated STL file"));
+    }
+    if binary_len != Some(bytes.len() as u64) {
+        return Err(invalid("STL file not of its triangle count"));
+    }
+
+    // Triangles of 50 bytes: normal, three corners, attribute count
+    let mut weld = Weld::default();
+    let corner = |t: &[u8], v: usize| std::array::from_fn(|k| f64::from(f32::from_le_bytes(t[12 * v + 4 * k..][..4].try_into().expect("4 bytes"))));
+    let faces = bytes[84..].chunks_exact(50).map(|t| [1, 2, 3].map(|v| weld.index(corner(t, v)))).collect();
+    TriMesh::new(weld.vertices, faces).map_err(invalid)
+}
+
+fn read_stl_ascii(bytes: &[u8]) -> io::Result<TriMesh> {
+    let text = std::str::from_utf8(bytes).map_err(|_| invalid("STL text not UTF-8"))?;
+    let mut words = text.split_ascii_whitespace();
+    let mut weld = Weld::default();
+    let (mut faces, mut facet) = (Vec::new(), Vec::new());
+    while let Some(word) = words.next() {
+        match word {
+            "vertex" => {
+                let mut p = [0.0; 3];
+                for x in &mut p {
+                    *x = words.next().and_then(|w| w.parse().ok()).ok_or_else(|| invalid("Malformed STL vertex"))?;
+                }
+                facet.push(weld.index(p));
+            }
+            "endfacet" => {
+                let &[a, b, c] = facet.as_slice() else {
+                    return Err(invalid("STL facet without three vertices"));
+                };
+                faces.push([a, b, c]);
+                facet.clear();
+            }
+            _ => {}
+        }
+    }
+    TriMesh::new(weld.vertices, faces).map_err(invalid)
+}
+
+/// Vertices deduplicated by their coordinates' bits
+#[derive(Default)]
+struct Weld {
+    vertices: Vec<[f64; 3]>,
+    index: HashMap<[u64; 3], u32>,
+}
+
+impl Weld {
+    fn index(&mut self, p: [f64; 3]) -> u32 {
+        // Adding zero makes -0.0 into 0.0, the same point
+        let key = p.map(|x| (x + 0.0).to_bits());
+        *self.index.entry(key).or_insert_with(|| {
+            self.vertices.push(p);
+            self.vertices.len() as u32 - 1
+        })
+    }
+}
+
+/// Wavefront OBJ: `v` lines and `f` lines, the latter with indices from 1,
+/// or negative to count back from the last vertex, each with any texture
+/// and normal indices after a `/`
+pub fn read_obj<R: BufRead>(r: &mut R) -> io::Result<TriMesh> {
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    let mut polygon = Vec::new();
+    for line in r.lines() {
+        let line = line?;
+        let mut words = line.split_ascii_whitespace();
+        match words.next() {
+            Some("v") => {
+                let mut p = [0.0; 3];
+                for x in &mut p {
+                    *x = words.next().and_then(|w| w.parse().ok()).ok_or_else(|| invalid("Malformed OBJ vertex"))?;
+                }
+                vertices.push(p);
+            }
+            Some("f") => {
+                polygon.clear();
+                for word in words {
+                    let i: i64 = word.split('/').next().and_then(|i| i.parse().ok()).ok_or_else(|| invalid("Malformed OBJ face"))?;
+                    let i = if i < 0 { vertices.len() as i64 + i } else { i - 1 };
+                    if !(0..vertices.len() as i64).contains(&i) {
+                        return Err(invalid("OBJ face index out of range"));
+                    }
+                    polygon.push(i as u32);
+                }
+                fan(&polygon, &mut faces)?;
+            }
+            _ => {} // Normals, texture coordinates, groups, materials, lines
+        }
+    }
+    TriMesh::new(vertices, faces).map_err(invalid)
+}
+
+/// Triangles of a convex polygon, about its first corner
+fn fan(polygon: &[u32], faces: &mut Vec<[u32; 3]>) -> io::Result<()> {
+    if polygon.len() < 3 {
+        return Err(invalid("Face with fewer than three vertices"));
+    }
+    faces.extend((1..polygon.len() - 1).map(|i| [polygon[0], polygon[i], polygon[i + 1]]));
+    Ok(())
+}
+
+/// How a PLY body stores its numbers
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlyEncoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// A PLY scalar type
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> io::Result<Self> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return Err(invalid("Unknown PLY property type")),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// The value in `b`, of [PlyType::size] bytes, in big-endian order
+    fn read_be(self, b: &[u8]) -> f64 {
+        match self {
+            Self::I8 => f64::from(b[0] as i8),
+            Self::U8 => f64::from(b[0]),
+            Self::I16 => f64::from(i16::from_be_bytes([b[0], b[1]])),
+            Self::U16 => f64::from(u16::from_be_bytes([b[0], b[1]])),
+            Self::I32 => f64::from(i32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+            Self::U32 => f64::from(u32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+            Self::F32 => f64::from(f32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+            Self::F64 => f64::from_be_bytes(b.try_into().expect("8 bytes")),
+        }
+    }
+}
+
+/// A property of a PLY element: one scalar, or a list of them after
+/// their count
+#[derive(Clone, Debug, PartialEq)]
+struct PlyProperty {
+    name: String,
+    count: Option<PlyType>,
+    kind: PlyType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// Numbers of a PLY body, one at a time
+struct PlyBody<'a> {
+    bytes: &'a [u8],
+    at: usize,
+    encoding: PlyEncoding,
+}
+
+impl PlyBody<'_> {
+    fn next(&mut self, kind: PlyType) -> io::Result<f64> {
+        if self.encoding == PlyEncoding::Ascii {
+            let rest = &self.bytes[self.at..];
+            let start = rest.iter().position(|c| !c.is_ascii_whitespace()).ok_or_else(|| invalid("Truncated PLY file"))?;
+            let len = rest[start..].iter().position(u8::is_ascii_whitespace).unwrap_or(rest.len() - start);
+            self.at += start + len;
+            return std::str::from_utf8(&rest[start..start + len]).ok().and_then(|w| w.parse().ok()).ok_or_else(|| invalid("Malformed PLY number"));
+        }
+        let mut b = self.bytes.get(self.at..self.at + kind.size()).ok_or_else(|| invalid("Truncated PLY file"))?.to_vec();
+        self.at += kind.size();
+        if self.encoding == PlyEncoding::LittleEndian {
+            b.reverse();
+        }
+        Ok(kind.read_be(&b))
+    }
+
+    /// A property's values, one for a scalar
+    fn property(&mut self, p: &PlyProperty, values: &mut Vec<f64>) -> io::Result<()> {
+        values.clear();
+        let n = match p.count {
+            None => 1,
+            Some(count) => {
+                let n = self.next(count)?;
+                if n < 0.0 || n.fract() != 0.0 {
+                    return Err(invalid("Malformed PLY list count"));
+                }
+                n as usize
+            }
+        };
+        for _ in 0..n {
+            values.push(self.next(p.kind)?);
+        }
+        Ok(())
+    }
+}
+
+/// PLY: the `x`, `y` and `z` of each `vertex`, and the `vertex_indices`
+/// (or `vertex_index`) list of each `face`; other elements and properties
+/// are read past
+pub fn read_ply<R: Read>(r: &mut R) -> io::Result<TriMesh> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    const END: &[u8] = b"end_header";
+    let end = bytes.windows(END.len()).position(|w| w == END).ok_or_else(|| invalid("PLY file without end_header"))?;
+    let header = std::str::from_utf8(&bytes[..end]).map_err(|_| invalid("PLY header not UTF-8"))?;
+    let body = bytes[end + END.len()..].strip_prefix(b"\r").unwrap_or(&bytes[end + END.len()..]);
+    let body = body.strip_prefix(b"\n").ok_or_else(|| invalid("Malformed PLY header"))?;
+
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(invalid("Not a PLY file"));
+    }
+    let (mut encoding, mut elements) = (None, Vec::<PlyElement>::new());
+    for line in lines {
+        let words: Vec<&str> = line.split_ascii_whitespace().collect();
+        match words[..] {
+            ["format", format, _] => {
+                encoding = Some(match format {
+                    "ascii" => PlyEncoding::Ascii,
+                    "binary_little_endian" => PlyEncoding::LittleEndian,
+                    "binary_big_endian" => PlyEncoding::BigEndian,
+                    _ => return Err(invalid("Unknown PLY format")),
+                })
+            }
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| invalid("Malformed PLY element"))?;
+                elements.push(PlyElement { name: name.to_string(), count, properties: Vec::new() });
+            }
+            ["property", "list", count, kind, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid("PLY property outside an element"))?;
+                element.properties.push(PlyProperty { name: name.to_string(), count: Some(PlyType::parse(count)?), kind: PlyType::parse(kind)? });
+            }
+            ["property", kind, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid("PLY property outside an element"))?;
+                element.properties.push(PlyProperty { name: name.to_string(), count: None, kind: PlyType::parse(kind)? });
+            }
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            _ => return Err(invalid("Malformed PLY header")),
+        }
+    }
+
+    let mut body = PlyBody { bytes: body, at: 0, encoding: encoding.ok_or_else(|| invalid("PLY file without format"))? };
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    let (mut values, mut polygon) = (Vec::new(), Vec::new());
+    for element in &elements {
+        let find = |names: &[&str]| element.properties.iter().position(|p| names.contains(&p.name.as_str()));
+        let xyz = [find(&["x"]), find(&["y"]), find(&["z"])];
+        if element.name == "vertex" && xyz.iter().flatten().any(|&i| element.properties[i].count.is_some()) {
+            return Err(invalid("PLY vertex coordinate is a list"));
+        }
+        let indices = find(&["vertex_indices", "vertex_index"]);
+        for _ in 0..element.count {
+            let mut p = [f64::NAN; 3];
+            for (i, property) in element.properties.iter().enumerate() {
+                body.property(property, &mut values)?;
+                match element.name.as_str() {
+                    "vertex" => {
+                        if let Some(k) = xyz.iter().position(|&j| j == Some(i)) {
+                            p[k] = values[0];
+                        }
+                    }
+                    "face" if indices == Some(i) => {
+                        polygon.clear();
+                        for &v in &values {
+                            if v < 0.0 || v.fract() != 0.0 || v >= u32::MAX as f64 {
+                                return Err(invalid("PLY face index out of range"));
+                            }
+                            polygon.push(v as u32);
+                        }
+                        fan(&polygon, &mut faces)?;
+                    }
+                    _ => {}
+                }
+            }
+            if element.name == "vertex" {
+                if xyz.contains(&None) {
+                    return Err(invalid("PLY vertex without x, y and z"));
+                }
+                vertices.push(p);
+            }
+        }
+    }
+    TriMesh::new(vertices, faces).map_err(|_| invalid("PLY face index out of range"))
+}
+
+/// Nodes of a Gmsh element type, for reading past those that aren't
+/// surface triangles, quadrilaterals or tetrahedra
+fn msh_nodes(element_type: usize) -> io::Result<usize> {
+    Ok(match element_type {
+        15 => 1,                   // Point
+        1 => 2,                    // Line
+        8 => 3,                    // Second-order line
+        2 => 3,                    // Triangle
+        3 | 4 => 4,                // Quadrilateral, tetrahedron
+        7 => 5,                    // Pyramid
+        6 | 9 => 6,                // Prism, second-order triangle
+        5 | 16 => 8,               // Hexahedron, serendipity quadrilateral
+        10 => 9,                   // Second-order quadrilateral
+        11 => 10,                  // Second-order tetrahedron
+        17 => 20,                  // Serendipity hexahedron
+        _ => return Err(invalid("Unsupported MSH element type")),
+    })
+}
+
+/// Whitespace-separated words of an MSH file
+struct MshWords<'a>(std::str::SplitAsciiWhitespace<'a>);
+
+impl MshWords<'_> {
+    fn word(&mut self) -> io::Result<&str> {
+        self.0.next().ok_or_else(|| invalid("Truncated MSH file"))
+    }
+
+    fn int(&mut self) -> io::Result<usize> {
+        self.word()?.parse().map_err(|_| invalid("Malformed MSH integer"))
+    }
+
+    fn point(&mut self) -> io::Result<[f64; 3]> {
+        let mut p = [0.0; 3];
+        for x in &mut p {
+            *x = self.word()?.parse().map_err(|_| invalid("Malformed MSH node"))?;
+        }
+        Ok(p)
+    }
+
+    /// Node tags of an element of `kind`
+    fn nodes(&mut self, kind: usize, nodes: &mut Vec<usize>) -> io::Result<()> {
+        nodes.clear();
+        for _ in 0..msh_nodes(kind)? {
+            nodes.push(self.int()?);
+        }
+        Ok(())
+    }
+}
+
+/// Gmsh MSH in ASCII, version 2.2 or 4.1
+pub fn read_msh<R: BufRead>(r: &mut R) -> io::Result<TriMesh> {
+    let mut text = String::new();
+    r.read_to_string(&mut text)?;
+    let mut words = MshWords(text.split_ascii_whitespace());
+
+    let (mut version, mut vertices, mut tag_index) = (None, Vec::new(), HashMap::new());
+    let (mut faces, mut tets, mut nodes) = (Vec::new(), Vec::new(), Vec::new());
+    while let Some(section) = words.0.next() {
+        match section {
+            "$MeshFormat" => {
+                let v = words.word()?;
+                version = Some(match v {
+                    "2" | "2.1" | "2.2" => 2,
+                    "4.1" => 4,
+                    _ => return Err(invalid("Unsupported MSH version")),
+                });
+                if words.int()? != 0 {
+                    return Err(invalid("Binary MSH files are not supported"));
+                }
+            }
+            "$Nodes" if version == Some(2) => {
+                for _ in 0..words.int()? {
+                    tag_index.insert(words.int()?, vertices.len() as u32);
+                    vertices.push(words.point()?);
+                }
+            }
+            "$Nodes" if version == Some(4) => {
+                let [blocks, _, _, _] = [words.int()?, words.int()?, words.int()?, words.int()?];
+                for _ in 0..blocks {
+                    let [dim, _, parametric, n] = [words.int()?, words.int()?, words.int()?, words.int()?];
+                    let tags = (0..n).map(|_| words.int()).collect::<io::Result<Vec<_>>>()?;
+                    let extra = parametric.checked_mul(dim).ok_or_else(|| invalid("Malformed MSH node block"))?;
+                    for tag in tags {
+                        tag_index.insert(tag, vertices.len() as u32);
+                        vertices.push(words.point()?);
+                        for _ in 0..extra {
+                            words.word()?;
+                        }
+                    }
+                }
+            }
+            "$Elements" if version == Some(2) => {
+                for _ in 0..words.int()? {
+                    let [_, kind, tags] = [words.int()?, words.int()?, words.int()?];
+                    for _ in 0..tags {
+                        words.word()?;
+                    }
+                    words.nodes(kind, &mut nodes)?;
+                    msh_element(kind, &nodes, &tag_index, &mut faces, &mut tets)?;
+                }
+            }
+            "$Elements" if version == Some(4) => {
+                let [blocks, _, _, _] = [words.int()?, words.int()?, words.int()?, words.int()?];
+                for _ in 0..blocks {
+                    let [_, _, kind, n] = [words.int()?, words.int()?, words.int()?, words.int()?];
+                    for _ in 0..n {
+                        words.word()?; // Element tag
+                        words.nodes(kind, &mut nodes)?;
+                        msh_element(kind, &nodes, &tag_index, &mut faces, &mut tets)?;
+                    }
+                }
+            }
+            "$Nodes" | "$Elements" => return Err(invalid("MSH section before $MeshFormat")),
+            _ => {}
+        }
+
+        // Whatever of the section is left, up to its end
+        if let Some(name) = section.strip_prefix('$').filter(|name| !name.starts_with("End")) {
+            let end = format!("$End{name}");
+            while words.word()? != end {}
+        }
+    }
+    if version.is_none() {
+        return Err(invalid("MSH file without $MeshFormat"));
+    }
+    if faces.is_empty() {
+        faces = boundary(&vertices, &tets);
+    }
+    TriMesh::new(vertices, faces).map_err(invalid)
+}
+
+/// Add an element of `kind` on the nodes tagged `nodes` to the faces or
+/// the tetrahedra, if it is either
+fn msh_element(kind: usize, nodes: &[usize], tag_index: &HashMap<usize, u32>, faces: &mut Vec<[u32; 3]>, tets: &mut Vec<[u32; 4]>) -> io::Result<()> {
+    if !matches!(kind, 2..=4) {
+        return Ok(());
+    }
+    let ids = nodes.iter().map(|t| tag_index.get(t).copied().ok_or_else(|| invalid("MSH element on an unknown node"))).collect::<io::Result<Vec<u32>>>()?;
+    match kind {
+        4 => tets.push([ids[0], ids[1], ids[2], ids[3]]),
+        _ => fan(&ids, faces)?,
+    }
+    Ok(())
+}
+
+/// Faces of `tets` that no other tetrahedron shares, wound outward
+fn boundary(vertices: &[[f64; 3]], tets: &[[u32; 4]]) -> Vec<[u32; 3]> {
+    let mut count: HashMap<[u32; 3], (usize, [u32; 3])> = HashMap::new();
+    for &[a, b, c, d] in tets {
+        let p = [a, b, c, d].map(|v| vertices[v as usize]);
+        let [a, b] = if dot(sub(p[1], p[0]), cross(sub(p[2], p[0]), sub(p[3], p[0]))) < 0.0 { [b, a] } else { [a, b] };
+        for face in [[a, c, b], [a, b, d], [a, d, c], [b, c, d]] {
+            let mut key = face;
+            key.sort_unstable();
+            count.entry(key).or_insert((0, face)).0 += 1;
+        }
+    }
+    let mut faces: Vec<[u32; 3]> = count.into_values().filter(|&(n, _)| n == 1).map(|(_, face)| face).collect();
+    faces.sort_unstable();
+    faces
+}
@@ -0,0 +1,105 @@
+//! Meshes and their attributes in `.npz` archives, one array per field:
+//!
+//! ```python
+//! mesh = np.load("mesh.npz")
+//! mesh["vertices"]            # (n, 3)
+//! mesh["faces"]               # (m, 3), vertex indices as float64
+//! mesh["face/emissivity"]     # (m,), one per attribute
+//! ```
+//!
+//! Indices are stored as float64 like everything else in [crate::npy],
+//! which is exact below 2⁵³, and checked to be whole on the way back in.
+
+use crate::attributes::AttributeMap;
+use crate::mesh::{TetMesh, TriMesh};
+use crate::npy::{read_npz, write_npz, NpyArray};
+use std::io::{self, Read, Seek, Write};
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Write a triangle mesh with its vertex and face attributes
+pub fn write_tri_mesh_npz<W: Write + Seek>(w: W, mesh: &TriMesh) -> io::Result<()> {
+    write_mesh(w, mesh.vertices(), ("faces", mesh.faces()), &[("vertex", mesh.vertex_attributes()), ("face", mesh.face_attributes())])
+}
+
+/// Read a triangle mesh written by [write_tri_mesh_npz]
+pub fn read_tri_mesh_npz<R: Read + Seek>(r: R) -> io::Result<TriMesh> {
+    let arrays = read_npz(r)?;
+    let vertices = read_elements(&arrays, "vertices", |x| x)?;
+    let faces = read_elements(&arrays, "faces", to_index)?;
+    let mut mesh = TriMesh::new(vertices, faces).map_err(invalid)?;
+    read_attributes(&arrays, "vertex", mesh.vertex_attributes_mut())?;
+    read_attributes(&arrays, "face", mesh.face_attributes_mut())?;
+    Ok(mesh)
+}
+
+/// Write a tetrahedron mesh with its vertex and cell attributes
+pub fn write_tet_mesh_npz<W: Write + Seek>(w: W, mesh: &TetMesh) -> io::Result<()> {
+    write_mesh(w, mesh.vertices(), ("cells", mesh.cells()), &[("vertex", mesh.vertex_attributes()), ("cell", mesh.cell_attributes())])
+}
+
+/// Read a tetrahedron mesh written by [write_tet_mesh_npz]
+pub fn read_tet_mesh_npz<R: Read + Seek>(r: R) -> io::Result<TetMesh> {
+    let arrays = read_npz(r)?;
+    let vertices = read_elements(&arrays, "vertices", |x| x)?;
+    let cells = read_elements(&arrays, "cells", to_index)?;
+    let mut mesh = TetMesh::new(vertices, cells).map_err(invalid)?;
+    read_attributes(&arrays, "vertex", mesh.vertex_attributes_mut())?;
+    read_attributes(&arrays, "cell", mesh.cell_attributes_mut())?;
+    Ok(mesh)
+}
+
+fn write_mesh<W: Write + Seek, const N: usize>(
+    w: W,
+    vertices: &[[f64; 3]],
+    (name, elements): (&str, &[[u32; N]]),
+    attributes: &[(&str, &AttributeMap<f64>)],
+) -> io::Result<()> {
+    let element_data: Vec<f64> = elements.as_flattened().iter().map(|&i| f64::from(i)).collect();
+    let named: Vec<(String, [usize; 1], &[f64])> = attributes
+        .iter()
+        .flat_map(|(prefix, map)| map.iter().map(move |(k, v)| (format!("{prefix}/{k}"), [map.len()], v)))
+        .collect();
+
+    let (vertex_shape, element_shape) = ([vertices.len(), 3], [elements.len(), N]);
+    let mut arrays: Vec<(&str, &[usize], &[f64])> =
+        vec![("vertices", &vertex_shape, vertices.as_flattened()), (name, &element_shape, &element_data)];
+    arrays.extend(named.iter().map(|(k, shape, v)| (k.as_str(), &shape[..], *v)));
+    write_npz(w, &arrays)
+}
+
+/// Array `name` of shape `(n, N)`, converted row by row
+fn read_elements<T, const N: usize>(
+    arrays: &[(String, NpyArray)],
+    name: &str,
+    convert: impl Fn(f64) -> T,
+) -> io::Result<Vec<[T; N]>> {
+    let arr = arrays.iter().find(|(k, _)| k == name).map(|(_, a)| a).ok_or_else(|| invalid("Missing mesh array"))?;
+    if arr.shape.len() != 2 || arr.shape[1] != N {
+        return Err(invalid("Mesh array has the wrong shape"));
+    }
+    Ok(arr.data.chunks_exact(N).map(|c| std::array::from_fn(|i| convert(c[i]))).collect())
+}
+
+/// Vertex index from its float64 encoding, or `u32::MAX`, which no mesh
+/// can address, for anything that isn't one
+#[inline]
+fn to_index(x: f64) -> u32 {
+    if x >= 0.0 && x < u32::MAX as f64 && x.fract() == 0.0 { x as u32 } else { u32::MAX }
+}
+
+/// Load every `prefix/name` array into `map`
+fn read_attributes(arrays: &[(String, NpyArray)], prefix: &str, map: &mut AttributeMap<f64>) -> io::Result<()> {
+    for (key, arr) in arrays {
+        let Some(name) = key.strip_prefix(prefix).and_then(|k| k.strip_prefix('/')) else {
+            continue;
+        };
+        if arr.shape.len() != 1 {
+            return Err(invalid("Attribute array has the wrong shape"));
+        }
+        map.insert(name, arr.data.clone()).map_err(invalid)?;
+    }
+    Ok(())
+}
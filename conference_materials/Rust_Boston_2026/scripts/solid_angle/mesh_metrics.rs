@@ -0,0 +1,158 @@
+//! A [TriMesh] at a glance: the volume, surface area, centroid and
+//! inertia of the solid it encloses, whether it is watertight, and the
+//! shape quality of its faces.
+//!
+//! Mass properties integrate over the enclosed solid face by face, by the
+//! divergence theorem (Eberly, "Polyhedral Mass Properties"), at unit
+//! density, so the inertia tensor is per unit density and about the
+//! centroid. They only mean something for a closed mesh wound outward;
+//! wound inward, the volume comes out negative.
+//!
+//! A face's quality is `4√3 A / (a² + b² + c²)` for area `A` and sides
+//! `a`, `b`, `c`: 1 for an equilateral triangle, 0 for a degenerate one.
+//! Its smallest angle says much the same in degrees.
+
+use crate::mesh::TriMesh;
+use crate::vec3::{cross, dot, norm, sub};
+use rayon::prelude::*;
+
+/// Volume, area, centroid and inertia, from [mass_properties]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MassProperties {
+    /// Enclosed volume, negative for a mesh wound inward
+    pub volume: f64,
+    pub area: f64,
+    /// Centroid of the enclosed volume
+    pub centroid: [f64; 3],
+    /// Inertia tensor about the centroid at unit density
+    pub inertia: [[f64; 3]; 3],
+}
+
+/// The `1`, `x`, `x²`, `x³` and mixed-term integrands of one coordinate
+/// over a triangle, as Eberly's subexpressions
+#[inline]
+fn subexpressions(w: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+    let t0 = w[0] + w[1];
+    let f1 = t0 + w[2];
+    let t1 = w[0] * w[0];
+    let t2 = t1 + w[1] * t0;
+    let f2 = t2 + w[2] * f1;
+    let f3 = w[0] * t1 + w[1] * t2 + w[2] * f2;
+    let g = std::array::from_fn(|i| f2 + w[i] * (f1 + w[i]));
+    ([f1, f2, f3], g)
+}
+
+/// Mass properties of the solid `mesh` encloses; see the module docs
+pub fn mass_properties(mesh: &TriMesh) -> MassProperties {
+    // ∫1, ∫x, ∫y, ∫z, ∫x², ∫y², ∫z², ∫xy, ∫yz, ∫zx over the volume
+    let mut integrals = [0.0; 10];
+    let mut area = 0.0;
+    for [p0, p1, p2] in mesh.triangles() {
+        let d = cross(sub(p1, p0), sub(p2, p0));
+        area += 0.5 * norm(d);
+        let [(fx, gx), (fy, gy), (fz, gz)] = [0, 1, 2].map(|k| subexpressions([p0[k], p1[k], p2[k]]));
+        let [x, y, z] = [0, 1, 2].map(|k| [p0[k], p1[k], p2[k]]);
+        integrals[0] += d[0] * fx[0];
+        integrals[1] += d[0] * fx[1];
+        integrals[2] += d[1] * fy[1];
+        integrals[3] += d[2] * fz[1];
+        integrals[4] += d[0] * fx[2];
+        integrals[5] += d[1] * fy[2];
+        integrals[6] += d[2] * fz[2];
+        integrals[7] += d[0] * dot(y, gx);
+        integrals[8] += d[1] * dot(z, gy);
+        integrals[9] += d[2] * dot(x, gz);
+    }
+    const SCALE: [f64; 10] = [1.0 / 6.0, 1.0 / 24.0, 1.0 / 24.0, 1.0 / 24.0, 1.0 / 60.0, 1.0 / 60.0, 1.0 / 60.0, 1.0 / 120.0, 1.0 / 120.0, 1.0 / 120.0];
+    let i: [f64; 10] = std::array::from_fn(|k| integrals[k] * SCALE[k]);
+
+    let volume = i[0];
+    let c = [i[1] / volume, i[2] / volume, i[3] / volume];
+    let xx = i[5] + i[6] - volume * (c[1] * c[1] + c[2] * c[2]);
+    let yy = i[4] + i[6] - volume * (c[2] * c[2] + c[0] * c[0]);
+    let zz = i[4] + i[5] - volume * (c[0] * c[0] + c[1] * c[1]);
+    let xy = -(i[7] - volume * c[0] * c[1]);
+    let yz = -(i[8] - volume * c[1] * c[2]);
+    let zx = -(i[9] - volume * c[2] * c[0]);
+    MassProperties { volume, area, centroid: c, inertia: [[xx, xy, zx], [xy, yy, yz], [zx, yz, zz]] }
+}
+
+/// What keeps a mesh from being watertight, from [watertightness]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Watertightness {
+    /// Edges bordering one face
+    pub boundary_edges: usize,
+    /// Edges bordering more than two faces
+    pub non_manifold_edges: usize,
+    /// Edges between two faces that run along them the same way, so that
+    /// one of the two is wound the wrong way round
+    pub misoriented_edges: usize,
+    /// Faces with a repeated vertex or no area
+    pub degenerate_faces: usize,
+}
+
+impl Watertightness {
+    /// Closed, manifold and consistently wound, so that it encloses a
+    /// solid; degenerate faces don't break that
+    pub fn is_watertight(&self) -> bool {
+        self.boundary_edges == 0 && self.non_manifold_edges == 0 && self.misoriented_edges == 0
+    }
+}
+
+/// Whether `mesh` is watertight, and if not, where not
+pub fn watertightness(mesh: &TriMesh) -> Watertightness {
+    let adjacency = mesh.build_adjacency();
+    let faces = mesh.faces();
+    let runs = |f: u32, [u, v]: [u32; 2]| {
+        let [a, b, c] = faces[f as usize];
+        [[a, b], [b, c], [c, a]].contains(&[u, v])
+    };
+    let (boundary_edges, non_manifold_edges) = (adjacency.boundary_edges().count(), adjacency.non_manifold_edges().count());
+    let misoriented_edges = (0..adjacency.edges.len())
+        .into_par_iter()
+        .filter(|&e| match *adjacency.edge_faces.row(e) {
+            [f, g] => runs(f, adjacency.edges[e]) == runs(g, adjacency.edges[e]),
+            _ => false,
+        })
+        .count();
+    let degenerate_faces = (0..faces.len())
+        .into_par_iter()
+        .filter(|&f| {
+            let [a, b, c] = faces[f];
+            let [p0, p1, p2] = mesh.triangle(f);
+            a == b || b == c || c == a || norm(cross(sub(p1, p0), sub(p2, p0))) == 0.0
+        })
+        .count();
+    Watertightness { boundary_edges, non_manifold_edges, misoriented_edges, degenerate_faces }
+}
+
+/// Quality of each face, `4√3 A / (a² + b² + c²)`
+pub fn face_quality(mesh: &TriMesh) -> Vec<f64> {
+    (0..mesh.faces().len())
+        .into_par_iter()
+        .map(|f| {
+            let [a, b, c] = mesh.triangle(f);
+            let sides: f64 = [sub(b, a), sub(c, b), sub(a, c)].iter().map(|&e| dot(e, e)).sum();
+            if sides == 0.0 {
+                return 0.0;
+            }
+            2.0 * 3.0_f64.sqrt() * norm(cross(sub(b, a), sub(c, a))) / sides
+        })
+        .collect()
+}
+
+/// Smallest angle of each face, in degrees; 0 for a degenerate one
+pub fn min_angles(mesh: &TriMesh) -> Vec<f64> {
+    (0..mesh.faces().len())
+        .into_par_iter()
+        .map(|f| {
+            let p = mesh.triangle(f);
+            (0..3)
+                .map(|i| {
+                    let (u, v) = (sub(p[(i + 1) % 3], p[i]), sub(p[(i + 2) % 3], p[i]));
+                    libm::atan2(norm(cross(u, v)), dot(u, v)).to_degrees()
+                })
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+//! Mixed precision: narrow storage, `f64` arithmetic.
+//!
+//! Million-element sweeps are memory-bound, and `f32` vertices halve the
+//! bytes read. The kernels here widen each vertex to `f64` on load and run
+//! the `f64` kernel, so the triple product and `atan2` keep full
+//! precision; the only error beyond the `f64` kernel's is the rounding of
+//! the stored coordinates (and of the output, if narrow too). Widening is
+//! exact, so results match the `f64` kernel on the widened inputs bit for
+//! bit.
+//!
+//! With the `half` feature, `half::f16` and `half::bf16` are storage
+//! formats too, for meshes coming out of ML pipelines or GPU buffers. Their
+//! 11 and 8 significant bits put vertices off by about 5e-4 and 4e-3 of
+//! their magnitude, which is the typical relative error of the angles
+//! too; but a near-flat tetrahedron can round to the other orientation,
+//! so the worst case is the whole sphere.
+
+use crate::par::{chunk_len, par_threshold};
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use rayon::prelude::*;
+
+/// Floating-point storage format, widened to `f64` for computing
+pub trait Storage: Copy + Send + Sync {
+    fn to_f64(self) -> f64;
+    /// Round to nearest
+    fn from_f64(x: f64) -> Self;
+}
+
+impl Storage for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+}
+
+impl Storage for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+}
+
+#[cfg(feature = "half")]
+impl Storage for half::f16 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        half::f16::to_f64(self)
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        half::f16::from_f64(x)
+    }
+}
+
+#[cfg(feature = "half")]
+impl Storage for half::bf16 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        half::bf16::to_f64(self)
+    }
+
+    #[inline]
+    fn from_f64(x: f64) -> Self {
+        half::bf16::from_f64(x)
+    }
+}
+
+#[inline]
+fn widen<S: Storage>(tet: &[[S; 3]; 4]) -> [[f64; 3]; 4] {
+    tet.map(|v| v.map(S::to_f64))
+}
+
+/// Variant of [crate::tetrahedron::solid_angle_tetrahedron] reading `I`
+/// vertices and writing `O` results, computing in `f64`
+#[inline]
+pub fn solid_angle_tetrahedron_mixed<I: Storage, O: Storage>(
+    tetrahedra: &[[[I; 3]; 4]],
+    out: &mut [O],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for (tet, y) in tetrahedra.iter().zip(out.iter_mut()) {
+        let [v0, v1, v2, v3] = widen(tet);
+        *y = O::from_f64(solid_angle_tetrahedron_scalar(v0, v1, v2, v3));
+    }
+
+    Ok(())
+}
+
+/// Thread-parallel [solid_angle_tetrahedron_mixed], chunked as
+/// [crate::par::solid_angle_tetrahedra_par]
+pub fn solid_angle_tetrahedra_mixed_par<I: Storage, O: Storage>(
+    tetrahedra: &[[[I; 3]; 4]],
+    out: &mut [O],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return solid_angle_tetrahedron_mixed(tetrahedra, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(t, o)| solid_angle_tetrahedron_mixed(t, o))
+}
@@ -0,0 +1,65 @@
+//! Total solid angle of one mesh seen from many origins.
+
+use crate::mesh::TriMesh;
+use crate::par::par_threshold;
+use crate::sum::CompensatedSum;
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use rayon::prelude::*;
+
+/// Origins sharing one pass over the mesh
+pub(crate) const BLOCK: usize = 8;
+
+/// Total solid angle subtended by `mesh` at each origin, `+4π` inside a
+/// closed outward-wound mesh and `0` outside.
+///
+/// Origins are processed in blocks that each stream the mesh once, rather
+/// than once per origin, and blocks run in parallel when there are at
+/// least [par_threshold] origin-face pairs.
+pub fn solid_angles_multi_origin(
+    mesh: &TriMesh,
+    origins: &[[f64; 3]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if origins.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    let pairs = origins.len().saturating_mul(mesh.faces().len());
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!(
+        "solid_angles_multi_origin",
+        origins = origins.len(),
+        faces = mesh.faces().len(),
+        parallel = pairs >= par_threshold()
+    )
+    .entered();
+
+    // Parallelize over origins, never over faces, so sums stay deterministic
+    if pairs < par_threshold() {
+        origins
+            .chunks(BLOCK)
+            .zip(out.chunks_mut(BLOCK))
+            .for_each(|(o, s)| solid_angles_block(mesh.triangles(), o, s));
+    } else {
+        (origins.par_chunks(BLOCK), out.par_chunks_mut(BLOCK))
+            .into_par_iter()
+            .for_each(|(o, s)| solid_angles_block(mesh.triangles(), o, s));
+    }
+
+    Ok(())
+}
+
+/// Accumulate up to [BLOCK] origins over a single pass of the triangles
+#[inline]
+pub(crate) fn solid_angles_block(triangles: impl Iterator<Item = [[f64; 3]; 3]>, origins: &[[f64; 3]], out: &mut [f64]) {
+    let mut acc = [CompensatedSum::new(); BLOCK];
+    for tri in triangles {
+        for (a, &o) in acc.iter_mut().zip(origins) {
+            a.add(solid_angle_tetrahedron_scalar(o, tri[0], tri[1], tri[2]));
+        }
+    }
+    for (y, a) in out.iter_mut().zip(&acc) {
+        *y = a.value();
+    }
+}
@@ -0,0 +1,109 @@
+//! Explicit NEON kernel for AArch64, two tetrahedra per `float64x2_t`.
+//!
+//! LLVM's autovectorizer gives up on the AoS tetrahedron layout on
+//! AArch64, so the scalar kernel runs one lane at a time there. This
+//! transposes pairs of tetrahedra into lanes and does the vector algebra
+//! with `vfmaq_f64`, in exactly the order of the scalar kernel's
+//! `mul_add`s, so results are bit-identical to it. `atan2` has no NEON
+//! instruction and stays per lane through [crate::math::atan2], except
+//! under `math-poly`, whose [crate::math::atan2_f64x2] takes both lanes.
+//!
+//! NEON is part of the AArch64 baseline, so no detection is needed.
+#![cfg(target_arch = "aarch64")]
+
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use std::arch::aarch64::*;
+
+type V3 = [float64x2_t; 3];
+
+/// Lanes `[x0, x1]`
+#[inline]
+#[target_feature(enable = "neon")]
+fn pair(x0: f64, x1: f64) -> float64x2_t {
+    vcombine_f64(vdup_n_f64(x0), vdup_n_f64(x1))
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+fn sub(a: V3, b: V3) -> V3 {
+    [vsubq_f64(a[0], b[0]), vsubq_f64(a[1], b[1]), vsubq_f64(a[2], b[2])]
+}
+
+/// As [crate::vec3::dot]: u0 v0 + (u1 v1 + u2 v2), fused
+#[inline]
+#[target_feature(enable = "neon")]
+fn dot(u: V3, v: V3) -> float64x2_t {
+    vfmaq_f64(vfmaq_f64(vmulq_f64(u[2], v[2]), u[1], v[1]), u[0], v[0])
+}
+
+/// As [crate::vec3::cross]
+#[inline]
+#[target_feature(enable = "neon")]
+fn cross(u: V3, v: V3) -> V3 {
+    [
+        vfmaq_f64(vnegq_f64(vmulq_f64(u[2], v[1])), u[1], v[2]),
+        vfmaq_f64(vnegq_f64(vmulq_f64(u[0], v[2])), u[2], v[0]),
+        vfmaq_f64(vnegq_f64(vmulq_f64(u[1], v[0])), u[0], v[1]),
+    ]
+}
+
+/// One lane's angle, with the scalar kernel's degeneracy check
+#[inline]
+fn lane(abc: f64, triple: f64, denom: f64) -> f64 {
+    if abc != 0.0 { 2.0 * crate::math::atan2(triple, denom) } else { 0.0 }
+}
+
+/// Solid angles of two tetrahedra, one per lane
+#[inline]
+#[target_feature(enable = "neon")]
+#[allow(unexpected_cfgs)] // As in crate::math
+fn solid_angle_pair(t0: &[[f64; 3]; 4], t1: &[[f64; 3]; 4]) -> [f64; 2] {
+    let mut v = [[vdupq_n_f64(0.0); 3]; 4];
+    for k in 0..4 {
+        for j in 0..3 {
+            v[k][j] = pair(t0[k][j], t1[k][j]);
+        }
+    }
+    let (a, b, c) = (sub(v[1], v[0]), sub(v[2], v[0]), sub(v[3], v[0]));
+    let (la, lb, lc) = (vsqrtq_f64(dot(a, a)), vsqrtq_f64(dot(b, b)), vsqrtq_f64(dot(c, c)));
+    let abc = vmulq_f64(vmulq_f64(la, lb), lc);
+
+    let triple = dot(a, cross(b, c));
+    let denom = vfmaq_f64(vfmaq_f64(vfmaq_f64(abc, dot(b, c), la), dot(a, c), lb), dot(a, b), lc);
+
+    if cfg!(feature = "math-poly") {
+        let angle = vmulq_f64(vdupq_n_f64(2.0), crate::math::atan2_f64x2(triple, denom));
+        let angle = vbslq_f64(vceqzq_f64(abc), vdupq_n_f64(0.0), angle);
+        return [vgetq_lane_f64::<0>(angle), vgetq_lane_f64::<1>(angle)];
+    }
+    [
+        lane(vgetq_lane_f64::<0>(abc), vgetq_lane_f64::<0>(triple), vgetq_lane_f64::<0>(denom)),
+        lane(vgetq_lane_f64::<1>(abc), vgetq_lane_f64::<1>(triple), vgetq_lane_f64::<1>(denom)),
+    ]
+}
+
+/// NEON variant of [crate::tetrahedron::solid_angle_tetrahedron]
+#[inline]
+pub fn solid_angle_tetrahedron_neon(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // SAFETY: NEON is in the baseline of every AArch64 target
+    unsafe { solid_angle_tetrahedron_neon_unchecked(tetrahedra, out) };
+    Ok(())
+}
+
+#[target_feature(enable = "neon")]
+fn solid_angle_tetrahedron_neon_unchecked(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) {
+    // Two at a time, then the odd one out
+    let (tet_pairs, tet_rest) = tetrahedra.as_chunks::<2>();
+    let (out_pairs, out_rest) = out.as_chunks_mut::<2>();
+    for (t, y) in tet_pairs.iter().zip(out_pairs) {
+        *y = solid_angle_pair(&t[0], &t[1]);
+    }
+    for (t, y) in tet_rest.iter().zip(out_rest) {
+        *y = solid_angle_tetrahedron_scalar(t[0], t[1], t[2], t[3]);
+    }
+}
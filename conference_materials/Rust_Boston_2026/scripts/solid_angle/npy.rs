@@ -0,0 +1,218 @@
+//! Readers/writers for NumPy `.npy` and `.npz` files holding `float64`
+//! arrays, so point and tetrahedron arrays can move between the Python
+//! preprocessing scripts and the kernels without a PyO3 dependency.
+//!
+//! ```python
+//! np.save("tets.npy", tets)  # shape (n, 4, 3), float64
+//! omega = np.load("solid_angles.npy")  # shape (n,)
+//! ```
+//!
+//! Only C-ordered `<f8`/`>f8` arrays are accepted; anything else is
+//! rejected with `InvalidData` rather than silently converted.
+
+use std::io::{self, Read, Seek, Write};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+/// Dense, C-ordered float64 array as stored in a `.npy` file
+#[derive(Clone, Debug, PartialEq)]
+pub struct NpyArray {
+    pub shape: Vec<usize>,
+    pub data: Vec<f64>,
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Write a float64 array of the given shape in `.npy` format.
+pub fn write_f64<W: Write>(w: &mut W, shape: &[usize], data: &[f64]) -> io::Result<()> {
+    // Check bounds
+    if shape.iter().product::<usize>() != data.len() {
+        return Err(invalid("Dimension mismatch"));
+    }
+
+    // Header dict. Python wants a trailing comma for 1-tuples.
+    let dims: Vec<String> = shape.iter().map(|d| d.to_string()).collect();
+    let shape_repr = match dims.len() {
+        1 => format!("({},)", dims[0]),
+        _ => format!("({})", dims.join(", ")),
+    };
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {shape_repr}, }}");
+
+    // Pad with spaces so the data starts on a 64-byte boundary, then
+    // pick the smallest format version whose length field fits
+    let padded_len = |preamble: usize| (preamble + header.len() + 1).next_multiple_of(64) - preamble;
+    let v1 = padded_len(10) <= u16::MAX as usize;
+    let padded = if v1 { padded_len(10) } else { padded_len(12) };
+    header.extend(std::iter::repeat_n(' ', padded - header.len() - 1));
+    header.push('\n');
+
+    w.write_all(MAGIC)?;
+    if v1 {
+        w.write_all(&[1, 0])?;
+        w.write_all(&(header.len() as u16).to_le_bytes())?;
+    } else {
+        w.write_all(&[2, 0])?;
+        w.write_all(&(header.len() as u32).to_le_bytes())?;
+    }
+    w.write_all(header.as_bytes())?;
+
+    // Stage through a fixed buffer instead of one write per element
+    let mut buf = [0_u8; 8 * 1024];
+    for chunk in data.chunks(1024) {
+        for (b, x) in buf.chunks_exact_mut(8).zip(chunk) {
+            b.copy_from_slice(&x.to_le_bytes());
+        }
+        w.write_all(&buf[..8 * chunk.len()])?;
+    }
+
+    Ok(())
+}
+
+/// Read a float64 array in `.npy` format.
+pub fn read_f64<R: Read>(r: &mut R) -> io::Result<NpyArray> {
+    // Preamble
+    let mut magic = [0_u8; 8];
+    r.read_exact(&mut magic)?;
+    if &magic[..6] != MAGIC {
+        return Err(invalid("Not an .npy file"));
+    }
+    let header_len = match magic[6] {
+        1 => {
+            let mut len = [0_u8; 2];
+            r.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0_u8; 4];
+            r.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        _ => return Err(invalid("Unsupported .npy format version")),
+    };
+    let mut header = vec![0_u8; header_len];
+    r.read_exact(&mut header)?;
+    let header = std::str::from_utf8(&header).map_err(|_| invalid("Malformed .npy header"))?;
+
+    // Header dict
+    let little_endian = match header_value(header, "descr") {
+        Some("'<f8'") | Some("'|f8'") => true,
+        Some("'>f8'") => false,
+        _ => return Err(invalid("Only float64 .npy arrays are supported")),
+    };
+    match header_value(header, "fortran_order") {
+        Some("False") => {}
+        Some("True") => return Err(invalid("Fortran-ordered .npy arrays are not supported")),
+        _ => return Err(invalid("Malformed .npy header")),
+    }
+    let shape = parse_shape(header).ok_or(invalid("Malformed .npy header"))?;
+
+    // Data
+    let n = shape
+        .iter()
+        .try_fold(1_usize, |acc, &d| acc.checked_mul(d))
+        .ok_or(invalid("Malformed .npy header"))?;
+    let mut data = Vec::with_capacity(n.min(1 << 20)); // Not trusting the header for a huge allocation
+    let mut buf = [0_u8; 8 * 1024];
+    while data.len() < n {
+        let m = (n - data.len()).min(1024);
+        r.read_exact(&mut buf[..8 * m])?;
+        data.extend(buf[..8 * m].chunks_exact(8).map(|b| {
+            let b = b.try_into().unwrap();
+            if little_endian {
+                f64::from_le_bytes(b)
+            } else {
+                f64::from_be_bytes(b)
+            }
+        }));
+    }
+
+    Ok(NpyArray { shape, data })
+}
+
+/// Raw text of the value stored under `key` in the header dict,
+/// up to the next top-level comma
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{key}'"))? + key.len() + 2;
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let end = rest.find([',', '}'])?;
+    Some(rest[..end].trim())
+}
+
+/// Parse the `'shape': (a, b, ...)` entry of the header dict
+fn parse_shape(header: &str) -> Option<Vec<usize>> {
+    let start = header.find("'shape'")? + "'shape'".len();
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('(')?;
+    let dims = &rest[..rest.find(')')?];
+    dims.split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse().ok())
+        .collect()
+}
+
+/// Write tetrahedra as an `(n, 4, 3)` array.
+pub fn write_tetrahedra<W: Write>(w: &mut W, tetrahedra: &[[[f64; 3]; 4]]) -> io::Result<()> {
+    write_f64(w, &[tetrahedra.len(), 4, 3], tetrahedra.as_flattened().as_flattened())
+}
+
+/// Read tetrahedra from an `(n, 4, 3)` array.
+pub fn read_tetrahedra<R: Read>(r: &mut R) -> io::Result<Vec<[[f64; 3]; 4]>> {
+    let arr = read_f64(r)?;
+    if arr.shape.len() != 3 || arr.shape[1..] != [4, 3] {
+        return Err(invalid("Expected tetrahedra with shape (n, 4, 3)"));
+    }
+    Ok(arr
+        .data
+        .chunks_exact(12)
+        .map(|t| {
+            let v = |i: usize| [t[3 * i], t[3 * i + 1], t[3 * i + 2]];
+            [v(0), v(1), v(2), v(3)]
+        })
+        .collect())
+}
+
+/// Write points as an `(n, 3)` array.
+pub fn write_points<W: Write>(w: &mut W, points: &[[f64; 3]]) -> io::Result<()> {
+    write_f64(w, &[points.len(), 3], points.as_flattened())
+}
+
+/// Read points from an `(n, 3)` array.
+pub fn read_points<R: Read>(r: &mut R) -> io::Result<Vec<[f64; 3]>> {
+    let arr = read_f64(r)?;
+    if arr.shape.len() != 2 || arr.shape[1] != 3 {
+        return Err(invalid("Expected points with shape (n, 3)"));
+    }
+    Ok(arr.data.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect())
+}
+
+/// Write named arrays to an uncompressed `.npz` archive, as `np.savez` does.
+pub fn write_npz<W: Write + Seek>(w: W, arrays: &[(&str, &[usize], &[f64])]) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(w);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .large_file(true);
+    for &(name, shape, data) in arrays {
+        zip.start_file(format!("{name}.npy"), options)?;
+        write_f64(&mut zip, shape, data)?;
+    }
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// Read every array in an `.npz` archive, stored or deflated, keyed by
+/// name without the `.npy` suffix.
+pub fn read_npz<R: Read + Seek>(r: R) -> io::Result<Vec<(String, NpyArray)>> {
+    let mut zip = zip::ZipArchive::new(r)?;
+    let mut arrays = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let name = file.name()?.trim_end_matches(".npy").to_owned();
+        arrays.push((name, read_f64(&mut file)?));
+    }
+
+    Ok(arrays)
+}
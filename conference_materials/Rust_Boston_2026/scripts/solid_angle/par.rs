@@ -0,0 +1,262 @@
+//! Rayon-parallel drivers for the slice kernels, as in `examples/type2_demo.rs`.
+//!
+//! With the `trace` feature, each driver call opens a `tracing` span with
+//! the element count and path taken, and each parallel chunk a nested one,
+//! so a subscriber can report chunk sizes, thread counts and durations.
+//!
+//! The `_cancellable` drivers take a [CancellationToken] and check it
+//! before each chunk, so a batch started from a UI or a service can be
+//! stopped part way without stopping the process.
+
+use crate::tetrahedron::{
+    slice_assume_init_mut, solid_angle_tetrahedron, solid_angle_tetrahedron_scalar, solid_angle_tetrahedron_uninit,
+    solid_angle_tetrahedron_unchecked, PairedSlices,
+};
+use rayon::iter::Map;
+use rayon::prelude::*;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+
+/// Populated once on first access, then never again.
+/// No lock used for access after initialization!
+static PHYSICAL_CORES: LazyLock<usize> = LazyLock::new(num_cpus::get_physical);
+
+/// Default element count below which the `_par` drivers run serially.
+/// Roughly where rayon's fork/join overhead stops paying for itself.
+pub const DEFAULT_PAR_THRESHOLD: usize = 10_000;
+
+static PAR_THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_PAR_THRESHOLD);
+
+/// Element count below which the `_par` drivers run serially
+#[inline]
+pub fn par_threshold() -> usize {
+    PAR_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Set the element count below which the `_par` drivers run serially,
+/// for all threads. Zero always goes parallel.
+pub fn set_par_threshold(n: usize) {
+    PAR_THRESHOLD.store(n, Ordering::Relaxed);
+}
+
+/// Default cap on elements per parallel chunk
+pub const DEFAULT_MAX_CHUNK: usize = 1024;
+
+static MAX_CHUNK: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CHUNK);
+
+/// Cap on elements per parallel chunk
+#[inline]
+pub fn max_chunk() -> usize {
+    MAX_CHUNK.load(Ordering::Relaxed)
+}
+
+/// Set the cap on elements per parallel chunk, for all threads. Smaller
+/// chunks balance load better; larger ones cost less to schedule.
+pub fn set_max_chunk(n: usize) {
+    MAX_CHUNK.store(n.max(1), Ordering::Relaxed);
+}
+
+/// Worker threads to split across. Only use real cores!
+#[inline]
+fn num_threads() -> usize {
+    rayon::current_num_threads().min(*PHYSICAL_CORES)
+}
+
+/// Chunk length for `n` elements
+#[inline]
+pub(crate) fn chunk_len(n: usize) -> usize {
+    max_chunk().min(n / num_threads()).max(1) // Never zero, even for tiny inputs
+}
+
+/// Vector-parallel variant of [crate::tetrahedron::solid_angle_tetrahedron_scalar].
+/// Falls back to the serial kernel below [par_threshold] elements.
+#[inline] // Enable cross-crate inlining
+pub fn solid_angle_tetrahedra_par(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("solid_angle_tetrahedra_par", n = out.len(), parallel = out.len() >= par_threshold()).entered();
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return solid_angle_tetrahedron(tetrahedra, out);
+    }
+
+    // Chunk inputs
+    let chunk = chunk_len(out.len());
+    let (tet_chunks, out_chunks) = (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk));
+    #[cfg(feature = "trace")]
+    tracing::debug!(chunk, threads = num_threads(), "chunked");
+
+    // Do vector calculations over each chunk in parallel
+    (tet_chunks, out_chunks)
+        .into_par_iter()
+        .try_for_each(|(tetc, outc)| {
+            #[cfg(feature = "trace")]
+            let _span = tracing::trace_span!("chunk", len = outc.len()).entered();
+            solid_angle_tetrahedron(tetc, outc)
+        })?;
+
+    Ok(())
+}
+
+/// Error from the `_cancellable` drivers once their token is cancelled
+pub const CANCELLED: &str = "Cancelled";
+
+/// Flag shared between clones, for stopping the `_cancellable` drivers
+/// from another thread. A cancelled call returns [CANCELLED] after at
+/// most one chunk per thread more, about [max_chunk] elements each, with
+/// the output partly written. Cancelling is for good; use a new token
+/// for the next call.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every call holding a clone of this token at its next chunk
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// [CANCELLED] if cancelled
+    #[inline]
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.is_cancelled() { Err(CANCELLED) } else { Ok(()) }
+    }
+}
+
+/// `kernel` over `inputs` a chunk at a time, checking `token` before
+/// each: in parallel from [par_threshold] elements, as the drivers
+/// chunk, and in chunks of [max_chunk] on the calling thread below
+pub(crate) fn cancellable<T: Sync, O: Send>(
+    inputs: &[T],
+    out: &mut [O],
+    token: &CancellationToken,
+    kernel: impl Fn(&[T], &mut [O]) -> Result<(), &'static str> + Sync,
+) -> Result<(), &'static str> {
+    // Check bounds
+    if inputs.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    let step = |(x, y): (&[T], &mut [O])| {
+        token.check()?;
+        kernel(x, y)
+    };
+    if out.len() < par_threshold() {
+        let chunk = max_chunk();
+        return inputs.chunks(chunk).zip(out.chunks_mut(chunk)).try_for_each(step);
+    }
+
+    let chunk = chunk_len(out.len());
+    (inputs.par_chunks(chunk), out.par_chunks_mut(chunk)).into_par_iter().try_for_each(step)
+}
+
+/// [solid_angle_tetrahedra_par], stopped by `token`; see [CancellationToken]
+pub fn solid_angle_tetrahedra_par_cancellable(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+    token: &CancellationToken,
+) -> Result<(), &'static str> {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("solid_angle_tetrahedra_par_cancellable", n = out.len(), parallel = out.len() >= par_threshold()).entered();
+
+    cancellable(tetrahedra, out, token, solid_angle_tetrahedron)
+}
+
+/// Infallible variant of [solid_angle_tetrahedra_par], see [PairedSlices]
+#[inline]
+pub fn solid_angle_tetrahedra_par_unchecked(pairs: PairedSlices<'_>) {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("solid_angle_tetrahedra_par_unchecked", n = pairs.len(), parallel = pairs.len() >= par_threshold()).entered();
+
+    // Small batches are faster without the thread pool
+    if pairs.len() < par_threshold() {
+        return solid_angle_tetrahedron_unchecked(pairs);
+    }
+
+    // Chunks of equal-length slices are equal length too
+    let chunk = chunk_len(pairs.len());
+    let (tetrahedra, out) = pairs.into_parts();
+    (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .for_each(|(tetc, outc)| {
+            for (tet, y) in tetc.iter().zip(outc.iter_mut()) {
+                *y = solid_angle_of(*tet);
+            }
+        });
+}
+
+/// Variant of [solid_angle_tetrahedra_par] writing into uninitialized memory.
+/// Returns the now-initialized output.
+#[inline] // Enable cross-crate inlining
+pub fn solid_angle_tetrahedra_par_uninit<'a>(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &'a mut [MaybeUninit<f64>],
+) -> Result<&'a mut [f64], &'static str> {
+    // Check bounds up front, so no chunk can be left unwritten
+    if tetrahedra.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("solid_angle_tetrahedra_par_uninit", n = out.len(), parallel = out.len() >= par_threshold()).entered();
+
+    // Small batches are faster without the thread pool
+    if out.len() < par_threshold() {
+        return solid_angle_tetrahedron_uninit(tetrahedra, out);
+    }
+
+    // Chunk inputs
+    let chunk = chunk_len(out.len());
+    let (tet_chunks, out_chunks) = (tetrahedra.par_chunks(chunk), out.par_chunks_mut(chunk));
+    #[cfg(feature = "trace")]
+    tracing::debug!(chunk, threads = num_threads(), "chunked");
+
+    // Do vector calculations over each chunk in parallel
+    (tet_chunks, out_chunks)
+        .into_par_iter()
+        .try_for_each(|(tetc, outc)| {
+            #[cfg(feature = "trace")]
+            let _span = tracing::trace_span!("chunk", len = outc.len()).entered();
+            solid_angle_tetrahedron_uninit(tetc, outc).map(|_| ())
+        })?;
+
+    // SAFETY: The chunks cover the whole output and each one was written in
+    // full, since try_for_each only returns Ok if every chunk did
+    Ok(unsafe { slice_assume_init_mut(out) })
+}
+
+#[inline]
+fn solid_angle_of(tet: [[f64; 3]; 4]) -> f64 {
+    solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3])
+}
+
+/// Parallel iterator returned by [ParSolidAngles::par_solid_angles]
+pub type ParSolidAnglesIter<I> = Map<I, fn([[f64; 3]; 4]) -> f64>;
+
+/// Extension trait fusing the kernel into an existing rayon chain,
+/// instead of collecting the tetrahedra into a slice first.
+/// Indexed iterators stay indexed, so `zip`/`collect_into_vec` still work.
+pub trait ParSolidAngles: ParallelIterator<Item = [[f64; 3]; 4]> + Sized {
+    fn par_solid_angles(self) -> ParSolidAnglesIter<Self> {
+        self.map(solid_angle_of as fn(_) -> f64)
+    }
+}
+
+impl<I: ParallelIterator<Item = [[f64; 3]; 4]>> ParSolidAngles for I {}
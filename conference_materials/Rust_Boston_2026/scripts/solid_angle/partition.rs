@@ -0,0 +1,170 @@
+//! Splitting a [TetMesh] into balanced parts for distributed runs.
+//!
+//! Recursive coordinate bisection (RCB) on cell centroids: cut along the
+//! widest axis at the point that balances the cell counts, and recurse.
+//! It needs no graph and keeps parts compact, though its cuts are not as
+//! short as a graph partitioner's like METIS. Each part then gets ghost
+//! layers, the neighboring cells owned by other parts, so that per-part
+//! work touching neighbors can run without communication.
+
+use crate::mesh::TetMesh;
+
+/// Cells of one part, by index into the whole mesh
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Part {
+    /// Cells this part owns; every cell is owned by exactly one part
+    pub owned: Vec<u32>,
+    /// Cells owned elsewhere within the ghost layers of this part
+    pub ghosts: Vec<u32>,
+}
+
+impl Part {
+    /// Standalone mesh of the owned cells then the ghosts, with vertices
+    /// renumbered and the originals' attributes carried over.
+    ///
+    /// Adds cell attributes `global_id`, each cell's index in `mesh`, and
+    /// `ghost`, 1 for ghosts and 0 for owned cells, plus vertex attribute
+    /// `global_id`.
+    pub fn to_mesh(&self, mesh: &TetMesh) -> Result<TetMesh, &'static str> {
+        let cells: Vec<u32> = self.owned.iter().chain(&self.ghosts).copied().collect();
+
+        // Renumber vertices in order of first use
+        let mut local = vec![u32::MAX; mesh.vertices().len()];
+        let mut global_vertices = Vec::new();
+        let local_cells = cells
+            .iter()
+            .map(|&c| {
+                mesh.cells()[c as usize].map(|v| {
+                    if local[v as usize] == u32::MAX {
+                        local[v as usize] = global_vertices.len() as u32;
+                        global_vertices.push(v);
+                    }
+                    local[v as usize]
+                })
+            })
+            .collect();
+        let vertices = global_vertices.iter().map(|&v| mesh.vertices()[v as usize]).collect();
+        let mut part = TetMesh::new(vertices, local_cells)?;
+
+        for (name, values) in mesh.vertex_attributes().iter() {
+            part.vertex_attributes_mut().insert(name, global_vertices.iter().map(|&v| values[v as usize]).collect())?;
+        }
+        for (name, values) in mesh.cell_attributes().iter() {
+            part.cell_attributes_mut().insert(name, cells.iter().map(|&c| values[c as usize]).collect())?;
+        }
+        part.vertex_attributes_mut().insert("global_id", global_vertices.iter().map(|&v| f64::from(v)).collect())?;
+        part.cell_attributes_mut().insert("global_id", cells.iter().map(|&c| f64::from(c)).collect())?;
+        let ghost = (0..cells.len()).map(|i| if i < self.owned.len() { 0.0 } else { 1.0 }).collect();
+        part.cell_attributes_mut().insert("ghost", ghost)?;
+        Ok(part)
+    }
+}
+
+/// Part index of each cell, from recursive coordinate bisection into
+/// `parts` parts whose sizes differ by at most one cell
+pub fn partition_rcb(mesh: &TetMesh, parts: usize) -> Result<Vec<u32>, &'static str> {
+    if parts == 0 || parts > mesh.cells().len().max(1) {
+        return Err("Part count must be between 1 and the number of cells");
+    }
+
+    let centroids: Vec<[f64; 3]> = mesh
+        .tetrahedra()
+        .map(|t| [0, 1, 2].map(|k| 0.25 * (t[0][k] + t[1][k] + t[2][k] + t[3][k])))
+        .collect();
+    let mut cells: Vec<u32> = (0..mesh.cells().len() as u32).collect();
+    let mut assignment = vec![0; cells.len()];
+    bisect(&centroids, &mut cells, 0, parts, &mut assignment);
+    Ok(assignment)
+}
+
+/// Assign `cells` to parts `first..first + parts`
+fn bisect(centroids: &[[f64; 3]], cells: &mut [u32], first: usize, parts: usize, assignment: &mut [u32]) {
+    if parts == 1 {
+        for &c in cells.iter() {
+            assignment[c as usize] = first as u32;
+        }
+        return;
+    }
+
+    // Widest axis of the centroids' bounding box
+    let (mut lo, mut hi) = ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]);
+    for &c in cells.iter() {
+        for k in 0..3 {
+            lo[k] = lo[k].min(centroids[c as usize][k]);
+            hi[k] = hi[k].max(centroids[c as usize][k]);
+        }
+    }
+    let axis = (0..3).max_by(|&a, &b| (hi[a] - lo[a]).total_cmp(&(hi[b] - lo[b]))).unwrap_or(0);
+
+    // Split the parts in two, and the cells in the same proportion
+    let left_parts = parts / 2;
+    let split = cells.len() * left_parts / parts;
+    cells.select_nth_unstable_by(split, |&a, &b| centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis]));
+    let (left, right) = cells.split_at_mut(split);
+    bisect(centroids, left, first, left_parts, assignment);
+    bisect(centroids, right, first + left_parts, parts - left_parts, assignment);
+}
+
+/// Owned cells and `layers` ghost layers for each part of `assignment`.
+/// One layer is every cell sharing a vertex with the part; each further
+/// layer adds the cells sharing a vertex with the last.
+pub fn parts_with_ghosts(mesh: &TetMesh, assignment: &[u32], layers: usize) -> Result<Vec<Part>, &'static str> {
+    // Check bounds
+    let n = mesh.cells().len();
+    if assignment.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Cells around each vertex, as compressed rows
+    let nv = mesh.vertices().len();
+    let mut offsets = vec![0_usize; nv + 1];
+    for cell in mesh.cells() {
+        for &v in cell {
+            offsets[v as usize + 1] += 1;
+        }
+    }
+    for v in 0..nv {
+        offsets[v + 1] += offsets[v];
+    }
+    let mut fill = offsets.clone();
+    let mut vertex_cells = vec![0_u32; offsets[nv]];
+    for (c, cell) in mesh.cells().iter().enumerate() {
+        for &v in cell {
+            vertex_cells[fill[v as usize]] = c as u32;
+            fill[v as usize] += 1;
+        }
+    }
+
+    let count = assignment.iter().map(|&p| p as usize + 1).max().unwrap_or(0);
+    let mut parts = vec![Part::default(); count];
+    for (c, &p) in assignment.iter().enumerate() {
+        parts[p as usize].owned.push(c as u32);
+    }
+
+    // Grow each part a layer at a time from its frontier
+    let mut seen = vec![usize::MAX; n]; // Part that last reached each cell
+    for (p, part) in parts.iter_mut().enumerate() {
+        for &c in &part.owned {
+            seen[c as usize] = p;
+        }
+        let mut frontier = part.owned.clone();
+        for _ in 0..layers {
+            let mut next = Vec::new();
+            for &c in &frontier {
+                for &v in &mesh.cells()[c as usize] {
+                    for &d in &vertex_cells[offsets[v as usize]..offsets[v as usize + 1]] {
+                        if seen[d as usize] != p {
+                            seen[d as usize] = p;
+                            next.push(d);
+                        }
+                    }
+                }
+            }
+            part.ghosts.extend_from_slice(&next);
+            frontier = next;
+        }
+        part.ghosts.sort_unstable();
+    }
+
+    Ok(parts)
+}
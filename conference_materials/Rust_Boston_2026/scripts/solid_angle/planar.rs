@@ -0,0 +1,182 @@
+//! Planar analogues of the 3D kernels: signed triangle area, the angle
+//! subtended by a segment or polyline, the winding angle of a polygon
+//! around a point, and point-in-polygon.
+//!
+//! The winding angle plays the role of the total solid angle of a closed
+//! mesh: `±2π` for points inside a simple polygon (sign from orientation,
+//! positive for counterclockwise), `0` outside, `2πk` for a polygon winding
+//! `k` times.
+
+use crate::batch::batch_kernel;
+use crate::par::{chunk_len, par_threshold};
+use crate::vec3::{dot, perp_dot, sub};
+use rayon::prelude::*;
+use std::f64::consts::TAU;
+
+/// Signed area of a triangle, positive if counterclockwise
+#[inline]
+pub fn signed_area_triangle_scalar(p0: [f64; 2], p1: [f64; 2], p2: [f64; 2]) -> f64 {
+    0.5 * perp_dot(sub(p1, p0), sub(p2, p0)) // (m^2)
+}
+
+batch_kernel! {
+    /// Vector variant of [signed_area_triangle_scalar]
+    pub fn signed_area_triangle_scalar([p0, p1, p2]: [[f64; 2]; 3]) -> f64
+        => signed_area_triangle, signed_area_triangle_par, signed_area_triangle_vec
+}
+
+/// Signed angle subtended at `origin` by the segment from `p0` to `p1`,
+/// positive if counterclockwise. The 2D analogue of
+/// [crate::tetrahedron::solid_angle_tetrahedron_scalar].
+#[inline]
+pub fn plane_angle_segment(origin: [f64; 2], p0: [f64; 2], p1: [f64; 2]) -> f64 {
+    let (a, b) = (sub(p0, origin), sub(p1, origin)); // (m)
+    libm::atan2(perp_dot(a, b), dot(a, b)) // (rad)
+}
+
+batch_kernel! {
+    /// Vector variant of [plane_angle_segment], each element `[origin, p0, p1]`
+    pub fn plane_angle_segment([origin, p0, p1]: [[f64; 2]; 3]) -> f64
+        => plane_angle_segments, plane_angle_segments_par, plane_angle_segments_vec
+}
+
+/// Total signed angle subtended at `origin` by an open polyline.
+/// As with the solid angle of an open surface, stretches seen from behind
+/// cancel stretches in front, so a wall folding back counts once.
+#[inline]
+pub fn plane_angle_polyline(origin: [f64; 2], polyline: &[[f64; 2]]) -> f64 {
+    polyline.windows(2).map(|w| plane_angle_segment(origin, w[0], w[1])).sum() // (rad)
+}
+
+/// [plane_angle_polyline] of one polyline seen from many origins
+#[inline]
+pub fn plane_angle_polyline_multi_origin(
+    polyline: &[[f64; 2]],
+    origins: &[[f64; 2]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if origins.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = plane_angle_polyline(origins[i], polyline);
+    }
+
+    Ok(())
+}
+
+/// Parallel variant of [plane_angle_polyline_multi_origin]. Falls back to
+/// the serial kernel below [par_threshold] origin-segment pairs.
+pub fn plane_angle_polyline_multi_origin_par(
+    polyline: &[[f64; 2]],
+    origins: &[[f64; 2]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if origins.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if origins.len().saturating_mul(polyline.len()) < par_threshold() {
+        return plane_angle_polyline_multi_origin(polyline, origins, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (origins.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(oc, yc)| plane_angle_polyline_multi_origin(polyline, oc, yc))
+}
+
+/// Total angle swept around `origin` walking the closed polygon `polygon`,
+/// the last vertex connecting back to the first
+#[inline]
+pub fn winding_angle_scalar(origin: [f64; 2], polygon: &[[f64; 2]]) -> f64 {
+    match (polygon.first(), polygon.last()) {
+        (Some(&first), Some(&last)) => plane_angle_polyline(origin, polygon) + plane_angle_segment(origin, last, first),
+        _ => 0.0,
+    }
+}
+
+/// Nonzero-rule containment of `point` in `polygon`.
+/// Undefined for points on the boundary.
+#[inline]
+pub fn point_in_polygon_scalar(point: [f64; 2], polygon: &[[f64; 2]]) -> bool {
+    (winding_angle_scalar(point, polygon) / TAU).round() != 0.0
+}
+
+/// Vector variant of [winding_angle_scalar], over many points
+#[inline]
+pub fn winding_angle(polygon: &[[f64; 2]], points: &[[f64; 2]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if points.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = winding_angle_scalar(points[i], polygon);
+    }
+
+    Ok(())
+}
+
+/// Vector-parallel variant of [winding_angle_scalar]. Falls back to the
+/// serial kernel below [par_threshold] point-edge pairs.
+pub fn winding_angle_par(polygon: &[[f64; 2]], points: &[[f64; 2]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if points.len().saturating_mul(polygon.len()) < par_threshold() {
+        return winding_angle(polygon, points, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (points.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(pc, oc)| winding_angle(polygon, pc, oc))
+}
+
+/// Vector variant of [point_in_polygon_scalar], over many points
+#[inline]
+pub fn point_in_polygon(polygon: &[[f64; 2]], points: &[[f64; 2]], out: &mut [bool]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if points.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = point_in_polygon_scalar(points[i], polygon);
+    }
+
+    Ok(())
+}
+
+/// Vector-parallel variant of [point_in_polygon_scalar]. Falls back to the
+/// serial kernel below [par_threshold] point-edge pairs.
+pub fn point_in_polygon_par(polygon: &[[f64; 2]], points: &[[f64; 2]], out: &mut [bool]) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if points.len().saturating_mul(polygon.len()) < par_threshold() {
+        return point_in_polygon(polygon, points, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (points.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(pc, oc)| point_in_polygon(polygon, pc, oc))
+}
@@ -0,0 +1,330 @@
+//! Quick-look plots, to sanity-check results without leaving Rust:
+//! histograms of per-element results as SVG, and coverage rasters and
+//! functions on the sphere as PNG heatmaps.
+//!
+//! Heatmaps map values linearly onto viridis between the two ends of a
+//! range, the data's own extremes unless one is given, and clamp outside
+//! it. NaN, and the corners outside a Mollweide ellipse, are transparent.
+//!
+//! [raster_sphere] draws a function of direction over the whole sphere,
+//! such as the solid angle a mesh subtends, a horizon mask or an antenna
+//! pattern, in one of two [Projection]s, in the axes of
+//! [crate::spherical]: `z` up, longitude 0 along `x` at the center, east
+//! to the right. In the equal-area Mollweide projection a region covers
+//! a share of the ellipse's pixels equal to its share of `4π` sr; in the
+//! equirectangular one a pixel covers `cos(latitude)` times the solid
+//! angle of one on the equator.
+//!
+//! The PNGs are 8-bit RGBA, one deflated IDAT, no filtering: small
+//! enough for plots, and readable by anything.
+#![cfg(feature = "plot")]
+
+use crate::raster::Raster;
+use crate::stats::Stats;
+use rayon::prelude::*;
+use std::f64::consts::{PI, SQRT_2};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+/// Viridis at nine evenly spaced stops, interpolated linearly between
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+/// Newton iterations for the Mollweide auxiliary angle; converges to
+/// machine precision well within this away from the poles
+const MOLLWEIDE_ITERATIONS: usize = 16;
+
+/// RGBA pixels, row-major from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    /// All transparent
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![[0; 4]; width * height] }
+    }
+
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> [u8; 4] {
+        self.pixels[row * self.width + col]
+    }
+
+    /// Write as a PNG
+    pub fn write_png<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        // Check bounds
+        if self.pixels.len() != self.width * self.height {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Dimension mismatch"));
+        }
+        let (width, height) = (u32::try_from(self.width), u32::try_from(self.height));
+        let (Ok(width @ 1..), Ok(height @ 1..)) = (width, height) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "PNG must be 1 to 2^32 - 1 pixels a side"));
+        };
+
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+        let mut header = Vec::with_capacity(13);
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend([8, 6, 0, 0, 0]); // 8-bit RGBA, deflate, no filter, no interlace
+        chunk(w, b"IHDR", &header)?;
+
+        let mut z = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        for row in self.pixels.chunks(self.width) {
+            z.write_all(&[0])?; // Filter type: none
+            z.write_all(row.as_flattened())?;
+        }
+        chunk(w, b"IDAT", &z.finish()?)?;
+        chunk(w, b"IEND", &[])
+    }
+}
+
+/// One PNG chunk: length, type, data, CRC of type and data
+fn chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large"))?;
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc.finalize().to_be_bytes())
+}
+
+/// Viridis at `t` in `[0, 1]`, clamped outside, transparent for NaN
+pub fn colormap(t: f64) -> [u8; 4] {
+    if t.is_nan() {
+        return [0; 4];
+    }
+    let x = t.clamp(0.0, 1.0) * (VIRIDIS.len() - 1) as f64;
+    let i = (x as usize).min(VIRIDIS.len() - 2);
+    let f = x - i as f64;
+    let [r, g, b] = [0, 1, 2].map(|k| {
+        let (a, b) = (VIRIDIS[i][k] as f64, VIRIDIS[i + 1][k] as f64);
+        (a + f * (b - a)).round() as u8
+    });
+    [r, g, b, 255]
+}
+
+/// Smallest and largest values other than NaN, or `None` if there are
+/// none
+pub fn finite_range(values: &[f64]) -> Option<[f64; 2]> {
+    let [lo, hi] = values.iter().filter(|x| !x.is_nan()).fold([f64::INFINITY, f64::NEG_INFINITY], |[lo, hi], &x| [lo.min(x), hi.max(x)]);
+    (lo <= hi).then_some([lo, hi])
+}
+
+/// `values` as colors over `range`, or their own extremes; a range with
+/// no width puts everything at its middle
+pub fn colors(values: &[f64], range: Option<[f64; 2]>) -> Vec<[u8; 4]> {
+    let Some([lo, hi]) = range.or_else(|| finite_range(values)) else {
+        return vec![[0; 4]; values.len()];
+    };
+    values.iter().map(|&x| colormap(if hi > lo || x.is_nan() { (x - lo) / (hi - lo) } else { 0.5 })).collect()
+}
+
+/// Heatmap of `raster`, north up, each cell `scale` pixels square
+pub fn heatmap(raster: &Raster, range: Option<[f64; 2]>, scale: usize) -> Result<Image, &'static str> {
+    // Check bounds
+    if raster.data.len() != raster.width * raster.height {
+        return Err("Dimension mismatch");
+    }
+    if scale == 0 {
+        return Err("Scale must be at least 1");
+    }
+
+    let cells = colors(&raster.data, range);
+    let mut image = Image::new(raster.width * scale, raster.height * scale);
+    for (row, out) in image.pixels.chunks_mut(image.width).enumerate() {
+        let src = &cells[row / scale * raster.width..][..raster.width];
+        for (col, px) in out.iter_mut().enumerate() {
+            *px = src[col / scale];
+        }
+    }
+    Ok(image)
+}
+
+/// Direction at a point of the Mollweide ellipse, `x` in `[-2√2, 2√2]`
+/// east and `y` in `[-√2, √2]` north, or `None` outside it
+#[inline]
+pub fn mollweide_inverse(x: f64, y: f64) -> Option<[f64; 3]> {
+    if (x / (2.0 * SQRT_2)).powi(2) + (y / SQRT_2).powi(2) > 1.0 {
+        return None;
+    }
+    let theta = (y / SQRT_2).clamp(-1.0, 1.0).asin();
+    let sin_lat = ((2.0 * theta + (2.0 * theta).sin()) / PI).clamp(-1.0, 1.0);
+    let cos_lat = (1.0 - sin_lat * sin_lat).sqrt();
+    let cos_theta = theta.cos();
+    let lon = if cos_theta > 0.0 { (PI * x / (2.0 * SQRT_2 * cos_theta)).clamp(-PI, PI) } else { 0.0 };
+    Some([cos_lat * lon.cos(), cos_lat * lon.sin(), sin_lat])
+}
+
+/// Point of the Mollweide ellipse for a unit direction, the inverse of
+/// [mollweide_inverse]
+#[inline]
+pub fn mollweide_forward(dir: [f64; 3]) -> [f64; 2] {
+    let lat = dir[2].clamp(-1.0, 1.0).asin();
+    let lon = dir[1].atan2(dir[0]);
+
+    // Solve 2θ + sin 2θ = π sin φ by Newton's method
+    let target = PI * lat.sin();
+    let mut theta = lat;
+    for _ in 0..MOLLWEIDE_ITERATIONS {
+        let d = 2.0 + 2.0 * (2.0 * theta).cos();
+        if d <= f64::EPSILON {
+            break; // At a pole, where θ = φ already
+        }
+        theta -= (2.0 * theta + (2.0 * theta).sin() - target) / d;
+    }
+    [2.0 * SQRT_2 / PI * lon * theta.cos(), SQRT_2 * theta.sin()]
+}
+
+/// Map of the whole sphere onto a rectangle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Projection {
+    /// Longitude and latitude as plain `x` and `y`: simple to read, but
+    /// stretched toward the poles
+    Equirectangular,
+    /// Equal area, in an ellipse touching the edges of the image
+    #[default]
+    Mollweide,
+}
+
+impl Projection {
+    /// Direction at `[u, v]` across and down the image, each in `[0, 1]`,
+    /// or `None` off the map
+    #[inline]
+    pub fn direction(self, [u, v]: [f64; 2]) -> Option<[f64; 3]> {
+        match self {
+            Self::Equirectangular => {
+                let (lon, lat) = ((2.0 * u - 1.0) * PI, (0.5 - v) * PI);
+                let ((sin_lon, cos_lon), (sin_lat, cos_lat)) = (lon.sin_cos(), lat.sin_cos());
+                Some([cos_lat * cos_lon, cos_lat * sin_lon, sin_lat])
+            }
+            Self::Mollweide => mollweide_inverse((2.0 * u - 1.0) * 2.0 * SQRT_2, (1.0 - 2.0 * v) * SQRT_2),
+        }
+    }
+
+    /// Where a unit direction falls, `[u, v]` as for [Projection::direction]
+    #[inline]
+    pub fn position(self, dir: [f64; 3]) -> [f64; 2] {
+        match self {
+            Self::Equirectangular => {
+                let (lon, lat) = (dir[1].atan2(dir[0]), dir[2].clamp(-1.0, 1.0).asin());
+                [0.5 + lon / (2.0 * PI), 0.5 - lat / PI]
+            }
+            Self::Mollweide => {
+                let [x, y] = mollweide_forward(dir);
+                [0.5 + x / (4.0 * SQRT_2), 0.5 - y / (2.0 * SQRT_2)]
+            }
+        }
+    }
+}
+
+/// `f` at the center of each pixel of a `width` by `height` map of the
+/// sphere, row-major from the top left, NaN off the map. Rows are
+/// evaluated in parallel.
+pub fn sample_sphere(f: impl Fn([f64; 3]) -> f64 + Sync, width: usize, height: usize, projection: Projection) -> Result<Vec<f64>, &'static str> {
+    // Check bounds
+    if width == 0 || height == 0 {
+        return Err("Image must be at least 1 pixel a side");
+    }
+
+    let mut values = vec![f64::NAN; width * height];
+    values.par_chunks_mut(width).enumerate().for_each(|(row, out)| {
+        let v = (row as f64 + 0.5) / height as f64;
+        for (col, x) in out.iter_mut().enumerate() {
+            let u = (col as f64 + 0.5) / width as f64;
+            *x = projection.direction([u, v]).map_or(f64::NAN, &f);
+        }
+    });
+    Ok(values)
+}
+
+/// Heatmap of `f` over the whole sphere, `width` by `height` pixels in
+/// `projection`, colored over the range of values seen; see
+/// [sample_sphere]
+pub fn raster_sphere(f: impl Fn([f64; 3]) -> f64 + Sync, width: usize, height: usize, projection: Projection) -> Result<Image, &'static str> {
+    let values = sample_sphere(f, width, height, projection)?;
+    Ok(Image { width, height, pixels: colors(&values, None) })
+}
+
+/// [raster_sphere] in Mollweide, `width` pixels wide and half as tall,
+/// colored over `range` or the values seen
+pub fn mollweide(f: impl Fn([f64; 3]) -> f64 + Sync, width: usize, range: Option<[f64; 2]>) -> Result<Image, &'static str> {
+    // Check bounds
+    if width < 2 {
+        return Err("Image must be at least 2 pixels wide");
+    }
+
+    let values = sample_sphere(f, width, width / 2, Projection::Mollweide)?;
+    Ok(Image { width, height: width / 2, pixels: colors(&values, range) })
+}
+
+/// Number as an axis label: fixed point for ordinary magnitudes,
+/// scientific notation for the rest
+fn label(x: f64) -> String {
+    if x == 0.0 {
+        "0".into()
+    } else if (1e-2..1e4).contains(&x.abs()) {
+        format!("{x:.3}")
+    } else {
+        format!("{x:.2e}")
+    }
+}
+
+/// Escape text for SVG
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Histogram of [Stats::histogram] as an SVG document, bars spanning
+/// `[min, max]` with the title and value axis labelled
+pub fn histogram_svg(stats: &Stats, title: &str) -> String {
+    const W: f64 = 640.0;
+    const H: f64 = 400.0;
+    const MARGIN: [f64; 4] = [40.0, 20.0, 50.0, 60.0]; // Top, right, bottom, left
+    let (plot_w, plot_h) = (W - MARGIN[1] - MARGIN[3], H - MARGIN[0] - MARGIN[2]);
+    let peak = stats.histogram.iter().copied().max().unwrap_or(0).max(1);
+    let bar = plot_w / stats.histogram.len().max(1) as f64;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{W}" height="{H}" viewBox="0 0 {W} {H}" font-family="sans-serif" font-size="12">"#);
+    let _ = writeln!(svg, r#"<rect width="{W}" height="{H}" fill="white"/>"#);
+    let _ = writeln!(svg, r#"<text x="{}" y="24" text-anchor="middle" font-size="14">{}</text>"#, W / 2.0, escape(title));
+    for (i, &count) in stats.histogram.iter().enumerate() {
+        let h = plot_h * count as f64 / peak as f64;
+        let (x, y) = (MARGIN[3] + i as f64 * bar, MARGIN[0] + plot_h - h);
+        let _ = writeln!(svg, r##"<rect x="{x:.2}" y="{y:.2}" width="{bar:.2}" height="{h:.2}" fill="#3b518b" stroke="white" stroke-width="0.5"><title>{count}</title></rect>"##);
+    }
+
+    // Axes, with the extremes and peak count
+    let (x0, y0) = (MARGIN[3], MARGIN[0] + plot_h);
+    let _ = writeln!(svg, r#"<path d="M{x0} {} V{y0} H{}" fill="none" stroke="black"/>"#, MARGIN[0], x0 + plot_w);
+    let _ = writeln!(svg, r#"<text x="{x0}" y="{}" text-anchor="start">{}</text>"#, y0 + 18.0, label(stats.min));
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, x0 + plot_w, y0 + 18.0, label(stats.max));
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{peak}</text>"#, x0 - 6.0, MARGIN[0] + 4.0);
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">0</text>"#, x0 - 6.0, y0 + 4.0);
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle">n = {}, NaN = {}, mean = {}, std = {}</text>"#,
+        x0 + plot_w / 2.0,
+        H - 14.0,
+        stats.count,
+        stats.nan,
+        label(stats.mean),
+        label(stats.std)
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,50 @@
+//! Exact geometric predicates, for decisions that must agree everywhere
+//! they are made, like which side of a cutting plane a shared vertex is on.
+//!
+//! Each predicate is the sign of a sum of products. A floating-point
+//! evaluation with a forward error bound settles almost every call; the
+//! rest are redone exactly by splitting every product with
+//! [crate::dd::two_prod] and summing into a nonoverlapping expansion
+//! (Shewchuk 1997, "Adaptive Precision Floating-Point Arithmetic and Fast
+//! Robust Geometric Predicates"), whose sign is that of its largest
+//! component. Exact barring overflow and underflow.
+
+use crate::dd::{two_prod, two_sum};
+use std::cmp::Ordering;
+
+/// Exact sign of the sum of `terms`
+pub fn sign_of_sum<const N: usize>(terms: [f64; N]) -> Ordering {
+    // Grow-Expansion, one term at a time; components stay in increasing
+    // magnitude with no overlapping bits, interspersed with zeros
+    let mut e = [0.0; N];
+    for (m, &b) in terms.iter().enumerate() {
+        let mut q = b;
+        for h in &mut e[..m] {
+            (q, *h) = two_sum(q, *h);
+        }
+        e[m] = q;
+    }
+    e.iter().rev().find(|x| **x != 0.0).map_or(Ordering::Equal, |x| x.total_cmp(&0.0))
+}
+
+/// Exact sign of `normal · (x - point)`: which side of the plane through
+/// `point` with normal `normal` the point `x` is on
+pub fn plane_side(point: [f64; 3], normal: [f64; 3], x: [f64; 3]) -> Ordering {
+    // Filter: with `m` the sum of the products' magnitudes, the naive sum
+    // of six products is within about 3 ε m of the exact value
+    let (mut d, mut m) = (0.0, 0.0);
+    for k in 0..3 {
+        d += normal[k] * x[k] - normal[k] * point[k];
+        m += (normal[k] * x[k]).abs() + (normal[k] * point[k]).abs();
+    }
+    if d.abs() > 4.0 * f64::EPSILON * m {
+        return d.total_cmp(&0.0);
+    }
+
+    let mut terms = [0.0; 12];
+    for k in 0..3 {
+        (terms[4 * k], terms[4 * k + 1]) = two_prod(normal[k], x[k]);
+        (terms[4 * k + 2], terms[4 * k + 3]) = two_prod(-normal[k], point[k]);
+    }
+    sign_of_sum(terms)
+}
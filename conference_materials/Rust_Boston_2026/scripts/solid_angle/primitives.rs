@@ -0,0 +1,151 @@
+//! Ready-made closed meshes, wound outward: the five Platonic solids and
+//! the usual primitives, for tests, demos and occluders that don't need
+//! a CAD file.
+//!
+//! The Platonic solids are [crate::closed_form]'s, moved and scaled to a
+//! circumradius. Cylinders, cones, capsules and spheres are profiles
+//! revolved about an axis in `segments` steps, their caps fanned from a
+//! vertex on the axis; curved profiles take `rings` steps per quarter
+//! turn. Their flat faces are not subdivided: a mesh this coarse is exact
+//! for solid angles, whatever it would do for shading.
+
+use crate::closed_form::{self, SpecialCase};
+use crate::mesh::TriMesh;
+use crate::sampling::frame;
+use crate::vec3::{norm, sub};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// A family's mesh with its circumradius scaled to `radius`, about `center`
+fn platonic<const V: usize, const F: usize>(case: SpecialCase<V, F>, center: [f64; 3], radius: f64) -> TriMesh {
+    let vertices = case.mesh.vertices();
+    let scale = radius / vertices.iter().map(|&v| norm(v)).fold(0.0, f64::max);
+    let vertices = vertices.iter().map(|v| std::array::from_fn(|k| center[k] + scale * v[k])).collect();
+    TriMesh::new(vertices, case.mesh.faces().to_vec()).expect("Families only reference their own vertices")
+}
+
+/// Regular tetrahedron of circumradius `radius`: 4 faces
+pub fn regular_tetrahedron(center: [f64; 3], radius: f64) -> TriMesh {
+    platonic(closed_form::regular_tetrahedron(), center, radius)
+}
+
+/// Octahedron of circumradius `radius`, vertices on the axes: 8 faces
+pub fn octahedron(center: [f64; 3], radius: f64) -> TriMesh {
+    platonic(closed_form::octahedron(), center, radius)
+}
+
+/// Cube of circumradius `radius`, edges along the axes: 12 faces, two to
+/// a square
+pub fn cube(center: [f64; 3], radius: f64) -> TriMesh {
+    platonic(closed_form::cube(), center, radius)
+}
+
+/// Icosahedron of circumradius `radius`: 20 faces
+pub fn icosahedron(center: [f64; 3], radius: f64) -> TriMesh {
+    platonic(closed_form::icosahedron(), center, radius)
+}
+
+/// Dodecahedron of circumradius `radius`: 60 faces, five to a pentagon,
+/// fanned from its centre
+pub fn dodecahedron(center: [f64; 3], radius: f64) -> TriMesh {
+    platonic(closed_form::dodecahedron(), center, radius)
+}
+
+/// Axis-aligned box from corner `lo` to `hi`: 12 faces
+pub fn cuboid(lo: [f64; 3], hi: [f64; 3]) -> Result<TriMesh, &'static str> {
+    if (0..3).any(|k| lo[k] >= hi[k]) {
+        return Err("Box corners out of order");
+    }
+    let vertices = (0..8).map(|i: usize| std::array::from_fn(|k| if i & (1 << k) == 0 { lo[k] } else { hi[k] })).collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]]).collect();
+    TriMesh::new(vertices, faces)
+}
+
+/// Closed cylinder of `radius` from the centre of one end, `a`, to the
+/// other, `b`: `4 segments` faces
+pub fn cylinder(a: [f64; 3], b: [f64; 3], radius: f64, segments: u32) -> Result<TriMesh, &'static str> {
+    let h = norm(sub(b, a));
+    revolve(a, b, &[[0.0, 0.0], [radius, 0.0], [radius, h], [0.0, h]], segments)
+}
+
+/// Cone of `radius` from the centre of its base, `base`, to `apex`:
+/// `2 segments` faces
+pub fn cone(base: [f64; 3], apex: [f64; 3], radius: f64, segments: u32) -> Result<TriMesh, &'static str> {
+    let h = norm(sub(apex, base));
+    revolve(base, apex, &[[0.0, 0.0], [radius, 0.0], [0.0, h]], segments)
+}
+
+/// Capsule of `radius` about the segment from `a` to `b`, hemispheres at
+/// either end: `4 segments rings` faces
+pub fn capsule(a: [f64; 3], b: [f64; 3], radius: f64, segments: u32, rings: u32) -> Result<TriMesh, &'static str> {
+    let h = norm(sub(b, a));
+    if rings < 1 {
+        return Err("Too few rings");
+    }
+    if h == 0.0 {
+        return Err("Degenerate axis");
+    }
+    let quarter = |i: u32| FRAC_PI_2 * f64::from(i) / f64::from(rings);
+    let bottom = (0..=rings).map(|i| [radius * quarter(i).sin(), radius * (1.0 - quarter(i).cos())]);
+    let top = (0..=rings).map(|i| [radius * quarter(i).cos(), radius + h + radius * quarter(i).sin()]);
+    let profile: Vec<[f64; 2]> = bottom.chain(top).collect();
+    let reach = radius / h;
+    revolve(std::array::from_fn(|k| a[k] - reach * (b[k] - a[k])), std::array::from_fn(|k| b[k] + reach * (b[k] - a[k])), &profile, segments)
+}
+
+/// Sphere of `radius` about `center`, in `2 rings` latitudes and
+/// `segments` longitudes: `2 segments (2 rings - 1)` faces
+pub fn uv_sphere(center: [f64; 3], radius: f64, segments: u32, rings: u32) -> Result<TriMesh, &'static str> {
+    if rings < 1 {
+        return Err("Too few rings");
+    }
+    let profile: Vec<[f64; 2]> = (0..=2 * rings)
+        .map(|i| {
+            let theta = FRAC_PI_2 * f64::from(i) / f64::from(rings);
+            [radius * theta.sin(), radius * (1.0 - theta.cos())]
+        })
+        .collect();
+    let [x, y, z] = center;
+    revolve([x, y, z - radius], [x, y, z + radius], &profile, segments)
+}
+
+/// Revolve `profile`, `[r, z]` from the axis at `from` to the axis at
+/// `to`, about the line between them. `z` runs along it from `from`, and
+/// each end of the profile must be on the axis, `r = 0`.
+fn revolve(from: [f64; 3], to: [f64; 3], profile: &[[f64; 2]], segments: u32) -> Result<TriMesh, &'static str> {
+    // Check bounds
+    if segments < 3 {
+        return Err("Too few segments");
+    }
+    let axis = sub(to, from);
+    let len = norm(axis);
+    if len == 0.0 || !len.is_finite() {
+        return Err("Degenerate axis");
+    }
+    let rings = &profile[1..profile.len() - 1];
+    if rings.iter().any(|p| p[0] <= 0.0 || p[0].is_nan()) {
+        return Err("Nonpositive radius");
+    }
+    let [s, t, n] = frame(axis.map(|x| x / len));
+
+    // Poles at either end, rings of `segments` between them
+    let mut vertices = Vec::with_capacity(2 + rings.len() * segments as usize);
+    let at = |r: f64, phi: f64, z: f64| std::array::from_fn(|k| from[k] + r * (phi.cos() * s[k] + phi.sin() * t[k]) + z * n[k]);
+    vertices.push(at(0.0, 0.0, profile[0][1]));
+    for &[r, z] in rings {
+        vertices.extend((0..segments).map(|j| at(r, TAU * f64::from(j) / f64::from(segments), z)));
+    }
+    vertices.push(at(0.0, 0.0, profile[profile.len() - 1][1]));
+
+    // Caps fanned from the poles, quads between rings, all counter-clockwise
+    // seen from outside
+    let (m, top) = (rings.len() as u32, vertices.len() as u32 - 1);
+    let id = |ring: u32, j: u32| 1 + ring * segments + j % segments;
+    let mut faces = Vec::with_capacity(2 * segments as usize * rings.len());
+    faces.extend((0..segments).map(|j| [0, id(0, j + 1), id(0, j)]));
+    for ring in 0..m - 1 {
+        faces.extend((0..segments).flat_map(|j| [[id(ring, j), id(ring, j + 1), id(ring + 1, j + 1)], [id(ring, j), id(ring + 1, j + 1), id(ring + 1, j)]]));
+    }
+    faces.extend((0..segments).map(|j| [id(m - 1, j), id(m - 1, j + 1), top]));
+    TriMesh::new(vertices, faces)
+}
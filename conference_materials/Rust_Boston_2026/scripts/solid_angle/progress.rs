@@ -0,0 +1,96 @@
+//! Progress and per-stage timing for long runs.
+//!
+//! With the `progress` feature, an `indicatif` bar on stderr shows the
+//! current stage, elements per second and the ETA. Without it, only the
+//! stage timings are kept, so callers need no `cfg` of their own.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Elements per progress update in [Progress::run_chunked]
+pub const DEFAULT_PROGRESS_CHUNK: usize = 1 << 16;
+
+/// Progress of one run through named stages
+pub struct Progress {
+    #[cfg(feature = "progress")]
+    bar: indicatif::ProgressBar,
+    stages: Vec<(String, Duration)>,
+    current: Option<(String, Instant)>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "progress")]
+            bar: indicatif::ProgressBar::new(0).with_style(
+                indicatif::ProgressStyle::with_template(
+                    "{msg:>12} [{bar:40}] {human_pos}/{human_len} {per_sec} ETA {eta}",
+                )
+                .expect("Template is valid")
+                .progress_chars("=> "),
+            ),
+            stages: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// End the current stage and start one named `name` over `len`
+    /// elements, or zero for a stage not counted in elements
+    pub fn stage(&mut self, name: &str, len: u64) {
+        self.end_stage();
+        #[cfg(feature = "progress")]
+        {
+            self.bar.reset();
+            self.bar.set_length(len);
+            self.bar.set_message(name.to_owned());
+        }
+        #[cfg(not(feature = "progress"))]
+        let _ = len;
+        self.current = Some((name.to_owned(), Instant::now()));
+    }
+
+    /// Count `n` more elements done in the current stage
+    #[inline]
+    pub fn inc(&self, n: u64) {
+        #[cfg(feature = "progress")]
+        self.bar.inc(n);
+        #[cfg(not(feature = "progress"))]
+        let _ = n;
+    }
+
+    /// Run `f` over `0..n` in chunks of `chunk`, counting each one done
+    pub fn run_chunked<E>(
+        &self,
+        n: usize,
+        chunk: usize,
+        mut f: impl FnMut(Range<usize>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let chunk = chunk.max(1);
+        for start in (0..n).step_by(chunk) {
+            let end = n.min(start + chunk);
+            f(start..end)?;
+            self.inc((end - start) as u64);
+        }
+        Ok(())
+    }
+
+    fn end_stage(&mut self) {
+        if let Some((name, start)) = self.current.take() {
+            self.stages.push((name, start.elapsed()));
+        }
+    }
+
+    /// End the run, clearing the bar, and return each stage's wall time
+    pub fn finish(mut self) -> Vec<(String, Duration)> {
+        self.end_stage();
+        #[cfg(feature = "progress")]
+        self.bar.finish_and_clear();
+        self.stages
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,429 @@
+//! Typed quantities over the `f64` API: lengths in; solid angles, areas
+//! and volumes out. A CAD part in millimetres placed in a room in metres
+//! otherwise runs without complaint and gives view factors and coverage
+//! for a part a thousand times too big.
+//!
+//! Each quantity holds its value in SI units (m, m², m³, sr), so unit
+//! conversions happen once, at construction and at [Length::to] and the
+//! like, and arithmetic between quantities only type-checks where the
+//! dimensions agree: lengths multiply into areas, areas into volumes,
+//! and like divided by like is a plain ratio.
+//!
+//! [UnitMesh] is a [TriMesh] tagged with the unit its coordinates are in.
+//! Its queries take origins as [Length]s and convert them into the
+//! mesh's unit, so the mesh itself is never rescaled; with everything in
+//! metres, results are the raw functions' bit for bit.
+//!
+//! A [SolidAngle] prints as a [crate::units::Sr] does, in steradians,
+//! square degrees and percent of the sphere, or in one of them with
+//! [SolidAngle::in_unit].
+
+use crate::mesh::TriMesh;
+use crate::multi_origin::solid_angles_multi_origin;
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::thermal;
+use crate::units::{InUnit, SolidAngleUnit, Sr};
+use crate::vec3::{cross, dot, norm, sub};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Units of length, and their squares and cubes for areas and volumes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Metre,
+    Millimetre,
+    Centimetre,
+    Kilometre,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    /// Metres in one of this unit
+    pub const fn metres(self) -> f64 {
+        match self {
+            Self::Metre => 1.0,
+            Self::Millimetre => 1e-3,
+            Self::Centimetre => 1e-2,
+            Self::Kilometre => 1e3,
+            Self::Inch => 0.0254,
+            Self::Foot => 0.3048,
+        }
+    }
+
+    /// Point with coordinates in this unit
+    pub fn point(self, p: [f64; 3]) -> [Length; 3] {
+        p.map(|x| Length::new(x, self))
+    }
+}
+
+/// Arithmetic shared by every quantity: sums and differences of like
+/// quantities, scaling by plain numbers, and ratios of like quantities
+macro_rules! quantity {
+    ($name:ident) => {
+        impl $name {
+            pub const ZERO: Self = Self(0.0);
+
+            /// Value in SI units
+            #[inline]
+            pub const fn from_si(x: f64) -> Self {
+                Self(x)
+            }
+
+            /// Value in SI units
+            #[inline]
+            pub const fn si(self) -> f64 {
+                self.0
+            }
+
+            #[inline]
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl SubAssign for $name {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Mul<f64> for $name {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: f64) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl Mul<$name> for f64 {
+            type Output = $name;
+
+            #[inline]
+            fn mul(self, rhs: $name) -> $name {
+                $name(self * rhs.0)
+            }
+        }
+
+        impl Div<f64> for $name {
+            type Output = Self;
+
+            #[inline]
+            fn div(self, rhs: f64) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl Div for $name {
+            type Output = f64;
+
+            #[inline]
+            fn div(self, rhs: Self) -> f64 {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                Self(iter.map(|q| q.0).sum())
+            }
+        }
+    };
+}
+
+/// Length, held in metres
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Length(f64);
+
+/// Area, held in square metres
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Area(f64);
+
+/// Volume, held in cubic metres
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Volume(f64);
+
+/// Solid angle, held in steradians
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct SolidAngle(f64);
+
+quantity!(Length);
+quantity!(Area);
+quantity!(Volume);
+quantity!(SolidAngle);
+
+impl Length {
+    #[inline]
+    pub fn new(x: f64, unit: LengthUnit) -> Self {
+        Self(x * unit.metres())
+    }
+
+    /// Value in `unit`
+    #[inline]
+    pub fn to(self, unit: LengthUnit) -> f64 {
+        self.0 / unit.metres()
+    }
+}
+
+impl Area {
+    /// `x` square `unit`s
+    #[inline]
+    pub fn new(x: f64, unit: LengthUnit) -> Self {
+        Self(x * unit.metres() * unit.metres())
+    }
+
+    /// Value in square `unit`s
+    #[inline]
+    pub fn to(self, unit: LengthUnit) -> f64 {
+        self.0 / (unit.metres() * unit.metres())
+    }
+}
+
+impl Volume {
+    /// `x` cubic `unit`s
+    #[inline]
+    pub fn new(x: f64, unit: LengthUnit) -> Self {
+        Self(x * unit.metres().powi(3))
+    }
+
+    /// Value in cubic `unit`s
+    #[inline]
+    pub fn to(self, unit: LengthUnit) -> f64 {
+        self.0 / unit.metres().powi(3)
+    }
+}
+
+impl SolidAngle {
+    #[inline]
+    pub const fn from_sr(x: f64) -> Self {
+        Self(x)
+    }
+
+    #[inline]
+    pub const fn sr(self) -> f64 {
+        self.0
+    }
+
+    /// Shown in `unit` alone, as [Sr::in_unit]
+    #[inline]
+    pub fn in_unit(self, unit: SolidAngleUnit) -> InUnit {
+        Sr::from(self).in_unit(unit)
+    }
+}
+
+impl From<SolidAngle> for Sr {
+    #[inline]
+    fn from(q: SolidAngle) -> Self {
+        Sr(q.0)
+    }
+}
+
+impl From<Sr> for SolidAngle {
+    #[inline]
+    fn from(sr: Sr) -> Self {
+        SolidAngle(sr.0)
+    }
+}
+
+/// As [Sr]'s, precision included
+impl fmt::Display for SolidAngle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&Sr::from(*self), f)
+    }
+}
+
+impl Mul for Length {
+    type Output = Area;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Area {
+        Area(self.0 * rhs.0)
+    }
+}
+
+impl Mul<Length> for Area {
+    type Output = Volume;
+
+    #[inline]
+    fn mul(self, rhs: Length) -> Volume {
+        Volume(self.0 * rhs.0)
+    }
+}
+
+impl Mul<Area> for Length {
+    type Output = Volume;
+
+    #[inline]
+    fn mul(self, rhs: Area) -> Volume {
+        Volume(self.0 * rhs.0)
+    }
+}
+
+impl Div<Length> for Area {
+    type Output = Length;
+
+    #[inline]
+    fn div(self, rhs: Length) -> Length {
+        Length(self.0 / rhs.0)
+    }
+}
+
+impl Div<Length> for Volume {
+    type Output = Area;
+
+    #[inline]
+    fn div(self, rhs: Length) -> Area {
+        Area(self.0 / rhs.0)
+    }
+}
+
+impl Div<Area> for Volume {
+    type Output = Length;
+
+    #[inline]
+    fn div(self, rhs: Area) -> Length {
+        Length(self.0 / rhs.0)
+    }
+}
+
+/// Solid angle of triangle `tri` seen from `origin`, signed by winding as
+/// [solid_angle_tetrahedron_scalar]
+#[inline]
+pub fn solid_angle_triangle(origin: [Length; 3], tri: [[Length; 3]; 3]) -> SolidAngle {
+    let m = |p: [Length; 3]| p.map(Length::si);
+    SolidAngle(solid_angle_tetrahedron_scalar(m(origin), m(tri[0]), m(tri[1]), m(tri[2])))
+}
+
+/// A [TriMesh] whose coordinates are in a known unit; see the module
+/// docs
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitMesh {
+    mesh: TriMesh,
+    unit: LengthUnit,
+}
+
+impl UnitMesh {
+    #[inline]
+    pub fn new(mesh: TriMesh, unit: LengthUnit) -> Self {
+        Self { mesh, unit }
+    }
+
+    #[inline]
+    pub fn mesh(&self) -> &TriMesh {
+        &self.mesh
+    }
+
+    #[inline]
+    pub fn unit(&self) -> LengthUnit {
+        self.unit
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (TriMesh, LengthUnit) {
+        (self.mesh, self.unit)
+    }
+
+    #[inline]
+    pub fn vertex(&self, i: usize) -> [Length; 3] {
+        self.unit.point(self.mesh.vertices()[i])
+    }
+
+    /// The same mesh with its coordinates in `unit`; attributes are kept
+    /// as they are
+    pub fn to(&self, unit: LengthUnit) -> Self {
+        let mut mesh = self.mesh.clone();
+        if unit != self.unit {
+            let scale = self.unit.metres() / unit.metres();
+            mesh.vertices_mut().iter_mut().for_each(|v| *v = v.map(|x| x * scale));
+        }
+        Self { mesh, unit }
+    }
+
+    /// Concatenation of `parts`, each converted to `unit`, as one mesh for
+    /// view factors and other whole-assembly queries. Attributes are
+    /// dropped.
+    pub fn concat(parts: &[UnitMesh], unit: LengthUnit) -> Result<Self, &'static str> {
+        let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+        for part in parts {
+            let scale = part.unit.metres() / unit.metres();
+            let base = vertices.len() as u32;
+            faces.extend(part.mesh.faces().iter().map(|f| f.map(|v| v + base)));
+            vertices.extend(part.mesh.vertices().iter().map(|v| v.map(|x| x * scale)));
+        }
+        Ok(Self { mesh: TriMesh::new(vertices, faces)?, unit })
+    }
+
+    /// Area of each face
+    pub fn face_areas(&self) -> Vec<Area> {
+        let scale = self.unit.metres() * self.unit.metres();
+        self.mesh.triangles().map(|[a, b, c]| Area(0.5 * norm(cross(sub(b, a), sub(c, a))) * scale)).collect()
+    }
+
+    /// Total area of the faces
+    pub fn area(&self) -> Area {
+        self.face_areas().into_iter().sum()
+    }
+
+    /// Enclosed volume of a closed mesh, by the divergence theorem;
+    /// positive when wound outward
+    pub fn volume(&self) -> Volume {
+        let raw: f64 = self.mesh.triangles().map(|[a, b, c]| dot(a, cross(b, c)) / 6.0).sum();
+        Volume::new(raw, self.unit)
+    }
+
+    /// Total solid angle subtended at each origin, as
+    /// [solid_angles_multi_origin]
+    pub fn solid_angles(&self, origins: &[[Length; 3]]) -> Result<Vec<SolidAngle>, &'static str> {
+        let local: Vec<[f64; 3]> = origins.iter().map(|p| p.map(|x| x.to(self.unit))).collect();
+        let mut out = vec![0.0; origins.len()];
+        solid_angles_multi_origin(&self.mesh, &local, &mut out)?;
+        Ok(out.into_iter().map(SolidAngle).collect())
+    }
+
+    /// View factor matrix between the faces, as [thermal::view_factors].
+    /// The factors are ratios and need no unit, but every face must be in
+    /// the same one: assemble parts with [UnitMesh::concat] first.
+    pub fn view_factors(&self) -> Result<Vec<f64>, &'static str> {
+        let n = self.mesh.faces().len();
+        let mut out = vec![0.0; n * n];
+        thermal::view_factors(&self.mesh, &mut out)?;
+        Ok(out)
+    }
+}
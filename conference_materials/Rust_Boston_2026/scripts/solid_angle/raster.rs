@@ -0,0 +1,292 @@
+//! Single-band rasters on a north-up grid: elevation models in, sky view
+//! factor out.
+//!
+//! Row 0 is the northernmost; `origin` is the north-west corner of the
+//! grid and cells are `cell` square, so cell `(row, col)` is centered at
+//! `x = origin[0] + (col + 0.5) cell`, `y = origin[1] - (row + 0.5) cell`.
+//! That is the affine transform GeoTIFF stores, and with the `geotiff`
+//! feature [write_geotiff] and [read_geotiff] move rasters in and out of
+//! GIS tools. They cover the plain case only: one band, float samples,
+//! uncompressed strips, little-endian; anything else is rejected with
+//! `InvalidData`. NaN marks cells with no data.
+
+use crate::bvh::Bvh;
+use crate::horizon::sky_view_factor;
+use crate::mesh::TriMesh;
+use rayon::prelude::*;
+
+/// Side of the square tiles [sky_view_factor_raster] hands to each thread
+pub const TILE: usize = 32;
+
+/// See the module docs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    /// North-west corner of the grid
+    pub origin: [f64; 2],
+    pub cell: f64,
+    /// Row-major from the north-west, NaN for no data
+    pub data: Vec<f64>,
+}
+
+impl Raster {
+    /// All NaN, on the grid given
+    pub fn new(width: usize, height: usize, origin: [f64; 2], cell: f64) -> Self {
+        Self { width, height, origin, cell, data: vec![f64::NAN; width * height] }
+    }
+
+    /// Same grid as `self`, all NaN
+    pub fn like(&self) -> Self {
+        Self::new(self.width, self.height, self.origin, self.cell)
+    }
+
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.width + col]
+    }
+
+    /// Cell center, east and north
+    #[inline]
+    pub fn center(&self, row: usize, col: usize) -> [f64; 2] {
+        [self.origin[0] + (col as f64 + 0.5) * self.cell, self.origin[1] - (row as f64 + 0.5) * self.cell]
+    }
+}
+
+/// Terrain surface of an elevation model: a vertex at each cell center,
+/// two faces per square of four, none touching a NaN
+pub fn dem_mesh(dem: &Raster) -> Result<TriMesh, &'static str> {
+    // Check bounds
+    if dem.data.len() != dem.width * dem.height {
+        return Err("Dimension mismatch");
+    }
+
+    let vertices = (0..dem.height)
+        .flat_map(|row| (0..dem.width).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let [x, y] = dem.center(row, col);
+            [x, y, dem.get(row, col)]
+        })
+        .collect();
+    let id = |row: usize, col: usize| (row * dem.width + col) as u32;
+    let faces = (1..dem.height)
+        .flat_map(|row| (1..dem.width).map(move |col| (row, col)))
+        .filter(|&(row, col)| [(row - 1, col - 1), (row - 1, col), (row, col - 1), (row, col)].iter().all(|&(r, c)| !dem.get(r, c).is_nan()))
+        .flat_map(|(row, col)| {
+            let (nw, ne, sw, se) = (id(row - 1, col - 1), id(row - 1, col), id(row, col - 1), id(row, col));
+            [[sw, se, ne], [sw, ne, nw]] // Counterclockwise seen from above
+        })
+        .collect();
+    TriMesh::new(vertices, faces)
+}
+
+/// Sky view factor at every cell of `dem`, seen from `observer_height`
+/// above its center over `azimuth_bins` bins; see
+/// [crate::horizon::sky_view_factor]. NaN cells stay NaN. A height of
+/// zero is the ground itself, the slope of the cells around counting
+/// toward the mask.
+///
+/// Threads take [TILE]-square tiles rather than rows: neighbouring
+/// observers cast through the same part of the BVH, so a tile keeps it in
+/// cache where a long row would sweep through all of it.
+pub fn sky_view_factor_raster(dem: &Raster, observer_height: f64, azimuth_bins: usize) -> Result<Raster, &'static str> {
+    let terrain = dem_mesh(dem)?;
+    let bvh = Bvh::new(&terrain);
+
+    let tiles: Vec<[usize; 2]> = (0..dem.height.div_ceil(TILE))
+        .flat_map(|r| (0..dem.width.div_ceil(TILE)).map(move |c| [r * TILE, c * TILE]))
+        .collect();
+    let done = tiles
+        .par_iter()
+        .map(|&[row0, col0]| {
+            let cells = (row0..(row0 + TILE).min(dem.height)).flat_map(|row| (col0..(col0 + TILE).min(dem.width)).map(move |col| (row, col)));
+            cells
+                .map(|(row, col)| {
+                    let z = dem.get(row, col);
+                    if z.is_nan() {
+                        return Ok(f64::NAN);
+                    }
+                    let [x, y] = dem.center(row, col);
+                    sky_view_factor([x, y, z + observer_height], &terrain, &bvh, azimuth_bins)
+                })
+                .collect::<Result<Vec<f64>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Scatter the tiles back into rows
+    let mut svf = dem.like();
+    for (&[row0, col0], tile) in tiles.iter().zip(&done) {
+        let cols = (col0 + TILE).min(dem.width) - col0;
+        for (i, values) in tile.chunks_exact(cols).enumerate() {
+            let start = (row0 + i) * dem.width + col0;
+            svf.data[start..start + cols].copy_from_slice(values);
+        }
+    }
+    Ok(svf)
+}
+
+#[cfg(feature = "geotiff")]
+use std::io::{self, Read, Write};
+
+#[cfg(feature = "geotiff")]
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Write `raster` as a GeoTIFF: float64, one strip, the grid as pixel
+/// scale and tiepoint, pixel-is-area. No CRS is recorded; GIS tools will
+/// ask for one.
+#[cfg(feature = "geotiff")]
+pub fn write_geotiff<W: Write>(w: &mut W, raster: &Raster) -> io::Result<()> {
+    // Check bounds
+    if raster.data.len() != raster.width * raster.height {
+        return Err(invalid("Dimension mismatch"));
+    }
+
+    // Header, directory, the directory's out-of-line values, then pixels
+    const ENTRIES: usize = 14;
+    let extra = 8 + 2 + 12 * ENTRIES + 4;
+    let (scale, tiepoint, keys) = (extra, extra + 24, extra + 24 + 48);
+    let pixels = keys + 16;
+    let bytes = 8 * raster.data.len();
+    let (Ok(width), Ok(height), Ok(_)) = (u32::try_from(raster.width), u32::try_from(raster.height), u32::try_from(pixels + bytes)) else {
+        return Err(invalid("Raster too large for a classic TIFF"));
+    };
+
+    w.write_all(b"II*\0")?;
+    w.write_all(&8_u32.to_le_bytes())?;
+    w.write_all(&(ENTRIES as u16).to_le_bytes())?;
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+    const DOUBLE: u16 = 12;
+    let entries: [(u16, u16, u32, u32); ENTRIES] = [
+        (256, LONG, 1, width),               // ImageWidth
+        (257, LONG, 1, height),              // ImageLength
+        (258, SHORT, 1, 64),                 // BitsPerSample
+        (259, SHORT, 1, 1),                  // Compression: none
+        (262, SHORT, 1, 1),                  // Photometric: black is zero
+        (273, LONG, 1, pixels as u32),       // StripOffsets
+        (277, SHORT, 1, 1),                  // SamplesPerPixel
+        (278, LONG, 1, height),              // RowsPerStrip
+        (279, LONG, 1, bytes as u32),        // StripByteCounts
+        (284, SHORT, 1, 1),                  // PlanarConfiguration: chunky
+        (339, SHORT, 1, 3),                  // SampleFormat: float
+        (33550, DOUBLE, 3, scale as u32),    // ModelPixelScale
+        (33922, DOUBLE, 6, tiepoint as u32), // ModelTiepoint
+        (34735, SHORT, 8, keys as u32),      // GeoKeyDirectory
+    ];
+    for (tag, kind, count, value) in entries {
+        w.write_all(&tag.to_le_bytes())?;
+        w.write_all(&kind.to_le_bytes())?;
+        w.write_all(&count.to_le_bytes())?;
+        w.write_all(&value.to_le_bytes())?; // SHORTs sit in the low half, as little-endian puts them
+    }
+    w.write_all(&0_u32.to_le_bytes())?; // No next directory
+
+    for x in [raster.cell, raster.cell, 0.0, 0.0, 0.0, 0.0, raster.origin[0], raster.origin[1], 0.0] {
+        w.write_all(&x.to_le_bytes())?;
+    }
+    // Version 1.1.0, one key: GTRasterTypeGeoKey = RasterPixelIsArea
+    for k in [1_u16, 1, 0, 1, 1025, 0, 1, 1] {
+        w.write_all(&k.to_le_bytes())?;
+    }
+
+    // Stage through a fixed buffer instead of one write per element
+    let mut buf = [0_u8; 8 * 1024];
+    for chunk in raster.data.chunks(1024) {
+        for (b, x) in buf.chunks_exact_mut(8).zip(chunk) {
+            b.copy_from_slice(&x.to_le_bytes());
+        }
+        w.write_all(&buf[..8 * chunk.len()])?;
+    }
+
+    Ok(())
+}
+
+/// Read a GeoTIFF of the kind [write_geotiff] writes, or GDAL's default
+/// for a float32 or float64 band: any number of strips, the grid from
+/// pixel scale and tiepoint (unit cells at the origin without them).
+#[cfg(feature = "geotiff")]
+pub fn read_geotiff<R: Read>(r: &mut R) -> io::Result<Raster> {
+    let mut file = Vec::new();
+    r.read_to_end(&mut file)?;
+    let bytes = |at: usize, n: usize| file.get(at..at + n).ok_or(invalid("Truncated TIFF"));
+    let u16_at = |at: usize| Ok::<_, io::Error>(u16::from_le_bytes(bytes(at, 2)?.try_into().unwrap()));
+    let u32_at = |at: usize| Ok::<_, io::Error>(u32::from_le_bytes(bytes(at, 4)?.try_into().unwrap()));
+    let f64_at = |at: usize| Ok::<_, io::Error>(f64::from_le_bytes(bytes(at, 8)?.try_into().unwrap()));
+
+    if bytes(0, 4)? != b"II*\0" {
+        return Err(invalid("Not a little-endian classic TIFF"));
+    }
+
+    // Directory entries: (tag, type, count, where the values start)
+    let ifd = u32_at(4)? as usize;
+    let entries = (0..u16_at(ifd)? as usize)
+        .map(|i| {
+            let at = ifd + 2 + 12 * i;
+            let (tag, kind, count) = (u16_at(at)?, u16_at(at + 2)?, u32_at(at + 4)? as usize);
+            let size = match kind {
+                3 => 2,
+                4 => 4,
+                12 => 8,
+                _ => 1,
+            };
+            let values = if size * count <= 4 { at + 8 } else { u32_at(at + 8)? as usize };
+            Ok((tag, kind, count, values))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let find = |tag: u16| entries.iter().find(|e| e.0 == tag).copied();
+    let ints = |tag: u16| -> io::Result<Vec<usize>> {
+        let Some((_, kind, count, at)) = find(tag) else {
+            return Err(invalid("Missing TIFF tag"));
+        };
+        (0..count)
+            .map(|i| match kind {
+                3 => Ok(u16_at(at + 2 * i)? as usize),
+                4 => Ok(u32_at(at + 4 * i)? as usize),
+                _ => Err(invalid("Unsupported TIFF tag type")),
+            })
+            .collect()
+    };
+    let int = |tag: u16, default: usize| match find(tag) {
+        Some(_) => Ok(ints(tag)?[0]),
+        None => Ok::<_, io::Error>(default),
+    };
+
+    // Only the plain case
+    let (width, height) = (int(256, 0)?, int(257, 0)?);
+    let bits = int(258, 1)?;
+    if int(259, 1)? != 1 || int(277, 1)? != 1 || int(339, 1)? != 3 || !(bits == 32 || bits == 64) || find(322).is_some() {
+        return Err(invalid("Only uncompressed single-band float strips are supported"));
+    }
+
+    // Grid, from the first tiepoint and the pixel scale
+    let doubles = |tag: u16, n: usize| match find(tag) {
+        Some((_, 12, count, at)) if count >= n => (0..n).map(|i| f64_at(at + 8 * i)).collect::<io::Result<Vec<_>>>().map(Some),
+        Some(_) => Err(invalid("Malformed GeoTIFF grid")),
+        None => Ok(None),
+    };
+    let scale = doubles(33550, 2)?.unwrap_or(vec![1.0, 1.0]);
+    let tie = doubles(33922, 6)?.unwrap_or(vec![0.0; 6]);
+    if scale[0] != scale[1] {
+        return Err(invalid("Only square cells are supported"));
+    }
+    let origin = [tie[3] - tie[0] * scale[0], tie[4] + tie[1] * scale[1]];
+
+    // Samples, strip by strip
+    let n = width.checked_mul(height).ok_or(invalid("Malformed TIFF"))?;
+    let mut data = Vec::with_capacity(n.min(1 << 20)); // Not trusting the header for a huge allocation
+    for (offset, count) in ints(273)?.into_iter().zip(ints(279)?) {
+        let strip = bytes(offset, count)?;
+        match bits {
+            32 => data.extend(strip.chunks_exact(4).map(|b| f64::from(f32::from_le_bytes(b.try_into().unwrap())))),
+            _ => data.extend(strip.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap()))),
+        }
+    }
+    if data.len() < n {
+        return Err(invalid("Truncated TIFF"));
+    }
+    data.truncate(n);
+
+    Ok(Raster { width, height, origin, cell: scale[0], data })
+}
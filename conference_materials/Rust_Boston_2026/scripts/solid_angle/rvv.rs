@@ -0,0 +1,110 @@
+//! Experimental RISC-V vector (RVV 1.0) kernel.
+//!
+//! Stable Rust has no RVV intrinsics, so the vector algebra is inline
+//! assembly. It is strip-mined: `vsetvli` grants however many lanes the
+//! hardware has (`VLEN / 64` per register), strided loads transpose that
+//! many tetrahedra out of the AoS layout, and the loop advances by the
+//! granted length, so one binary runs on any vector length. The `fmacc`
+//! and `fmsac` operands follow the scalar kernel's `mul_add`s, so results
+//! are bit-identical to it; `atan2` runs per element through [crate::math::atan2].
+//!
+//! Behind the `rvv` feature, and only compiled for `riscv64`. Neither
+//! runtime detection of RISC-V extensions nor `cfg(target_feature = "v")`
+//! is stable, so enabling the feature is the promise that the target has
+//! the V extension; the assembly turns it on for itself.
+#![cfg(all(feature = "rvv", target_arch = "riscv64"))]
+
+use std::arch::asm;
+
+/// Most elements per strip, bounding the scratch buffers. Lanes beyond
+/// it on very wide machines (VLEN > 4096) simply go unused.
+const STRIP: usize = 64;
+
+/// Bytes between consecutive tetrahedra, the stride of the transposing loads
+const TET_BYTES: usize = 96;
+
+/// RVV variant of [crate::tetrahedron::solid_angle_tetrahedron]
+pub fn solid_angle_tetrahedron_rvv(tetrahedra: &[[[f64; 3]; 4]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    let (mut triple, mut denom, mut abc) = ([0.0; STRIP], [0.0; STRIP], [0.0; STRIP]);
+    let mut i = 0;
+    while i < n {
+        let vl: usize;
+        // SAFETY: vsetvli grants vl <= avl = min(n - i, STRIP), so the
+        // strided loads read within tetrahedra[i..i + vl] and the stores
+        // write within the scratch buffers. LLVM treats vl and vtype as
+        // unknown after any inline asm, so changing them is allowed.
+        unsafe {
+            asm!(
+                ".option push",
+                ".option arch, +v",
+                "vsetvli {vl}, {avl}, e64, m1, ta, ma",
+                // Vertex k, component j into v(3k + j)
+                "vlse64.v v0, ({p}), {stride}",
+                "addi {t}, {p}, 8",  "vlse64.v v1, ({t}), {stride}",
+                "addi {t}, {p}, 16", "vlse64.v v2, ({t}), {stride}",
+                "addi {t}, {p}, 24", "vlse64.v v3, ({t}), {stride}",
+                "addi {t}, {p}, 32", "vlse64.v v4, ({t}), {stride}",
+                "addi {t}, {p}, 40", "vlse64.v v5, ({t}), {stride}",
+                "addi {t}, {p}, 48", "vlse64.v v6, ({t}), {stride}",
+                "addi {t}, {p}, 56", "vlse64.v v7, ({t}), {stride}",
+                "addi {t}, {p}, 64", "vlse64.v v8, ({t}), {stride}",
+                "addi {t}, {p}, 72", "vlse64.v v9, ({t}), {stride}",
+                "addi {t}, {p}, 80", "vlse64.v v10, ({t}), {stride}",
+                "addi {t}, {p}, 88", "vlse64.v v11, ({t}), {stride}",
+                // Vertex vectors a, b, c in v3..v11
+                "vfsub.vv v3, v3, v0", "vfsub.vv v4, v4, v1", "vfsub.vv v5, v5, v2",
+                "vfsub.vv v6, v6, v0", "vfsub.vv v7, v7, v1", "vfsub.vv v8, v8, v2",
+                "vfsub.vv v9, v9, v0", "vfsub.vv v10, v10, v1", "vfsub.vv v11, v11, v2",
+                // Lengths la, lb, lc in v12..v14, each u0 u0 + (u1 u1 + u2 u2)
+                "vfmul.vv v12, v5, v5", "vfmacc.vv v12, v4, v4", "vfmacc.vv v12, v3, v3", "vfsqrt.v v12, v12",
+                "vfmul.vv v13, v8, v8", "vfmacc.vv v13, v7, v7", "vfmacc.vv v13, v6, v6", "vfsqrt.v v13, v13",
+                "vfmul.vv v14, v11, v11", "vfmacc.vv v14, v10, v10", "vfmacc.vv v14, v9, v9", "vfsqrt.v v14, v14",
+                // Length product in v15
+                "vfmul.vv v15, v12, v13", "vfmul.vv v15, v15, v14",
+                // b × c in v16..v18, each u1 v2 - u2 v1 with one rounding
+                "vfmul.vv v16, v8, v10", "vfmsac.vv v16, v7, v11",
+                "vfmul.vv v17, v6, v11", "vfmsac.vv v17, v8, v9",
+                "vfmul.vv v18, v7, v9", "vfmsac.vv v18, v6, v10",
+                // Triple product in v19
+                "vfmul.vv v19, v5, v18", "vfmacc.vv v19, v4, v17", "vfmacc.vv v19, v3, v16",
+                // a·b, a·c, b·c in v20..v22
+                "vfmul.vv v20, v5, v8", "vfmacc.vv v20, v4, v7", "vfmacc.vv v20, v3, v6",
+                "vfmul.vv v21, v5, v11", "vfmacc.vv v21, v4, v10", "vfmacc.vv v21, v3, v9",
+                "vfmul.vv v22, v8, v11", "vfmacc.vv v22, v7, v10", "vfmacc.vv v22, v6, v9",
+                // Denominator in v23
+                "vmv.v.v v23, v15", "vfmacc.vv v23, v22, v12", "vfmacc.vv v23, v21, v13", "vfmacc.vv v23, v20, v14",
+                "vse64.v v19, ({triple})",
+                "vse64.v v23, ({denom})",
+                "vse64.v v15, ({abc})",
+                ".option pop",
+                avl = in(reg) (n - i).min(STRIP),
+                p = in(reg) tetrahedra[i..].as_ptr(),
+                stride = in(reg) TET_BYTES,
+                triple = in(reg) triple.as_mut_ptr(),
+                denom = in(reg) denom.as_mut_ptr(),
+                abc = in(reg) abc.as_mut_ptr(),
+                vl = out(reg) vl, // Not lateout: written before the inputs are done with
+                t = out(reg) _,
+                out("v0") _, out("v1") _, out("v2") _, out("v3") _, out("v4") _, out("v5") _,
+                out("v6") _, out("v7") _, out("v8") _, out("v9") _, out("v10") _, out("v11") _,
+                out("v12") _, out("v13") _, out("v14") _, out("v15") _, out("v16") _, out("v17") _,
+                out("v18") _, out("v19") _, out("v20") _, out("v21") _, out("v22") _, out("v23") _,
+                options(nostack),
+            );
+        }
+
+        // Angles, with the scalar kernel's degeneracy check
+        for k in 0..vl {
+            out[i + k] = if abc[k] != 0.0 { 2.0 * crate::math::atan2(triple[k], denom[k]) } else { 0.0 };
+        }
+        i += vl;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Directions drawn uniformly over the solid angle a triangle, convex
+//! polygon or cone subtends, for Monte Carlo view factors and lighting:
+//! each sample has density `1 / Ω` per steradian, so an integral over the
+//! shape's directions is `Ω` times the mean of the integrand.
+//!
+//! Samplers take two uniforms in `[0, 1)` rather than a generator, so
+//! stratified or low-discrepancy points can be fed in, and map them
+//! continuously: nearby uniforms give nearby directions.
+//!
+//! The triangle sampler is Arvo's construction ("Stratified sampling of
+//! spherical triangles", SIGGRAPH 1995): `u[0]` picks the point `c'` on
+//! edge `a c` whose sub-triangle `a b c'` has that share of the solid
+//! angle, and `u[1]` a point on the arc from `b` to `c'`, uniform in the
+//! cosine of the distance from `b`. Arvo finds `c'` in closed form, but
+//! that form cancels badly as triangles shrink: its sub-area share is off
+//! by 1e-9 at half a steradian, 7e-7 at 5e-3 sr, and 3% at 5e-9 sr. Here
+//! `c'` is solved for against the solid-angle kernel instead, which is
+//! accurate at every size, and the arc is sampled by half angles.
+
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::vec3::{cross, dot, norm, sub};
+use std::f64::consts::TAU;
+
+/// A shape directions can be drawn over, as seen from a fixed origin
+pub trait DirectionSampler {
+    /// Solid angle covered (sr)
+    fn solid_angle(&self) -> f64;
+
+    /// Unit direction from the origin for uniforms `u` in `[0, 1)`
+    fn sample(&self, u: [f64; 2]) -> [f64; 3];
+
+    /// Density of [DirectionSampler::sample] (1/sr); infinite for an
+    /// empty shape
+    fn pdf(&self) -> f64 {
+        1.0 / self.solid_angle()
+    }
+}
+
+/// A triangle as seen from an origin, set up for sampling
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SphericalTriangle {
+    /// Unit vectors to the vertices
+    a: [f64; 3],
+    b: [f64; 3],
+    c: [f64; 3],
+    area: f64,
+}
+
+impl SphericalTriangle {
+    /// `triangle` seen from `origin`; either winding
+    pub fn new(origin: [f64; 3], triangle: [[f64; 3]; 3]) -> Self {
+        let [a, b, c] = triangle.map(|v| unit(sub(v, origin)));
+        let area = solid_angle_tetrahedron_scalar(origin, triangle[0], triangle[1], triangle[2]).abs();
+        Self { a, b, c, area }
+    }
+
+    /// Point `s` of the way from `a` to `c` whose sub-triangle `a b c'`
+    /// has solid angle `target`: Newton's method on the kernel, kept in a
+    /// shrinking bracket by bisecting when a step would leave it. The
+    /// sub-area is near linear in `s`, so a few steps do.
+    fn split(&self, target: f64) -> [f64; 3] {
+        let Self { a, b, c, area } = *self;
+        let v = sub(c, a);
+        let at = |s: f64| std::array::from_fn(|k| a[k] + s * v[k]);
+
+        // Ω(s) = 2 atan(N / D) with N = [a, b, p], D = |p| (1 + a·b) +
+        // a·p + b·p, all linear in s but |p|
+        let sign = dot(a, cross(b, c)).signum();
+        let (dn, ab) = (sign * dot(a, cross(b, v)), 1.0 + dot(a, b));
+        let slope = |p: [f64; 3]| {
+            let lp = norm(p);
+            let (n, d) = (sign * dot(a, cross(b, p)), lp * ab + dot(a, p) + dot(b, p));
+            let dd = ab * dot(p, v) / lp + dot(a, v) + dot(b, v);
+            2.0 * (dn * d - n * dd) / (n * n + d * d)
+        };
+
+        let (mut lo, mut hi, mut s) = (0.0, 1.0, target / area);
+        for _ in 0..SPLIT_ITERATIONS {
+            let p = at(s);
+            let f = solid_angle_tetrahedron_scalar([0.0; 3], a, b, p).abs() - target;
+            if f.abs() <= 8.0 * f64::EPSILON * area {
+                break;
+            }
+            if f < 0.0 {
+                lo = s;
+            } else {
+                hi = s;
+            }
+            let newton = s - f / slope(p);
+            let next = if newton > lo && newton < hi { newton } else { 0.5 * (lo + hi) };
+            if next == s {
+                break;
+            }
+            s = next;
+        }
+        unit(at(s))
+    }
+}
+
+/// Cap on [SphericalTriangle]'s root-finding; a few suffice in practice
+const SPLIT_ITERATIONS: usize = 64;
+
+impl DirectionSampler for SphericalTriangle {
+    #[inline]
+    fn solid_angle(&self) -> f64 {
+        self.area
+    }
+
+    fn sample(&self, u: [f64; 2]) -> [f64; 3] {
+        if self.area == 0.0 {
+            return self.a;
+        }
+
+        // The sub-triangle a b c' of solid angle u[0] Ω, c' on arc a c
+        let (b, cp) = (self.b, self.split(u[0] * self.area));
+
+        // Then uniform in cos θ along the arc from b to c', by the half
+        // angle: 1 - cos θ = 2 sin²(θ/2) would cancel for short arcs
+        let chord = sub(cp, b);
+        let half = (u[1].sqrt() * 0.5 * norm(chord)).min(1.0).asin();
+        let (sin_theta, cos_theta) = (2.0 * half).sin_cos();
+        let w = axpy(cos_theta, b, sin_theta, unit(reject(chord, b)));
+        if w.iter().all(|x| x.is_finite()) {
+            w
+        } else {
+            b // c' at b
+        }
+    }
+}
+
+/// A convex polygon as seen from an origin: its fan of triangles from
+/// the first vertex, one picked by solid angle and then sampled
+#[derive(Clone, Debug, PartialEq)]
+pub struct SphericalPolygon {
+    triangles: Vec<SphericalTriangle>,
+    /// Running total of the triangles' solid angles
+    cumulative: Vec<f64>,
+}
+
+impl SphericalPolygon {
+    /// Convex `polygon` seen from `origin`, from outside its plane; needs
+    /// three vertices or more
+    pub fn new(origin: [f64; 3], polygon: &[[f64; 3]]) -> Result<Self, &'static str> {
+        if polygon.len() < 3 {
+            return Err("Polygon needs three vertices");
+        }
+        let triangles: Vec<SphericalTriangle> = polygon[1..].windows(2).map(|e| SphericalTriangle::new(origin, [polygon[0], e[0], e[1]])).collect();
+        let cumulative = triangles
+            .iter()
+            .scan(0.0, |total, t| {
+                *total += t.area;
+                Some(*total)
+            })
+            .collect();
+        Ok(Self { triangles, cumulative })
+    }
+}
+
+impl DirectionSampler for SphericalPolygon {
+    #[inline]
+    fn solid_angle(&self) -> f64 {
+        *self.cumulative.last().unwrap()
+    }
+
+    fn sample(&self, u: [f64; 2]) -> [f64; 3] {
+        // Pick by u[0], then stretch its share of [0, 1) back over [0, 1)
+        let x = u[0] * self.solid_angle();
+        let i = self.cumulative.partition_point(|&c| c <= x).min(self.triangles.len() - 1);
+        let start = if i == 0 { 0.0 } else { self.cumulative[i - 1] };
+        let t = &self.triangles[i];
+        let u0 = if t.area > 0.0 { ((x - start) / t.area).clamp(0.0, 1.0) } else { 0.0 };
+        t.sample([u0, u[1]])
+    }
+}
+
+/// Cone of directions within `half_angle` of `axis`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cone {
+    /// Orthonormal frame, the unit axis last
+    frame: [[f64; 3]; 3],
+    cos_half_angle: f64,
+}
+
+impl Cone {
+    /// `axis` need not be unit; `half_angle` in `[0, π]` (rad)
+    pub fn new(axis: [f64; 3], half_angle: f64) -> Self {
+        Self { frame: frame(unit(axis)), cos_half_angle: half_angle.cos() }
+    }
+}
+
+impl DirectionSampler for Cone {
+    #[inline]
+    fn solid_angle(&self) -> f64 {
+        TAU * (1.0 - self.cos_half_angle)
+    }
+
+    fn sample(&self, u: [f64; 2]) -> [f64; 3] {
+        // Uniform in cos θ over [cos half-angle, 1], uniform in azimuth
+        let cos_theta = 1.0 - u[0] * (1.0 - self.cos_half_angle);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (TAU * u[1]).sin_cos();
+        let [s, t, n] = self.frame;
+        std::array::from_fn(|k| sin_theta * (cos_phi * s[k] + sin_phi * t[k]) + cos_theta * n[k])
+    }
+}
+
+#[inline]
+fn unit(v: [f64; 3]) -> [f64; 3] {
+    let len = norm(v);
+    v.map(|x| x / len)
+}
+
+/// `p x + q y`
+#[inline]
+fn axpy(p: f64, x: [f64; 3], q: f64, y: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|k| p * x[k] + q * y[k])
+}
+
+/// Part of `v` perpendicular to unit `n`
+#[inline]
+fn reject(v: [f64; 3], n: [f64; 3]) -> [f64; 3] {
+    axpy(1.0, v, -dot(v, n), n)
+}
+
+/// Orthonormal frame with unit `n` last, branch-free (Duff et al.,
+/// "Building an orthonormal basis, revisited", JCGT 2017)
+#[inline]
+pub(crate) fn frame(n: [f64; 3]) -> [[f64; 3]; 3] {
+    let sign = 1.0_f64.copysign(n[2]);
+    let a = -1.0 / (sign + n[2]);
+    let b = n[0] * n[1] * a;
+    [[1.0 + sign * n[0] * n[0] * a, sign * b, -sign * n[0]], [b, sign + n[1] * n[1] * a, -n[1]], n]
+}
@@ -0,0 +1,286 @@
+//! Assemblies of named, instanced meshes (a spacecraft's bus, wings and
+//! antennas; a building's floors and façade panels) with a two-level
+//! BVH, so coverage and view factors run over the whole model at once.
+//!
+//! Each distinct mesh, by [Arc], gets one bottom-level [Bvh] (BLAS) in
+//! its own frame, shared by all its instances as in
+//! [crate::instance]. The top level (TLAS) is a hierarchy over the
+//! instances' world-space boxes, built the same way as [Bvh]: median
+//! splits along the longest axis down to [LEAF] instances. A segment is
+//! tested against an instance's BLAS only where it passes that
+//! instance's box, so a query touches a handful of the thousands of
+//! panels in an array.
+//!
+//! Instances carry named values, one each, as meshes do for faces:
+//! temperature, emissivity, a subsystem ID.
+
+use crate::attributes::AttributeMap;
+use crate::bounds::Aabb;
+use crate::bvh::{slab, Bvh, LEAF};
+use crate::instance::{self, Affine3, Instance};
+use crate::thermal::view_factors_with;
+use crate::vec3::sub;
+use crate::visibility::{visibility_matrix_with, BitMatrix};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Node {
+    bounds: Aabb,
+    /// Leaf: first instance in `order`. Interior: index of the right child.
+    start: u32,
+    /// Instances in a leaf; zero for interior nodes
+    count: u32,
+}
+
+/// See the module docs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    names: Vec<String>,
+    index: BTreeMap<String, usize>,
+    instances: Vec<Instance>,
+    instance_attributes: AttributeMap<f64>,
+    /// One hierarchy per distinct mesh, and which each instance uses
+    blas: Vec<Bvh>,
+    blas_of: Vec<usize>,
+    /// Map from world space to each instance's mesh; `None` if singular
+    inverses: Vec<Option<Affine3>>,
+    /// World-space box of each instance
+    boxes: Vec<Aabb>,
+    /// First face of each instance in the scene's numbering, and past the
+    /// last
+    starts: Vec<usize>,
+    nodes: Vec<Node>,
+    /// Instance indices, each leaf's contiguous
+    order: Vec<u32>,
+}
+
+impl Scene {
+    /// Scene of uniquely named instances. Instances sharing a mesh share
+    /// its BLAS.
+    pub fn new(instances: Vec<(String, Instance)>) -> Result<Self, &'static str> {
+        let mut index = BTreeMap::new();
+        for (i, (name, _)) in instances.iter().enumerate() {
+            if index.insert(name.clone(), i).is_some() {
+                return Err("Duplicate instance name");
+            }
+        }
+        let (names, instances): (Vec<String>, Vec<Instance>) = instances.into_iter().unzip();
+
+        // One BLAS per distinct mesh
+        let (mut blas, mut blas_of, mut seen) = (Vec::new(), Vec::with_capacity(instances.len()), HashMap::new());
+        for i in &instances {
+            let b = *seen.entry(Arc::as_ptr(&i.mesh)).or_insert_with(|| {
+                blas.push(Bvh::new(&i.mesh));
+                blas.len() - 1
+            });
+            blas_of.push(b);
+        }
+
+        let mut starts = Vec::with_capacity(instances.len() + 1);
+        starts.push(0);
+        for i in &instances {
+            starts.push(starts[starts.len() - 1] + i.faces());
+        }
+
+        let n = instances.len();
+        let mut scene = Self {
+            names,
+            index,
+            instances,
+            instance_attributes: AttributeMap::new(n),
+            blas,
+            blas_of,
+            inverses: Vec::new(),
+            boxes: Vec::new(),
+            starts,
+            nodes: Vec::new(),
+            order: Vec::new(),
+        };
+        scene.rebuild();
+        Ok(scene)
+    }
+
+    #[inline]
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    #[inline]
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Index of the instance called `name`
+    #[inline]
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    #[inline]
+    pub fn instance_attributes(&self) -> &AttributeMap<f64> {
+        &self.instance_attributes
+    }
+
+    #[inline]
+    pub fn instance_attributes_mut(&mut self) -> &mut AttributeMap<f64> {
+        &mut self.instance_attributes
+    }
+
+    /// Distinct meshes, each with its own BLAS
+    #[inline]
+    pub fn meshes(&self) -> usize {
+        self.blas.len()
+    }
+
+    /// Faces of every instance, numbered through the instances in order
+    #[inline]
+    pub fn faces(&self) -> usize {
+        self.starts[self.instances.len()]
+    }
+
+    /// Instance and face within its mesh of the scene's face `f`
+    #[inline]
+    pub fn face(&self, f: usize) -> (usize, usize) {
+        let i = self.starts.partition_point(|&s| s <= f) - 1;
+        (i, f - self.starts[i])
+    }
+
+    /// Placed vertex positions of the scene's face `f`
+    #[inline]
+    pub fn triangle(&self, f: usize) -> [[f64; 3]; 3] {
+        let (i, face) = self.face(f);
+        self.instances[i].triangle(face)
+    }
+
+    /// Box around every placed face; empty for an empty scene
+    #[inline]
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |root| root.bounds)
+    }
+
+    /// Move instance `i` and rebuild the top level; the meshes and their
+    /// BLAS stay as they are
+    pub fn set_transform(&mut self, i: usize, transform: Affine3) -> Result<(), &'static str> {
+        // Check bounds
+        if i >= self.instances.len() {
+            return Err("Dimension mismatch");
+        }
+        self.instances[i].transform = transform;
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Total solid angle subtended by the scene at each origin, as
+    /// [crate::instance::solid_angles]
+    pub fn solid_angles(&self, origins: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+        instance::solid_angles(&self.instances, origins, out)
+    }
+
+    /// Whether any placed face crosses the segment from `from` to `to`,
+    /// excluding [crate::bvh::T_EPS] at either end
+    pub fn occluded(&self, from: [f64; 3], to: [f64; 3]) -> bool {
+        let Some(root) = self.nodes.first() else {
+            return false;
+        };
+        let inv = sub(to, from).map(|d| 1.0 / d);
+        if !slab(&root.bounds, from, inv) {
+            return false;
+        }
+
+        let blocks = |i: usize| {
+            self.inverses[i].is_some_and(|m| self.blas[self.blas_of[i]].occluded(&self.instances[i].mesh, m.apply(from), m.apply(to)))
+        };
+        let mut stack = [0_u32; 64]; // One more than the depth, at most
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let node = &self.nodes[stack[top] as usize];
+            if node.count > 0 {
+                let leaf = &self.order[node.start as usize..(node.start + node.count) as usize];
+                if leaf.iter().any(|&i| slab(&self.boxes[i as usize], from, inv) && blocks(i as usize)) {
+                    return true;
+                }
+                continue;
+            }
+            let (left, right) = (stack[top] + 1, node.start);
+            for child in [right, left] {
+                if slab(&self.nodes[child as usize].bounds, from, inv) {
+                    stack[top] = child;
+                    top += 1;
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether each of `sources` sees each of `targets` past the scene, as
+    /// [crate::visibility::visibility_matrix] past one mesh
+    pub fn visibility_matrix(&self, sources: &[[f64; 3]], targets: &[[f64; 3]]) -> BitMatrix {
+        visibility_matrix_with(sources, targets, |from, to| self.occluded(from, to))
+    }
+
+    /// View factor matrix between the scene's faces, as
+    /// [crate::thermal::view_factors], except that a pair whose centroids
+    /// don't see each other past the scene exchanges nothing. That
+    /// all-or-nothing shadowing is exact for faces wholly in or out of
+    /// view, and converges as partly shadowed faces are refined.
+    pub fn view_factors(&self, out: &mut [f64]) -> Result<(), &'static str> {
+        let n = self.faces();
+        view_factors_with(n, |f| self.triangle(f), out)?;
+        let centroids: Vec<[f64; 3]> = (0..n).map(|f| self.triangle(f)).map(|[a, b, c]| std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0)).collect();
+        for i in 0..n {
+            for j in 0..n {
+                let f = &mut out[i * n + j];
+                if *f > 0.0 && self.occluded(centroids[i], centroids[j]) {
+                    *f = 0.0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// World-space box of instance `i`: its BLAS's box, placed
+    fn instance_box(&self, i: usize) -> Aabb {
+        let local = self.blas[self.blas_of[i]].bounds();
+        if local.is_empty() {
+            return Aabb::EMPTY;
+        }
+        let corners: [[f64; 3]; 8] = std::array::from_fn(|c| std::array::from_fn(|k| if c & (1 << k) == 0 { local.lo[k] } else { local.hi[k] }));
+        Aabb::from_points(&corners.map(|p| self.instances[i].transform.apply(p)))
+    }
+
+    /// Recompute the inverses, boxes and top level from the transforms
+    fn rebuild(&mut self) {
+        self.inverses = self.instances.iter().map(|i| i.transform.inverse().ok()).collect();
+        self.boxes = (0..self.instances.len()).map(|i| self.instance_box(i)).collect();
+        let mut order: Vec<u32> = (0..self.boxes.len() as u32).collect();
+        let mut nodes = Vec::with_capacity(2 * order.len().div_ceil(LEAF));
+        if !order.is_empty() {
+            build(&self.boxes, &mut order, 0, &mut nodes);
+        }
+        self.nodes = nodes;
+        self.order = order;
+    }
+}
+
+/// Build the subtree over `order`, which starts at `offset` in the full
+/// order, appending its nodes depth first; returns its root's index
+fn build(boxes: &[Aabb], order: &mut [u32], offset: usize, nodes: &mut Vec<Node>) -> u32 {
+    let bounds = order.iter().fold(Aabb::EMPTY, |b, &i| b.union(boxes[i as usize]));
+    let index = nodes.len() as u32;
+    nodes.push(Node { bounds, start: offset as u32, count: order.len() as u32 });
+    if order.len() <= LEAF {
+        return index;
+    }
+
+    let extent = bounds.extent();
+    let axis = (0..3).fold(0, |a, k| if extent[k] > extent[a] { k } else { a });
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |&a, &b| boxes[a as usize].center()[axis].total_cmp(&boxes[b as usize].center()[axis]));
+    let (left, right) = order.split_at_mut(mid);
+    build(boxes, left, offset, nodes);
+    let right = build(boxes, right, offset + mid, nodes);
+    nodes[index as usize] = Node { bounds, start: right, count: 0 };
+    index
+}
@@ -0,0 +1,298 @@
+//! Signed distance functions for the shapes of [crate::primitives], and
+//! combinators building solids from them, for procedural occluders that
+//! need no mesh at all.
+//!
+//! An [Sdf] is negative inside, zero on the surface and positive outside,
+//! and never changes faster than the distance moved. The sphere, box,
+//! capsule, cylinder and cone are exact (after Quilez's catalogue); a
+//! [ConvexPolyhedron], the Platonic solids among them, is exact inside
+//! and underestimates near edges and corners outside; [Union],
+//! [Intersection] and [Difference] are exact on one side of their
+//! surfaces and underestimate on the other, and [SmoothUnion] by up to
+//! `k / 4` where it blends. An underestimate is still a safe step, so
+//! [Sdf::occluded] marches any of them.
+
+use crate::bvh::T_EPS;
+use crate::mesh::TriMesh;
+use crate::par::par_threshold;
+use crate::vec3::{cross, dot, norm, sub};
+use rayon::prelude::*;
+
+/// Steps [Sdf::occluded] takes before calling a segment blocked. Leaving
+/// a surface at angle `θ` the steps grow only by `1 + sin θ`, so a
+/// segment from a point on it within about a milliradian of tangent
+/// counts as grazing, and blocked.
+pub const MAX_STEPS: usize = 1 << 14;
+
+/// Signed distance to a solid's surface, negative inside
+pub trait Sdf {
+    fn distance(&self, p: [f64; 3]) -> f64;
+
+    #[inline]
+    fn contains(&self, p: [f64; 3]) -> bool {
+        self.distance(p) < 0.0
+    }
+
+    /// Whether the solid blocks the segment from `from` to `to`, excluding
+    /// [T_EPS] at either end as [crate::bvh::Bvh::occluded] does, by
+    /// sphere tracing
+    fn occluded(&self, from: [f64; 3], to: [f64; 3]) -> bool {
+        let len = norm(sub(to, from));
+        if len == 0.0 {
+            return false;
+        }
+
+        // Parameter along the segment, stepped by the distance clear of the
+        // surface; a hit is well inside the exclusion at either end, so a
+        // segment leaving a surface point doesn't hit where it starts
+        let mut t = T_EPS;
+        for _ in 0..MAX_STEPS {
+            if t >= 1.0 - T_EPS {
+                return false;
+            }
+            let p = std::array::from_fn(|k| from[k] + t * (to[k] - from[k]));
+            let step = self.distance(p) / len;
+            if step < 1e-3 * T_EPS {
+                return true;
+            }
+            t += step;
+        }
+        true
+    }
+
+    fn union<B: Sdf>(self, other: B) -> Union<Self, B>
+    where
+        Self: Sized,
+    {
+        Union(self, other)
+    }
+
+    fn intersection<B: Sdf>(self, other: B) -> Intersection<Self, B>
+    where
+        Self: Sized,
+    {
+        Intersection(self, other)
+    }
+
+    /// This solid with `other` cut out of it
+    fn difference<B: Sdf>(self, other: B) -> Difference<Self, B>
+    where
+        Self: Sized,
+    {
+        Difference(self, other)
+    }
+
+    /// Union with the seam filleted over about `k`
+    fn smooth_union<B: Sdf>(self, other: B, k: f64) -> SmoothUnion<Self, B>
+    where
+        Self: Sized,
+    {
+        SmoothUnion { a: self, b: other, k }
+    }
+}
+
+impl<T: Sdf + ?Sized> Sdf for &T {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        (**self).distance(p)
+    }
+}
+
+impl<T: Sdf + ?Sized> Sdf for Box<T> {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        (**self).distance(p)
+    }
+}
+
+/// Signed distance from each of `points` to `sdf`, into `out`
+pub fn distances<S: Sdf + Sync>(sdf: &S, points: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+    if points.len() < par_threshold() {
+        points.iter().zip(out.iter_mut()).for_each(|(&p, y)| *y = sdf.distance(p));
+    } else {
+        points.par_iter().zip(out.par_iter_mut()).for_each(|(&p, y)| *y = sdf.distance(p));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: [f64; 3],
+    pub radius: f64,
+}
+
+impl Sdf for Sphere {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        norm(sub(p, self.center)) - self.radius
+    }
+}
+
+/// Axis-aligned box from corner `lo` to `hi`, as [crate::primitives::cuboid]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cuboid {
+    pub lo: [f64; 3],
+    pub hi: [f64; 3],
+}
+
+impl Sdf for Cuboid {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        // Distance past each face pair from the centre
+        let q: [f64; 3] = std::array::from_fn(|k| (p[k] - 0.5 * (self.lo[k] + self.hi[k])).abs() - 0.5 * (self.hi[k] - self.lo[k]));
+        norm(q.map(|x| x.max(0.0))) + q[0].max(q[1]).max(q[2]).min(0.0)
+    }
+}
+
+/// Segment from `a` to `b` swept by a ball of `radius`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capsule {
+    pub a: [f64; 3],
+    pub b: [f64; 3],
+    pub radius: f64,
+}
+
+impl Sdf for Capsule {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        let (pa, ba) = (sub(p, self.a), sub(self.b, self.a));
+        let h = (dot(pa, ba) / dot(ba, ba)).clamp(0.0, 1.0);
+        norm::<3>(std::array::from_fn(|k| pa[k] - h * ba[k])) - self.radius
+    }
+}
+
+/// Closed cylinder of `radius` between the centres of its ends, `a` and `b`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cylinder {
+    pub a: [f64; 3],
+    pub b: [f64; 3],
+    pub radius: f64,
+}
+
+impl Sdf for Cylinder {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        // Radial and axial excess, both scaled by |ba|²
+        let (pa, ba) = (sub(p, self.a), sub(self.b, self.a));
+        let (baba, paba) = (dot(ba, ba), dot(pa, ba));
+        let x = norm::<3>(std::array::from_fn(|k| pa[k] * baba - ba[k] * paba)) - self.radius * baba;
+        let y = (paba - 0.5 * baba).abs() - 0.5 * baba;
+        let (x2, y2) = (x * x, y * y * baba);
+        let d = if x.max(y) < 0.0 { -x2.min(y2) } else { x.max(0.0).powi(2) + if y > 0.0 { y2 } else { 0.0 } };
+        d.signum() * d.abs().sqrt() / baba
+    }
+}
+
+/// Cone of `radius` from the centre of its base, `base`, to `apex`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cone {
+    pub base: [f64; 3],
+    pub apex: [f64; 3],
+    pub radius: f64,
+}
+
+impl Sdf for Cone {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        // In the half-plane through the axis: `x` from it, `paba` along it
+        // as a fraction of the height
+        let (pa, ba) = (sub(p, self.base), sub(self.apex, self.base));
+        let baba = dot(ba, ba);
+        let paba = dot(pa, ba) / baba;
+        let x = (dot(pa, pa) - paba * paba * baba).max(0.0).sqrt();
+        let r = self.radius;
+
+        // Nearest on the base disc, then on the slant side
+        let cax = (x - if paba < 0.5 { r } else { 0.0 }).max(0.0);
+        let cay = (paba - 0.5).abs() - 0.5;
+        let f = ((-r * (x - r) + paba * baba) / (r * r + baba)).clamp(0.0, 1.0);
+        let (cbx, cby) = (x - r + f * r, paba - f);
+        let sign = if cbx < 0.0 && cay < 0.0 { -1.0 } else { 1.0 };
+        sign * (cax * cax + cay * cay * baba).min(cbx * cbx + cby * cby * baba).sqrt()
+    }
+}
+
+/// Intersection of the half-spaces behind a convex mesh's faces: exact
+/// inside, a lower bound outside
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvexPolyhedron {
+    /// Unit outward normal and offset of each face's plane
+    planes: Vec<([f64; 3], f64)>,
+}
+
+impl ConvexPolyhedron {
+    /// The planes of a closed, outward-wound, convex `mesh`, such as
+    /// [crate::primitives::icosahedron]; degenerate faces are skipped
+    pub fn new(mesh: &TriMesh) -> Result<Self, &'static str> {
+        let planes: Vec<([f64; 3], f64)> = mesh
+            .triangles()
+            .filter_map(|[a, b, c]| {
+                let n = cross(sub(b, a), sub(c, a));
+                let len = norm(n);
+                (len > 0.0).then(|| {
+                    let n = n.map(|x| x / len);
+                    (n, dot(n, a))
+                })
+            })
+            .collect();
+        if planes.len() < 4 {
+            return Err("Too few faces to enclose a volume");
+        }
+        Ok(Self { planes })
+    }
+}
+
+impl Sdf for ConvexPolyhedron {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        self.planes.iter().map(|&(n, offset)| dot(n, p) - offset).fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Union<A, B>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        self.0.distance(p).min(self.1.distance(p))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Intersection<A, B>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        self.0.distance(p).max(self.1.distance(p))
+    }
+}
+
+/// The first solid with the second cut out of it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difference<A, B>(pub A, pub B);
+
+impl<A: Sdf, B: Sdf> Sdf for Difference<A, B> {
+    #[inline]
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        self.0.distance(p).max(-self.1.distance(p))
+    }
+}
+
+/// Union blended over about `k` by the quadratic smooth minimum: below
+/// both constituents by at most `k / 4`, and equal to their union where
+/// they are more than `k` apart
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothUnion<A, B> {
+    pub a: A,
+    pub b: B,
+    pub k: f64,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn distance(&self, p: [f64; 3]) -> f64 {
+        let (a, b) = (self.a.distance(p), self.b.distance(p));
+        let h = (self.k - (a - b).abs()).max(0.0) / self.k;
+        a.min(b) - 0.25 * h * h * self.k
+    }
+}
@@ -0,0 +1,151 @@
+//! HTTP front end for the kernels, so clients in any language can use
+//! them without linking Rust.
+//!
+//! | Route                   | Request body                                 | Response body              |
+//! |-------------------------|----------------------------------------------|----------------------------|
+//! | `POST /solid_angles`    | packed tetrahedra, 12 little-endian `f64` each | one little-endian `f64` each |
+//! | `POST /winding_numbers` | `.npz` mesh as in [crate::mesh_io], plus `points` `(n, 3)` | `.npy` `(n,)`  |
+//! | `POST /view_factors`    | `.npz` mesh                                  | `.npy` `(m, m)`            |
+//! | `GET /stats`            |                                              | `<launches> <tetrahedra>`  |
+//!
+//! `/solid_angles` streams: results for each piece of the body are sent
+//! as soon as it arrives, so neither side holds the whole batch. The mesh
+//! routes take the whole archive, up to a configurable size. Kernels run
+//! on tokio's blocking threads, where the rayon drivers take over; the
+//! stream's pieces go through a [BatchQueue], which merges pieces from
+//! concurrent clients into shared launches and holds uploads back once
+//! its memory budget is spent.
+//!
+//! Behind the `serve` feature.
+
+use crate::batch_queue::BatchQueue;
+use crate::mesh_io::read_tri_mesh_npz;
+use crate::multi_origin::solid_angles_multi_origin;
+use crate::npy::{read_npz, write_f64};
+use crate::thermal::view_factors;
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::Router;
+use futures_util::{stream, StreamExt};
+use std::f64::consts::PI;
+use std::io::{self, Cursor};
+use tokio::sync::mpsc;
+
+/// Bytes per packed tetrahedron
+const TET_BYTES: usize = 96;
+
+/// Error response with a plain-text reason
+type Rejection = (StatusCode, String);
+
+fn bad_request(e: impl ToString) -> Rejection {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+/// Routes for all kernels, accepting `.npz` bodies of up to `max_body`
+/// bytes and streaming solid angles through `queue`
+pub fn router(max_body: usize, queue: BatchQueue) -> Router {
+    Router::new()
+        .route("/solid_angles", post(solid_angles))
+        .route("/winding_numbers", post(winding_numbers))
+        .route("/view_factors", post(view_factors_npz))
+        .route("/stats", get(stats))
+        .layer(DefaultBodyLimit::max(max_body))
+        .with_state(queue)
+}
+
+/// Take the whole tetrahedra off the front of `carry`, leaving any partial one
+fn take_tetrahedra(carry: &mut Vec<u8>) -> Vec<[[f64; 3]; 4]> {
+    let whole = carry.len() / TET_BYTES * TET_BYTES;
+    let tets = carry[..whole]
+        .chunks_exact(TET_BYTES)
+        .map(|t| {
+            let x = |i: usize| f64::from_le_bytes(t[8 * i..8 * i + 8].try_into().unwrap());
+            std::array::from_fn(|v| std::array::from_fn(|k| x(3 * v + k)))
+        })
+        .collect();
+    carry.drain(..whole);
+    tets
+}
+
+/// Solid angles of one piece of the stream, as little-endian bytes
+async fn solve_chunk(queue: &BatchQueue, tets: Vec<[[f64; 3]; 4]>) -> io::Result<Bytes> {
+    let out = queue.solid_angles(tets).await.map_err(io::Error::other)?;
+    Ok(Bytes::from_iter(out.iter().flat_map(|x| x.to_le_bytes())))
+}
+
+async fn solid_angles(State(queue): State<BatchQueue>, body: Body) -> Body {
+    // Results go out through a short channel, so a slow reader slows the upload
+    let (tx, mut rx) = mpsc::channel::<io::Result<Bytes>>(4);
+    tokio::spawn(async move {
+        let mut chunks = body.into_data_stream();
+        let mut carry = Vec::with_capacity(TET_BYTES);
+        while let Some(chunk) = chunks.next().await {
+            let result = match chunk {
+                Ok(chunk) => {
+                    carry.extend_from_slice(&chunk);
+                    solve_chunk(&queue, take_tetrahedra(&mut carry)).await
+                }
+                Err(e) => Err(io::Error::other(e)),
+            };
+            let failed = result.is_err();
+            if tx.send(result).await.is_err() || failed {
+                return; // Client gone, or the response is already broken
+            }
+        }
+        if !carry.is_empty() {
+            let _ = tx.send(Err(io::Error::new(io::ErrorKind::InvalidData, "Body is not whole tetrahedra"))).await;
+        }
+    });
+    Body::from_stream(stream::poll_fn(move |cx| rx.poll_recv(cx)))
+}
+
+/// Kernel launches of the queue so far, and the tetrahedra they covered
+async fn stats(State(queue): State<BatchQueue>) -> String {
+    let (launches, elements) = queue.stats();
+    format!("{launches} {elements}\n")
+}
+
+/// Array as a `.npy` body
+fn npy_body(shape: &[usize], data: &[f64]) -> Result<Vec<u8>, Rejection> {
+    let mut buf = Vec::new();
+    write_f64(&mut buf, shape, data).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(buf)
+}
+
+async fn winding_numbers(body: Bytes) -> Result<Vec<u8>, Rejection> {
+    let mesh = read_tri_mesh_npz(Cursor::new(&body[..])).map_err(bad_request)?;
+    let points = read_npz(Cursor::new(&body[..]))
+        .map_err(bad_request)?
+        .into_iter()
+        .find(|(name, _)| name == "points")
+        .map(|(_, arr)| arr)
+        .ok_or_else(|| bad_request("Missing points array"))?;
+    if points.shape.len() != 2 || points.shape[1] != 3 {
+        return Err(bad_request("Expected points with shape (n, 3)"));
+    }
+    let points: Vec<[f64; 3]> = points.data.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+
+    let winding = tokio::task::spawn_blocking(move || {
+        let mut out = vec![0.0; points.len()];
+        solid_angles_multi_origin(&mesh, &points, &mut out).map(|()| out.iter().map(|w| w / (4.0 * PI)).collect::<Vec<_>>())
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    npy_body(&[winding.len()], &winding)
+}
+
+async fn view_factors_npz(body: Bytes) -> Result<Vec<u8>, Rejection> {
+    let mesh = read_tri_mesh_npz(Cursor::new(&body[..])).map_err(bad_request)?;
+    let m = mesh.faces().len();
+    let f = tokio::task::spawn_blocking(move || {
+        let mut out = vec![0.0; m * m];
+        view_factors(&mesh, &mut out).map(|()| out)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    npy_body(&[m, m], &f)
+}
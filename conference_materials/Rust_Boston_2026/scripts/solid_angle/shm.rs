@@ -0,0 +1,150 @@
+//! Resident worker over shared memory, for orchestrators in other
+//! processes that would otherwise spawn a process per call.
+//!
+//! Inputs and outputs live in one file mapped by both sides, normally in
+//! `/dev/shm` so that it is POSIX shared memory rather than disk. Only
+//! short text lines cross the worker's stdin/stdout:
+//!
+//! ```text
+//! > tetrahedra <n> <input offset> <output offset>
+//! < ok
+//! > remap
+//! < ok
+//! > quit
+//! ```
+//!
+//! Offsets are in bytes from the start of the file and must be 8-byte
+//! aligned. `tetrahedra` reads `n` packed tetrahedra (`12n` native-endian
+//! `f64`) and writes `n` solid angles. `remap` picks up a file the client
+//! has resized. Any failure is answered with `err <reason>` and the
+//! worker keeps serving.
+//!
+//! The views of the mapped file are [crate::bytes]', so this needs the
+//! `bytemuck` feature too.
+#![cfg(feature = "bytemuck")]
+
+use crate::bytes::{tetrahedra_from_bytes, values_from_bytes_mut};
+use crate::par::solid_angle_tetrahedra_par;
+use memmap2::MmapMut;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// One control-protocol command
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Request {
+    /// Solid angles of `n` tetrahedra at byte offset `input`, written at byte offset `output`
+    Tetrahedra { n: usize, input: usize, output: usize },
+    /// Map the file again, after the client resized it
+    Remap,
+    /// Stop serving
+    Quit,
+}
+
+impl Request {
+    /// Parse one line of the control protocol
+    pub fn parse(line: &str) -> Result<Self, &'static str> {
+        let mut words = line.split_whitespace();
+        let command = words.next();
+        let mut number = || -> Result<usize, &'static str> {
+            words.next().ok_or("Missing argument")?.parse().map_err(|_| "Bad number")
+        };
+        match command {
+            Some("tetrahedra") => Ok(Self::Tetrahedra { n: number()?, input: number()?, output: number()? }),
+            Some("remap") => Ok(Self::Remap),
+            Some("quit") => Ok(Self::Quit),
+            _ => Err("Unknown command"),
+        }
+    }
+}
+
+/// Shared file mapped read-write
+pub struct SharedBuffer {
+    path: PathBuf,
+    map: MmapMut,
+}
+
+impl SharedBuffer {
+    /// Map an existing file
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let map = Self::map(&path)?;
+        Ok(Self { path, map })
+    }
+
+    /// Create or truncate a file of `len` zero bytes, and map it
+    pub fn create(path: impl AsRef<Path>, len: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path)?;
+        file.set_len(len)?;
+        Self::open(path)
+    }
+
+    fn map(path: &Path) -> io::Result<MmapMut> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        // SAFETY: The protocol gives the worker the buffer from a request
+        // until it answers, and the client does not touch it meanwhile
+        unsafe { MmapMut::map_mut(&file) }
+    }
+
+    /// Map the file again at its current length
+    pub fn remap(&mut self) -> io::Result<()> {
+        self.map = Self::map(&self.path)?;
+        Ok(())
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+
+    /// Run one request against the mapped buffer
+    pub fn execute(&mut self, request: Request) -> Result<(), &'static str> {
+        match request {
+            Request::Tetrahedra { n, input, output } => {
+                let (in_len, out_len) = (n.checked_mul(96).ok_or("Too many elements")?, n * 8);
+                let (in_end, out_end) = (input.checked_add(in_len), output.checked_add(out_len));
+                match (in_end, out_end) {
+                    (Some(i), Some(o)) if i <= self.map.len() && o <= self.map.len() => (),
+                    _ => return Err("Region out of bounds"),
+                }
+
+                // Split the map between the two regions, which must not overlap
+                let (inputs, outputs) = if output >= input + in_len {
+                    let (a, b) = self.map.split_at_mut(output);
+                    (&a[input..input + in_len], &mut b[..out_len])
+                } else if input >= output + out_len {
+                    let (a, b) = self.map.split_at_mut(input);
+                    (&b[..in_len], &mut a[output..output + out_len])
+                } else {
+                    return Err("Input and output regions overlap");
+                };
+                solid_angle_tetrahedra_par(tetrahedra_from_bytes(inputs)?, values_from_bytes_mut(outputs)?)
+            }
+            Request::Remap => self.remap().map_err(|_| "Cannot map file"),
+            Request::Quit => Ok(()),
+        }
+    }
+}
+
+/// Answer requests from `control` on `reply` until `quit` or end of input
+pub fn serve<R: BufRead, W: Write>(buffer: &mut SharedBuffer, control: R, mut reply: W) -> io::Result<()> {
+    for line in control.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = Request::parse(&line);
+        match request.and_then(|r| buffer.execute(r)) {
+            Ok(()) => writeln!(reply, "ok")?,
+            Err(e) => writeln!(reply, "err {e}")?,
+        }
+        reply.flush()?; // The client is blocked on this line
+        if request == Ok(Request::Quit) {
+            break;
+        }
+    }
+    Ok(())
+}
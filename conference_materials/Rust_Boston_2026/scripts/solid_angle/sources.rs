@@ -0,0 +1,205 @@
+//! Analytic light sources: a uniform disk (the Sun) and a uniform sky
+//! dome, each with its radiance, the solid angle it subtends, a sampler,
+//! and its exact irradiance on a plane, so Monte Carlo irradiance
+//! integrals built from the samples can be checked against a closed form.
+//!
+//! Coordinates are local east-north-up as in the horizon module: `z` is
+//! the zenith. Radiance is per steradian, in whatever units the caller
+//! uses (W/m²/sr for broadband irradiance in W/m²).
+//!
+//! A disk's irradiance on a plane is its radiance times its projected
+//! solid angle, `π sin²α cos β` for a disk of angular radius `α` whose
+//! center is `β` from the plane's normal, while the disk is all in front
+//! of the plane. Where the plane cuts the disk, Stokes' theorem turns the
+//! projected solid angle of the part in front into line integrals along
+//! the cut circle and along the plane's great circle, both closed form.
+
+use crate::sampling::{Cone, DirectionSampler};
+use crate::vec3::{cross, dot, norm};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+/// Mean angular radius of the Sun seen from Earth (rad), 0.2666°
+pub const SUN_ANGULAR_RADIUS: f64 = 4.652e-3;
+
+/// A source of light over a set of directions
+pub trait Source {
+    /// Solid angle it covers (sr)
+    fn solid_angle(&self) -> f64;
+
+    /// Radiance arriving from unit `direction`; zero outside the source
+    fn radiance(&self, direction: [f64; 3]) -> f64;
+
+    /// Unit direction into the source for uniforms `u` in `[0, 1)`
+    fn sample(&self, u: [f64; 2]) -> [f64; 3];
+
+    /// Density of [Source::sample] at unit `direction` (1/sr)
+    fn pdf(&self, direction: [f64; 3]) -> f64;
+
+    /// Exact irradiance on a plane with unit `normal`, from the side it
+    /// faces: `∫ L(ω) max(n·ω, 0) dω`
+    fn irradiance(&self, normal: [f64; 3]) -> f64;
+}
+
+/// Monte Carlo estimate of [Source::irradiance] on a plane with unit
+/// `normal`, one sample of `source` per uniform pair: the mean of
+/// `L max(n·ω, 0) / pdf`. Unbiased for any uniforms; stratified ones
+/// converge faster.
+pub fn irradiance_estimate<S: Source>(source: &S, normal: [f64; 3], uniforms: impl IntoIterator<Item = [f64; 2]>) -> f64 {
+    let (mut sum, mut n) = (0.0, 0_usize);
+    for u in uniforms {
+        let d = source.sample(u);
+        let cos = dot(normal, d);
+        if cos > 0.0 {
+            sum += source.radiance(d) * cos / source.pdf(d);
+        }
+        n += 1;
+    }
+    sum / n as f64
+}
+
+/// Disk of uniform radiance, such as the Sun, sampled uniformly over
+/// its solid angle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Disk {
+    /// Unit direction to the center
+    axis: [f64; 3],
+    angular_radius: f64,
+    radiance: f64,
+    cone: Cone,
+}
+
+impl Disk {
+    /// Disk centered on `direction` (need not be unit) with
+    /// `angular_radius` in `[0, π/2]` (rad)
+    pub fn new(direction: [f64; 3], angular_radius: f64, radiance: f64) -> Self {
+        let len = norm(direction);
+        let axis = direction.map(|x| x / len);
+        Self { axis, angular_radius, radiance, cone: Cone::new(axis, angular_radius) }
+    }
+
+    /// The Sun toward `direction`, giving `normal_irradiance` on a plane
+    /// facing it (direct normal irradiance, about 1000 W/m² on a clear
+    /// day at sea level)
+    pub fn sun(direction: [f64; 3], normal_irradiance: f64) -> Self {
+        let radiance = normal_irradiance / (PI * SUN_ANGULAR_RADIUS.sin().powi(2));
+        Self::new(direction, SUN_ANGULAR_RADIUS, radiance)
+    }
+
+    /// Unit direction to the center
+    #[inline]
+    pub fn axis(&self) -> [f64; 3] {
+        self.axis
+    }
+
+    /// Projected solid angle of the part in front of a plane whose unit
+    /// normal makes angle `beta` with the axis (sr)
+    pub fn projected_solid_angle(&self, beta: f64) -> f64 {
+        let alpha = self.angular_radius;
+        let (sin_a, cos_a) = alpha.sin_cos();
+        let (sin_b, cos_b) = beta.sin_cos();
+        if beta <= FRAC_PI_2 - alpha {
+            return PI * sin_a * sin_a * cos_b; // All in front
+        }
+        if beta >= FRAC_PI_2 + alpha {
+            return 0.0; // All behind
+        }
+
+        // In front where the cut circle's angle about the axis, measured
+        // from the normal's side, is within t0
+        let t0 = (-(cos_a * cos_b) / (sin_a * sin_b)).clamp(-1.0, 1.0).acos();
+        let circle = sin_a * (sin_a * cos_b * t0 - cos_a * sin_b * t0.sin());
+        let chord = (sin_a * t0.sin()).asin(); // Half the arc of the plane's circle
+        circle + chord
+    }
+}
+
+impl Source for Disk {
+    #[inline]
+    fn solid_angle(&self) -> f64 {
+        self.cone.solid_angle()
+    }
+
+    #[inline]
+    fn radiance(&self, direction: [f64; 3]) -> f64 {
+        if dot(direction, self.axis) >= self.angular_radius.cos() {
+            self.radiance
+        } else {
+            0.0
+        }
+    }
+
+    #[inline]
+    fn sample(&self, u: [f64; 2]) -> [f64; 3] {
+        self.cone.sample(u)
+    }
+
+    #[inline]
+    fn pdf(&self, direction: [f64; 3]) -> f64 {
+        if dot(direction, self.axis) >= self.angular_radius.cos() {
+            self.cone.pdf()
+        } else {
+            0.0
+        }
+    }
+
+    fn irradiance(&self, normal: [f64; 3]) -> f64 {
+        let beta = libm::atan2(norm(cross(normal, self.axis)), dot(normal, self.axis));
+        self.radiance * self.projected_solid_angle(beta)
+    }
+}
+
+/// Sky of uniform radiance over the upper hemisphere, sampled with
+/// density proportional to the cosine from the zenith, which makes the
+/// estimate of horizontal irradiance exact for every sample. The ground
+/// below the horizon contributes nothing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sky {
+    radiance: f64,
+}
+
+impl Sky {
+    /// Sky of `radiance`
+    pub fn new(radiance: f64) -> Self {
+        Self { radiance }
+    }
+
+    /// The sky giving `horizontal_irradiance` (diffuse horizontal
+    /// irradiance) on level ground: `E = π L`
+    pub fn from_horizontal_irradiance(horizontal_irradiance: f64) -> Self {
+        Self::new(horizontal_irradiance / PI)
+    }
+}
+
+impl Source for Sky {
+    #[inline]
+    fn solid_angle(&self) -> f64 {
+        TAU
+    }
+
+    #[inline]
+    fn radiance(&self, direction: [f64; 3]) -> f64 {
+        if direction[2] > 0.0 {
+            self.radiance
+        } else {
+            0.0
+        }
+    }
+
+    /// Malley's method: uniform on the unit disk, lifted to the hemisphere
+    fn sample(&self, u: [f64; 2]) -> [f64; 3] {
+        let r = u[0].sqrt();
+        let (sin_phi, cos_phi) = (TAU * u[1]).sin_cos();
+        [r * cos_phi, r * sin_phi, (1.0 - u[0]).sqrt()]
+    }
+
+    #[inline]
+    fn pdf(&self, direction: [f64; 3]) -> f64 {
+        direction[2].max(0.0) / PI
+    }
+
+    /// Isotropic sky on a tilted plane: `π L (1 + n_z) / 2`
+    #[inline]
+    fn irradiance(&self, normal: [f64; 3]) -> f64 {
+        0.5 * PI * self.radiance * (1.0 + normal[2])
+    }
+}
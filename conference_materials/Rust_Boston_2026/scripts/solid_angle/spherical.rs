@@ -0,0 +1,171 @@
+//! Spherical geometry on the unit sphere: great-circle distance, polygon
+//! area, and point-in-polygon.
+//!
+//! Points are unit vectors; [lat_lon_to_unit] converts from geodetic
+//! degrees, treating the Earth as a sphere. A polygon is its vertices in
+//! order, joined by the shorter great-circle arcs, and encloses the region
+//! to its left, which is the counterclockwise region seen from outside
+//! the sphere. The area of that region in steradians is the solid angle
+//! it subtends at the center, so it comes straight from the tetrahedron
+//! kernel. Multiply by `R²` for area on a sphere of radius `R`.
+
+use crate::par::{chunk_len, par_threshold};
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::vec3::{cross, dot, norm};
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Unit vector for geodetic latitude and longitude in degrees, with `z`
+/// toward the north pole and `x` toward `(0, 0)`
+#[inline]
+pub fn lat_lon_to_unit(lat: f64, lon: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+/// Angle between two unit vectors, well-conditioned at every separation,
+/// unlike `acos(u·v)` near 0 and π or haversine near π
+#[inline]
+pub fn central_angle(u: [f64; 3], v: [f64; 3]) -> f64 {
+    libm::atan2(norm(cross(u, v)), dot(u, v)) // (rad)
+}
+
+/// Great-circle distance between two `[lat, lon]` points in degrees,
+/// on a sphere of the given radius
+#[inline]
+pub fn great_circle_distance(a: [f64; 2], b: [f64; 2], radius: f64) -> f64 {
+    radius * central_angle(lat_lon_to_unit(a[0], a[1]), lat_lon_to_unit(b[0], b[1])) // (m)
+}
+
+/// Vector variant of [great_circle_distance], over `[a, b]` pairs
+#[inline]
+pub fn great_circle_distances(pairs: &[[[f64; 2]; 2]], radius: f64, out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if pairs.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let [a, b] = pairs[i];
+        out[i] = great_circle_distance(a, b, radius);
+    }
+
+    Ok(())
+}
+
+/// Area of the spherical polygon on the unit sphere, equal to the solid
+/// angle it subtends at the center, in `[0, 4π)`. Reversing the vertex
+/// order gives the complement, `4π` minus the area.
+pub fn spherical_polygon_area(polygon: &[[f64; 3]]) -> f64 {
+    let Some(&v0) = polygon.first() else {
+        return 0.0;
+    };
+
+    // Fan of signed triangles from the first vertex; each is off by a
+    // multiple of 4π at most, so the sum is right modulo 4π
+    let sum: f64 = polygon[1..]
+        .windows(2)
+        .map(|w| solid_angle_tetrahedron_scalar([0.0; 3], v0, w[0], w[1]))
+        .sum(); // (sr)
+    sum.rem_euclid(4.0 * PI)
+}
+
+/// [spherical_polygon_area] for `[lat, lon]` vertices in degrees
+pub fn spherical_polygon_area_lat_lon(polygon: &[[f64; 2]]) -> f64 {
+    let vertices: Vec<[f64; 3]> = polygon.iter().map(|p| lat_lon_to_unit(p[0], p[1])).collect();
+    spherical_polygon_area(&vertices)
+}
+
+/// Whether the shorter arcs `ab` and `cd` cross at a point interior to
+/// both. Shared endpoints and collinear arcs count as not crossing.
+#[inline]
+fn arcs_cross(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> bool {
+    // c and d on opposite sides of the great circle through a and b
+    let ab = cross(a, b);
+    let acb = -dot(ab, c);
+    let bda = dot(ab, d);
+    if acb * bda <= 0.0 {
+        return false;
+    }
+    // and a and b on opposite sides of the one through c and d, consistently
+    let cd = cross(c, d);
+    let cbd = -dot(cd, b);
+    let dac = dot(cd, a);
+    acb * cbd > 0.0 && acb * dac > 0.0
+}
+
+/// Whether `point` is inside `polygon`, that is, to the left of its
+/// boundary. Undefined for points on the boundary, or almost antipodal to
+/// the first edge's midpoint, where the arc between them is ambiguous.
+///
+/// Counts boundary crossings along the arc to `point` from a reference
+/// point just left of the first edge, which is inside by construction.
+pub fn point_in_spherical_polygon(point: [f64; 3], polygon: &[[f64; 3]]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    let n = polygon.len();
+
+    // Reference point: nudge the first edge's midpoint toward its left side
+    let (v0, v1) = (polygon[0], polygon[1]);
+    let normal = cross(v0, v1);
+    let offset = 1e-9 / norm(normal);
+    let reference = [0, 1, 2].map(|i| 0.5 * (v0[i] + v1[i]) + offset * normal[i]);
+    let reference = {
+        let r = norm(reference);
+        reference.map(|x| x / r)
+    };
+
+    // If the reference and the point straddle an edge, that is a crossing
+    let crossings = (0..n)
+        .filter(|&i| arcs_cross(reference, point, polygon[i], polygon[(i + 1) % n]))
+        .count();
+    crossings % 2 == 0
+}
+
+/// Vector variant of [point_in_spherical_polygon], over many points
+#[inline]
+pub fn points_in_spherical_polygon(
+    polygon: &[[f64; 3]],
+    points: &[[f64; 3]],
+    out: &mut [bool],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if points.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        out[i] = point_in_spherical_polygon(points[i], polygon);
+    }
+
+    Ok(())
+}
+
+/// Parallel variant of [points_in_spherical_polygon]. Falls back to the
+/// serial kernel below [par_threshold] point-edge pairs.
+pub fn points_in_spherical_polygon_par(
+    polygon: &[[f64; 3]],
+    points: &[[f64; 3]],
+    out: &mut [bool],
+) -> Result<(), &'static str> {
+    // Check bounds
+    if points.len() != out.len() {
+        return Err("Dimension mismatch");
+    }
+
+    // Small batches are faster without the thread pool
+    if points.len().saturating_mul(polygon.len()) < par_threshold() {
+        return points_in_spherical_polygon(polygon, points, out);
+    }
+
+    let chunk = chunk_len(out.len());
+    (points.par_chunks(chunk), out.par_chunks_mut(chunk))
+        .into_par_iter()
+        .try_for_each(|(pc, oc)| points_in_spherical_polygon(polygon, pc, oc))
+}
@@ -0,0 +1,141 @@
+//! Summary statistics of kernel outputs.
+//!
+//! Moments come from one parallel pass: each chunk runs Welford's update,
+//! and chunks combine with Chan et al.'s pairwise merge, which stays
+//! accurate where the textbook sum-of-squares formula cancels. The
+//! histogram spans `[min, max]`, so it needs those first and takes a
+//! second pass. NaNs, e.g. from `degeneracy = "nan"`, are counted apart
+//! and left out of everything else.
+
+use rayon::prelude::*;
+use std::fmt;
+
+/// Histogram bins in [summarize]
+pub const DEFAULT_BINS: usize = 32;
+
+/// Elements per parallel chunk
+const CHUNK: usize = 1 << 14;
+
+/// Running count, mean, spread and extremes, mergeable across threads
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Moments {
+    pub count: usize,
+    pub nan: usize,
+    pub mean: f64,
+    /// Sum of squared deviations from the mean
+    pub m2: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for Moments {
+    fn default() -> Self {
+        Self { count: 0, nan: 0, mean: 0.0, m2: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+}
+
+impl Moments {
+    /// Welford's update
+    #[inline]
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            self.nan += 1;
+            return;
+        }
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// Chan et al.'s combination of two disjoint sets
+    pub fn merge(self, other: Self) -> Self {
+        let count = self.count + other.count;
+        let (mean, m2) = match (self.count, other.count) {
+            (0, _) => (other.mean, other.m2),
+            (_, 0) => (self.mean, self.m2),
+            (a, b) => {
+                let delta = other.mean - self.mean;
+                let (a, b, n) = (a as f64, b as f64, count as f64);
+                (self.mean + delta * b / n, self.m2 + other.m2 + delta * delta * a * b / n)
+            }
+        };
+        Self { count, nan: self.nan + other.nan, mean, m2, min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// Population variance, as NumPy's default
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.m2 / self.count as f64 }
+    }
+}
+
+/// Summary of one output array, from [summarize]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    /// Non-NaN elements
+    pub count: usize,
+    pub nan: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub std: f64,
+    /// Counts in equal bins spanning `[min, max]`, the last one closed
+    pub histogram: Vec<u64>,
+}
+
+/// Statistics and a [DEFAULT_BINS]-bin histogram of `results`
+pub fn summarize(results: &[f64]) -> Stats {
+    summarize_bins(results, DEFAULT_BINS)
+}
+
+/// [summarize] with `bins` histogram bins
+pub fn summarize_bins(results: &[f64], bins: usize) -> Stats {
+    let bins = bins.max(1);
+    let m = results
+        .par_chunks(CHUNK)
+        .map(|c| c.iter().fold(Moments::default(), |mut m, &x| {
+            m.push(x);
+            m
+        }))
+        .reduce(Moments::default, Moments::merge);
+
+    let width = (m.max - m.min) / bins as f64;
+    let histogram = if m.count == 0 {
+        vec![0; bins]
+    } else {
+        results
+            .par_chunks(CHUNK)
+            .map(|c| {
+                let mut h = vec![0_u64; bins];
+                for &x in c.iter().filter(|x| !x.is_nan()) {
+                    let i = if width > 0.0 { ((x - m.min) / width) as usize } else { 0 };
+                    h[i.min(bins - 1)] += 1;
+                }
+                h
+            })
+            .reduce(|| vec![0; bins], |a, b| a.iter().zip(&b).map(|(x, y)| x + y).collect())
+    };
+
+    let (min, max) = if m.count == 0 { (f64::NAN, f64::NAN) } else { (m.min, m.max) };
+    Stats { count: m.count, nan: m.nan, min, max, mean: if m.count == 0 { f64::NAN } else { m.mean }, std: m.variance().sqrt(), histogram }
+}
+
+impl fmt::Display for Stats {
+    /// A few lines for a terminal, with the histogram as a bar of blocks
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        writeln!(f, "count: {} ({} NaN)", self.count, self.nan)?;
+        writeln!(f, "min: {:.6e} max: {:.6e}", self.min, self.max)?;
+        writeln!(f, "mean: {:.6e} std: {:.6e}", self.mean, self.std)?;
+        let top = self.histogram.iter().copied().max().unwrap_or(0).max(1);
+        let bar: String = self
+            .histogram
+            .iter()
+            .map(|&c| if c == 0 { ' ' } else { BLOCKS[((c * 7).div_ceil(top)) as usize] })
+            .collect();
+        writeln!(f, "histogram: [{bar}]")
+    }
+}
@@ -0,0 +1,994 @@
+//! STEP (ISO 10303-21) boundary representations faceted into [TriMesh]es,
+//! so CAD models need no separate conversion toolchain. Needs the `step`
+//! feature.
+//!
+//! Each solid (`MANIFOLD_SOLID_BREP`, `BREP_WITH_VOIDS`) or surface model
+//! (`SHELL_BASED_SURFACE_MODEL`) becomes one mesh, in metres by the
+//! file's length unit. This is a faceter for analytic geometry, not a
+//! CAD kernel: faces may lie on planes, cylinders and cones, and their
+//! edges on lines, polylines, circles and ellipses. B-splines, tori,
+//! spheres and cone apexes are refused, naming the entity. Assembly
+//! placements are not applied, so each body stays in its own frame.
+//! IGES is not read; CAD tools export STEP AP203/AP214/AP242 as well.
+//!
+//! Every edge is sampled once, to within `chord` of its curve, and shared
+//! by the two faces it bounds, so a closed shell makes a closed mesh.
+//! Faces are triangulated in their surface's parameters (arc length
+//! around, height along), boundary loops bridged into one polygon and
+//! ear-clipped. On curved faces, interior edges whose midpoints stray
+//! more than `chord` from the surface are split until none do. Each face
+//! winds counterclockwise about its outward normal, as the shell's
+//! orientation flags give it.
+#![cfg(feature = "step")]
+
+use crate::mesh::TriMesh;
+use crate::vec3::{cross, dot, norm, sub};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::f64::consts::{PI, TAU};
+use std::io::{self, Read};
+
+/// Most rounds of splitting the interior edges of a curved face
+const MAX_REFINE: usize = 48;
+
+/// Most segments per edge, however fine `chord`
+const MAX_SEGMENTS: usize = 1 << 16;
+
+/// Deepest nesting of parameter lists, far beyond any real file's, so
+/// that a forged one can't exhaust the stack
+const MAX_NESTING: usize = 128;
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn unsupported(kind: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Unsupported STEP entity {kind}"))
+}
+
+/// Each body of a STEP stream, named as in the file, faceted to within
+/// `chord` (m) of its curved surfaces, in metres
+pub fn read_step<R: Read>(r: &mut R, chord: f64) -> io::Result<Vec<(String, TriMesh)>> {
+    if chord.is_nan() || chord <= 0.0 {
+        return Err(invalid("Chord tolerance must be positive"));
+    }
+    let mut text = Vec::new();
+    r.read_to_end(&mut text)?;
+    let model = Model::new(Parser { s: &text, at: 0, depth: 0 }.file()?)?;
+
+    let mut ids: Vec<usize> = model.entities.keys().copied().collect();
+    ids.sort_unstable();
+    let mut bodies = Vec::new();
+    for id in ids {
+        let (kind, p) = model.get(id)?;
+        let shells: Vec<usize> = match kind {
+            "MANIFOLD_SOLID_BREP" => vec![reference(arg(p, 1)?)?],
+            "BREP_WITH_VOIDS" => std::iter::once(arg(p, 1)).chain(list(arg(p, 2)?)?.iter().map(Ok)).map(|s| reference(s?)).collect::<io::Result<_>>()?,
+            "SHELL_BASED_SURFACE_MODEL" => list(arg(p, 1)?)?.iter().map(reference).collect::<io::Result<_>>()?,
+            _ => continue,
+        };
+        let name = match arg(p, 0)? {
+            Param::Str(s) if !s.is_empty() => s.clone(),
+            _ => format!("#{id}"),
+        };
+        let mut faceter = Faceter { model: &model, chord: chord / model.length, vertices: Vec::new(), points: HashMap::new(), edges: HashMap::new(), faces: Vec::new() };
+        for shell in shells {
+            faceter.shell(shell, false)?;
+        }
+        let vertices = faceter.vertices.iter().map(|v| v.map(|x| x * model.length)).collect();
+        bodies.push((name, TriMesh::new(vertices, faceter.faces).map_err(invalid)?));
+    }
+    Ok(bodies)
+}
+
+/// A parameter of an entity
+#[derive(Clone, Debug, PartialEq)]
+enum Param {
+    Ref(usize),
+    Number(f64),
+    Str(String),
+    Enum(String),
+    List(Vec<Param>),
+    /// A value tagged with its type, `LENGTH_MEASURE(2.5)`
+    Typed(String, Vec<Param>),
+    /// `$` or `*`
+    Unset,
+}
+
+/// An entity instance: one part, or several for a complex instance
+/// `(A(..) B(..))`
+type Entity = Vec<(String, Vec<Param>)>;
+
+fn arg(p: &[Param], i: usize) -> io::Result<&Param> {
+    p.get(i).ok_or_else(|| invalid("Too few STEP parameters"))
+}
+
+fn reference(p: &Param) -> io::Result<usize> {
+    match p {
+        Param::Ref(id) => Ok(*id),
+        _ => Err(invalid("Expected a STEP reference")),
+    }
+}
+
+fn number(p: &Param) -> io::Result<f64> {
+    match p {
+        Param::Number(x) => Ok(*x),
+        Param::Typed(_, inner) if inner.len() == 1 => number(&inner[0]),
+        _ => Err(invalid("Expected a STEP number")),
+    }
+}
+
+fn list(p: &Param) -> io::Result<&[Param]> {
+    match p {
+        Param::List(items) => Ok(items),
+        _ => Err(invalid("Expected a STEP list")),
+    }
+}
+
+fn boolean(p: &Param) -> io::Result<bool> {
+    match p {
+        Param::Enum(e) if e == "T" => Ok(true),
+        Param::Enum(e) if e == "F" => Ok(false),
+        _ => Err(invalid("Expected a STEP boolean")),
+    }
+}
+
+/// Recursive descent over the exchange structure
+struct Parser<'a> {
+    s: &'a [u8],
+    at: usize,
+    /// Parameter lists open at `at`
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Entity instances of every data section
+    fn file(&mut self) -> io::Result<HashMap<usize, Entity>> {
+        if self.keyword()? != "ISO-10303-21" {
+            return Err(invalid("Not a STEP file"));
+        }
+        self.expect(b';')?;
+        let mut entities = HashMap::new();
+        loop {
+            match self.keyword()?.as_str() {
+                "HEADER" => {
+                    self.expect(b';')?;
+                    while self.keyword()? != "ENDSEC" {
+                        self.params()?;
+                        self.expect(b';')?;
+                    }
+                }
+                "DATA" => {
+                    if self.peek() == Some(b'(') {
+                        self.params()?; // AP242's named sections
+                    }
+                    self.expect(b';')?;
+                    while self.peek() == Some(b'#') {
+                        self.at += 1;
+                        let id = self.integer()?;
+                        self.expect(b'=')?;
+                        entities.insert(id, self.entity()?);
+                        self.expect(b';')?;
+                    }
+                    if self.keyword()? != "ENDSEC" {
+                        return Err(invalid("Malformed STEP data section"));
+                    }
+                }
+                "END-ISO-10303-21" => return Ok(entities),
+                _ => return Err(invalid("Malformed STEP file")),
+            }
+            self.expect(b';')?;
+        }
+    }
+
+    /// Skip whitespace and comments, and look at what follows
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            while self.s.get(self.at).is_some_and(u8::is_ascii_whitespace) {
+                self.at += 1;
+            }
+            if !self.s[self.at..].starts_with(b"/*") {
+                return self.s.get(self.at).copied();
+            }
+            self.at = self.s[self.at..].windows(2).position(|w| w == b"*/").map_or(self.s.len(), |i| self.at + i + 2);
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> io::Result<()> {
+        if self.peek() != Some(c) {
+            return Err(invalid("Malformed STEP file"));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self) -> io::Result<String> {
+        self.peek();
+        let start = self.at;
+        while self.s.get(self.at).is_some_and(|&c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+            self.at += 1;
+        }
+        if start == self.at {
+            return Err(invalid("Malformed STEP file"));
+        }
+        Ok(String::from_utf8_lossy(&self.s[start..self.at]).to_ascii_uppercase())
+    }
+
+    fn integer(&mut self) -> io::Result<usize> {
+        let start = self.at;
+        while self.s.get(self.at).is_some_and(u8::is_ascii_digit) {
+            self.at += 1;
+        }
+        std::str::from_utf8(&self.s[start..self.at]).ok().and_then(|d| d.parse().ok()).ok_or_else(|| invalid("Malformed STEP reference"))
+    }
+
+    /// `NAME(..)`, or `(NAME(..) NAME(..))` for a complex instance
+    fn entity(&mut self) -> io::Result<Entity> {
+        if self.peek() != Some(b'(') {
+            return Ok(vec![(self.keyword()?, self.params()?)]);
+        }
+        self.at += 1;
+        let mut parts = Vec::new();
+        while self.peek() != Some(b')') {
+            parts.push((self.keyword()?, self.params()?));
+        }
+        self.at += 1;
+        Ok(parts)
+    }
+
+    /// `(..)`, at most [MAX_NESTING] deep
+    fn params(&mut self) -> io::Result<Vec<Param>> {
+        if self.depth == MAX_NESTING {
+            return Err(invalid("STEP parameters nested too deeply"));
+        }
+        self.depth += 1;
+        let params = self.param_list();
+        self.depth -= 1;
+        params
+    }
+
+    fn param_list(&mut self) -> io::Result<Vec<Param>> {
+        self.expect(b'(')?;
+        let mut params = Vec::new();
+        if self.peek() == Some(b')') {
+            self.at += 1;
+            return Ok(params);
+        }
+        loop {
+            params.push(self.param()?);
+            match self.peek() {
+                Some(b',') => self.at += 1,
+                Some(b')') => {
+                    self.at += 1;
+                    return Ok(params);
+                }
+                _ => return Err(invalid("Malformed STEP parameters")),
+            }
+        }
+    }
+
+    fn param(&mut self) -> io::Result<Param> {
+        match self.peek().ok_or_else(|| invalid("Truncated STEP file"))? {
+            b'#' => {
+                self.at += 1;
+                Ok(Param::Ref(self.integer()?))
+            }
+            b'$' | b'*' => {
+                self.at += 1;
+                Ok(Param::Unset)
+            }
+            b'(' => Ok(Param::List(self.params()?)),
+            quote @ (b'\'' | b'"') => {
+                // Strings double their quotes; binaries never contain one
+                let mut s = Vec::new();
+                self.at += 1;
+                loop {
+                    match self.s.get(self.at) {
+                        None => return Err(invalid("Truncated STEP file")),
+                        Some(&c) if c == quote && self.s.get(self.at + 1) == Some(&quote) => {
+                            s.push(c);
+                            self.at += 2;
+                        }
+                        Some(&c) if c == quote => break,
+                        Some(&c) => {
+                            s.push(c);
+                            self.at += 1;
+                        }
+                    }
+                }
+                self.at += 1;
+                Ok(Param::Str(String::from_utf8_lossy(&s).into_owned()))
+            }
+            b'.' => {
+                self.at += 1;
+                let e = self.keyword()?;
+                self.expect(b'.')?;
+                Ok(Param::Enum(e))
+            }
+            b'0'..=b'9' | b'-' | b'+' => {
+                let start = self.at;
+                while self.s.get(self.at).is_some_and(|&c| c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'-' | b'+')) {
+                    self.at += 1;
+                }
+                let digits = std::str::from_utf8(&self.s[start..self.at]).expect("ASCII");
+                digits.parse().map(Param::Number).map_err(|_| invalid("Malformed STEP number"))
+            }
+            _ => Ok(Param::Typed(self.keyword()?, self.params()?)),
+        }
+    }
+}
+
+/// Placement of a surface or curve: origin and right-handed unit axes
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    origin: [f64; 3],
+    x: [f64; 3],
+    y: [f64; 3],
+    z: [f64; 3],
+}
+
+impl Frame {
+    fn at(&self, x: f64, y: f64, z: f64) -> [f64; 3] {
+        std::array::from_fn(|k| self.origin[k] + x * self.x[k] + y * self.y[k] + z * self.z[k])
+    }
+
+    fn local(&self, p: [f64; 3]) -> [f64; 3] {
+        let d = sub(p, self.origin);
+        [dot(d, self.x), dot(d, self.y), dot(d, self.z)]
+    }
+}
+
+/// The entities, with the file's units
+struct Model {
+    entities: HashMap<usize, Entity>,
+    /// Metres per length unit
+    length: f64,
+    /// Radians per plane angle unit
+    angle: f64,
+}
+
+impl Model {
+    fn new(entities: HashMap<usize, Entity>) -> io::Result<Self> {
+        // The first unit of each kind, in file order
+        let mut ids: Vec<usize> = entities.keys().copied().collect();
+        ids.sort_unstable();
+        let mut model = Self { entities, length: f64::NAN, angle: 1.0 };
+        let first = |kind: &str| ids.iter().copied().find(|id| model.entities[id].iter().any(|(k, _)| k == kind));
+        let (length, angle) = (first("LENGTH_UNIT"), first("PLANE_ANGLE_UNIT"));
+        model.length = model.unit(length.ok_or_else(|| invalid("STEP file has no length unit"))?)?;
+        if let Some(angle) = angle {
+            model.angle = model.unit(angle)?;
+        }
+        Ok(model)
+    }
+
+    /// First part of entity `id`
+    fn get(&self, id: usize) -> io::Result<(&str, &[Param])> {
+        let entity = self.entities.get(&id).ok_or_else(|| invalid("Dangling STEP reference"))?;
+        entity.first().map(|(k, p)| (k.as_str(), p.as_slice())).ok_or_else(|| invalid("Empty STEP entity"))
+    }
+
+    /// Part `kind` of entity `id`, simple or complex
+    fn part(&self, id: usize, kind: &str) -> Option<&[Param]> {
+        self.entities.get(&id)?.iter().find(|(k, _)| k == kind).map(|(_, p)| p.as_slice())
+    }
+
+    /// Size of unit `id` in SI base units: a prefixed SI unit, or a named
+    /// one defined by a measure in another unit, followed down to the SI
+    /// unit at the end of the chain
+    fn unit(&self, mut id: usize) -> io::Result<f64> {
+        let mut scale = 1.0;
+        let mut seen = HashSet::new();
+        while seen.insert(id) {
+            if let Some(p) = self.part(id, "SI_UNIT") {
+                return match arg(p, 0)? {
+                    Param::Unset => Ok(scale),
+                    Param::Enum(prefix) => si_prefix(prefix).map(|x| scale * x).ok_or_else(|| unsupported(prefix)),
+                    _ => Err(invalid("Malformed SI unit")),
+                };
+            }
+            let p = self.part(id, "CONVERSION_BASED_UNIT").ok_or_else(|| invalid("Unsupported STEP unit"))?;
+            let (_, measure) = self.get(reference(arg(p, 1)?)?)?;
+            scale *= number(arg(measure, 0)?)?;
+            id = reference(arg(measure, 1)?)?;
+        }
+        Err(invalid("Cyclic STEP unit"))
+    }
+
+    fn point(&self, id: usize) -> io::Result<[f64; 3]> {
+        match self.get(id)? {
+            ("CARTESIAN_POINT", p) => coordinates(list(arg(p, 1)?)?),
+            (kind, _) => Err(unsupported(kind)),
+        }
+    }
+
+    fn direction(&self, id: usize) -> io::Result<[f64; 3]> {
+        let d = match self.get(id)? {
+            ("DIRECTION", p) => coordinates(list(arg(p, 1)?)?)?,
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let len = norm(d);
+        if len.is_nan() || len == 0.0 {
+            return Err(invalid("Zero STEP direction"));
+        }
+        Ok(d.map(|x| x / len))
+    }
+
+    /// `AXIS2_PLACEMENT_3D`: `z` the axis, `x` the reference direction
+    /// made perpendicular to it
+    fn frame(&self, id: usize) -> io::Result<Frame> {
+        let p = match self.get(id)? {
+            ("AXIS2_PLACEMENT_3D", p) => p,
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let origin = self.point(reference(arg(p, 1)?)?)?;
+        let z = match arg(p, 2)? {
+            Param::Unset => [0.0, 0.0, 1.0],
+            d => self.direction(reference(d)?)?,
+        };
+        let reference_x = match p.get(3) {
+            None | Some(Param::Unset) => [1.0, 0.0, 0.0],
+            Some(d) => self.direction(reference(d)?)?,
+        };
+        let mut x: [f64; 3] = std::array::from_fn(|k| reference_x[k] - dot(reference_x, z) * z[k]);
+        if norm(x) < 1e-9 {
+            x = std::array::from_fn(|k| [0.0, 1.0, 0.0][k] - dot([0.0, 1.0, 0.0], z) * z[k]);
+        }
+        let len = norm(x);
+        let x = x.map(|c| c / len);
+        Ok(Frame { origin, x, y: cross(z, x), z })
+    }
+}
+
+fn si_prefix(prefix: &str) -> Option<f64> {
+    let exponent = match prefix {
+        "EXA" => 18,
+        "PETA" => 15,
+        "TERA" => 12,
+        "GIGA" => 9,
+        "MEGA" => 6,
+        "KILO" => 3,
+        "HECTO" => 2,
+        "DECA" => 1,
+        "DECI" => -1,
+        "CENTI" => -2,
+        "MILLI" => -3,
+        "MICRO" => -6,
+        "NANO" => -9,
+        "PICO" => -12,
+        "FEMTO" => -15,
+        "ATTO" => -18,
+        _ => return None,
+    };
+    Some(10.0_f64.powi(exponent))
+}
+
+fn coordinates(c: &[Param]) -> io::Result<[f64; 3]> {
+    match c {
+        [x, y] => Ok([number(x)?, number(y)?, 0.0]),
+        [x, y, z] => Ok([number(x)?, number(y)?, number(z)?]),
+        _ => Err(invalid("Malformed STEP point")),
+    }
+}
+
+/// Surfaces faces may lie on, with parameters `(θ, v)`: angle about the
+/// axis and height along it, or plane coordinates
+#[derive(Clone, Copy, Debug)]
+enum Surface {
+    Plane(Frame),
+    Cylinder(Frame, f64),
+    /// Radius at the origin and tangent of the half-angle
+    Cone(Frame, f64, f64),
+}
+
+impl Surface {
+    fn frame(&self) -> &Frame {
+        match self {
+            Self::Plane(f) | Self::Cylinder(f, _) | Self::Cone(f, _, _) => f,
+        }
+    }
+
+    fn curved(&self) -> bool {
+        !matches!(self, Self::Plane(_))
+    }
+
+    /// Parameters of a point on the surface, and its distance from the
+    /// axis
+    fn param(&self, p: [f64; 3]) -> ([f64; 2], f64) {
+        let [x, y, z] = self.frame().local(p);
+        match self {
+            Self::Plane(_) => ([x, y], 0.0),
+            _ => ([y.atan2(x), z], x.hypot(y)),
+        }
+    }
+
+    fn at(&self, [a, v]: [f64; 2]) -> [f64; 3] {
+        match *self {
+            Self::Plane(f) => f.at(a, v, 0.0),
+            Self::Cylinder(f, r) => f.at(r * a.cos(), r * a.sin(), v),
+            Self::Cone(f, r, tan) => f.at((r + v * tan) * a.cos(), (r + v * tan) * a.sin(), v),
+        }
+    }
+}
+
+/// A face's vertex: where it is in the face's parameters, and which mesh
+/// vertex it is. A seam's vertices appear twice, a period apart.
+#[derive(Clone, Copy, Debug)]
+struct Local {
+    uv: [f64; 2],
+    id: u32,
+}
+
+/// Builds one body's mesh, sharing vertices and edge samples between its
+/// faces
+struct Faceter<'a> {
+    model: &'a Model,
+    /// In the file's length unit
+    chord: f64,
+    vertices: Vec<[f64; 3]>,
+    /// Mesh vertex of each `VERTEX_POINT`
+    points: HashMap<usize, u32>,
+    /// Samples of each `EDGE_CURVE`, start to end
+    edges: HashMap<usize, Vec<u32>>,
+    faces: Vec<[u32; 3]>,
+}
+
+impl Faceter<'_> {
+    fn push(&mut self, p: [f64; 3]) -> u32 {
+        self.vertices.push(p);
+        self.vertices.len() as u32 - 1
+    }
+
+    fn shell(&mut self, id: usize, flip: bool) -> io::Result<()> {
+        let model = self.model;
+        match model.get(id)? {
+            ("CLOSED_SHELL" | "OPEN_SHELL", p) => {
+                for face in list(arg(p, 1)?)? {
+                    self.face(reference(face)?, flip)?;
+                }
+                Ok(())
+            }
+            ("ORIENTED_CLOSED_SHELL" | "ORIENTED_OPEN_SHELL", p) => self.shell(reference(arg(p, 2)?)?, flip ^ !boolean(arg(p, 3)?)?),
+            (kind, _) => Err(unsupported(kind)),
+        }
+    }
+
+    fn vertex(&mut self, id: usize) -> io::Result<u32> {
+        if let Some(&v) = self.points.get(&id) {
+            return Ok(v);
+        }
+        let p = match self.model.get(id)? {
+            ("VERTEX_POINT", p) => self.model.point(reference(arg(p, 1)?)?)?,
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let v = self.push(p);
+        self.points.insert(id, v);
+        Ok(v)
+    }
+
+    /// Samples of an `EDGE_CURVE` from its start vertex to its end
+    fn edge(&mut self, id: usize) -> io::Result<Vec<u32>> {
+        if let Some(samples) = self.edges.get(&id) {
+            return Ok(samples.clone());
+        }
+        let model = self.model;
+        let p = match model.get(id)? {
+            ("EDGE_CURVE", p) => p,
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let (a, b) = (self.vertex(reference(arg(p, 1)?)?)?, self.vertex(reference(arg(p, 2)?)?)?);
+        let same_sense = boolean(arg(p, 4)?)?;
+
+        // Seam and surface curves carry the curve in space first
+        let mut curve = reference(arg(p, 3)?)?;
+        while let ("SURFACE_CURVE" | "SEAM_CURVE", c) = model.get(curve)? {
+            curve = reference(arg(c, 1)?)?;
+        }
+        let mut samples = vec![a];
+        match model.get(curve)? {
+            ("LINE", _) => {}
+            ("POLYLINE", c) => {
+                let points = list(arg(c, 1)?)?;
+                if points.len() < 2 {
+                    return Err(invalid("STEP polyline with fewer than two points"));
+                }
+                let mut inner: Vec<[f64; 3]> = points[1..points.len() - 1].iter().map(|q| model.point(reference(q)?)).collect::<io::Result<_>>()?;
+                if !same_sense {
+                    inner.reverse();
+                }
+                samples.extend(inner.into_iter().map(|q| self.push(q)));
+            }
+            (kind @ ("CIRCLE" | "ELLIPSE"), c) => {
+                let frame = model.frame(reference(arg(c, 1)?)?)?;
+                let (ra, rb) = if kind == "CIRCLE" { (number(arg(c, 2)?)?, number(arg(c, 2)?)?) } else { (number(arg(c, 2)?)?, number(arg(c, 3)?)?) };
+                let angle = |v: u32| {
+                    let [x, y, _] = frame.local(self.vertices[v as usize]);
+                    (y / rb).atan2(x / ra)
+                };
+
+                // Around the curve's own sense, or against it; a closed
+                // edge goes all the way round
+                let (start, end) = (angle(a), angle(b));
+                let sweep = if same_sense { (end - start).rem_euclid(TAU) } else { (start - end).rem_euclid(TAU) };
+                let sweep = if a == b || sweep == 0.0 { TAU } else { sweep };
+                let r = ra.max(rb);
+                let step = if self.chord < r { 2.0 * (1.0 - self.chord / r).acos() } else { PI };
+                let n = ((sweep / step).ceil() as usize).clamp(if a == b { 3 } else { 1 }, MAX_SEGMENTS);
+                let sign = if same_sense { 1.0 } else { -1.0 };
+                for i in 1..n {
+                    let t = start + sign * sweep * i as f64 / n as f64;
+                    let q = frame.at(ra * t.cos(), rb * t.sin(), 0.0);
+                    samples.push(self.push(q));
+                }
+            }
+            (kind, _) => return Err(unsupported(kind)),
+        }
+        samples.push(b);
+        self.edges.insert(id, samples.clone());
+        Ok(samples)
+    }
+
+    /// Mesh vertices around a bound, each once, in the bound's sense;
+    /// `None` for a lone vertex
+    fn bound(&mut self, id: usize) -> io::Result<Option<Vec<u32>>> {
+        let model = self.model;
+        let (kind, p) = model.get(id)?;
+        if kind != "FACE_OUTER_BOUND" && kind != "FACE_BOUND" {
+            return Err(unsupported(kind));
+        }
+        let forward = boolean(arg(p, 2)?)?;
+        let edges = match model.get(reference(arg(p, 1)?)?)? {
+            ("EDGE_LOOP", l) => list(arg(l, 1)?)?,
+            ("VERTEX_LOOP", _) => return Ok(None),
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let mut ids: Vec<u32> = Vec::new();
+        for e in edges {
+            let mut samples = match model.get(reference(e)?)? {
+                ("ORIENTED_EDGE", o) => {
+                    let mut s = self.edge(reference(arg(o, 3)?)?)?;
+                    if !boolean(arg(o, 4)?)? {
+                        s.reverse();
+                    }
+                    s
+                }
+                (kind, _) => return Err(unsupported(kind)),
+            };
+            if ids.last().is_some_and(|&last| last != samples[0]) {
+                return Err(invalid("STEP edge loop not connected"));
+            }
+            if !ids.is_empty() {
+                samples.remove(0);
+            }
+            ids.extend(samples);
+        }
+        if ids.len() < 2 || ids.first() != ids.last() {
+            return Err(invalid("STEP edge loop not closed"));
+        }
+        ids.pop();
+        if !forward {
+            ids.reverse();
+        }
+        Ok(Some(ids))
+    }
+
+    fn face(&mut self, id: usize, flip: bool) -> io::Result<()> {
+        let model = self.model;
+        let p = match model.get(id)? {
+            ("ADVANCED_FACE" | "FACE_SURFACE", p) => p,
+            ("ORIENTED_FACE", p) => return self.face(reference(arg(p, 2)?)?, flip ^ !boolean(arg(p, 3)?)?),
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let surface = match model.get(reference(arg(p, 2)?)?)? {
+            ("PLANE", s) => Surface::Plane(model.frame(reference(arg(s, 1)?)?)?),
+            ("CYLINDRICAL_SURFACE", s) => Surface::Cylinder(model.frame(reference(arg(s, 1)?)?)?, number(arg(s, 2)?)?),
+            ("CONICAL_SURFACE", s) => Surface::Cone(model.frame(reference(arg(s, 1)?)?)?, number(arg(s, 2)?)?, (number(arg(s, 3)?)? * model.angle).tan()),
+            (kind, _) => return Err(unsupported(kind)),
+        };
+        let mut loops = Vec::new();
+        for b in list(arg(p, 1)?)? {
+            if let Some(ids) = self.bound(reference(b)?)? {
+                loops.push(ids);
+            }
+        }
+
+        // Parameters: plane coordinates, or arc length at the face's mean
+        // radius and height, unwrapped along each loop
+        let params: Vec<Vec<([f64; 2], f64)>> = loops.iter().map(|l| l.iter().map(|&v| surface.param(self.vertices[v as usize])).collect()).collect();
+        let count = params.iter().map(Vec::len).sum::<usize>() as f64;
+        let radius = params.iter().flatten().map(|&(_, r)| r).sum::<f64>() / count;
+        if surface.curved() && params.iter().flatten().any(|&(_, r)| r <= 1e-9 * radius) {
+            return Err(invalid("Unsupported STEP geometry: a cone's apex"));
+        }
+        let period = if surface.curved() { TAU * radius } else { f64::INFINITY };
+        let mut polygons: Vec<(Vec<Local>, f64)> = Vec::new();
+        for (ids, uv) in loops.iter().zip(&params) {
+            let mut locals: Vec<Local> = Vec::with_capacity(ids.len());
+            for (&id, &([a, v], _)) in ids.iter().zip(uv) {
+                let u = if surface.curved() { radius * a } else { a };
+                let u = match locals.last() {
+                    Some(prev) if surface.curved() => u + period * ((prev.uv[0] - u) / period).round(),
+                    _ => u,
+                };
+                locals.push(Local { uv: [u, v], id });
+            }
+            let first = locals[0].uv[0];
+            let last = locals[locals.len() - 1].uv[0];
+            let close = first + period * ((last - first) / period).round();
+            polygons.push((locals, if surface.curved() { close - first } else { 0.0 }));
+        }
+
+        // One polygon: holes bridged to the outer loop, or a band between
+        // two loops that wrap round the surface cut along a seam
+        let wraps = polygons.iter().filter(|(_, net)| net.abs() > 0.5 * period).count();
+        let polygon = match wraps {
+            0 => {
+                let mut rings: Vec<Vec<Local>> = polygons.into_iter().map(|(l, _)| l).collect();
+                let outer = (0..rings.len()).max_by(|&i, &j| area(&rings[i]).abs().total_cmp(&area(&rings[j]).abs())).ok_or_else(|| invalid("STEP face without bounds"))?;
+                let mut outer = rings.swap_remove(outer);
+                if area(&outer) < 0.0 {
+                    outer.reverse();
+                }
+                for hole in &mut rings {
+                    if area(hole) > 0.0 {
+                        hole.reverse();
+                    }
+                }
+                bridge(outer, rings)
+            }
+            2 if polygons.len() == 2 => band(&polygons[0], &polygons[1], period),
+            _ => return Err(invalid("Unsupported STEP geometry: a face wrapping its surface")),
+        };
+
+        // Triangulate, split what strays from the surface, and wind about
+        // the face's normal. Copies of a vertex at one place, as bridging
+        // makes, are one; a seam's two sides are not. Boundary edges stay
+        // as sampled, shared with the neighbouring faces.
+        let mut first: HashMap<(u32, u64, u64), usize> = HashMap::new();
+        let same: Vec<usize> = polygon.iter().enumerate().map(|(i, l)| *first.entry((l.id, l.uv[0].to_bits(), l.uv[1].to_bits())).or_insert(i)).collect();
+        let loop_edges: HashSet<(u32, u32)> = loops.iter().flat_map(|l| (0..l.len()).map(|i| key(l[i], l[(i + 1) % l.len()]))).collect();
+        let n = polygon.len();
+        let boundary: HashSet<(usize, usize)> = (0..n)
+            .filter(|&i| loop_edges.contains(&key(polygon[i].id, polygon[(i + 1) % n].id)))
+            .map(|i| (same[i].min(same[(i + 1) % n]), same[i].max(same[(i + 1) % n])))
+            .collect();
+        let mut triangles: Vec<[usize; 3]> = ear_clip(&polygon)?.into_iter().map(|t| t.map(|i| same[i])).collect();
+        let mut locals = polygon;
+        if surface.curved() {
+            self.refine(&surface, radius, &boundary, &mut locals, &mut triangles)?;
+        }
+        let same_sense = boolean(arg(p, 3)?)?;
+        for t in triangles {
+            let [a, b, c] = t.map(|i| locals[i].id);
+            if a != b && b != c && c != a {
+                self.faces.push(if same_sense != flip { [a, b, c] } else { [a, c, b] });
+            }
+        }
+        Ok(())
+    }
+
+    /// Split interior edges whose midpoints stray more than `chord` from
+    /// the surface, and the triangles either side of them, until none do.
+    /// A triangle split at all is split across the edge spanning it around
+    /// the axis, the way the surface curves, so each split narrows it.
+    fn refine(&mut self, surface: &Surface, radius: f64, boundary: &HashSet<(usize, usize)>, locals: &mut Vec<Local>, triangles: &mut Vec<[usize; 3]>) -> io::Result<()> {
+        let on = |[u, v]: [f64; 2]| surface.at([u / radius, v]);
+        let edge = |t: &[usize; 3], k: usize| (t[k].min(t[(k + 1) % 3]), t[k].max(t[(k + 1) % 3]));
+        for _ in 0..MAX_REFINE {
+            let strays = |(i, j): (usize, usize)| {
+                let (a, b) = (locals[i], locals[j]);
+                let (pa, pb) = (self.vertices[a.id as usize], self.vertices[b.id as usize]);
+                let chord_mid: [f64; 3] = std::array::from_fn(|k| 0.5 * (pa[k] + pb[k]));
+                norm(sub(on(mid(a.uv, b.uv)), chord_mid)) > self.chord
+            };
+            let mut marked: BTreeSet<(usize, usize)> = triangles.iter().flat_map(|t| (0..3).map(move |k| edge(t, k))).filter(|e| !boundary.contains(e) && strays(*e)).collect();
+            if marked.is_empty() {
+                return Ok(());
+            }
+            let widest = |t: &[usize; 3]| {
+                let width = |k: usize| (locals[t[(k + 1) % 3]].uv[0] - locals[t[k]].uv[0]).abs();
+                (0..3).max_by(|&k, &l| width(k).total_cmp(&width(l))).expect("Three edges")
+            };
+            let widest: Vec<usize> = triangles.iter().map(widest).collect();
+            loop {
+                let before = marked.len();
+                for (t, &k) in triangles.iter().zip(&widest) {
+                    let e = edge(t, k);
+                    if !boundary.contains(&e) && (0..3).any(|k| marked.contains(&edge(t, k))) {
+                        marked.insert(e);
+                    }
+                }
+                if marked.len() == before {
+                    break;
+                }
+            }
+
+            let mut split: HashMap<(usize, usize), usize> = HashMap::with_capacity(marked.len());
+            for (i, j) in marked {
+                let uv = mid(locals[i].uv, locals[j].uv);
+                let id = self.push(on(uv));
+                locals.push(Local { uv, id });
+                split.insert((i, j), locals.len() - 1);
+            }
+            let at = |i: usize, j: usize| split.get(&(i.min(j), i.max(j))).copied();
+            let mut next = Vec::with_capacity(4 * triangles.len());
+            for (t, &k) in triangles.iter().zip(&widest) {
+                // Rotate so the first edge is split: the widest, if it is
+                let r = std::iter::once(k).chain(0..3).find(|&k| split.contains_key(&edge(t, k)));
+                let Some(r) = r else {
+                    next.push(*t);
+                    continue;
+                };
+                let [a, b, c] = [t[r], t[(r + 1) % 3], t[(r + 2) % 3]];
+                let ab = at(a, b).expect("Chosen split");
+                match (at(b, c), at(c, a)) {
+                    (None, None) => next.extend([[a, ab, c], [ab, b, c]]),
+                    (Some(bc), None) => next.extend([[a, ab, c], [ab, b, bc], [ab, bc, c]]),
+                    (None, Some(ca)) => next.extend([[a, ab, ca], [ab, c, ca], [ab, b, c]]),
+                    (Some(bc), Some(ca)) => next.extend([[a, ab, ca], [ab, c, ca], [ab, b, bc], [ab, bc, c]]),
+                }
+            }
+            *triangles = next;
+        }
+        Err(invalid("STEP face did not refine to the chord tolerance"))
+    }
+}
+
+fn mid(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [0.5 * (a[0] + b[0]), 0.5 * (a[1] + b[1])]
+}
+
+/// Undirected edge between two mesh vertices
+fn key(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+/// Signed area of a polygon in the parameter plane, positive
+/// counterclockwise
+fn area(polygon: &[Local]) -> f64 {
+    let n = polygon.len();
+    0.5 * (0..n).map(|i| perp(polygon[i].uv, polygon[(i + 1) % n].uv)).sum::<f64>()
+}
+
+fn perp(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+/// Twice the signed area of triangle `abc`
+fn orient(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Whether segments `ab` and `cd` cross at a point interior to both
+fn crosses(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    let (d1, d2) = (orient(a, b, c), orient(a, b, d));
+    let (d3, d4) = (orient(c, d, a), orient(c, d, b));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Join clockwise `holes` into counterclockwise `outer` by a cut from each
+/// hole's rightmost vertex to the nearest polygon vertex it can see
+fn bridge(mut outer: Vec<Local>, mut holes: Vec<Vec<Local>>) -> Vec<Local> {
+    let rightmost = |h: &[Local]| (0..h.len()).max_by(|&i, &j| h[i].uv[0].total_cmp(&h[j].uv[0])).unwrap_or(0);
+    holes.sort_by(|a, b| b[rightmost(b)].uv[0].total_cmp(&a[rightmost(a)].uv[0]));
+    for h in 0..holes.len() {
+        let m = rightmost(&holes[h]);
+        let from = holes[h][m].uv;
+        let edges = |ring: &[Local]| (0..ring.len()).map(|i| (ring[i].uv, ring[(i + 1) % ring.len()].uv)).collect::<Vec<_>>();
+        let blocking: Vec<([f64; 2], [f64; 2])> = std::iter::once(&outer).chain(&holes[h..]).flat_map(|r| edges(r)).collect();
+        let mut order: Vec<usize> = (0..outer.len()).collect();
+        let dist = |i: usize| (outer[i].uv[0] - from[0]).hypot(outer[i].uv[1] - from[1]);
+        order.sort_by(|&i, &j| dist(i).total_cmp(&dist(j)));
+        let j = order.into_iter().find(|&j| blocking.iter().all(|&(c, d)| !crosses(from, outer[j].uv, c, d))).unwrap_or(0);
+        let hole = &holes[h];
+        let cut: Vec<Local> = hole[m..].iter().chain(&hole[..=m]).copied().chain(std::iter::once(outer[j])).collect();
+        outer.splice(j + 1..j + 1, cut);
+    }
+    outer
+}
+
+/// One polygon from two loops that each wrap once round a periodic
+/// surface, cut open along a seam between their first points
+fn band((a, net_a): &(Vec<Local>, f64), (b, net_b): &(Vec<Local>, f64), period: f64) -> Vec<Local> {
+    let shift = |l: Local, du: f64| Local { uv: [l.uv[0] + du, l.uv[1]], id: l.id };
+
+    // The first loop forward, the second back, so they run apart
+    let mut a = a.clone();
+    if *net_a < 0.0 {
+        a.reverse();
+    }
+    let mut b = b.clone();
+    if *net_b > 0.0 {
+        b.reverse();
+    }
+    let end = shift(a[0], period);
+
+    // Start the second loop where it is closest round to the first's start
+    let near = |l: &Local| (l.uv[0] - end.uv[0]).rem_euclid(period).min((end.uv[0] - l.uv[0]).rem_euclid(period));
+    let k = (0..b.len()).min_by(|&i, &j| near(&b[i]).total_cmp(&near(&b[j]))).unwrap_or(0);
+    let s = period * ((end.uv[0] - b[k].uv[0]) / period).round();
+    let mut polygon = a.clone();
+    polygon.push(end);
+    polygon.extend(b[k..].iter().map(|&l| shift(l, s)));
+    polygon.extend(b[..=k].iter().map(|&l| shift(l, s - period)));
+    if area(&polygon) < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+/// Ear clipping of a counterclockwise polygon, which may touch itself
+/// along cuts; indices into it. The ear with the shortest diagonal goes
+/// first, which strips a band round a cylinder into one row of triangles
+/// rather than fans.
+fn ear_clip(polygon: &[Local]) -> io::Result<Vec<[usize; 3]>> {
+    let n = polygon.len();
+    let uv = |i: usize| polygon[i].uv;
+    let (mut prev, mut next): (Vec<usize>, Vec<usize>) = ((0..n).map(|i| (i + n - 1) % n).collect(), (0..n).map(|i| (i + 1) % n).collect());
+    let mut triangles = Vec::with_capacity(n);
+    let mut left = n;
+
+    // Strictly convex, with no other vertex inside or on it, but for
+    // copies of its corners
+    let ear = |prev: &[usize], next: &[usize], c: usize| {
+        let (p, q) = (prev[c], next[c]);
+        let (a, b, d) = (uv(p), uv(c), uv(q));
+        let scale = (b[0] - a[0]).hypot(b[1] - a[1]) * (d[0] - b[0]).hypot(d[1] - b[1]);
+        if orient(a, b, d) <= 1e-12 * scale {
+            return false;
+        }
+        let mut i = next[q];
+        while i != p {
+            let x = uv(i);
+            if x != a && x != b && x != d && orient(a, b, x) >= 0.0 && orient(b, d, x) >= 0.0 && orient(d, a, x) >= 0.0 {
+                return false;
+            }
+            i = next[i];
+        }
+        true
+    };
+    let diagonal = |prev: &[usize], next: &[usize], c: usize| {
+        let (a, d) = (uv(prev[c]), uv(next[c]));
+        Reverse(((d[0] - a[0]).hypot(d[1] - a[1]).to_bits(), c, prev[c], next[c]))
+    };
+
+    // Ears by diagonal; an entry is stale once its corner's neighbours
+    // change, and anything not yet an ear waits for a rescan
+    let mut ears: BinaryHeap<_> = (0..n).filter(|&c| ear(&prev, &next, c)).map(|c| diagonal(&prev, &next, c)).collect();
+    let mut at = 0;
+    while left > 3 {
+        let Some(Reverse((_, c, p, q))) = ears.pop() else {
+            ears = std::iter::successors(Some(next[at]), |&i| Some(next[i]).filter(|&i| i != next[at]))
+                .filter(|&c| ear(&prev, &next, c))
+                .map(|c| diagonal(&prev, &next, c))
+                .collect();
+            if !ears.is_empty() {
+                continue;
+            }
+            // Nothing left but slivers along a line
+            let ring: Vec<Local> = std::iter::successors(Some(at), |&i| Some(next[i]).filter(|&i| i != at)).map(|i| polygon[i]).collect();
+            let (lo, hi) = ring.iter().fold(([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]), |(lo, hi), l| ([lo[0].min(l.uv[0]), lo[1].min(l.uv[1])], [hi[0].max(l.uv[0]), hi[1].max(l.uv[1])]));
+            if area(&ring).abs() <= 1e-12 * (hi[0] - lo[0]).hypot(hi[1] - lo[1]).powi(2) {
+                return Ok(triangles);
+            }
+            return Err(invalid("STEP face could not be triangulated"));
+        };
+        if prev[c] != p || next[c] != q || !ear(&prev, &next, c) {
+            continue;
+        }
+        triangles.push([p, c, q]);
+        (next[p], prev[q]) = (q, p);
+        left -= 1;
+        at = p;
+        for corner in [p, q] {
+            if ear(&prev, &next, corner) {
+                ears.push(diagonal(&prev, &next, corner));
+            }
+        }
+    }
+    let (p, c, q) = (prev[at], at, next[at]);
+    if orient(uv(p), uv(c), uv(q)) > 0.0 {
+        triangles.push([p, c, q]);
+    }
+    Ok(triangles)
+}
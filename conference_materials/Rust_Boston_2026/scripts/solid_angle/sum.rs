@@ -0,0 +1,33 @@
+//! Compensated summation.
+
+/// Neumaier's improved Kahan summation. Tracks the rounding error of each
+/// addition, so the total stays accurate to about one ulp regardless of
+/// the number or order of terms.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CompensatedSum {
+    sum: f64,
+    err: f64,
+}
+
+impl CompensatedSum {
+    pub const fn new() -> Self {
+        Self { sum: 0.0, err: 0.0 }
+    }
+
+    #[inline]
+    pub fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+        // Recover whichever operand lost low bits
+        self.err += if self.sum.abs() >= x.abs() {
+            (self.sum - t) + x
+        } else {
+            (x - t) + self.sum
+        };
+        self.sum = t;
+    }
+
+    #[inline]
+    pub fn value(&self) -> f64 {
+        self.sum + self.err
+    }
+}
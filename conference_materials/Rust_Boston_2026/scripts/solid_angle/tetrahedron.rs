@@ -0,0 +1,180 @@
+//! FMA solid-angle kernel of the talk (`examples/fma_demo.rs`), shared by
+//! the examples so they all exercise the same implementation. On targets
+//! without FMA in hardware it runs unfused; see [crate::vec3::FUSED].
+
+use crate::math::atan2;
+use crate::vec3::{cross, dot, mul_add, norm, sub};
+use std::mem::MaybeUninit;
+
+/// Angular portion of a sphere subtended by a
+/// tetrahedron with the first vertex as the origin.
+/// https://en.wikipedia.org/wiki/Solid_angle#Tetrahedron
+#[inline(always)] // Into crate::dispatch's AVX2 copy, whatever the caller count
+pub fn solid_angle_tetrahedron_scalar(
+    v0: [f64; 3],
+    v1: [f64; 3],
+    v2: [f64; 3],
+    v3: [f64; 3],
+) -> f64 {
+    // Vertex vectors
+    let (a, b, c) = (sub(v1, v0), sub(v2, v0), sub(v3, v0)); // (m)
+    let (la, lb, lc) = (norm(a), norm(b), norm(c)); // (m) Vertex vector lengths
+    let abc = la * lb * lc; // (m^3) Length product. Branch determined here!
+
+    // Solid angle
+    let triple = dot(a, cross(b, c)); // (m^3) Scalar triple product
+    let denom = mul_add(dot(a, b), lc, mul_add(dot(a, c), lb, mul_add(dot(b, c), la, abc))); // (m^3)
+    let angle = 2.0 * atan2(triple, denom); // (rad) Backend per crate::math
+
+    // Check for degeneracy _last_ to avoid disrupting flow
+    if abc != 0.0 {
+        angle
+    } else {
+        0.0
+    }
+}
+
+/// Vector variant of [solid_angle_tetrahedron_scalar]
+#[inline] // Enable cross-crate inlining
+pub fn solid_angle_tetrahedron(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &mut [f64],
+) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tet = tetrahedra[i];
+        out[i] = solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]);
+    }
+
+    Ok(())
+}
+
+/// Tetrahedra and an output of the same length, checked once here so that
+/// the `_unchecked` kernels taking it have nothing left to fail on
+#[derive(Debug)]
+pub struct PairedSlices<'a> {
+    tetrahedra: &'a [[[f64; 3]; 4]],
+    out: &'a mut [f64],
+}
+
+impl<'a> PairedSlices<'a> {
+    pub fn new(tetrahedra: &'a [[[f64; 3]; 4]], out: &'a mut [f64]) -> Result<Self, &'static str> {
+        // Check bounds
+        if tetrahedra.len() != out.len() {
+            return Err("Dimension mismatch");
+        }
+        Ok(Self { tetrahedra, out })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.out.is_empty()
+    }
+
+    /// Split into the first `mid` pairs and the rest, clamping `mid` to
+    /// the length rather than panicking
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        let mid = mid.min(self.len());
+        let (t0, t1) = self.tetrahedra.split_at(mid);
+        let (o0, o1) = self.out.split_at_mut(mid);
+        (Self { tetrahedra: t0, out: o0 }, Self { tetrahedra: t1, out: o1 })
+    }
+
+    pub fn into_parts(self) -> (&'a [[[f64; 3]; 4]], &'a mut [f64]) {
+        (self.tetrahedra, self.out)
+    }
+}
+
+/// Infallible variant of [solid_angle_tetrahedron], for callers that
+/// can't handle or don't want a `Result` (or any panic path)
+#[inline]
+pub fn solid_angle_tetrahedron_unchecked(pairs: PairedSlices<'_>) {
+    for (tet, y) in pairs.tetrahedra.iter().zip(pairs.out.iter_mut()) {
+        *y = solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]);
+    }
+}
+
+/// Variant of [solid_angle_tetrahedron] for a batch of compile-time size,
+/// e.g. 4, 8 or 16 tetrahedra in a BVH leaf. With the length in the type
+/// there is no bounds check, no `Result` and no loop remainder, so the
+/// compiler can unroll and vectorize the whole batch in place.
+///
+/// Not `_fixed`, which is the fixed-point kernel in [crate::fixed].
+#[inline]
+pub fn solid_angle_tetrahedron_array<const N: usize>(tetrahedra: &[[[f64; 3]; 4]; N], out: &mut [f64; N]) {
+    for i in 0..N {
+        let tet = tetrahedra[i];
+        out[i] = solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]);
+    }
+}
+
+/// Variant of [solid_angle_tetrahedron] writing into uninitialized memory,
+/// which skips the zero-fill pass over write-only outputs.
+/// Returns the now-initialized output.
+#[inline]
+pub fn solid_angle_tetrahedron_uninit<'a>(
+    tetrahedra: &[[[f64; 3]; 4]],
+    out: &'a mut [MaybeUninit<f64>],
+) -> Result<&'a mut [f64], &'static str> {
+    // Check bounds
+    let n = out.len();
+    if tetrahedra.len() != n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tet = tetrahedra[i];
+        out[i].write(solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]));
+    }
+
+    // SAFETY: Every element was written above
+    Ok(unsafe { slice_assume_init_mut(out) })
+}
+
+/// The one cast from uninitialized to initialized outputs, shared by the
+/// `_uninit` drivers. `MaybeUninit<f64>` has the layout of `f64`, so the
+/// cast itself is sound; the caller vouches for the contents.
+///
+/// # Safety
+/// Every element of `out` must have been written.
+#[inline]
+pub(crate) unsafe fn slice_assume_init_mut(out: &mut [MaybeUninit<f64>]) -> &mut [f64] {
+    // SAFETY: Same layout, same length, same lifetime; initialized per the contract
+    unsafe { &mut *(out as *mut [MaybeUninit<f64>] as *mut [f64]) }
+}
+
+/// Lazy variant of [solid_angle_tetrahedron], for composing with iterator
+/// pipelines without collecting into an intermediate slice.
+/// Prefer the slice kernels when peak throughput matters.
+#[inline]
+pub fn solid_angle_iter<I>(tetrahedra: I) -> impl Iterator<Item = f64>
+where
+    I: IntoIterator<Item = [[f64; 3]; 4]>,
+{
+    tetrahedra
+        .into_iter()
+        .map(|tet| solid_angle_tetrahedron_scalar(tet[0], tet[1], tet[2], tet[3]))
+}
+
+/// Lazy solid angles subtended at a fixed `origin` by each triangle.
+#[inline]
+pub fn solid_angle_triangles_iter<I>(origin: [f64; 3], triangles: I) -> impl Iterator<Item = f64>
+where
+    I: IntoIterator<Item = [[f64; 3]; 3]>,
+{
+    triangles
+        .into_iter()
+        .map(move |tri| solid_angle_tetrahedron_scalar(origin, tri[0], tri[1], tri[2]))
+}
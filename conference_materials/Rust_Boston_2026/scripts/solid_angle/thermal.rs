@@ -0,0 +1,146 @@
+//! Radiative exchange between gray, diffuse surfaces.
+//!
+//! View factors come from the projected solid angle of each face, the
+//! cosine-weighted cousin of the solid angle kernel, and the net heat flux
+//! from the radiosity (gray-body network) equations over them. A face
+//! radiates from the side its counterclockwise winding faces, so an
+//! enclosure's faces should be wound to face inward.
+
+use crate::mesh::TriMesh;
+use crate::vec3::{cross, dot, norm, sub};
+
+/// Stefan-Boltzmann constant
+pub const STEFAN_BOLTZMANN: f64 = 5.670_374_419e-8; // (W/m²/K⁴)
+
+/// Unit normal of a triangle, on the side it winds counterclockwise
+#[inline]
+fn unit_normal(tri: [[f64; 3]; 3]) -> [f64; 3] {
+    let c = cross(sub(tri[1], tri[0]), sub(tri[2], tri[0]));
+    let len = norm(c);
+    c.map(|x| x / len)
+}
+
+/// View factor from a differential surface at `p` with unit normal
+/// `normal` to the triangle `tri`: its projected solid angle over π,
+/// by Lambert's edge formula.
+///
+/// Exact when the whole triangle is in front of `p` and faces it; zero if
+/// it faces away. Triangles straddling the plane at `p` are not clipped.
+#[inline]
+pub fn view_factor_point_triangle(p: [f64; 3], normal: [f64; 3], tri: [[f64; 3]; 3]) -> f64 {
+    let tri_normal = unit_normal(tri);
+    if dot(tri_normal, sub(p, tri[0])) <= 0.0 {
+        return 0.0;
+    }
+
+    // Sum over edges of the arc angle times the cosine of its plane's normal
+    let r = tri.map(|v| sub(v, p));
+    let mut sum = 0.0;
+    for k in 0..3 {
+        let c = cross(r[k], r[(k + 1) % 3]);
+        let len = norm(c);
+        if len > 0.0 {
+            sum += libm::atan2(len, dot(r[k], r[(k + 1) % 3])) * dot(normal, c) / len;
+        }
+    }
+    (-0.5 * sum / std::f64::consts::PI).max(0.0)
+}
+
+/// View factor matrix of a mesh, row-major, with `out[i * n + j]` the
+/// fraction of face `i`'s emission that reaches face `j`.
+///
+/// Each row is evaluated at face `i`'s centroid, a one-point quadrature
+/// that converges as the mesh is refined. The row sums are exact: 1 for a
+/// closed, inward-wound convex enclosure. Faces are assumed not to shadow
+/// each other, which holds in a convex enclosure.
+pub fn view_factors(mesh: &TriMesh, out: &mut [f64]) -> Result<(), &'static str> {
+    view_factors_with(mesh.faces().len(), |i| mesh.triangle(i), out)
+}
+
+/// [view_factors] between `n` faces given by `triangle`, for geometry
+/// that isn't one mesh
+pub(crate) fn view_factors_with(n: usize, triangle: impl Fn(usize) -> [[f64; 3]; 3], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    if out.len() != n * n {
+        return Err("Dimension mismatch");
+    }
+
+    // Do calculations
+    for i in 0..n {
+        let tri = triangle(i);
+        let normal = unit_normal(tri);
+        let centroid = [0, 1, 2].map(|k| (tri[0][k] + tri[1][k] + tri[2][k]) / 3.0);
+        for j in 0..n {
+            out[i * n + j] = if i == j { 0.0 } else { view_factor_point_triangle(centroid, normal, triangle(j)) };
+        }
+    }
+
+    Ok(())
+}
+
+/// Net radiative heat flux leaving each surface, for row-major view
+/// factors `f` (`n × n`), emissivities in `(0, 1]`, and temperatures.
+///
+/// Solves the radiosity equations `J_i = ε_i σ T_i⁴ + (1 - ε_i) Σ_j F_ij J_j`
+/// and returns `q_i = J_i - Σ_j F_ij J_j` (W/m²); multiply by area for W.
+/// Rows summing to less than 1 lose the rest to surroundings at 0 K.
+pub fn radiative_flux(f: &[f64], emissivity: &[f64], temperature: &[f64], out: &mut [f64]) -> Result<(), &'static str> {
+    // Check bounds
+    let n = out.len();
+    if emissivity.len() != n || temperature.len() != n || f.len() != n * n {
+        return Err("Dimension mismatch");
+    }
+    if emissivity.iter().any(|&e| !(e > 0.0 && e <= 1.0)) {
+        return Err("Emissivity outside (0, 1]");
+    }
+
+    // Do calculations
+    // (I - diag(1 - ε) F) J = ε σ T⁴
+    let mut a = vec![0.0; n * n];
+    let mut j: Vec<f64> = (0..n).map(|i| emissivity[i] * STEFAN_BOLTZMANN * temperature[i].powi(4)).collect();
+    for i in 0..n {
+        for k in 0..n {
+            a[i * n + k] = if i == k { 1.0 } else { 0.0 } - (1.0 - emissivity[i]) * f[i * n + k];
+        }
+    }
+    solve(&mut a, &mut j)?;
+
+    for i in 0..n {
+        let incident: f64 = (0..n).map(|k| f[i * n + k] * j[k]).sum(); // (W/m²) Irradiation
+        out[i] = j[i] - incident;
+    }
+
+    Ok(())
+}
+
+/// Solve `a x = b` in place by Gaussian elimination with partial pivoting,
+/// leaving `x` in `b`
+fn solve(a: &mut [f64], b: &mut [f64]) -> Result<(), &'static str> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&r, &s| a[r * n + col].abs().total_cmp(&a[s * n + col].abs()))
+            .unwrap_or(col);
+        if a[pivot * n + col] == 0.0 {
+            return Err("Singular system");
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap(pivot * n + k, col * n + k);
+            }
+            b.swap(pivot, col);
+        }
+        for row in col + 1..n {
+            let m = a[row * n + col] / a[col * n + col];
+            for k in col..n {
+                a[row * n + k] -= m * a[col * n + k];
+            }
+            b[row] -= m * b[col];
+        }
+    }
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|k| a[row * n + k] * b[k]).sum();
+        b[row] = (b[row] - rest) / a[row * n + row];
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Connectivity of a [TriMesh]: faces around each vertex, faces across
+//! each edge, and the edges themselves.
+//!
+//! Everything is built by sorting flat lists of (key, face) pairs with
+//! rayon's parallel sort and reading rows off the sorted runs, so there is
+//! no per-vertex or per-face allocation and the work splits evenly however
+//! irregular the mesh. Rows are in compressed sparse row (CSR) form, and
+//! every row is in ascending order.
+
+use crate::mesh::TriMesh;
+use rayon::prelude::*;
+
+/// Rows of `u32` indices in compressed sparse row form: row `i` is
+/// `indices[offsets[i]..offsets[i + 1]]`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    indices: Vec<u32>,
+}
+
+impl Csr {
+    /// Rows from sorted `(row, index)` pairs packed by [pack], with `rows` rows
+    fn from_sorted_pairs(rows: usize, pairs: &[u64]) -> Self {
+        let offsets = (0..=rows).into_par_iter().map(|r| pairs.partition_point(|&p| ((p >> 32) as usize) < r)).collect();
+        let indices = pairs.par_iter().map(|&p| p as u32).collect();
+        Self { offsets, indices }
+    }
+
+    /// Number of rows
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn row(&self, i: usize) -> &[u32] {
+        &self.indices[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[u32]> + '_ {
+        (0..self.len()).map(|i| self.row(i))
+    }
+
+    /// Start of each row, plus the end of the last
+    #[inline]
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// All rows back to back
+    #[inline]
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
+/// Connectivity of a [TriMesh], from [TriMesh::build_adjacency]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Adjacency {
+    /// Faces using each vertex
+    pub vertex_faces: Csr,
+    /// Faces sharing an edge with each face
+    pub face_neighbors: Csr,
+    /// Undirected edges as `[lo, hi]` vertex pairs, sorted
+    pub edges: Vec<[u32; 2]>,
+    /// Faces bordering each edge: one on a boundary, two on a manifold
+    /// interior, more where the mesh is non-manifold
+    pub edge_faces: Csr,
+    /// Index into [Adjacency::edges] of each face's edges `ab`, `bc`, `ca`
+    pub face_edges: Vec<[u32; 3]>,
+}
+
+impl Adjacency {
+    /// Edges bordering only one face
+    pub fn boundary_edges(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.edges.len()).filter(|&e| self.edge_faces.row(e).len() == 1).map(|e| e as u32)
+    }
+
+    /// Edges bordering more than two faces
+    pub fn non_manifold_edges(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.edges.len()).filter(|&e| self.edge_faces.row(e).len() > 2).map(|e| e as u32)
+    }
+
+    /// Whether every edge borders exactly two faces
+    pub fn is_closed_manifold(&self) -> bool {
+        (0..self.edges.len()).all(|e| self.edge_faces.row(e).len() == 2)
+    }
+}
+
+impl TriMesh {
+    /// Vertex-to-face, face-to-face and edge maps, built in parallel
+    pub fn build_adjacency(&self) -> Adjacency {
+        let faces = self.faces();
+
+        // Faces around each vertex
+        let mut pairs: Vec<u64> =
+            faces.par_iter().enumerate().flat_map_iter(|(f, face)| face.map(|v| pack(v, f as u32))).collect();
+        pairs.par_sort_unstable();
+        let vertex_faces = Csr::from_sorted_pairs(self.vertices().len(), &pairs);
+
+        // Half-edges keyed by their undirected edge, so that sorting brings
+        // together the faces on either side
+        let mut half_edges: Vec<(u64, u32)> = faces
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(f, &[a, b, c])| [[a, b], [b, c], [c, a]].map(|[u, v]| (edge_key(u, v), f as u32)))
+            .collect();
+        half_edges.par_sort_unstable();
+
+        // Number the edges at the first half-edge of each run
+        let starts: Vec<usize> = (0..half_edges.len())
+            .into_par_iter()
+            .filter(|&i| i == 0 || half_edges[i].0 != half_edges[i - 1].0)
+            .collect();
+        let keys: Vec<u64> = starts.par_iter().map(|&i| half_edges[i].0).collect();
+        let edge_faces = Csr {
+            offsets: starts.iter().copied().chain([half_edges.len()]).collect(),
+            indices: half_edges.par_iter().map(|&(_, f)| f).collect(),
+        };
+
+        // Each face's edges, looked up in the sorted list
+        let face_edges = faces
+            .par_iter()
+            .map(|&[a, b, c]| [[a, b], [b, c], [c, a]].map(|[u, v]| keys.partition_point(|&k| k < edge_key(u, v)) as u32))
+            .collect();
+
+        // Each pair of distinct faces across an edge, both ways round;
+        // faces sharing two edges appear twice, hence the dedup
+        let mut pairs: Vec<u64> = (0..keys.len())
+            .into_par_iter()
+            .flat_map_iter(|e| {
+                let row = edge_faces.row(e);
+                row.iter().flat_map(move |&f| row.iter().filter(move |&&g| g != f).map(move |&g| pack(f, g)))
+            })
+            .collect();
+        pairs.par_sort_unstable();
+        pairs.dedup();
+        let face_neighbors = Csr::from_sorted_pairs(faces.len(), &pairs);
+
+        let edges = keys.par_iter().map(|&k| [(k >> 32) as u32, k as u32]).collect();
+        Adjacency { vertex_faces, face_neighbors, edges, edge_faces, face_edges }
+    }
+}
+
+/// `(hi, lo)` as one integer, so pairs sort as fast as plain integers
+#[inline]
+fn pack(hi: u32, lo: u32) -> u64 {
+    (u64::from(hi) << 32) | u64::from(lo)
+}
+
+/// Key of the undirected edge between vertices `u` and `v`, in the order
+/// of their sorted `[lo, hi]` pairs
+#[inline]
+fn edge_key(u: u32, v: u32) -> u64 {
+    pack(u.min(v), u.max(v))
+}
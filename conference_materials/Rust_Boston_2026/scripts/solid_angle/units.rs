@@ -0,0 +1,156 @@
+//! Solid angle for readers who don't think in steradians: square degrees,
+//! as sky surveys and antenna beams are quoted, and the fraction of the
+//! whole sphere.
+//!
+//! [Sr] shows a value in all three, to four significant figures (whole
+//! numbers kept whole) unless the format asks for another precision, and
+//! [Sr::in_unit] in one.
+//! Values too small or large for fixed point go to scientific notation,
+//! so a pinhole's 1e-9 sr doesn't print as zero.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Square degrees in one steradian, `(180/π)²`
+pub const DEG2_PER_SR: f64 = (180.0 / PI) * (180.0 / PI);
+
+/// The whole sphere, `4π` sr
+pub const SPHERE_SR: f64 = 4.0 * PI;
+
+/// The whole sphere, about 41 253 deg²
+pub const SPHERE_DEG2: f64 = SPHERE_SR * DEG2_PER_SR;
+
+#[inline]
+pub fn sr_to_deg2(sr: f64) -> f64 {
+    sr * DEG2_PER_SR
+}
+
+#[inline]
+pub fn deg2_to_sr(deg2: f64) -> f64 {
+    deg2 / DEG2_PER_SR
+}
+
+/// Fraction of the whole sphere, 1 for `4π`
+#[inline]
+pub fn sr_to_fraction(sr: f64) -> f64 {
+    sr / SPHERE_SR
+}
+
+#[inline]
+pub fn fraction_to_sr(fraction: f64) -> f64 {
+    fraction * SPHERE_SR
+}
+
+/// A unit of solid angle to show or read values in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SolidAngleUnit {
+    #[default]
+    Steradian,
+    SquareDegree,
+    /// Percent of the whole sphere
+    PercentOfSphere,
+}
+
+impl SolidAngleUnit {
+    /// `sr` steradians as a value in this unit
+    #[inline]
+    pub fn value(self, sr: f64) -> f64 {
+        match self {
+            Self::Steradian => sr,
+            Self::SquareDegree => sr_to_deg2(sr),
+            Self::PercentOfSphere => 100.0 * sr_to_fraction(sr),
+        }
+    }
+
+    /// `x` of this unit in steradians
+    #[inline]
+    pub fn to_sr(self, x: f64) -> f64 {
+        match self {
+            Self::Steradian => x,
+            Self::SquareDegree => deg2_to_sr(x),
+            Self::PercentOfSphere => fraction_to_sr(x / 100.0),
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Steradian => "sr",
+            Self::SquareDegree => "deg²",
+            Self::PercentOfSphere => "% of sphere",
+        }
+    }
+}
+
+impl FromStr for SolidAngleUnit {
+    type Err = &'static str;
+
+    /// `sr`, `deg2` or `deg²`, or `%`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "sr" | "steradian" | "steradians" => Ok(Self::Steradian),
+            "deg2" | "deg²" | "deg^2" | "sqdeg" => Ok(Self::SquareDegree),
+            "%" | "percent" | "sphere" => Ok(Self::PercentOfSphere),
+            _ => Err("Unknown solid angle unit"),
+        }
+    }
+}
+
+/// Solid angle in steradians, shown as `1.571 sr (5157 deg², 12.50% of
+/// sphere)`
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Sr(pub f64);
+
+impl Sr {
+    /// Shown in `unit` alone, as `5157 deg²`
+    #[inline]
+    pub fn in_unit(self, unit: SolidAngleUnit) -> InUnit {
+        InUnit { sr: self.0, unit }
+    }
+}
+
+impl fmt::Display for Sr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = f.precision().unwrap_or(4);
+        let [sr, deg2, percent] = [SolidAngleUnit::Steradian, SolidAngleUnit::SquareDegree, SolidAngleUnit::PercentOfSphere].map(|u| significant(u.value(self.0), digits));
+        write!(f, "{sr} sr ({deg2} deg², {percent}% of sphere)")
+    }
+}
+
+/// Solid angle in one unit, from [Sr::in_unit]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InUnit {
+    sr: f64,
+    unit: SolidAngleUnit,
+}
+
+impl fmt::Display for InUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = significant(self.unit.value(self.sr), f.precision().unwrap_or(4));
+        match self.unit {
+            SolidAngleUnit::PercentOfSphere => write!(f, "{value}{}", self.unit.symbol()),
+            _ => write!(f, "{value} {}", self.unit.symbol()),
+        }
+    }
+}
+
+/// `x` to `digits` significant figures, but never rounding a whole
+/// number: fixed point from 1e-3 up to 1e6, scientific notation outside
+fn significant(x: f64, digits: usize) -> String {
+    let digits = digits.max(1);
+    if x == 0.0 || !x.is_finite() {
+        return format!("{x}");
+    }
+    let magnitude = x.abs().log10().floor() as i32;
+    if !(-3..6).contains(&magnitude) {
+        return format!("{:.*e}", digits - 1, x);
+    }
+    let decimals = (digits as i32 - 1 - magnitude).max(0) as usize;
+    let fixed = format!("{x:.decimals$}");
+
+    // Rounding up can carry into another digit, 9.9996 to 10.000
+    if decimals > 0 && fixed.parse::<f64>().is_ok_and(|y| y.abs().log10().floor() as i32 > magnitude) {
+        return format!("{x:.0$}", decimals - 1);
+    }
+    fixed
+}
@@ -0,0 +1,81 @@
+//! Small fixed-size vector helpers shared by the kernels.
+//!
+//! `sub`, `dot` and `norm` are generic over the dimension, so the same
+//! helpers serve the 3D kernels and the planar ones in [crate::planar].
+//! Each `[f64; N]` instance unrolls completely.
+//!
+//! Products are summed with fused multiply-adds wherever the target has
+//! them in hardware. Where it doesn't (32-bit Arm and RISC-V, WebAssembly,
+//! most microcontrollers), each `mul_add` would be a call to a software
+//! `fma` several times slower than the product and sum it replaces, so
+//! there the helpers and [crate::tetrahedron]'s kernel fall back to
+//! separate roundings, and to `libm` for the square root as for `atan2`.
+//! x86_64 counts as having FMA even in a portable build, as every CPU
+//! since 2013 does and [crate::dispatch] reaches it at runtime. The
+//! `soft-float` feature forces the fallback anywhere, to check it on a
+//! host. The order of operations is the same either way, and results
+//! differ by at most [UNFUSED_TOLERANCE] `κ ε |Ω|`, with `κ` the
+//! condition estimate of [crate::condition]; see `soft_float_example.rs`.
+
+/// Whether multiply-adds are fused, as above
+#[allow(unexpected_cfgs)] // Each script declares only the features it uses
+pub const FUSED: bool = !cfg!(feature = "soft-float")
+    && cfg!(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "arm64ec",
+        target_arch = "riscv64",
+        target_arch = "powerpc64",
+        target_arch = "s390x",
+        target_arch = "loongarch64",
+    ));
+
+/// Bound on the difference between unfused and fused solid angles, in
+/// units of `κ ε |Ω|`; about 5 at most in `soft_float_example.rs`
+pub const UNFUSED_TOLERANCE: f64 = 16.0;
+
+/// `a b + c`, rounded once where [FUSED] and twice otherwise
+#[inline(always)]
+pub fn mul_add(a: f64, b: f64, c: f64) -> f64 {
+    if FUSED { a.mul_add(b, c) } else { a * b + c }
+}
+
+/// Correctly rounded square root, from `libm` where not [FUSED]
+#[inline(always)]
+pub fn sqrt(x: f64) -> f64 {
+    if FUSED { x.sqrt() } else { libm::sqrt(x) }
+}
+
+#[inline]
+pub fn sub<const N: usize>(a: [f64; N], b: [f64; N]) -> [f64; N] {
+    std::array::from_fn(|i| a[i] - b[i])
+}
+
+#[inline]
+pub fn dot<const N: usize>(u: [f64; N], v: [f64; N]) -> f64 {
+    // Innermost pair first, so 3D rounds exactly as u0 v0 + (u1 v1 + u2 v2)
+    let Some(last) = N.checked_sub(1) else {
+        return 0.0;
+    };
+    (0..last).rev().fold(u[last] * v[last], |acc, i| mul_add(u[i], v[i], acc))
+}
+
+#[inline]
+pub fn cross(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        mul_add(u[1], v[2], -u[2] * v[1]),
+        mul_add(u[2], v[0], -u[0] * v[2]),
+        mul_add(u[0], v[1], -u[1] * v[0]),
+    ]
+}
+
+/// 2D cross product, the z component of `u × v`
+#[inline]
+pub fn perp_dot(u: [f64; 2], v: [f64; 2]) -> f64 {
+    mul_add(u[0], v[1], -u[1] * v[0])
+}
+
+#[inline]
+pub fn norm<const N: usize>(u: [f64; N]) -> f64 {
+    sqrt(dot(u, u))
+}
@@ -0,0 +1,195 @@
+//! Debug views of meshes and queries, for finding orientation and
+//! winding mistakes by eye.
+//!
+//! A [Recording] collects meshes, query points, rays and per-element
+//! scalars under entity paths such as `"room/walls"`, after the Rerun
+//! viewer's logging API, and [Recording::write_glb] saves them as one
+//! binary glTF with vertex colors, which Rerun, Blender and browser
+//! viewers all open. Logging to a path again replaces what was there.
+//! Scalars are colored as [crate::plot]'s heatmaps, over the range given
+//! or their own.
+//!
+//! Meshes are written single-sided and flat-shaded, so a face wound the
+//! wrong way vanishes from the side it should show on.
+//! [Recording::log_normals] draws each face's normal as a short line
+//! from its centroid, which makes the same point from any side.
+//! Positions are written as given, in `f32`: glTF's axes are `+y` up, so
+//! `z`-up data lies on its side, which is fine for debugging.
+#![cfg(feature = "viewer")]
+
+use crate::mesh::TriMesh;
+use crate::plot::colors;
+use crate::vec3::{cross, norm, sub};
+use serde_json::json;
+use std::io::{self, Write};
+
+/// `"glTF"`, little-endian
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// glTF primitive modes
+const POINTS: u32 = 0;
+const LINES: u32 = 1;
+const TRIANGLES: u32 = 4;
+
+/// What is logged at one path: a primitive mode and its vertices, each
+/// with a color
+#[derive(Clone, Debug, PartialEq)]
+struct Entity {
+    path: String,
+    mode: u32,
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[u8; 4]>,
+}
+
+/// See the module docs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    entities: Vec<Entity>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entity paths in the order first logged
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entities.iter().map(|e| e.path.as_str())
+    }
+
+    /// Log at `path`, replacing what was there
+    fn log(&mut self, path: &str, mode: u32, positions: Vec<[f32; 3]>, colors: Vec<[u8; 4]>) {
+        let entity = Entity { path: path.to_string(), mode, positions, colors };
+        match self.entities.iter_mut().find(|e| e.path == path) {
+            Some(e) => *e = entity,
+            None => self.entities.push(entity),
+        }
+    }
+
+    /// `mesh` in one color
+    pub fn log_mesh(&mut self, path: &str, mesh: &TriMesh, color: [u8; 4]) {
+        let positions = corners(mesh);
+        let colors = vec![color; positions.len()];
+        self.log(path, TRIANGLES, positions, colors);
+    }
+
+    /// `mesh` with each face colored by its value in `values`; NaN faces
+    /// are transparent
+    pub fn log_face_scalars(&mut self, path: &str, mesh: &TriMesh, values: &[f64], range: Option<[f64; 2]>) -> Result<(), &'static str> {
+        // Check bounds
+        if values.len() != mesh.faces().len() {
+            return Err("Dimension mismatch");
+        }
+
+        let colors = colors(values, range).into_iter().flat_map(|c| [c; 3]).collect();
+        self.log(path, TRIANGLES, corners(mesh), colors);
+        Ok(())
+    }
+
+    /// Query points in one color
+    pub fn log_points(&mut self, path: &str, points: &[[f64; 3]], color: [u8; 4]) {
+        self.log(path, POINTS, points.iter().map(|&p| single(p)).collect(), vec![color; points.len()]);
+    }
+
+    /// Query points colored by their values, such as the solid angle
+    /// subtended at each
+    pub fn log_point_scalars(&mut self, path: &str, points: &[[f64; 3]], values: &[f64], range: Option<[f64; 2]>) -> Result<(), &'static str> {
+        // Check bounds
+        if values.len() != points.len() {
+            return Err("Dimension mismatch");
+        }
+
+        self.log(path, POINTS, points.iter().map(|&p| single(p)).collect(), colors(values, range));
+        Ok(())
+    }
+
+    /// Rays or other segments, each `[from, to]`
+    pub fn log_rays(&mut self, path: &str, rays: &[[[f64; 3]; 2]], color: [u8; 4]) {
+        self.log(path, LINES, rays.iter().flat_map(|r| r.map(single)).collect(), vec![color; 2 * rays.len()]);
+    }
+
+    /// The normal of each face of `mesh` by its winding, `length` long
+    /// from the centroid
+    pub fn log_normals(&mut self, path: &str, mesh: &TriMesh, length: f64, color: [u8; 4]) {
+        let rays: Vec<[[f64; 3]; 2]> = mesh
+            .triangles()
+            .map(|[a, b, c]| {
+                let n = cross(sub(b, a), sub(c, a));
+                let scale = if norm(n) > 0.0 { length / norm(n) } else { 0.0 };
+                let centroid: [f64; 3] = std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0);
+                [centroid, std::array::from_fn(|k| centroid[k] + scale * n[k])]
+            })
+            .collect();
+        self.log_rays(path, &rays, color);
+    }
+
+    /// Write everything logged as a `.glb`: one node per entity, named
+    /// by its path, in one scene. Entities with nothing in them are left
+    /// out.
+    pub fn write_glb<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let (mut bin, mut views, mut accessors, mut meshes, mut nodes) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for e in self.entities.iter().filter(|e| !e.positions.is_empty()) {
+            let [lo, hi] = e.positions.iter().fold([[f32::INFINITY; 3], [f32::NEG_INFINITY; 3]], |[lo, hi], p| {
+                [std::array::from_fn(|k| lo[k].min(p[k])), std::array::from_fn(|k| hi[k].max(p[k]))]
+            });
+            let position = accessors.len();
+            views.push(json!({ "buffer": 0, "byteOffset": bin.len(), "byteLength": 12 * e.positions.len(), "target": 34962 }));
+            bin.extend(e.positions.iter().flatten().flat_map(|x| x.to_le_bytes()));
+            accessors.push(json!({ "bufferView": views.len() - 1, "componentType": 5126, "count": e.positions.len(), "type": "VEC3", "min": lo, "max": hi }));
+            views.push(json!({ "buffer": 0, "byteOffset": bin.len(), "byteLength": 4 * e.colors.len(), "target": 34962 }));
+            bin.extend(e.colors.iter().flatten());
+            accessors.push(json!({ "bufferView": views.len() - 1, "componentType": 5121, "normalized": true, "count": e.colors.len(), "type": "VEC4" }));
+            let primitive = json!({ "attributes": { "POSITION": position, "COLOR_0": position + 1 }, "mode": e.mode, "material": 0 });
+            meshes.push(json!({ "name": e.path, "primitives": [primitive] }));
+            nodes.push(json!({ "name": e.path, "mesh": meshes.len() - 1 }));
+        }
+
+        let material = json!({ "name": "vertex colors", "pbrMetallicRoughness": { "metallicFactor": 0.0, "roughnessFactor": 1.0 }, "alphaMode": "BLEND" });
+        let mut doc = json!({
+            "asset": { "version": "2.0", "generator": "solid_angle viewer" },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }],
+            "nodes": nodes,
+            "meshes": meshes,
+            "materials": [material],
+            "accessors": accessors,
+            "bufferViews": views,
+        });
+        if !bin.is_empty() {
+            doc["buffers"] = json!([{ "byteLength": bin.len() }]);
+        }
+        if let Some(top) = doc.as_object_mut() {
+            top.retain(|_, v| v.as_array().is_none_or(|a| !a.is_empty())); // glTF allows no empty arrays
+        }
+
+        // Chunks are padded to four bytes, JSON with spaces
+        let mut json = serde_json::to_vec(&doc).map_err(io::Error::other)?;
+        json.resize(json.len().next_multiple_of(4), b' ');
+        bin.resize(bin.len().next_multiple_of(4), 0);
+        let total = 12 + 8 + json.len() + if bin.is_empty() { 0 } else { 8 + bin.len() };
+        let total = u32::try_from(total).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "glTF binary too large"))?;
+        w.write_all(&GLB_MAGIC.to_le_bytes())?;
+        w.write_all(&2_u32.to_le_bytes())?;
+        w.write_all(&total.to_le_bytes())?;
+        for (kind, data) in [(CHUNK_JSON, &json), (CHUNK_BIN, &bin)] {
+            if !data.is_empty() {
+                w.write_all(&(data.len() as u32).to_le_bytes())?;
+                w.write_all(&kind.to_le_bytes())?;
+                w.write_all(data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn single(p: [f64; 3]) -> [f32; 3] {
+    p.map(|x| x as f32)
+}
+
+/// Positions of each face's corners in turn, unshared so each face can
+/// take its own color
+fn corners(mesh: &TriMesh) -> Vec<[f32; 3]> {
+    mesh.triangles().flat_map(|t| t.map(single)).collect()
+}
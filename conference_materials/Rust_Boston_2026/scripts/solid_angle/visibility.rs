@@ -0,0 +1,122 @@
+//! Line-of-sight visibility between two point sets past an occluding
+//! mesh: which ground stations see which relays, which mesh points see
+//! each other for view factors, which targets a sensor covers.
+//!
+//! [visibility_matrix] tests every source-target segment against the
+//! mesh through its [Bvh] and packs the answers into a [BitMatrix], one
+//! row per source. Work is split across threads by 64-target words of
+//! the matrix, so it parallelizes whether there are many sources or
+//! only a few with many targets. Points on the mesh itself don't block
+//! their own segments; see [crate::bvh::T_EPS].
+
+use crate::bvh::Bvh;
+use crate::mesh::TriMesh;
+use crate::par::{chunk_len, par_threshold};
+use rayon::prelude::*;
+
+/// Dense matrix of bits, rows packed into `u64` words, lowest bit first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words: Vec<u64>,
+}
+
+impl BitMatrix {
+    /// All-false `rows` by `cols`
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self { rows, cols, words: vec![0; rows * cols.div_ceil(64)] }
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Words per row; the bits past `cols` in the last are zero
+    #[inline]
+    pub fn row_words(&self) -> usize {
+        self.cols.div_ceil(64)
+    }
+
+    /// Panics out of bounds, as indexing does
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        assert!(row < self.rows && col < self.cols, "BitMatrix index out of bounds");
+        self.words[row * self.row_words() + col / 64] >> (col % 64) & 1 == 1
+    }
+
+    /// Panics out of bounds, as indexing does
+    #[inline]
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        assert!(row < self.rows && col < self.cols, "BitMatrix index out of bounds");
+        let w = row * self.row_words() + col / 64;
+        self.words[w] = self.words[w] & !(1 << (col % 64)) | u64::from(value) << (col % 64);
+    }
+
+    /// Packed words of `row`
+    #[inline]
+    pub fn row(&self, row: usize) -> &[u64] {
+        &self.words[row * self.row_words()..(row + 1) * self.row_words()]
+    }
+
+    /// True bits in `row`
+    #[inline]
+    pub fn count_row(&self, row: usize) -> usize {
+        self.row(row).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// True bits overall
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// Whether each of `sources` sees each of `targets` past `mesh`, as bit
+/// `(source, target)` of the result. `bvh` must have been built from
+/// `mesh`.
+pub fn visibility_matrix(sources: &[[f64; 3]], targets: &[[f64; 3]], mesh: &TriMesh, bvh: &Bvh) -> Result<BitMatrix, &'static str> {
+    // Check the BVH is for this mesh, as far as can be told
+    if bvh.faces() != mesh.faces().len() {
+        return Err("BVH built for another mesh");
+    }
+    Ok(visibility_matrix_with(sources, targets, |from, to| bvh.occluded(mesh, from, to)))
+}
+
+/// [visibility_matrix] past whatever `occluded` tests, for occluders that
+/// aren't one mesh
+pub(crate) fn visibility_matrix_with(
+    sources: &[[f64; 3]],
+    targets: &[[f64; 3]],
+    occluded: impl Fn([f64; 3], [f64; 3]) -> bool + Sync,
+) -> BitMatrix {
+    let mut matrix = BitMatrix::new(sources.len(), targets.len());
+    let row_words = matrix.row_words();
+    if row_words == 0 {
+        return matrix;
+    }
+
+    // One word: up to 64 targets from one source
+    let fill = |w: usize, word: &mut u64| {
+        let (source, first) = (sources[w / row_words], (w % row_words) * 64);
+        for (bit, &target) in targets[first..].iter().take(64).enumerate() {
+            *word |= u64::from(!occluded(source, target)) << bit;
+        }
+    };
+
+    // Small matrices are faster without the thread pool
+    if sources.len() * targets.len() < par_threshold() {
+        matrix.words.iter_mut().enumerate().for_each(|(w, word)| fill(w, word));
+    } else {
+        let chunk = chunk_len(matrix.words.len());
+        matrix.words.par_chunks_mut(chunk).enumerate().for_each(|(c, words)| {
+            words.iter_mut().enumerate().for_each(|(i, word)| fill(c * chunk + i, word));
+        });
+    }
+    matrix
+}
@@ -0,0 +1,207 @@
+//! Fast winding numbers of a triangle mesh, for inside/outside tests on
+//! many points (Barill et al., "Fast Winding Numbers for Soups and
+//! Clouds", 2018).
+//!
+//! The exact winding number is the total solid angle over `4π`, one
+//! kernel call per face per point. Here faces are grouped in a tree like
+//! [crate::bvh]'s (median splits along the longest axis, stored flat in
+//! depth-first order), and each node keeps the dipole expansion of its
+//! faces about their area-weighted centroid, to second order. A point
+//! farther from a node than [FastWinding::beta] times the node's radius
+//! takes the expansion; nearer, the query descends, down to leaves
+//! summed exactly. Cost is about `log n` per point in place of `n`.
+//!
+//! At the default [DEFAULT_BETA] of 2 the error is a few `1e-3` on
+//! average and a few `1e-2` at worst, near the surface; it falls about
+//! as `1 / beta²`. Thresholded at `1/2`, inside and outside only differ
+//! from the exact sum's where that is within the error of `1/2`, which
+//! for a closed mesh is on the faces themselves. Like the exact sum, the
+//! result is `1` inside a closed mesh wound outward, `0` outside, and
+//! some fraction for an open one.
+//!
+//! The tree holds face indices, not a copy of the mesh, so queries take
+//! the mesh it was built from again, as [crate::bvh::Bvh]'s do.
+
+use crate::mesh::TriMesh;
+use crate::tetrahedron::solid_angle_tetrahedron_scalar;
+use crate::vec3::{cross, dot, norm, sub};
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Most faces per leaf
+pub const LEAF: usize = 8;
+
+/// Far-field ratio of distance to node radius, per Barill et al.
+pub const DEFAULT_BETA: f64 = 2.0;
+
+/// Points per rayon task in [FastWinding::winding_numbers]
+const MIN_POINTS_PER_TASK: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Node {
+    /// Area-weighted centroid of the faces below
+    center: [f64; 3],
+    /// Farthest corner of those faces from `center`
+    radius: f64,
+    /// Sum of area times unit normal, `Σ aᵢ nᵢ`
+    dipole: [f64; 3],
+    /// Second-order term, `Σ aᵢ (cᵢ - center) ⊗ nᵢ` for face centroids `cᵢ`
+    moment: [[f64; 3]; 3],
+    /// Leaf: first face in `order`. Interior: index of the right child.
+    start: u32,
+    /// Faces in a leaf; zero for interior nodes
+    count: u32,
+}
+
+/// See the module docs
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastWinding {
+    nodes: Vec<Node>,
+    /// Face indices, each leaf's contiguous
+    order: Vec<u32>,
+    beta: f64,
+}
+
+impl FastWinding {
+    /// Tree over every face of `mesh`, at [DEFAULT_BETA]
+    pub fn new(mesh: &TriMesh) -> Self {
+        let mut order: Vec<u32> = (0..mesh.faces().len() as u32).collect();
+        let centroids: Vec<[f64; 3]> = mesh.triangles().map(|[a, b, c]| std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0)).collect();
+        let mut nodes = Vec::with_capacity(2 * order.len().div_ceil(LEAF));
+        if !order.is_empty() {
+            build(mesh, &centroids, &mut order, 0, &mut nodes);
+        }
+        Self { nodes, order, beta: DEFAULT_BETA }
+    }
+
+    /// The same tree with far-field ratio `beta`: larger is more accurate
+    /// and slower, and infinite is the exact sum
+    pub fn with_beta(mut self, beta: f64) -> Result<Self, &'static str> {
+        if beta.is_nan() || beta <= 0.0 {
+            return Err("Far-field ratio must be positive");
+        }
+        self.beta = beta;
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn beta(&self) -> f64 {
+        self.beta
+    }
+
+    /// Faces of the mesh this was built from
+    #[inline]
+    pub fn faces(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Winding number of `mesh` about `q`
+    pub fn winding_number(&self, mesh: &TriMesh, q: [f64; 3]) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        let mut w = 0.0;
+        let mut stack = [0_u32; 64]; // One more than the depth, at most
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let node = &self.nodes[stack[top] as usize];
+            let r = sub(node.center, q);
+            let d = norm(r);
+            if d > self.beta * node.radius {
+                w += far_field(node, r, d);
+                continue;
+            }
+            if node.count > 0 {
+                let faces = &self.order[node.start as usize..(node.start + node.count) as usize];
+                for &f in faces {
+                    let [a, b, c] = mesh.triangle(f as usize);
+                    w += solid_angle_tetrahedron_scalar(q, a, b, c) / (4.0 * PI);
+                }
+                continue;
+            }
+            let left = stack[top] + 1;
+            stack[top] = node.start;
+            stack[top + 1] = left;
+            top += 2;
+        }
+        w
+    }
+
+    /// [FastWinding::winding_number] at each of `points`, in parallel
+    pub fn winding_numbers(&self, mesh: &TriMesh, points: &[[f64; 3]], out: &mut [f64]) -> Result<(), &'static str> {
+        if points.len() != out.len() {
+            return Err("Dimension mismatch");
+        }
+        if mesh.faces().len() != self.faces() {
+            return Err("Mesh is not the one the tree was built from");
+        }
+        (points, out).into_par_iter().with_min_len(MIN_POINTS_PER_TASK).for_each(|(&q, w)| *w = self.winding_number(mesh, q));
+        Ok(())
+    }
+}
+
+/// A node's faces seen from `r = center - q`, `d = |r|`, by the dipole
+/// expansion to second order
+#[inline]
+fn far_field(node: &Node, r: [f64; 3], d: f64) -> f64 {
+    let (d3, d5) = (d * d * d, d * d * d * d * d);
+    let first = dot(r, node.dipole) / d3;
+    let m = &node.moment;
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let rmr = dot(r, [dot(m[0], r), dot(m[1], r), dot(m[2], r)]);
+    (first + trace / d3 - 3.0 * rmr / d5) / (4.0 * PI)
+}
+
+/// Append the subtree over `order` (which starts at `offset` in the full
+/// order) to `nodes`, returning its index
+fn build(mesh: &TriMesh, centroids: &[[f64; 3]], order: &mut [u32], offset: usize, nodes: &mut Vec<Node>) -> u32 {
+    let index = nodes.len() as u32;
+    nodes.push(expansion(mesh, centroids, order, offset));
+    if order.len() <= LEAF {
+        return index;
+    }
+
+    let (lo, hi) = order.iter().fold(([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]), |(lo, hi), &f| {
+        let c = centroids[f as usize];
+        (std::array::from_fn(|k| lo[k].min(c[k])), std::array::from_fn(|k| hi[k].max(c[k])))
+    });
+    let axis = (0..3).fold(0, |a, k| if hi[k] - lo[k] > hi[a] - lo[a] { k } else { a });
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by(mid, |&a, &b| centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis]));
+    let (left, right) = order.split_at_mut(mid);
+    build(mesh, centroids, left, offset, nodes);
+    let right = build(mesh, centroids, right, offset + mid, nodes);
+    nodes[index as usize].start = right;
+    nodes[index as usize].count = 0;
+    index
+}
+
+/// Expansion of the faces in `order`, as a leaf over them
+fn expansion(mesh: &TriMesh, centroids: &[[f64; 3]], order: &[u32], offset: usize) -> Node {
+    let (mut area, mut center, mut dipole) = (0.0, [0.0; 3], [0.0; 3]);
+    for &f in order {
+        let [a, b, c] = mesh.triangle(f as usize);
+        let n = cross(sub(b, a), sub(c, a)).map(|x| 0.5 * x);
+        let s = norm(n);
+        area += s;
+        (0..3).for_each(|k| (center[k], dipole[k]) = (center[k] + s * centroids[f as usize][k], dipole[k] + n[k]));
+    }
+    // Faces of no area have no weight; place their node at their centroids
+    if area > 0.0 {
+        center = center.map(|x| x / area);
+    } else {
+        let n = order.len() as f64;
+        center = std::array::from_fn(|k| order.iter().map(|&f| centroids[f as usize][k]).sum::<f64>() / n);
+    }
+
+    let (mut radius, mut moment) = (0.0_f64, [[0.0; 3]; 3]);
+    for &f in order {
+        let [a, b, c] = mesh.triangle(f as usize);
+        let n = cross(sub(b, a), sub(c, a)).map(|x| 0.5 * x);
+        let offset = sub(centroids[f as usize], center);
+        (0..3).for_each(|j| (0..3).for_each(|k| moment[j][k] += offset[j] * n[k]));
+        radius = [a, b, c].iter().fold(radius, |r, &p| r.max(norm(sub(p, center))));
+    }
+    Node { center, radius, dipole, moment, start: offset as u32, count: order.len() as u32 }
+}
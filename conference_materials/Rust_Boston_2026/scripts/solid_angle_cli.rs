@@ -0,0 +1,177 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! zip = { version = "9", default-features = false, features = ["deflate"] }
+//! serde = { version = "1", features = ["derive"] }
+//! toml = "0.9"
+//! indicatif = { version = "0.18", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//! tikv-jemallocator = { version = "0.6", optional = true }
+//! mimalloc = { version = "0.1", optional = true }
+//!
+//! [features]
+//! default = ["progress"]
+//! jemalloc = ["dep:tikv-jemallocator"]
+//! mimalloc = ["dep:mimalloc"]
+//! progress = ["dep:indicatif"]
+//! rvv = []
+//! trace = ["dep:tracing"]
+//! serde = []
+//! ```
+//!
+//! Batch solid angles and point classification over `.npy`/`.npz` files,
+//! with a progress bar and a per-stage timing summary on stderr, and
+//! summary statistics of the results on stdout, the mean solid angle also
+//! in square degrees and percent of the sphere:
+//!
+//! ```text
+//! rust-script solid_angle_cli.rs tetrahedra tets.npy solid_angles.npy
+//! rust-script solid_angle_cli.rs winding mesh.npz points.npy winding_numbers.npy
+//! rust-script solid_angle_cli.rs --config run.toml --kernel dd tetrahedra tets.bin out.bin
+//! rust-script solid_angle_cli.rs capabilities
+//! rust-script solid_angle_cli.rs autotune [n]
+//! ```
+//!
+//! Settings come from `--config` and flags, as in `solid_angle/config.rs`.
+//! `capabilities` prints the build's features and the CPU's ISA support,
+//! for bug reports. `autotune` sweeps the parallel settings on `n` random
+//! tetrahedra (default 2²⁰) and saves the best for this host, as in
+//! `solid_angle/autotune.rs`; every later run loads them, with `--threads`
+//! still taking precedence.
+//!
+//! The `jemalloc` and `mimalloc` features swap the global allocator, which
+//! can matter for loading big meshes; `capabilities` reports which is in.
+//! As rust-script has no feature flags, build the generated package:
+//!
+//! ```text
+//! cd $(rust-script -p solid_angle_cli.rs | tail -1) && cargo run --release --features jemalloc -- capabilities
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/alloc.rs"]
+mod alloc;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/autotune.rs"]
+mod autotune;
+#[path = "solid_angle/capabilities.rs"]
+mod capabilities;
+#[path = "solid_angle/config.rs"]
+mod config;
+#[path = "solid_angle/dd.rs"]
+mod dd;
+#[path = "solid_angle/dispatch.rs"]
+mod dispatch;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_io.rs"]
+mod mesh_io;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/neon.rs"]
+mod neon;
+#[path = "solid_angle/npy.rs"]
+mod npy;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/progress.rs"]
+mod progress;
+#[path = "solid_angle/rvv.rs"]
+mod rvv;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/units.rs"]
+mod units;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use progress::Progress;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+
+const USAGE: &str = "Usage: solid_angle_cli [--config <file.toml>] [--<setting> <value>]... tetrahedra <tets> <out>\n       solid_angle_cli [--config <file.toml>] [--<setting> <value>]... winding <mesh.npz> <points> <out>\n       solid_angle_cli [--threads <n>] capabilities\n       solid_angle_cli autotune [n]";
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut config = config::Config::from_args(&mut args)?;
+    if args.first().is_some_and(|a| a == "autotune") {
+        let n = args.get(1).map_or(Ok(1 << 20), |s| s.parse()).map_err(io::Error::other)?;
+        let tuning = autotune::tune(n).map_err(io::Error::other)?;
+        println!("{tuning:?}");
+        println!("Saved to {}", tuning.save()?.display());
+        return Ok(());
+    }
+    if let Some(tuning) = autotune::load()? {
+        tuning.apply().map_err(io::Error::other)?;
+        config.threads = config.threads.or(Some(tuning.threads));
+    }
+    config.init_threads()?;
+    if args == ["capabilities"] {
+        print!("{}", capabilities::capabilities());
+        return Ok(());
+    }
+    let mut progress = Progress::new();
+
+    let out = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["tetrahedra", tets_path, out_path] => {
+            progress.stage("read", 0);
+            let flat: Vec<[f64; 12]> = config.read_input(&mut BufReader::new(File::open(tets_path)?))?;
+            let tets: Vec<[[f64; 3]; 4]> =
+                flat.iter().map(|t| std::array::from_fn(|v| std::array::from_fn(|k| t[3 * v + k]))).collect();
+
+            progress.stage("solid angle", tets.len() as u64);
+            let mut out = vec![0.0; tets.len()];
+            progress.run_chunked(tets.len(), config.chunk_size(), |r| {
+                config.solid_angles(&tets[r.clone()], &mut out[r.clone()], r.start)
+            })?;
+
+            progress.stage("write", 0);
+            config.write_output(&mut BufWriter::new(File::create(out_path)?), &out)?;
+            out
+        }
+        ["winding", mesh_path, points_path, out_path] => {
+            progress.stage("read", 0);
+            let mesh = mesh_io::read_tri_mesh_npz(BufReader::new(File::open(mesh_path)?))?;
+            let points: Vec<[f64; 3]> = config.read_input(&mut BufReader::new(File::open(points_path)?))?;
+
+            // Chunks of origins, so each update is about the same number of kernel calls
+            progress.stage("classify", points.len() as u64);
+            let mut out = vec![0.0; points.len()];
+            let chunk = (config.chunk_size() / mesh.faces().len().max(1)).max(64);
+            progress.run_chunked(points.len(), chunk, |r| {
+                multi_origin::solid_angles_multi_origin(&mesh, &points[r.clone()], &mut out[r])
+            })
+            .map_err(io::Error::other)?;
+            out.iter_mut().for_each(|w| *w /= 4.0 * PI);
+
+            progress.stage("write", 0);
+            config.write_output(&mut BufWriter::new(File::create(out_path)?), &out)?;
+            out
+        }
+        _ => return Err(io::Error::other(USAGE)),
+    };
+
+    // Per-stage timing summary
+    for (stage, time) in progress.finish() {
+        eprintln!("{stage:>12}: {:>10.3} ms", time.as_secs_f64() * 1e3);
+    }
+    println!("Wrote {} values", out.len());
+    let summary = stats::summarize(&out);
+    print!("{summary}");
+    if args[0] == "tetrahedra" {
+        println!("mean: {}", units::Sr(summary.mean));
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Irradiance from a disk and a uniform sky on planes of every tilt,
+//! Monte Carlo from each source's samples against its closed form,
+//! including disks the plane cuts.
+//!
+//! ```text
+//! rust-script sources_example.rs [samples per plane]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sources.rs"]
+mod sources;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use sources::{irradiance_estimate, Disk, Sky, Source};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Uniforms on a jittered `k` by `k` grid
+fn stratified(rng: &mut inputs::Pcg64, k: usize) -> Vec<[f64; 2]> {
+    (0..k * k).map(|i| [((i / k) as f64 + rng.unit()) / k as f64, ((i % k) as f64 + rng.unit()) / k as f64]).collect()
+}
+
+/// Unit normal tilted `tilt` from the zenith toward azimuth `az`
+fn tilted(tilt: f64, az: f64) -> [f64; 3] {
+    [tilt.sin() * az.sin(), tilt.sin() * az.cos(), tilt.cos()]
+}
+
+fn main() {
+    let n: usize = std::env::args().nth(1).map_or(1 << 16, |s| s.parse().unwrap());
+    let k = (n as f64).sqrt() as usize;
+    let mut rng = inputs::Pcg64::new(170, 0);
+
+    // A wide disk 30° up in the east, against planes tilted through it
+    // and past, so the plane cuts it for a stretch in the middle
+    let (alpha, radiance) = (0.3, 2.0);
+    let disk = Disk::new(tilted(FRAC_PI_2 - 0.5, FRAC_PI_2), alpha, radiance);
+    let mut worst = 0.0_f64;
+    for i in 0..=24 {
+        let normal = tilted(PI * i as f64 / 24.0, -FRAC_PI_2);
+        let (exact, estimate) = (disk.irradiance(normal), irradiance_estimate(&disk, normal, stratified(&mut rng, k)));
+        worst = worst.max((estimate - exact).abs() / (radiance * disk.solid_angle()));
+    }
+    println!("disk of radius {alpha} rad: Monte Carlo off by at most {worst:.1e} of L Ω over 25 tilts");
+    assert!(worst < 1e-4, "disk irradiance off");
+
+    // Closed forms at the ends of the cut and at its middle, where half
+    // the disk is in front: α - sin α cos α
+    let beta = |b: f64| disk.projected_solid_angle(b);
+    let full = PI * alpha.sin().powi(2);
+    assert!((beta(FRAC_PI_2 - alpha + 1e-9) - full * (FRAC_PI_2 - alpha).cos()).abs() < 1e-9);
+    assert!(beta(FRAC_PI_2 + alpha - 1e-9).abs() < 1e-12);
+    assert!((beta(FRAC_PI_2) - (alpha - alpha.sin() * alpha.cos())).abs() < 1e-15);
+
+    // The Sun: its direct normal irradiance face on, then the cosine law
+    let sun = Disk::sun(tilted(0.4, 2.0), 900.0);
+    let face_on = sun.irradiance(sun.axis());
+    let at_60 = sun.irradiance(tilted(0.4 + PI / 3.0, 2.0));
+    println!("sun: {face_on:.9} W/m² face on, {at_60:.9} W/m² at 60°");
+    assert!((face_on - 900.0).abs() < 1e-9 && (at_60 - 450.0).abs() < 1e-9);
+
+    // Sky: cosine sampling is exact for level ground, sample by sample
+    let sky = Sky::from_horizontal_irradiance(120.0);
+    let level = irradiance_estimate(&sky, [0.0, 0.0, 1.0], (0..64).map(|_| [rng.unit(), rng.unit()]));
+    assert!((level - 120.0).abs() < 1e-12, "level sky irradiance {level}");
+    // Tilted planes converge slowly, n·ω / cos θ being unbounded near
+    // the horizon: the variance diverges logarithmically
+    let mut worst = 0.0_f64;
+    for i in 0..=12 {
+        let normal = tilted(PI * i as f64 / 12.0, 1.0);
+        let (exact, estimate) = (sky.irradiance(normal), irradiance_estimate(&sky, normal, stratified(&mut rng, k)));
+        worst = worst.max((estimate - exact).abs() / 120.0);
+    }
+    println!("sky: {level} W/m² level, Monte Carlo off by at most {worst:.1e} of that over 13 tilts");
+    assert!(worst < 2e-2, "sky irradiance off");
+
+    // Global irradiance on a south-facing panel tilted 35°
+    let panel = tilted(35_f64.to_radians(), PI);
+    let sun = Disk::sun(tilted(40_f64.to_radians(), 2.8), 850.0);
+    println!("panel: {:.1} W/m² direct + {:.1} W/m² diffuse", sun.irradiance(panel), sky.irradiance(panel));
+}
@@ -0,0 +1,184 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! flate2 = { version = "1", default-features = false, features = ["zlib-rs"], optional = true }
+//! crc32fast = { version = "1", optional = true }
+//!
+//! [features]
+//! default = ["plot"]
+//! geotiff = []
+//! plot = ["dep:flate2", "dep:crc32fast"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Functions on the sphere as maps: the directions in which a panel
+//! blocks the view, the sky above a valley's horizon, and a beam
+//! pattern, each integrated from its pixels against the solid angle
+//! worked out elsewhere in the crate.
+//!
+//! ```text
+//! rust-script sphere_map_example.rs out_dir
+//! ```
+//!
+//! writes the three maps as PNGs to look at.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "plot"))]
+compile_error!("sphere_map_example.rs needs the plot feature");
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/plot.rs"]
+mod plot;
+#[path = "solid_angle/raster.rs"]
+mod raster;
+#[path = "solid_angle/spherical.rs"]
+mod spherical;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use bvh::Bvh;
+use mesh::TriMesh;
+use plot::Projection;
+use std::f64::consts::{PI, TAU};
+use vec3::dot;
+
+/// Sum of `values` over the map, each pixel weighted by the solid angle
+/// it covers in `projection`
+fn integrate(values: &[f64], width: usize, height: usize, projection: Projection) -> f64 {
+    let pixel = match projection {
+        Projection::Mollweide => {
+            let inside = values.iter().filter(|x| !x.is_nan()).count();
+            4.0 * PI / inside as f64
+        }
+        Projection::Equirectangular => TAU / width as f64 * PI / height as f64,
+    };
+    values
+        .chunks(width)
+        .enumerate()
+        .map(|(row, r)| {
+            let lat = (0.5 - (row as f64 + 0.5) / height as f64) * PI;
+            let weight = if projection == Projection::Equirectangular { lat.cos() } else { 1.0 };
+            r.iter().filter(|x| !x.is_nan()).sum::<f64>() * weight * pixel
+        })
+        .sum()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = std::env::args().nth(1);
+    let mut maps = Vec::new();
+
+    // Both projections invert, and the parallel rows land in order
+    for projection in [Projection::Equirectangular, Projection::Mollweide] {
+        for (lat, lon) in [(0.0, 0.0), (45.0, 90.0), (-30.0, -150.0), (80.0, 10.0), (-60.0, 170.0)] {
+            let d = spherical::lat_lon_to_unit(lat, lon);
+            let back = projection.direction(projection.position(d)).unwrap();
+            assert!(vec3::norm(vec3::sub(back, d)) < 1e-9, "{projection:?} ({lat}, {lon}): {back:?}");
+        }
+        let (w, h) = (37, 23);
+        let values = plot::sample_sphere(|d| d[0] + 2.0 * d[1] + 3.0 * d[2], w, h, projection)?;
+        for (i, &x) in values.iter().enumerate() {
+            let uv = [((i % w) as f64 + 0.5) / w as f64, ((i / w) as f64 + 0.5) / h as f64];
+            let expect = projection.direction(uv).map_or(f64::NAN, |d| d[0] + 2.0 * d[1] + 3.0 * d[2]);
+            assert!(x == expect || x.is_nan() && expect.is_nan());
+        }
+    }
+    assert_eq!(Projection::Equirectangular.position([0.0, 0.0, 1.0]), [0.5, 0.0]);
+    assert!(Projection::Mollweide.direction([0.02, 0.02]).is_none());
+
+    // A 2 m panel 1.5 m away: the directions a ray leaves the origin and
+    // hits it cover the solid angle the panel subtends
+    let panel = TriMesh::new(vec![[1.5, -1.0, -0.5], [1.5, 1.0, -0.5], [1.5, 1.0, 1.5], [1.5, -1.0, 1.5]], vec![[0, 1, 2], [0, 2, 3]])?;
+    let bvh = Bvh::new(&panel);
+    let omega: f64 = panel.triangles().map(|[a, b, c]| tetrahedron::solid_angle_tetrahedron_scalar([0.0; 3], a, b, c).abs()).sum();
+    let blocked = |d: [f64; 3]| if bvh.occluded(&panel, [0.0; 3], d.map(|x| 100.0 * x)) { 1.0 } else { 0.0 };
+    for (projection, w, h) in [(Projection::Mollweide, 720, 360), (Projection::Equirectangular, 720, 360)] {
+        let values = plot::sample_sphere(blocked, w, h, projection)?;
+        let seen = integrate(&values, w, h, projection);
+        println!("panel: {seen:.4} sr from {projection:?} pixels, {omega:.4} sr exact");
+        assert!((seen - omega).abs() < 5e-3 * omega, "{projection:?}: {seen} against {omega}");
+    }
+    maps.push(("panel", plot::raster_sphere(blocked, 720, 360, Projection::Mollweide)?));
+
+    // Sky over the floor of a V-shaped valley running north and south,
+    // against the sky solid angle of its horizon profile
+    let mut dem = raster::Raster::new(40, 40, [0.0, 40.0], 1.0);
+    for (i, z) in dem.data.iter_mut().enumerate() {
+        *z = 0.5 * ((i % 40) as f64 - 19.5).abs();
+    }
+    let terrain = raster::dem_mesh(&dem)?;
+    let terrain_bvh = Bvh::new(&terrain);
+    let [east, north] = dem.center(20, 19);
+    let observer = [east + 0.5, north, dem.get(20, 19) + 0.1];
+    let bins = 360;
+    let profile = horizon::horizon_profile(observer, &terrain, &terrain_bvh, bins)?;
+    let sky = |d: [f64; 3]| {
+        let az = libm::atan2(d[0], d[1]).rem_euclid(TAU);
+        let bin = ((az / TAU * bins as f64) as usize).min(bins - 1);
+        if d[2].asin() > profile[bin] { 1.0 } else { 0.0 }
+    };
+    let (w, h) = (1440, 720);
+    let values = plot::sample_sphere(sky, w, h, Projection::Equirectangular)?;
+    let (seen, exact) = (integrate(&values, w, h, Projection::Equirectangular), horizon::sky_solid_angle(&profile));
+    println!("sky: {seen:.4} sr from equirectangular pixels, {exact:.4} sr from the profile");
+    assert!((seen - exact).abs() < 5e-3 * exact && exact < TAU);
+    maps.push(("sky", plot::raster_sphere(sky, 720, 360, Projection::Equirectangular)?));
+
+    // A cos⁸ beam: brightest where its axis falls, and carrying
+    // 2π/9 sr in all
+    let axis = spherical::lat_lon_to_unit(20.0, -60.0);
+    let beam = |d: [f64; 3]| dot(d, axis).max(0.0).powi(8);
+    let values = plot::sample_sphere(beam, 720, 360, Projection::Mollweide)?;
+    let total = integrate(&values, 720, 360, Projection::Mollweide);
+    println!("beam: {total:.5} sr from pixels, {:.5} sr exact", TAU / 9.0);
+    assert!((total - TAU / 9.0).abs() < 1e-3 * TAU / 9.0);
+    let image = plot::raster_sphere(beam, 720, 360, Projection::Mollweide)?;
+    let brightest = (0..values.len()).filter(|&i| !values[i].is_nan()).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap();
+    let [u, v] = Projection::Mollweide.position(axis);
+    let (row, col) = ((v * 360.0) as usize, (u * 720.0) as usize);
+    assert!((brightest / 720).abs_diff(row) <= 1 && (brightest % 720).abs_diff(col) <= 1);
+    assert_eq!(image.get(brightest / 720, brightest % 720), plot::colormap(1.0));
+    assert_eq!(image.get(0, 0), [0; 4]);
+    maps.push(("beam", image));
+
+    // Degenerate inputs
+    assert!(plot::sample_sphere(beam, 0, 10, Projection::Mollweide).is_err());
+    assert!(plot::raster_sphere(beam, 10, 0, Projection::Equirectangular).is_err());
+
+    if let Some(dir) = out_dir {
+        let dir = std::path::Path::new(&dir);
+        for (name, map) in &maps {
+            let mut png = Vec::new();
+            map.write_png(&mut png)?;
+            std::fs::write(dir.join(format!("{name}.png")), png)?;
+        }
+        println!("wrote maps to {}", dir.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Great-circle distances, spherical polygon areas, and containment, with
+//! a Monte Carlo check that the two agree.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/spherical.rs"]
+mod spherical;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use spherical::lat_lon_to_unit;
+use std::f64::consts::PI;
+
+const EARTH_RADIUS: f64 = 6_371_008.8; // (m) IUGG mean radius
+
+fn main() -> Result<(), &'static str> {
+    // London to New York
+    let (london, new_york) = ([51.5074, -0.1278], [40.7128, -74.0060]);
+    let d = spherical::great_circle_distance(london, new_york, EARTH_RADIUS);
+    println!("London - New York: {:.1} km", d / 1e3);
+    assert!((d - 5_570e3).abs() < 5e3);
+
+    // Octant, and its complement when wound the other way
+    let octant = [[0.0, 0.0], [0.0, 90.0], [90.0, 0.0]];
+    let area = spherical::spherical_polygon_area_lat_lon(&octant);
+    let reversed = spherical::spherical_polygon_area_lat_lon(&[octant[0], octant[2], octant[1]]);
+    println!("octant: {:.15} × 4π, reversed: {:.15} × 4π", area / (4.0 * PI), reversed / (4.0 * PI));
+    assert!((area - PI / 2.0).abs() < 1e-14 && (reversed - 3.5 * PI).abs() < 1e-14);
+
+    // Northern hemisphere, bounded by the equator
+    let equator = [[0.0, 0.0], [0.0, 90.0], [0.0, 180.0], [0.0, 270.0]];
+    assert!((spherical::spherical_polygon_area_lat_lon(&equator) - 2.0 * PI).abs() < 1e-14);
+
+    // Containment
+    let octant: Vec<[f64; 3]> = octant.iter().map(|p| lat_lon_to_unit(p[0], p[1])).collect();
+    assert!(spherical::point_in_spherical_polygon(lat_lon_to_unit(30.0, 45.0), &octant));
+    assert!(!spherical::point_in_spherical_polygon(lat_lon_to_unit(-30.0, 45.0), &octant));
+    assert!(!spherical::point_in_spherical_polygon(lat_lon_to_unit(30.0, -135.0), &octant));
+
+    // The fraction of uniform points inside a polygon approaches its area over 4π.
+    // This one winds clockwise seen from outside, so it encloses the larger side.
+    let region: Vec<[f64; 3]> = [[10.0, -20.0], [35.0, 60.0], [-5.0, 120.0], [-40.0, 30.0], [-15.0, 10.0]]
+        .iter()
+        .map(|p| lat_lon_to_unit(p[0], p[1]))
+        .collect();
+    let area = spherical::spherical_polygon_area(&region);
+    let n = 200_000;
+    let points: Vec<[f64; 3]> = (0..n)
+        .map(|i| {
+            // Fibonacci lattice, near-uniform on the sphere
+            let z = 1.0 - (2 * i + 1) as f64 / n as f64;
+            let (s, c) = (PI * (3.0 - 5_f64.sqrt()) * i as f64).sin_cos();
+            let r = (1.0 - z * z).sqrt();
+            [r * c, r * s, z]
+        })
+        .collect();
+    let mut inside = vec![false; n];
+    spherical::points_in_spherical_polygon_par(&region, &points, &mut inside)?;
+    let fraction = inside.iter().filter(|&&b| b).count() as f64 / n as f64;
+    println!("region: area {:.6} × 4π, sampled {fraction:.6}", area / (4.0 * PI));
+    assert!((fraction - area / (4.0 * PI)).abs() < 1e-3);
+    Ok(())
+}
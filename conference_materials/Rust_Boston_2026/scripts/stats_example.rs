@@ -0,0 +1,63 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Summary statistics over kernel output, against a naive two-pass
+//! computation, and on data where the one-pass sum-of-squares formula
+//! loses everything to cancellation.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+fn main() -> Result<(), &'static str> {
+    let n = 1 << 20;
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 147, n);
+    let mut omega = vec![0.0; n];
+    par::solid_angle_tetrahedra_par(&tets, &mut omega)?;
+    omega[7] = f64::NAN; // As from degeneracy = "nan"
+
+    let s = stats::summarize(&omega);
+    print!("{s}");
+
+    // Two-pass reference over the non-NaN values
+    let valid: Vec<f64> = omega.iter().copied().filter(|x| !x.is_nan()).collect();
+    let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+    let var = valid.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / valid.len() as f64;
+    assert_eq!((s.count, s.nan), (n - 1, 1));
+    assert!((s.mean - mean).abs() <= 1e-12 * mean.abs().max(1.0));
+    assert!((s.std - var.sqrt()).abs() <= 1e-12 * var.sqrt());
+    assert_eq!(s.histogram.iter().sum::<u64>(), s.count as u64);
+    assert_eq!(s.min, valid.iter().copied().fold(f64::INFINITY, f64::min));
+
+    // Large offset, tiny spread: E[x²] - E[x]² cancels completely, Welford/Chan doesn't
+    let offset: Vec<f64> = (0..n).map(|i| 1e9 + (i % 2) as f64).collect();
+    let naive = offset.iter().map(|x| x * x).sum::<f64>() / n as f64 - (offset.iter().sum::<f64>() / n as f64).powi(2);
+    let s = stats::summarize_bins(&offset, 2);
+    println!("offset data: std {} (exact 0.5), sum-of-squares variance {naive}", s.std);
+    assert_eq!(s.std, 0.5);
+    assert_eq!(s.histogram, vec![n as u64 / 2; 2]);
+
+    // Degenerate inputs
+    assert_eq!(stats::summarize(&[]).count, 0);
+    assert_eq!(stats::summarize(&[3.0; 5]).histogram[0], 5);
+    Ok(())
+}
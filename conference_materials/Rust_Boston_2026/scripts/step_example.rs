@@ -0,0 +1,368 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! default = ["step"]
+//! step = []
+//! defmt = ["dep:defmt"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Solid angles of STEP parts:
+//!
+//! ```text
+//! rust-script step_example.rs part.step [chord (m)]
+//! ```
+//!
+//! With no arguments, writes and reads back a box in millimetres, a plate
+//! with a bored hole in inches, and cylinders and a frustum with their
+//! seams written both ways: each closed, manifold and wound outward, with
+//! volumes exact for the planar parts and converging with the chord for
+//! the curved, and unsupported geometry and units refused.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "step"))]
+compile_error!("step_example.rs needs the step feature");
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/step.rs"]
+mod step;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use mesh::TriMesh;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use vec3::{cross, dot, norm, sub};
+
+/// Enclosed volume, by the divergence theorem
+fn volume(mesh: &TriMesh) -> f64 {
+    mesh.triangles().map(|[a, b, c]| dot(a, cross(b, c)) / 6.0).sum()
+}
+
+/// Closed, manifold and wound outward, with Euler characteristic `euler`
+/// and `4π` at `inside`
+fn check_closed(name: &str, mesh: &TriMesh, euler: i64, inside: [f64; 3]) -> Result<(), &'static str> {
+    let adjacency = mesh.build_adjacency();
+    assert!(adjacency.is_closed_manifold(), "{name}: not a closed manifold");
+    let chi = mesh.vertices().len() as i64 - adjacency.edges.len() as i64 + mesh.faces().len() as i64;
+    assert_eq!(chi, euler, "{name}: wrong topology");
+    let mut omega = [0.0; 2];
+    multi_origin::solid_angles_multi_origin(mesh, &[inside, [1e3, -2e3, 5e2]], &mut omega)?;
+    assert!((omega[0] - 4.0 * PI).abs() < 1e-10 && omega[1].abs() < 1e-10, "{name}: {omega:?}");
+    assert!(volume(mesh) > 0.0, "{name}: wound inward");
+    Ok(())
+}
+
+/// Writes a STEP file's data section an entity at a time
+struct Step {
+    entities: Vec<String>,
+}
+
+impl Step {
+    /// A file whose first entities are `units`, the length unit first
+    fn new(units: &[&str]) -> Self {
+        Self { entities: units.iter().map(|u| u.to_string()).collect() }
+    }
+
+    /// Add an entity, returning its reference
+    fn add(&mut self, entity: String) -> String {
+        self.entities.push(entity);
+        format!("#{}", self.entities.len())
+    }
+
+    fn point(&mut self, [x, y, z]: [f64; 3]) -> String {
+        self.add(format!("CARTESIAN_POINT('',({x:?},{y:?},{z:?}))"))
+    }
+
+    fn direction(&mut self, [x, y, z]: [f64; 3]) -> String {
+        self.add(format!("DIRECTION('',({x:?},{y:?},{z:?}))"))
+    }
+
+    fn axis(&mut self, origin: [f64; 3], z: [f64; 3], x: [f64; 3]) -> String {
+        let (o, z, x) = (self.point(origin), self.direction(z), self.direction(x));
+        self.add(format!("AXIS2_PLACEMENT_3D('',{o},{z},{x})"))
+    }
+
+    fn vertex(&mut self, p: [f64; 3]) -> String {
+        let p = self.point(p);
+        self.add(format!("VERTEX_POINT('',{p})"))
+    }
+
+    /// Straight edge between two vertices at `a` and `b`
+    fn line(&mut self, (va, a): (&str, [f64; 3]), (vb, b): (&str, [f64; 3])) -> String {
+        let (p, d) = (self.point(a), self.direction(sub(b, a)));
+        let v = self.add(format!("VECTOR('',{d},{:?})", norm(sub(b, a))));
+        let line = self.add(format!("LINE('',{p},{v})"));
+        self.add(format!("EDGE_CURVE('',{va},{vb},{line},.T.)"))
+    }
+
+    /// Whole circle about `z` through vertex `v`, which lies along `x`
+    fn circle(&mut self, v: &str, center: [f64; 3], z: [f64; 3], x: [f64; 3], r: f64) -> String {
+        let axis = self.axis(center, z, x);
+        let circle = self.add(format!("CIRCLE('',{axis},{r:?})"));
+        self.add(format!("EDGE_CURVE('',{v},{v},{circle},.T.)"))
+    }
+
+    /// Bound of oriented edges, each forward or back
+    fn bound(&mut self, edges: &[(&str, bool)], outer: bool) -> String {
+        let oriented: Vec<String> = edges.iter().map(|&(e, forward)| self.add(format!("ORIENTED_EDGE('',*,*,{e},{})", flag(forward)))).collect();
+        let edge_loop = self.add(format!("EDGE_LOOP('',({}))", oriented.join(",")));
+        let kind = if outer { "FACE_OUTER_BOUND" } else { "FACE_BOUND" };
+        self.add(format!("{kind}('',{edge_loop},.T.)"))
+    }
+
+    fn face(&mut self, bounds: &[String], surface: &str, same_sense: bool) -> String {
+        self.add(format!("ADVANCED_FACE('',({}),{surface},{})", bounds.join(","), flag(same_sense)))
+    }
+
+    fn solid(&mut self, name: &str, faces: &[String]) -> String {
+        let shell = self.add(format!("CLOSED_SHELL('',({}))", faces.join(",")));
+        self.add(format!("MANIFOLD_SOLID_BREP('{name}',{shell})"))
+    }
+
+    /// The whole exchange file
+    fn finish(&self) -> String {
+        let mut out = String::from("ISO-10303-21;\nHEADER;\n/* written by step_example.rs */\n");
+        out += "FILE_DESCRIPTION(('solid angle test parts'),'2;1');\n";
+        out += "FILE_NAME('parts.step','2026-10-15T00:00:00',('J. Logan'),(''),'nobody''s CAD','','');\n";
+        out += "FILE_SCHEMA(('AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }'));\nENDSEC;\nDATA;\n";
+        for (i, e) in self.entities.iter().enumerate() {
+            out += &format!("#{}={e};\n", i + 1);
+        }
+        out + "ENDSEC;\nEND-ISO-10303-21;\n"
+    }
+}
+
+fn flag(b: bool) -> &'static str {
+    if b {
+        ".T."
+    } else {
+        ".F."
+    }
+}
+
+fn unit(x: [f64; 3]) -> [f64; 3] {
+    x.map(|c| c / norm(x))
+}
+
+/// Planar faces of a polyhedron, each polygon counterclockwise from
+/// outside, with whole-circle `holes` as `(polygon, edge)` inner bounds
+fn polyhedron(step: &mut Step, vertices: &[[f64; 3]], polygons: &[Vec<usize>], holes: &[(usize, &str)]) -> Vec<String> {
+    let ids: Vec<String> = vertices.iter().map(|&v| step.vertex(v)).collect();
+    let mut edges: HashMap<(usize, usize), String> = HashMap::new();
+    let mut faces = Vec::new();
+    for (f, polygon) in polygons.iter().enumerate() {
+        let mut oriented = Vec::new();
+        for k in 0..polygon.len() {
+            let (a, b) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+            let (lo, hi) = (a.min(b), a.max(b));
+            let e = edges.entry((lo, hi)).or_insert_with(|| step.line((&ids[lo], vertices[lo]), (&ids[hi], vertices[hi]))).clone();
+            oriented.push((e, a < b));
+        }
+        let oriented: Vec<(&str, bool)> = oriented.iter().map(|(e, forward)| (e.as_str(), *forward)).collect();
+        let mut bounds = vec![step.bound(&oriented, true)];
+        bounds.extend(holes.iter().filter(|&&(h, _)| h == f).map(|&(_, e)| step.bound(&[(e, true)], false)).collect::<Vec<_>>());
+        let [a, b, c] = [0, 1, 2].map(|k| vertices[polygon[k]]);
+        let axis = step.axis(a, unit(cross(sub(b, a), sub(c, a))), unit(sub(b, a)));
+        let plane = step.add(format!("PLANE('',{axis})"));
+        faces.push(step.face(&bounds, &plane, true));
+    }
+    faces
+}
+
+/// Box faces, counterclockwise from outside, over corners numbered by
+/// their bits: x in bit 0, y in bit 1, z in bit 2
+const BOX: [[usize; 4]; 6] = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+
+fn corners(lo: [f64; 3], hi: [f64; 3]) -> Vec<[f64; 3]> {
+    (0..8).map(|c| std::array::from_fn(|k| if c & (1 << k) == 0 { lo[k] } else { hi[k] })).collect()
+}
+
+/// The lateral face of a cylinder (`r1 == r0`) or frustum about z, from
+/// radius `r0` at `z0` to `r1` at `z1`, between whole circles `bottom` and
+/// `top`, each through its vertex along +x. `outward` for a solid, not
+/// for a bore. With `seam`, the face is one loop cut along a line at +x,
+/// as most exporters write it; without, it is bounded by the two circles.
+#[allow(clippy::too_many_arguments)]
+fn lateral(step: &mut Step, [r0, r1]: [f64; 2], [z0, z1]: [f64; 2], [bottom, top]: [&str; 2], [v0, v1]: [&str; 2], outward: bool, seam: bool, degrees: f64) -> String {
+    let axis = step.axis([0.0, 0.0, z0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+    let surface = if r0 == r1 {
+        step.add(format!("CYLINDRICAL_SURFACE('',{axis},{r0:?})"))
+    } else {
+        let half_angle = ((r1 - r0) / (z1 - z0)).atan() / degrees;
+        step.add(format!("CONICAL_SURFACE('',{axis},{r0:?},{half_angle:?})"))
+    };
+    let bounds = if seam {
+        let line = step.line((v0, [r0, 0.0, z0]), (v1, [r1, 0.0, z1]));
+        vec![step.bound(&[(bottom, true), (&line, true), (top, false), (&line, false)], true)]
+    } else {
+        vec![step.bound(&[(bottom, true)], true), step.bound(&[(top, false)], false)]
+    };
+    step.face(&bounds, &surface, outward)
+}
+
+/// Closed cylinder or frustum about z, from radius `r0` at z = 0 to `r1`
+/// at `h`
+fn frustum(step: &mut Step, name: &str, [r0, r1]: [f64; 2], h: f64, seam: bool, degrees: f64) -> String {
+    let (v0, v1) = (step.vertex([r0, 0.0, 0.0]), step.vertex([r1, 0.0, h]));
+    let bottom = step.circle(&v0, [0.0; 3], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], r0);
+    let top = step.circle(&v1, [0.0, 0.0, h], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], r1);
+    let side = lateral(step, [r0, r1], [0.0, h], [&bottom, &top], [&v0, &v1], true, seam, degrees);
+    let mut caps = Vec::new();
+    for (z, edge, normal) in [(0.0, &bottom, -1.0), (h, &top, 1.0)] {
+        let axis = step.axis([0.0, 0.0, z], [0.0, 0.0, normal], [1.0, 0.0, 0.0]);
+        let plane = step.add(format!("PLANE('',{axis})"));
+        let bound = step.bound(&[(edge, true)], true);
+        caps.push(step.face(&[bound], &plane, true));
+    }
+    step.solid(name, &[caps[0].clone(), side, caps[1].clone()])
+}
+
+const METRE: &str = "(LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT($,.METRE.))";
+const MILLIMETRE: &str = "(LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.))";
+const RADIAN: &str = "(NAMED_UNIT(*) PLANE_ANGLE_UNIT() SI_UNIT($,.RADIAN.))";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [path, rest @ ..] = &args[..] {
+        let chord = rest.first().map_or(Ok(1e-4), |s| s.parse())?;
+        for (name, mesh) in step::read_step(&mut BufReader::new(File::open(path)?), chord)? {
+            let closed = mesh.build_adjacency().is_closed_manifold();
+            println!("{name}: {} faces, volume {:.6e} m³, {}", mesh.faces().len(), volume(&mesh), if closed { "closed" } else { "open" });
+        }
+        return Ok(());
+    }
+
+    // A 10 mm box: exact, whatever the chord
+    let mut part = Step::new(&[MILLIMETRE, RADIAN]);
+    let polygons: Vec<Vec<usize>> = BOX.iter().map(|f| f.to_vec()).collect();
+    let faces = polyhedron(&mut part, &corners([0.0; 3], [10.0; 3]), &polygons, &[]);
+    part.solid("box", &faces);
+    let bodies = step::read_step(&mut part.finish().as_bytes(), 1.0)?;
+    let [(name, cube)] = &bodies[..] else { panic!("{} bodies", bodies.len()) };
+    assert_eq!((name.as_str(), cube.faces().len()), ("box", 12));
+    check_closed("box", cube, 2, [0.005; 3])?;
+    assert!((volume(cube) - 1e-6).abs() < 1e-20, "box: volume {}", volume(cube));
+
+    // A 2 x 2 x 0.25 inch plate bored through with a 0.5 inch hole: the
+    // bore faces its axis, and the plate has one handle
+    let inch = "(CONVERSION_BASED_UNIT('INCH',#4) LENGTH_UNIT() NAMED_UNIT(*))";
+    let measure = "LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4),#3)";
+    let mut plate = Step::new(&[inch, RADIAN, MILLIMETRE, measure]);
+    let (r, t) = (0.25, 0.25);
+    let (v0, v1) = (plate.vertex([r, 0.0, 0.0]), plate.vertex([r, 0.0, t]));
+    let bottom = plate.circle(&v0, [0.0; 3], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], r);
+    let top = plate.circle(&v1, [0.0, 0.0, t], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], r);
+    let mut faces = polyhedron(&mut plate, &corners([-1.0, -1.0, 0.0], [1.0, 1.0, t]), &polygons, &[(0, &bottom), (1, &top)]);
+    faces.push(lateral(&mut plate, [r, r], [0.0, t], [&bottom, &top], [&v0, &v1], false, true, 1.0));
+    plate.solid("plate", &faces);
+    let inside = [0.8, 0.8, 0.1].map(|x| 0.0254 * x);
+    let expected = 0.0254_f64.powi(3) * (4.0 - PI * r * r) * t;
+    println!("{:>22} {:>8} {:>8} {:>10}", "", "chord", "faces", "rel err");
+    for chord in [1e-4, 1e-5, 1e-6] {
+        let [(_, mesh)] = &step::read_step(&mut plate.finish().as_bytes(), chord)?[..] else { panic!() };
+        check_closed("plate", mesh, 0, inside)?;
+        let err = (volume(mesh) - expected) / expected;
+        assert!(err > 0.0 && err < 10.0 * chord / 0.0254, "plate: volume {} against {expected}", volume(mesh));
+        assert!(mesh.vertices().iter().all(|v| v[0].hypot(v[1]) >= r * 0.0254 * (1.0 - 1e-12)), "plate: vertex in the bore");
+        println!("{:>22} {chord:>8.0e} {:>8} {err:>10.1e}", "plate, bored", mesh.faces().len());
+    }
+
+    // A cylinder and a frustum in metres, its half-angle in degrees, with
+    // seams and without
+    let degree = "(CONVERSION_BASED_UNIT('DEGREE',#4) NAMED_UNIT(*) PLANE_ANGLE_UNIT())";
+    let measure = format!("PLANE_ANGLE_MEASURE_WITH_UNIT(PLANE_ANGLE_MEASURE({:?}),#3)", PI / 180.0);
+    let mut solids = Step::new(&[METRE, degree, RADIAN, &measure]);
+    let (r0, r1, h) = (0.3, 0.5, 1.2);
+    for seam in [true, false] {
+        frustum(&mut solids, &format!("cylinder, seam {seam}"), [r0, r0], h, seam, PI / 180.0);
+        frustum(&mut solids, &format!("frustum, seam {seam}"), [r0, r1], h, seam, PI / 180.0);
+    }
+    for chord in [1e-2, 1e-3, 1e-4] {
+        for (name, mesh) in step::read_step(&mut solids.finish().as_bytes(), chord)? {
+            check_closed(&name, &mesh, 2, [0.0, 0.1, 0.5])?;
+            let top = if name.starts_with("cylinder") { r0 } else { r1 };
+            let expected = PI * h * (r0 * r0 + r0 * top + top * top) / 3.0;
+            let err = (expected - volume(&mesh)) / expected;
+            assert!(err > 0.0 && err < 2.0 * chord / r0, "{name}: volume {} against {expected}", volume(&mesh));
+            let off = mesh.vertices().iter().map(|v| (v[0].hypot(v[1]) - (r0 + (top - r0) * v[2] / h)).abs().min(v[0].hypot(v[1]))).fold(0.0, f64::max);
+            assert!(off < 1e-12, "{name}: vertex {off} off the surface");
+            println!("{name:>22} {chord:>8.0e} {:>8} {err:>10.1e}", mesh.faces().len());
+        }
+    }
+
+    // Geometry and files this doesn't read
+    let mut spline = Step::new(&[METRE, RADIAN]);
+    let v = spline.vertex([1.0, 0.0, 0.0]);
+    let circle = spline.circle(&v, [0.0; 3], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], 1.0);
+    let surface = spline.add("B_SPLINE_SURFACE_WITH_KNOTS('',1,1,((#1,#1),(#1,#1)),.UNSPECIFIED.,.F.,.F.,.F.,(2),(2),(0.,1.),(0.,1.),.UNSPECIFIED.)".to_string());
+    let bound = spline.bound(&[(&circle, true)], true);
+    let face = spline.face(&[bound], &surface, true);
+    spline.solid("freeform", &[face]);
+    let err = step::read_step(&mut spline.finish().as_bytes(), 1e-3).err().map(|e| e.to_string());
+    assert_eq!(err.as_deref(), Some("Unsupported STEP entity B_SPLINE_SURFACE_WITH_KNOTS"));
+    let unitless = part.finish().replacen("LENGTH_UNIT()", "", 1);
+    assert_eq!(step::read_step(&mut unitless.as_bytes(), 1.0).err().map(|e| e.to_string()).as_deref(), Some("STEP file has no length unit"));
+    assert!(step::read_step(&mut part.finish().as_bytes(), 0.0).is_err());
+    assert!(step::read_step(&mut &part.finish().as_bytes()[..400], 1.0).is_err());
+    assert!(step::read_step(&mut "solid cube\nendsolid".as_bytes(), 1.0).is_err());
+
+    // Forged files: a unit defined in terms of itself, parameters nested
+    // past any stack, and a polyline too short to slice, are errors
+    // rather than aborts
+    let message = |text: &str| step::read_step(&mut text.as_bytes(), 1.0).err().map(|e| e.to_string());
+    let cyclic = "ISO-10303-21;\nDATA;\n#1=(CONVERSION_BASED_UNIT('INCH',#2) LENGTH_UNIT() NAMED_UNIT(*));\n#2=LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4),#1);\nENDSEC;\nEND-ISO-10303-21;\n";
+    assert_eq!(message(cyclic).as_deref(), Some("Cyclic STEP unit"));
+    let nested = format!("ISO-10303-21;\nDATA;\n#1=FOO({}\n", "(".repeat(1_000_000));
+    assert_eq!(message(&nested).as_deref(), Some("STEP parameters nested too deeply"));
+
+    // An edge on a polyline of no points
+    let mut forged = Step::new(&[METRE, RADIAN]);
+    let corners = [[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+    let v = corners.map(|p| forged.vertex(p));
+    let empty = forged.add("POLYLINE('',())".to_string());
+    let edges = [
+        forged.line((&v[0], corners[0]), (&v[1], corners[1])),
+        forged.line((&v[1], corners[1]), (&v[2], corners[2])),
+        forged.add(format!("EDGE_CURVE('',{},{},{empty},.T.)", v[2], v[0])),
+    ];
+    let bound = forged.bound(&[(&edges[0], true), (&edges[1], true), (&edges[2], true)], true);
+    let axis = forged.axis([0.0; 3], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+    let plane = forged.add(format!("PLANE('',{axis})"));
+    let face = forged.face(&[bound], &plane, true);
+    forged.solid("forged", &[face]);
+    assert_eq!(message(&forged.finish()).as_deref(), Some("STEP polyline with fewer than two points"));
+    Ok(())
+}
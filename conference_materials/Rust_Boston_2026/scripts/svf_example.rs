@@ -0,0 +1,136 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! default = ["geotiff"]
+//! geotiff = []
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Sky view factor rasters from elevation models:
+//!
+//! ```text
+//! rust-script svf_example.rs dem.tif svf.tif [bins] [observer height]
+//! ```
+//!
+//! With no arguments, checks a V-shaped valley against its masks worked
+//! out by hand, a plain against 1, and round-trips through GeoTIFF.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "geotiff"))]
+compile_error!("svf_example.rs needs the geotiff feature");
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/raster.rs"]
+mod raster;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use raster::Raster;
+use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::time::Instant;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [dem, out, rest @ ..] = &args[..] {
+        let bins = rest.first().map_or(Ok(36), |s| s.parse())?;
+        let height = rest.get(1).map_or(Ok(0.1), |s| s.parse())?;
+        let dem = raster::read_geotiff(&mut BufReader::new(File::open(dem)?))?;
+        let start = Instant::now();
+        let svf = raster::sky_view_factor_raster(&dem, height, bins)?;
+        println!("{} x {} cells in {:.1?}", dem.width, dem.height, start.elapsed());
+        raster::write_geotiff(&mut BufWriter::new(File::create(out)?), &svf)?;
+        return Ok(());
+    }
+
+    // V-shaped valley z = slope |x| running north-south. From the floor
+    // the mask looks to the far edge of the grid: the wall's elevation
+    // angle grows with distance, to atan(slope |sin az|) at infinity.
+    let (n, cell, slope, height, bins) = (121, 10.0, 0.5, 0.1, 72);
+    let mut dem = Raster::new(n, n, [-(n as f64) * cell / 2.0, n as f64 * cell / 2.0], cell);
+    for row in 0..n {
+        for col in 0..n {
+            dem.data[row * n + col] = slope * dem.center(row, col)[0].abs();
+        }
+    }
+    let start = Instant::now();
+    let svf = raster::sky_view_factor_raster(&dem, height, bins)?;
+    let elapsed = start.elapsed();
+    println!("{n} x {n} valley, {bins} bins: {:.1?}, {:.0} µs/cell", elapsed, 1e6 * elapsed.as_secs_f64() / (n * n) as f64);
+
+    let [lo, hi] = [dem.center(n - 1, 0), dem.center(0, n - 1)];
+    let mut worst = 0.0_f64;
+    for row in (0..n).step_by(10) {
+        let [x, y] = dem.center(row, n / 2);
+        let exact = (0..bins)
+            .map(|i| {
+                let d = horizon::direction((i as f64 + 0.5) * TAU / bins as f64, 0.0);
+                let reach = [(lo[0], hi[0], x, d[0]), (lo[1], hi[1], y, d[1])]
+                    .map(|(lo, hi, at, d)| if d > 0.0 { (hi - at) / d } else { (lo - at) / d })
+                    .into_iter()
+                    .fold(f64::INFINITY, f64::min)
+                    .abs(); // Not -0 at the grid's edge
+                let mask = ((slope * reach * d[0].abs() - height) / reach).atan().max(0.0);
+                mask.cos().powi(2)
+            })
+            .sum::<f64>()
+            / bins as f64;
+        worst = worst.max((svf.get(row, n / 2) - exact).abs());
+    }
+    println!("valley floor: SVF {:.4} mid-valley (1/√(1+s²) = {:.4} unbounded), off by at most {worst:.1e}", svf.get(n / 2, n / 2), 1.0 / slope.hypot(1.0));
+    assert!(worst < 1e-8, "valley SVF off");
+
+    // Tiles against one observer at a time
+    let terrain = raster::dem_mesh(&dem)?;
+    let bvh = bvh::Bvh::new(&terrain);
+    for (row, col) in [(0, 0), (7, 100), (60, 33), (120, 120)] {
+        let [x, y] = dem.center(row, col);
+        let one = horizon::sky_view_factor([x, y, dem.get(row, col) + height], &terrain, &bvh, bins)?;
+        assert!(one == svf.get(row, col), "tile disagrees at ({row}, {col})");
+    }
+
+    // Plain, with a hole of no data
+    let mut plain = Raster::new(40, 30, [500.0, 800.0], 2.0);
+    plain.data.fill(3.0);
+    plain.data[17 * 40 + 9] = f64::NAN;
+    let open = raster::sky_view_factor_raster(&plain, height, bins)?;
+    assert!(open.data.iter().enumerate().all(|(i, &v)| if i == 17 * 40 + 9 { v.is_nan() } else { v == 1.0 }));
+
+    // GeoTIFF
+    let mut bytes = Vec::new();
+    raster::write_geotiff(&mut bytes, &svf)?;
+    let back = raster::read_geotiff(&mut bytes.as_slice())?;
+    assert!(back == svf, "GeoTIFF round trip");
+    assert!(raster::read_geotiff(&mut &bytes[..100]).is_err());
+    println!("GeoTIFF round trip: {} bytes", bytes.len());
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! View factors from projected solid angles, and net radiative flux over
+//! them, checked against closed forms.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::f64::consts::PI;
+use thermal::STEFAN_BOLTZMANN;
+
+fn main() -> Result<(), &'static str> {
+    // Center of a unit square to a parallel unit square 1 m above it,
+    // against the corner-rectangle closed form summed over four quadrants
+    let (a, b, c, d) = ([-0.5, -0.5, 1.0], [0.5, -0.5, 1.0], [0.5, 0.5, 1.0], [-0.5, 0.5, 1.0]);
+    let f = [[a, c, b], [a, d, c]] // Wound to face down
+        .map(|tri| thermal::view_factor_point_triangle([0.0; 3], [0.0, 0.0, 1.0], tri))
+        .iter()
+        .sum::<f64>();
+    let s = 0.5 / 1.25_f64.sqrt();
+    let exact = 4.0 / PI * s * (0.5 / 1.25_f64.sqrt()).atan();
+    println!("point to parallel square: {f:.15}, closed form {exact:.15}");
+    assert!((f - exact).abs() < 1e-14);
+    // Facing away sees nothing
+    assert_eq!(thermal::view_factor_point_triangle([0.0; 3], [0.0, 0.0, 1.0], [a, b, c]), 0.0);
+
+    // Unit cube, wound inward for its interior
+    let vertices = (0..8)
+        .map(|i| [(i & 1) as f64 - 0.5, ((i >> 1) & 1) as f64 - 0.5, ((i >> 2) & 1) as f64 - 0.5])
+        .collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[2], q[1]], [q[0], q[3], q[2]]]).collect();
+    let cube = mesh::TriMesh::new(vertices, faces)?;
+    let n = cube.faces().len();
+    let mut f = vec![0.0; n * n];
+    thermal::view_factors(&cube, &mut f)?;
+    for i in 0..n {
+        let row: f64 = f[i * n..(i + 1) * n].iter().sum();
+        assert!((row - 1.0).abs() < 1e-14, "row {i} sums to {row}");
+    }
+    // Opposite face, whose exact value is 0.1998
+    let opposite = f[2] + f[3];
+    println!("cube face to opposite face, one-point quadrature: {opposite:.4}");
+    assert!((opposite - 0.1998).abs() < 0.05);
+
+    // An isothermal enclosure has no net exchange
+    let mut q = vec![0.0; n];
+    thermal::radiative_flux(&f, &vec![0.3; n], &vec![300.0; n], &mut q)?;
+    assert!(q.iter().all(|q| q.abs() < 1e-9));
+
+    // Infinite parallel plates: q = σ(T1⁴ - T2⁴) / (1/ε1 + 1/ε2 - 1)
+    let (e, t) = ([0.8, 0.3], [400.0, 300.0]);
+    let mut q = [0.0; 2];
+    thermal::radiative_flux(&[0.0, 1.0, 1.0, 0.0], &e, &t, &mut q)?;
+    let exact = STEFAN_BOLTZMANN * (t[0].powi(4) - t[1].powi(4)) / (1.0 / e[0] + 1.0 / e[1] - 1.0);
+    println!("parallel plates: {:.6} W/m², closed form {exact:.6} W/m²", q[0]);
+    assert!((q[0] - exact).abs() < 1e-12 * exact && (q[0] + q[1]).abs() < 1e-12 * exact);
+
+    // Alone in space, a surface just emits
+    let mut q = [0.0];
+    thermal::radiative_flux(&[0.0], &[0.9], &[290.0], &mut q)?;
+    assert!((q[0] - 0.9 * STEFAN_BOLTZMANN * 290_f64.powi(4)).abs() < 1e-12);
+
+    assert!(thermal::radiative_flux(&[0.0], &[0.0], &[290.0], &mut q).is_err());
+    Ok(())
+}
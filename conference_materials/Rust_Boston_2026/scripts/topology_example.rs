@@ -0,0 +1,114 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! rayon = "1"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Vertex-to-face, face-to-face and edge maps of a few meshes, checked
+//! against Euler's formula and against a serial build with a `BTreeMap`
+//! on a torus of `2 n²` faces. The parallel build sorts where the serial
+//! one hashes into a tree, so it needs a few threads to come out ahead:
+//!
+//! ```text
+//! rust-script topology_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/topology.rs"]
+mod topology;
+
+use mesh::TriMesh;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// `n` by `m` grid of quads split in two, wrapped into a torus if `closed`
+fn grid(n: u32, m: u32, closed: bool) -> TriMesh {
+    let (nv, mv) = if closed { (n, m) } else { (n + 1, m + 1) };
+    let id = |i: u32, j: u32| (i % nv) * mv + j % mv;
+    let vertices = (0..nv * mv).map(|k| [(k / mv) as f64, (k % mv) as f64, 0.0]).collect();
+    let faces = (0..n)
+        .flat_map(|i| (0..m).flat_map(move |j| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    TriMesh::new(vertices, faces).unwrap()
+}
+
+fn euler_characteristic(mesh: &TriMesh, adjacency: &topology::Adjacency) -> i64 {
+    mesh.vertices().len() as i64 - adjacency.edges.len() as i64 + mesh.faces().len() as i64
+}
+
+fn main() -> Result<(), &'static str> {
+    let n: u32 = std::env::args().nth(1).map_or(1000, |s| s.parse().unwrap());
+
+    // Unit cube: closed, genus 0, three neighbors per face
+    let vertices = (0..8).map(|i| [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64]).collect();
+    let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+    let faces = quads.iter().flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]]).collect();
+    let cube = TriMesh::new(vertices, faces)?;
+    let a = cube.build_adjacency();
+    println!("cube: {} edges, V - E + F = {}", a.edges.len(), euler_characteristic(&cube, &a));
+    assert_eq!(euler_characteristic(&cube, &a), 2);
+    assert!(a.is_closed_manifold());
+    assert!(a.face_neighbors.rows().all(|r| r.len() == 3));
+    assert_eq!(a.vertex_faces.indices().len(), 3 * cube.faces().len());
+    for (f, edges) in a.face_edges.iter().enumerate() {
+        let [p, q, r] = cube.faces()[f];
+        for (e, [u, v]) in edges.iter().zip([[p, q], [q, r], [r, p]]) {
+            assert_eq!(a.edges[*e as usize], [u.min(v), u.max(v)]);
+            assert!(a.edge_faces.row(*e as usize).contains(&(f as u32)));
+        }
+    }
+
+    // Open grid: one boundary edge per unit of perimeter, disc topology
+    let open = grid(3, 4, false);
+    let a = open.build_adjacency();
+    println!("3 x 4 grid: {} boundary edges, V - E + F = {}", a.boundary_edges().count(), euler_characteristic(&open, &a));
+    assert_eq!(a.boundary_edges().count(), 2 * (3 + 4));
+    assert_eq!(euler_characteristic(&open, &a), 1);
+
+    // Three triangles on one edge
+    let fan = TriMesh::new(vec![[0.0; 3]; 5], vec![[0, 1, 2], [1, 0, 3], [0, 1, 4]])?;
+    let a = fan.build_adjacency();
+    assert_eq!(a.non_manifold_edges().map(|e| a.edges[e as usize]).collect::<Vec<_>>(), [[0, 1]]);
+    assert_eq!(a.face_neighbors.row(0), [1, 2]);
+
+    // Torus: closed, genus 1, against a serial build
+    let torus = grid(n, n, true);
+    let start = Instant::now();
+    let a = torus.build_adjacency();
+    let elapsed = start.elapsed();
+    println!(
+        "{n} x {n} torus: {} faces, {} edges, V - E + F = {}, built in {elapsed:.2?} on {} threads",
+        torus.faces().len(),
+        a.edges.len(),
+        euler_characteristic(&torus, &a),
+        rayon::current_num_threads()
+    );
+    assert_eq!(euler_characteristic(&torus, &a), 0);
+    assert!(a.is_closed_manifold());
+
+    let start = Instant::now();
+    let mut vertex_faces = vec![Vec::new(); torus.vertices().len()];
+    let mut edge_faces: BTreeMap<[u32; 2], Vec<u32>> = BTreeMap::new();
+    for (f, &[p, q, r]) in torus.faces().iter().enumerate() {
+        for v in [p, q, r] {
+            vertex_faces[v as usize].push(f as u32);
+        }
+        for [u, v] in [[p, q], [q, r], [r, p]] {
+            edge_faces.entry([u.min(v), u.max(v)]).or_default().push(f as u32);
+        }
+    }
+    println!("serial BTreeMap build: {:.2?}", start.elapsed());
+    assert!(vertex_faces.iter().zip(a.vertex_faces.rows()).all(|(x, y)| x == y));
+    assert!(edge_faces.keys().eq(&a.edges));
+    assert!(edge_faces.values().zip(a.edge_faces.rows()).all(|(x, y)| x == y));
+
+    Ok(())
+}
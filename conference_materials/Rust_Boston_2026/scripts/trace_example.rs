@@ -0,0 +1,69 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = "0.1"
+//! tracing-subscriber = "0.3"
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! default = ["trace"]
+//! trace = []
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! Span timings from the parallel drivers, printed as each span closes.
+//! Chunk spans are at `TRACE` level:
+//!
+//! ```text
+//! rust-script trace_example.rs [n] [level]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use tracing_subscriber::fmt::format::FmtSpan;
+
+fn main() -> Result<(), &'static str> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let n: usize = args.first().map_or(1 << 16, |s| s.parse().unwrap());
+    let level: tracing::Level = args.get(1).map_or(tracing::Level::DEBUG, |s| s.parse().unwrap());
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_thread_ids(true)
+        .init();
+
+    // One call either side of the serial fallback
+    for n in [par::par_threshold() / 2, n] {
+        let tets = vec![[[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]; n];
+        let mut out = vec![0.0; n];
+        par::solid_angle_tetrahedra_par(&tets, &mut out)?;
+    }
+
+    // Octahedron seen from a row of origins
+    let vertices = vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+    let faces = vec![[0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4], [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5]];
+    let octahedron = mesh::TriMesh::new(vertices, faces)?;
+    let origins: Vec<[f64; 3]> = (0..n).map(|i| [4.0 * i as f64 / n as f64 - 2.0, 0.1, 0.2]).collect();
+    let mut out = vec![0.0; n];
+    multi_origin::solid_angles_multi_origin(&octahedron, &origins, &mut out)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Checking lengths once with `PairedSlices`, then calling the infallible
+//! `_unchecked` kernels, e.g. from code that must not panic or return
+//! errors.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use tetrahedron::PairedSlices;
+
+fn main() -> Result<(), &'static str> {
+    let n = 100_000;
+    let tets = inputs::tetrahedra(inputs::Distribution::Random, 144, n);
+    let mut checked = vec![0.0; n];
+    tetrahedron::solid_angle_tetrahedron(&tets, &mut checked)?;
+
+    // The only fallible step is pairing the slices
+    assert!(PairedSlices::new(&tets, &mut vec![0.0; n - 1]).is_err());
+
+    let mut serial = vec![0.0; n];
+    tetrahedron::solid_angle_tetrahedron_unchecked(PairedSlices::new(&tets, &mut serial)?);
+    assert_eq!(serial, checked);
+
+    let mut parallel = vec![0.0; n];
+    par::solid_angle_tetrahedra_par_unchecked(PairedSlices::new(&tets, &mut parallel)?);
+    assert_eq!(parallel, checked);
+
+    // Halves stay paired, and an oversized split point is clamped
+    let mut halves = vec![0.0; n];
+    let (front, back) = PairedSlices::new(&tets, &mut halves)?.split_at(n / 2);
+    assert_eq!((front.len(), back.len()), (n / 2, n - n / 2));
+    tetrahedron::solid_angle_tetrahedron_unchecked(front);
+    let (back, empty) = back.split_at(n);
+    assert!(empty.is_empty());
+    tetrahedron::solid_angle_tetrahedron_unchecked(back);
+    assert_eq!(halves, checked);
+
+    println!("{n} tetrahedra: unchecked kernels match the checked ones");
+    Ok(())
+}
@@ -0,0 +1,60 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [features]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Filling a fresh output buffer through the `_uninit` kernels instead of
+//! zeroing it first with `vec![0.0; n]`.
+//!
+//! ```text
+//! rust-script uninit_example.rs [n]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use std::time::Instant;
+
+fn main() -> Result<(), &'static str> {
+    let n: usize = std::env::args().nth(1).map_or(1 << 21, |s| s.parse().unwrap());
+    let tets: Vec<[[f64; 3]; 4]> = (0..n)
+        .map(|i| {
+            let s = 1.0 + i as f64 * 1e-6;
+            [[0.0; 3], [s, 0.0, 0.0], [0.0, s, 0.0], [0.0, 0.0, s]]
+        })
+        .collect();
+
+    // Zeroed, then overwritten
+    let start = Instant::now();
+    let mut zeroed = vec![0.0; n];
+    par::solid_angle_tetrahedra_par(&tets, &mut zeroed)?;
+    let t_zeroed = start.elapsed();
+
+    // Written once
+    let start = Instant::now();
+    let mut uninit: Vec<f64> = Vec::with_capacity(n);
+    par::solid_angle_tetrahedra_par_uninit(&tets, uninit.spare_capacity_mut())?;
+    // SAFETY: The kernel initialized the first n elements, or returned early
+    unsafe { uninit.set_len(n) };
+    let t_uninit = start.elapsed();
+
+    assert_eq!(zeroed, uninit);
+    println!("n = {n}");
+    println!("    vec![0.0; n] + kernel: {t_zeroed:?}");
+    println!("    uninit kernel:         {t_uninit:?}");
+    Ok(())
+}
@@ -0,0 +1,52 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! ```
+//!
+//! Solid angle in steradians, square degrees and percent of the sphere:
+//! round trips, familiar values (the sphere, a hemisphere, the full Moon,
+//! a cube face from its centre), and how they print.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/units.rs"]
+mod units;
+
+use std::f64::consts::PI;
+use units::{SolidAngleUnit, Sr};
+
+fn main() {
+    // Round trips through every unit
+    for sr in [1e-9, 0.25, 1.0, PI, 4.0 * PI] {
+        for unit in [SolidAngleUnit::Steradian, SolidAngleUnit::SquareDegree, SolidAngleUnit::PercentOfSphere] {
+            let back = unit.to_sr(unit.value(sr));
+            assert!((back - sr).abs() <= 4.0 * f64::EPSILON * sr, "{unit:?}: {sr} back as {back}");
+        }
+    }
+
+    // The sphere, a hemisphere, and a cap of the Moon's 0.26° radius
+    assert!((units::SPHERE_DEG2 - 41_252.96).abs() < 0.01);
+    assert!((units::sr_to_deg2(1.0) - 3_282.806).abs() < 1e-3);
+    assert_eq!(units::sr_to_fraction(2.0 * PI), 0.5);
+    let moon = 2.0 * PI * (1.0 - 0.26_f64.to_radians().cos());
+    assert!((units::sr_to_deg2(moon) - PI * 0.26 * 0.26).abs() < 1e-5);
+
+    // Formatting, to four significant figures unless asked otherwise
+    assert_eq!(Sr(4.0 * PI).to_string(), "12.57 sr (41253 deg², 100.0% of sphere)");
+    assert_eq!(format!("{:.6}", Sr(2.0 * PI)), "6.28319 sr (20626.5 deg², 50.0000% of sphere)");
+    assert_eq!(Sr(moon).to_string(), "6.469e-5 sr (0.2124 deg², 5.148e-4% of sphere)");
+    assert_eq!(Sr(4.0 * PI / 6.0).in_unit(SolidAngleUnit::SquareDegree).to_string(), "6875 deg²");
+    assert_eq!(format!("{:.2}", Sr(4.0 * PI / 6.0).in_unit(SolidAngleUnit::PercentOfSphere)), "17% of sphere");
+    assert_eq!(Sr(9.99996).in_unit(SolidAngleUnit::Steradian).to_string(), "10.00 sr");
+    assert_eq!(Sr(0.0).to_string(), "0 sr (0 deg², 0% of sphere)");
+    assert_eq!(Sr(-0.5).in_unit(SolidAngleUnit::Steradian).to_string(), "-0.5000 sr");
+
+    // Units as the CLI tools take them
+    assert_eq!("deg²".parse(), Ok(SolidAngleUnit::SquareDegree));
+    assert_eq!(" % ".parse(), Ok(SolidAngleUnit::PercentOfSphere));
+    assert_eq!("sr".parse(), Ok(SolidAngleUnit::Steradian));
+    assert_eq!("arcmin2".parse::<SolidAngleUnit>(), Err("Unknown solid angle unit"));
+
+    for (name, sr) in [("sphere", 4.0 * PI), ("hemisphere", 2.0 * PI), ("cube face from centre", 4.0 * PI / 6.0), ("full Moon", moon)] {
+        println!("{name:>22}: {}", Sr(sr));
+    }
+}
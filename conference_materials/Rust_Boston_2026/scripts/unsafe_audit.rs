@@ -0,0 +1,289 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! bytemuck = { version = "1", optional = true }
+//! tracing = { version = "0.1", optional = true }
+//!
+//! [target.'cfg(target_os = "linux")'.dependencies]
+//! libc = { version = "0.2", optional = true }
+//!
+//! [features]
+//! default = ["bytemuck"]
+//! bytemuck = ["dep:bytemuck"]
+//! hugepages = ["dep:libc"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Targeted tests for every `unsafe` block in the shared modules, meant
+//! to run under Miri so that out-of-bounds access, uninitialized reads,
+//! misaligned pointers, leaks and double drops fail loudly:
+//!
+//! ```text
+//! rust-script unsafe_audit.rs [test name prefix]
+//!
+//! # In the package rust-script generates:
+//! export MIRIFLAGS="-Zmiri-disable-isolation -Zmiri-permissive-provenance -Zmiri-tree-borrows"
+//! cargo +nightly miri run -- aligned_vec bytes
+//! MIRIFLAGS="$MIRIFLAGS -Zmiri-ignore-leaks" cargo +nightly miri run -- uninit
+//! ```
+//!
+//! The flags are for the dependencies rather than this code: `num_cpus`
+//! reads `/proc`, and crossbeam's epoch GC under rayon casts integers to
+//! pointers and trips Stacked Borrows. Rayon's global pool outlives
+//! `main`, so the tests that use it skip the leak check, while the
+//! `AlignedVec` tests keep it. Sizes shrink under Miri, which is ~1000x
+//! slower, but still cross the parallel threshold.
+//!
+//! | Site                                    | Invariant                                                 | Test                |
+//! |-----------------------------------------|-----------------------------------------------------------|---------------------|
+//! | `tetrahedron::slice_assume_init_mut`    | every element was written; same layout as `f64`           | `uninit_*`          |
+//! | `AlignedVec` `Send`/`Sync`              | owns its elements like `Vec`                              | `aligned_vec_threads` |
+//! | `AlignedVec::new` dangling pointer      | `ALIGN` is a non-zero power of two                        | `aligned_vec_alignment` |
+//! | `AlignedVec::as_slice`/`as_mut_slice`   | first `len` initialized; pointer aligned and non-null     | all `aligned_vec_*` |
+//! | `AlignedVec::reserve` alloc/realloc     | non-zero size; old pointer from `layout(cap)`             | `aligned_vec_growth`, `_zst` |
+//! | `AlignedVec::reserve` copy (huge pages) | both hold `len`; distinct; old freed with `layout(cap)`   | `aligned_vec_huge_pages` |
+//! | `aligned_vec::advise_huge_pages`        | fresh allocation, huge-page aligned; advice only          | not under Miri; `aligned_vec_example.rs` |
+//! | `AlignedVec::push`/`pop`                | `len < cap` slot is free; popped slot leaves `len`        | `aligned_vec_growth`, `_drops` |
+//! | `AlignedVec::clear`/`drop`              | each element dropped once; dealloc with `layout(cap)`     | `aligned_vec_drops`, `_zst` |
+//! | `dispatch` AVX2+FMA kernel call         | path chosen only after detecting both features            | `dispatch_example.rs` |
+//! | `neon` kernel call                      | NEON is AArch64 baseline                                  | `dispatch_example.rs` on AArch64 |
+//! | `math` SIMD `atan2` calls, loads, stores | features detected first; whole chunks of the slices      | `math_example.rs`   |
+//! | `rvv` inline assembly                   | `vl <= avl` bounds loads and stores; V present per feature | `dispatch_example.rs --features rvv` on RISC-V |
+//! | `shm::SharedBuffer::map`                | client leaves the buffer alone until answered (protocol)  | not under Miri; `shm_client.py` |
+//! | `bytes_example.rs` `Mmap::map`          | file not modified while mapped (example-local)            | not under Miri      |
+//! | `uninit_example.rs` `set_len`           | the kernel returned `Ok`, so all `n` were written         | `uninit_par`        |
+//! | `#[unsafe(no_mangle)]` exports          | unique symbol names; safe Rust bodies                     | link of `asm_check.rs` |
+//!
+//! `bytes.rs` has no `unsafe` of its own; `bytemuck` checks size and
+//! alignment, which `bytes_casts` exercises on misaligned input. It runs
+//! with the default `bytemuck` feature; without it `bytes.rs` is compiled
+//! out. Memory maps can't be checked by Miri, so those sites rely on
+//! their documented protocol.
+//!
+//! There is no loom model: the only concurrency written here is the
+//! `par_threshold` atomic, a single independent value where `Relaxed` is
+//! enough, and the rayon and tokio primitives underneath are verified
+//! upstream. Data-race freedom of the parallel drivers themselves is
+//! checked by Miri's race detector in `uninit_par` and `aligned_vec_threads`.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/aligned_vec.rs"]
+mod aligned_vec;
+#[path = "solid_angle/bytes.rs"]
+mod bytes;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+
+use aligned_vec::{AlignedVec, AllocPolicy};
+use std::cell::Cell;
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+type Tet = [[f64; 3]; 4];
+
+/// Elements per test, small enough for Miri
+const N: usize = if cfg!(miri) { 40 } else { 10_000 };
+
+fn tets(n: usize) -> Vec<Tet> {
+    (0..n)
+        .map(|i| {
+            let s = 1.0 + i as f64 * 1e-3;
+            [[0.0; 3], [s, 0.0, 0.0], [0.0, s, 0.0], [0.0, 0.0, s]]
+        })
+        .collect()
+}
+
+/// Serial `_uninit` writes every element, and rejects a length mismatch
+/// before touching anything
+fn uninit_serial() {
+    let tets = tets(N);
+    let mut expected = vec![0.0; N];
+    tetrahedron::solid_angle_tetrahedron(&tets, &mut expected).unwrap();
+
+    let mut out = vec![MaybeUninit::<f64>::uninit(); N];
+    let written = tetrahedron::solid_angle_tetrahedron_uninit(&tets, &mut out).unwrap();
+    assert_eq!(written, &expected[..]);
+
+    let mut short = vec![MaybeUninit::<f64>::uninit(); N - 1];
+    assert!(tetrahedron::solid_angle_tetrahedron_uninit(&tets, &mut short).is_err());
+    assert!(tetrahedron::solid_angle_tetrahedron_uninit(&[], &mut []).unwrap().is_empty());
+}
+
+/// Parallel `_uninit` covers the output exactly, for sizes around the
+/// chunk boundaries, and `set_len` after it is sound
+fn uninit_par() {
+    par::set_par_threshold(0); // Always take the parallel path
+    for n in [0, 1, 2, 7, N] {
+        let tets = tets(n);
+        let mut expected = vec![0.0; n];
+        tetrahedron::solid_angle_tetrahedron(&tets, &mut expected).unwrap();
+
+        let mut v: Vec<f64> = Vec::with_capacity(n);
+        par::solid_angle_tetrahedra_par_uninit(&tets, v.spare_capacity_mut()).unwrap();
+        // SAFETY: The kernel returned Ok, so it initialized the first n elements
+        unsafe { v.set_len(n) };
+        assert_eq!(v, expected);
+    }
+    let mut short = vec![MaybeUninit::<f64>::uninit(); 2];
+    assert!(par::solid_angle_tetrahedra_par_uninit(&tets(3), &mut short).is_err());
+    par::set_par_threshold(par::DEFAULT_PAR_THRESHOLD);
+}
+
+/// Storage is aligned whether empty, grown, or holding over-aligned elements
+fn aligned_vec_alignment() {
+    fn check<T, const A: usize>(v: &AlignedVec<T, A>) {
+        assert_eq!(v.as_ptr() as usize % AlignedVec::<T, A>::ALIGN, 0);
+    }
+    let mut v = AlignedVec::<f64>::new();
+    check(&v);
+    v.extend((0..N).map(|i| i as f64));
+    check(&v);
+
+    #[repr(align(128))]
+    #[derive(Clone, Copy)]
+    struct Wide(u8);
+    let w = AlignedVec::<Wide, 16>::from_elem(Wide(1), 3);
+    assert_eq!(AlignedVec::<Wide, 16>::ALIGN, 128);
+    check(&w);
+    assert_eq!(w.iter().map(|x| x.0).sum::<u8>(), 3);
+}
+
+/// Growth through realloc keeps the contents, and pop returns each push
+fn aligned_vec_growth() {
+    let mut v = AlignedVec::<u64>::with_capacity(1);
+    for i in 0..N as u64 {
+        v.push(i);
+    }
+    assert!(v.capacity() >= N);
+    v.reserve(3 * N);
+    assert_eq!(v.iter().copied().sum::<u64>(), (N as u64 - 1) * N as u64 / 2);
+    for i in (0..N as u64).rev() {
+        assert_eq!(v.pop(), Some(i));
+    }
+    assert_eq!(v.pop(), None);
+
+    let c = AlignedVec::<u64>::from_slice(&[1, 2, 3]).clone();
+    assert_eq!(&c[..], &[1, 2, 3]);
+}
+
+/// Huge-page buffers are whole aligned huge pages once large enough,
+/// keep their contents as they grow, and clone with their policy
+fn aligned_vec_huge_pages() {
+    type Page = [u64; 512]; // 4 KiB, so a huge page is few elements
+    let per_huge = aligned_vec::HUGE_PAGE / size_of::<Page>();
+
+    let mut small = AlignedVec::<u64>::with_capacity_in(3, AllocPolicy::HugePages);
+    small.extend([1, 2, 3]);
+    assert!(small.capacity() < aligned_vec::HUGE_PAGE / 8); // No huge page for three
+
+    let mut v = AlignedVec::<Page>::with_capacity_in(1, AllocPolicy::HugePages);
+    for i in 0..2 * per_huge + 1 {
+        v.push([i as u64; 512]);
+    }
+    assert_eq!(v.as_ptr() as usize % aligned_vec::HUGE_PAGE, 0);
+    assert_eq!(v.capacity() % per_huge, 0); // Padding used as capacity
+    assert!(v.iter().enumerate().all(|(i, p)| p[0] == i as u64 && p[511] == i as u64));
+
+    let c = v.clone();
+    assert_eq!(c.policy(), AllocPolicy::HugePages);
+    assert!(c == v);
+}
+
+/// Every element is dropped exactly once, whether popped, cleared or
+/// dropped with the buffer
+fn aligned_vec_drops() {
+    let drops = Rc::new(Cell::new(0));
+    struct Counted(Rc<Cell<usize>>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let mut v = AlignedVec::<Counted>::new();
+    for _ in 0..10 {
+        v.push(Counted(drops.clone()));
+    }
+    drop(v.pop());
+    assert_eq!(drops.get(), 1);
+    v.clear();
+    assert_eq!(drops.get(), 10);
+    for _ in 0..5 {
+        v.push(Counted(drops.clone()));
+    }
+    drop(v);
+    assert_eq!(drops.get(), 15);
+}
+
+/// Zero-sized elements never allocate or deallocate
+fn aligned_vec_zst() {
+    let mut v = AlignedVec::<()>::new();
+    assert_eq!(v.capacity(), usize::MAX);
+    for _ in 0..N {
+        v.push(());
+    }
+    assert_eq!(v.len(), N);
+    assert_eq!(v.pop(), Some(()));
+    v.clear();
+    assert!(v.is_empty());
+}
+
+/// Send and Sync: buffers moved into and shared across threads
+fn aligned_vec_threads() {
+    let v = AlignedVec::<f64>::from_elem(1.0, N);
+    let total: f64 = std::thread::scope(|s| {
+        let halves = [&v[..N / 2], &v[N / 2..]];
+        let handles: Vec<_> = halves.map(|h| s.spawn(move || h.iter().sum::<f64>())).into();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    assert_eq!(total, N as f64);
+    let moved = std::thread::spawn(move || v.len()).join().unwrap();
+    assert_eq!(moved, N);
+}
+
+/// The casts check size and alignment instead of reading out of bounds
+#[cfg(feature = "bytemuck")]
+fn bytes_casts() {
+    let tets = tets(4);
+    let raw = bytes::tetrahedra_as_bytes(&tets);
+    assert_eq!(bytes::tetrahedra_from_bytes(raw).unwrap(), &tets[..]);
+    assert!(bytes::tetrahedra_from_bytes(&raw[..raw.len() - 8]).is_err());
+
+    let mut values = AlignedVec::<f64>::from_elem(0.0, 5);
+    let buf: &mut [u8] = bytemuck::cast_slice_mut(&mut values[..]);
+    assert!(bytes::values_from_bytes_mut(&mut buf[1..33]).is_err()); // Misaligned
+    bytes::values_from_bytes_mut(&mut buf[8..40]).unwrap().fill(2.0);
+    assert_eq!(&values[..], &[0.0, 2.0, 2.0, 2.0, 2.0]);
+}
+
+fn main() {
+    let tests: &[(&str, fn())] = &[
+        ("uninit_serial", uninit_serial),
+        ("uninit_par", uninit_par),
+        ("aligned_vec_alignment", aligned_vec_alignment),
+        ("aligned_vec_growth", aligned_vec_growth),
+        ("aligned_vec_huge_pages", aligned_vec_huge_pages),
+        ("aligned_vec_drops", aligned_vec_drops),
+        ("aligned_vec_zst", aligned_vec_zst),
+        ("aligned_vec_threads", aligned_vec_threads),
+        #[cfg(feature = "bytemuck")]
+        ("bytes_casts", bytes_casts),
+    ];
+    let filters: Vec<String> = std::env::args().skip(1).collect();
+    for (name, test) in tests {
+        if !filters.is_empty() && !filters.iter().any(|f| name.starts_with(f.as_str())) {
+            continue;
+        }
+        test();
+        println!("{name:<24} ok");
+    }
+}
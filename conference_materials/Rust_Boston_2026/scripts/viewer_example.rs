@@ -0,0 +1,196 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! serde_json = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//! flate2 = { version = "1", default-features = false, features = ["zlib-rs"], optional = true }
+//! crc32fast = { version = "1", optional = true }
+//!
+//! [features]
+//! default = ["viewer"]
+//! defmt = ["dep:defmt"]
+//! geotiff = []
+//! plot = ["dep:flate2", "dep:crc32fast"]
+//! viewer = ["plot"]
+//! trace = ["dep:tracing"]
+//! serde = ["dep:serde"]
+//! ```
+//!
+//! A cube with one face wound inward, recorded for a viewer: the mesh
+//! colored by which way each face points, its normals, query points
+//! colored by the solid angle the cube subtends there, and rays from one
+//! of them. The `.glb` is read back as a scene and its colors checked.
+//!
+//! ```text
+//! rust-script viewer_example.rs debug.glb
+//! ```
+//!
+//! writes it out to open in Rerun, Blender or a browser viewer.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[cfg(not(feature = "viewer"))]
+compile_error!("viewer_example.rs needs the viewer feature");
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/gltf.rs"]
+mod gltf;
+#[path = "solid_angle/horizon.rs"]
+mod horizon;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/instance.rs"]
+mod instance;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/plot.rs"]
+mod plot;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/raster.rs"]
+mod raster;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/scene.rs"]
+mod scene;
+#[path = "solid_angle/stats.rs"]
+mod stats;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/thermal.rs"]
+mod thermal;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/viewer.rs"]
+mod viewer;
+#[path = "solid_angle/visibility.rs"]
+mod visibility;
+
+use mesh::TriMesh;
+use serde_json::Value;
+use std::f64::consts::PI;
+use vec3::{cross, dot, sub};
+use viewer::Recording;
+
+/// The JSON chunk of a `.glb`
+fn glb_json(glb: &[u8]) -> Value {
+    let len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    assert_eq!(&glb[16..20], b"JSON");
+    serde_json::from_slice(&glb[20..20 + len]).unwrap()
+}
+
+/// Bytes of accessor `a` in the binary chunk of a `.glb`
+fn accessor_bytes<'a>(glb: &'a [u8], doc: &Value, a: usize) -> &'a [u8] {
+    let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    let bin = &glb[20 + json_len + 8..];
+    let view = &doc["bufferViews"][doc["accessors"][a]["bufferView"].as_u64().unwrap() as usize];
+    let (offset, len) = (view["byteOffset"].as_u64().unwrap() as usize, view["byteLength"].as_u64().unwrap() as usize);
+    &bin[offset..offset + len]
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // A unit cube with its top (+z) face's two triangles wound inward
+    let cube = primitives::cuboid([0.0; 3], [1.0; 3])?;
+    let top: Vec<usize> = (0..12).filter(|&f| cube.triangle(f).iter().all(|v| v[2] == 1.0)).collect();
+    assert_eq!(top.len(), 2);
+    let faces = cube.faces().iter().enumerate().map(|(f, &[a, b, c])| if top.contains(&f) { [a, c, b] } else { [a, b, c] }).collect();
+    let broken = TriMesh::new(cube.vertices().to_vec(), faces)?;
+
+    // Which way each face points, +1 outward, -1 inward
+    let outward: Vec<f64> = broken
+        .triangles()
+        .map(|[a, b, c]| {
+            let centroid = std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0 - 0.5);
+            dot(cross(sub(b, a), sub(c, a)), centroid).signum()
+        })
+        .collect();
+    assert_eq!(outward.iter().filter(|&&s| s < 0.0).count(), 2);
+
+    // Solid angle at points through the cube: 4π inside a sound cube, but
+    // the flipped face takes twice its own share off
+    let points: Vec<[f64; 3]> = (0..9).map(|i| [0.5, 0.5, -0.5 + 0.25 * i as f64]).collect();
+    let omega = |mesh: &TriMesh, p: [f64; 3]| mesh.triangles().map(|[a, b, c]| tetrahedron::solid_angle_tetrahedron_scalar(p, a, b, c)).sum::<f64>();
+    let seen: Vec<f64> = points.iter().map(|&p| omega(&broken, p)).collect();
+    assert!((omega(&cube, [0.5; 3]) - 4.0 * PI).abs() < 1e-12 && (seen[4] - 8.0 * PI / 3.0).abs() < 1e-12, "{seen:?}");
+
+    let mut rec = Recording::new();
+    rec.log_face_scalars("cube/winding", &broken, &outward, Some([-1.0, 1.0]))?;
+    rec.log_normals("cube/normals", &broken, 0.1, [255, 0, 0, 255]);
+    rec.log_point_scalars("queries", &points, &seen, Some([0.0, 4.0 * PI]))?;
+    let rays: Vec<[[f64; 3]; 2]> = broken.triangles().map(|[a, b, c]| [points[4], std::array::from_fn(|k| (a[k] + b[k] + c[k]) / 3.0)]).collect();
+    rec.log_rays("queries/rays", &rays, [255, 255, 255, 255]);
+    rec.log_normals("cube/normals", &broken, 0.25, [255, 0, 0, 255]); // Replaces the first
+    rec.log_mesh("empty", &TriMesh::new(Vec::new(), Vec::new())?, [0; 4]);
+    assert_eq!(rec.paths().collect::<Vec<_>>(), ["cube/winding", "cube/normals", "queries", "queries/rays", "empty"]);
+
+    let mut glb = Vec::new();
+    rec.write_glb(&mut glb)?;
+    assert_eq!(glb.len() % 4, 0);
+    assert_eq!(u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize, glb.len());
+
+    // Read back, the triangles as a scene; points and lines are skipped
+    // and the empty entity left out
+    let back = gltf::read_glb(&mut &glb[..])?.scene;
+    assert_eq!(back.names(), ["cube/winding", "cube/normals", "queries", "queries/rays"]);
+    assert_eq!(back.instances().iter().map(|i| i.faces()).collect::<Vec<_>>(), [12, 0, 0, 0]);
+    for (f, t) in broken.triangles().enumerate() {
+        let got = back.instances()[0].triangle(f);
+        assert_eq!(got, t.map(|v| v.map(|x| x as f32 as f64)));
+    }
+
+    // Modes, and the colors: the flipped faces at the bottom of the map,
+    // the rest at the top, and the normals the length last logged
+    let doc = glb_json(&glb);
+    let modes: Vec<u64> = doc["meshes"].as_array().unwrap().iter().map(|m| m["primitives"][0]["mode"].as_u64().unwrap()).collect();
+    assert_eq!(modes, [4, 1, 0, 1]);
+    let colors = accessor_bytes(&glb, &doc, 1);
+    for f in 0..12 {
+        let expect = plot::colormap(if top.contains(&f) { 0.0 } else { 1.0 });
+        assert!(colors[12 * f..12 * f + 12].chunks(4).all(|c| c == expect), "face {f}");
+    }
+    let normals: Vec<f32> = accessor_bytes(&glb, &doc, 2).chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+    let inward = &normals[6 * top[0]..6 * top[0] + 6];
+    assert!((inward[5] - inward[2] + 0.25).abs() < 1e-6, "top normal {inward:?}");
+    assert_eq!(doc["accessors"][4]["min"][2].as_f64(), Some(-0.5));
+    let queries = accessor_bytes(&glb, &doc, 5);
+    assert_eq!(&queries[16..20], plot::colormap(seen[4] / (4.0 * PI)));
+
+    // Degenerate inputs
+    assert_eq!(rec.log_face_scalars("x", &broken, &outward[1..], None), Err("Dimension mismatch"));
+    assert_eq!(rec.log_point_scalars("x", &points, &[], None), Err("Dimension mismatch"));
+    let mut empty = Vec::new();
+    Recording::new().write_glb(&mut empty)?;
+    assert!(glb_json(&empty)["accessors"].is_null() && gltf::read_glb(&mut &empty[..])?.scene.names().is_empty());
+
+    if let Some(path) = std::env::args().nth(1) {
+        std::fs::write(&path, &glb)?;
+        println!("wrote {path}");
+    }
+    println!("solid angle through the cube with its top flipped: {:.3?}", seen);
+    Ok(())
+}
@@ -0,0 +1,146 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//!
+//! [features]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Ground stations and relays around a tessellated unit Earth: which
+//! station sees which relay. The BVH answer is checked bit for bit
+//! against testing every face, serial against parallel, and both against
+//! the exact ball wherever the facets can't make a difference.
+//!
+//! ```text
+//! rust-script visibility_example.rs [stations] [relays]
+//! ```
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/arena.rs"]
+mod arena;
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/bounds.rs"]
+mod bounds;
+#[path = "solid_angle/bvh.rs"]
+mod bvh;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/visibility.rs"]
+mod visibility;
+
+use std::f64::consts::PI;
+use std::time::Instant;
+use vec3::{dot, sub};
+
+/// Outward-wound UV sphere of `2 n²` faces, as in `profile_solid_angle.rs`
+fn sphere(n: u32) -> mesh::TriMesh {
+    let vertices = (0..=n)
+        .flat_map(|i| {
+            let theta = PI * f64::from(i) / f64::from(n);
+            (0..2 * n).map(move |j| {
+                let phi = PI * f64::from(j) / f64::from(n);
+                [theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()]
+            })
+        })
+        .collect();
+    let id = |i: u32, j: u32| i * 2 * n + j % (2 * n);
+    let faces = (0..n)
+        .flat_map(|i| (0..2 * n).flat_map(move |j| [[id(i, j), id(i + 1, j), id(i + 1, j + 1)], [id(i, j), id(i + 1, j + 1), id(i, j + 1)]]))
+        .collect();
+    mesh::TriMesh::new(vertices, faces).unwrap()
+}
+
+/// Random points at radius in `[r0, r1)`
+fn shell(rng: &mut inputs::Pcg64, n: usize, r0: f64, r1: f64) -> Vec<[f64; 3]> {
+    (0..n)
+        .map(|_| loop {
+            let p = rng.point();
+            let len = dot(p, p).sqrt();
+            if len > 1e-3 && len <= 1.0 {
+                let r = rng.uniform(r0, r1);
+                break p.map(|x| x * r / len);
+            }
+        })
+        .collect()
+}
+
+/// Closest approach of the segment from `p` to `q` to the origin
+fn closest_approach(p: [f64; 3], q: [f64; 3]) -> f64 {
+    let d = sub(q, p);
+    let t = (-dot(p, d) / dot(d, d)).clamp(0.0, 1.0);
+    let c: [f64; 3] = std::array::from_fn(|k| p[k] + t * d[k]);
+    dot(c, c).sqrt()
+}
+
+fn main() -> Result<(), &'static str> {
+    let args: Vec<usize> = std::env::args().skip(1).map(|s| s.parse().unwrap()).collect();
+    let (n_stations, n_relays) = (args.first().copied().unwrap_or(256), args.get(1).copied().unwrap_or(4096));
+
+    let n = 64;
+    let earth = sphere(n);
+    let start = Instant::now();
+    let bvh = bvh::Bvh::new(&earth);
+    let built = start.elapsed();
+
+    let mut rng = inputs::Pcg64::new(166, 0);
+    let stations = shell(&mut rng, n_stations, 1.001, 1.01);
+    let relays = shell(&mut rng, n_relays, 1.5, 4.0);
+
+    let start = Instant::now();
+    let seen = visibility::visibility_matrix(&stations, &relays, &earth, &bvh)?;
+    let elapsed = start.elapsed();
+    let pairs = n_stations * n_relays;
+    println!(
+        "{} faces, BVH in {built:.1?}; {n_stations} x {n_relays} in {elapsed:.1?}, {:.0} ns/pair; {:.1}% visible",
+        earth.faces().len(),
+        1e9 * elapsed.as_secs_f64() / pairs as f64,
+        100.0 * seen.count() as f64 / pairs as f64
+    );
+
+    // Serial, by raising the threshold past the matrix
+    let threshold = par::par_threshold();
+    par::set_par_threshold(usize::MAX);
+    let serial = visibility::visibility_matrix(&stations, &relays, &earth, &bvh)?;
+    par::set_par_threshold(threshold);
+    assert!(serial == seen, "parallel matrix differs from serial");
+
+    // Every face, for a sample of rows; and the exact ball, outside the
+    // band between the facets' inscribed radius and the sphere
+    let inscribed = (PI / f64::from(n)).cos().powi(2);
+    let mut ambiguous = 0;
+    for (s, &station) in stations.iter().enumerate().step_by(16) {
+        for (r, &relay) in relays.iter().enumerate() {
+            let brute = !earth.triangles().any(|t| bvh::segment_crosses(t, station, relay));
+            assert_eq!(seen.get(s, r), brute, "BVH disagrees with brute force, station {s}, relay {r}");
+            let approach = closest_approach(station, relay);
+            if approach < inscribed {
+                assert!(!brute, "segment through the inscribed ball not blocked");
+            } else if approach > 1.0 {
+                assert!(brute, "segment clear of the sphere blocked");
+            } else {
+                ambiguous += 1;
+            }
+        }
+    }
+    println!("brute force and exact ball agree; {ambiguous} grazing pairs left to the facets");
+
+    assert!(visibility::visibility_matrix(&stations, &relays, &sphere(4), &bvh).is_err());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#!/usr/bin/env rust-script
+//! ```cargo
+//! [dependencies]
+//! libm = "0.2.15"
+//! rayon = "1"
+//! num_cpus = "1"
+//! tracing = { version = "0.1", optional = true }
+//! serde = { version = "1", features = ["derive"], optional = true }
+//! defmt = { version = "1", optional = true }
+//!
+//! [features]
+//! defmt = ["dep:defmt"]
+//! serde = ["dep:serde"]
+//! trace = ["dep:tracing"]
+//! ```
+//!
+//! Fast winding numbers (`solid_angle/winding.rs`) against the exact sum
+//! of `solid_angle/multi_origin.rs`, on a sphere, a capsule and a box
+//! with a cylinder through it wound inward (a hole): the error at the
+//! default far-field ratio, its fall as the ratio grows, the exact sum at
+//! an infinite one, inside/outside agreeing everywhere, and the speedup.
+#![allow(dead_code)] // Shared modules are compiled whole
+
+#[path = "solid_angle/attributes.rs"]
+mod attributes;
+#[path = "solid_angle/closed_form.rs"]
+mod closed_form;
+#[path = "solid_angle/const_eval.rs"]
+mod const_eval;
+#[path = "solid_angle/inputs.rs"]
+mod inputs;
+#[path = "solid_angle/math.rs"]
+mod math;
+#[path = "solid_angle/mesh.rs"]
+mod mesh;
+#[path = "solid_angle/mesh_fixed.rs"]
+mod mesh_fixed;
+#[path = "solid_angle/multi_origin.rs"]
+mod multi_origin;
+#[path = "solid_angle/par.rs"]
+mod par;
+#[path = "solid_angle/primitives.rs"]
+mod primitives;
+#[path = "solid_angle/sampling.rs"]
+mod sampling;
+#[path = "solid_angle/sum.rs"]
+mod sum;
+#[path = "solid_angle/tetrahedron.rs"]
+mod tetrahedron;
+#[path = "solid_angle/vec3.rs"]
+mod vec3;
+#[path = "solid_angle/winding.rs"]
+mod winding;
+
+use inputs::Pcg64;
+use mesh::TriMesh;
+use std::f64::consts::PI;
+use std::time::Instant;
+use winding::FastWinding;
+
+/// All of `parts` as one mesh, those flagged `true` wound inward
+fn union(parts: &[(TriMesh, bool)]) -> TriMesh {
+    let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+    for (part, inward) in parts {
+        let base = vertices.len() as u32;
+        vertices.extend_from_slice(part.vertices());
+        faces.extend(part.faces().iter().map(|&[a, b, c]| if *inward { [a, c, b] } else { [a, b, c] }.map(|v| v + base)));
+    }
+    TriMesh::new(vertices, faces).expect("Indices in range")
+}
+
+fn main() -> Result<(), &'static str> {
+    let mesh = union(&[
+        (primitives::uv_sphere([-2.0, 0.0, 0.0], 1.0, 48, 24)?, false),
+        (primitives::capsule([1.0, -1.0, -1.0], [1.5, 1.0, 1.5], 0.5, 32, 8)?, false),
+        (primitives::cuboid([-1.0, -1.0, -3.0], [1.0, 1.0, -2.0])?, false),
+        (primitives::cylinder([0.0, 0.0, -3.0], [0.0, 0.0, -2.0], 0.4, 32)?, true),
+    ]);
+    let mut rng = Pcg64::new(104, 0);
+    let points: Vec<[f64; 3]> = (0..4_000).map(|_| [rng.uniform(-3.5, 2.5), rng.uniform(-1.5, 1.5), rng.uniform(-3.5, 2.5)]).collect();
+    println!("{} faces, {} points", mesh.faces().len(), points.len());
+
+    let start = Instant::now();
+    let mut exact = vec![0.0; points.len()];
+    multi_origin::solid_angles_multi_origin(&mesh, &points, &mut exact)?;
+    exact.iter_mut().for_each(|w| *w /= 4.0 * PI);
+    let exact_time = start.elapsed();
+
+    let start = Instant::now();
+    let tree = FastWinding::new(&mesh);
+    let build_time = start.elapsed();
+    let mut fast = vec![0.0; points.len()];
+    let start = Instant::now();
+    tree.winding_numbers(&mesh, &points, &mut fast)?;
+    let fast_time = start.elapsed();
+
+    // Inside the hole is outside the box
+    let hole = tree.winding_number(&mesh, [0.0, 0.0, -2.5]);
+    let solid = tree.winding_number(&mesh, [0.7, 0.7, -2.5]);
+    assert!(hole.abs() < 1e-2 && (solid - 1.0).abs() < 1e-2, "{hole} {solid}");
+
+    let error = |w: &[f64]| w.iter().zip(&exact).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+    let mean_error = |w: &[f64]| w.iter().zip(&exact).map(|(a, b)| (a - b).abs()).sum::<f64>() / w.len() as f64;
+    let at_default = error(&fast);
+    let disagree = fast.iter().zip(&exact).filter(|(a, b)| (**a > 0.5) != (**b > 0.5)).count();
+    let inside = exact.iter().filter(|&&w| w > 0.5).count();
+    println!("beta {}: max error {at_default:.2e}, mean {:.2e}, {disagree} of {inside} inside disagree", tree.beta(), mean_error(&fast));
+    assert!(at_default < 0.1 && mean_error(&fast) < 5e-3 && disagree == 0);
+    println!(
+        "exact {:.1} ms, fast {:.1} ms + {:.1} ms to build, {:.0}x",
+        exact_time.as_secs_f64() * 1e3,
+        fast_time.as_secs_f64() * 1e3,
+        build_time.as_secs_f64() * 1e3,
+        exact_time.as_secs_f64() / fast_time.as_secs_f64()
+    );
+
+    // Wider far field, smaller error; infinite is the exact sum
+    let mut last = at_default;
+    for beta in [3.0, 5.0, f64::INFINITY] {
+        let tree = tree.clone().with_beta(beta)?;
+        tree.winding_numbers(&mesh, &points, &mut fast)?;
+        let e = error(&fast);
+        println!("beta {beta}: max error {e:.2e}, mean {:.2e}", mean_error(&fast));
+        assert!(e < last || e < 1e-12);
+        last = e;
+    }
+    assert!(last < 1e-12);
+
+    // Misuse
+    assert_eq!(tree.clone().with_beta(0.0).err(), Some("Far-field ratio must be positive"));
+    assert_eq!(tree.clone().with_beta(f64::NAN).err(), Some("Far-field ratio must be positive"));
+    assert_eq!(tree.winding_numbers(&mesh, &points, &mut fast[1..]), Err("Dimension mismatch"));
+    let other = primitives::cube([0.0; 3], 1.0);
+    assert_eq!(tree.winding_numbers(&other, &points, &mut fast), Err("Mesh is not the one the tree was built from"));
+    let empty = TriMesh::new(vec![], vec![])?;
+    assert_eq!(FastWinding::new(&empty).winding_number(&empty, [0.0; 3]), 0.0);
+    Ok(())
+}